@@ -265,7 +265,7 @@ void test_upd_ema(int64_t n, int64_t d, pd_t* val) {
       qs->fact_[i] = dec_fact[i];
     }
 
-    upd_ema(ema, val, conf, 1, qs, -9);
+    upd_ema(ema, val, conf, 1, qs, -9, PD_EMA_DECAY, PD_EMA_MAX_DIFF);
 
     pd_t result[1];
     result->v_ = ema->val_;
@@ -1,4 +1,6 @@
 /// The goal of this file is to provide the upd_aggregate function to local rust tests
+/// The on-chain program aggregates in Rust, c_upd_aggregate_pythnet is only used as a reference
+/// implementation in the tests.
 
 /// We need to allocate some heap space for upd_aggregate
 /// When compiling for the solana runtime, the heap space is preallocated and PC_HEAP_START is provided by <solana.h>
@@ -13,6 +15,6 @@ extern bool c_upd_aggregate_pythnet( pc_price_t *ptr, uint64_t slot, int64_t tim
   return upd_aggregate(ptr, slot, timestamp );
 }
 
-extern void c_upd_twap( pc_price_t *ptr, int64_t nslots ){
-  upd_twap(ptr, nslots);
+extern void c_upd_twap( pc_price_t *ptr, int64_t nslots, int64_t decay_rate, int64_t max_diff ){
+  upd_twap(ptr, nslots, decay_rate, max_diff);
 }
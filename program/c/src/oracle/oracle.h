@@ -24,10 +24,20 @@ extern "C" {
 
   // Total price component slots available
 #define PC_NUM_COMP_PYTHNET     128
+#define PC_NUM_COMP_SOLANA      64
 
+// PC_SOLANA is defined in features.h by builds for Solana (the `solana` cargo feature), other
+// builds are for Pythnet
+#ifdef PC_SOLANA
+#define PC_NUM_COMP_SLOTS PC_NUM_COMP_SOLANA
+// PC_NUM_COMP - number of price components in use
+#define PC_NUM_COMP 32
+#else
+#define PC_NUM_COMP_SLOTS PC_NUM_COMP_PYTHNET
 // PC_NUM_COMP - number of price components in use
 // Not whole PC_NUM_COMP_PYTHNET because of stack issues appearing in upd_aggregate()
 #define PC_NUM_COMP 64
+#endif
 
 
 #define PC_PROD_ACC_SIZE    512
@@ -47,6 +57,17 @@ extern "C" {
 #define PC_STATUS_HALTED      2
 #define PC_STATUS_AUCTION     3
 #define PC_STATUS_IGNORED     4
+// low-confidence quote, included in the aggregate
+#define PC_STATUS_UNRELIABLE  5
+// aggregate of a majority of unreliable quotes
+#define PC_STATUS_DEGRADED    6
+
+// aggregation modes
+#define PC_AGG_MODE_DEFAULT   0
+// modes only implemented in rust, upd_aggregate treats them as PC_AGG_MODE_DEFAULT
+#define PC_AGG_MODE_WEIGHTED  1
+#define PC_AGG_MODE_TRIMMED_MEAN 2
+#define PC_AGG_MODE_MID          3
 
 // account types
 #define PC_ACCTYPE_MAPPING    1
@@ -54,6 +75,11 @@ extern "C" {
 #define PC_ACCTYPE_PRICE      3
 #define PC_ACCTYPE_TEST       4
 #define PC_ACCTYPE_PERMISSIONS       5
+#define PC_ACCTYPE_MESSAGE_BUFFER    6
+#define PC_ACCTYPE_COMPRESSED_PRICE  7
+#define PC_ACCTYPE_SYMBOL_INDEX      8
+#define PC_ACCTYPE_COMPONENT_LOG     9
+#define PC_ACCTYPE_DERIVED_PRICE     10
 
 
 // Compute budget requested per price update instruction
@@ -193,8 +219,10 @@ typedef struct pc_price
   uint8_t         min_pub_;           // min publishers for valid price
   int8_t          message_sent_;      // flag to indicate if the current aggregate price has been sent as a message to the message buffer, 0 if not sent, 1 if sent
   uint8_t         max_latency_;       // configurable max latency in slots between send and receive
-  int8_t          drv3_;              // space for future derived values
-  int32_t         drv4_;              // space for future derived values
+  uint8_t         agg_mode_;          // aggregation mode, one of PC_AGG_MODE_*
+  uint16_t        outlier_band_;      // exclude quotes this many conf intervals from the previous aggregate (0 to disable, Rust aggregation only)
+  uint8_t         skipped_slots_;     // slots skipped by the cluster that extend max_latency_ in the last aggregation (Rust aggregation only)
+  uint8_t         layout_version_;    // version of the layout of the price account (Rust program only)
   pc_pub_key_t    prod_;              // product id/ref-account
   pc_pub_key_t    next_;              // next price account in list
   uint64_t        prev_slot_;         // valid slot of previous aggregate with TRADING status
@@ -226,8 +254,10 @@ Here is the breakdown of the sizes (in bytes) for each field within the pc_price
 - min_pub_ (uint8_t): 1 byte
 - message_sent_ (int8_t): 1 byte
 - max_latency_ (uint8_t): 1 byte
-- drv3_ (int8_t): 1 byte
-- drv4_ (int32_t): 4 bytes
+- agg_mode_ (uint8_t): 1 byte
+- outlier_band_ (uint16_t): 2 bytes
+- skipped_slots_ (uint8_t): 1 byte
+- layout_version_ (uint8_t): 1 byte
 - prod_ (pc_pub_key_t): 32 bytes (assuming pc_pub_key_t is a 32-byte array or struct)
 - next_ (pc_pub_key_t): 32 bytes (similar to prod_)
 - prev_slot_ (uint64_t): 8 bytes
@@ -246,10 +276,31 @@ The size of the comp_ array is variable and depends on PC_NUM_COMP, hence it is
 static_assert( sizeof( pc_price_t ) == PC_EXPECTED_PRICE_T_SIZE_PYTHNET, "" );
 #undef PC_EXPECTED_PRICE_T_SIZE_PYTHNET
 
+// The same sizes, spelled out for each value of PC_NUM_COMP
+#ifdef PC_SOLANA
+static_assert( sizeof( pc_price_t ) == 3312, "" );
+#else
+static_assert( sizeof( pc_price_t ) == 6384, "" );
+#endif
+
 
 // This constant needs to be an upper bound of the price account size, it is used within pythd for ztsd.
-// It is set tighly to the current price account + 96 component prices + 48 bytes for cumulative sums
-const uint64_t ZSTD_UPPER_BOUND = 3312 + 96 * sizeof( pc_price_comp_t) + 48;
+// It is set tighly to the largest price account: the fixed part, PC_NUM_COMP_SLOTS component prices,
+// 48 bytes for cumulative sums, then the extensions that price accounts get from the first
+// instruction that uses them, each after an 8 byte header of kind and size: the expiries of the
+// publishers (a key and a slot per component) from add_publisher_with_expiry, the pruning of the
+// publishers (8 bytes of configuration and a key and 8 bytes of state per component) from
+// set_publisher_pruning, the aggregation interval (a slot count) from set_agg_interval, the
+// weights of the publishers (a key and 8 bytes of weight per component) from set_publisher_weight,
+// and the time machine (a period count, a validity bitmap and 64 buckets of 48 bytes) from
+// init_time_machine
+const uint64_t ZSTD_UPPER_BOUND = 240 + PC_NUM_COMP_SLOTS * sizeof( pc_price_comp_t) + 48
+                                  + 5 * 8
+                                  + PC_NUM_COMP * ( sizeof( pc_pub_key_t ) + sizeof( uint64_t ) )
+                                  + 8 + PC_NUM_COMP * ( sizeof( pc_pub_key_t ) + 8 )
+                                  + sizeof( uint64_t )
+                                  + PC_NUM_COMP * ( sizeof( pc_pub_key_t ) + 8 )
+                                  + 16 + 64 * 48;
 
 
 // command enumeration
@@ -5,8 +5,9 @@ extern "C" {
 #endif
 
 #define PD_SCALE9     (1000000000L)
+#define PD_EMA_EXPO     (-9)     // exponent of temporary storage and of the decay rates
+// decay rate and reset gap of the default ema window of 5921 slots (see EmaWindow in the rust code)
 #define PD_EMA_MAX_DIFF 4145     // maximum slots before reset
-#define PD_EMA_EXPO     (-9)     // exponent of temporary storage
 #define PD_EMA_DECAY   (-117065) // 1e9*-log(2)/5921
 #define PC_FACTOR_SIZE       18
 
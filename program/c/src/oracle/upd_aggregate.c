@@ -7,10 +7,6 @@
 #include "features.h"
 
 
-extern bool c_upd_aggregate_pythnet( pc_price_t *ptr, uint64_t slot, int64_t timestamp ){
-  return upd_aggregate(ptr, slot, timestamp );
-}
-
-extern void c_upd_twap( pc_price_t *ptr, int64_t nslots ){
-  upd_twap(ptr, nslots);
+extern void c_upd_twap( pc_price_t *ptr, int64_t nslots, int64_t decay_rate, int64_t max_diff ){
+  upd_twap(ptr, nslots, decay_rate, max_diff);
 }
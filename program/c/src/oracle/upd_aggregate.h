@@ -76,8 +76,11 @@ static pc_qset_t *qset_new()
   return qs;
 }
 
+// decay_rate is the decay of the weights per slot at exponent PD_EMA_EXPO, and the ema restarts
+// after gaps longer than max_diff slots (see EmaWindow in the rust code)
 static void upd_ema(
-    pc_ema_t *ptr, pd_t *val, pd_t *conf, int64_t nslot, pc_qset_t *qs, int32_t expo
+    pc_ema_t *ptr, pd_t *val, pd_t *conf, int64_t nslot, pc_qset_t *qs, int32_t expo,
+    int64_t decay_rate, int64_t max_diff
     )
 {
   pd_t numer[1], denom[1], cwgt[1], wval[1], decay[1], diff[1], one[1];
@@ -87,14 +90,14 @@ static void upd_ema(
   } else {
     pd_set( cwgt, one );
   }
-  if ( nslot > PD_EMA_MAX_DIFF ) {
+  if ( nslot > max_diff ) {
     // initial condition
     pd_mul( numer, val, cwgt );
     pd_set( denom, cwgt );
   } else {
     // compute decay factor
     pd_new( diff, nslot, 0 );
-    pd_new( decay, PD_EMA_DECAY, PD_EMA_EXPO );
+    pd_new( decay, decay_rate, PD_EMA_EXPO );
     pd_mul( decay, decay, diff );
     pd_add( decay, decay, one, qs->fact_ );
 
@@ -120,15 +123,15 @@ static void upd_ema(
 }
 
 static inline void upd_twap(
-    pc_price_t *ptr, int64_t nslots )
+    pc_price_t *ptr, int64_t nslots, int64_t decay_rate, int64_t max_diff )
 {
   pc_qset_t *qs = qset_new( );
 
   pd_t px[1], conf[1];
   pd_new_scale( px, ptr->agg_.price_, ptr->expo_ );
   pd_new_scale( conf, ( int64_t )( ptr->agg_.conf_ ), ptr->expo_ );
-  upd_ema( &ptr->twap_, px, conf, nslots, qs, ptr->expo_ );
-  upd_ema( &ptr->twac_, conf, conf, nslots, qs, ptr->expo_ );
+  upd_ema( &ptr->twap_, px, conf, nslots, qs, ptr->expo_, decay_rate, max_diff );
+  upd_ema( &ptr->twac_, conf, conf, nslots, qs, ptr->expo_, decay_rate, max_diff );
 }
 
 // update aggregate price
@@ -13,6 +13,7 @@ fn main() {
     let target_arch = std::env::var("CARGO_CFG_TARGET_ARCH").unwrap();
 
     let has_feat_check = std::env::var("CARGO_FEATURE_CHECK").is_ok();
+    let has_feat_solana = std::env::var("CARGO_FEATURE_SOLANA").is_ok();
 
     // OUT_DIR is the path cargo provides to a build directory under `target/` specifically for
     // isolated build artifacts. We use this to build the C program and then link against the
@@ -39,7 +40,7 @@ fn main() {
     if has_feat_check {
         eprintln!("WARNING: `check` feature active, make build is skipped");
     } else {
-        do_make_build(make_targets, &out_dir);
+        do_make_build(make_targets, &out_dir, has_feat_solana);
 
         // Link against the right library for the architecture
         if target_arch == "bpf" {
@@ -59,8 +60,13 @@ fn main() {
         );
     });
 
-    // Generate and write bindings
-    let bindings = Builder::default()
+    // Generate and write bindings. PC_SOLANA is also defined by the features.h generated by the
+    // make build, but that build is skipped with the `check` feature.
+    let mut builder = Builder::default();
+    if has_feat_solana {
+        builder = builder.clang_arg("-DPC_SOLANA");
+    }
+    let bindings = builder
         .clang_arg(format!("-I{:}", get_solana_inc_path().display()))
         .header("./src/bindings.h")
         .rustfmt_bindings(true)
@@ -75,12 +81,18 @@ fn main() {
     println!("cargo:rerun-if-changed=../");
 }
 
-fn do_make_build(targets: Vec<&str>, out_dir: &Path) {
+fn do_make_build(targets: Vec<&str>, out_dir: &Path, solana: bool) {
     // We must forward OUT_DIR as an env variable to the make script otherwise it will output
     // its artifacts to the wrong place.
-    let make_output = std::process::Command::new("make")
+    let mut make_command = std::process::Command::new("make");
+    make_command
         .env("VERBOSE", "1")
-        .env("OUT_DIR", out_dir.display().to_string())
+        .env("OUT_DIR", out_dir.display().to_string());
+    // Makes the generated features.h define PC_SOLANA
+    if solana {
+        make_command.env("PC_SOLANA", "1");
+    }
+    let make_output = make_command
         .current_dir("../c")
         .args(targets)
         .output()
@@ -0,0 +1,247 @@
+//! The price model of the oracle: the aggregation of the quotes of the publishers of a price
+//! account into its aggregate price.
+//!
+//! `aggregate` is a pure function of the quotes and the configuration of the price account, and
+//! the program runs this very code in `upd_aggregate_with_weights`. Off-chain tools and light
+//! clients can use it to predict or check the aggregate of a price account (see
+//! `PriceAccountView`). The
+//! aggregation mode of the price account selects the algorithm that combines the included quotes
+//! (see `aggregation_algorithm`).
+
+use crate::constants::{
+    PC_MAX_SEND_LATENCY,
+    PC_NUM_COMP,
+    PC_STATUS_TRADING,
+    PC_STATUS_UNRELIABLE,
+};
+
+mod algorithms;
+
+pub use algorithms::{
+    aggregation_algorithm,
+    price_model,
+    AggregationAlgorithm,
+    Median,
+    MidOfQuotes,
+    TrimmedMean,
+    WeightedMedian,
+};
+
+/// Maximum latency in slots of the quotes included in the fast aggregate of a price account
+/// (`fast_agg_`)
+pub const FAST_AGGREGATE_MAX_LATENCY: u64 = 1;
+
+/// A publisher's quote, as seen by the aggregation
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Quote {
+    pub price:    i64,
+    pub conf:     u64,
+    pub status:   u32,
+    pub pub_slot: u64,
+    /// Weight of the quote in the weighted aggregation mode, a weight of 0 counts as 1
+    pub weight:   u32,
+    /// Publish time given by the publisher, 0 if it didn't give one
+    pub pub_time: i64,
+    /// Whether the publisher is excluded from the aggregation, by the authority or because it
+    /// was pruned
+    pub excluded: bool,
+}
+
+/// Configuration of the aggregation of a price account
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AggParams {
+    /// Minimum number of included quotes for a successful aggregation
+    pub min_pub:        u8,
+    /// Maximum latency in slots of the included quotes
+    pub max_latency:    i64,
+    /// Aggregation mode, one of `PC_AGG_MODE_*`
+    pub agg_mode:       u8,
+    /// Quotes more than this many confidence intervals away from `prev_aggregate` are excluded,
+    /// 0 to include all quotes
+    pub outlier_band:   u16,
+    /// Price and confidence of the previous successful aggregate, if any
+    pub prev_aggregate: Option<(i64, u64)>,
+    /// Quotes below `min_price` or above `max_price` are excluded, no bounds if both are 0
+    pub min_price:      i64,
+    pub max_price:      i64,
+}
+
+/// Result of the aggregation of a set of quotes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Aggregate {
+    /// Aggregate price and confidence, `None` if the aggregation failed
+    pub price_conf:      Option<(i64, u64)>,
+    /// Number of quotes included in the aggregate
+    pub num_qt:          u32,
+    /// Price and confidence of the fast aggregate, `None` if no quote is included in it
+    pub fast_price_conf: Option<(i64, u64)>,
+    /// Number of quotes included in the fast aggregate
+    pub fast_num_qt:     u32,
+    /// Earliest publish time of the quotes included in the aggregate that have one, 0 if the
+    /// aggregation failed or none of them has a publish time
+    pub pub_time:        i64,
+    /// Whether a majority of the quotes included in the aggregate are unreliable
+    /// (`PC_STATUS_UNRELIABLE`), false if the aggregation failed
+    pub degraded:        bool,
+}
+
+/// Aggregate `quotes` for `slot` with the configuration `params`. Only the first `PC_NUM_COMP`
+/// quotes are considered.
+///
+/// The aggregation fails if fewer than `min_pub` quotes (or no quote at all) are included (see
+/// `quote_weights`). Unreliable quotes are included like trading ones, but the aggregate is
+/// degraded if they are a majority of the included quotes. The fast aggregate is computed from the
+/// included quotes published at most `FAST_AGGREGATE_MAX_LATENCY` slots before `slot`, whether or
+/// not the aggregation succeeds.
+#[inline(never)]
+pub fn aggregate(quotes: &[Quote], slot: u64, params: &AggParams) -> Aggregate {
+    let quotes = &quotes[..quotes.len().min(PC_NUM_COMP as usize)];
+
+    // Weight of each quote, 0 if it is not included
+    let weights = quote_weights(quotes, slot, params);
+    let num_qt = num_included(&weights);
+    let price_conf = if num_qt == 0 || num_qt < u32::from(params.min_pub) {
+        None
+    } else {
+        model_aggregate(quotes, &weights, params.agg_mode)
+    };
+    let (pub_time, degraded) = if price_conf.is_some() {
+        let num_unreliable = quotes
+            .iter()
+            .zip(weights.iter())
+            .filter(|(quote, weight)| **weight != 0 && quote.status == PC_STATUS_UNRELIABLE)
+            .count() as u32;
+        (
+            earliest_pub_time(quotes, &weights),
+            2 * num_unreliable > num_qt,
+        )
+    } else {
+        (0, false)
+    };
+
+    let mut fast_weights = weights;
+    for (quote, weight) in quotes.iter().zip(fast_weights.iter_mut()) {
+        if slot.saturating_sub(quote.pub_slot) > FAST_AGGREGATE_MAX_LATENCY {
+            *weight = 0;
+        }
+    }
+
+    Aggregate {
+        price_conf,
+        num_qt,
+        fast_price_conf: model_aggregate(quotes, &fast_weights, params.agg_mode),
+        fast_num_qt: num_included(&fast_weights),
+        pub_time,
+        degraded,
+    }
+}
+
+/// Evaluate the algorithm of `agg_mode` (see `aggregation_algorithm`) on the quotes with a
+/// non-zero weight in `weights`, returning the aggregate price and confidence, or `None` if no
+/// quote is included.
+#[inline(never)]
+fn model_aggregate(
+    quotes: &[Quote],
+    weights: &[u32; PC_NUM_COMP as usize],
+    agg_mode: u8,
+) -> Option<(i64, u64)> {
+    aggregation_algorithm(agg_mode)
+        .unwrap_or(&Median)
+        .aggregate(quotes, weights)
+}
+
+/// Weight in the aggregate of `slot` of each of `quotes`, 0 if the quote is not included.
+///
+/// Quotes older than the max latency are stale, and the quotes of excluded publishers are never
+/// included. If there are price bounds, the quotes outside of
+/// `[min_price, max_price]` are excluded. If there is an outlier band, the quotes more than
+/// `outlier_band` confidence intervals away from the previous successful aggregate are excluded.
+/// The band is ignored if fewer than `min_pub` quotes (or no quote at all) are inside it, so that
+/// a genuine price move doesn't halt the aggregation.
+pub fn quote_weights(
+    quotes: &[Quote],
+    slot: u64,
+    params: &AggParams,
+) -> [u32; PC_NUM_COMP as usize] {
+    let mut weights = [0u32; PC_NUM_COMP as usize];
+    for (quote, weight) in quotes.iter().zip(weights.iter_mut()) {
+        if !quote.excluded
+            && is_valid_quote(quote, slot, params.max_latency)
+            && is_within_price_bounds(params, quote.price)
+        {
+            // Quotes without a weight count once
+            *weight = if quote.weight != 0 { quote.weight } else { 1 };
+        }
+    }
+
+    if let Some((prev_price, prev_conf)) = params.prev_aggregate {
+        if params.outlier_band != 0 {
+            let reference = i128::from(prev_price);
+            let max_distance = i128::from(params.outlier_band) * i128::from(prev_conf);
+            let is_outlier =
+                |quote: &Quote| (i128::from(quote.price) - reference).abs() > max_distance;
+
+            let num_in_band = quotes
+                .iter()
+                .zip(weights.iter())
+                .filter(|(quote, weight)| **weight != 0 && !is_outlier(quote))
+                .count();
+            if num_in_band != 0 && num_in_band >= usize::from(params.min_pub) {
+                for (quote, weight) in quotes.iter().zip(weights.iter_mut()) {
+                    if is_outlier(quote) {
+                        *weight = 0;
+                    }
+                }
+            }
+        }
+    }
+
+    weights
+}
+
+/// Number of included quotes in `weights`
+fn num_included(weights: &[u32]) -> u32 {
+    weights.iter().filter(|weight| **weight != 0).count() as u32
+}
+
+/// Earliest publish time of the included quotes that have one, 0 if none of them has
+fn earliest_pub_time(quotes: &[Quote], weights: &[u32]) -> i64 {
+    quotes
+        .iter()
+        .zip(weights.iter())
+        .filter(|(quote, weight)| **weight != 0 && quote.pub_time != 0)
+        .map(|(quote, _)| quote.pub_time)
+        .min()
+        .unwrap_or(0)
+}
+
+/// Whether `price` is within the price bounds of `params`, if any.
+fn is_within_price_bounds(params: &AggParams, price: i64) -> bool {
+    (params.min_price == 0 && params.max_price == 0)
+        || (params.min_price..=params.max_price).contains(&price)
+}
+
+/// Maximum latency in slots of the quotes included in the aggregate of a price account whose
+/// configured max latency (`max_latency_`) is `max_latency`, not counting the slots skipped by the
+/// cluster. A max latency of 0 is the default `PC_MAX_SEND_LATENCY`.
+pub fn max_latency(max_latency: u8) -> i64 {
+    if max_latency != 0 {
+        i64::from(max_latency)
+    } else {
+        i64::from(PC_MAX_SEND_LATENCY)
+    }
+}
+
+/// Whether `quote` is included in the aggregate of `slot`.
+fn is_valid_quote(quote: &Quote, slot: u64, max_latency: i64) -> bool {
+    let slot_diff = (slot as i64).wrapping_sub(quote.pub_slot as i64);
+    let price = quote.price;
+    let conf = quote.conf as i64;
+    // The conf checks ensure that price - conf and price + conf do not overflow. slot_diff is
+    // non-negative because publishers can't publish for a slot in the future.
+    (quote.status == PC_STATUS_TRADING || quote.status == PC_STATUS_UNRELIABLE)
+        && 0 < conf
+        && i64::MIN + conf <= price
+        && price <= i64::MAX - conf
+        && slot_diff <= max_latency
+}
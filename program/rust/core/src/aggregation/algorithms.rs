@@ -0,0 +1,259 @@
+//! The aggregation algorithms that a price account can select with its aggregation mode
+//! (`agg_mode_`, see `aggregation_algorithm`).
+
+use {
+    super::Quote,
+    crate::constants::{
+        PC_AGG_MODE_DEFAULT,
+        PC_AGG_MODE_MID,
+        PC_AGG_MODE_TRIMMED_MEAN,
+        PC_AGG_MODE_WEIGHTED,
+        PC_NUM_COMP,
+    },
+};
+
+/// An algorithm that combines the included quotes of a price account into an aggregate price and
+/// confidence. `weights` holds the weight of each of `quotes`, 0 if the quote is not included.
+/// The included quotes are valid (see `is_valid_quote`), so that their price - conf and
+/// price + conf fit in an i64.
+pub trait AggregationAlgorithm {
+    /// The aggregate price and confidence of the included quotes, `None` if no quote is included
+    fn aggregate(&self, quotes: &[Quote], weights: &[u32]) -> Option<(i64, u64)>;
+}
+
+/// The algorithm of the aggregation mode `agg_mode`, one of `PC_AGG_MODE_*`, `None` if it is not
+/// a known mode. Price accounts with an unknown mode are aggregated with `Median`, like in the C
+/// code.
+pub fn aggregation_algorithm(agg_mode: u8) -> Option<&'static dyn AggregationAlgorithm> {
+    match u32::from(agg_mode) {
+        PC_AGG_MODE_DEFAULT => Some(&Median),
+        PC_AGG_MODE_WEIGHTED => Some(&WeightedMedian),
+        PC_AGG_MODE_TRIMMED_MEAN => Some(&TrimmedMean),
+        PC_AGG_MODE_MID => Some(&MidOfQuotes),
+        _ => None,
+    }
+}
+
+/// The price model of the C oracle (`PC_AGG_MODE_DEFAULT`). Each included quote contributes
+/// price - conf, price and price + conf, the aggregate price is the median of these values and the
+/// confidence the larger of its distances to their p25 and p75 (see `price_model`).
+pub struct Median;
+
+/// `Median` where every quote counts as many times as its weight (`PC_AGG_MODE_WEIGHTED`, see
+/// `weighted_price_model`). Uniform weights give the same aggregate as `Median`.
+pub struct WeightedMedian;
+
+/// Mean of the included prices once the lowest and highest quarters of the quotes are discarded
+/// (`PC_AGG_MODE_TRIMMED_MEAN`), rounded toward negative infinity. The quotes are ordered by price
+/// then confidence and `n / 4` of the `n` included quotes are discarded on each side. The
+/// confidence is the larger of the mean confidence of the remaining quotes and half of the range
+/// of their prices, both rounded up. Weights are ignored.
+pub struct TrimmedMean;
+
+/// Midpoint of the lowest and highest included prices (`PC_AGG_MODE_MID`), rounded toward negative
+/// infinity. The confidence covers the confidence interval of every included quote: it is the
+/// distance from the midpoint to the furthest end of one of them. Weights are ignored.
+pub struct MidOfQuotes;
+
+impl AggregationAlgorithm for Median {
+    fn aggregate(&self, quotes: &[Quote], weights: &[u32]) -> Option<(i64, u64)> {
+        let (mut prices, num_prices, _) = model_prices(quotes, weights);
+        if num_prices == 0 {
+            return None;
+        }
+        let (p25, p50, p75) = price_model(&mut prices[..num_prices]);
+        interquartile_aggregate(p25, p50, p75)
+    }
+}
+
+impl AggregationAlgorithm for WeightedMedian {
+    fn aggregate(&self, quotes: &[Quote], weights: &[u32]) -> Option<(i64, u64)> {
+        let (mut prices, num_prices, total_weight) = model_prices(quotes, weights);
+        if num_prices == 0 {
+            return None;
+        }
+        let sorted = &mut prices[..num_prices];
+        sorted.sort_unstable();
+        let (p25, p50, p75) = weighted_price_model(quotes, weights, total_weight, sorted);
+        interquartile_aggregate(p25, p50, p75)
+    }
+}
+
+impl AggregationAlgorithm for TrimmedMean {
+    fn aggregate(&self, quotes: &[Quote], weights: &[u32]) -> Option<(i64, u64)> {
+        let mut included = [(0i64, 0u64); PC_NUM_COMP as usize];
+        let mut num_included = 0;
+        for (quote, weight) in quotes.iter().zip(weights.iter()) {
+            if *weight != 0 {
+                included[num_included] = (quote.price, quote.conf);
+                num_included += 1;
+            }
+        }
+        if num_included == 0 {
+            return None;
+        }
+        let sorted = &mut included[..num_included];
+        sorted.sort_unstable();
+        let num_trimmed = num_included / 4;
+        let kept = &sorted[num_trimmed..num_included - num_trimmed];
+
+        // The sums of at most PC_NUM_COMP values fit in 128 bits, and their means in 64 bits
+        let num_kept = kept.len() as u128;
+        let price_sum: i128 = kept.iter().map(|(price, _)| i128::from(*price)).sum();
+        let conf_sum: u128 = kept.iter().map(|(_, conf)| u128::from(*conf)).sum();
+        let price = price_sum.div_euclid(num_kept as i128) as i64;
+        let mean_conf = (conf_sum / num_kept + u128::from(conf_sum % num_kept != 0)) as u64;
+        let price_range = kept[kept.len() - 1].0.abs_diff(kept[0].0);
+        let conf = mean_conf.max(price_range / 2 + price_range % 2);
+
+        // This is paranoia as the confidences of the included quotes are positive
+        (conf != 0).then_some((price, conf))
+    }
+}
+
+impl AggregationAlgorithm for MidOfQuotes {
+    fn aggregate(&self, quotes: &[Quote], weights: &[u32]) -> Option<(i64, u64)> {
+        let mut included = quotes
+            .iter()
+            .zip(weights.iter())
+            .filter(|(_, weight)| **weight != 0)
+            .map(|(quote, _)| quote);
+        let first = included.next()?;
+        let (mut low, mut high) = (first.price, first.price);
+        let conf_i128 = |quote: &Quote| i128::from(quote.conf);
+        let (mut lowest, mut highest) = (
+            i128::from(first.price) - conf_i128(first),
+            i128::from(first.price) + conf_i128(first),
+        );
+        for quote in included {
+            low = low.min(quote.price);
+            high = high.max(quote.price);
+            lowest = lowest.min(i128::from(quote.price) - conf_i128(quote));
+            highest = highest.max(i128::from(quote.price) + conf_i128(quote));
+        }
+
+        let price = avg_2_i64(low, high);
+        let conf = (i128::from(price) - lowest).max(highest - i128::from(price));
+        // The interval ends fit in an i64, so their distances to the midpoint fit in a u64
+        let conf = u64::try_from(conf).ok()?;
+        (conf != 0).then_some((price, conf))
+    }
+}
+
+/// The values of the price model of `Median` and `WeightedMedian`: price - conf, price and
+/// price + conf for each included quote, along with their number and their total weight.
+fn model_prices(
+    quotes: &[Quote],
+    weights: &[u32],
+) -> ([i64; PC_NUM_COMP as usize * 3], usize, u64) {
+    let mut num_prices: usize = 0;
+    let mut total_weight: u64 = 0;
+    let mut prices = [0i64; PC_NUM_COMP as usize * 3];
+    for (quote, weight) in quotes.iter().zip(weights.iter()) {
+        if *weight != 0 {
+            let price = quote.price;
+            let conf = quote.conf as i64;
+            total_weight += 3 * u64::from(*weight);
+            prices[num_prices] = price - conf;
+            prices[num_prices + 1] = price;
+            prices[num_prices + 2] = price + conf;
+            num_prices += 3;
+        }
+    }
+    (prices, num_prices, total_weight)
+}
+
+/// The aggregate of the p25, p50 and p75 of a price model: the p50, with the larger of the left
+/// and right confidences.
+fn interquartile_aggregate(p25: i64, p50: i64, p75: i64) -> Option<(i64, u64)> {
+    // As p25 <= p50 <= p75, the confidences can't be negative, and they fit in a u64 even when the
+    // quotes span the whole i64 range. The C code computes them as i64 and wraps around in that
+    // case.
+    let agg_conf = p50.abs_diff(p25).max(p75.abs_diff(p50));
+
+    // This is paranoia as it is currently not possible with positive publisher confidences
+    if agg_conf == 0 {
+        return None;
+    }
+
+    Some((p50, agg_conf))
+}
+
+/// Sort `quotes` and return their p25, p50 and p75. `quotes` must not be empty.
+///
+/// For n quotes, the p25 is the quote of rank n / 4 and the p75 its mirror image. The p50 is the
+/// median, rounded down to the nearest integer if it is the average of the two middle quotes.
+pub fn price_model(quotes: &mut [i64]) -> (i64, i64, i64) {
+    quotes.sort_unstable();
+
+    let num_quotes = quotes.len();
+    let p25_index = num_quotes / 4;
+    let p50 = if num_quotes % 2 == 1 {
+        quotes[num_quotes / 2]
+    } else {
+        avg_2_i64(quotes[num_quotes / 2 - 1], quotes[num_quotes / 2])
+    };
+
+    (quotes[p25_index], p50, quotes[num_quotes - 1 - p25_index])
+}
+
+/// Weighted version of `price_model`. It computes the same ranks on the multiset of quotes where
+/// every quote is repeated by the weight of its component, such that uniform weights give the
+/// same result as the unweighted model. `sorted` holds the valid quotes in ascending order.
+fn weighted_price_model(
+    quotes: &[Quote],
+    weights: &[u32],
+    total_weight: u64,
+    sorted: &[i64],
+) -> (i64, i64, i64) {
+    let select = |rank: u64| weighted_select(quotes, weights, sorted, rank);
+
+    let p25_rank = total_weight / 4;
+    let p50 = if total_weight % 2 == 1 {
+        select(total_weight / 2)
+    } else {
+        avg_2_i64(select(total_weight / 2 - 1), select(total_weight / 2))
+    };
+
+    (select(p25_rank), p50, select(total_weight - 1 - p25_rank))
+}
+
+/// The quote of (0-indexed) rank `rank` in the multiset where every valid quote is repeated by
+/// the weight of its component.
+fn weighted_select(quotes: &[Quote], weights: &[u32], sorted: &[i64], rank: u64) -> i64 {
+    // Binary search for the first quote with more than `rank` quotes at or below it
+    let mut low = 0;
+    let mut high = sorted.len() - 1;
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if weight_at_or_below(quotes, weights, sorted[mid]) > rank {
+            high = mid;
+        } else {
+            low = mid + 1;
+        }
+    }
+    sorted[low]
+}
+
+/// Total weight of the valid quotes that are `<= value`, where each valid quote contributes
+/// its weight once for each of price - conf, price and price + conf.
+fn weight_at_or_below(quotes: &[Quote], weights: &[u32], value: i64) -> u64 {
+    quotes
+        .iter()
+        .zip(weights.iter())
+        .filter(|(_, weight)| **weight != 0)
+        .map(|(quote, weight)| {
+            let price = quote.price;
+            let conf = quote.conf as i64;
+            let count = u64::from(price - conf <= value)
+                + u64::from(price <= value)
+                + u64::from(price + conf <= value);
+            count * u64::from(*weight)
+        })
+        .sum()
+}
+
+/// Average of `x` and `y`, rounded toward negative infinity, without intermediate overflow.
+fn avg_2_i64(x: i64, y: i64) -> i64 {
+    (x >> 1) + (y >> 1) + (x & y & 1)
+}
@@ -0,0 +1,26 @@
+//! The constants of the C header of the oracle (`oracle.h`) used by the core code. The program
+//! checks at compile time that they are equal to its bindings of the header.
+
+/// Number of price components in use in a price account
+#[cfg(feature = "pythnet")]
+pub const PC_NUM_COMP: u32 = 64;
+#[cfg(feature = "solana")]
+pub const PC_NUM_COMP: u32 = 32;
+
+pub const PC_MAGIC: u32 = 0xa1b2c3d4;
+pub const PC_VERSION: u32 = 2;
+pub const PC_ACCTYPE_PRICE: u32 = 3;
+pub const PC_MAX_SEND_LATENCY: u32 = 25;
+
+pub const PC_STATUS_UNKNOWN: u32 = 0;
+pub const PC_STATUS_TRADING: u32 = 1;
+pub const PC_STATUS_HALTED: u32 = 2;
+pub const PC_STATUS_AUCTION: u32 = 3;
+pub const PC_STATUS_IGNORED: u32 = 4;
+pub const PC_STATUS_UNRELIABLE: u32 = 5;
+pub const PC_STATUS_DEGRADED: u32 = 6;
+
+pub const PC_AGG_MODE_DEFAULT: u32 = 0;
+pub const PC_AGG_MODE_WEIGHTED: u32 = 1;
+pub const PC_AGG_MODE_TRIMMED_MEAN: u32 = 2;
+pub const PC_AGG_MODE_MID: u32 = 3;
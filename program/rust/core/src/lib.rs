@@ -0,0 +1,19 @@
+//! The parts of the Pyth oracle program that don't depend on the Solana runtime: the layout of
+//! price accounts and the price model that aggregates their quotes. The program builds on this
+//! crate, so that clients that verify price accounts off-chain, including `no_std` environments
+//! such as light clients, parse and aggregate them with the code that runs on-chain.
+
+#![no_std]
+#![deny(warnings)]
+
+#[cfg(all(feature = "pythnet", feature = "solana"))]
+compile_error!("The `pythnet` and `solana` features are mutually exclusive");
+#[cfg(not(any(feature = "pythnet", feature = "solana")))]
+compile_error!("Either the `pythnet` or the `solana` feature must be enabled");
+
+pub mod aggregation;
+pub mod constants;
+pub mod price;
+
+#[cfg(test)]
+mod tests;
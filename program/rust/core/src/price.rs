@@ -0,0 +1,362 @@
+//! Read-only access to the bytes of a price account, without the `bytemuck` definitions of the
+//! program. The offsets are the offsets of the fields of `PriceAccount`, which the program checks
+//! at compile time (see `layout.rs`).
+
+use crate::{
+    aggregation::{
+        aggregate,
+        max_latency,
+        AggParams,
+        Aggregate,
+        Quote,
+    },
+    constants::{
+        PC_ACCTYPE_PRICE,
+        PC_MAGIC,
+        PC_NUM_COMP,
+        PC_VERSION,
+    },
+};
+#[cfg(feature = "solana-program")]
+use solana_program::pubkey::Pubkey;
+
+pub const ACCOUNT_HEADER_MAGIC_OFFSET: usize = 0;
+pub const ACCOUNT_HEADER_VERSION_OFFSET: usize = 4;
+pub const ACCOUNT_HEADER_ACCOUNT_TYPE_OFFSET: usize = 8;
+
+pub const PRICE_ACCOUNT_EXPONENT_OFFSET: usize = 20;
+pub const PRICE_ACCOUNT_NUM_OFFSET: usize = 24;
+pub const PRICE_ACCOUNT_NUM_QT_OFFSET: usize = 28;
+pub const PRICE_ACCOUNT_LAST_SLOT_OFFSET: usize = 32;
+pub const PRICE_ACCOUNT_TIMESTAMP_OFFSET: usize = 96;
+pub const PRICE_ACCOUNT_MIN_PUB_OFFSET: usize = 104;
+pub const PRICE_ACCOUNT_MAX_LATENCY_OFFSET: usize = 106;
+pub const PRICE_ACCOUNT_AGG_MODE_OFFSET: usize = 107;
+pub const PRICE_ACCOUNT_OUTLIER_BAND_OFFSET: usize = 108;
+pub const PRICE_ACCOUNT_SKIPPED_SLOTS_OFFSET: usize = 110;
+pub const PRICE_ACCOUNT_LAYOUT_VERSION_OFFSET: usize = 111;
+pub const PRICE_ACCOUNT_PREV_SLOT_OFFSET: usize = 176;
+pub const PRICE_ACCOUNT_PREV_PRICE_OFFSET: usize = 184;
+pub const PRICE_ACCOUNT_PREV_CONF_OFFSET: usize = 192;
+pub const PRICE_ACCOUNT_AGG_OFFSET: usize = 208;
+pub const PRICE_ACCOUNT_COMP_OFFSET: usize = 240;
+pub const PRICE_ACCOUNT_PUBLISHER_STATS_OFFSET: usize =
+    PRICE_ACCOUNT_COMP_OFFSET + PC_NUM_COMP as usize * PRICE_COMPONENT_SIZE;
+pub const PRICE_ACCOUNT_FAST_AGG_OFFSET: usize =
+    PRICE_ACCOUNT_PUBLISHER_STATS_OFFSET + PC_NUM_COMP as usize * PUBLISHER_STATS_SIZE;
+pub const PRICE_ACCOUNT_MIN_PRICE_OFFSET: usize = PRICE_ACCOUNT_FAST_AGG_OFFSET + 40;
+pub const PRICE_ACCOUNT_MAX_PRICE_OFFSET: usize = PRICE_ACCOUNT_FAST_AGG_OFFSET + 48;
+pub const PRICE_ACCOUNT_AGG_PUB_TIME_OFFSET: usize = PRICE_ACCOUNT_FAST_AGG_OFFSET + 56;
+/// Size of the fields read by `PriceAccountView`, up to `agg_pub_time_`
+pub const PRICE_ACCOUNT_MIN_SIZE: usize = PRICE_ACCOUNT_AGG_PUB_TIME_OFFSET + 8;
+
+/// The components of a price account are `PRICE_COMPONENT_SIZE` bytes apart from
+/// `PRICE_ACCOUNT_COMP_OFFSET`
+pub const PRICE_COMPONENT_SIZE: usize = 96;
+pub const PRICE_COMPONENT_PUB_OFFSET: usize = 0;
+pub const PRICE_COMPONENT_AGG_OFFSET: usize = 32;
+pub const PRICE_COMPONENT_LATEST_OFFSET: usize = 64;
+
+pub const PRICE_INFO_PRICE_OFFSET: usize = 0;
+pub const PRICE_INFO_CONF_OFFSET: usize = 8;
+pub const PRICE_INFO_STATUS_OFFSET: usize = 16;
+pub const PRICE_INFO_CORP_ACT_STATUS_OFFSET: usize = 20;
+pub const PRICE_INFO_PUB_SLOT_OFFSET: usize = 24;
+
+/// The statistics of the publishers of a price account are `PUBLISHER_STATS_SIZE` bytes apart from
+/// `PRICE_ACCOUNT_PUBLISHER_STATS_OFFSET`, in the order of the components
+pub const PUBLISHER_STATS_SIZE: usize = 88;
+pub const PUBLISHER_STATS_EXCLUDED_OFFSET: usize = 7;
+pub const PUBLISHER_STATS_PUBLISH_TIME_OFFSET: usize = 48;
+
+/// Only in price accounts with extensions, the header of the first extension. Each extension is
+/// `EXTENSION_HEADER_SIZE` bytes of header followed by its content, padded to a multiple of 8
+/// bytes, and a header of kind 0 ends the extensions.
+#[cfg(feature = "pythnet")]
+pub const PRICE_ACCOUNT_EXTENSIONS_OFFSET: usize = 12576;
+#[cfg(feature = "solana")]
+pub const PRICE_ACCOUNT_EXTENSIONS_OFFSET: usize = 6432;
+
+pub const EXTENSION_HEADER_SIZE: usize = 8;
+pub const EXTENSION_HEADER_KIND_OFFSET: usize = 0;
+/// Size of the content of the extension, without the header and the padding
+pub const EXTENSION_HEADER_SIZE_OFFSET: usize = 4;
+/// `PC_NUM_COMP` entries of `PUBLISHER_WEIGHT_SIZE` bytes, at most one per publisher
+pub const EXTENSION_KIND_PUBLISHER_WEIGHTS: u32 = 4;
+
+pub const PUBLISHER_WEIGHT_SIZE: usize = 40;
+pub const PUBLISHER_WEIGHT_PUBLISHER_OFFSET: usize = 0;
+pub const PUBLISHER_WEIGHT_WEIGHT_OFFSET: usize = 32;
+
+/// Why some bytes are not a price account
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// Fewer than `PRICE_ACCOUNT_MIN_SIZE` bytes
+    TooShort,
+    /// The magic number, version or account type of the header isn't the one of a price account
+    InvalidHeader,
+}
+
+/// A price and confidence interval, the aggregate of a price account or a quote of a publisher
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PriceInfo {
+    pub price:           i64,
+    pub conf:            u64,
+    /// One of `PC_STATUS_*`
+    pub status:          u32,
+    /// Unused by the program
+    pub corp_act_status: u32,
+    pub pub_slot:        u64,
+}
+
+/// A publisher of a price account, with its quotes
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Component {
+    /// Public key of the publisher
+    pub publisher:    [u8; 32],
+    /// Quote of the publisher in the last aggregation
+    pub agg:          PriceInfo,
+    /// Latest quote of the publisher
+    pub latest:       PriceInfo,
+    /// Publish time of the latest quote of the publisher, 0 if it didn't give one
+    pub publish_time: i64,
+    /// Whether the publisher is excluded from the aggregation, by the authority or because it
+    /// was pruned
+    pub excluded:     bool,
+    /// Weight of the publisher in the weighted aggregation mode, 0 if it wasn't given one
+    pub weight:       u32,
+}
+
+#[cfg(feature = "solana-program")]
+impl Component {
+    pub fn publisher_key(&self) -> Pubkey {
+        Pubkey::new_from_array(self.publisher)
+    }
+}
+
+/// The quote of the last aggregation of `component`, as seen by the aggregation
+impl From<&Component> for Quote {
+    fn from(component: &Component) -> Self {
+        Quote {
+            price:    component.agg.price,
+            conf:     component.agg.conf,
+            status:   component.agg.status,
+            pub_slot: component.agg.pub_slot,
+            weight:   component.weight,
+            pub_time: component.publish_time,
+            excluded: component.excluded,
+        }
+    }
+}
+
+/// A price account, read from its bytes
+#[derive(Clone, Copy, Debug)]
+pub struct PriceAccountView<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> PriceAccountView<'a> {
+    /// Check that `data` holds a price account
+    pub fn new(data: &'a [u8]) -> Result<Self, ParseError> {
+        if data.len() < PRICE_ACCOUNT_MIN_SIZE {
+            return Err(ParseError::TooShort);
+        }
+        let view = PriceAccountView { data };
+        if view.read_u32(ACCOUNT_HEADER_MAGIC_OFFSET) != PC_MAGIC
+            || view.read_u32(ACCOUNT_HEADER_VERSION_OFFSET) != PC_VERSION
+            || view.read_u32(ACCOUNT_HEADER_ACCOUNT_TYPE_OFFSET) != PC_ACCTYPE_PRICE
+        {
+            return Err(ParseError::InvalidHeader);
+        }
+        Ok(view)
+    }
+
+    pub fn exponent(&self) -> i32 {
+        self.read_u32(PRICE_ACCOUNT_EXPONENT_OFFSET) as i32
+    }
+
+    /// Number of publishers, at most `PC_NUM_COMP`
+    pub fn num_components(&self) -> usize {
+        self.read_u32(PRICE_ACCOUNT_NUM_OFFSET).min(PC_NUM_COMP) as usize
+    }
+
+    /// Number of quotes included in the last aggregation
+    pub fn num_qt(&self) -> u32 {
+        self.read_u32(PRICE_ACCOUNT_NUM_QT_OFFSET)
+    }
+
+    /// Slot of the last successful aggregation
+    pub fn last_slot(&self) -> u64 {
+        self.read_u64(PRICE_ACCOUNT_LAST_SLOT_OFFSET)
+    }
+
+    /// Unix timestamp of the last aggregation
+    pub fn timestamp(&self) -> i64 {
+        self.read_u64(PRICE_ACCOUNT_TIMESTAMP_OFFSET) as i64
+    }
+
+    /// The aggregate of the last aggregation, whose `pub_slot` is the slot of the aggregation
+    pub fn aggregate(&self) -> PriceInfo {
+        self.read_price_info(PRICE_ACCOUNT_AGG_OFFSET)
+    }
+
+    /// Earliest publish time of the quotes included in the last successful aggregation
+    pub fn agg_pub_time(&self) -> i64 {
+        self.read_extended_u64(PRICE_ACCOUNT_AGG_PUB_TIME_OFFSET) as i64
+    }
+
+    /// Version of the layout of the price account, like `pyth_oracle::migrations`. The price
+    /// accounts created before layout versions have version 0 until the program migrates them,
+    /// and the space after their components may hold stale components, which the view reads as 0.
+    pub fn layout_version(&self) -> u8 {
+        self.data[PRICE_ACCOUNT_LAYOUT_VERSION_OFFSET]
+    }
+
+    /// The publisher of component `index`, `None` if `index` is not below `num_components`
+    pub fn component(&self, index: usize) -> Option<Component> {
+        if index >= self.num_components() {
+            return None;
+        }
+        let offset = PRICE_ACCOUNT_COMP_OFFSET + index * PRICE_COMPONENT_SIZE;
+        let stats_offset = PRICE_ACCOUNT_PUBLISHER_STATS_OFFSET + index * PUBLISHER_STATS_SIZE;
+        let mut publisher = [0u8; 32];
+        publisher.copy_from_slice(
+            &self.data[offset + PRICE_COMPONENT_PUB_OFFSET..offset + PRICE_COMPONENT_AGG_OFFSET],
+        );
+        Some(Component {
+            publisher,
+            agg: self.read_price_info(offset + PRICE_COMPONENT_AGG_OFFSET),
+            latest: self.read_price_info(offset + PRICE_COMPONENT_LATEST_OFFSET),
+            publish_time: self.read_extended_u64(stats_offset + PUBLISHER_STATS_PUBLISH_TIME_OFFSET)
+                as i64,
+            excluded: self.layout_version() != 0
+                && self.data[stats_offset + PUBLISHER_STATS_EXCLUDED_OFFSET] != 0,
+            weight: self.publisher_weight(&publisher),
+        })
+    }
+
+    /// The weight of `publisher`, 0 if it has none or if the price account has no weights
+    pub fn publisher_weight(&self, publisher: &[u8; 32]) -> u32 {
+        let weights = match self.extension(EXTENSION_KIND_PUBLISHER_WEIGHTS) {
+            Some(weights) => weights,
+            None => return 0,
+        };
+        weights
+            .chunks_exact(PUBLISHER_WEIGHT_SIZE)
+            .find(|entry| {
+                entry[PUBLISHER_WEIGHT_PUBLISHER_OFFSET..PUBLISHER_WEIGHT_WEIGHT_OFFSET]
+                    == publisher[..]
+            })
+            .map_or(0, |entry| {
+                let mut bytes = [0u8; 4];
+                bytes.copy_from_slice(
+                    &entry[PUBLISHER_WEIGHT_WEIGHT_OFFSET..PUBLISHER_WEIGHT_WEIGHT_OFFSET + 4],
+                );
+                u32::from_le_bytes(bytes)
+            })
+    }
+
+    /// The content of the extension of kind `kind`, `None` if the price account has none (see
+    /// `pyth_oracle::accounts::PriceAccount::add_extension`)
+    fn extension(&self, kind: u32) -> Option<&'a [u8]> {
+        let mut offset = PRICE_ACCOUNT_EXTENSIONS_OFFSET;
+        while offset + EXTENSION_HEADER_SIZE <= self.data.len() {
+            let extension_kind = self.read_u32(offset + EXTENSION_HEADER_KIND_OFFSET);
+            let size = self.read_u32(offset + EXTENSION_HEADER_SIZE_OFFSET) as usize;
+            let start = offset + EXTENSION_HEADER_SIZE;
+            if extension_kind == 0 || start + size > self.data.len() {
+                return None;
+            }
+            if extension_kind == kind {
+                return Some(&self.data[start..start + size]);
+            }
+            offset = start + (size + 7) / 8 * 8;
+        }
+        None
+    }
+
+    /// The configuration of the aggregation of the price account, like
+    /// `pyth_oracle::aggregation::agg_params`
+    pub fn agg_params(&self) -> AggParams {
+        let prev_slot = self.read_u64(PRICE_ACCOUNT_PREV_SLOT_OFFSET);
+        AggParams {
+            min_pub:        self.data[PRICE_ACCOUNT_MIN_PUB_OFFSET],
+            max_latency:    max_latency(self.data[PRICE_ACCOUNT_MAX_LATENCY_OFFSET])
+                + i64::from(self.data[PRICE_ACCOUNT_SKIPPED_SLOTS_OFFSET]),
+            agg_mode:       self.data[PRICE_ACCOUNT_AGG_MODE_OFFSET],
+            outlier_band:   self.read_u16(PRICE_ACCOUNT_OUTLIER_BAND_OFFSET),
+            // prev_slot_ is only set once there has been a successful aggregation
+            prev_aggregate: if prev_slot != 0 {
+                Some((
+                    self.read_u64(PRICE_ACCOUNT_PREV_PRICE_OFFSET) as i64,
+                    self.read_u64(PRICE_ACCOUNT_PREV_CONF_OFFSET),
+                ))
+            } else {
+                None
+            },
+            min_price:      self.read_extended_u64(PRICE_ACCOUNT_MIN_PRICE_OFFSET) as i64,
+            max_price:      self.read_extended_u64(PRICE_ACCOUNT_MAX_PRICE_OFFSET) as i64,
+        }
+    }
+
+    /// The quotes of the last aggregation of the publishers, along with their number
+    pub fn snapshot_quotes(&self) -> ([Quote; PC_NUM_COMP as usize], usize) {
+        let num_components = self.num_components();
+        let mut quotes = [Quote::default(); PC_NUM_COMP as usize];
+        for (index, quote) in quotes[..num_components].iter_mut().enumerate() {
+            if let Some(component) = self.component(index) {
+                *quote = Quote::from(&component);
+            }
+        }
+        (quotes, num_components)
+    }
+
+    /// Aggregate the quotes of the last aggregation again, which gives the aggregate of the price
+    /// account unless the publishers or the configuration changed since. Unlike the program, it
+    /// ignores the market hours of the price account, and the publish times are the ones of the
+    /// latest quotes of the publishers.
+    pub fn recompute_aggregate(&self) -> Aggregate {
+        let (quotes, num_quotes) = self.snapshot_quotes();
+        aggregate(
+            &quotes[..num_quotes],
+            self.aggregate().pub_slot,
+            &self.agg_params(),
+        )
+    }
+
+    fn read_price_info(&self, offset: usize) -> PriceInfo {
+        PriceInfo {
+            price:           self.read_u64(offset + PRICE_INFO_PRICE_OFFSET) as i64,
+            conf:            self.read_u64(offset + PRICE_INFO_CONF_OFFSET),
+            status:          self.read_u32(offset + PRICE_INFO_STATUS_OFFSET),
+            corp_act_status: self.read_u32(offset + PRICE_INFO_CORP_ACT_STATUS_OFFSET),
+            pub_slot:        self.read_u64(offset + PRICE_INFO_PUB_SLOT_OFFSET),
+        }
+    }
+
+    fn read_u16(&self, offset: usize) -> u16 {
+        let mut bytes = [0u8; 2];
+        bytes.copy_from_slice(&self.data[offset..offset + 2]);
+        u16::from_le_bytes(bytes)
+    }
+
+    fn read_u32(&self, offset: usize) -> u32 {
+        let mut bytes = [0u8; 4];
+        bytes.copy_from_slice(&self.data[offset..offset + 4]);
+        u32::from_le_bytes(bytes)
+    }
+
+    /// `read_u64` for the space after the components, 0 with layout version 0
+    fn read_extended_u64(&self, offset: usize) -> u64 {
+        if self.layout_version() == 0 {
+            return 0;
+        }
+        self.read_u64(offset)
+    }
+
+    fn read_u64(&self, offset: usize) -> u64 {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&self.data[offset..offset + 8]);
+        u64::from_le_bytes(bytes)
+    }
+}
@@ -0,0 +1,289 @@
+//! The tests are `no_std` like the rest of the crate, so that they also check that the crate
+//! doesn't need the standard library.
+
+use crate::{
+    aggregation::{
+        aggregate,
+        AggParams,
+        Quote,
+    },
+    constants::{
+        PC_ACCTYPE_PRICE,
+        PC_MAGIC,
+        PC_MAX_SEND_LATENCY,
+        PC_NUM_COMP,
+        PC_STATUS_TRADING,
+        PC_VERSION,
+    },
+    price::*,
+};
+
+const SLOT: u64 = 100;
+const ACCOUNT_SIZE: usize = PRICE_ACCOUNT_MIN_SIZE + 64;
+/// Room for an aggregation interval and the weights of the publishers
+const EXTENDED_ACCOUNT_SIZE: usize = PRICE_ACCOUNT_EXTENSIONS_OFFSET
+    + 2 * EXTENSION_HEADER_SIZE
+    + 8
+    + PC_NUM_COMP as usize * PUBLISHER_WEIGHT_SIZE;
+
+fn write(data: &mut [u8], offset: usize, bytes: &[u8]) {
+    data[offset..offset + bytes.len()].copy_from_slice(bytes);
+}
+
+fn write_price_info(data: &mut [u8], offset: usize, info: &PriceInfo) {
+    write(
+        data,
+        offset + PRICE_INFO_PRICE_OFFSET,
+        &info.price.to_le_bytes(),
+    );
+    write(
+        data,
+        offset + PRICE_INFO_CONF_OFFSET,
+        &info.conf.to_le_bytes(),
+    );
+    write(
+        data,
+        offset + PRICE_INFO_STATUS_OFFSET,
+        &info.status.to_le_bytes(),
+    );
+    write(
+        data,
+        offset + PRICE_INFO_CORP_ACT_STATUS_OFFSET,
+        &info.corp_act_status.to_le_bytes(),
+    );
+    write(
+        data,
+        offset + PRICE_INFO_PUB_SLOT_OFFSET,
+        &info.pub_slot.to_le_bytes(),
+    );
+}
+
+fn quote(price: i64, conf: u64) -> PriceInfo {
+    PriceInfo {
+        price,
+        conf,
+        status: PC_STATUS_TRADING,
+        corp_act_status: 0,
+        pub_slot: SLOT - 1,
+    }
+}
+
+/// A price account aggregated at `SLOT` from the quotes `prices`, the publisher of the component
+/// `excluded`, if any, is excluded
+fn price_account(prices: &[i64], excluded: Option<usize>) -> [u8; ACCOUNT_SIZE] {
+    let mut data = [0u8; ACCOUNT_SIZE];
+    write(
+        &mut data,
+        ACCOUNT_HEADER_MAGIC_OFFSET,
+        &PC_MAGIC.to_le_bytes(),
+    );
+    write(
+        &mut data,
+        ACCOUNT_HEADER_VERSION_OFFSET,
+        &PC_VERSION.to_le_bytes(),
+    );
+    write(
+        &mut data,
+        ACCOUNT_HEADER_ACCOUNT_TYPE_OFFSET,
+        &PC_ACCTYPE_PRICE.to_le_bytes(),
+    );
+    write(
+        &mut data,
+        PRICE_ACCOUNT_EXPONENT_OFFSET,
+        &(-8i32).to_le_bytes(),
+    );
+    write(
+        &mut data,
+        PRICE_ACCOUNT_NUM_OFFSET,
+        &(prices.len() as u32).to_le_bytes(),
+    );
+    data[PRICE_ACCOUNT_MIN_PUB_OFFSET] = 1;
+    data[PRICE_ACCOUNT_LAYOUT_VERSION_OFFSET] = 1;
+    write(
+        &mut data,
+        PRICE_ACCOUNT_AGG_OFFSET + PRICE_INFO_PUB_SLOT_OFFSET,
+        &SLOT.to_le_bytes(),
+    );
+    for (index, price) in prices.iter().enumerate() {
+        let offset = PRICE_ACCOUNT_COMP_OFFSET + index * PRICE_COMPONENT_SIZE;
+        data[offset + PRICE_COMPONENT_PUB_OFFSET] = index as u8 + 1;
+        write_price_info(
+            &mut data,
+            offset + PRICE_COMPONENT_AGG_OFFSET,
+            &quote(*price, 1),
+        );
+        write_price_info(
+            &mut data,
+            offset + PRICE_COMPONENT_LATEST_OFFSET,
+            &quote(*price + 1, 1),
+        );
+        let stats_offset = PRICE_ACCOUNT_PUBLISHER_STATS_OFFSET + index * PUBLISHER_STATS_SIZE;
+        write(
+            &mut data,
+            stats_offset + PUBLISHER_STATS_PUBLISH_TIME_OFFSET,
+            &(1_000 + index as i64).to_le_bytes(),
+        );
+        if excluded == Some(index) {
+            data[stats_offset + PUBLISHER_STATS_EXCLUDED_OFFSET] = 1;
+        }
+    }
+    data
+}
+
+#[test]
+fn test_parse_errors() {
+    let data = price_account(&[100], None);
+    assert!(PriceAccountView::new(&data).is_ok());
+    assert!(PriceAccountView::new(&data[..PRICE_ACCOUNT_MIN_SIZE]).is_ok());
+    assert_eq!(
+        PriceAccountView::new(&data[..PRICE_ACCOUNT_MIN_SIZE - 1]).err(),
+        Some(ParseError::TooShort)
+    );
+
+    for offset in [
+        ACCOUNT_HEADER_MAGIC_OFFSET,
+        ACCOUNT_HEADER_VERSION_OFFSET,
+        ACCOUNT_HEADER_ACCOUNT_TYPE_OFFSET,
+    ] {
+        let mut invalid = data;
+        invalid[offset] ^= 1;
+        assert_eq!(
+            PriceAccountView::new(&invalid).err(),
+            Some(ParseError::InvalidHeader)
+        );
+    }
+}
+
+#[test]
+fn test_parse() {
+    let data = price_account(&[100, 200], Some(1));
+    let view = PriceAccountView::new(&data).unwrap();
+    assert_eq!(view.exponent(), -8);
+    assert_eq!(view.num_components(), 2);
+    assert_eq!(view.aggregate().pub_slot, SLOT);
+
+    let component = view.component(1).unwrap();
+    assert_eq!(component.publisher[0], 2);
+    assert_eq!(component.agg, quote(200, 1));
+    assert_eq!(component.latest, quote(201, 1));
+    assert_eq!(component.publish_time, 1_001);
+    assert!(component.excluded);
+    assert_eq!(component.weight, 0);
+    assert!(!view.component(0).unwrap().excluded);
+    assert_eq!(view.component(2), None);
+
+    // Before layout versions, the space after the components may hold stale components
+    let mut data = data;
+    data[PRICE_ACCOUNT_LAYOUT_VERSION_OFFSET] = 0;
+    let view = PriceAccountView::new(&data).unwrap();
+    assert_eq!(view.layout_version(), 0);
+    let component = view.component(1).unwrap();
+    assert_eq!(component.latest, quote(201, 1));
+    assert_eq!(component.publish_time, 0);
+    assert!(!component.excluded);
+
+    assert_eq!(
+        view.agg_params(),
+        AggParams {
+            min_pub:        1,
+            max_latency:    i64::from(PC_MAX_SEND_LATENCY),
+            agg_mode:       0,
+            outlier_band:   0,
+            prev_aggregate: None,
+            min_price:      0,
+            max_price:      0,
+        }
+    );
+}
+
+#[test]
+fn test_publisher_weight() {
+    let mut data = [0u8; EXTENDED_ACCOUNT_SIZE];
+    data[..ACCOUNT_SIZE].copy_from_slice(&price_account(&[100, 200], None));
+    let mut publisher = [0u8; 32];
+    publisher[0] = 2;
+    let view = PriceAccountView::new(&data).unwrap();
+    assert_eq!(view.publisher_weight(&publisher), 0);
+
+    // The weights are found after another extension, an aggregation interval
+    let mut offset = PRICE_ACCOUNT_EXTENSIONS_OFFSET;
+    write(
+        &mut data,
+        offset + EXTENSION_HEADER_KIND_OFFSET,
+        &3u32.to_le_bytes(),
+    );
+    write(
+        &mut data,
+        offset + EXTENSION_HEADER_SIZE_OFFSET,
+        &8u32.to_le_bytes(),
+    );
+    offset += EXTENSION_HEADER_SIZE + 8;
+    write(
+        &mut data,
+        offset + EXTENSION_HEADER_KIND_OFFSET,
+        &EXTENSION_KIND_PUBLISHER_WEIGHTS.to_le_bytes(),
+    );
+    write(
+        &mut data,
+        offset + EXTENSION_HEADER_SIZE_OFFSET,
+        &((PC_NUM_COMP as usize * PUBLISHER_WEIGHT_SIZE) as u32).to_le_bytes(),
+    );
+    let entry = offset + EXTENSION_HEADER_SIZE + PUBLISHER_WEIGHT_SIZE;
+    write(
+        &mut data,
+        entry + PUBLISHER_WEIGHT_PUBLISHER_OFFSET,
+        &publisher,
+    );
+    write(
+        &mut data,
+        entry + PUBLISHER_WEIGHT_WEIGHT_OFFSET,
+        &7u32.to_le_bytes(),
+    );
+
+    let view = PriceAccountView::new(&data).unwrap();
+    assert_eq!(view.publisher_weight(&publisher), 7);
+    assert_eq!(view.component(1).unwrap().weight, 7);
+    assert_eq!(view.component(0).unwrap().weight, 0);
+
+    // A truncated extension is ignored
+    let view = PriceAccountView::new(&data[..EXTENDED_ACCOUNT_SIZE - 1]).unwrap();
+    assert_eq!(view.publisher_weight(&publisher), 0);
+}
+
+#[test]
+fn test_recompute_aggregate() {
+    let data = price_account(&[100, 200, 300], None);
+    let view = PriceAccountView::new(&data).unwrap();
+    let result = view.recompute_aggregate();
+    assert_eq!(result.price_conf.map(|(price, _)| price), Some(200));
+    assert_eq!(result.num_qt, 3);
+    assert_eq!(result.pub_time, 1_000);
+
+    let (quotes, num_quotes) = view.snapshot_quotes();
+    assert_eq!(
+        result,
+        aggregate(&quotes[..num_quotes], SLOT, &view.agg_params())
+    );
+
+    // The quote of an excluded publisher is not included
+    let data = price_account(&[100, 200, 300], Some(2));
+    let result = PriceAccountView::new(&data).unwrap().recompute_aggregate();
+    assert_eq!(result.num_qt, 2);
+
+    // Stale quotes are not included
+    let params = AggParams {
+        min_pub: 1,
+        max_latency: i64::from(PC_MAX_SEND_LATENCY),
+        ..AggParams::default()
+    };
+    let stale = Quote {
+        price: 100,
+        conf: 1,
+        status: PC_STATUS_TRADING,
+        pub_slot: 1,
+        ..Quote::default()
+    };
+    let result = aggregate(&[stale], SLOT, &params);
+    assert_eq!(result.price_conf, None);
+    assert_eq!(result.num_qt, 0);
+}
@@ -35,10 +35,15 @@ use {
 };
 
 
+mod component_log;
+mod compressed_price;
+mod derived_price;
 mod mapping;
+mod message_buffer;
 mod permission;
 mod price;
 mod product;
+mod symbol_index;
 
 // Some types only exist during use as a library.
 #[cfg(feature = "strum")]
@@ -49,20 +54,57 @@ pub use product::{
     create_pc_str_t,
 };
 pub use {
+    component_log::{
+        ComponentLogAccount,
+        ComponentLogEntry,
+    },
+    compressed_price::CompressedPriceAccount,
+    derived_price::DerivedPriceAccount,
     mapping::MappingAccount,
+    message_buffer::MessageBufferAccount,
     permission::PermissionAccount,
     price::{
+        AggInterval,
+        BucketAverage,
+        EmaDecay,
+        EmaWindow,
+        ExtensionHeader,
+        FastAggregate,
+        LastTrade,
+        MarketHours,
         PriceAccount,
+        PriceAccountExtension,
         PriceComponent,
         PriceCumulative,
         PriceEma,
+        PriceHistory,
         PriceInfo,
+        PruningState,
+        PublisherExpiries,
+        PublisherExpiry,
+        PublisherPruning,
+        PublisherStats,
+        PublisherWeight,
+        PublisherWeights,
         PythOracleSerialize,
+        StatusChange,
+        StatusHistory,
+        StatusHistoryEntry,
+        TimeMachine,
+        TimeMachineBucket,
+        EMA_WINDOWS,
     },
     product::{
         read_pc_str_t,
+        read_product_attributes,
         update_product_metadata,
+        validate_product_attributes,
         ProductAccount,
+        ProductAttributeKey,
+    },
+    symbol_index::{
+        SymbolIndexAccount,
+        SymbolIndexEntry,
     },
 };
 
@@ -76,6 +118,20 @@ pub const PERMISSIONS_SEED: &str = "permissions";
 /// such that the caller can authenticate its origin.
 pub const UPD_PRICE_WRITE_SEED: &str = "upd_price_write";
 
+/// The fees of price updates (see `PriceAccount::fee_lamports_`) are paid into the system account
+/// at the PDA `[TREASURY_SEED]`, from which the authority withdraws them with `withdraw_fee`.
+pub const TREASURY_SEED: &str = "treasury";
+
+/// The PDA `[PERMISSION_GRANT_SEED, authority, command]`, with the command as a little-endian
+/// `i32`, is an empty account that grants `authority` the permission to run the command (see
+/// `grant_permission`). Instructions that take the permissions account accept the grant of their
+/// funding account in its place, which only costs a PDA derivation.
+pub const PERMISSION_GRANT_SEED: &str = "permission_grant";
+
+/// The mapping account can be created at the PDA `[MAPPING_SEED, version]`, with the version as a
+/// little-endian `u32` (see `init_mapping_pda`), so that it is found from the program id alone.
+pub const MAPPING_SEED: &str = "mapping";
+
 #[repr(C)]
 #[derive(Copy, Clone, Zeroable, Pod)]
 pub struct AccountHeader {
@@ -105,6 +161,24 @@ pub trait PythAccount: Pod {
     /// have. `INITIAL_SIZE` <= `minimum_size()`
     const MINIMUM_SIZE: usize = size_of::<Self>();
 
+    /// Whether the account has another layout than the current one and must be migrated before
+    /// use. Only price accounts have layout versions (see `migrations`).
+    fn needs_migration(&self) -> bool {
+        false
+    }
+
+    /// Upgrade the account in place to the current layout.
+    fn migrate(&mut self) -> Result<(), OracleError> {
+        Ok(())
+    }
+
+    /// Whether the counts of the account are within the bounds of the tables they index, as they
+    /// always are in an account written by the program. An account that isn't is rejected with
+    /// `OracleError::CorruptedAccount` rather than indexed out of bounds.
+    fn is_consistent(&self) -> bool {
+        true
+    }
+
     /// Given an `AccountInfo`, verify it is sufficiently large and has the correct discriminator.
     fn initialize<'a>(
         account: &'a AccountInfo,
@@ -0,0 +1,66 @@
+use {
+    super::{
+        AccountHeader,
+        PythAccount,
+    },
+    crate::c_oracle_header::{
+        COMPONENT_LOG_CAPACITY,
+        PC_ACCTYPE_COMPONENT_LOG,
+    },
+    bytemuck::{
+        Pod,
+        Zeroable,
+    },
+    solana_program::pubkey::Pubkey,
+    std::mem::size_of,
+};
+
+/// Audit trail of the publisher updates of a price account: ring buffer of the last
+/// `COMPONENT_LOG_CAPACITY` component updates accepted by the price updates that are given this
+/// account. Once the buffer is full, each new entry overwrites the oldest one.
+#[repr(C)]
+#[derive(Copy, Clone, Pod, Zeroable)]
+pub struct ComponentLogAccount {
+    /// pyth account header
+    pub header:         AccountHeader,
+    /// The price account whose updates are logged, set by `init_component_log`
+    pub price_account_: Pubkey,
+    /// Sequence number of the next entry, i.e. the number of entries appended so far
+    pub next_seq_:      u64,
+    /// The entry of sequence number `seq` is at index `seq % COMPONENT_LOG_CAPACITY`
+    pub entries_:       [ComponentLogEntry; COMPONENT_LOG_CAPACITY],
+}
+
+/// A publisher update accepted by the price account
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Pod, Zeroable)]
+pub struct ComponentLogEntry {
+    /// Index of the component of the publisher in the price account at the time of the update
+    pub publisher_index_: u32,
+    /// Status of the component after the update, see `PriceInfo::status_`
+    pub status_:          u32,
+    /// Slot at which the update was accepted
+    pub slot_:            u64,
+    pub price_:           i64,
+    pub conf_:            u64,
+}
+
+impl ComponentLogAccount {
+    /// Append `entry` to the log, overwriting the oldest entry if the log is full
+    pub fn push(&mut self, entry: ComponentLogEntry) {
+        self.entries_[(self.next_seq_ % COMPONENT_LOG_CAPACITY as u64) as usize] = entry;
+        self.next_seq_ += 1;
+    }
+
+    /// The entries still in the log, from the oldest to the newest
+    pub fn entries(&self) -> impl Iterator<Item = &ComponentLogEntry> {
+        let len = self.next_seq_.min(COMPONENT_LOG_CAPACITY as u64);
+        (self.next_seq_ - len..self.next_seq_)
+            .map(|seq| &self.entries_[(seq % COMPONENT_LOG_CAPACITY as u64) as usize])
+    }
+}
+
+impl PythAccount for ComponentLogAccount {
+    const ACCOUNT_TYPE: u32 = PC_ACCTYPE_COMPONENT_LOG;
+    const INITIAL_SIZE: u32 = size_of::<ComponentLogAccount>() as u32;
+}
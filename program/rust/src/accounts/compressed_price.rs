@@ -0,0 +1,76 @@
+use {
+    super::{
+        AccountHeader,
+        PriceAccount,
+        PriceCumulative,
+        PriceEma,
+        PriceInfo,
+        PythAccount,
+    },
+    crate::c_oracle_header::PC_ACCTYPE_COMPRESSED_PRICE,
+    bytemuck::{
+        Pod,
+        Zeroable,
+    },
+    solana_program::pubkey::Pubkey,
+    std::mem::size_of,
+};
+
+/// What remains of a halted price account after `compress_price_account`: its configuration, its
+/// final aggregate and its moving averages, without the publishers' components. The fields have
+/// the same meaning as in `PriceAccount`. A compressed price account can't be updated anymore.
+#[repr(C)]
+#[derive(Copy, Clone, Pod, Zeroable)]
+pub struct CompressedPriceAccount {
+    pub header:             AccountHeader,
+    pub price_type:         u32,
+    pub exponent:           i32,
+    pub last_slot_:         u64,
+    pub timestamp_:         i64,
+    /// Last attempted aggregate, whose status is `PC_STATUS_HALTED`
+    pub agg_:               PriceInfo,
+    pub prev_slot_:         u64,
+    pub prev_price_:        i64,
+    pub prev_conf_:         u64,
+    pub prev_timestamp_:    i64,
+    pub twap_:              PriceEma,
+    pub twac_:              PriceEma,
+    pub product_account:    Pubkey,
+    /// Next price account in the list of the product, so that the list can still be walked
+    pub next_price_account: Pubkey,
+    pub unused_:            u64,
+    pub price_cumulative:   PriceCumulative,
+}
+
+impl CompressedPriceAccount {
+    /// Compress `price_data`, keeping its header except for the account type and size
+    pub fn compress(price_data: &PriceAccount) -> Self {
+        CompressedPriceAccount {
+            header:             AccountHeader {
+                account_type: Self::ACCOUNT_TYPE,
+                size: Self::INITIAL_SIZE,
+                ..price_data.header
+            },
+            price_type:         price_data.price_type,
+            exponent:           price_data.exponent,
+            last_slot_:         price_data.last_slot_,
+            timestamp_:         price_data.timestamp_,
+            agg_:               price_data.agg_,
+            prev_slot_:         price_data.prev_slot_,
+            prev_price_:        price_data.prev_price_,
+            prev_conf_:         price_data.prev_conf_,
+            prev_timestamp_:    price_data.prev_timestamp_,
+            twap_:              price_data.twap_,
+            twac_:              price_data.twac_,
+            product_account:    price_data.product_account,
+            next_price_account: price_data.next_price_account,
+            unused_:            0,
+            price_cumulative:   price_data.price_cumulative,
+        }
+    }
+}
+
+impl PythAccount for CompressedPriceAccount {
+    const ACCOUNT_TYPE: u32 = PC_ACCTYPE_COMPRESSED_PRICE;
+    const INITIAL_SIZE: u32 = size_of::<CompressedPriceAccount>() as u32;
+}
@@ -0,0 +1,41 @@
+use {
+    super::{
+        AccountHeader,
+        PriceInfo,
+        PythAccount,
+    },
+    crate::c_oracle_header::PC_ACCTYPE_DERIVED_PRICE,
+    bytemuck::{
+        Pod,
+        Zeroable,
+    },
+    solana_program::pubkey::Pubkey,
+    std::mem::size_of,
+};
+
+/// Price feed quoted in another price feed, e.g. ETH/BTC from ETH/USD and BTC/USD. Its aggregate
+/// is the ratio of the aggregates of a base and a quote price account, updated by the
+/// permissionless `upd_derived` instruction (see `aggregation::upd_derived_aggregate`).
+#[repr(C)]
+#[derive(Copy, Clone, Pod, Zeroable)]
+pub struct DerivedPriceAccount {
+    /// pyth account header
+    pub header:               AccountHeader,
+    pub exponent:             i32,
+    pub unused_:              u32,
+    /// Slot of the last update that computed the ratio
+    pub last_slot_:           u64,
+    /// Unix timestamp of the oldest parent aggregate of the last ratio
+    pub timestamp_:           i64,
+    /// Last ratio, whose status is `PC_STATUS_UNKNOWN` if the last update couldn't compute it
+    pub agg_:                 PriceInfo,
+    /// The price account of the numerator of the ratio, e.g. ETH/USD for ETH/BTC
+    pub base_price_account_:  Pubkey,
+    /// The price account of the denominator of the ratio, e.g. BTC/USD for ETH/BTC
+    pub quote_price_account_: Pubkey,
+}
+
+impl PythAccount for DerivedPriceAccount {
+    const ACCOUNT_TYPE: u32 = PC_ACCTYPE_DERIVED_PRICE;
+    const INITIAL_SIZE: u32 = size_of::<DerivedPriceAccount>() as u32;
+}
@@ -29,6 +29,10 @@ impl PythAccount for MappingAccount {
     const ACCOUNT_TYPE: u32 = PC_ACCTYPE_MAPPING;
     /// Equal to the offset of `prod_` in `MappingAccount`, see the trait comment for more detail
     const INITIAL_SIZE: u32 = PC_MAP_TABLE_T_PROD_OFFSET as u32;
+
+    fn is_consistent(&self) -> bool {
+        self.number_of_products <= PC_MAP_TABLE_SIZE
+    }
 }
 
 // Unsafe impl because product_list is of size 640 and there's no derived trait for this size
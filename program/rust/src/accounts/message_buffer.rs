@@ -0,0 +1,54 @@
+use {
+    super::{
+        AccountHeader,
+        PythAccount,
+    },
+    crate::{
+        c_oracle_header::{
+            MESSAGE_BUFFER_CAPACITY,
+            PC_ACCTYPE_MESSAGE_BUFFER,
+        },
+        messages::PriceMessage,
+    },
+    bytemuck::{
+        Pod,
+        Zeroable,
+    },
+    std::mem::size_of,
+};
+
+/// Ring buffer of the last `MESSAGE_BUFFER_CAPACITY` price messages appended by the aggregations
+/// of the price updates that are given this account. Once the buffer is full, each new message
+/// overwrites the oldest one.
+#[repr(C)]
+#[derive(Copy, Clone, Pod, Zeroable)]
+pub struct MessageBufferAccount {
+    /// pyth account header
+    pub header:    AccountHeader,
+    /// Sequence number of the next message, i.e. the number of messages appended so far
+    pub next_seq_: u64,
+    pub unused_:   u64,
+    /// The message of sequence number `seq` is at index `seq % MESSAGE_BUFFER_CAPACITY`
+    pub messages_: [PriceMessage; MESSAGE_BUFFER_CAPACITY],
+}
+
+impl MessageBufferAccount {
+    /// Append `message` to the buffer, overwriting the oldest message if the buffer is full
+    pub fn push(&mut self, mut message: PriceMessage) {
+        message.sequence = self.next_seq_;
+        self.messages_[(self.next_seq_ % MESSAGE_BUFFER_CAPACITY as u64) as usize] = message;
+        self.next_seq_ += 1;
+    }
+
+    /// The messages still in the buffer, from the oldest to the newest
+    pub fn messages(&self) -> impl Iterator<Item = &PriceMessage> {
+        let len = self.next_seq_.min(MESSAGE_BUFFER_CAPACITY as u64);
+        (self.next_seq_ - len..self.next_seq_)
+            .map(|seq| &self.messages_[(seq % MESSAGE_BUFFER_CAPACITY as u64) as usize])
+    }
+}
+
+impl PythAccount for MessageBufferAccount {
+    const ACCOUNT_TYPE: u32 = PC_ACCTYPE_MESSAGE_BUFFER;
+    const INITIAL_SIZE: u32 = size_of::<MessageBufferAccount>() as u32;
+}
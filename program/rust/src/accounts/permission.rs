@@ -5,18 +5,36 @@ use {
     },
     crate::{
         c_oracle_header::PC_ACCTYPE_PERMISSIONS,
+        error::OracleError,
         instruction::OracleCommand,
+        utils::pyth_assert,
     },
     bytemuck::{
+        try_from_bytes_mut,
         Pod,
         Zeroable,
     },
-    solana_program::pubkey::Pubkey,
-    std::mem::size_of,
+    solana_program::{
+        account_info::AccountInfo,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+    std::{
+        cell::RefMut,
+        mem::size_of,
+    },
 };
 
 /// This account stores the pubkeys that can execute administrative instructions in the Pyth
 /// program. Only the upgrade authority of the program can update these permissions.
+///
+/// The account also stores the last index assigned to a price feed (see `init_price_feed_index`)
+/// as a `u32` right after this struct. Accounts created before price feed indexes are resized
+/// to `PermissionAccount::NEW_SIZE` by `upd_permissions`.
+///
+/// The co-signing mode (see `is_co_signed`) is stored as a `u32` after the last price feed index.
+/// The account is resized to `PermissionAccount::CO_SIGNING_SIZE` the first time the mode is set
+/// by `set_co_signing`, and the mode is off in smaller accounts.
 #[repr(C)]
 #[derive(Copy, Clone, Pod, Zeroable)]
 pub struct PermissionAccount {
@@ -31,6 +49,7 @@ pub struct PermissionAccount {
     /// - Delete price accounts
     /// - Delete product accounts
     /// - Update product accounts
+    /// - Rotate the keys of publishers
     pub data_curation_authority: Pubkey,
     /// An authority that can  :
     /// - Add publishers
@@ -40,6 +59,11 @@ pub struct PermissionAccount {
 }
 
 impl PermissionAccount {
+    /// Size of the permissions account including the last assigned price feed index
+    pub const NEW_SIZE: usize = size_of::<PermissionAccount>() + size_of::<u32>();
+    /// Size of the permissions account including the co-signing mode
+    pub const CO_SIGNING_SIZE: usize = Self::NEW_SIZE + size_of::<u32>();
+
     pub fn is_authorized(&self, key: &Pubkey, command: OracleCommand) -> bool {
         #[allow(clippy::match_like_matches_macro)]
         match (*key, command) {
@@ -47,9 +71,79 @@ impl PermissionAccount {
             (pubkey, OracleCommand::ResizePriceAccount) if pubkey == self.security_authority => {
                 true
             } // Allow for an admin key to resize the price account
+            (pubkey, OracleCommand::RotatePublisher) if pubkey == self.data_curation_authority => {
+                true
+            }
             _ => false,
         }
     }
+
+    /// Mutably borrow the last price feed index stored in the permissions account `account`.
+    /// Fails if the account has not been resized to `NEW_SIZE` yet.
+    pub fn load_last_feed_index_mut<'a>(
+        account: &'a AccountInfo,
+    ) -> Result<RefMut<'a, u32>, ProgramError> {
+        let data = account.try_borrow_mut_data()?;
+        pyth_assert(
+            data.len() >= Self::NEW_SIZE,
+            OracleError::AccountTooSmall.into(),
+        )?;
+
+        RefMut::filter_map(data, |data| {
+            try_from_bytes_mut(&mut data[size_of::<PermissionAccount>()..Self::NEW_SIZE]).ok()
+        })
+        .map_err(|_| OracleError::DeserializationError.into())
+    }
+
+    /// Whether the co-signing mode is on in the permissions account `account`, i.e. whether the
+    /// co-signed commands must be co-signed by two distinct authority keys
+    pub fn is_co_signing(account: &AccountInfo) -> Result<bool, ProgramError> {
+        let data = account.try_borrow_data()?;
+        Ok(data
+            .get(Self::NEW_SIZE..Self::CO_SIGNING_SIZE)
+            .map_or(false, |mode| mode.iter().any(|byte| *byte != 0)))
+    }
+
+    /// Mutably borrow the co-signing mode stored in the permissions account `account`, 1 if it is
+    /// on and 0 otherwise. Fails if the account has not been resized to `CO_SIGNING_SIZE` yet.
+    pub fn load_co_signing_mut<'a>(
+        account: &'a AccountInfo,
+    ) -> Result<RefMut<'a, u32>, ProgramError> {
+        let data = account.try_borrow_mut_data()?;
+        pyth_assert(
+            data.len() >= Self::CO_SIGNING_SIZE,
+            OracleError::AccountTooSmall.into(),
+        )?;
+
+        RefMut::filter_map(data, |data| {
+            try_from_bytes_mut(&mut data[Self::NEW_SIZE..Self::CO_SIGNING_SIZE]).ok()
+        })
+        .map_err(|_| OracleError::DeserializationError.into())
+    }
+
+    /// Whether `command` must be co-signed when the co-signing mode is on. These commands can
+    /// remove or replace a publisher, rescale or halt a price feed. `ResignPublisher` also removes
+    /// a publisher, but it is signed by that publisher rather than by an authority key, and
+    /// `CompactComponents` only removes the components that no longer have a publisher.
+    pub fn is_co_signed(command: OracleCommand) -> bool {
+        matches!(
+            command,
+            OracleCommand::DelPublisher
+                | OracleCommand::RotatePublisher
+                | OracleCommand::SetExponent
+                | OracleCommand::HaltPrice
+        )
+    }
+
+    /// Whether `key` is one of the authority keys of the permissions account
+    pub fn is_authority(&self, key: &Pubkey) -> bool {
+        [
+            self.master_authority,
+            self.data_curation_authority,
+            self.security_authority,
+        ]
+        .contains(key)
+    }
 }
 
 impl PythAccount for PermissionAccount {
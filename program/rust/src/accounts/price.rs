@@ -26,18 +26,50 @@ mod price_pythnet {
     use {
         super::*,
         crate::{
+            aggregation::is_aggregated_status,
             c_oracle_header::{
+                MAX_CPI_CALLERS,
                 PC_MAX_SEND_LATENCY,
-                PC_NUM_COMP_PYTHNET,
-                PC_STATUS_TRADING,
+                PC_NUM_COMP,
+                PC_NUM_COMP_SLOTS,
+                PC_PRICE_T_COMP_OFFSET,
+                PRICE_HISTORY_LEN,
+                PUBLISHER_STATS_NUM_ROUNDS,
+                STATUS_HISTORY_LEN,
+                TIME_MACHINE_BUCKET_SECONDS,
+                TIME_MACHINE_NUM_BUCKETS,
             },
             error::OracleError,
+            migrations,
+            utils::{
+                get_rent,
+                pyth_assert,
+                send_lamports,
+                try_convert,
+            },
+        },
+        bytemuck::{
+            bytes_of,
+            pod_read_unaligned,
+            try_from_bytes,
+            try_from_bytes_mut,
+        },
+        solana_program::{
+            account_info::AccountInfo,
+            program_error::ProgramError,
+        },
+        std::{
+            cell::{
+                Ref,
+                RefMut,
+            },
+            ops::Range,
         },
     };
 
-    /// Pythnet-only extended price account format. This extension is
-    /// an append-only change that adds extra publisher slots and
-    /// PriceCumulative for TWAP processing.
+    /// Extended price account format. This extension is an append-only change that adds extra
+    /// publisher slots and PriceCumulative for TWAP processing. The account has room for
+    /// `PC_NUM_COMP_SLOTS` components, of which only `PC_NUM_COMP` are used.
     #[repr(C)]
     #[derive(Copy, Clone, Pod, Zeroable)]
     pub struct PriceAccountPythnet {
@@ -50,7 +82,7 @@ mod price_pythnet {
         pub num_:               u32,
         /// Number of valid quotes for the last aggregation
         pub num_qt_:            u32,
-        /// Last slot with a succesful aggregation (status : TRADING)
+        /// Last slot with a succesful aggregation (status : TRADING or DEGRADED)
         pub last_slot_:         u64,
         /// Second to last slot where aggregation was attempted
         pub valid_slot_:        u64,
@@ -65,14 +97,22 @@ mod price_pythnet {
         pub message_sent_:      u8,
         /// Configurable max latency in slots between send and receive
         pub max_latency_:       u8,
-        /// Unused placeholder for alignment
-        pub unused_2_:          i8,
-        pub unused_3_:          i32,
+        /// Aggregation mode, one of `PC_AGG_MODE_*`
+        pub agg_mode_:          u8,
+        /// Quotes more than this many confidence intervals away from the previous aggregate
+        /// are excluded from the aggregate, 0 to include all quotes
+        pub outlier_band_:      u16,
+        /// Number of slots skipped by the cluster by which the max latency was extended in the
+        /// last aggregation, at most `u8::MAX`
+        pub skipped_slots_:     u8,
+        /// Version of the layout of the account, see `migrations`. It is kept in the unused space
+        /// of the price accounts created before layout versions, which is 0 on all of them.
+        pub layout_version_:    u8,
         /// Corresponding product account
         pub product_account:    Pubkey,
         /// Next price account in the list
         pub next_price_account: Pubkey,
-        /// Second to last slot where aggregation was succesful (i.e. status : TRADING)
+        /// Second to last slot where aggregation was succesful (i.e. status : TRADING or DEGRADED)
         pub prev_slot_:         u64,
         /// Aggregate price at prev_slot_
         pub prev_price_:        i64,
@@ -82,18 +122,71 @@ mod price_pythnet {
         pub prev_timestamp_:    i64,
         /// Last attempted aggregate results
         pub agg_:               PriceInfo,
-        /// Publishers' price components. NOTE(2023-10-06): On Pythnet, not all
-        /// PC_NUM_COMP_PYTHNET slots are used due to stack size
-        /// issues in the C code. For iterating over price components,
-        /// PC_NUM_COMP must be used.
-        pub comp_:              [PriceComponent; PC_NUM_COMP_PYTHNET as usize],
+        /// Publishers' price components. NOTE(2023-10-06): The account has room for
+        /// PC_NUM_COMP_SLOTS components but only PC_NUM_COMP are used due to stack size issues
+        /// in the C code. The space of the unused components holds `publisher_stats_`.
+        pub comp_:              [PriceComponent; PC_NUM_COMP as usize],
+        /// Statistics of the publisher of each price component, indexed like `comp_`
+        pub publisher_stats_:   [PublisherStats; PC_NUM_COMP as usize],
+        /// Aggregate of the freshest quotes of the last aggregation attempt
+        pub fast_agg_:          FastAggregate,
+        /// Maximum number of price updates accepted from each publisher in a slot, 0 for no limit
+        pub max_slot_updates_:  u8,
+        /// Unused, cleared by the migration of the price accounts created before layout versions
+        pub unused_:            u8,
+        /// 1 if the price updates must be top-level instructions or directly invoked by one of
+        /// `cpi_callers_`, 0 to accept any price update
+        pub cpi_guard_:         u8,
+        /// 1 if the publishers take turns aggregating the price account (see
+        /// `aggregation_leader`), 0 if the first price update of each slot aggregates
+        pub agg_rotation_:      u8,
+        /// Stable index of the price feed among all the price feeds of the program, 0 until one
+        /// is assigned by `init_price_feed_index`
+        pub feed_index_:        u32,
+        /// Quotes below `min_price_` or above `max_price_` are excluded from the aggregate, no
+        /// bounds if both are 0
+        pub min_price_:         i64,
+        pub max_price_:         i64,
+        /// Earliest publish time of the quotes included in the last successful aggregate, among
+        /// the quotes that have one (see `PublisherStats::publish_time_`), 0 if none of them has
+        pub agg_pub_time_:      i64,
+        /// Programs allowed to invoke price updates via CPI when `cpi_guard_` is set, unused
+        /// entries are the default pubkey
+        pub cpi_callers_:       [Pubkey; MAX_CPI_CALLERS],
+        /// Weekly trading sessions, outside of which the aggregation fails
+        pub market_hours_:      MarketHours,
+        /// Aggregates whose confidence is more than `max_conf_ratio_` basis points of their price
+        /// are published with status `PC_STATUS_UNKNOWN` instead, 0 to publish any aggregate
+        pub max_conf_ratio_:    u32,
+        /// Lamports paid by the publisher into the treasury (see `TREASURY_SEED`) for each price
+        /// update, 0 for no fee
+        pub fee_lamports_:      u64,
+        /// Last change of the aggregate status (`agg_.status_`), see `record_status_change`
+        pub status_change_:     StatusChange,
+        /// Last successful aggregates, from which the drift of the price is estimated. Price
+        /// accounts on Solana have no room for it.
+        #[cfg(feature = "pythnet")]
+        pub price_history_:     PriceHistory,
+        /// Last `STATUS_HISTORY_LEN` changes of the aggregate status, an audit trail of the halts
+        /// and outages of the feed. Price accounts on Solana have no room for it.
+        #[cfg(feature = "pythnet")]
+        pub status_history_:    StatusHistory,
         /// Cumulative sums of aggregative price and confidence used to compute arithmetic moving averages
         pub price_cumulative:   PriceCumulative,
     }
 
+    // The components and their unused space fill exactly `PC_NUM_COMP_SLOTS` component slots
+    // between the fixed part of the account and the cumulative sums
+    const _: () = assert!(
+        size_of::<PriceAccountPythnet>()
+            == PC_PRICE_T_COMP_OFFSET as usize
+                + PC_NUM_COMP_SLOTS as usize * size_of::<PriceComponent>()
+                + size_of::<PriceCumulative>()
+    );
+
     impl PriceAccountPythnet {
         pub fn as_price_feed_message(&self, key: &Pubkey) -> PriceFeedMessage {
-            let (price, conf, publish_time) = if self.agg_.status_ == PC_STATUS_TRADING {
+            let (price, conf, publish_time) = if is_aggregated_status(self.agg_.status_) {
                 (self.agg_.price_, self.agg_.conf_, self.timestamp_)
             } else {
                 (self.prev_price_, self.prev_conf_, self.prev_timestamp_)
@@ -111,14 +204,17 @@ mod price_pythnet {
             }
         }
         /// This function gets triggered when there's a succesful aggregation and updates the cumulative sums
+        /// and the aggregate counter
         pub fn update_price_cumulative(&mut self) -> Result<(), OracleError> {
-            if self.agg_.status_ == PC_STATUS_TRADING {
+            if is_aggregated_status(self.agg_.status_) {
                 self.price_cumulative.update(
                     self.agg_.price_,
                     self.agg_.conf_,
                     self.agg_.pub_slot_.saturating_sub(self.prev_slot_),
                     self.max_latency_,
-                ); // pub_slot should always be >= prev_slot, but we protect ourselves against underflow just in case
+                )?; // pub_slot should always be >= prev_slot, but we protect ourselves against underflow just in case
+                self.price_cumulative.aggregate_counter =
+                    self.price_cumulative.aggregate_counter.wrapping_add(1);
                 Ok(())
             } else {
                 Err(OracleError::NeedsSuccesfulAggregation)
@@ -126,7 +222,7 @@ mod price_pythnet {
         }
 
         pub fn as_twap_message(&self, key: &Pubkey) -> TwapMessage {
-            let publish_time = if self.agg_.status_ == PC_STATUS_TRADING {
+            let publish_time = if is_aggregated_status(self.agg_.status_) {
                 self.timestamp_
             } else {
                 self.prev_timestamp_
@@ -143,17 +239,1232 @@ mod price_pythnet {
                 publish_slot: self.last_slot_,
             }
         }
+
+        /// Record the last successful aggregate in the price history. Price accounts on Solana
+        /// don't keep a history.
+        pub fn record_price_history(&mut self) {
+            #[cfg(feature = "pythnet")]
+            self.price_history_
+                .record(self.agg_.pub_slot_, self.agg_.price_);
+        }
+
+        /// Drift of the aggregate price over the price history (see `PriceHistory::drift`),
+        /// always `None` on Solana.
+        #[cfg(all(feature = "pythnet", any(test, feature = "library")))]
+        pub fn price_drift(&self) -> Option<i64> {
+            self.price_history_.drift()
+        }
+
+        #[cfg(all(feature = "solana", any(test, feature = "library")))]
+        pub fn price_drift(&self) -> Option<i64> {
+            None
+        }
+
+        /// Record the change of the aggregate status from `prev_status` to the current
+        /// `agg_.status_` in `slot` at the unix timestamp `timestamp`, if the status changed. The
+        /// processors that set the aggregate status call this afterwards. On Pythnet, the change
+        /// is also added to the status history.
+        pub fn record_status_change(&mut self, prev_status: u32, slot: u64, timestamp: i64) {
+            if self.agg_.status_ == prev_status {
+                return;
+            }
+            // The statuses are the small constants `PC_STATUS_*`
+            self.status_change_ = StatusChange {
+                slot_:         slot,
+                timestamp_:    timestamp,
+                prev_status_:  prev_status as u8,
+                status_:       self.agg_.status_ as u8,
+                agg_deferred_: self.status_change_.agg_deferred_,
+                unused_:       0,
+                num_changes_:  self.status_change_.num_changes_.wrapping_add(1),
+            };
+            #[cfg(feature = "pythnet")]
+            self.status_history_.record(&self.status_change_);
+        }
+
+        /// The last status changes recorded in the status history, the most recent first.
+        #[cfg(all(feature = "pythnet", any(test, feature = "library")))]
+        pub fn status_changes(&self) -> impl Iterator<Item = &StatusHistoryEntry> + '_ {
+            let num_changes = self.status_change_.num_changes_;
+            (0..num_changes.min(STATUS_HISTORY_LEN as u32)).map(move |i| {
+                &self.status_history_.entries_
+                    [num_changes.wrapping_sub(i + 1) as usize % STATUS_HISTORY_LEN]
+            })
+        }
+
+        /// Change the exponent of the price account, rescaling the stored prices and confidences
+        /// (aggregates, publisher quotes and trades, EMAs and cumulative sums) to the new exponent.
+        /// Prices are rounded toward zero and confidences up when the exponent increases.
+        ///
+        /// Returns an error if a value doesn't fit at the new exponent, in which case the account
+        /// is left unchanged.
+        pub fn set_exponent(&mut self, exponent: i32) -> Result<(), OracleError> {
+            let shift = self
+                .exponent
+                .checked_sub(exponent)
+                .ok_or(OracleError::IntegerCastingError)?;
+            let price = |value: i64| -> Result<i64, OracleError> {
+                try_convert(rescale_price(i128::from(value), shift)?)
+            };
+            let conf = |value: u64| -> Result<u64, OracleError> {
+                try_convert(rescale_conf(u128::from(value), shift)?)
+            };
+
+            // Every value is rescaled before the account is modified. The arrays are too large to
+            // be copied on the stack, so their values are only checked here and rescaled again
+            // below, which can't fail anymore.
+            let agg_price = price(self.agg_.price_)?;
+            let agg_conf = conf(self.agg_.conf_)?;
+            let prev_price = price(self.prev_price_)?;
+            let prev_conf = conf(self.prev_conf_)?;
+            let fast_agg_price = price(self.fast_agg_.price_)?;
+            let fast_agg_conf = conf(self.fast_agg_.conf_)?;
+            let min_price = price(self.min_price_)?;
+            let max_price = price(self.max_price_)?;
+            // The numerators and denominators of the EMAs are decimal numbers that don't depend
+            // on the exponent, only their values do.
+            let twap = price(self.twap_.val_)?;
+            let twac = try_convert(conf(try_convert(self.twac_.val_)?)?)?;
+            let price_cumulative = rescale_price(self.price_cumulative.price, shift)?;
+            let conf_cumulative = rescale_conf(self.price_cumulative.conf, shift)?;
+            #[cfg(feature = "pythnet")]
+            for history_price in self.price_history_.prices_.iter() {
+                price(*history_price)?;
+            }
+            for component in self.comp_.iter() {
+                for info in [&component.agg_, &component.latest_] {
+                    price(info.price_)?;
+                    conf(info.conf_)?;
+                }
+            }
+            for stats in self.publisher_stats_.iter() {
+                price(stats.last_trade_.price_)?;
+            }
+
+            self.agg_.price_ = agg_price;
+            self.agg_.conf_ = agg_conf;
+            self.prev_price_ = prev_price;
+            self.prev_conf_ = prev_conf;
+            self.fast_agg_.price_ = fast_agg_price;
+            self.fast_agg_.conf_ = fast_agg_conf;
+            self.min_price_ = min_price;
+            self.max_price_ = max_price;
+            self.twap_.val_ = twap;
+            self.twac_.val_ = twac;
+            self.price_cumulative.price = price_cumulative;
+            self.price_cumulative.conf = conf_cumulative;
+            #[cfg(feature = "pythnet")]
+            for history_price in self.price_history_.prices_.iter_mut() {
+                *history_price = price(*history_price)?;
+            }
+            for component in self.comp_.iter_mut() {
+                for info in [&mut component.agg_, &mut component.latest_] {
+                    info.price_ = price(info.price_)?;
+                    info.conf_ = conf(info.conf_)?;
+                }
+            }
+            for stats in self.publisher_stats_.iter_mut() {
+                stats.last_trade_.price_ = price(stats.last_trade_.price_)?;
+            }
+
+            self.exponent = exponent;
+            Ok(())
+        }
+    }
+
+    /// The regions stored after the `PriceAccount`, or extensions, are added by the first
+    /// instruction that needs them (see `add_extension`). Each extension starts with an
+    /// `ExtensionHeader` giving its kind and the size of its content, and the extensions follow
+    /// each other in the order they were added, so that adding one never moves the others. The
+    /// space after the last extension is zeroed, a header of kind 0 ends the extensions.
+    impl PriceAccountPythnet {
+        /// Size of a price account with every extension, the largest price account
+        pub const MAX_SIZE: usize = size_of::<PriceAccountPythnet>()
+            + Self::extension_size::<PublisherExpiries>()
+            + Self::extension_size::<PublisherPruning>()
+            + Self::extension_size::<AggInterval>()
+            + Self::extension_size::<PublisherWeights>()
+            + Self::extension_size::<TimeMachine>();
+
+        /// Space taken by the extension `T`, header included. The content is padded to a
+        /// multiple of 8 bytes to keep the next header aligned.
+        pub const fn extension_size<T: PriceAccountExtension>() -> usize {
+            size_of::<ExtensionHeader>() + padded_extension_size(size_of::<T>())
+        }
+
+        /// The range of the content of the extension `T` in `tail`, the data of the price
+        /// account after the `PriceAccount`, or the offset of the end of the extensions in
+        /// `tail` if the price account has no `T`.
+        fn find_extension<T: PriceAccountExtension>(tail: &[u8]) -> Result<Range<usize>, usize> {
+            let mut offset = 0;
+            while let Some(header) = tail.get(offset..offset + size_of::<ExtensionHeader>()) {
+                let header = pod_read_unaligned::<ExtensionHeader>(header);
+                let start = offset + size_of::<ExtensionHeader>();
+                let size = header.size_ as usize;
+                if header.kind_ == 0 || start + size > tail.len() {
+                    break;
+                }
+                if header.kind_ == T::KIND && size >= size_of::<T>() {
+                    return Ok(start..start + size_of::<T>());
+                }
+                offset = start + padded_extension_size(size);
+            }
+            Err(offset)
+        }
+
+        /// The content of the extension `T` in `tail`, the data of the price account after the
+        /// `PriceAccount` (see `load_checked_with_tail`), `None` if the price account has no `T`.
+        pub fn extension_data<T: PriceAccountExtension>(tail: &[u8]) -> Option<&[u8]> {
+            Self::find_extension::<T>(tail)
+                .ok()
+                .map(|range| &tail[range])
+        }
+
+        /// Whether the price account `account` has the extension `T`
+        pub fn has_extension<T: PriceAccountExtension>(
+            account: &AccountInfo,
+        ) -> Result<bool, ProgramError> {
+            let data = account.try_borrow_data()?;
+            Ok(
+                Self::extension_data::<T>(data.get(size_of::<Self>()..).unwrap_or_default())
+                    .is_some(),
+            )
+        }
+
+        /// Borrow the extension `T` of the price account `account`, `None` if it has no `T`. Unlike
+        /// `load_checked`, the header of the account is not checked.
+        pub fn load_extension<'a, T: PriceAccountExtension>(
+            account: &'a AccountInfo,
+        ) -> Result<Option<Ref<'a, T>>, ProgramError> {
+            let data = account.try_borrow_data()?;
+            let range = match Self::find_extension::<T>(
+                data.get(size_of::<Self>()..).unwrap_or_default(),
+            ) {
+                Ok(range) => range,
+                Err(_) => return Ok(None),
+            };
+
+            Ref::filter_map(data, |data| {
+                try_from_bytes(&data[size_of::<Self>()..][range]).ok()
+            })
+            .map(Some)
+            .map_err(|_| OracleError::DeserializationError.into())
+        }
+
+        /// Mutably borrow the price account `account` along with its extension `T`. Fails if the
+        /// account has no `T` yet. Unlike `load_checked`, the header of the account is not
+        /// checked.
+        pub fn load_with_extension_mut<'a, T: PriceAccountExtension>(
+            account: &'a AccountInfo,
+        ) -> Result<(RefMut<'a, PriceAccountPythnet>, RefMut<'a, T>), ProgramError> {
+            let data = account.try_borrow_mut_data()?;
+            let range =
+                Self::find_extension::<T>(data.get(size_of::<Self>()..).unwrap_or_default())
+                    .map_err(|_| OracleError::AccountTooSmall)?;
+
+            let (price_data, extension) = RefMut::map_split(data, move |data| {
+                let (price_data, tail) = data.split_at_mut(size_of::<PriceAccountPythnet>());
+                (price_data, &mut tail[range])
+            });
+            Ok((
+                RefMut::filter_map(price_data, |data| try_from_bytes_mut(data).ok())
+                    .map_err(|_| OracleError::DeserializationError)?,
+                RefMut::filter_map(extension, |data| try_from_bytes_mut(data).ok())
+                    .map_err(|_| OracleError::DeserializationError)?,
+            ))
+        }
+
+        /// Add the extension `T`, zeroed, after the last extension of the price account
+        /// `account`, unless it already has one. The account is resized if there is no room
+        /// for it, the funding account paying for the rent of the extra space.
+        pub fn add_extension<'a, T: PriceAccountExtension>(
+            account: &AccountInfo<'a>,
+            funding_account: &AccountInfo<'a>,
+            system_program: &AccountInfo<'a>,
+        ) -> Result<(), ProgramError> {
+            let offset = {
+                let data = account.try_borrow_data()?;
+                match Self::find_extension::<T>(data.get(size_of::<Self>()..).unwrap_or_default()) {
+                    Ok(_) => return Ok(()),
+                    Err(end) => size_of::<Self>() + end,
+                }
+            };
+            let size = offset + Self::extension_size::<T>();
+            if account.data_len() < size {
+                let target_rent = get_rent()?.minimum_balance(size);
+                let missing_rent = target_rent.saturating_sub(account.lamports());
+                if missing_rent > 0 {
+                    send_lamports(funding_account, account, system_program, missing_rent)?;
+                }
+                account.realloc(size, true)?;
+            }
+
+            let header = ExtensionHeader {
+                kind_: T::KIND,
+                size_: try_convert::<_, u32>(size_of::<T>())?,
+            };
+            let mut data = account.try_borrow_mut_data()?;
+            let (header_data, content) =
+                data[offset..size].split_at_mut(size_of::<ExtensionHeader>());
+            header_data.copy_from_slice(bytes_of(&header));
+            content.fill(0);
+            Ok(())
+        }
+    }
+
+    /// `size` rounded up to a multiple of 8
+    const fn padded_extension_size(size: usize) -> usize {
+        (size + 7) / 8 * 8
+    }
+
+    impl PriceAccountExtension for PublisherExpiries {
+        const KIND: u32 = 1;
+    }
+
+    /// The expiries of the publishers of a price account (see `add_publisher_with_expiry`) are
+    /// its `PublisherExpiries` extension. The entries are keyed by publisher rather than indexed
+    /// like `comp_` so that they don't move when the components are sorted.
+    impl PriceAccountPythnet {
+        /// The last slot at which the price account `account` accepts the price updates of
+        /// `publisher`, `None` if the publisher has no expiry.
+        pub fn read_publisher_expiry(
+            account: &AccountInfo,
+            publisher: &Pubkey,
+        ) -> Result<Option<u64>, ProgramError> {
+            let data = account.try_borrow_data()?;
+            Ok(Self::publisher_expiry(
+                data.get(size_of::<Self>()..).unwrap_or_default(),
+                publisher,
+            ))
+        }
+
+        /// Like `read_publisher_expiry`, from `tail`, the data of the price account after the
+        /// `PriceAccount` (see `load_checked_with_tail`).
+        pub fn publisher_expiry(tail: &[u8], publisher: &Pubkey) -> Option<u64> {
+            Self::extension_data::<PublisherExpiries>(tail)
+                .and_then(|expiries| {
+                    expiries
+                        .chunks_exact(size_of::<PublisherExpiry>())
+                        .map(pod_read_unaligned::<PublisherExpiry>)
+                        .find(|expiry| expiry.publisher_ == *publisher)
+                })
+                .map(|expiry| expiry.expiry_slot_)
+        }
+
+        /// Set the expiry of `publisher`, one of the current `publishers` of the price account
+        /// `account`. The entries of the publishers removed since they were given an expiry are
+        /// reused. Fails if the account has no `PublisherExpiries` yet.
+        pub fn set_publisher_expiry(
+            account: &AccountInfo,
+            publisher: &Pubkey,
+            expiry_slot: u64,
+            publishers: &[Pubkey],
+        ) -> Result<(), ProgramError> {
+            let (_, mut expiries) = Self::load_with_extension_mut::<PublisherExpiries>(account)?;
+            let index = expiries
+                .iter()
+                .position(|expiry| expiry.publisher_ == *publisher)
+                .or_else(|| {
+                    expiries
+                        .iter()
+                        .position(|expiry| !publishers.contains(&expiry.publisher_))
+                })
+                .ok_or(ProgramError::InvalidArgument)?;
+            expiries[index] = PublisherExpiry {
+                publisher_:   *publisher,
+                expiry_slot_: expiry_slot,
+            };
+            Ok(())
+        }
+
+        /// Move the expiry of `old_publisher`, if any, to `new_publisher`, removing the expiry
+        /// that `new_publisher` may have had. Clears the expiry of `new_publisher` if
+        /// `old_publisher` is `None`.
+        pub fn replace_publisher_expiry(
+            account: &AccountInfo,
+            old_publisher: Option<&Pubkey>,
+            new_publisher: &Pubkey,
+        ) -> Result<(), ProgramError> {
+            if !Self::has_extension::<PublisherExpiries>(account)? {
+                return Ok(());
+            }
+            let (_, mut expiries) = Self::load_with_extension_mut::<PublisherExpiries>(account)?;
+            for expiry in expiries.iter_mut() {
+                if expiry.publisher_ == *new_publisher {
+                    *expiry = PublisherExpiry::zeroed();
+                } else if Some(&expiry.publisher_) == old_publisher {
+                    expiry.publisher_ = *new_publisher;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    impl PriceAccountExtension for PublisherPruning {
+        const KIND: u32 = 2;
+    }
+
+    /// The pruning of the publishers of a price account (see `set_publisher_pruning`) is its
+    /// `PublisherPruning` extension.
+    impl PriceAccountPythnet {
+        /// Move the pruning state of `old_publisher`, if any, to `new_publisher`, like
+        /// `replace_publisher_expiry`. `new_publisher` is admitted if `old_publisher` is `None`.
+        pub fn replace_publisher_pruning(
+            account: &AccountInfo,
+            old_publisher: Option<&Pubkey>,
+            new_publisher: &Pubkey,
+        ) -> Result<(), ProgramError> {
+            if !Self::has_extension::<PublisherPruning>(account)? {
+                return Ok(());
+            }
+            let (_, mut pruning) = Self::load_with_extension_mut::<PublisherPruning>(account)?;
+            for state in pruning.states_.iter_mut() {
+                if state.publisher_ == *new_publisher {
+                    *state = PruningState::zeroed();
+                } else if Some(&state.publisher_) == old_publisher {
+                    state.publisher_ = *new_publisher;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    impl PriceAccountExtension for AggInterval {
+        const KIND: u32 = 3;
+    }
+
+    /// The aggregation interval of a price account (see `set_agg_interval`) is its
+    /// `AggInterval` extension.
+    impl PriceAccountPythnet {
+        /// Minimum number of slots between two aggregations of the price account `account`, 1
+        /// (every slot) if it was never set.
+        pub fn read_agg_interval(account: &AccountInfo) -> Result<u64, ProgramError> {
+            let data = account.try_borrow_data()?;
+            Ok(Self::agg_interval(
+                data.get(size_of::<Self>()..).unwrap_or_default(),
+            ))
+        }
+
+        /// Like `read_agg_interval`, from `tail`, the data of the price account after the
+        /// `PriceAccount` (see `load_checked_with_tail`).
+        pub fn agg_interval(tail: &[u8]) -> u64 {
+            Self::extension_data::<AggInterval>(tail)
+                .map_or(1, |data| pod_read_unaligned::<AggInterval>(data).slots_)
+                .max(1)
+        }
+    }
+
+    impl PriceAccountExtension for PublisherWeights {
+        const KIND: u32 = 4;
+    }
+
+    /// The weights of the publishers of a price account (see `set_publisher_weight`) are its
+    /// `PublisherWeights` extension.
+    impl PriceAccountPythnet {
+        /// Move the weight of `old_publisher`, if any, to `new_publisher`, like
+        /// `replace_publisher_expiry`. Clears the weight of `new_publisher` if `old_publisher` is
+        /// `None`.
+        pub fn replace_publisher_weight(
+            account: &AccountInfo,
+            old_publisher: Option<&Pubkey>,
+            new_publisher: &Pubkey,
+        ) -> Result<(), ProgramError> {
+            if !Self::has_extension::<PublisherWeights>(account)? {
+                return Ok(());
+            }
+            let (_, mut weights) = Self::load_with_extension_mut::<PublisherWeights>(account)?;
+            for entry in weights.entries_.iter_mut() {
+                if entry.publisher_ == *new_publisher {
+                    *entry = PublisherWeight::zeroed();
+                } else if Some(&entry.publisher_) == old_publisher {
+                    entry.publisher_ = *new_publisher;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    impl PriceAccountExtension for TimeMachine {
+        const KIND: u32 = 5;
+    }
+
+    /// The time machine of a price account (see `init_time_machine`) is its `TimeMachine`
+    /// extension.
+    impl PriceAccountPythnet {
+        /// Record the last successful aggregate of the price account `account` in its time
+        /// machine, if it has one.
+        pub fn record_time_machine(account: &AccountInfo) -> Result<(), ProgramError> {
+            if !Self::has_extension::<TimeMachine>(account)? {
+                return Ok(());
+            }
+            let (price_data, mut time_machine) =
+                Self::load_with_extension_mut::<TimeMachine>(account)?;
+            // Like the cumulative sums, see `update_price_cumulative`
+            time_machine.record(
+                price_data.timestamp_,
+                price_data.agg_.price_,
+                price_data.agg_.conf_,
+                price_data
+                    .agg_
+                    .pub_slot_
+                    .saturating_sub(price_data.prev_slot_),
+            )?;
+            Ok(())
+        }
+
+        /// Clear the buckets of the time machine of the price account `account`, if it has one.
+        pub fn clear_time_machine(account: &AccountInfo) -> Result<(), ProgramError> {
+            if !Self::has_extension::<TimeMachine>(account)? {
+                return Ok(());
+            }
+            let (_, mut time_machine) = Self::load_with_extension_mut::<TimeMachine>(account)?;
+            *time_machine = TimeMachine::zeroed();
+            Ok(())
+        }
+    }
+
+    /// Rescale `value` by `10^shift`, rounding toward zero.
+    fn rescale_price(value: i128, shift: i32) -> Result<i128, OracleError> {
+        let factor = 10i128
+            .checked_pow(shift.unsigned_abs())
+            .ok_or(OracleError::IntegerCastingError)?;
+        if shift >= 0 {
+            value
+                .checked_mul(factor)
+                .ok_or(OracleError::IntegerCastingError)
+        } else {
+            Ok(value / factor)
+        }
+    }
+
+    /// Rescale `value` by `10^shift`, rounding up.
+    fn rescale_conf(value: u128, shift: i32) -> Result<u128, OracleError> {
+        let factor = 10u128
+            .checked_pow(shift.unsigned_abs())
+            .ok_or(OracleError::IntegerCastingError)?;
+        if shift >= 0 {
+            value
+                .checked_mul(factor)
+                .ok_or(OracleError::IntegerCastingError)
+        } else {
+            Ok(value / factor + u128::from(value % factor != 0))
+        }
+    }
+
+    /// Rolling statistics over the last `PUBLISHER_STATS_NUM_ROUNDS` aggregation rounds of a
+    /// publisher. They are reset when the publisher is added to the price account.
+    #[repr(C)]
+    #[cfg_attr(test, derive(Debug, PartialEq))]
+    #[derive(Copy, Clone, Pod, Zeroable)]
+    pub struct PublisherStats {
+        /// Bit `i` is set if the publisher's quote was included in the aggregate `i` rounds ago
+        pub included_rounds_: u32,
+        /// Number of rounds recorded, up to `PUBLISHER_STATS_NUM_ROUNDS`
+        pub num_rounds_:      u8,
+        /// Index in `latencies_` of the next round
+        pub cursor_:          u8,
+        /// Number of price updates accepted in `last_upd_slot_`
+        pub num_updates_:     u8,
+        /// Non-zero if the quotes of the publisher are stored but excluded from the aggregation:
+        /// bit `EXCLUDED` is set by `SetPublisherExcluded` and bit `PRUNED` while the publisher is
+        /// pruned (see `PublisherPruning`)
+        pub excluded_:        u8,
+        /// Latency in slots of the quote included in each round, 0 if it wasn't included
+        pub latencies_:       [u8; PUBLISHER_STATS_NUM_ROUNDS],
+        /// Slot of the last accepted price update
+        pub last_upd_slot_:   u64,
+        /// Publish time of the latest quote of the publisher that has one, as given by
+        /// `UpdPriceWithTime`, 0 if none of its quotes has
+        pub publish_time_:    i64,
+        /// Freshest trade print attached to a price update of the publisher, see
+        /// `UpdPriceWithTrade`
+        pub last_trade_:      LastTrade,
+    }
+
+    impl PublisherStats {
+        /// Bit of `excluded_` set for publishers excluded with `SetPublisherExcluded`
+        pub const EXCLUDED: u8 = 1;
+        /// Bit of `excluded_` set for pruned publishers
+        pub const PRUNED: u8 = 2;
+
+        /// Record an aggregation round. `latency` is the latency of the publisher's quote if it
+        /// was included in the aggregate, `None` otherwise.
+        pub fn record_round(&mut self, latency: Option<u64>) {
+            self.included_rounds_ <<= 1;
+            if latency.is_some() {
+                self.included_rounds_ |= 1;
+            }
+            let cursor = usize::from(self.cursor_) % PUBLISHER_STATS_NUM_ROUNDS;
+            self.latencies_[cursor] =
+                latency.map_or(0, |latency| latency.min(u64::from(u8::MAX)) as u8);
+            self.cursor_ = ((cursor + 1) % PUBLISHER_STATS_NUM_ROUNDS) as u8;
+            if usize::from(self.num_rounds_) < PUBLISHER_STATS_NUM_ROUNDS {
+                self.num_rounds_ += 1;
+            }
+        }
+
+        /// Number of price updates of the publisher accepted in `slot`
+        pub fn num_updates_in_slot(&self, slot: u64) -> u8 {
+            if self.last_upd_slot_ == slot {
+                self.num_updates_
+            } else {
+                0
+            }
+        }
+
+        /// Record a price update of the publisher accepted in `slot`
+        pub fn record_update(&mut self, slot: u64) {
+            self.num_updates_ = self.num_updates_in_slot(slot).saturating_add(1);
+            self.last_upd_slot_ = slot;
+            self.last_trade_.heartbeat_slots_ = 0;
+        }
+
+        /// Record a heartbeat of the publisher in `slot`, see `Heartbeat`
+        pub fn record_heartbeat(&mut self, slot: u64) {
+            let heartbeat_slots = slot.saturating_sub(self.last_upd_slot_);
+            self.last_trade_.heartbeat_slots_ = heartbeat_slots.min(u64::from(u32::MAX)) as u32;
+        }
+
+        /// Last slot in which the publisher was seen alive, i.e. sent a price update or a
+        /// heartbeat. Unlike `last_upd_slot_`, it tells a publisher that has no new price apart
+        /// from a publisher that is down.
+        pub fn last_seen_slot(&self) -> u64 {
+            self.last_upd_slot_
+                .saturating_add(u64::from(self.last_trade_.heartbeat_slots_))
+        }
+    }
+
+    /// A trade observed by a publisher on one of its venues, as attached to a price update with
+    /// `UpdPriceWithTrade`. The price is at the exponent of the price account.
+    #[repr(C)]
+    #[cfg_attr(test, derive(Debug, PartialEq))]
+    #[derive(Copy, Clone, Pod, Zeroable)]
+    pub struct LastTrade {
+        pub price_:           i64,
+        /// Traded quantity, in units chosen by the publisher. 0 if the publisher never attached
+        /// a trade to its price updates.
+        pub size_:            u64,
+        /// Publishing slot of the price update that carried the trade
+        pub pub_slot_:        u64,
+        /// Identifier of the venue of the trade, assigned by the publisher
+        pub venue_id_:        u32,
+        /// Not part of the trade: number of slots from `PublisherStats::last_upd_slot_` to the
+        /// last heartbeat of the publisher, see `PublisherStats::last_seen_slot`. It is kept here
+        /// for lack of room in `PublisherStats`, and reset by every price update.
+        pub heartbeat_slots_: u32,
+    }
+
+    /// Header of an extension of a price account, see `PriceAccount::add_extension`
+    #[repr(C)]
+    #[cfg_attr(test, derive(Debug, PartialEq))]
+    #[derive(Copy, Clone, Pod, Zeroable)]
+    pub struct ExtensionHeader {
+        /// `PriceAccountExtension::KIND` of the extension
+        pub kind_: u32,
+        /// Size of the content of the extension, without the header and the padding
+        pub size_: u32,
+    }
+
+    /// A region stored after the `PriceAccount` in the price accounts that use it, see
+    /// `PriceAccount::add_extension`
+    pub trait PriceAccountExtension: Pod {
+        /// Kind of the extension in its `ExtensionHeader`, unique and never 0
+        const KIND: u32;
+    }
+
+    /// Minimum number of slots between two aggregations of a price account, see
+    /// `PriceAccount::read_agg_interval`
+    #[repr(C)]
+    #[cfg_attr(test, derive(Debug, PartialEq))]
+    #[derive(Copy, Clone, Pod, Zeroable)]
+    pub struct AggInterval {
+        /// 0 counts as 1
+        pub slots_: u64,
+    }
+
+    /// Last slot at which a publisher of a price account is allowed to publish, see
+    /// `PriceAccount::read_publisher_expiry`
+    #[repr(C)]
+    #[cfg_attr(test, derive(Debug, PartialEq))]
+    #[derive(Copy, Clone, Pod, Zeroable)]
+    pub struct PublisherExpiry {
+        /// The default pubkey in unused entries
+        pub publisher_:   Pubkey,
+        pub expiry_slot_: u64,
+    }
+
+    /// There is at most one entry per publisher of the price account
+    pub type PublisherExpiries = [PublisherExpiry; PC_NUM_COMP as usize];
+
+    /// Weights of the publishers of a price account in the weighted aggregation mode (see
+    /// `PC_AGG_MODE_WEIGHTED`). Publishers without an entry have weight 0, which the aggregation
+    /// counts as 1.
+    #[repr(C)]
+    #[cfg_attr(test, derive(Debug, PartialEq))]
+    #[derive(Copy, Clone, Pod, Zeroable)]
+    pub struct PublisherWeights {
+        /// Weight of the publisher of each component, indexed like `comp_` as of the last
+        /// aggregation round. There is at most one entry per publisher.
+        pub entries_: [PublisherWeight; PC_NUM_COMP as usize],
+    }
+
+    impl PublisherWeights {
+        /// The weights of the publishers of `components`, indexed like them. The entries are
+        /// moved to their component when the components were moved since the last aggregation
+        /// round.
+        pub fn component_weights(
+            &mut self,
+            components: &[PriceComponent],
+        ) -> [u32; PC_NUM_COMP as usize] {
+            let mut weights = [0; PC_NUM_COMP as usize];
+            for (index, (component, weight)) in
+                components.iter().zip(weights.iter_mut()).enumerate()
+            {
+                if self.entries_[index].publisher_ != component.pub_ {
+                    // The entries before `index` that are in place belong to other publishers
+                    match self
+                        .entries_
+                        .iter()
+                        .position(|entry| entry.publisher_ == component.pub_)
+                    {
+                        Some(other) => self.entries_.swap(index, other),
+                        None => continue,
+                    }
+                }
+                *weight = self.entries_[index].weight_;
+            }
+            weights
+        }
+
+        /// Set the weight of `publisher`, one of the current `publishers` of the price account.
+        /// The entries of the publishers removed since they were given a weight are reused.
+        pub fn set(
+            &mut self,
+            publisher: &Pubkey,
+            weight: u32,
+            publishers: &[Pubkey],
+        ) -> Result<(), ProgramError> {
+            let index = self
+                .entries_
+                .iter()
+                .position(|entry| entry.publisher_ == *publisher)
+                .or_else(|| {
+                    self.entries_
+                        .iter()
+                        .position(|entry| !publishers.contains(&entry.publisher_))
+                })
+                .ok_or(ProgramError::InvalidArgument)?;
+            self.entries_[index] = PublisherWeight {
+                publisher_: *publisher,
+                weight_:    weight,
+                unused_:    0,
+            };
+            Ok(())
+        }
+    }
+
+    /// Weight of a publisher of a price account, see `PublisherWeights`
+    #[repr(C)]
+    #[cfg_attr(test, derive(Debug, PartialEq))]
+    #[derive(Copy, Clone, Pod, Zeroable)]
+    pub struct PublisherWeight {
+        /// The default pubkey in unused entries
+        pub publisher_: Pubkey,
+        pub weight_:    u32,
+        pub unused_:    u32,
+    }
+
+    /// Automatic exclusion from the aggregation of the publishers that stop contributing to it,
+    /// see `set_publisher_pruning`. Each publisher is either admitted or pruned:
+    /// - an admitted publisher whose quote is not included in `prune_rounds_` consecutive
+    ///   aggregation rounds, because it is missing, stale or an outlier, is pruned;
+    /// - a pruned publisher whose quote would have been included in `readmit_rounds_` consecutive
+    ///   rounds is admitted again.
+    ///
+    /// The quotes of pruned publishers are still stored but excluded from the aggregation like
+    /// those of manually excluded publishers (see `PublisherStats::PRUNED`). Manually excluded
+    /// publishers keep their pruning state until they are included again.
+    #[repr(C)]
+    #[cfg_attr(test, derive(Debug, PartialEq))]
+    #[derive(Copy, Clone, Pod, Zeroable)]
+    pub struct PublisherPruning {
+        /// Number of consecutive missed rounds after which a publisher is pruned, 0 if the
+        /// pruning is disabled
+        pub prune_rounds_:   u8,
+        /// Number of consecutive rounds a pruned publisher must contribute to be admitted again
+        pub readmit_rounds_: u8,
+        pub unused_:         [u8; 6],
+        /// State of the publisher of each component, indexed like `comp_` as of the last
+        /// aggregation round. Publishers without a state are admitted.
+        pub states_:         [PruningState; PC_NUM_COMP as usize],
+    }
+
+    impl PublisherPruning {
+        /// The state of the publisher of `components[index]`. States are moved to their
+        /// component when the components were moved since the last aggregation round, and
+        /// created for new publishers in place of a state that no component needs. This must be
+        /// called for the components in order.
+        pub fn state_mut(
+            &mut self,
+            index: usize,
+            components: &[PriceComponent],
+        ) -> &mut PruningState {
+            let publisher = components[index].pub_;
+            if self.states_[index].publisher_ != publisher {
+                // The states before `index` belong to the previous components
+                let is_needed = |state: &PruningState| {
+                    components[index + 1..]
+                        .iter()
+                        .any(|component| component.pub_ == state.publisher_)
+                };
+                let other = (index..self.states_.len())
+                    .find(|other| self.states_[*other].publisher_ == publisher)
+                    .or_else(|| {
+                        (index..self.states_.len()).find(|other| !is_needed(&self.states_[*other]))
+                    })
+                    .unwrap_or(index);
+                self.states_.swap(index, other);
+                if self.states_[index].publisher_ != publisher {
+                    self.states_[index] = PruningState {
+                        publisher_: publisher,
+                        ..PruningState::zeroed()
+                    };
+                }
+            }
+            &mut self.states_[index]
+        }
+    }
+
+    /// Pruning state of a publisher, see `PublisherPruning`
+    #[repr(C)]
+    #[cfg_attr(test, derive(Debug, PartialEq))]
+    #[derive(Copy, Clone, Pod, Zeroable)]
+    pub struct PruningState {
+        /// The default pubkey in unused states
+        pub publisher_: Pubkey,
+        /// 1 if the publisher is pruned, 0 if it is admitted
+        pub pruned_:    u8,
+        pub unused_:    [u8; 3],
+        /// Number of consecutive rounds counting towards the next transition: the rounds in which
+        /// the quote of an admitted publisher was not included, or in which the quote of a pruned
+        /// publisher would have been included
+        pub rounds_:    u32,
+    }
+
+    impl PruningState {
+        /// Record an aggregation round, in which the quote of the publisher was `included`, or
+        /// would have been if the publisher is pruned. Returns whether the publisher is pruned
+        /// after the round.
+        pub fn record_round(
+            &mut self,
+            included: bool,
+            prune_rounds: u8,
+            readmit_rounds: u8,
+        ) -> bool {
+            let (counts, threshold) = if self.pruned_ != 0 {
+                (included, readmit_rounds)
+            } else {
+                (!included, prune_rounds)
+            };
+            self.rounds_ = if counts {
+                self.rounds_.saturating_add(1)
+            } else {
+                0
+            };
+            if self.rounds_ >= u32::from(threshold) {
+                self.pruned_ ^= 1;
+                self.rounds_ = 0;
+            }
+            self.pruned_ != 0
+        }
+    }
+
+    /// Averages of the successful aggregates of a price account over consecutive periods of
+    /// `TIME_MACHINE_BUCKET_SECONDS` seconds, or buckets, see `init_time_machine`. The buckets of
+    /// the last `TIME_MACHINE_NUM_BUCKETS` periods up to the last aggregate are kept, the bucket of
+    /// period number `n` being in entry `(n - 1) % TIME_MACHINE_NUM_BUCKETS` and overwriting the
+    /// oldest buckets first. The periods without an aggregate, when the feed was dark, have a
+    /// bucket marked invalid in `valid_` rather than carrying the previous averages forward.
+    #[repr(C)]
+    #[cfg_attr(test, derive(Debug, PartialEq))]
+    #[derive(Copy, Clone, Pod, Zeroable)]
+    pub struct TimeMachine {
+        /// Number of periods since the first aggregate recorded by the time machine, the dark
+        /// ones included
+        pub num_buckets_: u64,
+        /// Bit `i` is set if entry `i` of `buckets_` is the bucket of a period with aggregates
+        pub valid_:       u64,
+        pub buckets_:     [TimeMachineBucket; TIME_MACHINE_NUM_BUCKETS],
+    }
+
+    impl TimeMachine {
+        /// Entry of `buckets_` of the bucket of period number `n`
+        fn entry(n: u64) -> usize {
+            n.wrapping_sub(1) as usize % TIME_MACHINE_NUM_BUCKETS
+        }
+
+        /// Add an aggregate of `price` and `conf` computed at `timestamp` and held for `slot_gap`
+        /// slots to the bucket of its period. If the period is after the one of the last bucket,
+        /// its bucket is started and the buckets of the dark periods in between are marked
+        /// invalid. Returns `ArithmeticOverflow`, leaving the buckets unchanged, if a sum
+        /// overflows.
+        pub fn record(
+            &mut self,
+            timestamp: i64,
+            price: i64,
+            conf: u64,
+            slot_gap: u64,
+        ) -> Result<(), OracleError> {
+            let start_time = timestamp - timestamp.rem_euclid(TIME_MACHINE_BUCKET_SECONDS);
+            let last_bucket = self.buckets().next().copied();
+            // An aggregate older than the last bucket, if the clock went back, is added to it
+            let (bucket, num_new_periods) = match last_bucket {
+                Some(bucket) if bucket.start_time_ >= start_time => (bucket, 0),
+                _ => (
+                    TimeMachineBucket {
+                        start_time_: start_time,
+                        ..TimeMachineBucket::zeroed()
+                    },
+                    last_bucket.map_or(1, |bucket| {
+                        (start_time.saturating_sub(bucket.start_time_)
+                            / TIME_MACHINE_BUCKET_SECONDS) as u64
+                    }),
+                ),
+            };
+
+            // The products fit in 128 bits, only the sums can overflow
+            let bucket = TimeMachineBucket {
+                start_time_: bucket.start_time_,
+                num_slots_:  bucket
+                    .num_slots_
+                    .checked_add(slot_gap)
+                    .ok_or(OracleError::ArithmeticOverflow)?,
+                price_sum_:  bucket
+                    .price_sum_
+                    .checked_add(i128::from(price) * i128::from(slot_gap))
+                    .ok_or(OracleError::ArithmeticOverflow)?,
+                conf_sum_:   bucket
+                    .conf_sum_
+                    .checked_add(u128::from(conf) * u128::from(slot_gap))
+                    .ok_or(OracleError::ArithmeticOverflow)?,
+            };
+
+            // The buckets of older dark periods would be overwritten by the next ones anyway
+            let num_dark_periods = num_new_periods.saturating_sub(1);
+            let first_kept = num_dark_periods.saturating_sub(TIME_MACHINE_NUM_BUCKETS as u64 - 1);
+            for i in first_kept..num_dark_periods {
+                let entry = Self::entry(self.num_buckets_.wrapping_add(i + 1));
+                self.buckets_[entry] = TimeMachineBucket {
+                    start_time_: start_time
+                        - (num_dark_periods - i) as i64 * TIME_MACHINE_BUCKET_SECONDS,
+                    ..TimeMachineBucket::zeroed()
+                };
+                self.valid_ &= !(1 << entry);
+            }
+            self.num_buckets_ = self.num_buckets_.wrapping_add(num_new_periods);
+            let entry = Self::entry(self.num_buckets_);
+            self.buckets_[entry] = bucket;
+            self.valid_ |= 1 << entry;
+            Ok(())
+        }
+
+        /// The buckets of the time machine with aggregates, the most recent first. The buckets
+        /// of the dark periods are skipped.
+        pub fn buckets(&self) -> impl Iterator<Item = &TimeMachineBucket> + '_ {
+            let num_buckets = self.num_buckets_;
+            (0..num_buckets.min(TIME_MACHINE_NUM_BUCKETS as u64))
+                .map(move |i| Self::entry(num_buckets.wrapping_sub(i)))
+                .filter(move |&entry| self.valid_ & (1 << entry) != 0)
+                .map(move |entry| &self.buckets_[entry])
+        }
+
+        /// The averages of the `k` most recent buckets with aggregates whose period is over at
+        /// `timestamp`, the most recent first. Windowed averages of any length up to the kept
+        /// history can be computed from them, see `BucketAverage`.
+        pub fn recent_averages(
+            &self,
+            timestamp: i64,
+            k: usize,
+        ) -> impl Iterator<Item = BucketAverage> + '_ {
+            self.buckets()
+                .filter(move |bucket| bucket.end_time() <= timestamp)
+                .filter_map(TimeMachineBucket::average)
+                .take(k)
+        }
+    }
+
+    /// The successful aggregates of a price account during a period of its time machine, see
+    /// `TimeMachine`
+    #[repr(C)]
+    #[cfg_attr(test, derive(Debug, PartialEq))]
+    #[derive(Copy, Clone, Pod, Zeroable)]
+    pub struct TimeMachineBucket {
+        /// Unix timestamp of the start of the period, a multiple of `TIME_MACHINE_BUCKET_SECONDS`
+        pub start_time_: i64,
+        /// Number of slots the aggregates of the bucket were held for, the sum of their slot gaps
+        pub num_slots_:  u64,
+        /// Sum of price * slot_gap of the aggregates of the bucket, like `PriceCumulative::price`
+        pub price_sum_:  i128,
+        /// Sum of conf * slot_gap of the aggregates of the bucket
+        pub conf_sum_:   u128,
+    }
+
+    impl TimeMachineBucket {
+        /// Unix timestamp of the end of the period of the bucket, excluded
+        pub fn end_time(&self) -> i64 {
+            self.start_time_.saturating_add(TIME_MACHINE_BUCKET_SECONDS)
+        }
+
+        /// The average aggregate of the bucket, `None` if its aggregates were held for no slot.
+        pub fn average(&self) -> Option<BucketAverage> {
+            if self.num_slots_ == 0 {
+                return None;
+            }
+            Some(BucketAverage {
+                start_time: self.start_time_,
+                price:      try_convert(self.price_sum_ / i128::from(self.num_slots_)).ok()?,
+                conf:       try_convert(self.conf_sum_ / u128::from(self.num_slots_)).ok()?,
+                num_slots:  self.num_slots_,
+            })
+        }
+    }
+
+    /// Average price and confidence of the aggregates of a `TimeMachineBucket`, weighted by the
+    /// number of slots they were held for. The average over several buckets is the average of
+    /// theirs weighted by their `num_slots`.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct BucketAverage {
+        /// Unix timestamp of the start of the period of the bucket
+        pub start_time: i64,
+        pub price:      i64,
+        pub conf:       u64,
+        /// See `TimeMachineBucket::num_slots_`
+        pub num_slots:  u64,
+    }
+
+    /// The last `PRICE_HISTORY_LEN` successful aggregates of a price account, the oldest ones
+    /// being overwritten first.
+    #[repr(C)]
+    #[cfg_attr(test, derive(Debug, PartialEq))]
+    #[derive(Copy, Clone, Pod, Zeroable)]
+    pub struct PriceHistory {
+        /// Number of recorded aggregates, up to `PRICE_HISTORY_LEN`
+        pub num_entries_: u8,
+        /// Index in `slots_` and `prices_` of the next aggregate
+        pub cursor_:      u8,
+        pub unused_:      [u8; 14],
+        /// Slot of each aggregate
+        pub slots_:       [u64; PRICE_HISTORY_LEN],
+        /// Price of each aggregate
+        pub prices_:      [i64; PRICE_HISTORY_LEN],
+    }
+
+    impl PriceHistory {
+        /// Record the aggregate `price` of `slot`.
+        pub fn record(&mut self, slot: u64, price: i64) {
+            let cursor = usize::from(self.cursor_) % PRICE_HISTORY_LEN;
+            self.slots_[cursor] = slot;
+            self.prices_[cursor] = price;
+            self.cursor_ = ((cursor + 1) % PRICE_HISTORY_LEN) as u8;
+            if usize::from(self.num_entries_) < PRICE_HISTORY_LEN {
+                self.num_entries_ += 1;
+            }
+        }
+
+        /// The recorded aggregates as `(slot, price)`, in no particular order.
+        #[cfg(any(test, feature = "library"))]
+        pub fn entries(&self) -> impl Iterator<Item = (u64, i64)> + '_ {
+            let num_entries = usize::from(self.num_entries_).min(PRICE_HISTORY_LEN);
+            self.slots_[..num_entries]
+                .iter()
+                .copied()
+                .zip(self.prices_[..num_entries].iter().copied())
+        }
+
+        /// Least squares slope of the recorded prices against their slots, in price units per
+        /// slot scaled by `10^PRICE_DRIFT_DECIMALS` and rounded toward zero. `None` if the
+        /// recorded aggregates don't span two slots, or if the slope doesn't fit in an `i64`.
+        #[cfg(any(test, feature = "library"))]
+        pub fn drift(&self) -> Option<i64> {
+            // The slots and prices are taken relative to one of the aggregates, so that the sums
+            // only overflow for absurd histories.
+            let (origin_slot, origin_price) = self.entries().next()?;
+            let mut n: i128 = 0;
+            let mut sum_x: i128 = 0;
+            let mut sum_y: i128 = 0;
+            let mut sum_xx: i128 = 0;
+            let mut sum_xy: i128 = 0;
+            for (slot, price) in self.entries() {
+                let x = i128::from(slot) - i128::from(origin_slot);
+                let y = i128::from(price) - i128::from(origin_price);
+                n += 1;
+                sum_x += x;
+                sum_y += y;
+                sum_xx = sum_xx.checked_add(x.checked_mul(x)?)?;
+                sum_xy = sum_xy.checked_add(x.checked_mul(y)?)?;
+            }
+
+            let denominator = n
+                .checked_mul(sum_xx)?
+                .checked_sub(sum_x.checked_mul(sum_x)?)?;
+            if denominator == 0 {
+                return None;
+            }
+            let numerator = n
+                .checked_mul(sum_xy)?
+                .checked_sub(sum_x.checked_mul(sum_y)?)?;
+            let drift = numerator
+                .checked_mul(10i128.pow(crate::c_oracle_header::PRICE_DRIFT_DECIMALS))?
+                .checked_div(denominator)?;
+            i64::try_from(drift).ok()
+        }
+    }
+
+    /// A change of the aggregate status of a price account
+    #[repr(C)]
+    #[cfg_attr(test, derive(Debug, PartialEq))]
+    #[derive(Copy, Clone, Pod, Zeroable)]
+    pub struct StatusChange {
+        /// Slot of the change
+        pub slot_:         u64,
+        /// Unix timestamp of the change
+        pub timestamp_:    i64,
+        /// Aggregate status before and after the change. They are equal if the account was
+        /// migrated from a layout without status changes, in which case `slot_` and `timestamp_`
+        /// are the ones of the last aggregation before the migration.
+        pub prev_status_:  u8,
+        pub status_:       u8,
+        /// Not part of the change: 1 if the last aggregating price update deferred the
+        /// aggregation for lack of compute units (see `AggregationDeferred`), 0 once a price
+        /// update aggregates. It is kept here for lack of room in the price account, and kept by
+        /// `record_status_change`.
+        pub agg_deferred_: u8,
+        pub unused_:       u8,
+        /// Number of status changes of the price account, including this one
+        pub num_changes_:  u32,
+    }
+
+    /// A status change in the status history, see `StatusChange`
+    #[repr(C)]
+    #[cfg_attr(test, derive(Debug, PartialEq))]
+    #[derive(Copy, Clone, Pod, Zeroable)]
+    pub struct StatusHistoryEntry {
+        pub slot_:        u64,
+        /// Unix timestamp of the change, which fits in 32 bits until 2106
+        pub timestamp_:   u32,
+        pub prev_status_: u8,
+        pub status_:      u8,
+        pub unused_:      [u8; 2],
+    }
+
+    /// The last `STATUS_HISTORY_LEN` status changes of a price account. Change number `n` (see
+    /// `StatusChange::num_changes_`) is in entry `(n - 1) % STATUS_HISTORY_LEN`, overwriting the
+    /// oldest changes first.
+    #[repr(C)]
+    #[cfg_attr(test, derive(Debug, PartialEq))]
+    #[derive(Copy, Clone, Pod, Zeroable)]
+    pub struct StatusHistory {
+        pub entries_: [StatusHistoryEntry; STATUS_HISTORY_LEN],
+    }
+
+    impl StatusHistory {
+        /// Record `change`, the last status change of the price account.
+        pub fn record(&mut self, change: &StatusChange) {
+            let index = change.num_changes_.wrapping_sub(1) as usize % STATUS_HISTORY_LEN;
+            self.entries_[index] = StatusHistoryEntry {
+                slot_:        change.slot_,
+                timestamp_:   change.timestamp_.clamp(0, i64::from(u32::MAX)) as u32,
+                prev_status_: change.prev_status_,
+                status_:      change.status_,
+                unused_:      [0; 2],
+            };
+        }
+    }
+
+    /// Aggregate computed alongside `agg_` from the quotes published at most
+    /// `FAST_AGGREGATE_MAX_LATENCY` slots before the aggregation, regardless of `min_pub_`. It
+    /// reacts faster to price moves than `agg_` but may include fewer publishers.
+    #[repr(C)]
+    #[cfg_attr(test, derive(Debug, PartialEq))]
+    #[derive(Copy, Clone, Pod, Zeroable)]
+    pub struct FastAggregate {
+        pub price_:    i64,
+        pub conf_:     u64,
+        /// Slot of the aggregation attempt
+        pub pub_slot_: u64,
+        /// Number of quotes included
+        pub num_qt_:   u32,
+        /// `PC_STATUS_TRADING` if the aggregation succeeded, `PC_STATUS_UNKNOWN` otherwise
+        pub status_:   u32,
+    }
+
+    /// Number of minutes in a day, the granularity of `MarketHours`
+    const MINUTES_PER_DAY: u16 = 24 * 60;
+
+    /// Weekly schedule of the trading sessions of a market, in UTC. Feeds of markets following
+    /// daylight saving time need their schedule updated when the clocks change.
+    #[repr(C)]
+    #[cfg_attr(test, derive(Debug, PartialEq))]
+    #[derive(Copy, Clone, Pod, Zeroable)]
+    pub struct MarketHours {
+        /// Opening and closing minute of the session of each day of the week, starting on
+        /// Monday. The market is open from the opening minute included to the closing minute
+        /// excluded, and closed all day if they are equal. A session ending at midnight (minute
+        /// 1440) continues with a session of the next day starting at minute 0. The market is
+        /// always open if it is closed every day, i.e. without a schedule.
+        pub sessions_: [[u16; 2]; 7],
+    }
+
+    impl MarketHours {
+        pub fn new(sessions: [[u16; 2]; 7]) -> Self {
+            MarketHours {
+                sessions_: sessions,
+            }
+        }
+
+        /// Whether every session opens before it closes, and closes at midnight at the latest
+        pub fn is_valid(&self) -> bool {
+            self.sessions_
+                .iter()
+                .all(|[open, close]| open <= close && *close <= MINUTES_PER_DAY)
+        }
+
+        /// Whether the market is open at the unix timestamp `timestamp`
+        pub fn is_open(&self, timestamp: i64) -> bool {
+            if self.sessions_.iter().all(|[open, close]| open == close) {
+                return true;
+            }
+            let minutes = timestamp.div_euclid(60);
+            let minute_of_day = minutes.rem_euclid(i64::from(MINUTES_PER_DAY));
+            // The unix epoch is on a Thursday
+            let weekday = (minutes.div_euclid(i64::from(MINUTES_PER_DAY)) + 3).rem_euclid(7);
+            let [open, close] = self.sessions_[weekday as usize];
+            (i64::from(open)..i64::from(close)).contains(&minute_of_day)
+        }
     }
 
     impl PythAccount for PriceAccountPythnet {
         const ACCOUNT_TYPE: u32 = PC_ACCTYPE_PRICE;
         const INITIAL_SIZE: u32 = size_of::<PriceAccountPythnet>() as u32;
+
+        fn needs_migration(&self) -> bool {
+            migrations::needs_migration(self)
+        }
+
+        fn migrate(&mut self) -> Result<(), OracleError> {
+            migrations::migrate(self)
+        }
+
+        fn is_consistent(&self) -> bool {
+            self.num_ <= PC_NUM_COMP
+        }
     }
 
-    // This struct can't overflow since :
+    // The sums of a price account updated at a single exponent can't overflow since :
     // |sum(price * slotgap)| <= sum(|price * slotgap|) <= max(|price|) * sum(slotgap) <= i64::MAX * * current_slot <= i64::MAX * u64::MAX <= i128::MAX
     // |sum(conf * slotgap)| <= sum(|conf * slotgap|) <= max(|conf|) * sum(slotgap) <= u64::MAX * current_slot <= u64::MAX * u64::MAX <= u128::MAX
     // num_down_slots <= current_slot <= u64::MAX
+    // set_exponent rescales the sums without rescaling the slots though, so `update` checks them.
     /// Contains cumulative sums of aggregative price and confidence used to compute arithmetic moving averages.
     /// Informally the TWAP between time t and time T can be computed as :
     /// `(T.price_cumulative.price - t.price_cumulative.price) / (T.agg_.pub_slot_ - t.agg_.pub_slot_)`
@@ -161,30 +1472,56 @@ mod price_pythnet {
     #[derive(Copy, Clone, Pod, Zeroable)]
     pub struct PriceCumulative {
         /// Cumulative sum of price * slot_gap
-        pub price:          i128,
+        pub price:             i128,
         /// Cumulative sum of conf * slot_gap
-        pub conf:           u128,
+        pub conf:              u128,
         /// Cumulative number of slots where the price wasn't recently updated (within
         /// PC_MAX_SEND_LATENCY slots). This field should be used to calculate the downtime
         /// as a percent of slots between two times `T` and `t` as follows:
         /// `(T.num_down_slots - t.num_down_slots) / (T.agg_.pub_slot_ - t.agg_.pub_slot_)`
-        pub num_down_slots: u64,
-        /// Padding for alignment
-        pub unused:         u64,
+        pub num_down_slots:    u64,
+        /// Number of successful aggregates of the price account, incremented on each new aggregate
+        /// and kept by `reset_price`. Keeper bots and websocket proxies detect new aggregates by
+        /// comparing it to the last value they read. On older accounts, it counts the aggregates
+        /// since the upgrade of the program that added it.
+        pub aggregate_counter: u64,
     }
 
     impl PriceCumulative {
-        pub fn update(&mut self, price: i64, conf: u64, slot_gap: u64, max_latency: u8) {
-            self.price += i128::from(price) * i128::from(slot_gap);
-            self.conf += u128::from(conf) * u128::from(slot_gap);
+        /// Add an aggregate of `price` and `conf` held for `slot_gap` slots to the sums. Returns
+        /// `ArithmeticOverflow`, leaving the sums unchanged, if one of them overflows.
+        pub fn update(
+            &mut self,
+            price: i64,
+            conf: u64,
+            slot_gap: u64,
+            max_latency: u8,
+        ) -> Result<(), OracleError> {
             // Use PC_MAX_SEND_LATENCY if max_latency is 0, otherwise use max_latency
             let latency = if max_latency == 0 {
                 u64::from(PC_MAX_SEND_LATENCY)
             } else {
                 u64::from(max_latency)
             };
+            // The products fit in 128 bits, only the sums can overflow
+            let price_sum = self
+                .price
+                .checked_add(i128::from(price) * i128::from(slot_gap))
+                .ok_or(OracleError::ArithmeticOverflow)?;
+            let conf_sum = self
+                .conf
+                .checked_add(u128::from(conf) * u128::from(slot_gap))
+                .ok_or(OracleError::ArithmeticOverflow)?;
             // This is expected to saturate at 0 most of the time (while the feed is up).
-            self.num_down_slots += slot_gap.saturating_sub(latency);
+            let num_down_slots = self
+                .num_down_slots
+                .checked_add(slot_gap.saturating_sub(latency))
+                .ok_or(OracleError::ArithmeticOverflow)?;
+
+            self.price = price_sum;
+            self.conf = conf_sum;
+            self.num_down_slots = num_down_slots;
+            Ok(())
         }
     }
 }
@@ -218,6 +1555,7 @@ pub struct PriceInfo {
     pub price_:           i64,
     pub conf_:            u64,
     pub status_:          u32,
+    /// Unused by the program, the weights of `PC_AGG_MODE_WEIGHTED` are kept in `PublisherWeights`
     pub corp_act_status_: u32,
     pub pub_slot_:        u64,
 }
@@ -243,6 +1581,65 @@ pub struct PriceEma {
     pub denom_: i64,
 }
 
+/// ln(2) at exponent -9, the exponent of the decay rates (`PD_EMA_EXPO` in the C code)
+const LN_2_E9: i64 = 693_147_180;
+
+/// Granularity of the EMAs of a price account (`twap_` and `twac_`): the weight of an aggregate
+/// in the EMAs halves every `half_life_slots` slots. The C code only takes the decay parameters
+/// derived from it (see `EmaWindow::decay`), so that a granularity is a single number.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EmaWindow {
+    pub half_life_slots: i64,
+}
+
+/// The decay parameters of the EMAs passed to `c_upd_twap`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EmaDecay {
+    /// Decay of the weights per slot, `-ln(2) / half_life_slots` at exponent `PD_EMA_EXPO`
+    pub rate:     i64,
+    /// The weights decay by `1 + rate * slot_gap`, the first order approximation of the
+    /// exponential decay, which stays accurate up to about 0.7 half-life. The EMAs restart from
+    /// the new aggregate after longer gaps.
+    pub max_diff: i64,
+}
+
+impl EmaWindow {
+    /// The granularity of the EMAs of all price accounts, about 40 minutes at 400ms per slot
+    pub const DEFAULT: EmaWindow = EmaWindow {
+        half_life_slots: 5921,
+    };
+
+    /// The decay parameters of the EMAs for this granularity. Those of the default granularity,
+    /// -117065 and 4145, are the ones the EMAs have always been computed with.
+    pub const fn decay(&self) -> EmaDecay {
+        EmaDecay {
+            rate:     -(LN_2_E9 / self.half_life_slots),
+            // Rounded up
+            max_diff: (7 * self.half_life_slots + 9) / 10,
+        }
+    }
+}
+
+/// The supported granularities of the EMAs, from 1 minute to 1 day at 400ms per slot
+pub const EMA_WINDOWS: [EmaWindow; 6] = [
+    EmaWindow {
+        half_life_slots: 150,
+    },
+    EmaWindow {
+        half_life_slots: 750,
+    },
+    EmaWindow {
+        half_life_slots: 2250,
+    },
+    EmaWindow::DEFAULT,
+    EmaWindow {
+        half_life_slots: 9000,
+    },
+    EmaWindow {
+        half_life_slots: 216_000,
+    },
+];
+
 pub trait PythOracleSerialize {
     fn to_bytes(self) -> Vec<u8>;
 }
@@ -8,7 +8,11 @@ use {
             PC_ACCTYPE_PRODUCT,
             PC_PROD_ACC_SIZE,
         },
-        deserialize::load_checked,
+        deserialize::{
+            load,
+            load_checked,
+        },
+        error::OracleError,
         instruction::CommandHeader,
         utils::{
             pyth_assert,
@@ -16,6 +20,8 @@ use {
         },
     },
     bytemuck::{
+        pod_read_unaligned,
+        try_from_bytes_mut,
         Pod,
         Zeroable,
     },
@@ -23,12 +29,23 @@ use {
         account_info::AccountInfo,
         entrypoint::ProgramResult,
         program_error::ProgramError,
-        program_memory::sol_memcpy,
+        program_memory::{
+            sol_memcpy,
+            sol_memset,
+        },
         pubkey::Pubkey,
     },
-    std::mem::size_of,
+    std::{
+        cell::RefMut,
+        mem::size_of,
+    },
 };
 
+/// The metadata of the product follows this struct, up to `PC_PROD_ACC_SIZE` bytes.
+///
+/// The account also stores the reference price account of the product (see
+/// `set_reference_price`) as a `Pubkey` right after the space of the metadata. Product accounts
+/// are resized to `ProductAccount::REFERENCE_PRICE_SIZE` when their reference price is first set.
 #[repr(C)]
 #[derive(Copy, Clone, Pod, Zeroable)]
 pub struct ProductAccount {
@@ -36,17 +53,142 @@ pub struct ProductAccount {
     pub first_price_account: Pubkey,
 }
 
+impl ProductAccount {
+    /// Offset of the reference price account, after the space of the metadata
+    pub const REFERENCE_PRICE_OFFSET: usize = PC_PROD_ACC_SIZE as usize;
+    /// Size of the product account including the reference price account
+    pub const REFERENCE_PRICE_SIZE: usize = Self::REFERENCE_PRICE_OFFSET + size_of::<Pubkey>();
+
+    /// The reference price account stored in the product account `account`, `None` if it was
+    /// never set or has been cleared.
+    pub fn read_reference_price(account: &AccountInfo) -> Result<Option<Pubkey>, ProgramError> {
+        Ok(account
+            .try_borrow_data()?
+            .get(Self::REFERENCE_PRICE_OFFSET..Self::REFERENCE_PRICE_SIZE)
+            .map(pod_read_unaligned::<Pubkey>)
+            .filter(|reference_price| *reference_price != Pubkey::default()))
+    }
+
+    /// The value of the `symbol` attribute of the product account `account`, if it has one
+    pub fn read_symbol(account: &AccountInfo) -> Result<Option<Vec<u8>>, ProgramError> {
+        let data = account.try_borrow_data()?;
+        let size = try_convert::<_, usize>(load::<ProductAccount>(&data)?.header.size)?;
+        let kv_data = data
+            .get(size_of::<ProductAccount>()..size)
+            .ok_or(OracleError::AccountTooSmall)?;
+        Ok(read_product_attributes(kv_data)?
+            .into_iter()
+            .find(|(key, _)| {
+                ProductAttributeKey::from_bytes(key) == Some(ProductAttributeKey::Symbol)
+            })
+            .map(|(_, value)| value.to_vec()))
+    }
+
+    /// Mutably borrow the reference price account stored in the product account `account`.
+    /// Fails if the account has not been resized to `REFERENCE_PRICE_SIZE` yet.
+    pub fn load_reference_price_mut<'a>(
+        account: &'a AccountInfo,
+    ) -> Result<RefMut<'a, Pubkey>, ProgramError> {
+        let data = account.try_borrow_mut_data()?;
+        pyth_assert(
+            data.len() >= Self::REFERENCE_PRICE_SIZE,
+            OracleError::AccountTooSmall.into(),
+        )?;
+
+        RefMut::filter_map(data, |data| {
+            try_from_bytes_mut(&mut data[Self::REFERENCE_PRICE_OFFSET..Self::REFERENCE_PRICE_SIZE])
+                .ok()
+        })
+        .map_err(|_| OracleError::DeserializationError.into())
+    }
+}
+
 impl PythAccount for ProductAccount {
     const ACCOUNT_TYPE: u32 = PC_ACCTYPE_PRODUCT;
     const INITIAL_SIZE: u32 = size_of::<ProductAccount>() as u32;
     const MINIMUM_SIZE: usize = PC_PROD_ACC_SIZE as usize;
 }
 
+/// Keys of the product attributes with a known meaning. Product metadata may only use these keys,
+/// unless the program is built with the `unknown-product-attributes` feature.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProductAttributeKey {
+    /// Symbol of the feed, e.g. `Crypto.BTC/USD`
+    Symbol,
+    /// Class of the asset, e.g. `Crypto`, `Equity` or `FX`
+    AssetType,
+    /// Currency in which the price is quoted, e.g. `USD`
+    QuoteCurrency,
+    /// Asset being priced, e.g. `BTC`
+    Base,
+    /// Maturity of the contract, `Spot` for spot markets
+    Tenor,
+    /// Country of the market, e.g. `US`
+    Country,
+    /// Human readable description of the product
+    Description,
+    /// Symbol of the feed without its asset type, e.g. `BTCUSD`
+    GenericSymbol,
+    /// Symbol of the product in the CMS feed
+    CmsSymbol,
+    /// Symbol of the product in the CQS feed
+    CqsSymbol,
+    /// Symbol of the product at Nasdaq
+    NasdaqSymbol,
+    /// Trading hours of the market
+    WeeklySchedule,
+}
+
+impl ProductAttributeKey {
+    pub const ALL: [ProductAttributeKey; 12] = [
+        ProductAttributeKey::Symbol,
+        ProductAttributeKey::AssetType,
+        ProductAttributeKey::QuoteCurrency,
+        ProductAttributeKey::Base,
+        ProductAttributeKey::Tenor,
+        ProductAttributeKey::Country,
+        ProductAttributeKey::Description,
+        ProductAttributeKey::GenericSymbol,
+        ProductAttributeKey::CmsSymbol,
+        ProductAttributeKey::CqsSymbol,
+        ProductAttributeKey::NasdaqSymbol,
+        ProductAttributeKey::WeeklySchedule,
+    ];
+
+    /// The key as stored in the product metadata
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ProductAttributeKey::Symbol => "symbol",
+            ProductAttributeKey::AssetType => "asset_type",
+            ProductAttributeKey::QuoteCurrency => "quote_currency",
+            ProductAttributeKey::Base => "base",
+            ProductAttributeKey::Tenor => "tenor",
+            ProductAttributeKey::Country => "country",
+            ProductAttributeKey::Description => "description",
+            ProductAttributeKey::GenericSymbol => "generic_symbol",
+            ProductAttributeKey::CmsSymbol => "cms_symbol",
+            ProductAttributeKey::CqsSymbol => "cqs_symbol",
+            ProductAttributeKey::NasdaqSymbol => "nasdaq_symbol",
+            ProductAttributeKey::WeeklySchedule => "weekly_schedule",
+        }
+    }
+
+    /// The known key stored as `key` in the product metadata, if any
+    pub fn from_bytes(key: &[u8]) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|known_key| known_key.as_str().as_bytes() == key)
+    }
+}
+
 /// Updates the metadata in a product account.
 /// The product metadata is located after the header. It is a key-value storage
 /// where keys are strings and values are strings
 /// that is represented as a byte array with the following schema :
 /// `[len(key1), ...key1, len(val1), ...val1, len(key2), ...key2, len(val2), ...val2, ...]`
+///
+/// The new metadata replaces the existing metadata entirely. It is validated before anything is
+/// written, so the account is left untouched if it is malformed or doesn't fit in the account.
 pub fn update_product_metadata(
     instruction_data: &[u8],
     product_account: &AccountInfo,
@@ -57,33 +199,39 @@ pub fn update_product_metadata(
         ProgramError::InvalidInstructionData,
     )?;
 
-    let new_data_len = instruction_data.len() - size_of::<CommandHeader>();
-    let max_data_len = try_convert::<_, usize>(PC_PROD_ACC_SIZE)? - size_of::<ProductAccount>();
-    pyth_assert(new_data_len <= max_data_len, ProgramError::InvalidArgument)?;
+    let new_data = &instruction_data[size_of::<CommandHeader>()..];
+    // The space after `PC_PROD_ACC_SIZE` holds the reference price account
+    let max_data_len = product_account
+        .data_len()
+        .min(ProductAccount::REFERENCE_PRICE_OFFSET)
+        .saturating_sub(size_of::<ProductAccount>());
+    pyth_assert(
+        new_data.len() <= max_data_len,
+        ProgramError::InvalidArgument,
+    )?;
 
-    let new_data = &instruction_data[size_of::<CommandHeader>()..instruction_data.len()];
-    let mut idx = 0;
     // new_data must be a list of key-value pairs, both of which are instances of pc_str_t.
-    // Try reading the key-value pairs to validate that new_data is properly formatted.
-    while idx < new_data.len() {
-        let key = read_pc_str_t(&new_data[idx..])?;
-        idx += key.len();
-        let value = read_pc_str_t(&new_data[idx..])?;
-        idx += value.len();
-    }
+    validate_product_attributes(&read_product_attributes(new_data)?)?;
 
-    // This assertion shouldn't ever fail, but be defensive.
-    pyth_assert(idx == new_data.len(), ProgramError::InvalidArgument)?;
+    let old_size = try_convert::<_, usize>(
+        load_checked::<ProductAccount>(product_account, version)?
+            .header
+            .size,
+    )?;
 
     {
         let mut data = product_account.try_borrow_mut_data()?;
-        // Note that this memcpy doesn't necessarily overwrite all existing data in the account.
-        // This case is handled by updating the .size_ field below.
+        let new_end = size_of::<ProductAccount>() + new_data.len();
         sol_memcpy(
-            &mut data[size_of::<ProductAccount>()..],
+            &mut data[size_of::<ProductAccount>()..new_end],
             new_data,
             new_data.len(),
         );
+        // Clear the remainder of the previous metadata if it was longer
+        let old_end = old_size.min(data.len());
+        if old_end > new_end {
+            sol_memset(&mut data[new_end..old_end], 0, old_end - new_end);
+        }
     }
 
     let mut product_data = load_checked::<ProductAccount>(product_account, version)?;
@@ -91,6 +239,46 @@ pub fn update_product_metadata(
     Ok(())
 }
 
+/// Parse the key-value pairs of product metadata, returning the bytes of each key and value
+/// without their length prefix. Fails if `kv_data` is not a list of complete key-value pairs.
+pub fn read_product_attributes(kv_data: &[u8]) -> Result<Vec<(&[u8], &[u8])>, ProgramError> {
+    let mut attributes = Vec::new();
+    let mut idx = 0;
+    while idx < kv_data.len() {
+        let key = read_pc_str_t(&kv_data[idx..])?;
+        idx += key.len();
+        let value = read_pc_str_t(&kv_data[idx..])?;
+        idx += value.len();
+        attributes.push((&key[1..], &value[1..]));
+    }
+    Ok(attributes)
+}
+
+/// Check the keys and values of product metadata against `ProductAttributeKey`. Each key may
+/// appear once, and the values of the known keys must be non-empty UTF-8 strings. Unknown keys are
+/// rejected unless the program is built with the `unknown-product-attributes` feature.
+pub fn validate_product_attributes(attributes: &[(&[u8], &[u8])]) -> ProgramResult {
+    for (i, (key, value)) in attributes.iter().enumerate() {
+        pyth_assert(
+            attributes[..i]
+                .iter()
+                .all(|(previous_key, _)| previous_key != key),
+            OracleError::InvalidProductAttribute.into(),
+        )?;
+        match ProductAttributeKey::from_bytes(key) {
+            Some(_) => pyth_assert(
+                !value.is_empty() && std::str::from_utf8(value).is_ok(),
+                OracleError::InvalidProductAttribute.into(),
+            )?,
+            None => pyth_assert(
+                cfg!(feature = "unknown-product-attributes"),
+                OracleError::UnknownProductAttribute.into(),
+            )?,
+        }
+    }
+    Ok(())
+}
+
 /// Read a `pc_str_t` from the beginning of `source`. Returns a slice of `source` containing
 /// the bytes of the `pc_str_t`.
 pub fn read_pc_str_t(source: &[u8]) -> Result<&[u8], ProgramError> {
@@ -126,28 +314,16 @@ pub fn account_has_key_values(
             .header
             .size,
     )?;
-    let mut all_account_data = product_account.try_borrow_mut_data()?;
-    let kv_data = &mut all_account_data[size_of::<ProductAccount>()..account_size];
-    let mut kv_idx = 0;
-    let mut expected_idx = 0;
-
-    while kv_idx < kv_data.len() {
-        let key = read_pc_str_t(&kv_data[kv_idx..])?;
-        if key[0] != try_convert::<_, u8>(key.len())? - 1 {
-            return Ok(false);
-        }
-
-        if &key[1..] != expected[expected_idx].as_bytes() {
-            return Ok(false);
-        }
-
-        kv_idx += key.len();
-        expected_idx += 1;
-    }
-
-    if expected_idx != expected.len() {
-        return Ok(false);
-    }
+    let all_account_data = product_account.try_borrow_data()?;
+    let kv_data = &all_account_data[size_of::<ProductAccount>()..account_size];
 
-    Ok(true)
+    let strings = read_product_attributes(kv_data)?
+        .into_iter()
+        .flat_map(|(key, value)| [key, value])
+        .collect::<Vec<_>>();
+    Ok(strings.len() == expected.len()
+        && strings
+            .iter()
+            .zip(expected.iter())
+            .all(|(string, expected)| *string == expected.as_bytes()))
 }
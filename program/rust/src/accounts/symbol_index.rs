@@ -0,0 +1,156 @@
+use {
+    super::{
+        AccountHeader,
+        PythAccount,
+    },
+    crate::{
+        c_oracle_header::{
+            PC_ACCTYPE_SYMBOL_INDEX,
+            SYMBOL_INDEX_CAPACITY,
+        },
+        error::OracleError,
+    },
+    bytemuck::{
+        Pod,
+        Zeroable,
+    },
+    solana_program::{
+        hash::hash,
+        pubkey::Pubkey,
+    },
+    std::mem::size_of,
+};
+
+/// Index of the price accounts by the hash of the symbol of their product (see
+/// `SymbolIndexAccount::symbol_hash`), so that programs find the price account of a symbol in a
+/// constant number of steps. `add_price` and `del_price` maintain the index when they are given
+/// this account.
+///
+/// The index is a hash table with open addressing: the entry of a symbol hash is stored at the
+/// first free index found by linear probing from `SymbolIndexAccount::bucket`. Removals shift the
+/// following entries back, so that lookups can stop at the first free entry.
+#[repr(C)]
+#[derive(Copy, Clone, Pod, Zeroable)]
+pub struct SymbolIndexAccount {
+    /// pyth account header
+    pub header:       AccountHeader,
+    /// Number of indexed symbols
+    pub num_entries_: u32,
+    pub unused_:      u32,
+    pub entries_:     [SymbolIndexEntry; SYMBOL_INDEX_CAPACITY],
+}
+
+/// A symbol hash and its price account. The entry is free if the price account is the default
+/// `Pubkey`.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Pod, Zeroable)]
+pub struct SymbolIndexEntry {
+    pub symbol_hash_:   [u8; 32],
+    pub price_account_: Pubkey,
+}
+
+impl SymbolIndexEntry {
+    fn is_free(&self) -> bool {
+        self.price_account_ == Pubkey::default()
+    }
+}
+
+impl SymbolIndexAccount {
+    /// The SHA-256 hash of `symbol`, the value of the `symbol` attribute of a product
+    pub fn symbol_hash(symbol: &[u8]) -> [u8; 32] {
+        hash(symbol).to_bytes()
+    }
+
+    /// The index at which the probing for `symbol_hash` starts
+    pub fn bucket(symbol_hash: &[u8; 32]) -> usize {
+        let mut prefix = [0u8; 8];
+        prefix.copy_from_slice(&symbol_hash[..8]);
+        (u64::from_le_bytes(prefix) % SYMBOL_INDEX_CAPACITY as u64) as usize
+    }
+
+    /// `Ok` with the index of the entry of `symbol_hash`, or `Err` with the free index where it
+    /// would be inserted, `None` if the table is full
+    fn position(&self, symbol_hash: &[u8; 32]) -> Result<usize, Option<usize>> {
+        let bucket = Self::bucket(symbol_hash);
+        for offset in 0..SYMBOL_INDEX_CAPACITY {
+            let index = (bucket + offset) % SYMBOL_INDEX_CAPACITY;
+            let entry = &self.entries_[index];
+            if entry.is_free() {
+                return Err(Some(index));
+            }
+            if entry.symbol_hash_ == *symbol_hash {
+                return Ok(index);
+            }
+        }
+        Err(None)
+    }
+
+    /// The price account indexed under `symbol_hash`, if any
+    pub fn get(&self, symbol_hash: &[u8; 32]) -> Option<&Pubkey> {
+        self.position(symbol_hash)
+            .ok()
+            .map(|index| &self.entries_[index].price_account_)
+    }
+
+    /// Index `price_account` under `symbol_hash`. Fails if the symbol is already indexed, even
+    /// under the same price account, or if the table is full.
+    pub fn insert(
+        &mut self,
+        symbol_hash: &[u8; 32],
+        price_account: &Pubkey,
+    ) -> Result<(), OracleError> {
+        match self.position(symbol_hash) {
+            Ok(_) => Err(OracleError::SymbolAlreadyIndexed),
+            Err(None) => Err(OracleError::SymbolIndexFull),
+            Err(Some(index)) => {
+                self.entries_[index] = SymbolIndexEntry {
+                    symbol_hash_:   *symbol_hash,
+                    price_account_: *price_account,
+                };
+                self.num_entries_ += 1;
+                Ok(())
+            }
+        }
+    }
+
+    /// Remove the entry of `symbol_hash` if it is indexed under `price_account`. Returns whether
+    /// an entry was removed.
+    pub fn remove(&mut self, symbol_hash: &[u8; 32], price_account: &Pubkey) -> bool {
+        let removed = match self.position(symbol_hash) {
+            Ok(index) if self.entries_[index].price_account_ == *price_account => index,
+            _ => return false,
+        };
+
+        // Move back the entries of the probe sequence after the removed one that can't be reached
+        // through the free entry otherwise, i.e. whose bucket isn't between the free entry
+        // (excluded) and themselves
+        let mut free = removed;
+        for offset in 1..SYMBOL_INDEX_CAPACITY {
+            let index = (removed + offset) % SYMBOL_INDEX_CAPACITY;
+            let entry = self.entries_[index];
+            if entry.is_free() {
+                break;
+            }
+            let bucket = Self::bucket(&entry.symbol_hash_);
+            let distance =
+                |to: usize| (to + SYMBOL_INDEX_CAPACITY - bucket) % SYMBOL_INDEX_CAPACITY;
+            if distance(free) < distance(index) {
+                self.entries_[free] = entry;
+                free = index;
+            }
+        }
+        self.entries_[free] = SymbolIndexEntry::zeroed();
+        self.num_entries_ -= 1;
+        true
+    }
+
+    /// The indexed symbol hashes and their price accounts, in the order of the table
+    pub fn entries(&self) -> impl Iterator<Item = &SymbolIndexEntry> {
+        self.entries_.iter().filter(|entry| !entry.is_free())
+    }
+}
+
+impl PythAccount for SymbolIndexAccount {
+    const ACCOUNT_TYPE: u32 = PC_ACCTYPE_SYMBOL_INDEX;
+    const INITIAL_SIZE: u32 = size_of::<SymbolIndexAccount>() as u32;
+}
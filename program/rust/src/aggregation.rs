@@ -0,0 +1,449 @@
+//! Aggregation of the publishers' quotes into the aggregate price of a price account.
+//!
+//! This is a port of `upd_aggregate` and `price_model_core` from the C oracle code, which is kept
+//! in the native test library as a reference. The differential tests (see
+//! `tests/test_upd_aggregate_c.rs`) only cover price accounts that Rust aggregates like the
+//! default mode of the C code, with the settings added in Rust all disabled: no outlier band,
+//! price bounds, market hours, confidence ratio limit, publisher weights, unreliable or excluded
+//! quotes. For those accounts the results are bit for bit identical to the C implementation,
+//! apart from the fast aggregate (`FastAggregate`), which only exists in Rust. The C code doesn't
+//! implement these settings, so nothing is claimed for the accounts that enable them.
+//!
+//! The price model itself is the pure function `aggregate`, which only depends on the quotes and
+//! the configuration of the price account. It lives in the `no_std` crate `pyth_oracle_core`,
+//! along with the aggregation algorithms, so that off-chain tools and light clients predict or
+//! check the aggregate of a price account with the code of the program. The aggregation mode of
+//! the price account selects the algorithm that combines the included quotes (see
+//! `aggregation_algorithm`), the C implementation only has the default mode.
+
+pub use pyth_oracle_core::aggregation::{
+    aggregate,
+    aggregation_algorithm,
+    price_model,
+    AggParams,
+    Aggregate,
+    AggregationAlgorithm,
+    Median,
+    MidOfQuotes,
+    Quote,
+    TrimmedMean,
+    WeightedMedian,
+    FAST_AGGREGATE_MAX_LATENCY,
+};
+use {
+    crate::{
+        accounts::{
+            DerivedPriceAccount,
+            PriceAccount,
+            PriceInfo,
+            PublisherPruning,
+            PublisherStats,
+        },
+        c_oracle_header::{
+            PC_NUM_COMP,
+            PC_STATUS_DEGRADED,
+            PC_STATUS_TRADING,
+            PC_STATUS_UNKNOWN,
+        },
+    },
+    pyth_oracle_core::aggregation::quote_weights,
+};
+
+/// With aggregation rotation (see `agg_rotation_`), publishers that updated their price in the
+/// last `AGG_ROTATION_ACTIVE_SLOTS` slots take turns aggregating
+pub const AGG_ROTATION_ACTIVE_SLOTS: u64 = 10;
+
+/// With aggregation rotation, any publisher can aggregate once the price account could have been
+/// aggregated for `AGG_ROTATION_GRACE_SLOTS` slots (see `is_aggregating_update`), so that a
+/// missing leader delays the aggregation by at most that many slots
+pub const AGG_ROTATION_GRACE_SLOTS: u64 = 1;
+
+/// Compute units that an aggregating price update may consume with two components and with
+/// `PC_NUM_COMP` components. These are estimated ceilings rather than measured costs, which
+/// `test_compute_units` checks the aggregations stay below. They leave room for the CPIs to the
+/// message buffer and Wormhole programs in the 200k compute units of an instruction.
+pub const AGGREGATION_MIN_COMPUTE_UNITS: u32 = 40_000;
+pub const AGGREGATION_MAX_COMPUTE_UNITS: u32 = 150_000;
+
+/// The quote of `info`, without a weight (see `PublisherWeights`), publish time or exclusion
+impl From<&PriceInfo> for Quote {
+    fn from(info: &PriceInfo) -> Self {
+        Quote {
+            price:    info.price_,
+            conf:     info.conf_,
+            status:   info.status_,
+            pub_slot: info.pub_slot_,
+            weight:   0,
+            pub_time: 0,
+            excluded: false,
+        }
+    }
+}
+
+/// Update the aggregate price of `price_account` for `slot` from the latest quote of every
+/// publisher. Returns true if the aggregation succeeded, in which case the aggregate status is
+/// `PC_STATUS_TRADING`, or `PC_STATUS_DEGRADED` if the aggregate is degraded (see `aggregate`).
+///
+/// Quotes outside of the price bounds or the outlier band of the price account, if any, are
+/// excluded (see `quote_weights`). The fast aggregate (`fast_agg_`) is updated as well, whether or
+/// not the aggregation succeeds. Both fail if `timestamp` is outside of the market hours of the
+/// price account (see `MarketHours`), or if their confidence is too wide for the max confidence
+/// ratio of the price account (see `exceeds_max_conf_ratio`).
+///
+/// `weights` are the weights of the publishers of the components in the weighted aggregation
+/// mode (see `PublisherWeights::component_weights`).
+pub fn upd_aggregate_with_weights(
+    price_account: &mut PriceAccount,
+    weights: &[u32; PC_NUM_COMP as usize],
+    slot: u64,
+    timestamp: i64,
+) -> bool {
+    // Update the value of the previous price, if the previous aggregation succeeded.
+    if is_aggregated_status(price_account.agg_.status_) {
+        price_account.prev_slot_ = price_account.agg_.pub_slot_;
+        price_account.prev_price_ = price_account.agg_.price_;
+        price_account.prev_conf_ = price_account.agg_.conf_;
+        price_account.prev_timestamp_ = price_account.timestamp_;
+    }
+
+    // Update aggregate details ready for next slot
+    price_account.valid_slot_ = price_account.agg_.pub_slot_;
+    price_account.agg_.pub_slot_ = slot;
+    price_account.timestamp_ = timestamp;
+
+    // Copy contributing prices to aggregate snapshot
+    let num_components = price_account.num_.min(PC_NUM_COMP) as usize;
+    for component in price_account.comp_[..num_components].iter_mut() {
+        component.agg_ = component.latest_;
+    }
+
+    let (mut quotes, num_quotes) = snapshot_quotes(price_account);
+    for (quote, weight) in quotes.iter_mut().zip(weights.iter()) {
+        quote.weight = *weight;
+    }
+    let mut result = aggregate(&quotes[..num_quotes], slot, &agg_params(price_account));
+    if !price_account.market_hours_.is_open(timestamp) {
+        result.price_conf = None;
+        result.fast_price_conf = None;
+    }
+    let max_conf_ratio = price_account.max_conf_ratio_;
+    let is_narrow =
+        |(price, conf): &(i64, u64)| !exceeds_max_conf_ratio(*price, *conf, max_conf_ratio);
+    result.price_conf = result.price_conf.filter(is_narrow);
+    result.fast_price_conf = result.fast_price_conf.filter(is_narrow);
+
+    let fast_agg = &mut price_account.fast_agg_;
+    fast_agg.pub_slot_ = slot;
+    fast_agg.num_qt_ = result.fast_num_qt;
+    match result.fast_price_conf {
+        Some((price, conf)) => {
+            fast_agg.status_ = PC_STATUS_TRADING;
+            fast_agg.price_ = price;
+            fast_agg.conf_ = conf;
+        }
+        None => fast_agg.status_ = PC_STATUS_UNKNOWN,
+    }
+
+    price_account.num_qt_ = result.num_qt;
+    let (price, conf) = match result.price_conf {
+        Some(price_conf) => price_conf,
+        None => {
+            price_account.agg_.status_ = PC_STATUS_UNKNOWN;
+            return false;
+        }
+    };
+
+    // Update status and publish slot of last successful aggregation
+    price_account.agg_.status_ = if result.degraded {
+        PC_STATUS_DEGRADED
+    } else {
+        PC_STATUS_TRADING
+    };
+    price_account.last_slot_ = slot;
+    price_account.agg_.price_ = price;
+    price_account.agg_.conf_ = conf;
+    price_account.agg_pub_time_ = result.pub_time;
+
+    true
+}
+
+/// `upd_aggregate_with_weights` without publisher weights
+#[cfg(any(test, feature = "library"))]
+pub fn upd_aggregate(price_account: &mut PriceAccount, slot: u64, timestamp: i64) -> bool {
+    upd_aggregate_with_weights(price_account, &[0; PC_NUM_COMP as usize], slot, timestamp)
+}
+
+/// Whether the confidence `conf` of an aggregate is more than `max_conf_ratio` basis points of its
+/// price `price`, in which case the aggregate is published with status `PC_STATUS_UNKNOWN`. There
+/// is no limit if `max_conf_ratio` is 0. An aggregate price of 0 exceeds any limit, unless its
+/// confidence is 0 as well.
+pub fn exceeds_max_conf_ratio(price: i64, conf: u64, max_conf_ratio: u32) -> bool {
+    // Compare conf / |price| to max_conf_ratio / 10^4 without dividing
+    max_conf_ratio != 0
+        && u128::from(conf) * 10_000 > u128::from(price.unsigned_abs()) * u128::from(max_conf_ratio)
+}
+
+/// The ratio `base / quote` of two prices given as `(price, conf, expo)`, as a price and a
+/// confidence with exponent `expo`, e.g. the price of ETH in BTC from the prices of ETH and BTC in
+/// USD. The price is rounded toward 0. The confidence is the first-order bound
+/// `|ratio| * (base_conf / |base_price| + quote_conf / quote_price)`, rounded up, which holds
+/// whatever the correlation of the two prices. `None` if the quote price isn't positive or if the
+/// computation overflows.
+pub fn price_ratio(base: (i64, u64, i32), quote: (i64, u64, i32), expo: i32) -> Option<(i64, u64)> {
+    let (base_price, base_conf, base_expo) = base;
+    let (quote_price, quote_conf, quote_expo) = quote;
+    if quote_price <= 0 {
+        return None;
+    }
+    let base_price_abs = u128::from(base_price.unsigned_abs());
+    let quote_price = u128::from(quote_price.unsigned_abs());
+
+    // base / quote = ratio * 10^expo, so ratio = base_price / quote_price * 10^shift
+    let shift = i64::from(base_expo) - i64::from(quote_expo) - i64::from(expo);
+    let scale = 10u128.checked_pow(u32::try_from(shift.unsigned_abs()).ok()?)?;
+    let (numerator_scale, denominator_scale) = if shift >= 0 { (scale, 1) } else { (1, scale) };
+
+    let price = base_price_abs.checked_mul(numerator_scale)?
+        / quote_price.checked_mul(denominator_scale)?;
+    // |ratio| * (base_conf / |base_price| + quote_conf / quote_price)
+    //   = (base_conf * quote_price + |base_price| * quote_conf) / quote_price^2 * 10^shift
+    let conf_numerator = u128::from(base_conf)
+        .checked_mul(quote_price)?
+        .checked_add(base_price_abs.checked_mul(u128::from(quote_conf))?)?
+        .checked_mul(numerator_scale)?;
+    let conf_denominator = quote_price
+        .checked_mul(quote_price)?
+        .checked_mul(denominator_scale)?;
+    let conf =
+        conf_numerator / conf_denominator + u128::from(conf_numerator % conf_denominator != 0);
+
+    let price = i64::try_from(price).ok()?;
+    Some((
+        if base_price < 0 { -price } else { price },
+        u64::try_from(conf).ok()?,
+    ))
+}
+
+/// Update the aggregate of `derived_price_account` at `slot` to the ratio of the aggregates of its
+/// base and quote price accounts, `base` and `quote` (see `price_ratio`). The publish slot and
+/// time of the ratio are the earliest of those of the parent aggregates, and it is degraded if one
+/// of them is. If one of the parents has no aggregate or the ratio doesn't fit, the aggregate
+/// status is set to `PC_STATUS_UNKNOWN` and the last ratio is kept. Returns whether the aggregate
+/// was updated.
+pub fn upd_derived_aggregate(
+    derived_price_account: &mut DerivedPriceAccount,
+    base: &PriceAccount,
+    quote: &PriceAccount,
+    slot: u64,
+) -> bool {
+    let ratio =
+        if is_aggregated_status(base.agg_.status_) && is_aggregated_status(quote.agg_.status_) {
+            price_ratio(
+                (base.agg_.price_, base.agg_.conf_, base.exponent),
+                (quote.agg_.price_, quote.agg_.conf_, quote.exponent),
+                derived_price_account.exponent,
+            )
+        } else {
+            None
+        };
+    let (price, conf) = match ratio {
+        Some(ratio) => ratio,
+        None => {
+            derived_price_account.agg_.status_ = PC_STATUS_UNKNOWN;
+            return false;
+        }
+    };
+
+    derived_price_account.agg_ = PriceInfo {
+        price_:           price,
+        conf_:            conf,
+        status_:          if base.agg_.status_ == PC_STATUS_DEGRADED
+            || quote.agg_.status_ == PC_STATUS_DEGRADED
+        {
+            PC_STATUS_DEGRADED
+        } else {
+            PC_STATUS_TRADING
+        },
+        corp_act_status_: 0,
+        pub_slot_:        base.agg_.pub_slot_.min(quote.agg_.pub_slot_),
+    };
+    derived_price_account.timestamp_ = base.timestamp_.min(quote.timestamp_);
+    derived_price_account.last_slot_ = slot;
+    true
+}
+
+/// Whether `status` is the aggregate status of a successful aggregation, i.e. `PC_STATUS_TRADING`
+/// or `PC_STATUS_DEGRADED`.
+pub fn is_aggregated_status(status: u32) -> bool {
+    status == PC_STATUS_TRADING || status == PC_STATUS_DEGRADED
+}
+
+/// Whether the price update of the publisher of component `publisher_index` in `slot` aggregates
+/// `price_account` before updating the component. Only the first update of a slot aggregates, once
+/// `agg_interval_slots` slots have passed since the last aggregation (see
+/// `PriceAccount::read_agg_interval`). If the price account has aggregation rotation enabled, it
+/// also has to be an update of the aggregation leader of the slot (see `aggregation_leader`),
+/// unless the leader missed its turn.
+pub fn is_aggregating_update(
+    price_account: &PriceAccount,
+    agg_interval_slots: u64,
+    publisher_index: usize,
+    slot: u64,
+) -> bool {
+    let next_aggregation_slot = price_account
+        .agg_
+        .pub_slot_
+        .saturating_add(agg_interval_slots);
+    if slot < next_aggregation_slot {
+        return false;
+    }
+    if price_account.agg_rotation_ == 0
+        || slot >= next_aggregation_slot.saturating_add(AGG_ROTATION_GRACE_SLOTS)
+    {
+        return true;
+    }
+    aggregation_leader(price_account, slot).map_or(true, |leader| leader == publisher_index)
+}
+
+/// Compute units that a price update aggregating `price_account` may need, see
+/// `UpdPriceWithBudgetArgs::remaining_compute_units`
+pub fn aggregation_compute_units(price_account: &PriceAccount) -> u32 {
+    // Interpolated between the two ceilings, rounding up
+    let num_components = price_account.num_.clamp(2, PC_NUM_COMP);
+    let per_component =
+        (AGGREGATION_MAX_COMPUTE_UNITS - AGGREGATION_MIN_COMPUTE_UNITS + PC_NUM_COMP - 3)
+            / (PC_NUM_COMP - 2);
+    (AGGREGATION_MIN_COMPUTE_UNITS + (num_components - 2) * per_component)
+        .min(AGGREGATION_MAX_COMPUTE_UNITS)
+}
+
+/// The index of the component whose publisher aggregates `price_account` in `slot` when
+/// aggregation rotation is enabled. The leader rotates with the slot among the publishers that
+/// aren't excluded and updated their price in the last `AGG_ROTATION_ACTIVE_SLOTS` slots, so that
+/// no single publisher pays for every aggregation or chooses when it happens. `None` if there are
+/// no such publishers, in which case any publisher can aggregate.
+pub fn aggregation_leader(price_account: &PriceAccount, slot: u64) -> Option<usize> {
+    let num_components = price_account.num_.min(PC_NUM_COMP) as usize;
+    let is_active = |index: &usize| {
+        let stats = &price_account.publisher_stats_[*index];
+        stats.excluded_ == 0
+            && stats.num_updates_ > 0
+            && stats
+                .last_upd_slot_
+                .saturating_add(AGG_ROTATION_ACTIVE_SLOTS)
+                >= slot
+    };
+    let num_active = (0..num_components).filter(is_active).count() as u64;
+    if num_active == 0 {
+        return None;
+    }
+    (0..num_components)
+        .filter(is_active)
+        .nth((slot % num_active) as usize)
+}
+
+/// The aggregate snapshot quotes (`agg_`) of the components of `price_account`, with the publish
+/// times of the latest quotes of the publishers and their exclusions, along with the number of
+/// components. The quotes have no weight, which only matters to the aggregate itself.
+fn snapshot_quotes(price_account: &PriceAccount) -> ([Quote; PC_NUM_COMP as usize], usize) {
+    let num_components = price_account.num_.min(PC_NUM_COMP) as usize;
+    let mut quotes = [Quote::default(); PC_NUM_COMP as usize];
+    for ((quote, component), stats) in quotes
+        .iter_mut()
+        .zip(price_account.comp_[..num_components].iter())
+        .zip(price_account.publisher_stats_.iter())
+    {
+        *quote = Quote {
+            pub_time: stats.publish_time_,
+            excluded: stats.excluded_ != 0,
+            ..Quote::from(&component.agg_)
+        };
+    }
+    (quotes, num_components)
+}
+
+/// The configuration of the aggregation of `price_account`. The max latency is extended by the
+/// slots skipped by the cluster in the current aggregation (`skipped_slots_`).
+pub fn agg_params(price_account: &PriceAccount) -> AggParams {
+    AggParams {
+        min_pub:        price_account.min_pub_,
+        max_latency:    max_latency(price_account) + i64::from(price_account.skipped_slots_),
+        agg_mode:       price_account.agg_mode_,
+        outlier_band:   price_account.outlier_band_,
+        // prev_slot_ is only set once there has been a successful aggregation
+        prev_aggregate: if price_account.prev_slot_ != 0 {
+            Some((price_account.prev_price_, price_account.prev_conf_))
+        } else {
+            None
+        },
+        min_price:      price_account.min_price_,
+        max_price:      price_account.max_price_,
+    }
+}
+
+/// Record the aggregation round of `slot` in the statistics of every publisher. This must be
+/// called after `upd_aggregate_with_weights` for the same slot, whether or not the aggregation
+/// succeeded.
+pub fn upd_publisher_stats(price_account: &mut PriceAccount, slot: u64) {
+    let (quotes, num_quotes) = snapshot_quotes(price_account);
+    let quotes = &quotes[..num_quotes];
+    let weights = quote_weights(quotes, slot, &agg_params(price_account));
+    for ((quote, stats), weight) in quotes
+        .iter()
+        .zip(price_account.publisher_stats_.iter_mut())
+        .zip(weights.iter())
+    {
+        let latency = if *weight != 0 {
+            Some(slot.saturating_sub(quote.pub_slot))
+        } else {
+            None
+        };
+        stats.record_round(latency);
+    }
+}
+
+/// Prune or readmit the publishers of `price_account` after the aggregation round of `slot` (see
+/// `PublisherPruning`), and mark the pruned ones in their statistics so that the next rounds
+/// exclude them. This must be called after `upd_publisher_stats` for the same slot. Nothing
+/// happens if the pruning is disabled.
+pub fn upd_publisher_pruning(
+    price_account: &mut PriceAccount,
+    pruning: &mut PublisherPruning,
+    slot: u64,
+) {
+    let (prune_rounds, readmit_rounds) = (pruning.prune_rounds_, pruning.readmit_rounds_);
+    if prune_rounds == 0 {
+        return;
+    }
+
+    // Whether the quotes of the pruned publishers would have been included if they were admitted
+    let (mut quotes, num_quotes) = snapshot_quotes(price_account);
+    for (quote, stats) in quotes.iter_mut().zip(price_account.publisher_stats_.iter()) {
+        quote.excluded = stats.excluded_ & PublisherStats::EXCLUDED != 0;
+    }
+    let weights = quote_weights(&quotes[..num_quotes], slot, &agg_params(price_account));
+
+    for (index, weight) in weights[..num_quotes].iter().enumerate() {
+        let state = pruning.state_mut(index, &price_account.comp_[..num_quotes]);
+        let stats = &mut price_account.publisher_stats_[index];
+        let pruned = if stats.excluded_ & PublisherStats::EXCLUDED != 0 {
+            state.pruned_ != 0
+        } else if state.pruned_ != 0 {
+            state.record_round(*weight != 0, prune_rounds, readmit_rounds)
+        } else {
+            // The round of an admitted publisher was just recorded in its statistics
+            let included = stats.included_rounds_ & 1 != 0;
+            state.record_round(included, prune_rounds, readmit_rounds)
+        };
+        stats.excluded_ &= !PublisherStats::PRUNED;
+        if pruned {
+            stats.excluded_ |= PublisherStats::PRUNED;
+        }
+    }
+}
+
+/// Maximum latency in slots of the quotes included in the aggregate, not counting the slots
+/// skipped by the cluster (see `skipped_slots_`).
+pub fn max_latency(price_account: &PriceAccount) -> i64 {
+    pyth_oracle_core::aggregation::max_latency(price_account.max_latency_)
+}
@@ -0,0 +1,234 @@
+//! Compile-time checks that the Rust definitions shared with the C code have the layout of the C
+//! definitions that bindgen generates from `oracle.h`. The C code reads and writes the same
+//! accounts and instruction data through its own structs (see `c_upd_twap`), so a change to one
+//! side that shifts a field fails the build instead of corrupting the accounts.
+use {
+    crate::{
+        accounts::{
+            AccountHeader,
+            MappingAccount,
+            PriceAccount,
+            PriceComponent,
+            PriceEma,
+            PriceInfo,
+            ProductAccount,
+        },
+        c_oracle_header::{
+            self,
+            cmd_add_price_t,
+            cmd_add_publisher_t,
+            cmd_del_publisher_t,
+            cmd_hdr_t,
+            cmd_init_price_t,
+            cmd_set_min_pub_t,
+            cmd_upd_price_t,
+            pc_acc_t,
+            pc_ema_t,
+            pc_map_table_t,
+            pc_price_comp_t,
+            pc_price_info_t,
+            pc_price_t,
+            pc_prod_t,
+            pc_pub_key_t,
+        },
+        instruction::{
+            AddPriceArgs,
+            AddPublisherArgs,
+            CommandHeader,
+            DelPublisherArgs,
+            InitPriceArgs,
+            SetMinPubArgs,
+            UpdPriceArgs,
+        },
+        layout::offset_of,
+    },
+    solana_program::pubkey::Pubkey,
+    std::mem::{
+        align_of,
+        size_of,
+    },
+};
+
+/// Check that each field `$rust_field` of the `#[repr(C)]` Rust type `$rust` is at the offset of
+/// the field `$c_field` of the C type `$c`.
+macro_rules! assert_c_offsets {
+    ($rust:ty, $c:ty, $($rust_field:ident => $c_field:ident),+ $(,)?) => {
+        $(
+            const _: () = assert!(offset_of!($rust, $rust_field) == offset_of!($c, $c_field));
+        )+
+    };
+}
+
+/// Same as `assert_c_offsets`, also checking that `$rust` has the size of `$c` and an alignment
+/// that `$c` satisfies, so that the Rust type can be loaded wherever the C type is. The C types
+/// holding public keys are more aligned as `pc_pub_key_t` is a union with `u64`s.
+macro_rules! assert_c_layout {
+    ($rust:ty, $c:ty, $($rust_field:ident => $c_field:ident),+ $(,)?) => {
+        const _: () = assert!(size_of::<$rust>() == size_of::<$c>());
+        const _: () = assert!(align_of::<$rust>() <= align_of::<$c>());
+        assert_c_offsets!($rust, $c, $($rust_field => $c_field),+);
+    };
+}
+
+const _: () = assert!(size_of::<Pubkey>() == size_of::<pc_pub_key_t>());
+
+assert_c_layout!(
+    AccountHeader,
+    pc_acc_t,
+    magic_number => magic_,
+    version => ver_,
+    account_type => type_,
+    size => size_,
+);
+
+assert_c_layout!(
+    MappingAccount,
+    pc_map_table_t,
+    header => magic_,
+    number_of_products => num_,
+    unused_ => unused_,
+    next_mapping_account => next_,
+    products_list => prod_,
+);
+
+// The attributes of the product follow the fixed fields in both definitions
+assert_c_layout!(
+    ProductAccount,
+    pc_prod_t,
+    header => magic_,
+    first_price_account => px_acc_,
+);
+
+assert_c_layout!(
+    PriceInfo,
+    pc_price_info_t,
+    price_ => price_,
+    conf_ => conf_,
+    status_ => status_,
+    corp_act_status_ => corp_act_status_,
+    pub_slot_ => pub_slot_,
+);
+
+assert_c_layout!(
+    PriceComponent,
+    pc_price_comp_t,
+    pub_ => pub_,
+    agg_ => agg_,
+    latest_ => latest_,
+);
+
+assert_c_layout!(
+    PriceEma,
+    pc_ema_t,
+    val_ => val_,
+    numer_ => numer_,
+    denom_ => denom_,
+);
+
+// The C definition of the price account stops at the price components, the fields added by the
+// Rust code start right after them
+assert_c_offsets!(
+    PriceAccount,
+    pc_price_t,
+    header => magic_,
+    price_type => ptype_,
+    exponent => expo_,
+    num_ => num_,
+    num_qt_ => num_qt_,
+    last_slot_ => last_slot_,
+    valid_slot_ => valid_slot_,
+    twap_ => twap_,
+    twac_ => twac_,
+    timestamp_ => timestamp_,
+    min_pub_ => min_pub_,
+    message_sent_ => message_sent_,
+    max_latency_ => max_latency_,
+    agg_mode_ => agg_mode_,
+    outlier_band_ => outlier_band_,
+    skipped_slots_ => skipped_slots_,
+    layout_version_ => layout_version_,
+    product_account => prod_,
+    next_price_account => next_,
+    prev_slot_ => prev_slot_,
+    prev_price_ => prev_price_,
+    prev_conf_ => prev_conf_,
+    prev_timestamp_ => prev_timestamp_,
+    agg_ => agg_,
+    comp_ => comp_,
+);
+const _: () = assert!(offset_of!(PriceAccount, publisher_stats_) == size_of::<pc_price_t>());
+const _: () = assert!(align_of::<PriceAccount>() == align_of::<pc_price_t>());
+// 240 bytes of fixed fields followed by the `PC_NUM_COMP` price components of 96 bytes
+#[cfg(feature = "pythnet")]
+const _: () = assert!(size_of::<pc_price_t>() == 6384);
+#[cfg(feature = "solana")]
+const _: () = assert!(size_of::<pc_price_t>() == 3312);
+
+assert_c_layout!(CommandHeader, cmd_hdr_t, version => ver_, command => cmd_);
+
+assert_c_layout!(
+    AddPriceArgs,
+    cmd_add_price_t,
+    header => ver_,
+    exponent => expo_,
+    price_type => ptype_,
+);
+
+assert_c_layout!(
+    InitPriceArgs,
+    cmd_init_price_t,
+    header => ver_,
+    exponent => expo_,
+    price_type => ptype_,
+);
+
+assert_c_layout!(
+    SetMinPubArgs,
+    cmd_set_min_pub_t,
+    header => ver_,
+    minimum_publishers => min_pub_,
+);
+
+assert_c_layout!(AddPublisherArgs, cmd_add_publisher_t, header => ver_, publisher => pub_);
+
+assert_c_layout!(DelPublisherArgs, cmd_del_publisher_t, header => ver_, publisher => pub_);
+
+assert_c_layout!(
+    UpdPriceArgs,
+    cmd_upd_price_t,
+    header => ver_,
+    status => status_,
+    unused_ => unused_,
+    price => price_,
+    confidence => conf_,
+    publishing_slot => pub_slot_,
+);
+
+/// Check that the constants `$name` of `pyth_oracle_core`, which doesn't depend on the bindings,
+/// have the value of the constants of the C header.
+macro_rules! assert_c_constants {
+    ($($name:ident),+ $(,)?) => {
+        $(
+            const _: () = assert!(pyth_oracle_core::constants::$name == c_oracle_header::$name);
+        )+
+    };
+}
+
+assert_c_constants!(
+    PC_NUM_COMP,
+    PC_MAGIC,
+    PC_VERSION,
+    PC_ACCTYPE_PRICE,
+    PC_MAX_SEND_LATENCY,
+    PC_STATUS_UNKNOWN,
+    PC_STATUS_TRADING,
+    PC_STATUS_HALTED,
+    PC_STATUS_AUCTION,
+    PC_STATUS_IGNORED,
+    PC_STATUS_UNRELIABLE,
+    PC_STATUS_DEGRADED,
+    PC_AGG_MODE_DEFAULT,
+    PC_AGG_MODE_WEIGHTED,
+    PC_AGG_MODE_TRIMMED_MEAN,
+    PC_AGG_MODE_MID,
+);
@@ -9,7 +9,36 @@ include!("../codegen/bindings.rs");
 /// If ci > price / PC_MAX_CI_DIVISOR, set publisher status to unknown.
 /// (e.g., 20 means ci must be < 5% of price)
 pub const MAX_CI_DIVISOR: i64 = 3;
-/// Bound on the range of the exponent in price accounts. This number is set such that the
-/// PD-based EMA computation does not lose too much precision.
-pub const MAX_NUM_DECIMALS: i32 = 12;
+/// Maximum number of decimals of the prices in price accounts, i.e. the exponent of a price
+/// account is at least `-MAX_NUM_DECIMALS`.
+pub const MAX_NUM_DECIMALS: i32 = 18;
+/// Bound on the positive exponents of price accounts. This number is set such that the PD-based
+/// EMA computation does not lose too much precision.
+pub const MAX_EXPONENT: i32 = 12;
 pub const PRICE_ACCOUNT_DEFAULT_MIN_PUB: u8 = 20;
+/// Number of aggregation rounds covered by the per-publisher statistics of price accounts.
+pub const PUBLISHER_STATS_NUM_ROUNDS: usize = 32;
+/// Number of recent successful aggregates kept by price accounts on Pythnet to estimate the drift
+/// of the price, see `PriceHistory`.
+pub const PRICE_HISTORY_LEN: usize = 7;
+/// The price drift of a price account is in price units per slot, scaled by
+/// `10^PRICE_DRIFT_DECIMALS`.
+pub const PRICE_DRIFT_DECIMALS: u32 = 6;
+/// Number of recent status changes kept by price accounts on Pythnet, see `StatusHistory`.
+pub const STATUS_HISTORY_LEN: usize = 8;
+/// Maximum number of seconds by which the publish time of a price update can be ahead of the
+/// clock, to account for the clock drift between the publishers and the cluster.
+pub const MAX_PUBLISH_TIME_DRIFT: i64 = 10;
+/// Maximum number of programs allowed to invoke the price updates of a price account via CPI when
+/// its CPI guard is enabled.
+pub const MAX_CPI_CALLERS: usize = 4;
+/// Number of price messages kept by a message buffer account, see `MessageBufferAccount`.
+pub const MESSAGE_BUFFER_CAPACITY: usize = 128;
+/// Number of entries of the hash table of a symbol index account, see `SymbolIndexAccount`.
+pub const SYMBOL_INDEX_CAPACITY: usize = 4096;
+/// Number of component updates kept by a component log account, see `ComponentLogAccount`.
+pub const COMPONENT_LOG_CAPACITY: usize = 256;
+/// Number of buckets kept by the time machine of a price account, see `TimeMachine`.
+pub const TIME_MACHINE_NUM_BUCKETS: usize = 64;
+/// Duration of the buckets of the time machine of a price account, in seconds.
+pub const TIME_MACHINE_BUCKET_SECONDS: i64 = 60;
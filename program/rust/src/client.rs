@@ -0,0 +1,1295 @@
+//! Builders of the oracle instructions for off-chain clients. The instructions use the Borsh
+//! layout of `OracleInstruction` and the accounts documented in `OracleCommand`. The permissions
+//! account, the programdata account, the treasury and the mapping PDA are derived from `program_id`.
+
+#[cfg(feature = "wormhole")]
+use {
+    crate::wormhole::{
+        WORMHOLE_EMITTER_SEED,
+        WORMHOLE_MESSAGE_SEED,
+        WORMHOLE_PROGRAM_ID,
+    },
+    solana_program::sysvar::rent::Rent,
+};
+use {
+    crate::{
+        accounts::{
+            MappingAccount,
+            PERMISSIONS_SEED,
+            PERMISSION_GRANT_SEED,
+            TREASURY_SEED,
+        },
+        c_oracle_header::MAX_CPI_CALLERS,
+        events::AggregatePriceUpdate,
+        instruction::{
+            OracleCommand,
+            OracleInstruction,
+            PriceUpdate,
+        },
+    },
+    bytemuck::try_pod_read_unaligned,
+    solana_program::{
+        bpf_loader_upgradeable,
+        instruction::{
+            AccountMeta,
+            Instruction,
+        },
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        system_program,
+        sysvar::{
+            clock::Clock,
+            instructions,
+            SysvarId,
+        },
+    },
+    std::mem::size_of,
+};
+
+pub fn get_permissions_pubkey(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[PERMISSIONS_SEED.as_bytes()], program_id).0
+}
+
+pub fn get_treasury_pubkey(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[TREASURY_SEED.as_bytes()], program_id).0
+}
+
+/// The permission grant of `authority` for `command`, which instructions accept in place of the
+/// permissions account when `authority` is their funding account
+pub fn get_permission_grant_pubkey(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    command: OracleCommand,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            PERMISSION_GRANT_SEED.as_bytes(),
+            &authority.to_bytes(),
+            &(command as i32).to_le_bytes(),
+        ],
+        program_id,
+    )
+    .0
+}
+
+/// Accounts to append to the price updates of price accounts that charge a fee, i.e. the
+/// treasury and the system program
+pub fn fee_accounts(program_id: &Pubkey) -> [AccountMeta; 2] {
+    [
+        AccountMeta::new(get_treasury_pubkey(program_id), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ]
+}
+
+/// Append the instructions sysvar account to `instruction`, one of the co-signed commands (see
+/// `PermissionAccount::is_co_signed`), so that the program can check that another authority key
+/// signed an instruction of the same transaction in co-signing mode
+pub fn co_signed(mut instruction: Instruction) -> Instruction {
+    instruction
+        .accounts
+        .push(AccountMeta::new_readonly(instructions::id(), false));
+    instruction
+}
+
+/// The emitter of the attestations of the oracle in the Wormhole core bridge
+#[cfg(feature = "wormhole")]
+pub fn get_wormhole_emitter_pubkey(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[WORMHOLE_EMITTER_SEED.as_bytes()], program_id).0
+}
+
+/// The message account of the attestations of `price_account` in the Wormhole core bridge
+#[cfg(feature = "wormhole")]
+pub fn get_wormhole_message_pubkey(program_id: &Pubkey, price_account: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[WORMHOLE_MESSAGE_SEED.as_bytes(), &price_account.to_bytes()],
+        program_id,
+    )
+    .0
+}
+
+/// Accounts to append to the price updates of `price_account`, after the fee accounts, to post
+/// the attestations of its aggregates to the Wormhole core bridge. The accounts of the bridge are
+/// the PDAs of the core bridge program.
+#[cfg(feature = "wormhole")]
+pub fn wormhole_accounts(program_id: &Pubkey, price_account: &Pubkey) -> Vec<AccountMeta> {
+    let emitter = get_wormhole_emitter_pubkey(program_id);
+    let bridge_pda = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &WORMHOLE_PROGRAM_ID).0;
+    vec![
+        AccountMeta::new(bridge_pda(&[b"Bridge"]), false),
+        AccountMeta::new(
+            get_wormhole_message_pubkey(program_id, price_account),
+            false,
+        ),
+        AccountMeta::new_readonly(emitter, false),
+        AccountMeta::new(bridge_pda(&[b"Sequence", &emitter.to_bytes()]), false),
+        AccountMeta::new(bridge_pda(&[b"fee_collector"]), false),
+        AccountMeta::new_readonly(Clock::id(), false),
+        AccountMeta::new_readonly(Rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(WORMHOLE_PROGRAM_ID, false),
+    ]
+}
+
+pub fn get_programdata_pubkey(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[&program_id.to_bytes()], &bpf_loader_upgradeable::id()).0
+}
+
+fn instruction(
+    program_id: &Pubkey,
+    instruction: &OracleInstruction,
+    accounts: Vec<AccountMeta>,
+) -> Instruction {
+    Instruction::new_with_bytes(*program_id, &instruction.pack(), accounts)
+}
+
+/// Instruction taking the accounts of a price account configuration command, i.e. the funding
+/// account, the price account and the permissions account
+fn price_authority_instruction(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    price_account: &Pubkey,
+    oracle_instruction: &OracleInstruction,
+) -> Instruction {
+    instruction(
+        program_id,
+        oracle_instruction,
+        vec![
+            AccountMeta::new(*funding_account, true),
+            AccountMeta::new(*price_account, false),
+            AccountMeta::new_readonly(get_permissions_pubkey(program_id), false),
+        ],
+    )
+}
+
+pub fn init_mapping(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    mapping_account: &Pubkey,
+) -> Instruction {
+    instruction(
+        program_id,
+        &OracleInstruction::InitMapping,
+        vec![
+            AccountMeta::new(*funding_account, true),
+            AccountMeta::new(*mapping_account, true),
+            AccountMeta::new_readonly(get_permissions_pubkey(program_id), false),
+        ],
+    )
+}
+
+/// Create the mapping account at the mapping PDA (see `MappingAccount::find_pda`). The
+/// instruction must be sent until the PDA is initialized, i.e. three times. If `mapping_account`
+/// is given, the PDA takes over its products and it is closed.
+pub fn init_mapping_pda(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    mapping_account: Option<&Pubkey>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*funding_account, true),
+        AccountMeta::new(MappingAccount::find_pda(program_id), false),
+        AccountMeta::new_readonly(get_permissions_pubkey(program_id), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    accounts.extend(mapping_account.map(|key| AccountMeta::new(*key, false)));
+    instruction(program_id, &OracleInstruction::InitMappingPda, accounts)
+}
+
+pub fn add_mapping(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    tail_mapping_account: &Pubkey,
+    new_mapping_account: &Pubkey,
+) -> Instruction {
+    instruction(
+        program_id,
+        &OracleInstruction::AddMapping,
+        vec![
+            AccountMeta::new(*funding_account, true),
+            AccountMeta::new(*tail_mapping_account, true),
+            AccountMeta::new(*new_mapping_account, true),
+            AccountMeta::new_readonly(get_permissions_pubkey(program_id), false),
+        ],
+    )
+}
+
+pub fn add_product(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    mapping_account: &Pubkey,
+    product_account: &Pubkey,
+) -> Instruction {
+    instruction(
+        program_id,
+        &OracleInstruction::AddProduct,
+        vec![
+            AccountMeta::new(*funding_account, true),
+            AccountMeta::new(*mapping_account, false),
+            AccountMeta::new(*product_account, true),
+            AccountMeta::new_readonly(get_permissions_pubkey(program_id), false),
+        ],
+    )
+}
+
+/// Replace the metadata of `product_account` with `attributes`. Each key and value must be at
+/// most 255 bytes long.
+pub fn upd_product(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    product_account: &Pubkey,
+    attributes: &[(&str, &str)],
+) -> Instruction {
+    instruction(
+        program_id,
+        &OracleInstruction::UpdProduct {
+            attributes: attributes
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+        },
+        vec![
+            AccountMeta::new(*funding_account, true),
+            AccountMeta::new(*product_account, true),
+            AccountMeta::new_readonly(get_permissions_pubkey(program_id), false),
+        ],
+    )
+}
+
+pub fn del_product(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    mapping_account: &Pubkey,
+    product_account: &Pubkey,
+) -> Instruction {
+    instruction(
+        program_id,
+        &OracleInstruction::DelProduct,
+        vec![
+            AccountMeta::new(*funding_account, true),
+            AccountMeta::new(*mapping_account, true),
+            AccountMeta::new(*product_account, true),
+            AccountMeta::new_readonly(get_permissions_pubkey(program_id), false),
+        ],
+    )
+}
+
+pub fn add_price(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    product_account: &Pubkey,
+    price_account: &Pubkey,
+    exponent: i32,
+    price_type: u32,
+) -> Instruction {
+    instruction(
+        program_id,
+        &OracleInstruction::AddPrice {
+            exponent,
+            price_type,
+        },
+        vec![
+            AccountMeta::new(*funding_account, true),
+            AccountMeta::new(*product_account, true),
+            AccountMeta::new(*price_account, true),
+            AccountMeta::new_readonly(get_permissions_pubkey(program_id), false),
+        ],
+    )
+}
+
+pub fn init_price(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    price_account: &Pubkey,
+    exponent: i32,
+    price_type: u32,
+) -> Instruction {
+    instruction(
+        program_id,
+        &OracleInstruction::InitPrice {
+            exponent,
+            price_type,
+        },
+        vec![
+            AccountMeta::new(*funding_account, true),
+            AccountMeta::new(*price_account, true),
+            AccountMeta::new_readonly(get_permissions_pubkey(program_id), false),
+        ],
+    )
+}
+
+/// Delete `price_account` and send its lamports to `recipient`, or to the funding account if
+/// `recipient` is `None`
+pub fn del_price(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    product_account: &Pubkey,
+    price_account: &Pubkey,
+    recipient: Option<&Pubkey>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*funding_account, true),
+        AccountMeta::new(*product_account, true),
+        AccountMeta::new(*price_account, true),
+        AccountMeta::new_readonly(get_permissions_pubkey(program_id), false),
+    ];
+    if let Some(recipient) = recipient {
+        accounts.push(AccountMeta::new(*recipient, false));
+    }
+    instruction(program_id, &OracleInstruction::DelPrice, accounts)
+}
+
+/// Compress the halted `price_account` and send the lamports it no longer needs to `recipient`,
+/// or to the funding account if `recipient` is `None`
+pub fn compress_price_account(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    price_account: &Pubkey,
+    recipient: Option<&Pubkey>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*funding_account, true),
+        AccountMeta::new(*price_account, false),
+        AccountMeta::new_readonly(get_permissions_pubkey(program_id), false),
+    ];
+    if let Some(recipient) = recipient {
+        accounts.push(AccountMeta::new(*recipient, false));
+    }
+    instruction(
+        program_id,
+        &OracleInstruction::CompressPriceAccount,
+        accounts,
+    )
+}
+
+/// Remove the components without a publisher from `price_account`
+pub fn compact_components(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    price_account: &Pubkey,
+) -> Instruction {
+    price_authority_instruction(
+        program_id,
+        funding_account,
+        price_account,
+        &OracleInstruction::CompactComponents,
+    )
+}
+
+/// Set `price_account` as the reference price account of `product_account`
+pub fn set_reference_price(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    product_account: &Pubkey,
+    price_account: &Pubkey,
+) -> Instruction {
+    instruction(
+        program_id,
+        &OracleInstruction::SetReferencePrice,
+        vec![
+            AccountMeta::new(*funding_account, true),
+            AccountMeta::new(*product_account, false),
+            AccountMeta::new_readonly(*price_account, false),
+            AccountMeta::new_readonly(get_permissions_pubkey(program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn add_publisher(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    price_account: &Pubkey,
+    publisher: Pubkey,
+) -> Instruction {
+    price_authority_instruction(
+        program_id,
+        funding_account,
+        price_account,
+        &OracleInstruction::AddPublisher { publisher },
+    )
+}
+
+/// Add `publisher` to `price_account`, rejecting its price updates after `expiry_slot`
+pub fn add_publisher_with_expiry(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    price_account: &Pubkey,
+    publisher: Pubkey,
+    expiry_slot: u64,
+) -> Instruction {
+    instruction(
+        program_id,
+        &OracleInstruction::AddPublisherWithExpiry {
+            publisher,
+            expiry_slot,
+        },
+        vec![
+            AccountMeta::new(*funding_account, true),
+            AccountMeta::new(*price_account, false),
+            AccountMeta::new_readonly(get_permissions_pubkey(program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn del_publisher(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    price_account: &Pubkey,
+    publisher: Pubkey,
+) -> Instruction {
+    price_authority_instruction(
+        program_id,
+        funding_account,
+        price_account,
+        &OracleInstruction::DelPublisher { publisher },
+    )
+}
+
+/// Remove `publisher` from `price_account`, signed by the publisher itself
+pub fn resign_publisher(
+    program_id: &Pubkey,
+    publisher: &Pubkey,
+    price_account: &Pubkey,
+) -> Instruction {
+    instruction(
+        program_id,
+        &OracleInstruction::ResignPublisher,
+        vec![
+            AccountMeta::new(*publisher, true),
+            AccountMeta::new(*price_account, false),
+        ],
+    )
+}
+
+pub fn set_min_pub(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    price_account: &Pubkey,
+    minimum_publishers: u8,
+) -> Instruction {
+    price_authority_instruction(
+        program_id,
+        funding_account,
+        price_account,
+        &OracleInstruction::SetMinPub { minimum_publishers },
+    )
+}
+
+pub fn set_max_latency(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    price_account: &Pubkey,
+    max_latency: u8,
+) -> Instruction {
+    price_authority_instruction(
+        program_id,
+        funding_account,
+        price_account,
+        &OracleInstruction::SetMaxLatency { max_latency },
+    )
+}
+
+pub fn set_publisher_weight(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    price_account: &Pubkey,
+    publisher: Pubkey,
+    weight: u32,
+) -> Instruction {
+    instruction(
+        program_id,
+        &OracleInstruction::SetPublisherWeight { publisher, weight },
+        vec![
+            AccountMeta::new(*funding_account, true),
+            AccountMeta::new(*price_account, false),
+            AccountMeta::new_readonly(get_permissions_pubkey(program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Exclude `publisher` from the aggregation of `price_account`, or include it again
+pub fn set_publisher_excluded(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    price_account: &Pubkey,
+    publisher: Pubkey,
+    excluded: bool,
+) -> Instruction {
+    price_authority_instruction(
+        program_id,
+        funding_account,
+        price_account,
+        &OracleInstruction::SetPublisherExcluded {
+            publisher,
+            excluded,
+        },
+    )
+}
+
+/// Prune the publishers of `price_account` after `prune_rounds` consecutive missed aggregation
+/// rounds and readmit them after `readmit_rounds` contributing rounds, or disable the pruning if
+/// `prune_rounds` is 0
+pub fn set_publisher_pruning(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    price_account: &Pubkey,
+    prune_rounds: u8,
+    readmit_rounds: u8,
+) -> Instruction {
+    instruction(
+        program_id,
+        &OracleInstruction::SetPublisherPruning {
+            prune_rounds,
+            readmit_rounds,
+        },
+        vec![
+            AccountMeta::new(*funding_account, true),
+            AccountMeta::new(*price_account, false),
+            AccountMeta::new_readonly(get_permissions_pubkey(program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Aggregate `price_account` at most once every `agg_interval_slots` slots
+pub fn set_agg_interval(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    price_account: &Pubkey,
+    agg_interval_slots: u64,
+) -> Instruction {
+    instruction(
+        program_id,
+        &OracleInstruction::SetAggInterval { agg_interval_slots },
+        vec![
+            AccountMeta::new(*funding_account, true),
+            AccountMeta::new(*price_account, false),
+            AccountMeta::new_readonly(get_permissions_pubkey(program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Add a time machine to `price_account`, see `TimeMachine`
+pub fn init_time_machine(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    price_account: &Pubkey,
+) -> Instruction {
+    instruction(
+        program_id,
+        &OracleInstruction::InitTimeMachine,
+        vec![
+            AccountMeta::new(*funding_account, true),
+            AccountMeta::new(*price_account, false),
+            AccountMeta::new_readonly(get_permissions_pubkey(program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Enable or disable aggregation rotation on `price_account`
+pub fn set_agg_rotation(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    price_account: &Pubkey,
+    enabled: bool,
+) -> Instruction {
+    price_authority_instruction(
+        program_id,
+        funding_account,
+        price_account,
+        &OracleInstruction::SetAggRotation { enabled },
+    )
+}
+
+pub fn set_agg_mode(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    price_account: &Pubkey,
+    agg_mode: u8,
+) -> Instruction {
+    price_authority_instruction(
+        program_id,
+        funding_account,
+        price_account,
+        &OracleInstruction::SetAggMode { agg_mode },
+    )
+}
+
+pub fn set_outlier_band(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    price_account: &Pubkey,
+    outlier_band: u16,
+) -> Instruction {
+    price_authority_instruction(
+        program_id,
+        funding_account,
+        price_account,
+        &OracleInstruction::SetOutlierBand { outlier_band },
+    )
+}
+
+pub fn set_max_updates_per_slot(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    price_account: &Pubkey,
+    max_updates_per_slot: u8,
+) -> Instruction {
+    price_authority_instruction(
+        program_id,
+        funding_account,
+        price_account,
+        &OracleInstruction::SetMaxUpdatesPerSlot {
+            max_updates_per_slot,
+        },
+    )
+}
+
+pub fn set_price_bounds(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    price_account: &Pubkey,
+    min_price: i64,
+    max_price: i64,
+) -> Instruction {
+    price_authority_instruction(
+        program_id,
+        funding_account,
+        price_account,
+        &OracleInstruction::SetPriceBounds {
+            min_price,
+            max_price,
+        },
+    )
+}
+
+/// Publish the aggregates whose confidence is more than `max_conf_ratio` basis points of their
+/// price with status `PC_STATUS_UNKNOWN`, 0 to publish any aggregate
+pub fn set_max_conf_ratio(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    price_account: &Pubkey,
+    max_conf_ratio: u32,
+) -> Instruction {
+    price_authority_instruction(
+        program_id,
+        funding_account,
+        price_account,
+        &OracleInstruction::SetMaxConfRatio { max_conf_ratio },
+    )
+}
+
+pub fn set_cpi_guard(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    price_account: &Pubkey,
+    enabled: bool,
+    allowed_callers: [Pubkey; MAX_CPI_CALLERS],
+) -> Instruction {
+    price_authority_instruction(
+        program_id,
+        funding_account,
+        price_account,
+        &OracleInstruction::SetCpiGuard {
+            enabled,
+            allowed_callers,
+        },
+    )
+}
+
+pub fn set_market_hours(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    price_account: &Pubkey,
+    sessions: [[u16; 2]; 7],
+) -> Instruction {
+    price_authority_instruction(
+        program_id,
+        funding_account,
+        price_account,
+        &OracleInstruction::SetMarketHours { sessions },
+    )
+}
+
+pub fn init_message_buffer(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    message_buffer_account: &Pubkey,
+) -> Instruction {
+    instruction(
+        program_id,
+        &OracleInstruction::InitMessageBuffer,
+        vec![
+            AccountMeta::new(*funding_account, true),
+            AccountMeta::new(*message_buffer_account, true),
+            AccountMeta::new_readonly(get_permissions_pubkey(program_id), false),
+        ],
+    )
+}
+
+/// Initialize `symbol_index_account` as a symbol index, see `with_symbol_index`
+pub fn init_symbol_index(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    symbol_index_account: &Pubkey,
+) -> Instruction {
+    instruction(
+        program_id,
+        &OracleInstruction::InitSymbolIndex,
+        vec![
+            AccountMeta::new(*funding_account, true),
+            AccountMeta::new(*symbol_index_account, true),
+            AccountMeta::new_readonly(get_permissions_pubkey(program_id), false),
+        ],
+    )
+}
+
+/// Initialize `component_log_account` as the component log of `price_account`, see
+/// `with_component_log`
+pub fn init_component_log(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    component_log_account: &Pubkey,
+    price_account: &Pubkey,
+) -> Instruction {
+    instruction(
+        program_id,
+        &OracleInstruction::InitComponentLog,
+        vec![
+            AccountMeta::new(*funding_account, true),
+            AccountMeta::new(*component_log_account, true),
+            AccountMeta::new_readonly(*price_account, false),
+            AccountMeta::new_readonly(get_permissions_pubkey(program_id), false),
+        ],
+    )
+}
+
+/// Append `component_log_account` to `instruction`, a price update of the price account of the
+/// component log, so that the program logs the component update
+pub fn with_component_log(
+    mut instruction: Instruction,
+    component_log_account: &Pubkey,
+) -> Instruction {
+    instruction
+        .accounts
+        .push(AccountMeta::new(*component_log_account, false));
+    instruction
+}
+
+/// Initialize `derived_price_account` as the ratio of `base_price_account` to
+/// `quote_price_account`, at `exponent`
+pub fn init_derived_price(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    derived_price_account: &Pubkey,
+    base_price_account: &Pubkey,
+    quote_price_account: &Pubkey,
+    exponent: i32,
+) -> Instruction {
+    instruction(
+        program_id,
+        &OracleInstruction::InitDerivedPrice { exponent },
+        vec![
+            AccountMeta::new(*funding_account, true),
+            AccountMeta::new(*derived_price_account, true),
+            AccountMeta::new_readonly(*base_price_account, false),
+            AccountMeta::new_readonly(*quote_price_account, false),
+            AccountMeta::new_readonly(get_permissions_pubkey(program_id), false),
+        ],
+    )
+}
+
+/// Update the ratio of `derived_price_account` from its base and quote price accounts. Any
+/// account can pay for the transaction.
+pub fn upd_derived(
+    program_id: &Pubkey,
+    derived_price_account: &Pubkey,
+    base_price_account: &Pubkey,
+    quote_price_account: &Pubkey,
+) -> Instruction {
+    instruction(
+        program_id,
+        &OracleInstruction::UpdDerived,
+        vec![
+            AccountMeta::new(*derived_price_account, false),
+            AccountMeta::new_readonly(*base_price_account, false),
+            AccountMeta::new_readonly(*quote_price_account, false),
+            AccountMeta::new_readonly(Clock::id(), false),
+        ],
+    )
+}
+
+/// Append `symbol_index_account` to `instruction`, an `add_price` or `del_price` instruction, so
+/// that the program adds the price account to the symbol index or removes it
+pub fn with_symbol_index(
+    mut instruction: Instruction,
+    symbol_index_account: &Pubkey,
+) -> Instruction {
+    instruction
+        .accounts
+        .push(AccountMeta::new(*symbol_index_account, false));
+    instruction
+}
+
+/// Replace the key `old_publisher` of a publisher of `price_account` with `new_publisher`
+pub fn rotate_publisher(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    price_account: &Pubkey,
+    old_publisher: Pubkey,
+    new_publisher: Pubkey,
+) -> Instruction {
+    price_authority_instruction(
+        program_id,
+        funding_account,
+        price_account,
+        &OracleInstruction::RotatePublisher {
+            old_publisher,
+            new_publisher,
+        },
+    )
+}
+
+/// Charge `fee_lamports` for each price update of `price_account`, 0 for no fee. The funding
+/// account tops up the treasury to its rent exemption.
+pub fn set_fee(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    price_account: &Pubkey,
+    fee_lamports: u64,
+) -> Instruction {
+    let mut instruction = price_authority_instruction(
+        program_id,
+        funding_account,
+        price_account,
+        &OracleInstruction::SetFee { fee_lamports },
+    );
+    instruction.accounts.extend(fee_accounts(program_id));
+    instruction
+}
+
+/// Transfer `lamports` of the collected fees from the treasury to `destination_account`
+pub fn withdraw_fee(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    destination_account: &Pubkey,
+    lamports: u64,
+) -> Instruction {
+    instruction(
+        program_id,
+        &OracleInstruction::WithdrawFee { lamports },
+        vec![
+            AccountMeta::new(*funding_account, true),
+            AccountMeta::new(get_treasury_pubkey(program_id), false),
+            AccountMeta::new(*destination_account, false),
+            AccountMeta::new_readonly(get_permissions_pubkey(program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Grant `authority` the permission to run `command`
+pub fn grant_permission(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    authority: &Pubkey,
+    command: OracleCommand,
+) -> Instruction {
+    instruction(
+        program_id,
+        &OracleInstruction::GrantPermission {
+            authority: *authority,
+            command:   command as i32,
+        },
+        vec![
+            AccountMeta::new(*funding_account, true),
+            AccountMeta::new(
+                get_permission_grant_pubkey(program_id, authority, command),
+                false,
+            ),
+            AccountMeta::new_readonly(get_permissions_pubkey(program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Revoke the permission of `authority` to run `command`, the funding account gets the rent of
+/// the permission grant
+pub fn revoke_permission(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    authority: &Pubkey,
+    command: OracleCommand,
+) -> Instruction {
+    instruction(
+        program_id,
+        &OracleInstruction::RevokePermission {
+            authority: *authority,
+            command:   command as i32,
+        },
+        vec![
+            AccountMeta::new(*funding_account, true),
+            AccountMeta::new(
+                get_permission_grant_pubkey(program_id, authority, command),
+                false,
+            ),
+            AccountMeta::new_readonly(get_permissions_pubkey(program_id), false),
+        ],
+    )
+}
+
+/// Assign the next price feed index to `price_account`, the permissions account is written to
+pub fn init_price_feed_index(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    price_account: &Pubkey,
+) -> Instruction {
+    instruction(
+        program_id,
+        &OracleInstruction::InitPriceFeedIndex,
+        vec![
+            AccountMeta::new(*funding_account, true),
+            AccountMeta::new(*price_account, false),
+            AccountMeta::new(get_permissions_pubkey(program_id), false),
+        ],
+    )
+}
+
+pub fn set_exponent(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    price_account: &Pubkey,
+    exponent: i32,
+) -> Instruction {
+    price_authority_instruction(
+        program_id,
+        funding_account,
+        price_account,
+        &OracleInstruction::SetExponent { exponent },
+    )
+}
+
+pub fn halt_price(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    price_account: &Pubkey,
+) -> Instruction {
+    price_authority_instruction(
+        program_id,
+        funding_account,
+        price_account,
+        &OracleInstruction::HaltPrice,
+    )
+}
+
+pub fn resume_price(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    price_account: &Pubkey,
+) -> Instruction {
+    price_authority_instruction(
+        program_id,
+        funding_account,
+        price_account,
+        &OracleInstruction::ResumePrice,
+    )
+}
+
+/// Aggregate `price_account` at the current slot. Only accepted by devnet builds of the program.
+pub fn force_aggregate(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    price_account: &Pubkey,
+) -> Instruction {
+    instruction(
+        program_id,
+        &OracleInstruction::ForceAggregate,
+        vec![
+            AccountMeta::new(*funding_account, true),
+            AccountMeta::new(*price_account, false),
+            AccountMeta::new_readonly(Clock::id(), false),
+            AccountMeta::new_readonly(get_permissions_pubkey(program_id), false),
+        ],
+    )
+}
+
+/// Clear the prices of `price_account`. Only accepted by devnet builds of the program.
+pub fn reset_price(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    price_account: &Pubkey,
+) -> Instruction {
+    price_authority_instruction(
+        program_id,
+        funding_account,
+        price_account,
+        &OracleInstruction::ResetPrice,
+    )
+}
+
+/// Publish `update` to `price_account`. Publishers sensitive to compute units can send the same
+/// instruction in the legacy layout with `OracleInstruction::pack_legacy`.
+pub fn upd_price(
+    program_id: &Pubkey,
+    publisher: &Pubkey,
+    price_account: &Pubkey,
+    update: &PriceUpdate,
+) -> Instruction {
+    instruction(
+        program_id,
+        &OracleInstruction::UpdPrice {
+            status:          update.status,
+            price:           update.price,
+            confidence:      update.confidence,
+            publishing_slot: update.publishing_slot,
+        },
+        vec![
+            AccountMeta::new(*publisher, true),
+            AccountMeta::new(*price_account, false),
+            AccountMeta::new_readonly(Clock::id(), false),
+        ],
+    )
+}
+
+/// Publish `update` to `price_account` like `upd_price`, with `remaining_compute_units` compute
+/// units left for the instruction, so that the update defers the aggregation instead of failing
+/// if they aren't enough for it (see `UpdPriceWithBudgetArgs::remaining_compute_units`)
+pub fn upd_price_with_budget(
+    program_id: &Pubkey,
+    publisher: &Pubkey,
+    price_account: &Pubkey,
+    update: &PriceUpdate,
+    remaining_compute_units: u32,
+) -> Instruction {
+    instruction(
+        program_id,
+        &OracleInstruction::UpdPriceWithBudget {
+            status: update.status,
+            price: update.price,
+            confidence: update.confidence,
+            publishing_slot: update.publishing_slot,
+            remaining_compute_units,
+        },
+        vec![
+            AccountMeta::new(*publisher, true),
+            AccountMeta::new(*price_account, false),
+            AccountMeta::new_readonly(Clock::id(), false),
+        ],
+    )
+}
+
+/// Publish `update` to `price_account`, without failing the transaction if the update is
+/// rejected
+pub fn upd_price_no_fail_on_error(
+    program_id: &Pubkey,
+    publisher: &Pubkey,
+    price_account: &Pubkey,
+    update: &PriceUpdate,
+) -> Instruction {
+    instruction(
+        program_id,
+        &OracleInstruction::UpdPriceNoFailOnError {
+            status:          update.status,
+            price:           update.price,
+            confidence:      update.confidence,
+            publishing_slot: update.publishing_slot,
+        },
+        vec![
+            AccountMeta::new(*publisher, true),
+            AccountMeta::new(*price_account, false),
+            AccountMeta::new_readonly(Clock::id(), false),
+        ],
+    )
+}
+
+/// Publish `update` to `price_account` along with `publish_time`, the unix timestamp at which the
+/// publisher observed the price
+pub fn upd_price_with_time(
+    program_id: &Pubkey,
+    publisher: &Pubkey,
+    price_account: &Pubkey,
+    update: &PriceUpdate,
+    publish_time: i64,
+) -> Instruction {
+    instruction(
+        program_id,
+        &OracleInstruction::UpdPriceWithTime {
+            status: update.status,
+            price: update.price,
+            confidence: update.confidence,
+            publishing_slot: update.publishing_slot,
+            publish_time,
+        },
+        vec![
+            AccountMeta::new(*publisher, true),
+            AccountMeta::new(*price_account, false),
+            AccountMeta::new_readonly(Clock::id(), false),
+        ],
+    )
+}
+
+/// Publish `update` to `price_account` along with the last trade observed by the publisher, of
+/// price `trade_price` and size `trade_size` on the venue `venue_id`. A size of 0 publishes the
+/// update without a trade.
+pub fn upd_price_with_trade(
+    program_id: &Pubkey,
+    publisher: &Pubkey,
+    price_account: &Pubkey,
+    update: &PriceUpdate,
+    trade_price: i64,
+    trade_size: u64,
+    venue_id: u32,
+) -> Instruction {
+    instruction(
+        program_id,
+        &OracleInstruction::UpdPriceWithTrade {
+            status: update.status,
+            price: update.price,
+            confidence: update.confidence,
+            publishing_slot: update.publishing_slot,
+            trade_price,
+            trade_size,
+            venue_id,
+        },
+        vec![
+            AccountMeta::new(*publisher, true),
+            AccountMeta::new(*price_account, false),
+            AccountMeta::new_readonly(Clock::id(), false),
+        ],
+    )
+}
+
+/// Record that `publisher` is alive without publishing a price to `price_account`
+pub fn heartbeat(program_id: &Pubkey, publisher: &Pubkey, price_account: &Pubkey) -> Instruction {
+    instruction(
+        program_id,
+        &OracleInstruction::Heartbeat,
+        vec![
+            AccountMeta::new(*publisher, true),
+            AccountMeta::new(*price_account, false),
+            AccountMeta::new_readonly(Clock::id(), false),
+        ],
+    )
+}
+
+/// Publish each update to its price account with a single instruction
+pub fn upd_price_batch(
+    program_id: &Pubkey,
+    publisher: &Pubkey,
+    updates: &[(Pubkey, PriceUpdate)],
+) -> Instruction {
+    instruction(
+        program_id,
+        &OracleInstruction::UpdPriceBatch {
+            updates: updates.iter().map(|(_, update)| *update).collect(),
+        },
+        price_updates_accounts(publisher, updates),
+    )
+}
+
+/// Publish to several price accounts, skipping those that can't be updated. The results of the
+/// updates are returned by the program, see `parse_upd_price_scatter_results`.
+pub fn upd_price_scatter(
+    program_id: &Pubkey,
+    publisher: &Pubkey,
+    updates: &[(Pubkey, PriceUpdate)],
+) -> Instruction {
+    instruction(
+        program_id,
+        &OracleInstruction::UpdPriceScatter {
+            updates: updates.iter().map(|(_, update)| *update).collect(),
+        },
+        price_updates_accounts(publisher, updates),
+    )
+}
+
+/// Decode the return data of an `upd_price_scatter` instruction into the result of the update of
+/// each price account, in the order of the instruction. Returns `None` if `return_data` isn't a
+/// list of results.
+pub fn parse_upd_price_scatter_results(
+    return_data: &[u8],
+) -> Option<Vec<Result<(), ProgramError>>> {
+    let results = return_data.chunks_exact(size_of::<u64>());
+    if !results.remainder().is_empty() {
+        return None;
+    }
+    Some(
+        results
+            .map(
+                |result| match u64::from_le_bytes(result.try_into().unwrap()) {
+                    0 => Ok(()),
+                    error => Err(ProgramError::from(error)),
+                },
+            )
+            .collect(),
+    )
+}
+
+/// Decode the return data of an instruction that aggregated a price account, e.g. `upd_price`,
+/// into the new aggregate. Returns `None` if `return_data` isn't an aggregate.
+pub fn parse_aggregate_return_data(return_data: &[u8]) -> Option<AggregatePriceUpdate> {
+    try_pod_read_unaligned(return_data).ok()
+}
+
+/// Accounts of the price updates of `upd_price_batch` and `upd_price_scatter`
+fn price_updates_accounts(
+    publisher: &Pubkey,
+    updates: &[(Pubkey, PriceUpdate)],
+) -> Vec<AccountMeta> {
+    let mut accounts = vec![
+        AccountMeta::new(*publisher, true),
+        AccountMeta::new_readonly(Clock::id(), false),
+    ];
+    accounts.extend(
+        updates
+            .iter()
+            .map(|(price_account, _)| AccountMeta::new(*price_account, false)),
+    );
+    accounts
+}
+
+/// Set the authorities of the permissions account, signed by the upgrade authority of the program
+pub fn upd_permissions(
+    program_id: &Pubkey,
+    upgrade_authority: &Pubkey,
+    master_authority: Pubkey,
+    data_curation_authority: Pubkey,
+    security_authority: Pubkey,
+) -> Instruction {
+    instruction(
+        program_id,
+        &OracleInstruction::UpdPermissions {
+            master_authority,
+            data_curation_authority,
+            security_authority,
+        },
+        vec![
+            AccountMeta::new(*upgrade_authority, true),
+            AccountMeta::new_readonly(get_programdata_pubkey(program_id), false),
+            AccountMeta::new(get_permissions_pubkey(program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Turn the co-signing mode of the permissions account on or off
+pub fn set_co_signing(
+    program_id: &Pubkey,
+    upgrade_authority: &Pubkey,
+    enabled: bool,
+) -> Instruction {
+    instruction(
+        program_id,
+        &OracleInstruction::SetCoSigning { enabled },
+        vec![
+            AccountMeta::new(*upgrade_authority, true),
+            AccountMeta::new_readonly(get_programdata_pubkey(program_id), false),
+            AccountMeta::new(get_permissions_pubkey(program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
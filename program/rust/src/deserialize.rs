@@ -9,6 +9,9 @@ use {
         utils::pyth_assert,
     },
     bytemuck::{
+        allocation::zeroed_box,
+        bytes_of,
+        bytes_of_mut,
         try_from_bytes,
         try_from_bytes_mut,
         Pod,
@@ -23,6 +26,7 @@ use {
             RefMut,
         },
         mem::size_of,
+        ops::Deref,
     },
 };
 
@@ -49,46 +53,151 @@ pub fn load_mut<T: Pod>(data: &mut [u8]) -> Result<&mut T, OracleError> {
 }
 
 /// Get the data stored in `account` as a value of type `T`.
+/// This will fail if the account data is too short or not aligned for `T`.
 /// WARNING : Use `load_checked` to load initialized Pyth accounts
 pub fn load_account_as<'a, T: Pod>(account: &'a AccountInfo) -> Result<Ref<'a, T>, ProgramError> {
     let data = account.try_borrow_data()?;
+    pyth_assert(
+        data.len() >= size_of::<T>(),
+        OracleError::AccountTooSmall.into(),
+    )?;
 
-    Ok(Ref::map(data, |data| {
-        bytemuck::from_bytes(&data[0..size_of::<T>()])
-    }))
+    Ref::filter_map(data, |data| try_from_bytes(&data[0..size_of::<T>()]).ok())
+        .map_err(|_| OracleError::DeserializationError.into())
 }
 
 /// Mutably borrow the data in `account` as a value of type `T`.
 /// Any mutations to the returned value will be reflected in the account data.
+/// This will fail if the account data is too short or not aligned for `T`.
 /// WARNING : Use `load_checked` to load initialized Pyth accounts
 pub fn load_account_as_mut<'a, T: Pod>(
     account: &'a AccountInfo,
 ) -> Result<RefMut<'a, T>, ProgramError> {
     let data = account.try_borrow_mut_data()?;
+    pyth_assert(
+        data.len() >= size_of::<T>(),
+        OracleError::AccountTooSmall.into(),
+    )?;
 
-    Ok(RefMut::map(data, |data| {
-        bytemuck::from_bytes_mut(&mut data[0..size_of::<T>()])
-    }))
+    RefMut::filter_map(data, |data| {
+        try_from_bytes_mut(&mut data[0..size_of::<T>()]).ok()
+    })
+    .map_err(|_| OracleError::DeserializationError.into())
 }
 
+/// Load the initialized Pyth account `account` as a `T`, after checking its header (see
+/// `check_account_header`). Accounts with an older layout are migrated to the current one, which
+/// requires `account` to be writable, see `load_checked_read_only` otherwise. Inconsistent
+/// accounts (see `PythAccount::is_consistent`) are rejected.
 pub fn load_checked<'a, T: PythAccount>(
     account: &'a AccountInfo,
     version: u32,
 ) -> Result<RefMut<'a, T>, ProgramError> {
+    check_account_header::<T>(account, version)?;
+    let mut account_data = load_account_as_mut::<T>(account)?;
+    if account_data.needs_migration() {
+        pyth_assert(
+            account.is_writable,
+            OracleError::UnsupportedAccountLayout.into(),
+        )?;
+        account_data.migrate()?;
+    }
     pyth_assert(
-        account.data_len() >= T::MINIMUM_SIZE,
+        account_data.is_consistent(),
+        OracleError::CorruptedAccount.into(),
+    )?;
+    Ok(account_data)
+}
+
+/// Load the initialized Pyth account `account` like `load_checked`, along with `tail`, the data
+/// stored after the `T` in the account, in a single borrow of the account data.
+pub fn load_checked_with_tail<'a, T: PythAccount>(
+    account: &'a AccountInfo,
+    version: u32,
+) -> Result<(RefMut<'a, T>, RefMut<'a, [u8]>), ProgramError> {
+    check_account_header::<T>(account, version)?;
+    let data = account.try_borrow_mut_data()?;
+    pyth_assert(
+        data.len() >= size_of::<T>(),
         OracleError::AccountTooSmall.into(),
     )?;
 
-    {
-        let account_header = load_account_as::<AccountHeader>(account)?;
+    let (account_data, tail) = RefMut::map_split(data, |data| data.split_at_mut(size_of::<T>()));
+    let mut account_data = RefMut::filter_map(account_data, |data| try_from_bytes_mut(data).ok())
+        .map_err(|_| OracleError::DeserializationError)?;
+    if account_data.needs_migration() {
         pyth_assert(
-            account_header.magic_number == PC_MAGIC
-                && account_header.version == version
-                && account_header.account_type == T::ACCOUNT_TYPE,
-            OracleError::InvalidAccountHeader.into(),
+            account.is_writable,
+            OracleError::UnsupportedAccountLayout.into(),
         )?;
+        account_data.migrate()?;
+    }
+    pyth_assert(
+        account_data.is_consistent(),
+        OracleError::CorruptedAccount.into(),
+    )?;
+    Ok((account_data, tail))
+}
+
+/// A Pyth account loaded by `load_checked_read_only`
+pub enum ReadOnlyAccount<'a, T> {
+    /// The data of an account with the current layout
+    Borrowed(Ref<'a, T>),
+    /// A copy of the data of an account with an older layout, migrated to the current one
+    Migrated(Box<T>),
+}
+
+impl<'a, T> Deref for ReadOnlyAccount<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        match self {
+            ReadOnlyAccount::Borrowed(data) => data,
+            ReadOnlyAccount::Migrated(data) => data,
+        }
     }
+}
+
+/// Load the initialized Pyth account `account` like `load_checked`, without writing to it. An
+/// account with an older layout can't be migrated then, it is read as a copy migrated to the
+/// current layout, on the heap since price accounts don't fit on the stack.
+pub fn load_checked_read_only<'a, T: PythAccount>(
+    account: &'a AccountInfo,
+    version: u32,
+) -> Result<ReadOnlyAccount<'a, T>, ProgramError> {
+    check_account_header::<T>(account, version)?;
+    let account_data = load_account_as::<T>(account)?;
+    let account_data = if account_data.needs_migration() {
+        let mut migrated = zeroed_box::<T>();
+        bytes_of_mut(migrated.as_mut()).copy_from_slice(bytes_of(&*account_data));
+        migrated.migrate()?;
+        ReadOnlyAccount::Migrated(migrated)
+    } else {
+        ReadOnlyAccount::Borrowed(account_data)
+    };
+    pyth_assert(
+        account_data.is_consistent(),
+        OracleError::CorruptedAccount.into(),
+    )?;
+    Ok(account_data)
+}
+
+/// Check that `account` is large enough to hold a `T` and that its header has the Pyth magic
+/// number, the expected `version` and the account type of `T`.
+pub fn check_account_header<T: PythAccount>(
+    account: &AccountInfo,
+    version: u32,
+) -> Result<(), ProgramError> {
+    pyth_assert(
+        account.data_len() >= T::MINIMUM_SIZE,
+        OracleError::AccountTooSmall.into(),
+    )?;
 
-    load_account_as_mut::<T>(account)
+    let account_header = load_account_as::<AccountHeader>(account)?;
+    pyth_assert(
+        account_header.magic_number == PC_MAGIC
+            && account_header.version == version
+            && account_header.account_type == T::ACCOUNT_TYPE,
+        OracleError::InvalidAccountHeader.into(),
+    )
 }
@@ -1,11 +1,13 @@
 //! Error types
 use {
+    num_derive::FromPrimitive,
     solana_program::program_error::ProgramError,
     thiserror::Error,
 };
 
-/// Errors that may be returned by the oracle program
-#[derive(Clone, Debug, Eq, Error, PartialEq)]
+/// Errors that may be returned by the oracle program. The numeric values are the custom error
+/// codes seen by clients, so they must never change.
+#[derive(Clone, Debug, Eq, Error, FromPrimitive, PartialEq)]
 pub enum OracleError {
     /// Generic catch all error
     #[error("Generic")]
@@ -52,6 +54,72 @@ pub enum OracleError {
     PermissionViolation            = 619,
     #[error("NeedsSuccesfulAggregation")]
     NeedsSuccesfulAggregation      = 620,
+    #[error("PriceHalted")]
+    PriceHalted                    = 621,
+    /// The publisher already reached the maximum number of price updates in the current slot
+    #[error("TooManyPriceUpdates")]
+    TooManyPriceUpdates            = 622,
+    /// The account has a newer layout than the program supports, or an older one and is not
+    /// writable so it can't be migrated
+    #[error("UnsupportedAccountLayout")]
+    UnsupportedAccountLayout       = 623,
+    /// The publish time of a price update is too far ahead of the clock
+    #[error("PublishTimeInFuture")]
+    PublishTimeInFuture            = 624,
+    /// The price account only accepts price updates that are top-level instructions or directly
+    /// invoked by one of its allowed programs, and the update can't be checked to be one
+    #[error("UnauthorizedCpi")]
+    UnauthorizedCpi                = 625,
+    /// Every price feed index has already been assigned
+    #[error("MaxLastFeedIndexReached")]
+    MaxLastFeedIndexReached        = 626,
+    /// The price account charges a fee for price updates and the treasury and system program
+    /// accounts are missing
+    #[error("MissingFeeAccounts")]
+    MissingFeeAccounts             = 627,
+    /// The treasury doesn't hold enough lamports above its rent exemption for the withdrawal
+    #[error("InsufficientTreasuryFunds")]
+    InsufficientTreasuryFunds      = 628,
+    /// A product attribute key is not a `ProductAttributeKey`
+    #[error("UnknownProductAttribute")]
+    UnknownProductAttribute        = 629,
+    /// A product attribute is set twice, or the value of a known attribute is empty or not UTF-8
+    #[error("InvalidProductAttribute")]
+    InvalidProductAttribute        = 630,
+    /// The same account is passed in two roles of an instruction that must be distinct accounts
+    #[error("AliasedAccounts")]
+    AliasedAccounts                = 631,
+    /// The price account is not a price account of the product, or not its reference price
+    #[error("InvalidReferencePrice")]
+    InvalidReferencePrice          = 632,
+    /// A cumulative sum or another value derived from the prices of the account doesn't fit in
+    /// its type
+    #[error("ArithmeticOverflow")]
+    ArithmeticOverflow             = 633,
+    /// The co-signing mode is on and no other authority key signed the transaction
+    #[error("MissingCoSigner")]
+    MissingCoSigner                = 634,
+    /// The symbol of the product is already indexed by the symbol index account
+    #[error("SymbolAlreadyIndexed")]
+    SymbolAlreadyIndexed           = 635,
+    /// The hash table of the symbol index account is full
+    #[error("SymbolIndexFull")]
+    SymbolIndexFull                = 636,
+    /// The product has no `symbol` attribute to index its price account under
+    #[error("MissingSymbol")]
+    MissingSymbol                  = 637,
+    /// The account has a count beyond the capacity of its table, which the program never writes
+    #[error("CorruptedAccount")]
+    CorruptedAccount               = 638,
+    /// The publisher was added with an expiry slot that has passed
+    #[error("PublisherExpired")]
+    PublisherExpired               = 639,
+    /// The component log account logs the updates of another price account
+    #[error("InvalidComponentLog")]
+    InvalidComponentLog            = 640,
+    /// The price accounts are not the base and quote price accounts of the derived price account
+    #[error("InvalidDerivedPrice")]
+    InvalidDerivedPrice            = 641,
 }
 
 impl From<OracleError> for ProgramError {
@@ -0,0 +1,71 @@
+//! Structured events logged by the oracle program with `sol_log_data`.
+//!
+//! Events use the same encoding as Anchor's `emit!`: an 8 byte discriminator equal to the first 8
+//! bytes of `sha256("event:<EventName>")`, followed by the borsh serialization of the event. All
+//! event fields are little-endian integers or byte arrays laid out without padding, so the borsh
+//! serialization is simply the bytes of the `#[repr(C)]` struct.
+
+use {
+    bytemuck::{
+        bytes_of,
+        Pod,
+        Zeroable,
+    },
+    solana_program::{
+        log::sol_log_data,
+        pubkey::Pubkey,
+    },
+};
+
+pub trait Event: Pod {
+    /// First 8 bytes of `sha256("event:<EventName>")`
+    const DISCRIMINATOR: [u8; 8];
+
+    /// Discriminator followed by the serialized event.
+    fn data(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(Self::DISCRIMINATOR.len() + std::mem::size_of::<Self>());
+        data.extend_from_slice(&Self::DISCRIMINATOR);
+        data.extend_from_slice(bytes_of(self));
+        data
+    }
+
+    fn emit(&self) {
+        sol_log_data(&[&self.data()]);
+    }
+}
+
+/// Emitted every time a new aggregate price is successfully computed for a price account. No event
+/// is emitted when there are too few valid quotes for the aggregation to succeed.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Pod, Zeroable)]
+pub struct AggregatePriceUpdate {
+    pub price_account:    Pubkey,
+    pub slot:             u64,
+    pub price:            i64,
+    pub conf:             u64,
+    pub status:           u32,
+    /// Number of publishers that contributed to the aggregate
+    pub num_contributors: u32,
+}
+
+impl Event for AggregatePriceUpdate {
+    const DISCRIMINATOR: [u8; 8] = [6, 193, 153, 78, 230, 25, 136, 215];
+}
+
+/// Emitted when a price update leaves the aggregation of the slot to the next price update
+/// because too few compute units remain for it, see
+/// `UpdPriceWithBudgetArgs::remaining_compute_units`
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Pod, Zeroable)]
+pub struct AggregationDeferred {
+    pub price_account:           Pubkey,
+    pub slot:                    u64,
+    /// Compute units left for the price update, as given by the publisher
+    pub remaining_compute_units: u32,
+    /// Compute units that the aggregation may need (see `aggregation_compute_units`)
+    pub required_compute_units:  u32,
+}
+
+impl Event for AggregationDeferred {
+    const DISCRIMINATOR: [u8; 8] = [228, 66, 174, 97, 170, 61, 232, 77];
+}
@@ -0,0 +1,357 @@
+//! Deterministic fuzz harness for the oracle program.
+//!
+//! `run` decodes an arbitrary byte string into a set of accounts and a sequence of instructions,
+//! and executes the instructions natively with `process_instruction`. It panics if the program
+//! panics or if a successful instruction breaks one of the invariants of Pyth accounts:
+//! - the size of the accounts never changes;
+//! - an account holding the Pyth magic number keeps its account type, or has its header zeroed
+//!   when it is deleted;
+//! - only fresh (all zero) accounts are initialized, with the current version;
+//! - the size recorded in the header fits in the account and the number of entries of mapping and
+//!   price accounts never goes above their capacity.
+//!
+//! Each instruction runs as its own transaction: when it fails, the harness reverts every account
+//! like the runtime, and when it succeeds, the accounts left without lamports are cleared. The same
+//! input always runs the same instructions against the same accounts, so any failure found by a
+//! fuzzer can be reproduced by calling `run` on it.
+//!
+//! The input is read as:
+//! - the number of accounts (1 byte, at most `MAX_ACCOUNTS`);
+//! - for each account, a flags byte, a kind byte and the size of its data (2 bytes, little
+//!   endian), followed by its data for `ACCOUNT_RAW` accounts (see `decode_account`). The
+//!   accounts of the program are either zeroed or freshly initialized, since the program relies
+//!   on being the only one to write them;
+//! - instructions until the input runs out: the number of accounts of the instruction (1 byte),
+//!   their indices (1 byte each), the size of the instruction data (2 bytes, little endian) and
+//!   the instruction data.
+//!
+//! Cross-program invocations can't run outside of the runtime. The only ones the program makes
+//! require the program data account of the oracle or the PDA of the accumulator, which the harness
+//! never generates.
+
+use {
+    crate::{
+        accounts::{
+            AccountHeader,
+            MappingAccount,
+            PermissionAccount,
+            PriceAccount,
+            ProductAccount,
+            PythAccount,
+            PERMISSIONS_SEED,
+        },
+        c_oracle_header::{
+            PC_ACCTYPE_MAPPING,
+            PC_ACCTYPE_PRICE,
+            PC_MAGIC,
+            PC_MAP_TABLE_SIZE,
+            PC_NUM_COMP,
+            PC_VERSION,
+        },
+        processor::process_instruction,
+    },
+    bytemuck::Pod,
+    solana_program::{
+        account_info::AccountInfo,
+        clock::Epoch,
+        pubkey::Pubkey,
+        rent::Rent,
+        system_program,
+        sysvar,
+    },
+    std::mem::size_of,
+};
+
+/// Id of the oracle program in the harness
+pub const PROGRAM_ID: Pubkey = Pubkey::new_from_array([0xff; 32]);
+
+/// Maximum number of accounts of an input
+pub const MAX_ACCOUNTS: usize = 8;
+
+// Bits of the flags byte of an account
+pub const FLAG_SIGNER: u8 = 1;
+pub const FLAG_WRITABLE: u8 = 1 << 1;
+/// The owner is chosen by the two bits above `OWNER_SHIFT`
+pub const OWNER_SHIFT: u8 = 2;
+
+pub const OWNER_PROGRAM: u8 = 0;
+pub const OWNER_SYSTEM_PROGRAM: u8 = 1;
+pub const OWNER_SYSVAR: u8 = 2;
+
+// The low 4 bits of the kind byte of an account choose its data
+pub const ACCOUNT_ZEROED: u8 = 0;
+pub const ACCOUNT_RAW: u8 = 1;
+pub const ACCOUNT_MAPPING: u8 = 2;
+pub const ACCOUNT_PRODUCT: u8 = 3;
+pub const ACCOUNT_PRICE: u8 = 4;
+pub const ACCOUNT_PERMISSIONS: u8 = 5;
+
+// The high 4 bits of the kind byte of an account choose its key
+pub const KEY_PERMISSIONS: u8 = 1;
+pub const KEY_CLOCK: u8 = 2;
+pub const KEY_SLOT_HASHES: u8 = 3;
+pub const KEY_SYSTEM_PROGRAM: u8 = 4;
+
+/// Reads the input of the harness from the front
+struct Input<'a>(&'a [u8]);
+
+impl<'a> Input<'a> {
+    fn u8(&mut self) -> Option<u8> {
+        let (first, rest) = self.0.split_first()?;
+        self.0 = rest;
+        Some(*first)
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        Some(u16::from_le_bytes([self.u8()?, self.u8()?]))
+    }
+
+    /// The next `len` bytes, or all the remaining bytes if there are fewer
+    fn bytes(&mut self, len: usize) -> &'a [u8] {
+        let (bytes, rest) = self.0.split_at(len.min(self.0.len()));
+        self.0 = rest;
+        bytes
+    }
+}
+
+struct AccountState {
+    key:         Pubkey,
+    owner:       Pubkey,
+    is_signer:   bool,
+    is_writable: bool,
+    lamports:    u64,
+    data:        Vec<u8>,
+}
+
+/// Lamports and data of an account before an instruction
+struct Snapshot {
+    lamports: u64,
+    data:     Vec<u8>,
+}
+
+/// Run the instructions encoded in `input`, panicking if any of them breaks an invariant of the
+/// program. Returns the number of instructions that succeeded.
+pub fn run(input: &[u8]) -> usize {
+    let mut input = Input(input);
+    let mut states = decode_accounts(&mut input);
+    let accounts: Vec<AccountInfo> = states
+        .iter_mut()
+        .map(|state| {
+            AccountInfo::new(
+                &state.key,
+                state.is_signer,
+                state.is_writable,
+                &mut state.lamports,
+                &mut state.data,
+                &state.owner,
+                false,
+                Epoch::default(),
+            )
+        })
+        .collect();
+
+    let mut num_succeeded = 0;
+    while let Some((instruction_accounts, instruction_data)) =
+        decode_instruction(&mut input, &accounts)
+    {
+        let snapshots: Vec<Snapshot> = accounts
+            .iter()
+            .map(|account| Snapshot {
+                lamports: account.lamports(),
+                data:     account.data.borrow().to_vec(),
+            })
+            .collect();
+
+        match process_instruction(&PROGRAM_ID, &instruction_accounts, instruction_data) {
+            Ok(()) => {
+                num_succeeded += 1;
+                for (account, snapshot) in accounts.iter().zip(snapshots.iter()) {
+                    check_invariants(account, snapshot);
+                    if account.lamports() == 0 {
+                        account.data.borrow_mut().fill(0);
+                    }
+                }
+            }
+            Err(_) => {
+                for (account, snapshot) in accounts.iter().zip(snapshots.iter()) {
+                    **account.lamports.borrow_mut() = snapshot.lamports;
+                    account.data.borrow_mut().copy_from_slice(&snapshot.data);
+                }
+            }
+        }
+    }
+    num_succeeded
+}
+
+fn decode_accounts(input: &mut Input) -> Vec<AccountState> {
+    let num_accounts = usize::from(input.u8().unwrap_or(0)).min(MAX_ACCOUNTS);
+    let mut states: Vec<AccountState> = Vec::with_capacity(num_accounts);
+    for index in 0..num_accounts {
+        match decode_account(input, index, &states) {
+            Some(state) => states.push(state),
+            None => break,
+        }
+    }
+    states
+}
+
+/// Decode the account at `index`, given the accounts before it.
+fn decode_account(
+    input: &mut Input,
+    index: usize,
+    previous: &[AccountState],
+) -> Option<AccountState> {
+    let flags = input.u8()?;
+    let kind = input.u8()?;
+    let data_len = usize::from(input.u16()?);
+
+    // Each key is used by at most one account, like in a transaction
+    let unique_key = Pubkey::new_from_array([index as u8 + 1; 32]);
+    let key = match kind >> 4 {
+        KEY_PERMISSIONS => {
+            Pubkey::find_program_address(&[PERMISSIONS_SEED.as_bytes()], &PROGRAM_ID).0
+        }
+        KEY_CLOCK => sysvar::clock::id(),
+        KEY_SLOT_HASHES => sysvar::slot_hashes::id(),
+        KEY_SYSTEM_PROGRAM => system_program::id(),
+        _ => unique_key,
+    };
+    let key = if previous.iter().any(|state| state.key == key) {
+        unique_key
+    } else {
+        key
+    };
+
+    let owner = match (flags >> OWNER_SHIFT) & 0b11 {
+        OWNER_PROGRAM => PROGRAM_ID,
+        OWNER_SYSTEM_PROGRAM => system_program::id(),
+        OWNER_SYSVAR => sysvar::id(),
+        _ => Pubkey::new_from_array([0xfe; 32]),
+    };
+
+    let data = match kind & 0xf {
+        ACCOUNT_ZEROED => vec![0; data_len],
+        ACCOUNT_RAW => {
+            let mut data = input.bytes(data_len).to_vec();
+            data.resize(data_len, 0);
+            if owner == PROGRAM_ID {
+                data.fill(0);
+            }
+            data
+        }
+        ACCOUNT_MAPPING => initialized_data::<MappingAccount>(data_len),
+        ACCOUNT_PRODUCT => initialized_data::<ProductAccount>(data_len),
+        ACCOUNT_PRICE => initialized_data::<PriceAccount>(data_len),
+        ACCOUNT_PERMISSIONS => {
+            let mut data = initialized_data::<PermissionAccount>(data_len);
+            // The first account is the master authority, so that it can run the permissioned
+            // instructions
+            let master_authority = previous.first().map_or(key, |state| state.key);
+            bytemuck::from_bytes_mut::<PermissionAccount>(
+                &mut data[..size_of::<PermissionAccount>()],
+            )
+            .master_authority = master_authority;
+            data
+        }
+        _ => vec![0; data_len],
+    };
+
+    Some(AccountState {
+        key,
+        owner,
+        is_signer: flags & FLAG_SIGNER != 0,
+        is_writable: flags & FLAG_WRITABLE != 0,
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+    })
+}
+
+/// The data of a freshly initialized `T` account with at least `data_len` bytes.
+fn initialized_data<T: PythAccount>(data_len: usize) -> Vec<u8> {
+    let mut data = vec![0; data_len.max(T::MINIMUM_SIZE)];
+    let header = bytemuck::from_bytes_mut::<AccountHeader>(&mut data[..size_of::<AccountHeader>()]);
+    header.magic_number = PC_MAGIC;
+    header.version = PC_VERSION;
+    header.account_type = T::ACCOUNT_TYPE;
+    header.size = T::INITIAL_SIZE;
+    data
+}
+
+/// Decode the next instruction, returning `None` once the input runs out.
+fn decode_instruction<'a, 'b>(
+    input: &mut Input<'b>,
+    accounts: &[AccountInfo<'a>],
+) -> Option<(Vec<AccountInfo<'a>>, &'b [u8])> {
+    let num_accounts = usize::from(input.u8()?);
+    let mut instruction_accounts = Vec::with_capacity(num_accounts);
+    for _ in 0..num_accounts {
+        let index = usize::from(input.u8()?);
+        // The same account can be passed several times
+        if let Some(account) = accounts.get(index % accounts.len().max(1)) {
+            instruction_accounts.push(account.clone());
+        }
+    }
+    let data_len = usize::from(input.u16()?);
+    Some((instruction_accounts, input.bytes(data_len)))
+}
+
+/// Read a `T` at the start of `data`, if it is large enough.
+fn read<T: Pod>(data: &[u8]) -> Option<T> {
+    data.get(..size_of::<T>()).map(bytemuck::pod_read_unaligned)
+}
+
+/// Check the invariants of the program on `account` after a successful instruction.
+fn check_invariants(account: &AccountInfo, before: &Snapshot) {
+    let data = account.data.borrow();
+    assert_eq!(data.len(), before.data.len(), "account was resized");
+    if data[..] == before.data[..] {
+        return;
+    }
+
+    let header_before = read::<AccountHeader>(&before.data);
+    let had_magic = header_before.map_or(false, |header| header.magic_number == PC_MAGIC);
+    let header = match read::<AccountHeader>(&data) {
+        Some(header) if header.magic_number == PC_MAGIC => header,
+        header => {
+            assert!(
+                !had_magic
+                    || header.map_or(false, |header| bytemuck::bytes_of(&header)
+                        .iter()
+                        .all(|byte| *byte == 0)),
+                "account header was corrupted"
+            );
+            return;
+        }
+    };
+
+    match header_before {
+        Some(header_before) if had_magic => assert_eq!(
+            header.account_type, header_before.account_type,
+            "account type changed"
+        ),
+        _ => assert!(
+            before.data.iter().all(|byte| *byte == 0),
+            "initialized an account that wasn't fresh"
+        ),
+    }
+    assert_eq!(header.version, PC_VERSION, "wrong account version");
+    assert!(
+        header.size as usize <= data.len(),
+        "account size {} larger than the account",
+        header.size
+    );
+
+    match header.account_type {
+        PC_ACCTYPE_MAPPING => {
+            if let Some(mapping) = read::<MappingAccount>(&data) {
+                assert!(mapping.number_of_products <= PC_MAP_TABLE_SIZE);
+            }
+        }
+        PC_ACCTYPE_PRICE => {
+            if let Some(price) = read::<PriceAccount>(&data) {
+                assert!(price.num_ <= PC_NUM_COMP);
+            }
+        }
+        _ => {}
+    }
+}
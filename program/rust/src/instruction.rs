@@ -1,10 +1,21 @@
 use {
     crate::{
-        c_oracle_header::PC_VERSION,
+        accounts::read_product_attributes,
+        c_oracle_header::{
+            MAX_CPI_CALLERS,
+            PC_VERSION,
+        },
         deserialize::load,
         error::OracleError,
+        utils::try_convert,
+    },
+    borsh::{
+        BorshDeserialize,
+        BorshSerialize,
     },
     bytemuck::{
+        bytes_of,
+        pod_read_unaligned,
         Pod,
         Zeroable,
     },
@@ -14,6 +25,7 @@ use {
     },
     num_traits::FromPrimitive,
     solana_program::pubkey::Pubkey,
+    std::mem::size_of,
 };
 
 /// WARNING : NEW COMMANDS SHOULD BE ADDED AT THE END OF THE LIST
@@ -23,86 +35,343 @@ pub enum OracleCommand {
     /// Initialize first mapping list account
     // account[0] funding account       [signer writable]
     // account[1] mapping account       [signer writable]
-    InitMapping           = 0,
+    InitMapping            = 0,
     /// Initialize and add new mapping account
     // account[0] funding account       [signer writable]
     // account[1] tail mapping account  [signer writable]
     // account[2] new mapping account   [signer writable]
-    AddMapping            = 1,
+    AddMapping             = 1,
     /// Initialize and add new product reference data account
     // account[0] funding account       [signer writable]
     // account[1] mapping account       [signer writable]
     // account[2] new product account   [signer writable]
-    AddProduct            = 2,
+    AddProduct             = 2,
     /// Update product account
     // account[0] funding account       [signer writable]
     // account[1] product account       [signer writable]
-    UpdProduct            = 3,
+    UpdProduct             = 3,
     /// Add new price account to a product account
     // account[0] funding account       [signer writable]
     // account[1] product account       [signer writable]
     // account[2] new price account     [signer writable]
-    AddPrice              = 4,
+    AddPrice               = 4,
     /// Add publisher to symbol account
     // account[0] funding account       [signer writable]
     // account[1] price account         [signer writable]
-    AddPublisher          = 5,
+    AddPublisher           = 5,
     /// Delete publisher from symbol account
     // account[0] funding account       [signer writable]
     // account[1] price account         [signer writable]
-    DelPublisher          = 6,
+    DelPublisher           = 6,
     /// Publish component price
     // account[0] funding account       [signer writable]
     // account[1] price account         [writable]
     // account[2] sysvar_clock account  []
-    UpdPrice              = 7,
+    UpdPrice               = 7,
     /// Compute aggregate price
     // account[0] funding account       [signer writable]
     // account[1] price account         [writable]
     // account[2] sysvar_clock account  []
-    AggPrice              = 8,
+    AggPrice               = 8,
     /// (Re)initialize price account
     // account[0] funding account       [signer writable]
     // account[1] new price account     [signer writable]
-    InitPrice             = 9,
+    InitPrice              = 9,
     /// deprecated
-    InitTest              = 10,
+    InitTest               = 10,
     /// deprecated
-    UpdTest               = 11,
+    UpdTest                = 11,
     /// Set min publishers
     // account[0] funding account       [signer writable]
     // account[1] price account         [signer writable]
-    SetMinPub             = 12,
+    SetMinPub              = 12,
     /// Publish component price, never returning an error even if the update failed
     // account[0] funding account       [signer writable]
     // account[1] price account         [writable]
     // account[2] sysvar_clock account  []
-    UpdPriceNoFailOnError = 13,
+    UpdPriceNoFailOnError  = 13,
     /// Resizes a price account so that it fits the Time Machine
     // account[0] funding account       [signer writable]
     // account[1] price account         [signer writable]
     // account[2] system program        []
-    ResizePriceAccount    = 14,
+    ResizePriceAccount     = 14,
     /// Deletes a price account
     // account[0] funding account       [signer writable]
     // account[1] product account       [signer writable]
     // account[2] price account         [signer writable]
-    DelPrice              = 15,
+    // account[3] permissions account   []
+    // account[4] recipient account     [writable] (optional, defaults to the funding account)
+    DelPrice               = 15,
     /// Deletes a product account
     // key[0] funding account       [signer writable]
     // key[1] mapping account       [signer writable]
     // key[2] product account       [signer writable]
-    DelProduct            = 16,
+    DelProduct             = 16,
     /// Update authorities
     // key[0] upgrade authority         [signer writable]
     // key[1] programdata account       []
     // key[2] permissions account       [writable]
     // key[3] system program            []
-    UpdPermissions        = 17,
+    UpdPermissions         = 17,
     /// Set max latency
     // account[0] funding account       [signer writable]
     // account[1] price account         [signer writable]
-    SetMaxLatency         = 18,
+    SetMaxLatency          = 18,
+    /// Publish component prices to multiple price accounts
+    // account[0] funding account       [signer writable]
+    // account[1] sysvar_clock account  []
+    // account[2..] price accounts      [writable]
+    UpdPriceBatch          = 19,
+    /// Set the weight of a publisher for the weighted aggregation mode. Price accounts without
+    /// room for the weights are resized if it is not 0, the funding account paying for the rent
+    /// of the extra space.
+    // account[0] funding account       [signer writable]
+    // account[1] price account         [signer writable]
+    // account[2] permissions account   []
+    // account[3] system program        []
+    SetPublisherWeight     = 20,
+    /// Set aggregation mode
+    // account[0] funding account       [signer writable]
+    // account[1] price account         [signer writable]
+    SetAggMode             = 21,
+    /// Halt a price feed: set its aggregate status to halted and reject price updates
+    // account[0] funding account       [signer writable]
+    // account[1] price account         [signer writable]
+    HaltPrice              = 22,
+    /// Resume a halted price feed
+    // account[0] funding account       [signer writable]
+    // account[1] price account         [signer writable]
+    ResumePrice            = 23,
+    /// Set the outlier band of a price account
+    // account[0] funding account       [signer writable]
+    // account[1] price account         [signer writable]
+    SetOutlierBand         = 24,
+    /// Change the exponent of a price account, rescaling its stored prices
+    // account[0] funding account       [signer writable]
+    // account[1] price account         [signer writable]
+    SetExponent            = 25,
+    /// Remove the signing publisher from a price account, e.g. when it rotates its key
+    // account[0] publisher account     [signer writable]
+    // account[1] price account         [writable]
+    ResignPublisher        = 26,
+    /// Borsh-serialized `OracleInstruction`, which takes the accounts of the command of the same
+    /// name. The instruction data is a `CommandHeader` followed by the serialized instruction.
+    Borsh                  = 27,
+    /// Run the aggregation of a price account at the current slot. Only available in builds with
+    /// the `devnet` feature.
+    // account[0] funding account       [signer writable]
+    // account[1] price account         [writable]
+    // account[2] sysvar_clock account  []
+    // account[3] permissions account   []
+    ForceAggregate         = 28,
+    /// Clear the prices of a price account, keeping its configuration and publishers. Only
+    /// available in builds with the `devnet` feature.
+    // account[0] funding account       [signer writable]
+    // account[1] price account         [writable]
+    // account[2] permissions account   []
+    ResetPrice             = 29,
+    /// Set the maximum number of price updates accepted from each publisher in a slot
+    // account[0] funding account       [signer writable]
+    // account[1] price account         [signer writable]
+    SetMaxUpdatesPerSlot   = 30,
+    /// Set the bounds outside of which quotes are excluded from the aggregate
+    // account[0] funding account       [signer writable]
+    // account[1] price account         [signer writable]
+    SetPriceBounds         = 31,
+    /// Publish component price along with the time at which the publisher observed it
+    // account[0] funding account       [signer writable]
+    // account[1] price account         [writable]
+    // account[2] sysvar_clock account  []
+    UpdPriceWithTime       = 32,
+    /// Enable or disable the CPI guard of a price account and set the programs allowed to invoke
+    /// its price updates via CPI
+    // account[0] funding account       [signer writable]
+    // account[1] price account         [signer writable]
+    SetCpiGuard            = 33,
+    /// Set the weekly trading sessions of a price account, outside of which the aggregation fails
+    // account[0] funding account       [signer writable]
+    // account[1] price account         [signer writable]
+    SetMarketHours         = 34,
+    /// Assign the next price feed index to a price account that doesn't have one yet
+    // account[0] funding account       [signer writable]
+    // account[1] price account         [writable]
+    // account[2] permissions account   [writable]
+    InitPriceFeedIndex     = 35,
+    /// Initialize a message buffer account, to which price updates append their aggregates
+    // account[0] funding account       [signer writable]
+    // account[1] message buffer        [signer writable]
+    InitMessageBuffer      = 36,
+    /// Replace the key of a publisher of a price account, keeping its component and statistics
+    // account[0] funding account       [signer writable]
+    // account[1] price account         [signer writable]
+    // account[2] permissions account   []
+    RotatePublisher        = 37,
+    /// Set the fee in lamports paid into the treasury for each price update of a price account,
+    /// funding the treasury up to its rent exemption
+    // account[0] funding account       [signer writable]
+    // account[1] price account         [signer writable]
+    // account[2] permissions account   []
+    // account[3] treasury account      [writable]
+    // account[4] system program        []
+    SetFee                 = 38,
+    /// Withdraw lamports from the fees collected in the treasury
+    // account[0] funding account       [signer writable]
+    // account[1] treasury account      [writable]
+    // account[2] destination account   [writable]
+    // account[3] permissions account   []
+    // account[4] system program        []
+    WithdrawFee            = 39,
+    /// Grant a key the permission to run a command by creating its permission grant (see
+    /// `PERMISSION_GRANT_SEED`)
+    // account[0] funding account       [signer writable]
+    // account[1] permission grant      [writable]
+    // account[2] permissions account   []
+    // account[3] system program        []
+    GrantPermission        = 40,
+    /// Revoke a permission granted with `GrantPermission`, closing the permission grant
+    // account[0] funding account       [signer writable]
+    // account[1] permission grant      [writable]
+    // account[2] permissions account   []
+    RevokePermission       = 41,
+    /// Exclude a publisher from the aggregation of a price account, or include it again. The
+    /// quotes of an excluded publisher are still stored.
+    // account[0] funding account       [signer writable]
+    // account[1] price account         [signer writable]
+    // account[2] permissions account   []
+    SetPublisherExcluded   = 42,
+    /// Create the mapping account at the mapping PDA (see `MAPPING_SEED`), over several calls.
+    /// If the existing first mapping account is given, it is moved to the PDA.
+    // account[0] funding account       [signer writable]
+    // account[1] mapping PDA           [writable]
+    // account[2] permissions account   []
+    // account[3] system program        []
+    // account[4] mapping account       [writable] (optional)
+    InitMappingPda         = 43,
+    /// Publish component prices to multiple price accounts like `UpdPriceBatch`, skipping the
+    /// price accounts that can't be updated. The result of each update is sent as return data.
+    // account[0] funding account       [signer writable]
+    // account[1] sysvar_clock account  []
+    // account[2..] price accounts      [writable]
+    UpdPriceScatter        = 44,
+    /// Enable or disable aggregation rotation, which makes the publishers of a price account take
+    /// turns aggregating it
+    // account[0] funding account       [signer writable]
+    // account[1] price account         [signer writable]
+    // account[2] permissions account   []
+    SetAggRotation         = 45,
+    /// Compress a halted price account, which can't be updated afterwards, and reclaim the rent
+    /// of the space it no longer uses
+    // account[0] funding account       [signer writable]
+    // account[1] price account         [signer writable]
+    // account[2] permissions account   []
+    // account[3] recipient account     [writable] (optional)
+    CompressPriceAccount   = 46,
+    /// Set the reference price account of a product, i.e. its primary price feed among its price
+    /// accounts
+    // account[0] funding account       [signer writable]
+    // account[1] product account       [writable]
+    // account[2] price account         []
+    // account[3] permissions account   []
+    // account[4] system program        []
+    SetReferencePrice      = 47,
+    /// Publish component price along with the last trade observed by the publisher
+    // account[0] funding account       [signer writable]
+    // account[1] price account         [writable]
+    // account[2] sysvar_clock account  []
+    UpdPriceWithTrade      = 48,
+    /// Turn the co-signing mode of the permissions account on or off. In co-signing mode, the
+    /// co-signed commands must be co-signed by two distinct authority keys in the same
+    /// transaction (see `check_co_signed`).
+    // key[0] upgrade authority         [signer writable]
+    // key[1] programdata account       []
+    // key[2] permissions account       [writable]
+    // key[3] system program            []
+    SetCoSigning           = 49,
+    /// Remove the components without a publisher from a price account and zero its unused
+    /// component slots
+    // account[0] funding account       [signer writable]
+    // account[1] price account         [signer writable]
+    // account[2] permissions account   []
+    CompactComponents      = 50,
+    /// Initialize a symbol index account, which `add_price` and `del_price` maintain when they
+    /// are given it
+    // account[0] funding account       [signer writable]
+    // account[1] symbol index account  [signer writable]
+    // account[2] permissions account   []
+    InitSymbolIndex        = 51,
+    /// Add a publisher to a price account whose price updates are rejected after an expiry
+    /// slot. Price accounts without room for the expiries are resized, the funding account paying
+    /// for the rent of the extra space.
+    // account[0] funding account       [signer writable]
+    // account[1] price account         [signer writable]
+    // account[2] permissions account   []
+    // account[3] system program        []
+    AddPublisherWithExpiry = 52,
+    /// Set the max confidence ratio of a price account, above which its aggregates are published
+    /// with status `PC_STATUS_UNKNOWN`
+    // account[0] funding account       [signer writable]
+    // account[1] price account         [signer writable]
+    // account[2] permissions account   []
+    SetMaxConfRatio        = 53,
+    /// Record that a publisher is alive without publishing a price, e.g. while its market is
+    /// closed (see `PublisherStats::last_seen_slot`)
+    // account[0] funding account       [signer writable]
+    // account[1] price account         [writable]
+    // account[2] sysvar_clock account  []
+    Heartbeat              = 54,
+    /// Initialize a component log account for a price account, to which the price updates of the
+    /// price account given it append the publisher updates they accept
+    // account[0] funding account       [signer writable]
+    // account[1] component log account [signer writable]
+    // account[2] price account         []
+    // account[3] permissions account   []
+    InitComponentLog       = 55,
+    /// Initialize a derived price account, whose aggregate is the ratio of the aggregates of a
+    /// base and a quote price account
+    // account[0] funding account       [signer writable]
+    // account[1] derived price account [signer writable]
+    // account[2] base price account    []
+    // account[3] quote price account   []
+    // account[4] permissions account   []
+    InitDerivedPrice       = 56,
+    /// Update the aggregate of a derived price account to the ratio of the aggregates of its
+    /// base and quote price accounts. Anyone can send this instruction.
+    // account[0] derived price account [writable]
+    // account[1] base price account    []
+    // account[2] quote price account   []
+    // account[3] sysvar_clock account  []
+    UpdDerived             = 57,
+    /// Configure the automatic pruning of the publishers of a price account (see
+    /// `PublisherPruning`), or disable it and admit all the publishers again. Price accounts
+    /// without room for the pruning are resized, the funding account paying for the rent of the
+    /// extra space.
+    // account[0] funding account       [signer writable]
+    // account[1] price account         [signer writable]
+    // account[2] permissions account   []
+    // account[3] system program        []
+    SetPublisherPruning    = 58,
+    /// Set the minimum number of slots between two aggregations of a price account, 1 to
+    /// aggregate in every slot. Price accounts without room for the interval are resized if it is
+    /// more than one slot, the funding account paying for the rent of the extra space.
+    // account[0] funding account       [signer writable]
+    // account[1] price account         [signer writable]
+    // account[2] permissions account   []
+    // account[3] system program        []
+    SetAggInterval         = 59,
+    /// Publish component price like `UpdPrice`, along with the compute units left for the
+    /// instruction (see `UpdPriceWithBudgetArgs::remaining_compute_units`)
+    // account[0] funding account       [signer writable]
+    // account[1] price account         [writable]
+    // account[2] sysvar_clock account  []
+    UpdPriceWithBudget     = 60,
+    /// Start recording the averages of the aggregates of a price account over fixed periods (see
+    /// `TimeMachine`). The price account is resized, the funding account paying for the rent of
+    /// the extra space.
+    // account[0] funding account       [signer writable]
+    // account[1] price account         [signer writable]
+    // account[2] permissions account   []
+    // account[3] system program        []
+    InitTimeMachine        = 61,
 }
 
 #[repr(C)]
@@ -139,6 +408,15 @@ pub struct AddPublisherArgs {
 
 pub type DelPublisherArgs = AddPublisherArgs;
 
+#[repr(C)]
+#[derive(Zeroable, Pod, Copy, Clone)]
+pub struct AddPublisherWithExpiryArgs {
+    pub header:      CommandHeader,
+    pub publisher:   Pubkey,
+    /// Last slot at which the price updates of the publisher are accepted
+    pub expiry_slot: u64,
+}
+
 #[repr(C)]
 #[derive(Zeroable, Clone, Copy, Pod)]
 pub struct SetMinPubArgs {
@@ -158,6 +436,69 @@ pub struct UpdPriceArgs {
     pub publishing_slot: u64,
 }
 
+/// Arguments of `UpdPriceWithTime`, which start with the arguments of `UpdPrice`
+#[repr(C)]
+#[derive(Zeroable, Pod, Copy, Clone)]
+pub struct UpdPriceWithTimeArgs {
+    pub header:          CommandHeader,
+    pub status:          u32,
+    pub unused_:         u32,
+    pub price:           i64,
+    pub confidence:      u64,
+    pub publishing_slot: u64,
+    /// Unix timestamp at which the publisher observed the price
+    pub publish_time:    i64,
+}
+
+/// Arguments of `UpdPriceWithTrade`, which start with the arguments of `UpdPrice`
+#[repr(C)]
+#[derive(Zeroable, Pod, Copy, Clone)]
+pub struct UpdPriceWithTradeArgs {
+    pub header:          CommandHeader,
+    pub status:          u32,
+    pub unused_:         u32,
+    pub price:           i64,
+    pub confidence:      u64,
+    pub publishing_slot: u64,
+    /// Price of the trade at the exponent of the price account
+    pub trade_price:     i64,
+    /// Size of the trade, 0 if the update doesn't carry a trade
+    pub trade_size:      u64,
+    /// Identifier of the venue of the trade, assigned by the publisher
+    pub venue_id:        u32,
+    pub unused_2_:       u32,
+}
+
+/// Arguments of `UpdPriceWithBudget`, which start with the arguments of `UpdPrice`
+#[repr(C)]
+#[derive(Zeroable, Pod, Copy, Clone)]
+pub struct UpdPriceWithBudgetArgs {
+    pub header:                  CommandHeader,
+    pub status:                  u32,
+    pub unused_:                 u32,
+    pub price:                   i64,
+    pub confidence:              u64,
+    pub publishing_slot:         u64,
+    /// Compute units left for the instruction as estimated by the publisher, 0 if unknown. A
+    /// price update that would aggregate with fewer compute units than the aggregation may need
+    /// (see `aggregation_compute_units`) leaves the aggregation to the next price update instead
+    /// of failing.
+    pub remaining_compute_units: u32,
+    pub unused_2_:               u32,
+}
+
+/// A single price update of an `UpdPriceBatch` or `UpdPriceScatter` instruction. The instruction
+/// data contains a `CommandHeader` followed by one entry per price account.
+#[repr(C)]
+#[derive(Zeroable, Pod, Copy, Clone)]
+pub struct UpdPriceBatchEntry {
+    pub status:          u32,
+    pub unused_:         u32,
+    pub price:           i64,
+    pub confidence:      u64,
+    pub publishing_slot: u64,
+}
+
 #[repr(C)]
 #[derive(Zeroable, Pod, Copy, Clone)]
 pub struct UpdPermissionsArgs {
@@ -174,3 +515,1034 @@ pub struct SetMaxLatencyArgs {
     pub max_latency: u8,
     pub unused_:     [u8; 3],
 }
+
+#[repr(C)]
+#[derive(Zeroable, Pod, Copy, Clone)]
+pub struct SetPublisherWeightArgs {
+    pub header:    CommandHeader,
+    pub publisher: Pubkey,
+    pub weight:    u32,
+}
+
+#[repr(C)]
+#[derive(Zeroable, Clone, Copy, Pod)]
+pub struct SetAggModeArgs {
+    pub header:   CommandHeader,
+    pub agg_mode: u8,
+    pub unused_:  [u8; 3],
+}
+
+#[repr(C)]
+#[derive(Zeroable, Clone, Copy, Pod)]
+pub struct SetOutlierBandArgs {
+    pub header:       CommandHeader,
+    pub outlier_band: u16,
+    pub unused_:      [u8; 2],
+}
+
+#[repr(C)]
+#[derive(Zeroable, Clone, Copy, Pod)]
+pub struct SetMaxUpdatesPerSlotArgs {
+    pub header:               CommandHeader,
+    pub max_updates_per_slot: u8,
+    pub unused_:              [u8; 3],
+}
+
+#[repr(C)]
+#[derive(Zeroable, Clone, Copy, Pod)]
+pub struct SetPriceBoundsArgs {
+    pub header:    CommandHeader,
+    pub min_price: i64,
+    pub max_price: i64,
+}
+
+#[repr(C)]
+#[derive(Zeroable, Clone, Copy, Pod)]
+pub struct SetMaxConfRatioArgs {
+    pub header:         CommandHeader,
+    /// In basis points of the aggregate price, 0 for no limit
+    pub max_conf_ratio: u32,
+}
+
+#[repr(C)]
+#[derive(Zeroable, Clone, Copy, Pod)]
+pub struct SetPublisherPruningArgs {
+    pub header:         CommandHeader,
+    /// Number of consecutive missed rounds after which a publisher is pruned, 0 to disable the
+    /// pruning
+    pub prune_rounds:   u8,
+    /// Number of consecutive rounds a pruned publisher must contribute to be admitted again, at
+    /// least 1 if the pruning is enabled
+    pub readmit_rounds: u8,
+    pub unused_:        [u8; 2],
+}
+
+#[repr(C)]
+#[derive(Zeroable, Clone, Copy, Pod)]
+pub struct SetAggIntervalArgs {
+    pub header:             CommandHeader,
+    /// Minimum number of slots between two aggregations, at least 1
+    pub agg_interval_slots: u64,
+}
+
+#[repr(C)]
+#[derive(Zeroable, Clone, Copy, Pod)]
+pub struct SetCpiGuardArgs {
+    pub header:          CommandHeader,
+    /// 1 to enable the CPI guard, 0 to disable it
+    pub enabled:         u8,
+    pub unused_:         [u8; 7],
+    /// Unused entries are the default pubkey
+    pub allowed_callers: [Pubkey; MAX_CPI_CALLERS],
+}
+
+#[repr(C)]
+#[derive(Zeroable, Clone, Copy, Pod)]
+pub struct SetMarketHoursArgs {
+    pub header:   CommandHeader,
+    /// Opening and closing minute in UTC of the session of each day of the week, starting on
+    /// Monday (see `MarketHours`)
+    pub sessions: [[u16; 2]; 7],
+}
+
+#[repr(C)]
+#[derive(Zeroable, Clone, Copy, Pod)]
+pub struct RotatePublisherArgs {
+    pub header:        CommandHeader,
+    pub old_publisher: Pubkey,
+    pub new_publisher: Pubkey,
+}
+
+#[repr(C)]
+#[derive(Zeroable, Clone, Copy, Pod)]
+pub struct SetFeeArgs {
+    pub header:       CommandHeader,
+    pub fee_lamports: u64,
+}
+
+#[repr(C)]
+#[derive(Zeroable, Clone, Copy, Pod)]
+pub struct WithdrawFeeArgs {
+    pub header:   CommandHeader,
+    pub lamports: u64,
+}
+
+#[repr(C)]
+#[derive(Zeroable, Clone, Copy, Pod)]
+pub struct SetPublisherExcludedArgs {
+    pub header:    CommandHeader,
+    pub publisher: Pubkey,
+    /// 1 to exclude the publisher, 0 to include it
+    pub excluded:  u8,
+    pub unused_:   [u8; 3],
+}
+
+#[repr(C)]
+#[derive(Zeroable, Clone, Copy, Pod)]
+pub struct SetAggRotationArgs {
+    pub header:  CommandHeader,
+    /// 1 to enable aggregation rotation, 0 to disable it
+    pub enabled: u8,
+    pub unused_: [u8; 3],
+}
+
+#[repr(C)]
+#[derive(Zeroable, Clone, Copy, Pod)]
+pub struct SetCoSigningArgs {
+    pub header:  CommandHeader,
+    /// 1 to turn the co-signing mode on, 0 to turn it off
+    pub enabled: u8,
+    pub unused_: [u8; 3],
+}
+
+/// Arguments of `GrantPermission` and `RevokePermission`
+#[repr(C)]
+#[derive(Zeroable, Clone, Copy, Pod)]
+pub struct PermissionGrantArgs {
+    pub header:    CommandHeader,
+    pub authority: Pubkey,
+    /// The `OracleCommand` that `authority` is allowed to run
+    pub command:   i32,
+    pub unused_:   u32,
+}
+
+#[repr(C)]
+#[derive(Zeroable, Clone, Copy, Pod)]
+pub struct SetExponentArgs {
+    pub header:   CommandHeader,
+    pub exponent: i32,
+}
+
+#[repr(C)]
+#[derive(Zeroable, Clone, Copy, Pod)]
+pub struct InitDerivedPriceArgs {
+    pub header:   CommandHeader,
+    /// Exponent of the ratio
+    pub exponent: i32,
+}
+
+/// A single price update of an `OracleInstruction::UpdPriceBatch` or `UpdPriceScatter`
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PriceUpdate {
+    pub status:          u32,
+    pub price:           i64,
+    pub confidence:      u64,
+    pub publishing_slot: u64,
+}
+
+/// Instructions of the oracle with their arguments, as sent in the Borsh layout of the `Borsh`
+/// command. Each variant takes the accounts of the `OracleCommand` of the same name.
+/// WARNING : VARIANTS ARE SERIALIZED BY THEIR POSITION, NEW VARIANTS SHOULD BE ADDED AT THE END
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub enum OracleInstruction {
+    InitMapping,
+    AddMapping,
+    AddProduct,
+    UpdProduct {
+        attributes: Vec<(String, String)>,
+    },
+    AddPrice {
+        exponent:   i32,
+        price_type: u32,
+    },
+    AddPublisher {
+        publisher: Pubkey,
+    },
+    DelPublisher {
+        publisher: Pubkey,
+    },
+    UpdPrice {
+        status:          u32,
+        price:           i64,
+        confidence:      u64,
+        publishing_slot: u64,
+    },
+    AggPrice {
+        status:          u32,
+        price:           i64,
+        confidence:      u64,
+        publishing_slot: u64,
+    },
+    InitPrice {
+        exponent:   i32,
+        price_type: u32,
+    },
+    SetMinPub {
+        minimum_publishers: u8,
+    },
+    UpdPriceNoFailOnError {
+        status:          u32,
+        price:           i64,
+        confidence:      u64,
+        publishing_slot: u64,
+    },
+    DelPrice,
+    DelProduct,
+    UpdPermissions {
+        master_authority:        Pubkey,
+        data_curation_authority: Pubkey,
+        security_authority:      Pubkey,
+    },
+    SetMaxLatency {
+        max_latency: u8,
+    },
+    UpdPriceBatch {
+        updates: Vec<PriceUpdate>,
+    },
+    SetPublisherWeight {
+        publisher: Pubkey,
+        weight:    u32,
+    },
+    SetAggMode {
+        agg_mode: u8,
+    },
+    HaltPrice,
+    ResumePrice,
+    SetOutlierBand {
+        outlier_band: u16,
+    },
+    SetExponent {
+        exponent: i32,
+    },
+    ResignPublisher,
+    ForceAggregate,
+    ResetPrice,
+    SetMaxUpdatesPerSlot {
+        max_updates_per_slot: u8,
+    },
+    SetPriceBounds {
+        min_price: i64,
+        max_price: i64,
+    },
+    UpdPriceWithTime {
+        status:          u32,
+        price:           i64,
+        confidence:      u64,
+        publishing_slot: u64,
+        publish_time:    i64,
+    },
+    SetCpiGuard {
+        enabled:         bool,
+        allowed_callers: [Pubkey; MAX_CPI_CALLERS],
+    },
+    SetMarketHours {
+        sessions: [[u16; 2]; 7],
+    },
+    InitPriceFeedIndex,
+    InitMessageBuffer,
+    RotatePublisher {
+        old_publisher: Pubkey,
+        new_publisher: Pubkey,
+    },
+    SetFee {
+        fee_lamports: u64,
+    },
+    WithdrawFee {
+        lamports: u64,
+    },
+    GrantPermission {
+        authority: Pubkey,
+        command:   i32,
+    },
+    RevokePermission {
+        authority: Pubkey,
+        command:   i32,
+    },
+    SetPublisherExcluded {
+        publisher: Pubkey,
+        excluded:  bool,
+    },
+    InitMappingPda,
+    UpdPriceScatter {
+        updates: Vec<PriceUpdate>,
+    },
+    SetAggRotation {
+        enabled: bool,
+    },
+    CompressPriceAccount,
+    SetReferencePrice,
+    UpdPriceWithTrade {
+        status:          u32,
+        price:           i64,
+        confidence:      u64,
+        publishing_slot: u64,
+        trade_price:     i64,
+        trade_size:      u64,
+        venue_id:        u32,
+    },
+    SetCoSigning {
+        enabled: bool,
+    },
+    CompactComponents,
+    InitSymbolIndex,
+    AddPublisherWithExpiry {
+        publisher:   Pubkey,
+        expiry_slot: u64,
+    },
+    SetMaxConfRatio {
+        max_conf_ratio: u32,
+    },
+    Heartbeat,
+    InitComponentLog,
+    InitDerivedPrice {
+        exponent: i32,
+    },
+    UpdDerived,
+    SetPublisherPruning {
+        prune_rounds:   u8,
+        readmit_rounds: u8,
+    },
+    SetAggInterval {
+        agg_interval_slots: u64,
+    },
+    UpdPriceWithBudget {
+        status:                  u32,
+        price:                   i64,
+        confidence:              u64,
+        publishing_slot:         u64,
+        remaining_compute_units: u32,
+    },
+    InitTimeMachine,
+}
+
+fn command_header(command: OracleCommand) -> CommandHeader {
+    CommandHeader {
+        version: PC_VERSION,
+        command: command as i32,
+    }
+}
+
+/// Read a `T` at the beginning of `data`, which doesn't need to be aligned
+fn read_unaligned<T: Pod>(data: &[u8]) -> Result<T, OracleError> {
+    data.get(0..size_of::<T>())
+        .map(pod_read_unaligned)
+        .ok_or(OracleError::InstructionDataTooShort)
+}
+
+/// Read the `UpdPriceBatchEntry`s following the header of `UpdPriceBatch` and `UpdPriceScatter`
+fn read_price_updates(payload: &[u8]) -> Result<Vec<PriceUpdate>, OracleError> {
+    let entries = payload.chunks_exact(size_of::<UpdPriceBatchEntry>());
+    if !entries.remainder().is_empty() {
+        return Err(OracleError::DeserializationError);
+    }
+    Ok(entries
+        .map(|entry| {
+            let entry = pod_read_unaligned::<UpdPriceBatchEntry>(entry);
+            PriceUpdate {
+                status:          entry.status,
+                price:           entry.price,
+                confidence:      entry.confidence,
+                publishing_slot: entry.publishing_slot,
+            }
+        })
+        .collect())
+}
+
+impl OracleInstruction {
+    pub fn command(&self) -> OracleCommand {
+        match self {
+            OracleInstruction::InitMapping => OracleCommand::InitMapping,
+            OracleInstruction::AddMapping => OracleCommand::AddMapping,
+            OracleInstruction::AddProduct => OracleCommand::AddProduct,
+            OracleInstruction::UpdProduct { .. } => OracleCommand::UpdProduct,
+            OracleInstruction::AddPrice { .. } => OracleCommand::AddPrice,
+            OracleInstruction::AddPublisher { .. } => OracleCommand::AddPublisher,
+            OracleInstruction::DelPublisher { .. } => OracleCommand::DelPublisher,
+            OracleInstruction::UpdPrice { .. } => OracleCommand::UpdPrice,
+            OracleInstruction::AggPrice { .. } => OracleCommand::AggPrice,
+            OracleInstruction::InitPrice { .. } => OracleCommand::InitPrice,
+            OracleInstruction::SetMinPub { .. } => OracleCommand::SetMinPub,
+            OracleInstruction::UpdPriceNoFailOnError { .. } => OracleCommand::UpdPriceNoFailOnError,
+            OracleInstruction::DelPrice => OracleCommand::DelPrice,
+            OracleInstruction::DelProduct => OracleCommand::DelProduct,
+            OracleInstruction::UpdPermissions { .. } => OracleCommand::UpdPermissions,
+            OracleInstruction::SetMaxLatency { .. } => OracleCommand::SetMaxLatency,
+            OracleInstruction::UpdPriceBatch { .. } => OracleCommand::UpdPriceBatch,
+            OracleInstruction::SetPublisherWeight { .. } => OracleCommand::SetPublisherWeight,
+            OracleInstruction::SetAggMode { .. } => OracleCommand::SetAggMode,
+            OracleInstruction::HaltPrice => OracleCommand::HaltPrice,
+            OracleInstruction::ResumePrice => OracleCommand::ResumePrice,
+            OracleInstruction::SetOutlierBand { .. } => OracleCommand::SetOutlierBand,
+            OracleInstruction::SetExponent { .. } => OracleCommand::SetExponent,
+            OracleInstruction::ResignPublisher => OracleCommand::ResignPublisher,
+            OracleInstruction::ForceAggregate => OracleCommand::ForceAggregate,
+            OracleInstruction::ResetPrice => OracleCommand::ResetPrice,
+            OracleInstruction::SetMaxUpdatesPerSlot { .. } => OracleCommand::SetMaxUpdatesPerSlot,
+            OracleInstruction::SetPriceBounds { .. } => OracleCommand::SetPriceBounds,
+            OracleInstruction::UpdPriceWithTime { .. } => OracleCommand::UpdPriceWithTime,
+            OracleInstruction::SetCpiGuard { .. } => OracleCommand::SetCpiGuard,
+            OracleInstruction::SetMarketHours { .. } => OracleCommand::SetMarketHours,
+            OracleInstruction::InitPriceFeedIndex => OracleCommand::InitPriceFeedIndex,
+            OracleInstruction::InitMessageBuffer => OracleCommand::InitMessageBuffer,
+            OracleInstruction::RotatePublisher { .. } => OracleCommand::RotatePublisher,
+            OracleInstruction::SetFee { .. } => OracleCommand::SetFee,
+            OracleInstruction::WithdrawFee { .. } => OracleCommand::WithdrawFee,
+            OracleInstruction::GrantPermission { .. } => OracleCommand::GrantPermission,
+            OracleInstruction::RevokePermission { .. } => OracleCommand::RevokePermission,
+            OracleInstruction::SetPublisherExcluded { .. } => OracleCommand::SetPublisherExcluded,
+            OracleInstruction::InitMappingPda => OracleCommand::InitMappingPda,
+            OracleInstruction::UpdPriceScatter { .. } => OracleCommand::UpdPriceScatter,
+            OracleInstruction::SetAggRotation { .. } => OracleCommand::SetAggRotation,
+            OracleInstruction::CompressPriceAccount => OracleCommand::CompressPriceAccount,
+            OracleInstruction::SetReferencePrice => OracleCommand::SetReferencePrice,
+            OracleInstruction::UpdPriceWithTrade { .. } => OracleCommand::UpdPriceWithTrade,
+            OracleInstruction::SetCoSigning { .. } => OracleCommand::SetCoSigning,
+            OracleInstruction::CompactComponents => OracleCommand::CompactComponents,
+            OracleInstruction::InitSymbolIndex => OracleCommand::InitSymbolIndex,
+            OracleInstruction::AddPublisherWithExpiry { .. } => {
+                OracleCommand::AddPublisherWithExpiry
+            }
+            OracleInstruction::SetMaxConfRatio { .. } => OracleCommand::SetMaxConfRatio,
+            OracleInstruction::Heartbeat => OracleCommand::Heartbeat,
+            OracleInstruction::InitComponentLog => OracleCommand::InitComponentLog,
+            OracleInstruction::InitDerivedPrice { .. } => OracleCommand::InitDerivedPrice,
+            OracleInstruction::UpdDerived => OracleCommand::UpdDerived,
+            OracleInstruction::SetPublisherPruning { .. } => OracleCommand::SetPublisherPruning,
+            OracleInstruction::SetAggInterval { .. } => OracleCommand::SetAggInterval,
+            OracleInstruction::UpdPriceWithBudget { .. } => OracleCommand::UpdPriceWithBudget,
+            OracleInstruction::InitTimeMachine => OracleCommand::InitTimeMachine,
+        }
+    }
+
+    /// Serialize the instruction in the Borsh layout, i.e. a `Borsh` command header followed by
+    /// the Borsh serialization of the instruction
+    #[cfg(any(test, feature = "library"))]
+    pub fn pack(&self) -> Vec<u8> {
+        let mut data = bytes_of(&command_header(OracleCommand::Borsh)).to_vec();
+        // Serializing into a `Vec` can't fail
+        self.serialize(&mut data).unwrap();
+        data
+    }
+
+    /// Serialize the instruction in the legacy layout of its command, i.e. the bytes of its
+    /// arguments struct. Fails if a product attribute is longer than 255 bytes.
+    pub fn pack_legacy(&self) -> Result<Vec<u8>, OracleError> {
+        let header = command_header(self.command());
+        let data = match *self {
+            OracleInstruction::InitMapping
+            | OracleInstruction::AddMapping
+            | OracleInstruction::AddProduct
+            | OracleInstruction::DelPrice
+            | OracleInstruction::DelProduct
+            | OracleInstruction::HaltPrice
+            | OracleInstruction::ResumePrice
+            | OracleInstruction::ResignPublisher
+            | OracleInstruction::ForceAggregate
+            | OracleInstruction::ResetPrice
+            | OracleInstruction::InitPriceFeedIndex
+            | OracleInstruction::InitMessageBuffer
+            | OracleInstruction::InitMappingPda
+            | OracleInstruction::CompressPriceAccount
+            | OracleInstruction::SetReferencePrice
+            | OracleInstruction::CompactComponents
+            | OracleInstruction::InitSymbolIndex
+            | OracleInstruction::Heartbeat
+            | OracleInstruction::InitComponentLog
+            | OracleInstruction::UpdDerived
+            | OracleInstruction::InitTimeMachine => bytes_of(&header).to_vec(),
+            OracleInstruction::UpdProduct { ref attributes } => {
+                let mut data = bytes_of(&header).to_vec();
+                for string in attributes.iter().flat_map(|(key, value)| [key, value]) {
+                    data.push(try_convert(string.len())?);
+                    data.extend_from_slice(string.as_bytes());
+                }
+                data
+            }
+            OracleInstruction::AddPrice {
+                exponent,
+                price_type,
+            }
+            | OracleInstruction::InitPrice {
+                exponent,
+                price_type,
+            } => bytes_of(&AddPriceArgs {
+                header,
+                exponent,
+                price_type,
+            })
+            .to_vec(),
+            OracleInstruction::AddPublisher { publisher }
+            | OracleInstruction::DelPublisher { publisher } => {
+                bytes_of(&AddPublisherArgs { header, publisher }).to_vec()
+            }
+            OracleInstruction::UpdPrice {
+                status,
+                price,
+                confidence,
+                publishing_slot,
+            }
+            | OracleInstruction::AggPrice {
+                status,
+                price,
+                confidence,
+                publishing_slot,
+            }
+            | OracleInstruction::UpdPriceNoFailOnError {
+                status,
+                price,
+                confidence,
+                publishing_slot,
+            } => bytes_of(&UpdPriceArgs {
+                header,
+                status,
+                unused_: 0,
+                price,
+                confidence,
+                publishing_slot,
+            })
+            .to_vec(),
+            OracleInstruction::SetMinPub { minimum_publishers } => bytes_of(&SetMinPubArgs {
+                header,
+                minimum_publishers,
+                unused_: [0; 3],
+            })
+            .to_vec(),
+            OracleInstruction::UpdPermissions {
+                master_authority,
+                data_curation_authority,
+                security_authority,
+            } => bytes_of(&UpdPermissionsArgs {
+                header,
+                master_authority,
+                data_curation_authority,
+                security_authority,
+            })
+            .to_vec(),
+            OracleInstruction::SetMaxLatency { max_latency } => bytes_of(&SetMaxLatencyArgs {
+                header,
+                max_latency,
+                unused_: [0; 3],
+            })
+            .to_vec(),
+            OracleInstruction::UpdPriceBatch { ref updates }
+            | OracleInstruction::UpdPriceScatter { ref updates } => {
+                let mut data = bytes_of(&header).to_vec();
+                for update in updates {
+                    data.extend_from_slice(bytes_of(&UpdPriceBatchEntry {
+                        status:          update.status,
+                        unused_:         0,
+                        price:           update.price,
+                        confidence:      update.confidence,
+                        publishing_slot: update.publishing_slot,
+                    }));
+                }
+                data
+            }
+            OracleInstruction::SetPublisherWeight { publisher, weight } => {
+                bytes_of(&SetPublisherWeightArgs {
+                    header,
+                    publisher,
+                    weight,
+                })
+                .to_vec()
+            }
+            OracleInstruction::SetAggMode { agg_mode } => bytes_of(&SetAggModeArgs {
+                header,
+                agg_mode,
+                unused_: [0; 3],
+            })
+            .to_vec(),
+            OracleInstruction::SetOutlierBand { outlier_band } => bytes_of(&SetOutlierBandArgs {
+                header,
+                outlier_band,
+                unused_: [0; 2],
+            })
+            .to_vec(),
+            OracleInstruction::SetExponent { exponent } => {
+                bytes_of(&SetExponentArgs { header, exponent }).to_vec()
+            }
+            OracleInstruction::InitDerivedPrice { exponent } => {
+                bytes_of(&InitDerivedPriceArgs { header, exponent }).to_vec()
+            }
+            OracleInstruction::SetMaxUpdatesPerSlot {
+                max_updates_per_slot,
+            } => bytes_of(&SetMaxUpdatesPerSlotArgs {
+                header,
+                max_updates_per_slot,
+                unused_: [0; 3],
+            })
+            .to_vec(),
+            OracleInstruction::SetPriceBounds {
+                min_price,
+                max_price,
+            } => bytes_of(&SetPriceBoundsArgs {
+                header,
+                min_price,
+                max_price,
+            })
+            .to_vec(),
+            OracleInstruction::UpdPriceWithTime {
+                status,
+                price,
+                confidence,
+                publishing_slot,
+                publish_time,
+            } => bytes_of(&UpdPriceWithTimeArgs {
+                header,
+                status,
+                unused_: 0,
+                price,
+                confidence,
+                publishing_slot,
+                publish_time,
+            })
+            .to_vec(),
+            OracleInstruction::UpdPriceWithTrade {
+                status,
+                price,
+                confidence,
+                publishing_slot,
+                trade_price,
+                trade_size,
+                venue_id,
+            } => bytes_of(&UpdPriceWithTradeArgs {
+                header,
+                status,
+                unused_: 0,
+                price,
+                confidence,
+                publishing_slot,
+                trade_price,
+                trade_size,
+                venue_id,
+                unused_2_: 0,
+            })
+            .to_vec(),
+            OracleInstruction::SetCpiGuard {
+                enabled,
+                allowed_callers,
+            } => bytes_of(&SetCpiGuardArgs {
+                header,
+                enabled: u8::from(enabled),
+                unused_: [0; 7],
+                allowed_callers,
+            })
+            .to_vec(),
+            OracleInstruction::SetMarketHours { sessions } => {
+                bytes_of(&SetMarketHoursArgs { header, sessions }).to_vec()
+            }
+            OracleInstruction::RotatePublisher {
+                old_publisher,
+                new_publisher,
+            } => bytes_of(&RotatePublisherArgs {
+                header,
+                old_publisher,
+                new_publisher,
+            })
+            .to_vec(),
+            OracleInstruction::SetFee { fee_lamports } => bytes_of(&SetFeeArgs {
+                header,
+                fee_lamports,
+            })
+            .to_vec(),
+            OracleInstruction::WithdrawFee { lamports } => {
+                bytes_of(&WithdrawFeeArgs { header, lamports }).to_vec()
+            }
+            OracleInstruction::GrantPermission { authority, command }
+            | OracleInstruction::RevokePermission { authority, command } => {
+                bytes_of(&PermissionGrantArgs {
+                    header,
+                    authority,
+                    command,
+                    unused_: 0,
+                })
+                .to_vec()
+            }
+            OracleInstruction::SetPublisherExcluded {
+                publisher,
+                excluded,
+            } => bytes_of(&SetPublisherExcludedArgs {
+                header,
+                publisher,
+                excluded: u8::from(excluded),
+                unused_: [0; 3],
+            })
+            .to_vec(),
+            OracleInstruction::SetAggRotation { enabled } => bytes_of(&SetAggRotationArgs {
+                header,
+                enabled: u8::from(enabled),
+                unused_: [0; 3],
+            })
+            .to_vec(),
+            OracleInstruction::SetCoSigning { enabled } => bytes_of(&SetCoSigningArgs {
+                header,
+                enabled: u8::from(enabled),
+                unused_: [0; 3],
+            })
+            .to_vec(),
+            OracleInstruction::AddPublisherWithExpiry {
+                publisher,
+                expiry_slot,
+            } => bytes_of(&AddPublisherWithExpiryArgs {
+                header,
+                publisher,
+                expiry_slot,
+            })
+            .to_vec(),
+            OracleInstruction::SetMaxConfRatio { max_conf_ratio } => {
+                bytes_of(&SetMaxConfRatioArgs {
+                    header,
+                    max_conf_ratio,
+                })
+                .to_vec()
+            }
+            OracleInstruction::SetPublisherPruning {
+                prune_rounds,
+                readmit_rounds,
+            } => bytes_of(&SetPublisherPruningArgs {
+                header,
+                prune_rounds,
+                readmit_rounds,
+                unused_: [0; 2],
+            })
+            .to_vec(),
+            OracleInstruction::SetAggInterval { agg_interval_slots } => {
+                bytes_of(&SetAggIntervalArgs {
+                    header,
+                    agg_interval_slots,
+                })
+                .to_vec()
+            }
+            OracleInstruction::UpdPriceWithBudget {
+                status,
+                price,
+                confidence,
+                publishing_slot,
+                remaining_compute_units,
+            } => bytes_of(&UpdPriceWithBudgetArgs {
+                header,
+                status,
+                unused_: 0,
+                price,
+                confidence,
+                publishing_slot,
+                remaining_compute_units,
+                unused_2_: 0,
+            })
+            .to_vec(),
+        };
+        Ok(data)
+    }
+
+    /// Deserialize instruction data in either the Borsh layout or the legacy layout. Like the
+    /// processors, the legacy layout ignores the bytes after the arguments struct of fixed-size
+    /// commands.
+    pub fn unpack(data: &[u8]) -> Result<Self, OracleError> {
+        let header = read_unaligned::<CommandHeader>(data)?;
+        if header.version != PC_VERSION {
+            return Err(OracleError::InvalidInstructionVersion);
+        }
+        let command =
+            OracleCommand::from_i32(header.command).ok_or(OracleError::UnrecognizedInstruction)?;
+        let payload = &data[size_of::<CommandHeader>()..];
+
+        let instruction = match command {
+            OracleCommand::Borsh => OracleInstruction::try_from_slice(payload)
+                .map_err(|_| OracleError::DeserializationError)?,
+            OracleCommand::InitMapping => OracleInstruction::InitMapping,
+            OracleCommand::AddMapping => OracleInstruction::AddMapping,
+            OracleCommand::AddProduct => OracleInstruction::AddProduct,
+            OracleCommand::UpdProduct => OracleInstruction::UpdProduct {
+                attributes: read_product_attributes(payload)
+                    .map_err(|_| OracleError::DeserializationError)?
+                    .into_iter()
+                    .map(|(key, value)| {
+                        Ok((
+                            String::from_utf8(key.to_vec())
+                                .map_err(|_| OracleError::DeserializationError)?,
+                            String::from_utf8(value.to_vec())
+                                .map_err(|_| OracleError::DeserializationError)?,
+                        ))
+                    })
+                    .collect::<Result<_, OracleError>>()?,
+            },
+            OracleCommand::AddPrice | OracleCommand::InitPrice => {
+                let args = read_unaligned::<AddPriceArgs>(data)?;
+                if command == OracleCommand::AddPrice {
+                    OracleInstruction::AddPrice {
+                        exponent:   args.exponent,
+                        price_type: args.price_type,
+                    }
+                } else {
+                    OracleInstruction::InitPrice {
+                        exponent:   args.exponent,
+                        price_type: args.price_type,
+                    }
+                }
+            }
+            OracleCommand::AddPublisher => OracleInstruction::AddPublisher {
+                publisher: read_unaligned::<AddPublisherArgs>(data)?.publisher,
+            },
+            OracleCommand::DelPublisher => OracleInstruction::DelPublisher {
+                publisher: read_unaligned::<DelPublisherArgs>(data)?.publisher,
+            },
+            OracleCommand::UpdPrice
+            | OracleCommand::AggPrice
+            | OracleCommand::UpdPriceNoFailOnError => {
+                let args = read_unaligned::<UpdPriceArgs>(data)?;
+                let (status, price, confidence, publishing_slot) = (
+                    args.status,
+                    args.price,
+                    args.confidence,
+                    args.publishing_slot,
+                );
+                match command {
+                    OracleCommand::UpdPrice => OracleInstruction::UpdPrice {
+                        status,
+                        price,
+                        confidence,
+                        publishing_slot,
+                    },
+                    OracleCommand::AggPrice => OracleInstruction::AggPrice {
+                        status,
+                        price,
+                        confidence,
+                        publishing_slot,
+                    },
+                    _ => OracleInstruction::UpdPriceNoFailOnError {
+                        status,
+                        price,
+                        confidence,
+                        publishing_slot,
+                    },
+                }
+            }
+            OracleCommand::SetMinPub => OracleInstruction::SetMinPub {
+                minimum_publishers: read_unaligned::<SetMinPubArgs>(data)?.minimum_publishers,
+            },
+            OracleCommand::DelPrice => OracleInstruction::DelPrice,
+            OracleCommand::DelProduct => OracleInstruction::DelProduct,
+            OracleCommand::UpdPermissions => {
+                let args = read_unaligned::<UpdPermissionsArgs>(data)?;
+                OracleInstruction::UpdPermissions {
+                    master_authority:        args.master_authority,
+                    data_curation_authority: args.data_curation_authority,
+                    security_authority:      args.security_authority,
+                }
+            }
+            OracleCommand::SetMaxLatency => OracleInstruction::SetMaxLatency {
+                max_latency: read_unaligned::<SetMaxLatencyArgs>(data)?.max_latency,
+            },
+            OracleCommand::UpdPriceBatch => OracleInstruction::UpdPriceBatch {
+                updates: read_price_updates(payload)?,
+            },
+            OracleCommand::SetPublisherWeight => {
+                let args = read_unaligned::<SetPublisherWeightArgs>(data)?;
+                OracleInstruction::SetPublisherWeight {
+                    publisher: args.publisher,
+                    weight:    args.weight,
+                }
+            }
+            OracleCommand::SetAggMode => OracleInstruction::SetAggMode {
+                agg_mode: read_unaligned::<SetAggModeArgs>(data)?.agg_mode,
+            },
+            OracleCommand::HaltPrice => OracleInstruction::HaltPrice,
+            OracleCommand::ResumePrice => OracleInstruction::ResumePrice,
+            OracleCommand::SetOutlierBand => OracleInstruction::SetOutlierBand {
+                outlier_band: read_unaligned::<SetOutlierBandArgs>(data)?.outlier_band,
+            },
+            OracleCommand::SetExponent => OracleInstruction::SetExponent {
+                exponent: read_unaligned::<SetExponentArgs>(data)?.exponent,
+            },
+            OracleCommand::ResignPublisher => OracleInstruction::ResignPublisher,
+            OracleCommand::ForceAggregate => OracleInstruction::ForceAggregate,
+            OracleCommand::ResetPrice => OracleInstruction::ResetPrice,
+            OracleCommand::SetMaxUpdatesPerSlot => OracleInstruction::SetMaxUpdatesPerSlot {
+                max_updates_per_slot: read_unaligned::<SetMaxUpdatesPerSlotArgs>(data)?
+                    .max_updates_per_slot,
+            },
+            OracleCommand::SetPriceBounds => {
+                let args = read_unaligned::<SetPriceBoundsArgs>(data)?;
+                OracleInstruction::SetPriceBounds {
+                    min_price: args.min_price,
+                    max_price: args.max_price,
+                }
+            }
+            OracleCommand::UpdPriceWithTime => {
+                let args = read_unaligned::<UpdPriceWithTimeArgs>(data)?;
+                OracleInstruction::UpdPriceWithTime {
+                    status:          args.status,
+                    price:           args.price,
+                    confidence:      args.confidence,
+                    publishing_slot: args.publishing_slot,
+                    publish_time:    args.publish_time,
+                }
+            }
+            OracleCommand::SetCpiGuard => {
+                let args = read_unaligned::<SetCpiGuardArgs>(data)?;
+                OracleInstruction::SetCpiGuard {
+                    enabled:         args.enabled != 0,
+                    allowed_callers: args.allowed_callers,
+                }
+            }
+            OracleCommand::SetMarketHours => OracleInstruction::SetMarketHours {
+                sessions: read_unaligned::<SetMarketHoursArgs>(data)?.sessions,
+            },
+            OracleCommand::InitPriceFeedIndex => OracleInstruction::InitPriceFeedIndex,
+            OracleCommand::InitMessageBuffer => OracleInstruction::InitMessageBuffer,
+            OracleCommand::RotatePublisher => {
+                let args = read_unaligned::<RotatePublisherArgs>(data)?;
+                OracleInstruction::RotatePublisher {
+                    old_publisher: args.old_publisher,
+                    new_publisher: args.new_publisher,
+                }
+            }
+            OracleCommand::SetFee => OracleInstruction::SetFee {
+                fee_lamports: read_unaligned::<SetFeeArgs>(data)?.fee_lamports,
+            },
+            OracleCommand::WithdrawFee => OracleInstruction::WithdrawFee {
+                lamports: read_unaligned::<WithdrawFeeArgs>(data)?.lamports,
+            },
+            OracleCommand::GrantPermission => {
+                let args = read_unaligned::<PermissionGrantArgs>(data)?;
+                OracleInstruction::GrantPermission {
+                    authority: args.authority,
+                    command:   args.command,
+                }
+            }
+            OracleCommand::RevokePermission => {
+                let args = read_unaligned::<PermissionGrantArgs>(data)?;
+                OracleInstruction::RevokePermission {
+                    authority: args.authority,
+                    command:   args.command,
+                }
+            }
+            OracleCommand::SetPublisherExcluded => {
+                let args = read_unaligned::<SetPublisherExcludedArgs>(data)?;
+                OracleInstruction::SetPublisherExcluded {
+                    publisher: args.publisher,
+                    excluded:  args.excluded != 0,
+                }
+            }
+            OracleCommand::InitMappingPda => OracleInstruction::InitMappingPda,
+            OracleCommand::UpdPriceScatter => OracleInstruction::UpdPriceScatter {
+                updates: read_price_updates(payload)?,
+            },
+            OracleCommand::SetAggRotation => OracleInstruction::SetAggRotation {
+                enabled: read_unaligned::<SetAggRotationArgs>(data)?.enabled != 0,
+            },
+            OracleCommand::CompressPriceAccount => OracleInstruction::CompressPriceAccount,
+            OracleCommand::SetReferencePrice => OracleInstruction::SetReferencePrice,
+            OracleCommand::UpdPriceWithTrade => {
+                let args = read_unaligned::<UpdPriceWithTradeArgs>(data)?;
+                OracleInstruction::UpdPriceWithTrade {
+                    status:          args.status,
+                    price:           args.price,
+                    confidence:      args.confidence,
+                    publishing_slot: args.publishing_slot,
+                    trade_price:     args.trade_price,
+                    trade_size:      args.trade_size,
+                    venue_id:        args.venue_id,
+                }
+            }
+            OracleCommand::SetCoSigning => OracleInstruction::SetCoSigning {
+                enabled: read_unaligned::<SetCoSigningArgs>(data)?.enabled != 0,
+            },
+            OracleCommand::CompactComponents => OracleInstruction::CompactComponents,
+            OracleCommand::InitSymbolIndex => OracleInstruction::InitSymbolIndex,
+            OracleCommand::AddPublisherWithExpiry => {
+                let args = read_unaligned::<AddPublisherWithExpiryArgs>(data)?;
+                OracleInstruction::AddPublisherWithExpiry {
+                    publisher:   args.publisher,
+                    expiry_slot: args.expiry_slot,
+                }
+            }
+            OracleCommand::SetMaxConfRatio => OracleInstruction::SetMaxConfRatio {
+                max_conf_ratio: read_unaligned::<SetMaxConfRatioArgs>(data)?.max_conf_ratio,
+            },
+            OracleCommand::Heartbeat => OracleInstruction::Heartbeat,
+            OracleCommand::InitComponentLog => OracleInstruction::InitComponentLog,
+            OracleCommand::InitDerivedPrice => OracleInstruction::InitDerivedPrice {
+                exponent: read_unaligned::<InitDerivedPriceArgs>(data)?.exponent,
+            },
+            OracleCommand::UpdDerived => OracleInstruction::UpdDerived,
+            OracleCommand::SetPublisherPruning => {
+                let args = read_unaligned::<SetPublisherPruningArgs>(data)?;
+                OracleInstruction::SetPublisherPruning {
+                    prune_rounds:   args.prune_rounds,
+                    readmit_rounds: args.readmit_rounds,
+                }
+            }
+            OracleCommand::SetAggInterval => OracleInstruction::SetAggInterval {
+                agg_interval_slots: read_unaligned::<SetAggIntervalArgs>(data)?.agg_interval_slots,
+            },
+            OracleCommand::UpdPriceWithBudget => {
+                let args = read_unaligned::<UpdPriceWithBudgetArgs>(data)?;
+                OracleInstruction::UpdPriceWithBudget {
+                    status:                  args.status,
+                    price:                   args.price,
+                    confidence:              args.confidence,
+                    publishing_slot:         args.publishing_slot,
+                    remaining_compute_units: args.remaining_compute_units,
+                }
+            }
+            OracleCommand::InitTimeMachine => OracleInstruction::InitTimeMachine,
+            OracleCommand::InitTest
+            | OracleCommand::UpdTest
+            | OracleCommand::ResizePriceAccount => {
+                return Err(OracleError::UnrecognizedInstruction)
+            }
+        };
+        Ok(instruction)
+    }
+}
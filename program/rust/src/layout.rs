@@ -0,0 +1,274 @@
+//! Sizes, alignments and field offsets of the accounts of the program, for clients that read the
+//! accounts without deserializing them and for the processors that resize them. The offsets are
+//! derived from the Rust definitions of the accounts and checked at compile time against the
+//! offsets of the C definitions where both exist.
+use {
+    crate::{
+        accounts::{
+            AccountHeader,
+            AggInterval,
+            CompressedPriceAccount,
+            ExtensionHeader,
+            MappingAccount,
+            PermissionAccount,
+            PriceAccount,
+            PriceAccountExtension,
+            PriceComponent,
+            PriceCumulative,
+            PriceInfo,
+            ProductAccount,
+            PruningState,
+            PublisherExpiries,
+            PublisherExpiry,
+            PublisherPruning,
+            PublisherStats,
+            PublisherWeight,
+            PublisherWeights,
+            TimeMachine,
+            TimeMachineBucket,
+        },
+        c_oracle_header::{
+            PC_MAP_TABLE_T_PROD_OFFSET,
+            PC_PRICE_T_COMP_OFFSET,
+            PC_PROD_ACC_SIZE,
+            ZSTD_UPPER_BOUND,
+        },
+    },
+    pyth_oracle_core::price as core_layout,
+    std::mem::{
+        align_of,
+        size_of,
+    },
+};
+
+/// Offset in bytes of `$field` in the `#[repr(C)]` struct `$type`, usable in constants.
+macro_rules! offset_of {
+    ($type:ty, $field:ident) => {{
+        let uninit = std::mem::MaybeUninit::<$type>::uninit();
+        let base = uninit.as_ptr();
+        // SAFETY: The pointers are only used to compute an address within the same allocation,
+        // the uninitialized memory is never read.
+        unsafe { (std::ptr::addr_of!((*base).$field) as *const u8).offset_from(base as *const u8) }
+            as usize
+    }};
+}
+pub(crate) use offset_of;
+
+pub const ACCOUNT_HEADER_SIZE: usize = size_of::<AccountHeader>();
+pub const ACCOUNT_HEADER_MAGIC_OFFSET: usize = offset_of!(AccountHeader, magic_number);
+pub const ACCOUNT_HEADER_VERSION_OFFSET: usize = offset_of!(AccountHeader, version);
+pub const ACCOUNT_HEADER_ACCOUNT_TYPE_OFFSET: usize = offset_of!(AccountHeader, account_type);
+pub const ACCOUNT_HEADER_SIZE_OFFSET: usize = offset_of!(AccountHeader, size);
+
+pub const MAPPING_ACCOUNT_SIZE: usize = size_of::<MappingAccount>();
+pub const MAPPING_ACCOUNT_ALIGN: usize = align_of::<MappingAccount>();
+pub const MAPPING_ACCOUNT_NUMBER_OF_PRODUCTS_OFFSET: usize =
+    offset_of!(MappingAccount, number_of_products);
+pub const MAPPING_ACCOUNT_NEXT_MAPPING_ACCOUNT_OFFSET: usize =
+    offset_of!(MappingAccount, next_mapping_account);
+/// Also the `header.size` of an empty mapping account, each product adds a pubkey to it
+pub const MAPPING_ACCOUNT_PRODUCTS_LIST_OFFSET: usize = offset_of!(MappingAccount, products_list);
+
+/// Size of a product account, including the space of its attributes
+pub const PRODUCT_ACCOUNT_SIZE: usize = PC_PROD_ACC_SIZE as usize;
+pub const PRODUCT_ACCOUNT_ALIGN: usize = align_of::<ProductAccount>();
+pub const PRODUCT_ACCOUNT_FIRST_PRICE_ACCOUNT_OFFSET: usize =
+    offset_of!(ProductAccount, first_price_account);
+/// The attributes of a product follow its fixed fields
+pub const PRODUCT_ACCOUNT_ATTRIBUTES_OFFSET: usize = size_of::<ProductAccount>();
+/// Only in product accounts resized by `set_reference_price`
+pub const PRODUCT_ACCOUNT_REFERENCE_PRICE_OFFSET: usize = ProductAccount::REFERENCE_PRICE_OFFSET;
+
+pub const PRICE_ACCOUNT_SIZE: usize = size_of::<PriceAccount>();
+pub const PRICE_ACCOUNT_ALIGN: usize = align_of::<PriceAccount>();
+pub const PRICE_ACCOUNT_PRICE_TYPE_OFFSET: usize = offset_of!(PriceAccount, price_type);
+pub const PRICE_ACCOUNT_EXPONENT_OFFSET: usize = offset_of!(PriceAccount, exponent);
+pub const PRICE_ACCOUNT_NUM_OFFSET: usize = offset_of!(PriceAccount, num_);
+pub const PRICE_ACCOUNT_NUM_QT_OFFSET: usize = offset_of!(PriceAccount, num_qt_);
+pub const PRICE_ACCOUNT_LAST_SLOT_OFFSET: usize = offset_of!(PriceAccount, last_slot_);
+pub const PRICE_ACCOUNT_VALID_SLOT_OFFSET: usize = offset_of!(PriceAccount, valid_slot_);
+pub const PRICE_ACCOUNT_TWAP_OFFSET: usize = offset_of!(PriceAccount, twap_);
+pub const PRICE_ACCOUNT_TWAC_OFFSET: usize = offset_of!(PriceAccount, twac_);
+pub const PRICE_ACCOUNT_TIMESTAMP_OFFSET: usize = offset_of!(PriceAccount, timestamp_);
+pub const PRICE_ACCOUNT_MIN_PUB_OFFSET: usize = offset_of!(PriceAccount, min_pub_);
+pub const PRICE_ACCOUNT_PRODUCT_ACCOUNT_OFFSET: usize = offset_of!(PriceAccount, product_account);
+pub const PRICE_ACCOUNT_NEXT_PRICE_ACCOUNT_OFFSET: usize =
+    offset_of!(PriceAccount, next_price_account);
+pub const PRICE_ACCOUNT_PREV_SLOT_OFFSET: usize = offset_of!(PriceAccount, prev_slot_);
+pub const PRICE_ACCOUNT_PREV_PRICE_OFFSET: usize = offset_of!(PriceAccount, prev_price_);
+pub const PRICE_ACCOUNT_PREV_CONF_OFFSET: usize = offset_of!(PriceAccount, prev_conf_);
+pub const PRICE_ACCOUNT_PREV_TIMESTAMP_OFFSET: usize = offset_of!(PriceAccount, prev_timestamp_);
+pub const PRICE_ACCOUNT_AGG_OFFSET: usize = offset_of!(PriceAccount, agg_);
+pub const PRICE_ACCOUNT_COMP_OFFSET: usize = offset_of!(PriceAccount, comp_);
+pub const PRICE_ACCOUNT_PUBLISHER_STATS_OFFSET: usize = offset_of!(PriceAccount, publisher_stats_);
+pub const PRICE_ACCOUNT_FAST_AGG_OFFSET: usize = offset_of!(PriceAccount, fast_agg_);
+pub const PRICE_ACCOUNT_LAYOUT_VERSION_OFFSET: usize = offset_of!(PriceAccount, layout_version_);
+pub const PRICE_ACCOUNT_FEED_INDEX_OFFSET: usize = offset_of!(PriceAccount, feed_index_);
+/// Last change of the aggregate status, a `StatusChange`
+pub const PRICE_ACCOUNT_STATUS_CHANGE_OFFSET: usize = offset_of!(PriceAccount, status_change_);
+/// Only on Pythnet, a `StatusHistory`
+#[cfg(feature = "pythnet")]
+pub const PRICE_ACCOUNT_STATUS_HISTORY_OFFSET: usize = offset_of!(PriceAccount, status_history_);
+pub const PRICE_ACCOUNT_PRICE_CUMULATIVE_OFFSET: usize = offset_of!(PriceAccount, price_cumulative);
+/// The `u64` counter of aggregates, which clients can watch alone to detect new aggregates
+pub const PRICE_ACCOUNT_AGGREGATE_COUNTER_OFFSET: usize =
+    PRICE_ACCOUNT_PRICE_CUMULATIVE_OFFSET + offset_of!(PriceCumulative, aggregate_counter);
+/// Only in price accounts resized by the first instruction that needs one of their extensions,
+/// the `ExtensionHeader` of the first extension (see `PriceAccount::add_extension`). Each
+/// extension is `EXTENSION_HEADER_SIZE` bytes of header followed by its content, padded to a
+/// multiple of 8 bytes.
+pub const PRICE_ACCOUNT_EXTENSIONS_OFFSET: usize = size_of::<PriceAccount>();
+/// Size of a price account with every extension, the largest price account
+pub const PRICE_ACCOUNT_MAX_SIZE: usize = PriceAccount::MAX_SIZE;
+
+pub const EXTENSION_HEADER_SIZE: usize = size_of::<ExtensionHeader>();
+pub const EXTENSION_HEADER_KIND_OFFSET: usize = offset_of!(ExtensionHeader, kind_);
+pub const EXTENSION_HEADER_SIZE_OFFSET: usize = offset_of!(ExtensionHeader, size_);
+/// `PC_NUM_COMP` entries of `PUBLISHER_EXPIRY_SIZE` bytes, added by `add_publisher_with_expiry`
+pub const EXTENSION_KIND_PUBLISHER_EXPIRIES: u32 = PublisherExpiries::KIND;
+/// A `PublisherPruning`, added by `set_publisher_pruning`
+pub const EXTENSION_KIND_PUBLISHER_PRUNING: u32 = PublisherPruning::KIND;
+/// An `AggInterval`, added by `set_agg_interval`
+pub const EXTENSION_KIND_AGG_INTERVAL: u32 = AggInterval::KIND;
+/// `PC_NUM_COMP` entries of `PUBLISHER_WEIGHT_SIZE` bytes, added by `set_publisher_weight`
+pub const EXTENSION_KIND_PUBLISHER_WEIGHTS: u32 = PublisherWeights::KIND;
+/// A `TimeMachine`, added by `init_time_machine`
+pub const EXTENSION_KIND_TIME_MACHINE: u32 = TimeMachine::KIND;
+
+/// Size of a price account after `compress_price_account`
+pub const COMPRESSED_PRICE_ACCOUNT_SIZE: usize = size_of::<CompressedPriceAccount>();
+
+/// The components of a price account are `PRICE_COMPONENT_SIZE` bytes apart from
+/// `PRICE_ACCOUNT_COMP_OFFSET`
+pub const PRICE_COMPONENT_SIZE: usize = size_of::<PriceComponent>();
+pub const PRICE_COMPONENT_PUB_OFFSET: usize = offset_of!(PriceComponent, pub_);
+pub const PRICE_COMPONENT_AGG_OFFSET: usize = offset_of!(PriceComponent, agg_);
+pub const PRICE_COMPONENT_LATEST_OFFSET: usize = offset_of!(PriceComponent, latest_);
+
+pub const PUBLISHER_EXPIRY_SIZE: usize = size_of::<PublisherExpiry>();
+pub const PUBLISHER_EXPIRY_PUBLISHER_OFFSET: usize = offset_of!(PublisherExpiry, publisher_);
+pub const PUBLISHER_EXPIRY_EXPIRY_SLOT_OFFSET: usize = offset_of!(PublisherExpiry, expiry_slot_);
+
+pub const PUBLISHER_WEIGHT_SIZE: usize = size_of::<PublisherWeight>();
+pub const PUBLISHER_WEIGHT_PUBLISHER_OFFSET: usize = offset_of!(PublisherWeight, publisher_);
+pub const PUBLISHER_WEIGHT_WEIGHT_OFFSET: usize = offset_of!(PublisherWeight, weight_);
+
+pub const PUBLISHER_PRUNING_PRUNE_ROUNDS_OFFSET: usize =
+    offset_of!(PublisherPruning, prune_rounds_);
+pub const PUBLISHER_PRUNING_READMIT_ROUNDS_OFFSET: usize =
+    offset_of!(PublisherPruning, readmit_rounds_);
+/// `PC_NUM_COMP` states of `PRUNING_STATE_SIZE` bytes
+pub const PUBLISHER_PRUNING_STATES_OFFSET: usize = offset_of!(PublisherPruning, states_);
+pub const PRUNING_STATE_SIZE: usize = size_of::<PruningState>();
+pub const PRUNING_STATE_PUBLISHER_OFFSET: usize = offset_of!(PruningState, publisher_);
+pub const PRUNING_STATE_PRUNED_OFFSET: usize = offset_of!(PruningState, pruned_);
+pub const PRUNING_STATE_ROUNDS_OFFSET: usize = offset_of!(PruningState, rounds_);
+
+pub const TIME_MACHINE_NUM_BUCKETS_OFFSET: usize = offset_of!(TimeMachine, num_buckets_);
+/// Bit `i` of the little endian `u64` is set if bucket `i` is valid
+pub const TIME_MACHINE_VALID_OFFSET: usize = offset_of!(TimeMachine, valid_);
+/// `TIME_MACHINE_NUM_BUCKETS` buckets of `TIME_MACHINE_BUCKET_SIZE` bytes
+pub const TIME_MACHINE_BUCKETS_OFFSET: usize = offset_of!(TimeMachine, buckets_);
+pub const TIME_MACHINE_BUCKET_SIZE: usize = size_of::<TimeMachineBucket>();
+pub const TIME_MACHINE_BUCKET_START_TIME_OFFSET: usize = offset_of!(TimeMachineBucket, start_time_);
+pub const TIME_MACHINE_BUCKET_NUM_SLOTS_OFFSET: usize = offset_of!(TimeMachineBucket, num_slots_);
+pub const TIME_MACHINE_BUCKET_PRICE_SUM_OFFSET: usize = offset_of!(TimeMachineBucket, price_sum_);
+pub const TIME_MACHINE_BUCKET_CONF_SUM_OFFSET: usize = offset_of!(TimeMachineBucket, conf_sum_);
+
+pub const PRICE_INFO_SIZE: usize = size_of::<PriceInfo>();
+pub const PRICE_INFO_PRICE_OFFSET: usize = offset_of!(PriceInfo, price_);
+pub const PRICE_INFO_CONF_OFFSET: usize = offset_of!(PriceInfo, conf_);
+pub const PRICE_INFO_STATUS_OFFSET: usize = offset_of!(PriceInfo, status_);
+pub const PRICE_INFO_CORP_ACT_STATUS_OFFSET: usize = offset_of!(PriceInfo, corp_act_status_);
+pub const PRICE_INFO_PUB_SLOT_OFFSET: usize = offset_of!(PriceInfo, pub_slot_);
+
+/// Size of a permission account, including the space added by later versions of the program
+pub const PERMISSION_ACCOUNT_SIZE: usize = PermissionAccount::NEW_SIZE;
+
+// The C code and the off-chain clients read the accounts with these offsets
+const _: () = assert!(PRICE_ACCOUNT_COMP_OFFSET == PC_PRICE_T_COMP_OFFSET as usize);
+const _: () = assert!(MAPPING_ACCOUNT_PRODUCTS_LIST_OFFSET == PC_MAP_TABLE_T_PROD_OFFSET as usize);
+const _: () = assert!(PRICE_ACCOUNT_MAX_SIZE == ZSTD_UPPER_BOUND as usize);
+const _: () = assert!(PRICE_ACCOUNT_SIZE % PRICE_ACCOUNT_ALIGN == 0);
+const _: () = assert!(MAPPING_ACCOUNT_SIZE % MAPPING_ACCOUNT_ALIGN == 0);
+const _: () = assert!(PRODUCT_ACCOUNT_SIZE % PRODUCT_ACCOUNT_ALIGN == 0);
+// The cumulative sums are the last field of the price account
+const _: () = assert!(
+    PRICE_ACCOUNT_PRICE_CUMULATIVE_OFFSET == PRICE_ACCOUNT_SIZE - size_of::<PriceCumulative>()
+);
+
+// `pyth_oracle_core` reads the price accounts with its own offsets (see `PriceAccountView`)
+const _: () = assert!(core_layout::ACCOUNT_HEADER_MAGIC_OFFSET == ACCOUNT_HEADER_MAGIC_OFFSET);
+const _: () = assert!(core_layout::ACCOUNT_HEADER_VERSION_OFFSET == ACCOUNT_HEADER_VERSION_OFFSET);
+const _: () =
+    assert!(core_layout::ACCOUNT_HEADER_ACCOUNT_TYPE_OFFSET == ACCOUNT_HEADER_ACCOUNT_TYPE_OFFSET);
+const _: () = assert!(core_layout::PRICE_ACCOUNT_EXPONENT_OFFSET == PRICE_ACCOUNT_EXPONENT_OFFSET);
+const _: () = assert!(core_layout::PRICE_ACCOUNT_NUM_OFFSET == PRICE_ACCOUNT_NUM_OFFSET);
+const _: () = assert!(core_layout::PRICE_ACCOUNT_NUM_QT_OFFSET == PRICE_ACCOUNT_NUM_QT_OFFSET);
+const _: () =
+    assert!(core_layout::PRICE_ACCOUNT_LAST_SLOT_OFFSET == PRICE_ACCOUNT_LAST_SLOT_OFFSET);
+const _: () =
+    assert!(core_layout::PRICE_ACCOUNT_TIMESTAMP_OFFSET == PRICE_ACCOUNT_TIMESTAMP_OFFSET);
+const _: () = assert!(core_layout::PRICE_ACCOUNT_MIN_PUB_OFFSET == PRICE_ACCOUNT_MIN_PUB_OFFSET);
+const _: () = assert!(
+    core_layout::PRICE_ACCOUNT_MAX_LATENCY_OFFSET == offset_of!(PriceAccount, max_latency_)
+);
+const _: () =
+    assert!(core_layout::PRICE_ACCOUNT_AGG_MODE_OFFSET == offset_of!(PriceAccount, agg_mode_));
+const _: () = assert!(
+    core_layout::PRICE_ACCOUNT_OUTLIER_BAND_OFFSET == offset_of!(PriceAccount, outlier_band_)
+);
+const _: () = assert!(
+    core_layout::PRICE_ACCOUNT_SKIPPED_SLOTS_OFFSET == offset_of!(PriceAccount, skipped_slots_)
+);
+const _: () = assert!(
+    core_layout::PRICE_ACCOUNT_LAYOUT_VERSION_OFFSET == PRICE_ACCOUNT_LAYOUT_VERSION_OFFSET
+);
+const _: () =
+    assert!(core_layout::PRICE_ACCOUNT_PREV_SLOT_OFFSET == PRICE_ACCOUNT_PREV_SLOT_OFFSET);
+const _: () =
+    assert!(core_layout::PRICE_ACCOUNT_PREV_PRICE_OFFSET == PRICE_ACCOUNT_PREV_PRICE_OFFSET);
+const _: () =
+    assert!(core_layout::PRICE_ACCOUNT_PREV_CONF_OFFSET == PRICE_ACCOUNT_PREV_CONF_OFFSET);
+const _: () = assert!(core_layout::PRICE_ACCOUNT_AGG_OFFSET == PRICE_ACCOUNT_AGG_OFFSET);
+const _: () = assert!(core_layout::PRICE_ACCOUNT_COMP_OFFSET == PRICE_ACCOUNT_COMP_OFFSET);
+const _: () = assert!(
+    core_layout::PRICE_ACCOUNT_PUBLISHER_STATS_OFFSET == PRICE_ACCOUNT_PUBLISHER_STATS_OFFSET
+);
+const _: () = assert!(core_layout::PRICE_ACCOUNT_FAST_AGG_OFFSET == PRICE_ACCOUNT_FAST_AGG_OFFSET);
+const _: () =
+    assert!(core_layout::PRICE_ACCOUNT_MIN_PRICE_OFFSET == offset_of!(PriceAccount, min_price_));
+const _: () =
+    assert!(core_layout::PRICE_ACCOUNT_MAX_PRICE_OFFSET == offset_of!(PriceAccount, max_price_));
+const _: () = assert!(
+    core_layout::PRICE_ACCOUNT_AGG_PUB_TIME_OFFSET == offset_of!(PriceAccount, agg_pub_time_)
+);
+const _: () = assert!(core_layout::PRICE_ACCOUNT_MIN_SIZE <= PRICE_ACCOUNT_SIZE);
+const _: () = assert!(core_layout::PRICE_COMPONENT_SIZE == PRICE_COMPONENT_SIZE);
+const _: () = assert!(core_layout::PRICE_COMPONENT_PUB_OFFSET == PRICE_COMPONENT_PUB_OFFSET);
+const _: () = assert!(core_layout::PRICE_COMPONENT_AGG_OFFSET == PRICE_COMPONENT_AGG_OFFSET);
+const _: () = assert!(core_layout::PRICE_COMPONENT_LATEST_OFFSET == PRICE_COMPONENT_LATEST_OFFSET);
+const _: () = assert!(core_layout::PRICE_INFO_PRICE_OFFSET == PRICE_INFO_PRICE_OFFSET);
+const _: () = assert!(core_layout::PRICE_INFO_CONF_OFFSET == PRICE_INFO_CONF_OFFSET);
+const _: () = assert!(core_layout::PRICE_INFO_STATUS_OFFSET == PRICE_INFO_STATUS_OFFSET);
+const _: () =
+    assert!(core_layout::PRICE_INFO_CORP_ACT_STATUS_OFFSET == PRICE_INFO_CORP_ACT_STATUS_OFFSET);
+const _: () = assert!(core_layout::PRICE_INFO_PUB_SLOT_OFFSET == PRICE_INFO_PUB_SLOT_OFFSET);
+const _: () =
+    assert!(core_layout::PRICE_ACCOUNT_EXTENSIONS_OFFSET == PRICE_ACCOUNT_EXTENSIONS_OFFSET);
+const _: () = assert!(core_layout::EXTENSION_HEADER_SIZE == EXTENSION_HEADER_SIZE);
+const _: () = assert!(core_layout::EXTENSION_HEADER_KIND_OFFSET == EXTENSION_HEADER_KIND_OFFSET);
+const _: () = assert!(core_layout::EXTENSION_HEADER_SIZE_OFFSET == EXTENSION_HEADER_SIZE_OFFSET);
+const _: () =
+    assert!(core_layout::EXTENSION_KIND_PUBLISHER_WEIGHTS == EXTENSION_KIND_PUBLISHER_WEIGHTS);
+const _: () = assert!(core_layout::PUBLISHER_WEIGHT_SIZE == PUBLISHER_WEIGHT_SIZE);
+const _: () =
+    assert!(core_layout::PUBLISHER_WEIGHT_PUBLISHER_OFFSET == PUBLISHER_WEIGHT_PUBLISHER_OFFSET);
+const _: () =
+    assert!(core_layout::PUBLISHER_WEIGHT_WEIGHT_OFFSET == PUBLISHER_WEIGHT_WEIGHT_OFFSET);
+const _: () = assert!(core_layout::PUBLISHER_STATS_SIZE == size_of::<PublisherStats>());
+const _: () =
+    assert!(core_layout::PUBLISHER_STATS_EXCLUDED_OFFSET == offset_of!(PublisherStats, excluded_));
+const _: () = assert!(
+    core_layout::PUBLISHER_STATS_PUBLISH_TIME_OFFSET == offset_of!(PublisherStats, publish_time_)
+);
@@ -2,13 +2,35 @@
 // Allow non upper case globals from C
 #![allow(non_upper_case_globals)]
 
+#[cfg(all(feature = "pythnet", feature = "solana"))]
+compile_error!("The `pythnet` and `solana` features are mutually exclusive");
+#[cfg(not(any(feature = "pythnet", feature = "solana")))]
+compile_error!("Either the `pythnet` or the `solana` feature must be enabled");
+
 mod accounts;
+mod aggregation;
+mod c_layout;
 mod c_oracle_header;
 mod deserialize;
 mod error;
+mod events;
 mod instruction;
+pub mod layout;
+mod messages;
+mod migrations;
 mod processor;
 mod utils;
+#[cfg(feature = "wormhole")]
+mod wormhole;
+
+#[cfg(any(test, feature = "library"))]
+pub mod client;
+
+#[cfg(any(test, feature = "library"))]
+pub mod sdk;
+
+#[cfg(any(test, feature = "fuzz"))]
+pub mod fuzz;
 
 #[cfg(test)]
 mod tests;
@@ -28,6 +50,8 @@ pub use accounts::MessageType;
 #[cfg(feature = "library")]
 pub use accounts::{
     AccountHeader,
+    CompressedPriceAccount,
+    DerivedPriceAccount,
     MappingAccount,
     PermissionAccount,
     PriceAccount,
@@ -35,8 +59,34 @@ pub use accounts::{
     PriceEma,
     PriceInfo,
     ProductAccount,
+    ProductAttributeKey,
     PythAccount,
 };
+#[cfg(feature = "library")]
+pub use aggregation::{
+    aggregate,
+    aggregation_algorithm,
+    AggParams,
+    Aggregate,
+    AggregationAlgorithm,
+    Quote,
+};
+#[cfg(feature = "library")]
+pub use events::{
+    AggregatePriceUpdate,
+    Event,
+};
+#[cfg(feature = "library")]
+pub use instruction::{
+    OracleCommand,
+    OracleInstruction,
+    PriceUpdate,
+};
+#[cfg(all(feature = "library", feature = "wormhole"))]
+pub use wormhole::{
+    PriceAttestation,
+    WORMHOLE_PROGRAM_ID,
+};
 use {
     crate::error::OracleError,
     processor::process_instruction,
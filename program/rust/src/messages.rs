@@ -0,0 +1,51 @@
+//! Price messages appended to message buffer accounts on each successful aggregation.
+//!
+//! External attesters read the messages of a message buffer account and commit to them, e.g. in a
+//! Merkle tree, to prove the prices to other chains. A message is a fixed-size `#[repr(C)]` struct
+//! made of little-endian integers and byte arrays laid out without padding, so its serialization,
+//! and the leaf of an attester's tree, is simply the bytes of the struct.
+
+use {
+    crate::accounts::PriceAccount,
+    bytemuck::{
+        Pod,
+        Zeroable,
+    },
+    solana_program::pubkey::Pubkey,
+};
+
+/// Aggregate price of a price account after a successful aggregation
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Pod, Zeroable)]
+pub struct PriceMessage {
+    /// Position of the message among all the messages appended to its message buffer, starting
+    /// at 0
+    pub sequence:      u64,
+    pub price_account: Pubkey,
+    pub price:         i64,
+    pub conf:          u64,
+    pub exponent:      i32,
+    /// Stable index of the price feed, 0 if it has none (see `PriceAccount::feed_index_`)
+    pub feed_index:    u32,
+    /// Unix timestamp of the aggregation
+    pub publish_time:  i64,
+    /// Slot of the aggregation
+    pub slot:          u64,
+}
+
+impl PriceMessage {
+    /// The message of the last aggregate of `price_data`, the price account `price_account`. Its
+    /// sequence number is set when appending it to a message buffer.
+    pub fn from_aggregate(price_account: &Pubkey, price_data: &PriceAccount) -> Self {
+        PriceMessage {
+            sequence:      0,
+            price_account: *price_account,
+            price:         price_data.agg_.price_,
+            conf:          price_data.agg_.conf_,
+            exponent:      price_data.exponent,
+            feed_index:    price_data.feed_index_,
+            publish_time:  price_data.timestamp_,
+            slot:          price_data.agg_.pub_slot_,
+        }
+    }
+}
@@ -0,0 +1,173 @@
+//! In-place migrations of the layout of price accounts.
+//!
+//! The layout version of a price account is stored in its `layout_version_`, in the fixed part of
+//! the account that was unused before layout versions, so that it is 0 on all the price accounts
+//! created before them whatever the rest of their data. Each migration upgrades an account from
+//! some layout version to the next one, and `MIGRATIONS[n]` upgrades from version `n` to version
+//! `n + 1`, so the current layout version is the number of registered migrations. Price accounts
+//! with an older layout are migrated lazily, the first time they are loaded as a writable account
+//! (see `load_checked`). Read-only loads get a migrated copy instead (see
+//! `load_checked_read_only`).
+//!
+//! To change the layout of price accounts, add the new fields in place of unused space and
+//! register a migration that fills them in, so that accounts don't need a bespoke instruction.
+//! Every new field needs its own migration, even in space that older migrations cleared: accounts
+//! migrated by older versions of the program may have stale data there. Migrations must not
+//! change the size of the account.
+
+use {
+    crate::{
+        accounts::{
+            PriceAccount,
+            StatusChange,
+        },
+        error::OracleError,
+        layout::{
+            PRICE_ACCOUNT_PRICE_CUMULATIVE_OFFSET,
+            PRICE_ACCOUNT_PUBLISHER_STATS_OFFSET,
+        },
+    },
+    bytemuck::{
+        bytes_of_mut,
+        Zeroable,
+    },
+};
+
+/// Upgrade of a price account from one layout version to the next
+pub type Migration = fn(&mut PriceAccount) -> Result<(), OracleError>;
+
+/// The registered migrations, `MIGRATIONS[n]` upgrades price accounts from layout version `n`
+const MIGRATIONS: &[Migration] = &[
+    migrate_v0_to_v1,
+    migrate_v1_to_v2,
+    migrate_v2_to_v3,
+    migrate_v3_to_v4,
+    migrate_v4_to_v5,
+    migrate_v5_to_v6,
+    migrate_v6_to_v7,
+    migrate_v7_to_v8,
+    migrate_v8_to_v9,
+    migrate_v9_to_v10,
+];
+
+/// The layout version of the price accounts created by this program
+pub const PRICE_ACCOUNT_LAYOUT_VERSION: u8 = MIGRATIONS.len() as u8;
+
+/// Whether `price_account` has another layout version than the current one
+pub fn needs_migration(price_account: &PriceAccount) -> bool {
+    price_account.layout_version_ != PRICE_ACCOUNT_LAYOUT_VERSION
+}
+
+/// Upgrade `price_account` to the current layout version
+pub fn migrate(price_account: &mut PriceAccount) -> Result<(), OracleError> {
+    migrate_with(price_account, MIGRATIONS)
+}
+
+/// Upgrade `price_account` to the last layout version of `migrations` by running the migrations
+/// from its layout version on. Fails if the account has a newer layout version than the
+/// migrations support.
+pub fn migrate_with(
+    price_account: &mut PriceAccount,
+    migrations: &[Migration],
+) -> Result<(), OracleError> {
+    let from = usize::from(price_account.layout_version_);
+    let pending = migrations
+        .get(from..)
+        .ok_or(OracleError::UnsupportedAccountLayout)?;
+    for migration in pending {
+        migration(price_account)?;
+        price_account.layout_version_ += 1;
+    }
+    Ok(())
+}
+
+/// Layout version 0 is the layout of the price accounts created before layout versions, in which
+/// the space after the `PC_NUM_COMP` components in use belonged to the unused component slots.
+/// Live accounts can have stale components there, so the whole space is cleared, from the
+/// publisher statistics to the cumulative sums excluded, and every field it holds starts from its
+/// value when 0.
+fn migrate_v0_to_v1(price_account: &mut PriceAccount) -> Result<(), OracleError> {
+    bytes_of_mut(price_account)
+        [PRICE_ACCOUNT_PUBLISHER_STATS_OFFSET..PRICE_ACCOUNT_PRICE_CUMULATIVE_OFFSET]
+        .fill(0);
+    Ok(())
+}
+
+/// Layout version 2 adds the earliest publish time of the aggregate. It is cleared, as if none of
+/// the included quotes had a publish time.
+fn migrate_v1_to_v2(price_account: &mut PriceAccount) -> Result<(), OracleError> {
+    price_account.agg_pub_time_ = 0;
+    Ok(())
+}
+
+/// Layout version 3 adds the CPI guard. It is cleared, so that price updates are accepted from any
+/// caller as before.
+fn migrate_v2_to_v3(price_account: &mut PriceAccount) -> Result<(), OracleError> {
+    price_account.cpi_guard_ = 0;
+    price_account.cpi_callers_ = Zeroable::zeroed();
+    Ok(())
+}
+
+/// Layout version 4 adds the market hours. They are cleared, so that the market is always open.
+fn migrate_v3_to_v4(price_account: &mut PriceAccount) -> Result<(), OracleError> {
+    price_account.market_hours_ = Zeroable::zeroed();
+    Ok(())
+}
+
+/// Layout version 5 adds the price feed index. It is cleared, so that `init_price_feed_index` can
+/// assign one.
+fn migrate_v4_to_v5(price_account: &mut PriceAccount) -> Result<(), OracleError> {
+    price_account.feed_index_ = 0;
+    Ok(())
+}
+
+/// Layout version 6 adds the price update fee. It is cleared, so that price updates stay free.
+fn migrate_v5_to_v6(price_account: &mut PriceAccount) -> Result<(), OracleError> {
+    price_account.fee_lamports_ = 0;
+    Ok(())
+}
+
+/// Layout version 7 adds the aggregation rotation. It is cleared, so that the first price update of
+/// each slot aggregates as before.
+fn migrate_v6_to_v7(price_account: &mut PriceAccount) -> Result<(), OracleError> {
+    price_account.agg_rotation_ = 0;
+    Ok(())
+}
+
+/// Layout version 8 adds the price history on Pythnet. Its space belonged to the price components
+/// of older versions of the program, so it is cleared rather than assumed to be 0.
+#[cfg_attr(feature = "solana", allow(unused_variables))]
+fn migrate_v7_to_v8(price_account: &mut PriceAccount) -> Result<(), OracleError> {
+    #[cfg(feature = "pythnet")]
+    {
+        price_account.price_history_ = Zeroable::zeroed();
+    }
+    Ok(())
+}
+
+/// Layout version 9 adds the last status change, and the status history on Pythnet. Their space
+/// belonged to the price components of older versions of the program, so it is cleared. The
+/// actual last change of the status is unknown, it is recorded as a change to the current status
+/// at the last aggregation so that the status has been the same at least since then.
+fn migrate_v8_to_v9(price_account: &mut PriceAccount) -> Result<(), OracleError> {
+    let status = price_account.agg_.status_ as u8;
+    price_account.status_change_ = StatusChange {
+        slot_: price_account.agg_.pub_slot_,
+        timestamp_: price_account.timestamp_,
+        prev_status_: status,
+        status_: status,
+        ..StatusChange::zeroed()
+    };
+    #[cfg(feature = "pythnet")]
+    {
+        price_account.status_history_ = Zeroable::zeroed();
+    }
+    Ok(())
+}
+
+/// Layout version 10 adds the max confidence ratio in place of the unused end of the market hours.
+/// It is cleared, so that the aggregates of existing price accounts are published as before.
+fn migrate_v9_to_v10(price_account: &mut PriceAccount) -> Result<(), OracleError> {
+    price_account.max_conf_ratio_ = 0;
+    Ok(())
+}
@@ -4,6 +4,7 @@ use {
         instruction::{
             load_command_header_checked,
             OracleCommand,
+            OracleInstruction,
         },
     },
     solana_program::{
@@ -13,39 +14,126 @@ use {
     },
 };
 
+mod add_mapping;
 mod add_price;
 mod add_product;
 mod add_publisher;
+mod compact_components;
+mod compress_price_account;
 mod del_price;
 mod del_product;
 mod del_publisher;
+mod grant_permission;
+mod halt_price;
+mod heartbeat;
+mod init_component_log;
+mod init_derived_price;
 mod init_mapping;
+mod init_mapping_pda;
+mod init_message_buffer;
 mod init_price;
+mod init_price_feed_index;
+mod init_symbol_index;
+mod init_time_machine;
+mod resign_publisher;
+mod resume_price;
+mod revoke_permission;
+mod rotate_publisher;
+mod set_agg_interval;
+mod set_agg_mode;
+mod set_agg_rotation;
+mod set_co_signing;
+mod set_cpi_guard;
+mod set_exponent;
+mod set_fee;
+mod set_market_hours;
+mod set_max_conf_ratio;
 mod set_max_latency;
+mod set_max_updates_per_slot;
 mod set_min_pub;
+mod set_outlier_band;
+mod set_price_bounds;
+mod set_publisher_excluded;
+mod set_publisher_pruning;
+mod set_publisher_weight;
+mod set_reference_price;
+mod upd_derived;
 mod upd_permissions;
 mod upd_price;
 mod upd_product;
+mod withdraw_fee;
+
+#[cfg(feature = "devnet")]
+mod force_aggregate;
+#[cfg(feature = "devnet")]
+mod reset_price;
 
 pub use {
+    add_mapping::add_mapping,
     add_price::add_price,
     add_product::add_product,
-    add_publisher::add_publisher,
+    add_publisher::{
+        add_publisher,
+        add_publisher_with_expiry,
+    },
+    compact_components::compact_components,
+    compress_price_account::compress_price_account,
     del_price::del_price,
     del_product::del_product,
     del_publisher::del_publisher,
+    grant_permission::grant_permission,
+    halt_price::halt_price,
+    heartbeat::heartbeat,
+    init_component_log::init_component_log,
+    init_derived_price::init_derived_price,
     init_mapping::init_mapping,
+    init_mapping_pda::init_mapping_pda,
+    init_message_buffer::init_message_buffer,
     init_price::init_price,
+    init_price_feed_index::init_price_feed_index,
+    init_symbol_index::init_symbol_index,
+    init_time_machine::init_time_machine,
+    resign_publisher::resign_publisher,
+    resume_price::resume_price,
+    revoke_permission::revoke_permission,
+    rotate_publisher::rotate_publisher,
+    set_agg_interval::set_agg_interval,
+    set_agg_mode::set_agg_mode,
+    set_agg_rotation::set_agg_rotation,
+    set_co_signing::set_co_signing,
+    set_cpi_guard::set_cpi_guard,
+    set_exponent::set_exponent,
+    set_fee::set_fee,
+    set_market_hours::set_market_hours,
+    set_max_conf_ratio::set_max_conf_ratio,
     set_max_latency::set_max_latency,
+    set_max_updates_per_slot::set_max_updates_per_slot,
     set_min_pub::set_min_pub,
+    set_outlier_band::set_outlier_band,
+    set_price_bounds::set_price_bounds,
+    set_publisher_excluded::set_publisher_excluded,
+    set_publisher_pruning::set_publisher_pruning,
+    set_publisher_weight::set_publisher_weight,
+    set_reference_price::set_reference_price,
+    upd_derived::upd_derived,
     upd_permissions::upd_permissions,
     upd_price::{
-        c_upd_aggregate,
         c_upd_twap,
         upd_price,
+        upd_price_batch,
         upd_price_no_fail_on_error,
+        upd_price_scatter,
+        upd_price_with_budget,
+        upd_price_with_time,
+        upd_price_with_trade,
     },
     upd_product::upd_product,
+    withdraw_fee::withdraw_fee,
+};
+#[cfg(feature = "devnet")]
+pub use {
+    force_aggregate::force_aggregate,
+    reset_price::reset_price,
 };
 
 /// Dispatch to the right instruction in the oracle.
@@ -58,7 +146,7 @@ pub fn process_instruction(
 
     match load_command_header_checked(instruction_data)? {
         InitMapping => init_mapping(program_id, accounts, instruction_data),
-        AddMapping => Err(OracleError::UnrecognizedInstruction.into()),
+        AddMapping => add_mapping(program_id, accounts, instruction_data),
         AddProduct => add_product(program_id, accounts, instruction_data),
         UpdProduct => upd_product(program_id, accounts, instruction_data),
         AddPrice => add_price(program_id, accounts, instruction_data),
@@ -79,5 +167,76 @@ pub fn process_instruction(
         DelProduct => del_product(program_id, accounts, instruction_data),
         UpdPermissions => upd_permissions(program_id, accounts, instruction_data),
         SetMaxLatency => set_max_latency(program_id, accounts, instruction_data),
+        UpdPriceBatch => upd_price_batch(program_id, accounts, instruction_data),
+        SetPublisherWeight => set_publisher_weight(program_id, accounts, instruction_data),
+        SetAggMode => set_agg_mode(program_id, accounts, instruction_data),
+        HaltPrice => halt_price(program_id, accounts, instruction_data),
+        ResumePrice => resume_price(program_id, accounts, instruction_data),
+        SetOutlierBand => set_outlier_band(program_id, accounts, instruction_data),
+        SetExponent => set_exponent(program_id, accounts, instruction_data),
+        ResignPublisher => resign_publisher(program_id, accounts, instruction_data),
+        Borsh => process_borsh_instruction(program_id, accounts, instruction_data),
+        #[cfg(feature = "devnet")]
+        ForceAggregate => force_aggregate(program_id, accounts, instruction_data),
+        #[cfg(feature = "devnet")]
+        ResetPrice => reset_price(program_id, accounts, instruction_data),
+        #[cfg(not(feature = "devnet"))]
+        ForceAggregate | ResetPrice => {
+            solana_program::msg!(
+                "Devnet instructions are not available in this build. Bailing out!"
+            );
+            Err(OracleError::UnrecognizedInstruction.into())
+        }
+        SetMaxUpdatesPerSlot => set_max_updates_per_slot(program_id, accounts, instruction_data),
+        SetPriceBounds => set_price_bounds(program_id, accounts, instruction_data),
+        UpdPriceWithTime => upd_price_with_time(program_id, accounts, instruction_data),
+        SetCpiGuard => set_cpi_guard(program_id, accounts, instruction_data),
+        SetMarketHours => set_market_hours(program_id, accounts, instruction_data),
+        InitPriceFeedIndex => init_price_feed_index(program_id, accounts, instruction_data),
+        InitMessageBuffer => init_message_buffer(program_id, accounts, instruction_data),
+        RotatePublisher => rotate_publisher(program_id, accounts, instruction_data),
+        SetFee => set_fee(program_id, accounts, instruction_data),
+        WithdrawFee => withdraw_fee(program_id, accounts, instruction_data),
+        GrantPermission => grant_permission(program_id, accounts, instruction_data),
+        RevokePermission => revoke_permission(program_id, accounts, instruction_data),
+        SetPublisherExcluded => set_publisher_excluded(program_id, accounts, instruction_data),
+        InitMappingPda => init_mapping_pda(program_id, accounts, instruction_data),
+        UpdPriceScatter => upd_price_scatter(program_id, accounts, instruction_data),
+        SetAggRotation => set_agg_rotation(program_id, accounts, instruction_data),
+        CompressPriceAccount => compress_price_account(program_id, accounts, instruction_data),
+        SetReferencePrice => set_reference_price(program_id, accounts, instruction_data),
+        UpdPriceWithTrade => upd_price_with_trade(program_id, accounts, instruction_data),
+        SetCoSigning => set_co_signing(program_id, accounts, instruction_data),
+        CompactComponents => compact_components(program_id, accounts, instruction_data),
+        InitSymbolIndex => init_symbol_index(program_id, accounts, instruction_data),
+        AddPublisherWithExpiry => add_publisher_with_expiry(program_id, accounts, instruction_data),
+        SetMaxConfRatio => set_max_conf_ratio(program_id, accounts, instruction_data),
+        Heartbeat => heartbeat(program_id, accounts, instruction_data),
+        InitComponentLog => init_component_log(program_id, accounts, instruction_data),
+        InitDerivedPrice => init_derived_price(program_id, accounts, instruction_data),
+        UpdDerived => upd_derived(program_id, accounts, instruction_data),
+        SetPublisherPruning => set_publisher_pruning(program_id, accounts, instruction_data),
+        SetAggInterval => set_agg_interval(program_id, accounts, instruction_data),
+        UpdPriceWithBudget => upd_price_with_budget(program_id, accounts, instruction_data),
+        InitTimeMachine => init_time_machine(program_id, accounts, instruction_data),
     }
 }
+
+/// Process an instruction in the Borsh layout by converting it to the legacy layout of its
+/// command. The conversion never produces a `Borsh` command, so this recurses only once.
+fn process_borsh_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let legacy_data = OracleInstruction::unpack(instruction_data)?.pack_legacy()?;
+
+    // The processors load their arguments in place, which requires the 8-byte alignment of the
+    // instruction data provided by the runtime
+    let mut aligned_data = vec![0u64; (legacy_data.len() + 7) / 8];
+    let aligned_bytes =
+        &mut bytemuck::cast_slice_mut::<u64, u8>(&mut aligned_data)[..legacy_data.len()];
+    aligned_bytes.copy_from_slice(&legacy_data);
+
+    process_instruction(program_id, accounts, aligned_bytes)
+}
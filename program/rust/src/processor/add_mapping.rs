@@ -0,0 +1,81 @@
+use {
+    crate::{
+        accounts::{
+            MappingAccount,
+            PythAccount,
+        },
+        c_oracle_header::PC_MAP_TABLE_SIZE,
+        deserialize::{
+            load,
+            load_checked,
+        },
+        instruction::CommandHeader,
+        utils::{
+            check_distinct_accounts,
+            check_permissioned_funding_account,
+            check_valid_funding_account,
+            pyth_assert,
+        },
+        OracleError,
+    },
+    solana_program::{
+        account_info::AccountInfo,
+        entrypoint::ProgramResult,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+};
+
+/// Initialize a new mapping account and link it after the tail of the mapping account list. The
+/// tail mapping account must be full, new products are then added to the new mapping account.
+// account[0] funding account       [signer writable]
+// account[1] tail mapping account  [signer writable]
+// account[2] new mapping account   [signer writable]
+pub fn add_mapping(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let (funding_account, tail_mapping_account, new_mapping_account, permissions_account) =
+        match accounts {
+            [x, y, z, p] => Ok((x, y, z, p)),
+            _ => Err(OracleError::InvalidNumberOfAccounts),
+        }?;
+    check_distinct_accounts(&[
+        funding_account,
+        tail_mapping_account,
+        new_mapping_account,
+        permissions_account,
+    ])?;
+
+    let hdr = load::<CommandHeader>(instruction_data)?;
+
+    check_valid_funding_account(funding_account)?;
+    check_permissioned_funding_account(
+        program_id,
+        tail_mapping_account,
+        funding_account,
+        permissions_account,
+        hdr,
+    )?;
+    check_permissioned_funding_account(
+        program_id,
+        new_mapping_account,
+        funding_account,
+        permissions_account,
+        hdr,
+    )?;
+
+    let mut tail_mapping_data = load_checked::<MappingAccount>(tail_mapping_account, hdr.version)?;
+    // The tail mapping account must be full and the last one of the list
+    pyth_assert(
+        tail_mapping_data.number_of_products == PC_MAP_TABLE_SIZE
+            && tail_mapping_data.next_mapping_account == Pubkey::default(),
+        ProgramError::InvalidArgument,
+    )?;
+
+    MappingAccount::initialize(new_mapping_account, hdr.version)?;
+    tail_mapping_data.next_mapping_account = *new_mapping_account.key;
+
+    Ok(())
+}
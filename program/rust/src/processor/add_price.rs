@@ -4,6 +4,7 @@ use {
             PriceAccount,
             ProductAccount,
             PythAccount,
+            SymbolIndexAccount,
         },
         c_oracle_header::{
             PC_PTYPE_UNKNOWN,
@@ -14,11 +15,15 @@ use {
             load_checked,
         },
         instruction::AddPriceArgs,
+        migrations::PRICE_ACCOUNT_LAYOUT_VERSION,
         utils::{
+            check_distinct_accounts,
             check_exponent_range,
             check_permissioned_funding_account,
             check_valid_funding_account,
+            check_valid_writable_account,
             pyth_assert,
+            split_symbol_index_account,
         },
         OracleError,
     },
@@ -30,10 +35,14 @@ use {
     },
 };
 
-/// Add new price account to a product account
+/// Add new price account to a product account. If a symbol index account (see
+/// `SymbolIndexAccount`) is appended, the price account is indexed under the symbol of the
+/// product, which must have one and not be indexed yet.
 // account[0] funding account       [signer writable]
 // account[1] product account       [signer writable]
 // account[2] new price account     [signer writable]
+// account[3] permissions account   []
+// account[4] symbol index account  [writable] (optional)
 pub fn add_price(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
@@ -48,10 +57,18 @@ pub fn add_price(
     )?;
 
 
+    let (accounts, symbol_index_account) =
+        split_symbol_index_account(program_id, accounts, cmd_args.header.version);
     let (funding_account, product_account, price_account, permissions_account) = match accounts {
         [x, y, z, p] => Ok((x, y, z, p)),
         _ => Err(OracleError::InvalidNumberOfAccounts),
     }?;
+    check_distinct_accounts(&[
+        funding_account,
+        product_account,
+        price_account,
+        permissions_account,
+    ])?;
 
     check_valid_funding_account(funding_account)?;
     check_permissioned_funding_account(
@@ -69,16 +86,30 @@ pub fn add_price(
         &cmd_args.header,
     )?;
 
-    let mut product_data =
-        load_checked::<ProductAccount>(product_account, cmd_args.header.version)?;
+    if let Some(symbol_index_account) = symbol_index_account {
+        check_valid_writable_account(program_id, symbol_index_account)?;
+    }
+
+    {
+        let mut product_data =
+            load_checked::<ProductAccount>(product_account, cmd_args.header.version)?;
+
+        let mut price_data = PriceAccount::initialize(price_account, cmd_args.header.version)?;
+        price_data.exponent = cmd_args.exponent;
+        price_data.price_type = cmd_args.price_type;
+        price_data.product_account = *product_account.key;
+        price_data.next_price_account = product_data.first_price_account;
+        price_data.min_pub_ = PRICE_ACCOUNT_DEFAULT_MIN_PUB;
+        price_data.layout_version_ = PRICE_ACCOUNT_LAYOUT_VERSION;
+        product_data.first_price_account = *price_account.key;
+    }
 
-    let mut price_data = PriceAccount::initialize(price_account, cmd_args.header.version)?;
-    price_data.exponent = cmd_args.exponent;
-    price_data.price_type = cmd_args.price_type;
-    price_data.product_account = *product_account.key;
-    price_data.next_price_account = product_data.first_price_account;
-    price_data.min_pub_ = PRICE_ACCOUNT_DEFAULT_MIN_PUB;
-    product_data.first_price_account = *price_account.key;
+    if let Some(symbol_index_account) = symbol_index_account {
+        let symbol =
+            ProductAccount::read_symbol(product_account)?.ok_or(OracleError::MissingSymbol)?;
+        load_checked::<SymbolIndexAccount>(symbol_index_account, cmd_args.header.version)?
+            .insert(&SymbolIndexAccount::symbol_hash(&symbol), price_account.key)?;
+    }
 
     Ok(())
 }
@@ -12,7 +12,9 @@ use {
             load_checked,
         },
         instruction::CommandHeader,
+        layout::MAPPING_ACCOUNT_PRODUCTS_LIST_OFFSET,
         utils::{
+            check_distinct_accounts,
             check_permissioned_funding_account,
             check_valid_funding_account,
             pyth_assert,
@@ -26,10 +28,7 @@ use {
         program_error::ProgramError,
         pubkey::Pubkey,
     },
-    std::mem::{
-        size_of,
-        size_of_val,
-    },
+    std::mem::size_of,
 };
 
 /// Initialize and add new product reference data account
@@ -46,6 +45,12 @@ pub fn add_product(
             [x, y, z, p] => Ok((x, y, z, p)),
             _ => Err(OracleError::InvalidNumberOfAccounts),
         }?;
+    check_distinct_accounts(&[
+        funding_account,
+        tail_mapping_account,
+        new_product_account,
+        permissions_account,
+    ])?;
 
     let hdr = load::<CommandHeader>(instruction_data)?;
 
@@ -78,10 +83,8 @@ pub fn add_product(
     let current_index: usize = try_convert(mapping_data.number_of_products)?;
     mapping_data.products_list[current_index] = *new_product_account.key;
     mapping_data.number_of_products += 1;
-    mapping_data.header.size = try_convert::<_, u32>(
-        size_of::<MappingAccount>() - size_of_val(&mapping_data.products_list),
-    )? + mapping_data.number_of_products
-        * try_convert::<_, u32>(size_of::<Pubkey>())?;
+    mapping_data.header.size = try_convert::<_, u32>(MAPPING_ACCOUNT_PRODUCTS_LIST_OFFSET)?
+        + mapping_data.number_of_products * try_convert::<_, u32>(size_of::<Pubkey>())?;
 
     update_product_metadata(instruction_data, new_product_account, hdr.version)?;
 
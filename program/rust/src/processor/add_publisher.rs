@@ -3,6 +3,8 @@ use {
         accounts::{
             PriceAccount,
             PriceComponent,
+            PublisherExpiries,
+            PublisherStats,
             PythAccount,
         },
         c_oracle_header::PC_NUM_COMP,
@@ -10,8 +12,12 @@ use {
             load,
             load_checked,
         },
-        instruction::AddPublisherArgs,
+        instruction::{
+            AddPublisherArgs,
+            AddPublisherWithExpiryArgs,
+        },
         utils::{
+            check_distinct_accounts,
             check_permissioned_funding_account,
             check_valid_funding_account,
             pyth_assert,
@@ -29,6 +35,7 @@ use {
             sol_memset,
         },
         pubkey::Pubkey,
+        system_program::check_id,
     },
     std::mem::size_of,
 };
@@ -52,6 +59,7 @@ pub fn add_publisher(
         [x, y, p] => Ok((x, y, p)),
         _ => Err(OracleError::InvalidNumberOfAccounts),
     }?;
+    check_distinct_accounts(&[funding_account, price_account, permissions_account])?;
 
     check_valid_funding_account(funding_account)?;
     check_permissioned_funding_account(
@@ -63,22 +71,113 @@ pub fn add_publisher(
     )?;
 
 
-    let mut price_data = load_checked::<PriceAccount>(price_account, cmd_args.header.version)?;
+    {
+        let mut price_data = load_checked::<PriceAccount>(price_account, cmd_args.header.version)?;
+
+        // Use the call with the default pubkey (000..) as a trigger to sort the publishers as a
+        // migration step from unsorted list to sorted list.
+        if cmd_args.publisher == Pubkey::default() {
+            let num_comps = try_convert::<u32, usize>(price_data.num_)?;
+            let PriceAccount {
+                comp_,
+                publisher_stats_,
+                ..
+            } = &mut *price_data;
+            sort_price_comps(comp_, publisher_stats_, num_comps)?;
+            return Ok(());
+        }
 
-    // Use the call with the default pubkey (000..) as a trigger to sort the publishers as a
-    // migration step from unsorted list to sorted list.
-    if cmd_args.publisher == Pubkey::default() {
-        let num_comps = try_convert::<u32, usize>(price_data.num_)?;
-        sort_price_comps(&mut price_data.comp_, num_comps)?;
-        return Ok(());
+        add_component(&mut price_data, &cmd_args.publisher)?;
     }
 
+    // A publisher added again doesn't keep the expiry, the pruning state and the weight it was
+    // previously added with
+    PriceAccount::replace_publisher_expiry(price_account, None, &cmd_args.publisher)?;
+    PriceAccount::replace_publisher_pruning(price_account, None, &cmd_args.publisher)?;
+    PriceAccount::replace_publisher_weight(price_account, None, &cmd_args.publisher)
+}
+
+/// Add a publisher to a price account, like `add_publisher`, whose price updates are rejected
+/// after `expiry_slot` (see `PriceAccount::read_publisher_expiry`). Price accounts created
+/// without room for the expiries are resized, the funding account paying for the rent of the
+/// extra space.
+// account[0] funding account       [signer writable]
+// account[1] price account         [signer writable]
+// account[2] permissions account   []
+// account[3] system program        []
+pub fn add_publisher_with_expiry(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let cmd_args = load::<AddPublisherWithExpiryArgs>(instruction_data)?;
+
+    pyth_assert(
+        instruction_data.len() == size_of::<AddPublisherWithExpiryArgs>()
+            && cmd_args.publisher != Pubkey::default(),
+        ProgramError::InvalidArgument,
+    )?;
+
+    let (funding_account, price_account, permissions_account, system_program) = match accounts {
+        [x, y, p, s] => Ok((x, y, p, s)),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
+    }?;
+    check_distinct_accounts(&[
+        funding_account,
+        price_account,
+        permissions_account,
+        system_program,
+    ])?;
+
+    check_valid_funding_account(funding_account)?;
+    check_permissioned_funding_account(
+        program_id,
+        price_account,
+        funding_account,
+        permissions_account,
+        &cmd_args.header,
+    )?;
+    pyth_assert(
+        check_id(system_program.key),
+        OracleError::InvalidSystemAccount.into(),
+    )?;
+
+    let publishers = {
+        let mut price_data = load_checked::<PriceAccount>(price_account, cmd_args.header.version)?;
+        add_component(&mut price_data, &cmd_args.publisher)?;
+        let num_comps = try_convert::<u32, usize>(price_data.num_)?;
+        price_data.comp_[..num_comps]
+            .iter()
+            .map(|component| component.pub_)
+            .collect::<Vec<_>>()
+    };
+
+    // Make room for the expiries in price accounts created without it
+    PriceAccount::add_extension::<PublisherExpiries>(
+        price_account,
+        funding_account,
+        system_program,
+    )?;
+
+    PriceAccount::replace_publisher_pruning(price_account, None, &cmd_args.publisher)?;
+    PriceAccount::replace_publisher_weight(price_account, None, &cmd_args.publisher)?;
+    PriceAccount::set_publisher_expiry(
+        price_account,
+        &cmd_args.publisher,
+        cmd_args.expiry_slot,
+        &publishers,
+    )
+}
+
+/// Add a zeroed component of `publisher` to `price_data` and sort the components by publisher.
+/// Fails if the price account is full or if `publisher` is already one of its publishers.
+fn add_component(price_data: &mut PriceAccount, publisher: &Pubkey) -> Result<(), ProgramError> {
     if price_data.num_ >= PC_NUM_COMP {
         return Err(ProgramError::InvalidArgument);
     }
 
     for i in 0..(try_convert::<u32, usize>(price_data.num_)?) {
-        if cmd_args.publisher == price_data.comp_[i].pub_ {
+        if *publisher == price_data.comp_[i].pub_ {
             return Err(ProgramError::InvalidArgument);
         }
     }
@@ -89,13 +188,23 @@ pub fn add_publisher(
         0,
         size_of::<PriceComponent>(),
     );
-    price_data.comp_[current_index].pub_ = cmd_args.publisher;
+    sol_memset(
+        bytes_of_mut(&mut price_data.publisher_stats_[current_index]),
+        0,
+        size_of::<PublisherStats>(),
+    );
+    price_data.comp_[current_index].pub_ = *publisher;
     price_data.num_ += 1;
 
     // Sort the publishers in the list
     {
         let num_comps = try_convert::<u32, usize>(price_data.num_)?;
-        sort_price_comps(&mut price_data.comp_, num_comps)?;
+        let PriceAccount {
+            comp_,
+            publisher_stats_,
+            ..
+        } = price_data;
+        sort_price_comps(comp_, publisher_stats_, num_comps)?;
     }
 
     price_data.header.size = try_convert::<_, u32>(PriceAccount::INITIAL_SIZE)?;
@@ -150,12 +259,21 @@ fn heapsort(v: &mut [(Pubkey, usize)]) {
 /// The rust stdlib implementation is not used because it uses a fast sort variant that has
 /// a large code size.
 ///
+/// The statistics of each publisher in `stats` are moved along with its price component.
+///
 /// num_publishers is the number of publishers in the list that should be sorted. It is explicitly
 /// passed to avoid callers mistake of passing the full slice which may contain uninitialized values.
-fn sort_price_comps(comps: &mut [PriceComponent], num_comps: usize) -> Result<(), ProgramError> {
+pub fn sort_price_comps(
+    comps: &mut [PriceComponent],
+    stats: &mut [PublisherStats],
+    num_comps: usize,
+) -> Result<(), ProgramError> {
     let comps = comps
         .get_mut(..num_comps)
         .ok_or(ProgramError::InvalidArgument)?;
+    let stats = stats
+        .get_mut(..num_comps)
+        .ok_or(ProgramError::InvalidArgument)?;
 
     // Publishers are likely sorted in ascending order but
     // heapsorts creates a max-heap so we reverse the order
@@ -183,6 +301,7 @@ fn sort_price_comps(comps: &mut [PriceComponent], num_comps: usize) -> Result<()
         // loop over from i to index again when we reach i again.
         keys[i].1 = index;
         comps.swap(i, index);
+        stats.swap(i, index);
     }
 
     Ok(())
@@ -192,22 +311,38 @@ fn sort_price_comps(comps: &mut [PriceComponent], num_comps: usize) -> Result<()
 mod test {
     use {
         super::*,
+        bytemuck::Zeroable,
         quickcheck_macros::quickcheck,
     };
 
+    /// Statistics that identify the component at `index`
+    fn stats_for(num_comps: usize) -> Vec<PublisherStats> {
+        (0..num_comps)
+            .map(|index| PublisherStats {
+                included_rounds_: index as u32,
+                ..PublisherStats::zeroed()
+            })
+            .collect()
+    }
+
     #[quickcheck]
     pub fn test_sort_price_comps(mut comps: Vec<PriceComponent>) {
-        let mut rust_std_sorted_comps = comps.clone();
-        rust_std_sorted_comps.sort_by_key(|x| x.pub_);
-
         let num_comps = comps.len();
+        let mut stats = stats_for(num_comps);
+
+        let mut rust_std_sorted = comps.iter().copied().zip(stats.clone()).collect::<Vec<_>>();
+        rust_std_sorted.sort_by_key(|x| x.0.pub_);
+        let (rust_std_sorted_comps, rust_std_sorted_stats): (Vec<_>, Vec<_>) =
+            rust_std_sorted.into_iter().unzip();
+
         assert_eq!(
-            sort_price_comps(&mut comps, num_comps + 1),
+            sort_price_comps(&mut comps, &mut stats, num_comps + 1),
             Err(ProgramError::InvalidArgument)
         );
 
-        assert_eq!(sort_price_comps(&mut comps, num_comps), Ok(()));
+        assert_eq!(sort_price_comps(&mut comps, &mut stats, num_comps), Ok(()));
         assert_eq!(comps, rust_std_sorted_comps);
+        assert_eq!(stats, rust_std_sorted_stats);
     }
 
     #[quickcheck]
@@ -220,12 +355,13 @@ mod test {
         } else {
             num_comps % comps.len()
         };
+        let mut stats = stats_for(comps.len());
 
         let mut rust_std_sorted_comps = comps.get(..num_comps).unwrap().to_vec();
         rust_std_sorted_comps.sort_by_key(|x| x.pub_);
 
 
-        assert_eq!(sort_price_comps(&mut comps, num_comps), Ok(()));
+        assert_eq!(sort_price_comps(&mut comps, &mut stats, num_comps), Ok(()));
         assert_eq!(comps.get(..num_comps).unwrap(), rust_std_sorted_comps);
     }
 }
@@ -0,0 +1,90 @@
+use {
+    crate::{
+        accounts::{
+            PriceAccount,
+            PriceComponent,
+            PublisherStats,
+        },
+        c_oracle_header::PC_NUM_COMP,
+        deserialize::{
+            load,
+            load_checked,
+        },
+        instruction::CommandHeader,
+        utils::{
+            check_distinct_accounts,
+            check_permissioned_funding_account,
+            check_valid_funding_account,
+            pyth_assert,
+            try_convert,
+        },
+        OracleError,
+    },
+    bytemuck::Zeroable,
+    solana_program::{
+        account_info::AccountInfo,
+        entrypoint::ProgramResult,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+    std::mem::size_of,
+};
+
+/// Remove the components without a publisher from a price account and zero the slots after its
+/// last component (see `remove_empty_components`). The account keeps its size, as price accounts
+/// have a fixed number of component slots.
+// account[0] funding account       [signer writable]
+// account[1] price account         [signer writable]
+// account[2] permissions account   []
+pub fn compact_components(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let cmd = load::<CommandHeader>(instruction_data)?;
+
+    pyth_assert(
+        instruction_data.len() == size_of::<CommandHeader>(),
+        ProgramError::InvalidArgument,
+    )?;
+
+    let (funding_account, price_account, permissions_account) = match accounts {
+        [x, y, p] => Ok((x, y, p)),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
+    }?;
+    check_distinct_accounts(&[funding_account, price_account, permissions_account])?;
+
+    check_valid_funding_account(funding_account)?;
+    check_permissioned_funding_account(
+        program_id,
+        price_account,
+        funding_account,
+        permissions_account,
+        cmd,
+    )?;
+
+    let mut price_data = load_checked::<PriceAccount>(price_account, cmd.version)?;
+    remove_empty_components(&mut price_data)
+}
+
+/// Remove the components of `price_data` whose publisher is the default pubkey, which no publisher
+/// can update, shifting the following components (and their statistics) down to keep their order.
+/// Every slot after the remaining components is zeroed, so that no stale component is left past
+/// `num_`.
+pub fn remove_empty_components(price_data: &mut PriceAccount) -> ProgramResult {
+    let num_components = try_convert::<u32, usize>(price_data.num_)?.min(PC_NUM_COMP as usize);
+    let mut num_kept = 0;
+    for i in 0..num_components {
+        if price_data.comp_[i].pub_ != Pubkey::default() {
+            price_data.comp_[num_kept] = price_data.comp_[i];
+            price_data.publisher_stats_[num_kept] = price_data.publisher_stats_[i];
+            num_kept += 1;
+        }
+    }
+    for i in num_kept..PC_NUM_COMP as usize {
+        price_data.comp_[i] = PriceComponent::zeroed();
+        price_data.publisher_stats_[i] = PublisherStats::zeroed();
+    }
+    price_data.num_ = try_convert(num_kept)?;
+    Ok(())
+}
@@ -0,0 +1,93 @@
+use {
+    crate::{
+        accounts::{
+            CompressedPriceAccount,
+            PriceAccount,
+        },
+        c_oracle_header::PC_STATUS_HALTED,
+        deserialize::{
+            load,
+            load_account_as_mut,
+            load_checked,
+        },
+        instruction::CommandHeader,
+        utils::{
+            check_distinct_accounts,
+            check_permissioned_funding_account,
+            check_valid_funding_account,
+            check_valid_writable_account,
+            get_rent,
+            pyth_assert,
+        },
+        OracleError,
+    },
+    solana_program::{
+        account_info::AccountInfo,
+        entrypoint::ProgramResult,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+    std::mem::size_of,
+};
+
+/// Compress a halted price account into a `CompressedPriceAccount`, which keeps its final
+/// aggregate and moving averages. The account is shrunk to the size of a compressed price account
+/// and the lamports above its new rent exemption are sent to the recipient account (the funding
+/// account if no recipient is provided). The compressed account can't be updated anymore.
+// account[0] funding account       [signer writable]
+// account[1] price account         [signer writable]
+// account[2] permissions account   []
+// account[3] recipient account     [writable] (optional)
+pub fn compress_price_account(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let cmd = load::<CommandHeader>(instruction_data)?;
+
+    pyth_assert(
+        instruction_data.len() == size_of::<CommandHeader>(),
+        ProgramError::InvalidArgument,
+    )?;
+
+    let (funding_account, price_account, permissions_account, recipient_account) = match accounts {
+        [x, y, p] => Ok((x, y, p, x)),
+        [x, y, p, r] => Ok((x, y, p, r)),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
+    }?;
+    check_distinct_accounts(&[funding_account, price_account, permissions_account])?;
+
+    check_valid_funding_account(funding_account)?;
+    check_permissioned_funding_account(
+        program_id,
+        price_account,
+        funding_account,
+        permissions_account,
+        cmd,
+    )?;
+    check_valid_writable_account(program_id, price_account)?;
+    pyth_assert(
+        recipient_account.key != price_account.key,
+        ProgramError::InvalidArgument,
+    )?;
+
+    let compressed_data = {
+        let price_data = load_checked::<PriceAccount>(price_account, cmd.version)?;
+        pyth_assert(
+            price_data.agg_.status_ == PC_STATUS_HALTED,
+            ProgramError::InvalidArgument,
+        )?;
+        CompressedPriceAccount::compress(&price_data)
+    };
+
+    // Shrinking the account leaves the remaining data as is, so it is overwritten entirely
+    price_account.realloc(size_of::<CompressedPriceAccount>(), false)?;
+    *load_account_as_mut::<CompressedPriceAccount>(price_account)? = compressed_data;
+
+    let rent = get_rent()?.minimum_balance(size_of::<CompressedPriceAccount>());
+    let excess_lamports = price_account.lamports().saturating_sub(rent);
+    **price_account.lamports.borrow_mut() -= excess_lamports;
+    **recipient_account.lamports.borrow_mut() += excess_lamports;
+
+    Ok(())
+}
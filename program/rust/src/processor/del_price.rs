@@ -1,21 +1,28 @@
 use {
     crate::{
         accounts::{
+            AccountHeader,
             PriceAccount,
             ProductAccount,
+            SymbolIndexAccount,
         },
         deserialize::{
             load,
+            load_account_as_mut,
             load_checked,
         },
         instruction::CommandHeader,
         utils::{
+            check_distinct_accounts,
             check_permissioned_funding_account,
             check_valid_funding_account,
+            check_valid_writable_account,
             pyth_assert,
+            split_symbol_index_account,
         },
         OracleError,
     },
+    bytemuck::Zeroable,
     solana_program::{
         account_info::AccountInfo,
         entrypoint::ProgramResult,
@@ -25,11 +32,19 @@ use {
 };
 
 /// Delete a price account. This function will remove the link between the price account and its
-/// corresponding product account, then transfer any SOL in the price account to the funding
-/// account. This function can only delete the first price account in the linked list of
+/// corresponding product account, clear the reference price of the product if it is the price
+/// account, zero the header of the price account, then transfer any SOL in
+/// the price account to the recipient account (the funding account if no recipient is provided).
+/// This function can only delete the first price account in the linked list of
+/// price accounts for the given product account.
+/// If a symbol index account (see `SymbolIndexAccount`) is appended, the price account is removed
+/// from the index if it is indexed under the symbol of the product.
 // account[0] funding account       [signer writable]
 // account[1] product account       [signer writable]
 // account[2] price account         [signer writable]
+// account[3] permissions account   []
+// account[4] recipient account     [writable] (optional)
+// account[n] symbol index account  [writable] (optional)
 /// Warning: This function is dangerous and will break any programs that depend on the deleted
 /// price account!
 pub fn del_price(
@@ -37,12 +52,21 @@ pub fn del_price(
     accounts: &[AccountInfo],
     instruction_data: &[u8],
 ) -> ProgramResult {
-    let (funding_account, product_account, price_account, permissions_account) = match accounts {
-        [w, x, y, p] => Ok((w, x, y, p)),
-        _ => Err(OracleError::InvalidNumberOfAccounts),
-    }?;
-
     let cmd_args = load::<CommandHeader>(instruction_data)?;
+    let (accounts, symbol_index_account) =
+        split_symbol_index_account(program_id, accounts, cmd_args.version);
+    let (funding_account, product_account, price_account, permissions_account, recipient_account) =
+        match accounts {
+            [w, x, y, p] => Ok((w, x, y, p, w)),
+            [w, x, y, p, r] => Ok((w, x, y, p, r)),
+            _ => Err(OracleError::InvalidNumberOfAccounts),
+        }?;
+    check_distinct_accounts(&[
+        funding_account,
+        product_account,
+        price_account,
+        permissions_account,
+    ])?;
 
     check_valid_funding_account(funding_account)?;
     check_permissioned_funding_account(
@@ -59,6 +83,9 @@ pub fn del_price(
         permissions_account,
         cmd_args,
     )?;
+    if let Some(symbol_index_account) = symbol_index_account {
+        check_valid_writable_account(program_id, symbol_index_account)?;
+    }
 
     {
         let mut product_data = load_checked::<ProductAccount>(product_account, cmd_args.version)?;
@@ -76,12 +103,33 @@ pub fn del_price(
         product_data.first_price_account = price_data.next_price_account;
     }
 
+    // The product loses its reference price if it is the deleted price account
+    if ProductAccount::read_reference_price(product_account)? == Some(*price_account.key) {
+        *ProductAccount::load_reference_price_mut(product_account)? = Pubkey::default();
+    }
+
+    if let Some(symbol_index_account) = symbol_index_account {
+        if let Some(symbol) = ProductAccount::read_symbol(product_account)? {
+            load_checked::<SymbolIndexAccount>(symbol_index_account, cmd_args.version)?
+                .remove(&SymbolIndexAccount::symbol_hash(&symbol), price_account.key);
+        }
+    }
+
+    pyth_assert(
+        recipient_account.key != price_account.key,
+        ProgramError::InvalidArgument,
+    )?;
+
+    // Zero out the header so the account can't be loaded as a price account anymore, even within
+    // the same transaction.
+    *load_account_as_mut::<AccountHeader>(price_account)? = AccountHeader::zeroed();
+
     // Zero out the balance of the price account to delete it.
     // Note that you can't use the system program's transfer instruction to do this operation, as
     // that instruction fails if the source account has any data.
     let lamports = price_account.lamports();
     **price_account.lamports.borrow_mut() = 0;
-    **funding_account.lamports.borrow_mut() += lamports;
+    **recipient_account.lamports.borrow_mut() += lamports;
 
     Ok(())
 }
@@ -9,7 +9,9 @@ use {
             load_checked,
         },
         instruction::CommandHeader,
+        layout::MAPPING_ACCOUNT_PRODUCTS_LIST_OFFSET,
         utils::{
+            check_distinct_accounts,
             check_permissioned_funding_account,
             check_valid_funding_account,
             pyth_assert,
@@ -23,10 +25,7 @@ use {
         program_error::ProgramError,
         pubkey::Pubkey,
     },
-    std::mem::{
-        size_of,
-        size_of_val,
-    },
+    std::mem::size_of,
 };
 
 /// Delete a product account and remove it from the product list of its associated mapping account.
@@ -48,6 +47,12 @@ pub fn del_product(
         [w, x, y, p] => Ok((w, x, y, p)),
         _ => Err(OracleError::InvalidNumberOfAccounts),
     }?;
+    check_distinct_accounts(&[
+        funding_account,
+        mapping_account,
+        product_account,
+        permissions_account,
+    ])?;
 
     let cmd_args = load::<CommandHeader>(instruction_data)?;
 
@@ -101,10 +106,8 @@ pub fn del_product(
         mapping_data.products_list[product_index] = last_key_bytes;
         mapping_data.products_list[num_after_removal] = Pubkey::default();
         mapping_data.number_of_products = try_convert::<_, u32>(num_after_removal)?;
-        mapping_data.header.size = try_convert::<_, u32>(
-            size_of::<MappingAccount>() - size_of_val(&mapping_data.products_list),
-        )? + mapping_data.number_of_products
-            * try_convert::<_, u32>(size_of::<Pubkey>())?;
+        mapping_data.header.size = try_convert::<_, u32>(MAPPING_ACCOUNT_PRODUCTS_LIST_OFFSET)?
+            + mapping_data.number_of_products * try_convert::<_, u32>(size_of::<Pubkey>())?;
     }
 
     // Zero out the balance of the price account to delete it.
@@ -3,6 +3,7 @@ use {
         accounts::{
             PriceAccount,
             PriceComponent,
+            PublisherStats,
             PythAccount,
         },
         deserialize::{
@@ -11,9 +12,12 @@ use {
         },
         instruction::DelPublisherArgs,
         utils::{
+            check_co_signed,
+            check_distinct_accounts,
             check_permissioned_funding_account,
             check_valid_funding_account,
             pyth_assert,
+            split_instructions_account,
             try_convert,
         },
         OracleError,
@@ -32,6 +36,8 @@ use {
 /// Delete publisher from symbol account
 // account[0] funding account       [signer writable]
 // account[1] price account         [signer writable]
+// account[2] permissions account   []
+// account[3] sysvar_instructions   [] (optional, see `check_co_signed`)
 pub fn del_publisher(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
@@ -45,10 +51,12 @@ pub fn del_publisher(
         ProgramError::InvalidArgument,
     )?;
 
+    let (accounts, instructions_account) = split_instructions_account(accounts);
     let (funding_account, price_account, permissions_account) = match accounts {
         [x, y, p] => Ok((x, y, p)),
         _ => Err(OracleError::InvalidNumberOfAccounts),
     }?;
+    check_distinct_accounts(&[funding_account, price_account, permissions_account])?;
 
     check_valid_funding_account(funding_account)?;
     check_permissioned_funding_account(
@@ -58,13 +66,27 @@ pub fn del_publisher(
         permissions_account,
         &cmd_args.header,
     )?;
+    check_co_signed(
+        program_id,
+        funding_account,
+        permissions_account,
+        instructions_account,
+        &cmd_args.header,
+    )?;
 
     let mut price_data = load_checked::<PriceAccount>(price_account, cmd_args.header.version)?;
+    remove_publisher(&mut price_data, &cmd_args.publisher)
+}
 
+/// Remove `publisher` from `price_data`, shifting the following components (and their statistics)
+/// down to keep them sorted. Returns `InvalidArgument` if `publisher` is not a publisher of the
+/// price account.
+pub fn remove_publisher(price_data: &mut PriceAccount, publisher: &Pubkey) -> ProgramResult {
     for i in 0..(try_convert::<u32, usize>(price_data.num_)?) {
-        if cmd_args.publisher == price_data.comp_[i].pub_ {
+        if *publisher == price_data.comp_[i].pub_ {
             for j in i + 1..(try_convert::<u32, usize>(price_data.num_)?) {
                 price_data.comp_[j - 1] = price_data.comp_[j];
+                price_data.publisher_stats_[j - 1] = price_data.publisher_stats_[j];
             }
             price_data.num_ -= 1;
             let current_index: usize = try_convert(price_data.num_)?;
@@ -73,6 +95,11 @@ pub fn del_publisher(
                 0,
                 size_of::<PriceComponent>(),
             );
+            sol_memset(
+                bytes_of_mut(&mut price_data.publisher_stats_[current_index]),
+                0,
+                size_of::<PublisherStats>(),
+            );
             price_data.header.size = try_convert::<_, u32>(PriceAccount::INITIAL_SIZE)?;
             return Ok(());
         }
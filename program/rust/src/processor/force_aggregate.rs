@@ -0,0 +1,91 @@
+use {
+    crate::{
+        accounts::PriceAccount,
+        c_oracle_header::PC_STATUS_HALTED,
+        deserialize::{
+            load,
+            load_checked,
+        },
+        instruction::CommandHeader,
+        processor::upd_price::{
+            aggregate_price,
+            return_aggregate,
+        },
+        utils::{
+            check_distinct_accounts,
+            check_permissioned_funding_account,
+            check_valid_funding_account,
+            pyth_assert,
+        },
+        OracleError,
+    },
+    solana_program::{
+        account_info::AccountInfo,
+        entrypoint::ProgramResult,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        sysvar::{
+            clock::Clock,
+            Sysvar,
+        },
+    },
+    std::mem::size_of,
+};
+
+/// Run the aggregation of a price account at the current slot, like the first price update of a
+/// slot does, even if the aggregation already ran in this slot. The new aggregate, if any, is
+/// returned like by `upd_price`. Only available in devnet builds.
+// account[0] funding account       [signer writable]
+// account[1] price account         [writable]
+// account[2] sysvar_clock account  []
+// account[3] permissions account   []
+pub fn force_aggregate(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let cmd = load::<CommandHeader>(instruction_data)?;
+
+    pyth_assert(
+        instruction_data.len() == size_of::<CommandHeader>(),
+        ProgramError::InvalidArgument,
+    )?;
+
+    let (funding_account, price_account, clock_account, permissions_account) = match accounts {
+        [x, y, z, p] => Ok((x, y, z, p)),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
+    }?;
+    check_distinct_accounts(&[
+        funding_account,
+        price_account,
+        clock_account,
+        permissions_account,
+    ])?;
+
+    check_valid_funding_account(funding_account)?;
+    check_permissioned_funding_account(
+        program_id,
+        price_account,
+        funding_account,
+        permissions_account,
+        cmd,
+    )?;
+    let clock = Clock::from_account_info(clock_account)?;
+
+    {
+        let price_data = load_checked::<PriceAccount>(price_account, cmd.version)?;
+        pyth_assert(
+            price_data.agg_.status_ != PC_STATUS_HALTED,
+            OracleError::PriceHalted.into(),
+        )?;
+    }
+
+    return_aggregate(aggregate_price(
+        price_account,
+        &clock,
+        None,
+        None,
+        cmd.version,
+    )?);
+    Ok(())
+}
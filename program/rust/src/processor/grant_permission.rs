@@ -0,0 +1,119 @@
+use {
+    crate::{
+        accounts::{
+            PermissionAccount,
+            PERMISSION_GRANT_SEED,
+        },
+        deserialize::load,
+        instruction::{
+            OracleCommand,
+            PermissionGrantArgs,
+        },
+        utils::{
+            check_authorized_funding_account,
+            check_distinct_accounts,
+            check_valid_funding_account,
+            check_valid_permission_grant_account,
+            get_rent,
+            is_permission_grant,
+            pyth_assert,
+        },
+        OracleError,
+    },
+    num_traits::FromPrimitive,
+    solana_program::{
+        account_info::AccountInfo,
+        entrypoint::ProgramResult,
+        program::invoke_signed,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        system_instruction::create_account,
+        system_program::check_id,
+    },
+    std::mem::size_of,
+};
+
+/// Grant `authority` the permission to run `command` by creating its permission grant, an empty
+/// account at the PDA `[PERMISSION_GRANT_SEED, authority, command]`. Granting an existing
+/// permission does nothing. The permissions to grant and revoke permissions, to update the
+/// permissions account and to run the co-signed commands (see `PermissionAccount::is_co_signed`)
+/// can't be granted.
+// account[0] funding account       [signer writable]
+// account[1] permission grant      [writable]
+// account[2] permissions account   []
+// account[3] system program        []
+pub fn grant_permission(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let cmd = load::<PermissionGrantArgs>(instruction_data)?;
+
+    pyth_assert(
+        instruction_data.len() == size_of::<PermissionGrantArgs>(),
+        ProgramError::InvalidArgument,
+    )?;
+
+    let (funding_account, grant_account, permissions_account, system_program) = match accounts {
+        [x, g, p, s] => Ok((x, g, p, s)),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
+    }?;
+    check_distinct_accounts(&[
+        funding_account,
+        grant_account,
+        permissions_account,
+        system_program,
+    ])?;
+
+    check_valid_funding_account(funding_account)?;
+    check_authorized_funding_account(
+        program_id,
+        funding_account,
+        permissions_account,
+        &cmd.header,
+    )?;
+
+    let command = match OracleCommand::from_i32(cmd.command) {
+        Some(
+            OracleCommand::GrantPermission
+            | OracleCommand::RevokePermission
+            | OracleCommand::UpdPermissions,
+        )
+        | None => return Err(ProgramError::InvalidArgument),
+        Some(command) if PermissionAccount::is_co_signed(command) => {
+            return Err(ProgramError::InvalidArgument)
+        }
+        Some(command) => command,
+    };
+    let bump_seed =
+        check_valid_permission_grant_account(program_id, grant_account, &cmd.authority, command)?;
+    pyth_assert(
+        check_id(system_program.key),
+        OracleError::InvalidSystemAccount.into(),
+    )?;
+
+    if is_permission_grant(program_id, grant_account) {
+        return Ok(());
+    }
+
+    invoke_signed(
+        &create_account(
+            funding_account.key,
+            grant_account.key,
+            get_rent()?.minimum_balance(0),
+            0,
+            program_id,
+        ),
+        &[
+            funding_account.clone(),
+            grant_account.clone(),
+            system_program.clone(),
+        ],
+        &[&[
+            PERMISSION_GRANT_SEED.as_bytes(),
+            &cmd.authority.to_bytes(),
+            &cmd.command.to_le_bytes(),
+            &[bump_seed],
+        ]],
+    )
+}
@@ -0,0 +1,79 @@
+use {
+    crate::{
+        accounts::PriceAccount,
+        c_oracle_header::PC_STATUS_HALTED,
+        deserialize::{
+            load,
+            load_checked,
+        },
+        instruction::CommandHeader,
+        utils::{
+            check_co_signed,
+            check_distinct_accounts,
+            check_permissioned_funding_account,
+            check_valid_funding_account,
+            get_clock,
+            pyth_assert,
+            split_instructions_account,
+        },
+        OracleError,
+    },
+    solana_program::{
+        account_info::AccountInfo,
+        entrypoint::ProgramResult,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+    std::mem::size_of,
+};
+
+/// Halt a price feed. The aggregate status is set to `PC_STATUS_HALTED` and publisher price
+/// updates are rejected until the feed is resumed with `resume_price`. The halt is recorded as a
+/// status change at the current slot.
+// account[0] funding account       [signer writable]
+// account[1] price account         [signer writable]
+// account[2] permissions account   []
+// account[3] sysvar_instructions   [] (optional, see `check_co_signed`)
+pub fn halt_price(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let cmd = load::<CommandHeader>(instruction_data)?;
+
+    pyth_assert(
+        instruction_data.len() == size_of::<CommandHeader>(),
+        ProgramError::InvalidArgument,
+    )?;
+
+    let (accounts, instructions_account) = split_instructions_account(accounts);
+    let (funding_account, price_account, permissions_account) = match accounts {
+        [x, y, p] => Ok((x, y, p)),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
+    }?;
+    check_distinct_accounts(&[funding_account, price_account, permissions_account])?;
+
+    check_valid_funding_account(funding_account)?;
+    check_permissioned_funding_account(
+        program_id,
+        price_account,
+        funding_account,
+        permissions_account,
+        cmd,
+    )?;
+    check_co_signed(
+        program_id,
+        funding_account,
+        permissions_account,
+        instructions_account,
+        cmd,
+    )?;
+
+    let clock = get_clock()?;
+    let mut price_account_data = load_checked::<PriceAccount>(price_account, cmd.version)?;
+    let prev_status = price_account_data.agg_.status_;
+    price_account_data.agg_.status_ = PC_STATUS_HALTED;
+    price_account_data.record_status_change(prev_status, clock.slot, clock.unix_timestamp);
+
+    Ok(())
+}
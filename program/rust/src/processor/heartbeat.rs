@@ -0,0 +1,72 @@
+use {
+    crate::{
+        accounts::PriceAccount,
+        deserialize::{
+            load,
+            load_checked,
+        },
+        instruction::CommandHeader,
+        processor::upd_price::check_publisher_price_update,
+        utils::{
+            check_distinct_accounts,
+            check_valid_funding_account,
+            check_valid_writable_account,
+            pyth_assert,
+        },
+        OracleError,
+    },
+    solana_program::{
+        account_info::AccountInfo,
+        clock::Clock,
+        entrypoint::ProgramResult,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        sysvar::Sysvar,
+    },
+    std::mem::size_of,
+};
+
+/// Record that the signing publisher is alive without publishing a price, e.g. while its market
+/// is closed, so that monitoring can tell it apart from a publisher that is down (see
+/// `PublisherStats::last_seen_slot`). The quote of the publisher is left unchanged, and
+/// heartbeats are ignored by the aggregation: they don't aggregate the price account, nor count
+/// as price updates for the aggregation rotation or `max_slot_updates_`. Like price updates,
+/// heartbeats are rejected by halted price accounts and after the expiry of the publisher.
+// account[0] funding account       [signer writable]
+// account[1] price account         [writable]
+// account[2] sysvar_clock account  []
+pub fn heartbeat(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let cmd = load::<CommandHeader>(instruction_data)?;
+
+    pyth_assert(
+        instruction_data.len() == size_of::<CommandHeader>(),
+        ProgramError::InvalidArgument,
+    )?;
+
+    let (funding_account, price_account, clock_account) = match accounts {
+        [x, y, z] => Ok((x, y, z)),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
+    }?;
+    check_distinct_accounts(&[funding_account, price_account, clock_account])?;
+
+    check_valid_funding_account(funding_account)?;
+    check_valid_writable_account(program_id, price_account)?;
+    let clock = Clock::from_account_info(clock_account)?;
+
+    let publisher_expiry = PriceAccount::read_publisher_expiry(price_account, funding_account.key)?;
+    let mut price_data = load_checked::<PriceAccount>(price_account, cmd.version)?;
+    let publisher_index = check_publisher_price_update(
+        &price_data,
+        funding_account.key,
+        publisher_expiry,
+        None,
+        None,
+        clock.slot,
+    )?;
+    price_data.publisher_stats_[publisher_index].record_heartbeat(clock.slot);
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use {
+    crate::{
+        accounts::{
+            ComponentLogAccount,
+            PriceAccount,
+            PythAccount,
+        },
+        deserialize::{
+            load,
+            load_checked_read_only,
+        },
+        instruction::CommandHeader,
+        utils::{
+            check_distinct_accounts,
+            check_permissioned_funding_account,
+            check_valid_funding_account,
+            check_valid_readable_account,
+        },
+        OracleError,
+    },
+    solana_program::{
+        account_info::AccountInfo,
+        entrypoint::ProgramResult,
+        pubkey::Pubkey,
+    },
+};
+
+/// Initialize a component log account for a price account. Price updates of the price account
+/// given this account append the publisher updates they accept to it (see `ComponentLogAccount`
+/// and `upd_price`).
+// account[0] funding account       [signer writable]
+// account[1] component log account [signer writable]
+// account[2] price account         []
+// account[3] permissions account   []
+pub fn init_component_log(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let (funding_account, fresh_component_log_account, price_account, permissions_account) =
+        match accounts {
+            [x, y, z, p] => Ok((x, y, z, p)),
+            _ => Err(OracleError::InvalidNumberOfAccounts),
+        }?;
+    check_distinct_accounts(&[
+        funding_account,
+        fresh_component_log_account,
+        price_account,
+        permissions_account,
+    ])?;
+
+    let hdr = load::<CommandHeader>(instruction_data)?;
+
+    check_valid_funding_account(funding_account)?;
+    check_permissioned_funding_account(
+        program_id,
+        fresh_component_log_account,
+        funding_account,
+        permissions_account,
+        hdr,
+    )?;
+    check_valid_readable_account(program_id, price_account)?;
+    load_checked_read_only::<PriceAccount>(price_account, hdr.version)?;
+
+    ComponentLogAccount::initialize(fresh_component_log_account, hdr.version)?.price_account_ =
+        *price_account.key;
+
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use {
+    crate::{
+        accounts::{
+            DerivedPriceAccount,
+            PriceAccount,
+            PythAccount,
+        },
+        deserialize::{
+            load,
+            load_checked_read_only,
+        },
+        instruction::InitDerivedPriceArgs,
+        utils::{
+            check_distinct_accounts,
+            check_exponent_range,
+            check_permissioned_funding_account,
+            check_valid_funding_account,
+            check_valid_readable_account,
+            pyth_assert,
+        },
+        OracleError,
+    },
+    solana_program::{
+        account_info::AccountInfo,
+        entrypoint::ProgramResult,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+    std::mem::size_of,
+};
+
+/// Initialize a derived price account quoting the base price account in the quote price account,
+/// e.g. ETH/BTC from ETH/USD and BTC/USD, at the given exponent. Its aggregate is computed by
+/// `upd_derived`.
+// account[0] funding account       [signer writable]
+// account[1] derived price account [signer writable]
+// account[2] base price account    []
+// account[3] quote price account   []
+// account[4] permissions account   []
+pub fn init_derived_price(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let cmd = load::<InitDerivedPriceArgs>(instruction_data)?;
+
+    pyth_assert(
+        instruction_data.len() == size_of::<InitDerivedPriceArgs>(),
+        ProgramError::InvalidArgument,
+    )?;
+    check_exponent_range(cmd.exponent)?;
+
+    let (
+        funding_account,
+        fresh_derived_price_account,
+        base_price_account,
+        quote_price_account,
+        permissions_account,
+    ) = match accounts {
+        [x, y, b, q, p] => Ok((x, y, b, q, p)),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
+    }?;
+    check_distinct_accounts(&[
+        funding_account,
+        fresh_derived_price_account,
+        base_price_account,
+        quote_price_account,
+        permissions_account,
+    ])?;
+
+    check_valid_funding_account(funding_account)?;
+    check_permissioned_funding_account(
+        program_id,
+        fresh_derived_price_account,
+        funding_account,
+        permissions_account,
+        &cmd.header,
+    )?;
+    check_valid_readable_account(program_id, base_price_account)?;
+    check_valid_readable_account(program_id, quote_price_account)?;
+    load_checked_read_only::<PriceAccount>(base_price_account, cmd.header.version)?;
+    load_checked_read_only::<PriceAccount>(quote_price_account, cmd.header.version)?;
+
+    let mut derived_price_data =
+        DerivedPriceAccount::initialize(fresh_derived_price_account, cmd.header.version)?;
+    derived_price_data.exponent = cmd.exponent;
+    derived_price_data.base_price_account_ = *base_price_account.key;
+    derived_price_data.quote_price_account_ = *quote_price_account.key;
+
+    Ok(())
+}
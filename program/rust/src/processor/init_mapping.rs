@@ -7,6 +7,7 @@ use {
         deserialize::load,
         instruction::CommandHeader,
         utils::{
+            check_distinct_accounts,
             check_permissioned_funding_account,
             check_valid_funding_account,
         },
@@ -31,6 +32,7 @@ pub fn init_mapping(
         [x, y, p] => Ok((x, y, p)),
         _ => Err(OracleError::InvalidNumberOfAccounts),
     }?;
+    check_distinct_accounts(&[funding_account, fresh_mapping_account, permissions_account])?;
 
     let hdr = load::<CommandHeader>(instruction_data)?;
 
@@ -0,0 +1,147 @@
+use {
+    crate::{
+        accounts::{
+            MappingAccount,
+            PythAccount,
+            MAPPING_SEED,
+        },
+        deserialize::{
+            load,
+            load_checked,
+        },
+        instruction::CommandHeader,
+        utils::{
+            check_authorized_funding_account,
+            check_distinct_accounts,
+            check_valid_fresh_account,
+            check_valid_funding_account,
+            check_valid_mapping_pda_account,
+            check_valid_writable_account,
+            get_rent,
+            pyth_assert,
+            send_lamports,
+            try_convert,
+        },
+        OracleError,
+    },
+    solana_program::{
+        account_info::AccountInfo,
+        entrypoint::{
+            ProgramResult,
+            MAX_PERMITTED_DATA_INCREASE,
+        },
+        program::invoke_signed,
+        program_memory::sol_memset,
+        pubkey::Pubkey,
+        system_instruction::{
+            allocate,
+            assign,
+        },
+        system_program::check_id,
+    },
+};
+
+/// Create the mapping account at the mapping PDA of the version of the instruction (see
+/// `MAPPING_SEED`). A mapping account is larger than what a single instruction can allocate
+/// (`MAX_PERMITTED_DATA_INCREASE`), so the PDA grows over several calls and is initialized by the
+/// call that completes it.
+///
+/// If the existing first mapping account is given, the PDA is initialized with its products and
+/// next mapping account instead of empty, and the existing mapping account is closed with its rent
+/// going to the funding account.
+// account[0] funding account       [signer writable]
+// account[1] mapping PDA           [writable]
+// account[2] permissions account   []
+// account[3] system program        []
+// account[4] mapping account       [writable] (optional)
+pub fn init_mapping_pda(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let (funding_account, mapping_pda, permissions_account, system_program, mapping_account) =
+        match accounts {
+            [x, y, p, s] => Ok((x, y, p, s, None)),
+            [x, y, p, s, m] => Ok((x, y, p, s, Some(m))),
+            _ => Err(OracleError::InvalidNumberOfAccounts),
+        }?;
+    let mut roles = vec![
+        funding_account,
+        mapping_pda,
+        permissions_account,
+        system_program,
+    ];
+    roles.extend(mapping_account);
+    check_distinct_accounts(&roles)?;
+
+    let hdr = load::<CommandHeader>(instruction_data)?;
+
+    check_valid_funding_account(funding_account)?;
+    check_authorized_funding_account(program_id, funding_account, permissions_account, hdr)?;
+    let bump_seed = check_valid_mapping_pda_account(program_id, mapping_pda, hdr.version)?;
+    pyth_assert(
+        check_id(system_program.key),
+        OracleError::InvalidSystemAccount.into(),
+    )?;
+
+    if mapping_pda.data_len() == 0 {
+        // The whole rent is paid upfront, so that the next calls only need to grow the account.
+        // The PDA may already hold lamports, which is why it isn't created with `create_account`.
+        let target_rent = get_rent()?.minimum_balance(MappingAccount::MINIMUM_SIZE);
+        let missing_rent = target_rent.saturating_sub(mapping_pda.lamports());
+        if missing_rent > 0 {
+            send_lamports(funding_account, mapping_pda, system_program, missing_rent)?;
+        }
+
+        let seeds: &[&[u8]] = &[
+            MAPPING_SEED.as_bytes(),
+            &hdr.version.to_le_bytes(),
+            &[bump_seed],
+        ];
+        let space = MappingAccount::MINIMUM_SIZE.min(MAX_PERMITTED_DATA_INCREASE);
+        invoke_signed(
+            &allocate(mapping_pda.key, try_convert(space)?),
+            &[mapping_pda.clone(), system_program.clone()],
+            &[seeds],
+        )?;
+        invoke_signed(
+            &assign(mapping_pda.key, program_id),
+            &[mapping_pda.clone(), system_program.clone()],
+            &[seeds],
+        )?;
+    } else {
+        check_valid_writable_account(program_id, mapping_pda)?;
+        check_valid_fresh_account(mapping_pda)?;
+        let space =
+            MappingAccount::MINIMUM_SIZE.min(mapping_pda.data_len() + MAX_PERMITTED_DATA_INCREASE);
+        mapping_pda.realloc(space, true)?;
+    }
+
+    if mapping_pda.data_len() < MappingAccount::MINIMUM_SIZE {
+        return Ok(());
+    }
+
+    let mapping_account = match mapping_account {
+        Some(mapping_account) => mapping_account,
+        None => {
+            MappingAccount::initialize(mapping_pda, hdr.version)?;
+            return Ok(());
+        }
+    };
+
+    check_valid_writable_account(program_id, mapping_account)?;
+    {
+        let mapping_data = load_checked::<MappingAccount>(mapping_account, hdr.version)?;
+        let mut mapping_pda_data = MappingAccount::initialize(mapping_pda, hdr.version)?;
+        *mapping_pda_data = *mapping_data;
+    }
+
+    // The runtime deletes the mapping account once it has no lamports left
+    let data_len = mapping_account.data_len();
+    sol_memset(&mut mapping_account.try_borrow_mut_data()?, 0, data_len);
+    let lamports = mapping_account.lamports();
+    **mapping_account.lamports.borrow_mut() = 0;
+    **funding_account.lamports.borrow_mut() += lamports;
+
+    Ok(())
+}
@@ -0,0 +1,56 @@
+use {
+    crate::{
+        accounts::{
+            MessageBufferAccount,
+            PythAccount,
+        },
+        deserialize::load,
+        instruction::CommandHeader,
+        utils::{
+            check_distinct_accounts,
+            check_permissioned_funding_account,
+            check_valid_funding_account,
+        },
+        OracleError,
+    },
+    solana_program::{
+        account_info::AccountInfo,
+        entrypoint::ProgramResult,
+        pubkey::Pubkey,
+    },
+};
+
+/// Initialize a message buffer account. Price updates given this account append the aggregates
+/// they compute to it (see `upd_price`).
+// account[0] funding account       [signer writable]
+// account[1] message buffer        [signer writable]
+pub fn init_message_buffer(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let (funding_account, fresh_message_buffer_account, permissions_account) = match accounts {
+        [x, y, p] => Ok((x, y, p)),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
+    }?;
+    check_distinct_accounts(&[
+        funding_account,
+        fresh_message_buffer_account,
+        permissions_account,
+    ])?;
+
+    let hdr = load::<CommandHeader>(instruction_data)?;
+
+    check_valid_funding_account(funding_account)?;
+    check_permissioned_funding_account(
+        program_id,
+        fresh_message_buffer_account,
+        funding_account,
+        permissions_account,
+        hdr,
+    )?;
+
+    MessageBufferAccount::initialize(fresh_message_buffer_account, hdr.version)?;
+
+    Ok(())
+}
@@ -11,6 +11,7 @@ use {
         },
         instruction::InitPriceArgs,
         utils::{
+            check_distinct_accounts,
             check_exponent_range,
             check_permissioned_funding_account,
             check_valid_funding_account,
@@ -45,6 +46,7 @@ pub fn init_price(
         [x, y, p] => Ok((x, y, p)),
         _ => Err(OracleError::InvalidNumberOfAccounts),
     }?;
+    check_distinct_accounts(&[funding_account, price_account, permissions_account])?;
 
     check_valid_funding_account(funding_account)?;
     check_permissioned_funding_account(
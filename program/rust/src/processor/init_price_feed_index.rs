@@ -0,0 +1,74 @@
+use {
+    crate::{
+        accounts::{
+            PermissionAccount,
+            PriceAccount,
+        },
+        deserialize::{
+            load,
+            load_checked,
+        },
+        instruction::CommandHeader,
+        utils::{
+            check_distinct_accounts,
+            check_permissioned_funding_account,
+            check_valid_funding_account,
+            check_valid_writable_account,
+            pyth_assert,
+        },
+        OracleError,
+    },
+    solana_program::{
+        account_info::AccountInfo,
+        entrypoint::ProgramResult,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+    std::mem::size_of,
+};
+
+/// Assign the next price feed index to a price account. The last assigned index is stored in the
+/// permissions account, so that indexes are unique and start at 1. A price account keeps the
+/// index it already has.
+// account[0] funding account       [signer writable]
+// account[1] price account         [writable]
+// account[2] permissions account   [writable]
+pub fn init_price_feed_index(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let cmd = load::<CommandHeader>(instruction_data)?;
+
+    pyth_assert(
+        instruction_data.len() == size_of::<CommandHeader>(),
+        ProgramError::InvalidArgument,
+    )?;
+
+    let (funding_account, price_account, permissions_account) = match accounts {
+        [x, y, p] => Ok((x, y, p)),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
+    }?;
+    check_distinct_accounts(&[funding_account, price_account, permissions_account])?;
+
+    check_valid_funding_account(funding_account)?;
+    check_permissioned_funding_account(
+        program_id,
+        price_account,
+        funding_account,
+        permissions_account,
+        cmd,
+    )?;
+    check_valid_writable_account(program_id, permissions_account)?;
+
+    let mut price_account_data = load_checked::<PriceAccount>(price_account, cmd.version)?;
+    if price_account_data.feed_index_ == 0 {
+        let mut last_feed_index = PermissionAccount::load_last_feed_index_mut(permissions_account)?;
+        *last_feed_index = last_feed_index
+            .checked_add(1)
+            .ok_or(OracleError::MaxLastFeedIndexReached)?;
+        price_account_data.feed_index_ = *last_feed_index;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use {
+    crate::{
+        accounts::{
+            PythAccount,
+            SymbolIndexAccount,
+        },
+        deserialize::load,
+        instruction::CommandHeader,
+        utils::{
+            check_distinct_accounts,
+            check_permissioned_funding_account,
+            check_valid_funding_account,
+        },
+        OracleError,
+    },
+    solana_program::{
+        account_info::AccountInfo,
+        entrypoint::ProgramResult,
+        pubkey::Pubkey,
+    },
+};
+
+/// Initialize a symbol index account. `add_price` and `del_price` given this account add the
+/// price accounts to the index and remove them (see `SymbolIndexAccount`).
+// account[0] funding account       [signer writable]
+// account[1] symbol index account  [signer writable]
+// account[2] permissions account   []
+pub fn init_symbol_index(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let (funding_account, fresh_symbol_index_account, permissions_account) = match accounts {
+        [x, y, p] => Ok((x, y, p)),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
+    }?;
+    check_distinct_accounts(&[
+        funding_account,
+        fresh_symbol_index_account,
+        permissions_account,
+    ])?;
+
+    let hdr = load::<CommandHeader>(instruction_data)?;
+
+    check_valid_funding_account(funding_account)?;
+    check_permissioned_funding_account(
+        program_id,
+        fresh_symbol_index_account,
+        funding_account,
+        permissions_account,
+        hdr,
+    )?;
+
+    SymbolIndexAccount::initialize(fresh_symbol_index_account, hdr.version)?;
+
+    Ok(())
+}
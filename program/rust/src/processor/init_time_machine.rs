@@ -0,0 +1,76 @@
+use {
+    crate::{
+        accounts::{
+            PriceAccount,
+            TimeMachine,
+        },
+        deserialize::{
+            load,
+            load_checked,
+        },
+        instruction::CommandHeader,
+        utils::{
+            check_distinct_accounts,
+            check_permissioned_funding_account,
+            check_valid_funding_account,
+            pyth_assert,
+        },
+        OracleError,
+    },
+    solana_program::{
+        account_info::AccountInfo,
+        entrypoint::ProgramResult,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        system_program::check_id,
+    },
+    std::mem::size_of,
+};
+
+/// Add a time machine to a price account (see `TimeMachine`), so that the following successful
+/// aggregates are averaged over periods of `TIME_MACHINE_BUCKET_SECONDS` seconds. The price
+/// account is resized, the funding account paying for the rent of the extra space. Nothing
+/// changes if the price account already has a time machine.
+// account[0] funding account       [signer writable]
+// account[1] price account         [signer writable]
+// account[2] permissions account   []
+// account[3] system program        []
+pub fn init_time_machine(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let cmd = load::<CommandHeader>(instruction_data)?;
+
+    pyth_assert(
+        instruction_data.len() == size_of::<CommandHeader>(),
+        ProgramError::InvalidArgument,
+    )?;
+
+    let (funding_account, price_account, permissions_account, system_program) = match accounts {
+        [x, y, p, s] => Ok((x, y, p, s)),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
+    }?;
+    check_distinct_accounts(&[
+        funding_account,
+        price_account,
+        permissions_account,
+        system_program,
+    ])?;
+
+    check_valid_funding_account(funding_account)?;
+    check_permissioned_funding_account(
+        program_id,
+        price_account,
+        funding_account,
+        permissions_account,
+        cmd,
+    )?;
+    pyth_assert(
+        check_id(system_program.key),
+        OracleError::InvalidSystemAccount.into(),
+    )?;
+    load_checked::<PriceAccount>(price_account, cmd.version)?;
+
+    PriceAccount::add_extension::<TimeMachine>(price_account, funding_account, system_program)
+}
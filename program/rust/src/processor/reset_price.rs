@@ -0,0 +1,113 @@
+use {
+    crate::{
+        accounts::{
+            FastAggregate,
+            PriceAccount,
+            PriceCumulative,
+            PriceEma,
+            PriceInfo,
+            PruningState,
+            PublisherPruning,
+            PublisherStats,
+        },
+        deserialize::{
+            load,
+            load_checked,
+        },
+        instruction::CommandHeader,
+        utils::{
+            check_distinct_accounts,
+            check_permissioned_funding_account,
+            check_valid_funding_account,
+            get_clock,
+            pyth_assert,
+        },
+        OracleError,
+    },
+    bytemuck::Zeroable,
+    solana_program::{
+        account_info::AccountInfo,
+        entrypoint::ProgramResult,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+    std::mem::size_of,
+};
+
+/// Clear the prices of a price account: its aggregates, EMAs, cumulative sums and time machine,
+/// and the quotes, statistics and pruning states of its publishers. The configuration, the
+/// publishers and the links to other accounts are kept. The aggregate status becomes
+/// `PC_STATUS_UNKNOWN`, which also resumes a halted feed, and the status changes are kept as an
+/// audit trail. Only available in devnet builds.
+// account[0] funding account       [signer writable]
+// account[1] price account         [writable]
+// account[2] permissions account   []
+pub fn reset_price(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let cmd = load::<CommandHeader>(instruction_data)?;
+
+    pyth_assert(
+        instruction_data.len() == size_of::<CommandHeader>(),
+        ProgramError::InvalidArgument,
+    )?;
+
+    let (funding_account, price_account, permissions_account) = match accounts {
+        [x, y, p] => Ok((x, y, p)),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
+    }?;
+    check_distinct_accounts(&[funding_account, price_account, permissions_account])?;
+
+    check_valid_funding_account(funding_account)?;
+    check_permissioned_funding_account(
+        program_id,
+        price_account,
+        funding_account,
+        permissions_account,
+        cmd,
+    )?;
+
+    let clock = get_clock()?;
+    let mut price_data = load_checked::<PriceAccount>(price_account, cmd.version)?;
+    let prev_status = price_data.agg_.status_;
+    price_data.num_qt_ = 0;
+    price_data.last_slot_ = 0;
+    price_data.valid_slot_ = 0;
+    price_data.twap_ = PriceEma::zeroed();
+    price_data.twac_ = PriceEma::zeroed();
+    price_data.timestamp_ = 0;
+    price_data.message_sent_ = 0;
+    price_data.skipped_slots_ = 0;
+    price_data.prev_slot_ = 0;
+    price_data.prev_price_ = 0;
+    price_data.prev_conf_ = 0;
+    price_data.prev_timestamp_ = 0;
+    price_data.agg_ = PriceInfo::zeroed();
+    for component in price_data.comp_.iter_mut() {
+        component.agg_ = PriceInfo::zeroed();
+        component.latest_ = PriceInfo::zeroed();
+    }
+    for stats in price_data.publisher_stats_.iter_mut() {
+        *stats = PublisherStats::zeroed();
+    }
+    price_data.fast_agg_ = FastAggregate::zeroed();
+    price_data.agg_pub_time_ = 0;
+    // The aggregate counter keeps increasing across resets
+    price_data.price_cumulative = PriceCumulative {
+        aggregate_counter: price_data.price_cumulative.aggregate_counter,
+        ..PriceCumulative::zeroed()
+    };
+    price_data.record_status_change(prev_status, clock.slot, clock.unix_timestamp);
+    drop(price_data);
+
+    if PriceAccount::has_extension::<PublisherPruning>(price_account)? {
+        let (_, mut pruning) =
+            PriceAccount::load_with_extension_mut::<PublisherPruning>(price_account)?;
+        for state in pruning.states_.iter_mut() {
+            *state = PruningState::zeroed();
+        }
+    }
+    PriceAccount::clear_time_machine(price_account)
+}
@@ -0,0 +1,54 @@
+use {
+    crate::{
+        accounts::PriceAccount,
+        deserialize::{
+            load,
+            load_checked,
+        },
+        instruction::CommandHeader,
+        processor::del_publisher::remove_publisher,
+        utils::{
+            check_distinct_accounts,
+            check_valid_funding_account,
+            check_valid_writable_account,
+            pyth_assert,
+        },
+        OracleError,
+    },
+    solana_program::{
+        account_info::AccountInfo,
+        entrypoint::ProgramResult,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+    std::mem::size_of,
+};
+
+/// Remove the signing publisher from a price account. Unlike `del_publisher`, this doesn't need
+/// the authority: a publisher can only remove its own key.
+// account[0] publisher account     [signer writable]
+// account[1] price account         [writable]
+pub fn resign_publisher(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let cmd = load::<CommandHeader>(instruction_data)?;
+
+    pyth_assert(
+        instruction_data.len() == size_of::<CommandHeader>(),
+        ProgramError::InvalidArgument,
+    )?;
+
+    let (publisher_account, price_account) = match accounts {
+        [x, y] => Ok((x, y)),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
+    }?;
+    check_distinct_accounts(&[publisher_account, price_account])?;
+
+    check_valid_funding_account(publisher_account)?;
+    check_valid_writable_account(program_id, price_account)?;
+
+    let mut price_data = load_checked::<PriceAccount>(price_account, cmd.version)?;
+    remove_publisher(&mut price_data, publisher_account.key)
+}
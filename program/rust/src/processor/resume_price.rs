@@ -0,0 +1,72 @@
+use {
+    crate::{
+        accounts::PriceAccount,
+        c_oracle_header::{
+            PC_STATUS_HALTED,
+            PC_STATUS_UNKNOWN,
+        },
+        deserialize::{
+            load,
+            load_checked,
+        },
+        instruction::CommandHeader,
+        utils::{
+            check_distinct_accounts,
+            check_permissioned_funding_account,
+            check_valid_funding_account,
+            get_clock,
+            pyth_assert,
+        },
+        OracleError,
+    },
+    solana_program::{
+        account_info::AccountInfo,
+        entrypoint::ProgramResult,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+    std::mem::size_of,
+};
+
+/// Resume a price feed halted with `halt_price`. The aggregate status is set to
+/// `PC_STATUS_UNKNOWN` until the next successful aggregation.
+// account[0] funding account       [signer writable]
+// account[1] price account         [signer writable]
+pub fn resume_price(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let cmd = load::<CommandHeader>(instruction_data)?;
+
+    pyth_assert(
+        instruction_data.len() == size_of::<CommandHeader>(),
+        ProgramError::InvalidArgument,
+    )?;
+
+    let (funding_account, price_account, permissions_account) = match accounts {
+        [x, y, p] => Ok((x, y, p)),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
+    }?;
+    check_distinct_accounts(&[funding_account, price_account, permissions_account])?;
+
+    check_valid_funding_account(funding_account)?;
+    check_permissioned_funding_account(
+        program_id,
+        price_account,
+        funding_account,
+        permissions_account,
+        cmd,
+    )?;
+
+    let mut price_account_data = load_checked::<PriceAccount>(price_account, cmd.version)?;
+    pyth_assert(
+        price_account_data.agg_.status_ == PC_STATUS_HALTED,
+        ProgramError::InvalidArgument,
+    )?;
+    price_account_data.agg_.status_ = PC_STATUS_UNKNOWN;
+    let clock = get_clock()?;
+    price_account_data.record_status_change(PC_STATUS_HALTED, clock.slot, clock.unix_timestamp);
+
+    Ok(())
+}
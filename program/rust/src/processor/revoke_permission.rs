@@ -0,0 +1,72 @@
+use {
+    crate::{
+        deserialize::load,
+        instruction::{
+            OracleCommand,
+            PermissionGrantArgs,
+        },
+        utils::{
+            check_authorized_funding_account,
+            check_distinct_accounts,
+            check_valid_funding_account,
+            check_valid_permission_grant_account,
+            is_permission_grant,
+            pyth_assert,
+        },
+        OracleError,
+    },
+    num_traits::FromPrimitive,
+    solana_program::{
+        account_info::AccountInfo,
+        entrypoint::ProgramResult,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+    std::mem::size_of,
+};
+
+/// Revoke the permission of `authority` to run `command` by closing its permission grant. The
+/// rent of the permission grant goes to the funding account.
+// account[0] funding account       [signer writable]
+// account[1] permission grant      [writable]
+// account[2] permissions account   []
+pub fn revoke_permission(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let cmd = load::<PermissionGrantArgs>(instruction_data)?;
+
+    pyth_assert(
+        instruction_data.len() == size_of::<PermissionGrantArgs>(),
+        ProgramError::InvalidArgument,
+    )?;
+
+    let (funding_account, grant_account, permissions_account) = match accounts {
+        [x, g, p] => Ok((x, g, p)),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
+    }?;
+    check_distinct_accounts(&[funding_account, grant_account, permissions_account])?;
+
+    check_valid_funding_account(funding_account)?;
+    check_authorized_funding_account(
+        program_id,
+        funding_account,
+        permissions_account,
+        &cmd.header,
+    )?;
+
+    let command = OracleCommand::from_i32(cmd.command).ok_or(ProgramError::InvalidArgument)?;
+    check_valid_permission_grant_account(program_id, grant_account, &cmd.authority, command)?;
+    pyth_assert(
+        is_permission_grant(program_id, grant_account),
+        ProgramError::InvalidArgument,
+    )?;
+
+    // The runtime deletes the permission grant once it has no lamports left
+    let lamports = grant_account.lamports();
+    **grant_account.lamports.borrow_mut() = 0;
+    **funding_account.lamports.borrow_mut() += lamports;
+
+    Ok(())
+}
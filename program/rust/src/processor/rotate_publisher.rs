@@ -0,0 +1,117 @@
+use {
+    crate::{
+        accounts::PriceAccount,
+        deserialize::{
+            load,
+            load_checked,
+        },
+        instruction::RotatePublisherArgs,
+        processor::add_publisher::sort_price_comps,
+        utils::{
+            check_co_signed,
+            check_distinct_accounts,
+            check_permissioned_funding_account,
+            check_valid_funding_account,
+            pyth_assert,
+            split_instructions_account,
+            try_convert,
+        },
+        OracleError,
+    },
+    solana_program::{
+        account_info::AccountInfo,
+        entrypoint::ProgramResult,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+    std::mem::size_of,
+};
+
+/// Replace the key of a publisher of a price account. Unlike `del_publisher` followed by
+/// `add_publisher`, the latest price of the publisher and its statistics are kept, so the
+/// aggregate is unaffected by the rotation. The expiry and the weight of the publisher, if any,
+/// are kept as well.
+// account[0] funding account       [signer writable]
+// account[1] price account         [signer writable]
+// account[2] permissions account   []
+// account[3] sysvar_instructions   [] (optional, see `check_co_signed`)
+pub fn rotate_publisher(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let cmd_args = load::<RotatePublisherArgs>(instruction_data)?;
+
+    pyth_assert(
+        instruction_data.len() == size_of::<RotatePublisherArgs>()
+            && cmd_args.new_publisher != Pubkey::default(),
+        ProgramError::InvalidArgument,
+    )?;
+
+    let (accounts, instructions_account) = split_instructions_account(accounts);
+    let (funding_account, price_account, permissions_account) = match accounts {
+        [x, y, p] => Ok((x, y, p)),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
+    }?;
+    check_distinct_accounts(&[funding_account, price_account, permissions_account])?;
+
+    check_valid_funding_account(funding_account)?;
+    check_permissioned_funding_account(
+        program_id,
+        price_account,
+        funding_account,
+        permissions_account,
+        &cmd_args.header,
+    )?;
+    check_co_signed(
+        program_id,
+        funding_account,
+        permissions_account,
+        instructions_account,
+        &cmd_args.header,
+    )?;
+
+    {
+        let mut price_data = load_checked::<PriceAccount>(price_account, cmd_args.header.version)?;
+        let num_comps = try_convert::<u32, usize>(price_data.num_)?;
+
+        let mut old_index = None;
+        for i in 0..num_comps {
+            if price_data.comp_[i].pub_ == cmd_args.new_publisher {
+                return Err(ProgramError::InvalidArgument);
+            }
+            if price_data.comp_[i].pub_ == cmd_args.old_publisher {
+                old_index = Some(i);
+            }
+        }
+        let old_index = old_index.ok_or(ProgramError::InvalidArgument)?;
+        price_data.comp_[old_index].pub_ = cmd_args.new_publisher;
+
+        // The publishers are sorted by key, move the component and its statistics to their new
+        // place
+        let PriceAccount {
+            comp_,
+            publisher_stats_,
+            ..
+        } = &mut *price_data;
+        sort_price_comps(comp_, publisher_stats_, num_comps)?;
+    }
+
+    // The new key keeps the expiry, the pruning state and the weight of the publisher, if it has
+    // them
+    PriceAccount::replace_publisher_expiry(
+        price_account,
+        Some(&cmd_args.old_publisher),
+        &cmd_args.new_publisher,
+    )?;
+    PriceAccount::replace_publisher_pruning(
+        price_account,
+        Some(&cmd_args.old_publisher),
+        &cmd_args.new_publisher,
+    )?;
+    PriceAccount::replace_publisher_weight(
+        price_account,
+        Some(&cmd_args.old_publisher),
+        &cmd_args.new_publisher,
+    )
+}
@@ -0,0 +1,90 @@
+use {
+    crate::{
+        accounts::{
+            AggInterval,
+            PriceAccount,
+        },
+        deserialize::{
+            load,
+            load_checked,
+        },
+        instruction::SetAggIntervalArgs,
+        utils::{
+            check_distinct_accounts,
+            check_permissioned_funding_account,
+            check_valid_funding_account,
+            pyth_assert,
+        },
+        OracleError,
+    },
+    solana_program::{
+        account_info::AccountInfo,
+        entrypoint::ProgramResult,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        system_program::check_id,
+    },
+    std::mem::size_of,
+};
+
+/// Set the minimum number of slots between two aggregations of a price account (see
+/// `PriceAccount::read_agg_interval`), 1 to aggregate in every slot. The price updates of the
+/// slots in between only update their component, which saves the compute units of the
+/// aggregation for feeds that don't need a new aggregate in every slot. Price accounts created
+/// without room for the interval are resized when it is set to more than one slot, the funding
+/// account paying for the rent of the extra space.
+// account[0] funding account       [signer writable]
+// account[1] price account         [signer writable]
+// account[2] permissions account   []
+// account[3] system program        []
+pub fn set_agg_interval(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let cmd_args = load::<SetAggIntervalArgs>(instruction_data)?;
+
+    pyth_assert(
+        instruction_data.len() == size_of::<SetAggIntervalArgs>()
+            && cmd_args.agg_interval_slots != 0,
+        ProgramError::InvalidArgument,
+    )?;
+
+    let (funding_account, price_account, permissions_account, system_program) = match accounts {
+        [x, y, p, s] => Ok((x, y, p, s)),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
+    }?;
+    check_distinct_accounts(&[
+        funding_account,
+        price_account,
+        permissions_account,
+        system_program,
+    ])?;
+
+    check_valid_funding_account(funding_account)?;
+    check_permissioned_funding_account(
+        program_id,
+        price_account,
+        funding_account,
+        permissions_account,
+        &cmd_args.header,
+    )?;
+    pyth_assert(
+        check_id(system_program.key),
+        OracleError::InvalidSystemAccount.into(),
+    )?;
+    load_checked::<PriceAccount>(price_account, cmd_args.header.version)?;
+
+    if !PriceAccount::has_extension::<AggInterval>(price_account)? {
+        // Without an interval, the price account is already aggregated in every slot
+        if cmd_args.agg_interval_slots == 1 {
+            return Ok(());
+        }
+        PriceAccount::add_extension::<AggInterval>(price_account, funding_account, system_program)?;
+    }
+
+    let (_, mut agg_interval) =
+        PriceAccount::load_with_extension_mut::<AggInterval>(price_account)?;
+    agg_interval.slots_ = cmd_args.agg_interval_slots;
+    Ok(())
+}
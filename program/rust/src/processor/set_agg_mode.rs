@@ -0,0 +1,63 @@
+use {
+    crate::{
+        accounts::PriceAccount,
+        aggregation::aggregation_algorithm,
+        deserialize::{
+            load,
+            load_checked,
+        },
+        instruction::SetAggModeArgs,
+        utils::{
+            check_distinct_accounts,
+            check_permissioned_funding_account,
+            check_valid_funding_account,
+            pyth_assert,
+        },
+        OracleError,
+    },
+    solana_program::{
+        account_info::AccountInfo,
+        entrypoint::ProgramResult,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+    std::mem::size_of,
+};
+
+/// Set aggregation mode, which selects the aggregation algorithm of the price account (see
+/// `aggregation_algorithm`)
+// account[0] funding account       [signer writable]
+// account[1] price account         [signer writable]
+pub fn set_agg_mode(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let cmd = load::<SetAggModeArgs>(instruction_data)?;
+
+    pyth_assert(
+        instruction_data.len() == size_of::<SetAggModeArgs>()
+            && aggregation_algorithm(cmd.agg_mode).is_some(),
+        ProgramError::InvalidArgument,
+    )?;
+
+    let (funding_account, price_account, permissions_account) = match accounts {
+        [x, y, p] => Ok((x, y, p)),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
+    }?;
+    check_distinct_accounts(&[funding_account, price_account, permissions_account])?;
+
+    check_valid_funding_account(funding_account)?;
+    check_permissioned_funding_account(
+        program_id,
+        price_account,
+        funding_account,
+        permissions_account,
+        &cmd.header,
+    )?;
+
+    let mut price_account_data = load_checked::<PriceAccount>(price_account, cmd.header.version)?;
+    price_account_data.agg_mode_ = cmd.agg_mode;
+
+    Ok(())
+}
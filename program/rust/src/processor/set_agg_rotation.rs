@@ -0,0 +1,63 @@
+use {
+    crate::{
+        accounts::PriceAccount,
+        deserialize::{
+            load,
+            load_checked,
+        },
+        instruction::SetAggRotationArgs,
+        utils::{
+            check_distinct_accounts,
+            check_permissioned_funding_account,
+            check_valid_funding_account,
+            pyth_assert,
+        },
+        OracleError,
+    },
+    solana_program::{
+        account_info::AccountInfo,
+        entrypoint::ProgramResult,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+    std::mem::size_of,
+};
+
+/// Enable or disable aggregation rotation. While it is enabled, the publishers of the price
+/// account take turns aggregating it (see `aggregation_leader`) instead of the first publisher to
+/// update its price in each slot.
+// account[0] funding account       [signer writable]
+// account[1] price account         [signer writable]
+pub fn set_agg_rotation(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let cmd = load::<SetAggRotationArgs>(instruction_data)?;
+
+    pyth_assert(
+        instruction_data.len() == size_of::<SetAggRotationArgs>(),
+        ProgramError::InvalidArgument,
+    )?;
+    pyth_assert(cmd.enabled <= 1, ProgramError::InvalidArgument)?;
+
+    let (funding_account, price_account, permissions_account) = match accounts {
+        [x, y, p] => Ok((x, y, p)),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
+    }?;
+    check_distinct_accounts(&[funding_account, price_account, permissions_account])?;
+
+    check_valid_funding_account(funding_account)?;
+    check_permissioned_funding_account(
+        program_id,
+        price_account,
+        funding_account,
+        permissions_account,
+        &cmd.header,
+    )?;
+
+    let mut price_account_data = load_checked::<PriceAccount>(price_account, cmd.header.version)?;
+    price_account_data.agg_rotation_ = cmd.enabled;
+
+    Ok(())
+}
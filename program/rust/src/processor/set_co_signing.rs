@@ -0,0 +1,98 @@
+use {
+    crate::{
+        accounts::{
+            PermissionAccount,
+            PERMISSIONS_SEED,
+        },
+        deserialize::{
+            load,
+            load_checked,
+        },
+        instruction::SetCoSigningArgs,
+        utils::{
+            check_distinct_accounts,
+            check_is_upgrade_authority_for_program,
+            check_valid_funding_account,
+            check_valid_writable_account,
+            get_rent,
+            pyth_assert,
+            send_lamports,
+        },
+        OracleError,
+    },
+    solana_program::{
+        account_info::AccountInfo,
+        entrypoint::ProgramResult,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        system_program::check_id,
+    },
+    std::mem::size_of,
+};
+
+/// Turn the co-signing mode of the permissions account on or off (see `check_co_signed`). Like the
+/// other permissions, only the upgrade authority of the program can change it. The permissions
+/// account is resized to `PermissionAccount::CO_SIGNING_SIZE` if needed.
+// key[0] upgrade authority         [signer writable]
+// key[1] programdata account       []
+// key[2] permissions account       [writable]
+// key[3] system program            []
+pub fn set_co_signing(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let cmd_args = load::<SetCoSigningArgs>(instruction_data)?;
+
+    pyth_assert(
+        instruction_data.len() == size_of::<SetCoSigningArgs>(),
+        ProgramError::InvalidArgument,
+    )?;
+
+    let [funding_account, programdata_account, permissions_account, system_program] = match accounts
+    {
+        [w, x, y, z] => Ok([w, x, y, z]),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
+    }?;
+    check_distinct_accounts(&[
+        funding_account,
+        programdata_account,
+        permissions_account,
+        system_program,
+    ])?;
+
+    check_valid_funding_account(funding_account)?;
+    check_is_upgrade_authority_for_program(funding_account, programdata_account, program_id)?;
+
+    let (permission_pda_address, _) =
+        Pubkey::find_program_address(&[PERMISSIONS_SEED.as_bytes()], program_id);
+    pyth_assert(
+        permission_pda_address == *permissions_account.key,
+        OracleError::InvalidPda.into(),
+    )?;
+    pyth_assert(
+        check_id(system_program.key),
+        OracleError::InvalidSystemAccount.into(),
+    )?;
+    check_valid_writable_account(program_id, permissions_account)?;
+    load_checked::<PermissionAccount>(permissions_account, cmd_args.header.version)?;
+
+    if permissions_account.data_len() < PermissionAccount::CO_SIGNING_SIZE {
+        let target_rent = get_rent()?.minimum_balance(PermissionAccount::CO_SIGNING_SIZE);
+        let missing_rent = target_rent.saturating_sub(permissions_account.lamports());
+        if missing_rent > 0 {
+            send_lamports(
+                funding_account,
+                permissions_account,
+                system_program,
+                missing_rent,
+            )?;
+        }
+        permissions_account.realloc(PermissionAccount::CO_SIGNING_SIZE, true)?;
+    }
+
+    *PermissionAccount::load_co_signing_mut(permissions_account)? =
+        u32::from(cmd_args.enabled != 0);
+
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use {
+    crate::{
+        accounts::PriceAccount,
+        deserialize::{
+            load,
+            load_checked,
+        },
+        instruction::SetCpiGuardArgs,
+        utils::{
+            check_distinct_accounts,
+            check_permissioned_funding_account,
+            check_valid_funding_account,
+            pyth_assert,
+        },
+        OracleError,
+    },
+    solana_program::{
+        account_info::AccountInfo,
+        entrypoint::ProgramResult,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+    std::mem::size_of,
+};
+
+/// Enable or disable the CPI guard of a price account and set the programs that are allowed to
+/// invoke its price updates via CPI while it is enabled
+// account[0] funding account       [signer writable]
+// account[1] price account         [signer writable]
+pub fn set_cpi_guard(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let cmd = load::<SetCpiGuardArgs>(instruction_data)?;
+
+    pyth_assert(
+        instruction_data.len() == size_of::<SetCpiGuardArgs>(),
+        ProgramError::InvalidArgument,
+    )?;
+    pyth_assert(cmd.enabled <= 1, ProgramError::InvalidArgument)?;
+
+    let (funding_account, price_account, permissions_account) = match accounts {
+        [x, y, p] => Ok((x, y, p)),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
+    }?;
+    check_distinct_accounts(&[funding_account, price_account, permissions_account])?;
+
+    check_valid_funding_account(funding_account)?;
+    check_permissioned_funding_account(
+        program_id,
+        price_account,
+        funding_account,
+        permissions_account,
+        &cmd.header,
+    )?;
+
+    let mut price_account_data = load_checked::<PriceAccount>(price_account, cmd.header.version)?;
+    price_account_data.cpi_guard_ = cmd.enabled;
+    price_account_data.cpi_callers_ = cmd.allowed_callers;
+
+    Ok(())
+}
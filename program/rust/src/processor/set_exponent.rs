@@ -0,0 +1,77 @@
+use {
+    crate::{
+        accounts::PriceAccount,
+        deserialize::{
+            load,
+            load_checked,
+        },
+        instruction::SetExponentArgs,
+        utils::{
+            check_co_signed,
+            check_distinct_accounts,
+            check_exponent_range,
+            check_permissioned_funding_account,
+            check_valid_funding_account,
+            pyth_assert,
+            split_instructions_account,
+        },
+        OracleError,
+    },
+    solana_program::{
+        account_info::AccountInfo,
+        entrypoint::ProgramResult,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+    std::mem::size_of,
+};
+
+/// Change the exponent of a price account. The stored prices are rescaled to the new exponent,
+/// except for the buckets of the time machine, if any, which are cleared. Publishers must publish
+/// at the new exponent afterwards.
+// account[0] funding account       [signer writable]
+// account[1] price account         [signer writable]
+// account[2] permissions account   []
+// account[3] sysvar_instructions   [] (optional, see `check_co_signed`)
+pub fn set_exponent(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let cmd = load::<SetExponentArgs>(instruction_data)?;
+
+    pyth_assert(
+        instruction_data.len() == size_of::<SetExponentArgs>(),
+        ProgramError::InvalidArgument,
+    )?;
+    check_exponent_range(cmd.exponent)?;
+
+    let (accounts, instructions_account) = split_instructions_account(accounts);
+    let (funding_account, price_account, permissions_account) = match accounts {
+        [x, y, p] => Ok((x, y, p)),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
+    }?;
+    check_distinct_accounts(&[funding_account, price_account, permissions_account])?;
+
+    check_valid_funding_account(funding_account)?;
+    check_permissioned_funding_account(
+        program_id,
+        price_account,
+        funding_account,
+        permissions_account,
+        &cmd.header,
+    )?;
+    check_co_signed(
+        program_id,
+        funding_account,
+        permissions_account,
+        instructions_account,
+        &cmd.header,
+    )?;
+
+    let mut price_data = load_checked::<PriceAccount>(price_account, cmd.header.version)?;
+    price_data.set_exponent(cmd.exponent)?;
+    drop(price_data);
+
+    PriceAccount::clear_time_machine(price_account)
+}
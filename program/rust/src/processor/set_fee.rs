@@ -0,0 +1,95 @@
+use {
+    crate::{
+        accounts::PriceAccount,
+        deserialize::{
+            load,
+            load_checked,
+        },
+        instruction::SetFeeArgs,
+        utils::{
+            check_distinct_accounts,
+            check_permissioned_funding_account,
+            check_valid_funding_account,
+            check_valid_treasury_account,
+            get_rent,
+            pyth_assert,
+            send_lamports,
+        },
+        OracleError,
+    },
+    solana_program::{
+        account_info::AccountInfo,
+        entrypoint::ProgramResult,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        system_program::check_id,
+    },
+    std::mem::size_of,
+};
+
+/// Set the fee in lamports that publishers pay into the treasury for each price update of a price
+/// account, 0 for no fee. When a fee is set, the funding account tops up the treasury to its rent
+/// exemption so that fees smaller than it can be paid into it.
+// account[0] funding account       [signer writable]
+// account[1] price account         [signer writable]
+// account[2] permissions account   []
+// account[3] treasury account      [writable]
+// account[4] system program        []
+pub fn set_fee(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let cmd = load::<SetFeeArgs>(instruction_data)?;
+
+    pyth_assert(
+        instruction_data.len() == size_of::<SetFeeArgs>(),
+        ProgramError::InvalidArgument,
+    )?;
+
+    let (funding_account, price_account, permissions_account, treasury_account, system_program) =
+        match accounts {
+            [x, y, p, t, s] => Ok((x, y, p, t, s)),
+            _ => Err(OracleError::InvalidNumberOfAccounts),
+        }?;
+    check_distinct_accounts(&[
+        funding_account,
+        price_account,
+        permissions_account,
+        treasury_account,
+        system_program,
+    ])?;
+
+    check_valid_funding_account(funding_account)?;
+    check_permissioned_funding_account(
+        program_id,
+        price_account,
+        funding_account,
+        permissions_account,
+        &cmd.header,
+    )?;
+    check_valid_treasury_account(program_id, treasury_account)?;
+    pyth_assert(
+        check_id(system_program.key),
+        OracleError::InvalidSystemAccount.into(),
+    )?;
+
+    if cmd.fee_lamports > 0 {
+        let missing_rent = get_rent()?
+            .minimum_balance(0)
+            .saturating_sub(treasury_account.lamports());
+        if missing_rent > 0 {
+            send_lamports(
+                funding_account,
+                treasury_account,
+                system_program,
+                missing_rent,
+            )?;
+        }
+    }
+
+    let mut price_account_data = load_checked::<PriceAccount>(price_account, cmd.header.version)?;
+    price_account_data.fee_lamports_ = cmd.fee_lamports;
+
+    Ok(())
+}
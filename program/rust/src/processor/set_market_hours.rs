@@ -0,0 +1,66 @@
+use {
+    crate::{
+        accounts::{
+            MarketHours,
+            PriceAccount,
+        },
+        deserialize::{
+            load,
+            load_checked,
+        },
+        instruction::SetMarketHoursArgs,
+        utils::{
+            check_distinct_accounts,
+            check_permissioned_funding_account,
+            check_valid_funding_account,
+            pyth_assert,
+        },
+        OracleError,
+    },
+    solana_program::{
+        account_info::AccountInfo,
+        entrypoint::ProgramResult,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+    std::mem::size_of,
+};
+
+/// Set the weekly trading sessions of a price account, outside of which the aggregation fails.
+/// Closing every day removes the schedule.
+// account[0] funding account       [signer writable]
+// account[1] price account         [signer writable]
+pub fn set_market_hours(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let cmd = load::<SetMarketHoursArgs>(instruction_data)?;
+
+    pyth_assert(
+        instruction_data.len() == size_of::<SetMarketHoursArgs>(),
+        ProgramError::InvalidArgument,
+    )?;
+    let market_hours = MarketHours::new(cmd.sessions);
+    pyth_assert(market_hours.is_valid(), ProgramError::InvalidArgument)?;
+
+    let (funding_account, price_account, permissions_account) = match accounts {
+        [x, y, p] => Ok((x, y, p)),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
+    }?;
+    check_distinct_accounts(&[funding_account, price_account, permissions_account])?;
+
+    check_valid_funding_account(funding_account)?;
+    check_permissioned_funding_account(
+        program_id,
+        price_account,
+        funding_account,
+        permissions_account,
+        &cmd.header,
+    )?;
+
+    let mut price_account_data = load_checked::<PriceAccount>(price_account, cmd.header.version)?;
+    price_account_data.market_hours_ = market_hours;
+
+    Ok(())
+}
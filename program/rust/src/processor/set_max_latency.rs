@@ -7,6 +7,7 @@ use {
         },
         instruction::SetMaxLatencyArgs,
         utils::{
+            check_distinct_accounts,
             check_permissioned_funding_account,
             check_valid_funding_account,
             pyth_assert,
@@ -41,6 +42,7 @@ pub fn set_max_latency(
         [x, y, p] => Ok((x, y, p)),
         _ => Err(OracleError::InvalidNumberOfAccounts),
     }?;
+    check_distinct_accounts(&[funding_account, price_account, permissions_account])?;
 
     check_valid_funding_account(funding_account)?;
     check_permissioned_funding_account(
@@ -0,0 +1,61 @@
+use {
+    crate::{
+        accounts::PriceAccount,
+        deserialize::{
+            load,
+            load_checked,
+        },
+        instruction::SetMaxUpdatesPerSlotArgs,
+        utils::{
+            check_distinct_accounts,
+            check_permissioned_funding_account,
+            check_valid_funding_account,
+            pyth_assert,
+        },
+        OracleError,
+    },
+    solana_program::{
+        account_info::AccountInfo,
+        entrypoint::ProgramResult,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+    std::mem::size_of,
+};
+
+/// Set the maximum number of price updates accepted from each publisher in a slot, 0 for no limit.
+/// Further updates in the same slot are rejected.
+// account[0] funding account       [signer writable]
+// account[1] price account         [signer writable]
+pub fn set_max_updates_per_slot(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let cmd = load::<SetMaxUpdatesPerSlotArgs>(instruction_data)?; // Loading SetMaxUpdatesPerSlotArgs
+
+    pyth_assert(
+        instruction_data.len() == size_of::<SetMaxUpdatesPerSlotArgs>(), // Checking size of SetMaxUpdatesPerSlotArgs
+        ProgramError::InvalidArgument,
+    )?;
+
+    let (funding_account, price_account, permissions_account) = match accounts {
+        [x, y, p] => Ok((x, y, p)),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
+    }?;
+    check_distinct_accounts(&[funding_account, price_account, permissions_account])?;
+
+    check_valid_funding_account(funding_account)?;
+    check_permissioned_funding_account(
+        program_id,
+        price_account,
+        funding_account,
+        permissions_account,
+        &cmd.header,
+    )?;
+
+    let mut price_account_data = load_checked::<PriceAccount>(price_account, cmd.header.version)?;
+    price_account_data.max_slot_updates_ = cmd.max_updates_per_slot;
+
+    Ok(())
+}
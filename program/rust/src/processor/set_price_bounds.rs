@@ -0,0 +1,66 @@
+use {
+    crate::{
+        accounts::PriceAccount,
+        deserialize::{
+            load,
+            load_checked,
+        },
+        instruction::SetPriceBoundsArgs,
+        utils::{
+            check_distinct_accounts,
+            check_permissioned_funding_account,
+            check_valid_funding_account,
+            pyth_assert,
+        },
+        OracleError,
+    },
+    solana_program::{
+        account_info::AccountInfo,
+        entrypoint::ProgramResult,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+    std::mem::size_of,
+};
+
+/// Set the price bounds: quotes below `min_price` or above `max_price` are excluded from the
+/// aggregate, both 0 to include all quotes
+// account[0] funding account       [signer writable]
+// account[1] price account         [signer writable]
+pub fn set_price_bounds(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let cmd = load::<SetPriceBoundsArgs>(instruction_data)?; // Loading SetPriceBoundsArgs
+
+    pyth_assert(
+        instruction_data.len() == size_of::<SetPriceBoundsArgs>(), // Checking size of SetPriceBoundsArgs
+        ProgramError::InvalidArgument,
+    )?;
+    pyth_assert(
+        cmd.min_price <= cmd.max_price,
+        ProgramError::InvalidArgument,
+    )?;
+
+    let (funding_account, price_account, permissions_account) = match accounts {
+        [x, y, p] => Ok((x, y, p)),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
+    }?;
+    check_distinct_accounts(&[funding_account, price_account, permissions_account])?;
+
+    check_valid_funding_account(funding_account)?;
+    check_permissioned_funding_account(
+        program_id,
+        price_account,
+        funding_account,
+        permissions_account,
+        &cmd.header,
+    )?;
+
+    let mut price_account_data = load_checked::<PriceAccount>(price_account, cmd.header.version)?;
+    price_account_data.min_price_ = cmd.min_price;
+    price_account_data.max_price_ = cmd.max_price;
+
+    Ok(())
+}
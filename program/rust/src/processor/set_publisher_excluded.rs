@@ -0,0 +1,75 @@
+use {
+    crate::{
+        accounts::{
+            PriceAccount,
+            PublisherStats,
+        },
+        deserialize::{
+            load,
+            load_checked,
+        },
+        instruction::SetPublisherExcludedArgs,
+        utils::{
+            check_distinct_accounts,
+            check_permissioned_funding_account,
+            check_valid_funding_account,
+            pyth_assert,
+            try_convert,
+        },
+        OracleError,
+    },
+    solana_program::{
+        account_info::AccountInfo,
+        entrypoint::ProgramResult,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+    std::mem::size_of,
+};
+
+/// Exclude a publisher from the aggregation of a price account, or include it again. The
+/// publisher keeps publishing, e.g. for monitoring, but its quotes don't count towards the
+/// aggregate until it is included again.
+// account[0] funding account       [signer writable]
+// account[1] price account         [signer writable]
+// account[2] permissions account   []
+pub fn set_publisher_excluded(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let cmd_args = load::<SetPublisherExcludedArgs>(instruction_data)?;
+
+    pyth_assert(
+        instruction_data.len() == size_of::<SetPublisherExcludedArgs>()
+            && cmd_args.publisher != Pubkey::default()
+            && cmd_args.excluded <= 1,
+        ProgramError::InvalidArgument,
+    )?;
+
+    let (funding_account, price_account, permissions_account) = match accounts {
+        [x, y, p] => Ok((x, y, p)),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
+    }?;
+    check_distinct_accounts(&[funding_account, price_account, permissions_account])?;
+
+    check_valid_funding_account(funding_account)?;
+    check_permissioned_funding_account(
+        program_id,
+        price_account,
+        funding_account,
+        permissions_account,
+        &cmd_args.header,
+    )?;
+
+    let mut price_data = load_checked::<PriceAccount>(price_account, cmd_args.header.version)?;
+
+    for i in 0..(try_convert::<u32, usize>(price_data.num_)?) {
+        if cmd_args.publisher == price_data.comp_[i].pub_ {
+            let stats = &mut price_data.publisher_stats_[i];
+            stats.excluded_ = (stats.excluded_ & PublisherStats::PRUNED) | cmd_args.excluded;
+            return Ok(());
+        }
+    }
+    Err(ProgramError::InvalidArgument)
+}
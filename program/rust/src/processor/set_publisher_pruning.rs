@@ -0,0 +1,104 @@
+use {
+    crate::{
+        accounts::{
+            PriceAccount,
+            PruningState,
+            PublisherPruning,
+            PublisherStats,
+        },
+        deserialize::{
+            load,
+            load_checked,
+        },
+        instruction::SetPublisherPruningArgs,
+        utils::{
+            check_distinct_accounts,
+            check_permissioned_funding_account,
+            check_valid_funding_account,
+            pyth_assert,
+        },
+        OracleError,
+    },
+    bytemuck::Zeroable,
+    solana_program::{
+        account_info::AccountInfo,
+        entrypoint::ProgramResult,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        system_program::check_id,
+    },
+    std::mem::size_of,
+};
+
+/// Configure the automatic pruning of the publishers of a price account (see
+/// `PublisherPruning`). Changing the thresholds keeps the state of the publishers, disabling the
+/// pruning admits all of them again. Price accounts created without room for the pruning are
+/// resized when it is enabled, the funding account paying for the rent of the extra space.
+// account[0] funding account       [signer writable]
+// account[1] price account         [signer writable]
+// account[2] permissions account   []
+// account[3] system program        []
+pub fn set_publisher_pruning(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let cmd_args = load::<SetPublisherPruningArgs>(instruction_data)?;
+
+    pyth_assert(
+        instruction_data.len() == size_of::<SetPublisherPruningArgs>()
+            && (cmd_args.prune_rounds == 0 || cmd_args.readmit_rounds != 0),
+        ProgramError::InvalidArgument,
+    )?;
+
+    let (funding_account, price_account, permissions_account, system_program) = match accounts {
+        [x, y, p, s] => Ok((x, y, p, s)),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
+    }?;
+    check_distinct_accounts(&[
+        funding_account,
+        price_account,
+        permissions_account,
+        system_program,
+    ])?;
+
+    check_valid_funding_account(funding_account)?;
+    check_permissioned_funding_account(
+        program_id,
+        price_account,
+        funding_account,
+        permissions_account,
+        &cmd_args.header,
+    )?;
+    pyth_assert(
+        check_id(system_program.key),
+        OracleError::InvalidSystemAccount.into(),
+    )?;
+    load_checked::<PriceAccount>(price_account, cmd_args.header.version)?;
+
+    if !PriceAccount::has_extension::<PublisherPruning>(price_account)? {
+        // Without room for the pruning, the pruning is already disabled
+        if cmd_args.prune_rounds == 0 {
+            return Ok(());
+        }
+        PriceAccount::add_extension::<PublisherPruning>(
+            price_account,
+            funding_account,
+            system_program,
+        )?;
+    }
+
+    let (mut price_data, mut pruning) =
+        PriceAccount::load_with_extension_mut::<PublisherPruning>(price_account)?;
+    pruning.prune_rounds_ = cmd_args.prune_rounds;
+    pruning.readmit_rounds_ = cmd_args.readmit_rounds;
+    if cmd_args.prune_rounds == 0 {
+        for state in pruning.states_.iter_mut() {
+            *state = PruningState::zeroed();
+        }
+        for stats in price_data.publisher_stats_.iter_mut() {
+            stats.excluded_ &= !PublisherStats::PRUNED;
+        }
+    }
+    Ok(())
+}
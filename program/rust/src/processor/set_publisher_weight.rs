@@ -0,0 +1,104 @@
+use {
+    crate::{
+        accounts::{
+            PriceAccount,
+            PublisherWeights,
+        },
+        deserialize::{
+            load,
+            load_checked,
+        },
+        instruction::SetPublisherWeightArgs,
+        utils::{
+            check_distinct_accounts,
+            check_permissioned_funding_account,
+            check_valid_funding_account,
+            pyth_assert,
+            try_convert,
+        },
+        OracleError,
+    },
+    solana_program::{
+        account_info::AccountInfo,
+        entrypoint::ProgramResult,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        system_program::check_id,
+    },
+    std::mem::size_of,
+};
+
+/// Set the weight of a publisher for the weighted aggregation mode. A weight of 0 is
+/// treated as 1 during aggregation. The weights are stored after the price account (see
+/// `PublisherWeights`), price accounts created without room for them are resized when a publisher
+/// is given a non-zero weight, the funding account paying for the rent of the extra space.
+// account[0] funding account       [signer writable]
+// account[1] price account         [signer writable]
+// account[2] permissions account   []
+// account[3] system program        []
+pub fn set_publisher_weight(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let cmd_args = load::<SetPublisherWeightArgs>(instruction_data)?;
+
+    pyth_assert(
+        instruction_data.len() == size_of::<SetPublisherWeightArgs>()
+            && cmd_args.publisher != Pubkey::default(),
+        ProgramError::InvalidArgument,
+    )?;
+
+    let (funding_account, price_account, permissions_account, system_program) = match accounts {
+        [x, y, p, s] => Ok((x, y, p, s)),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
+    }?;
+    check_distinct_accounts(&[
+        funding_account,
+        price_account,
+        permissions_account,
+        system_program,
+    ])?;
+
+    check_valid_funding_account(funding_account)?;
+    check_permissioned_funding_account(
+        program_id,
+        price_account,
+        funding_account,
+        permissions_account,
+        &cmd_args.header,
+    )?;
+    pyth_assert(
+        check_id(system_program.key),
+        OracleError::InvalidSystemAccount.into(),
+    )?;
+
+    let publishers = {
+        let price_data = load_checked::<PriceAccount>(price_account, cmd_args.header.version)?;
+        let num_comps = try_convert::<u32, usize>(price_data.num_)?;
+        price_data.comp_[..num_comps]
+            .iter()
+            .map(|component| component.pub_)
+            .collect::<Vec<_>>()
+    };
+    pyth_assert(
+        publishers.contains(&cmd_args.publisher),
+        ProgramError::InvalidArgument,
+    )?;
+
+    if !PriceAccount::has_extension::<PublisherWeights>(price_account)? {
+        // Without room for the weights, every publisher already has weight 0
+        if cmd_args.weight == 0 {
+            return Ok(());
+        }
+        PriceAccount::add_extension::<PublisherWeights>(
+            price_account,
+            funding_account,
+            system_program,
+        )?;
+    }
+
+    let (_, mut weights) =
+        PriceAccount::load_with_extension_mut::<PublisherWeights>(price_account)?;
+    weights.set(&cmd_args.publisher, cmd_args.weight, &publishers)
+}
@@ -0,0 +1,107 @@
+use {
+    crate::{
+        accounts::{
+            PriceAccount,
+            ProductAccount,
+        },
+        deserialize::{
+            load,
+            load_checked,
+            load_checked_read_only,
+        },
+        instruction::CommandHeader,
+        utils::{
+            check_distinct_accounts,
+            check_permissioned_funding_account,
+            check_valid_funding_account,
+            check_valid_readable_account,
+            get_rent,
+            pyth_assert,
+            send_lamports,
+        },
+        OracleError,
+    },
+    solana_program::{
+        account_info::AccountInfo,
+        entrypoint::ProgramResult,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        system_program::check_id,
+    },
+    std::mem::size_of,
+};
+
+/// Set the reference price account of a product, which consumers use as the primary price feed of
+/// the product among its price accounts. The price account must be a price account of the
+/// product. Product accounts created without room for the reference price are resized, the
+/// funding account paying for the rent of the extra space.
+// account[0] funding account       [signer writable]
+// account[1] product account       [writable]
+// account[2] price account         []
+// account[3] permissions account   []
+// account[4] system program        []
+pub fn set_reference_price(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let cmd = load::<CommandHeader>(instruction_data)?;
+
+    pyth_assert(
+        instruction_data.len() == size_of::<CommandHeader>(),
+        ProgramError::InvalidArgument,
+    )?;
+
+    let (funding_account, product_account, price_account, permissions_account, system_program) =
+        match accounts {
+            [x, y, z, p, s] => Ok((x, y, z, p, s)),
+            _ => Err(OracleError::InvalidNumberOfAccounts),
+        }?;
+    check_distinct_accounts(&[
+        funding_account,
+        product_account,
+        price_account,
+        permissions_account,
+        system_program,
+    ])?;
+
+    check_valid_funding_account(funding_account)?;
+    check_permissioned_funding_account(
+        program_id,
+        product_account,
+        funding_account,
+        permissions_account,
+        cmd,
+    )?;
+    check_valid_readable_account(program_id, price_account)?;
+    pyth_assert(
+        check_id(system_program.key),
+        OracleError::InvalidSystemAccount.into(),
+    )?;
+
+    load_checked::<ProductAccount>(product_account, cmd.version)?;
+    pyth_assert(
+        load_checked_read_only::<PriceAccount>(price_account, cmd.version)?.product_account
+            == *product_account.key,
+        OracleError::InvalidReferencePrice.into(),
+    )?;
+
+    // Make room for the reference price in product accounts created before it
+    if product_account.data_len() < ProductAccount::REFERENCE_PRICE_SIZE {
+        let target_rent = get_rent()?.minimum_balance(ProductAccount::REFERENCE_PRICE_SIZE);
+        let missing_rent = target_rent.saturating_sub(product_account.lamports());
+        if missing_rent > 0 {
+            send_lamports(
+                funding_account,
+                product_account,
+                system_program,
+                missing_rent,
+            )?;
+        }
+        product_account.realloc(ProductAccount::REFERENCE_PRICE_SIZE, true)?;
+    }
+
+    *ProductAccount::load_reference_price_mut(product_account)? = *price_account.key;
+
+    Ok(())
+}
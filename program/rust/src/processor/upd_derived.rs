@@ -0,0 +1,89 @@
+use {
+    crate::{
+        accounts::{
+            DerivedPriceAccount,
+            PriceAccount,
+        },
+        aggregation::upd_derived_aggregate,
+        deserialize::{
+            load,
+            load_checked,
+            load_checked_read_only,
+        },
+        instruction::CommandHeader,
+        utils::{
+            check_distinct_accounts,
+            check_valid_readable_account,
+            check_valid_writable_account,
+            pyth_assert,
+        },
+        OracleError,
+    },
+    solana_program::{
+        account_info::AccountInfo,
+        clock::Clock,
+        entrypoint::ProgramResult,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        sysvar::Sysvar,
+    },
+    std::mem::size_of,
+};
+
+/// Update the aggregate of a derived price account to the ratio of the current aggregates of its
+/// base and quote price accounts (see `upd_derived_aggregate`). The instruction is permissionless:
+/// it only reads the parents, so anyone can crank it. A ratio that can't be computed, e.g. because
+/// a parent isn't trading, sets the aggregate status to `PC_STATUS_UNKNOWN` without failing.
+// account[0] derived price account [writable]
+// account[1] base price account    []
+// account[2] quote price account   []
+// account[3] sysvar_clock account  []
+pub fn upd_derived(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let cmd = load::<CommandHeader>(instruction_data)?;
+
+    pyth_assert(
+        instruction_data.len() == size_of::<CommandHeader>(),
+        ProgramError::InvalidArgument,
+    )?;
+
+    let (derived_price_account, base_price_account, quote_price_account, clock_account) =
+        match accounts {
+            [x, b, q, c] => Ok((x, b, q, c)),
+            _ => Err(OracleError::InvalidNumberOfAccounts),
+        }?;
+    check_distinct_accounts(&[
+        derived_price_account,
+        base_price_account,
+        quote_price_account,
+        clock_account,
+    ])?;
+
+    check_valid_writable_account(program_id, derived_price_account)?;
+    check_valid_readable_account(program_id, base_price_account)?;
+    check_valid_readable_account(program_id, quote_price_account)?;
+    let clock = Clock::from_account_info(clock_account)?;
+
+    let mut derived_price_data =
+        load_checked::<DerivedPriceAccount>(derived_price_account, cmd.version)?;
+    pyth_assert(
+        derived_price_data.base_price_account_ == *base_price_account.key
+            && derived_price_data.quote_price_account_ == *quote_price_account.key,
+        OracleError::InvalidDerivedPrice.into(),
+    )?;
+    let base_price_data = load_checked_read_only::<PriceAccount>(base_price_account, cmd.version)?;
+    let quote_price_data =
+        load_checked_read_only::<PriceAccount>(quote_price_account, cmd.version)?;
+
+    upd_derived_aggregate(
+        &mut derived_price_data,
+        &base_price_data,
+        &quote_price_data,
+        clock.slot,
+    );
+
+    Ok(())
+}
@@ -11,10 +11,13 @@ use {
         },
         instruction::UpdPermissionsArgs,
         utils::{
+            check_distinct_accounts,
             check_is_upgrade_authority_for_program,
             check_valid_funding_account,
             check_valid_writable_account,
+            get_rent,
             pyth_assert,
+            send_lamports,
         },
         OracleError,
     },
@@ -43,6 +46,12 @@ pub fn upd_permissions(
         [w, x, y, z] => Ok([w, x, y, z]),
         _ => Err(OracleError::InvalidNumberOfAccounts),
     }?;
+    check_distinct_accounts(&[
+        funding_account,
+        programdata_account,
+        permissions_account,
+        system_program,
+    ])?;
 
     let cmd_args = load::<UpdPermissionsArgs>(instruction_data)?;
 
@@ -74,6 +83,21 @@ pub fn upd_permissions(
 
     check_valid_writable_account(program_id, permissions_account)?;
 
+    // Make room for the last price feed index in permissions accounts created before it
+    if permissions_account.data_len() < PermissionAccount::NEW_SIZE {
+        let target_rent = get_rent()?.minimum_balance(PermissionAccount::NEW_SIZE);
+        let missing_rent = target_rent.saturating_sub(permissions_account.lamports());
+        if missing_rent > 0 {
+            send_lamports(
+                funding_account,
+                permissions_account,
+                system_program,
+                missing_rent,
+            )?;
+        }
+        permissions_account.realloc(PermissionAccount::NEW_SIZE, true)?;
+    }
+
     let mut permissions_account_data =
         load_checked::<PermissionAccount>(permissions_account, cmd_args.header.version)?;
     permissions_account_data.master_authority = cmd_args.master_authority;
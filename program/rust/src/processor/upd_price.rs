@@ -1,27 +1,77 @@
+#[cfg(feature = "wormhole")]
+use crate::wormhole::{
+    post_price_attestation,
+    split_wormhole_accounts,
+};
 use {
     crate::{
         accounts::{
+            ComponentLogAccount,
+            ComponentLogEntry,
+            EmaWindow,
+            LastTrade,
+            MessageBufferAccount,
             PriceAccount,
             PriceComponent,
             PriceInfo,
+            PublisherPruning,
+            PublisherWeights,
             PythOracleSerialize,
             UPD_PRICE_WRITE_SEED,
         },
+        aggregation::{
+            aggregation_compute_units,
+            is_aggregating_update,
+            max_latency,
+            upd_aggregate_with_weights,
+            upd_publisher_pruning,
+            upd_publisher_stats,
+        },
+        c_oracle_header::{
+            MAX_PUBLISH_TIME_DRIFT,
+            PC_NUM_COMP,
+            PC_STATUS_HALTED,
+        },
         deserialize::{
             load,
             load_checked,
+            load_checked_with_tail,
+        },
+        events::{
+            AggregatePriceUpdate,
+            AggregationDeferred,
+            Event,
+        },
+        instruction::{
+            CommandHeader,
+            UpdPriceArgs,
+            UpdPriceBatchEntry,
+            UpdPriceWithBudgetArgs,
+            UpdPriceWithTimeArgs,
+            UpdPriceWithTradeArgs,
         },
-        instruction::UpdPriceArgs,
+        messages::PriceMessage,
         utils::{
+            check_distinct_accounts,
             check_valid_funding_account,
+            check_valid_treasury_account,
             check_valid_writable_account,
+            get_direct_caller,
+            get_num_skipped_slots,
             get_status_for_conf_price_ratio,
             is_component_update,
             pyth_assert,
+            send_lamports,
+            split_component_log_account,
+            split_fee_accounts,
+            split_instructions_account,
+            split_message_buffer_account,
+            split_slot_hashes_account,
             try_convert,
         },
         OracleError,
     },
+    bytemuck::bytes_of,
     solana_program::{
         account_info::AccountInfo,
         clock::Clock,
@@ -30,38 +80,36 @@ use {
             AccountMeta,
             Instruction,
         },
-        program::invoke_signed,
+        msg,
+        program::{
+            invoke_signed,
+            set_return_data,
+        },
         program_error::ProgramError,
         program_memory::sol_memcmp,
         pubkey::Pubkey,
         sysvar::Sysvar,
     },
+    std::mem::size_of,
 };
 
 #[cfg(target_arch = "bpf")]
 #[link(name = "cpyth-bpf")]
 extern "C" {
-    pub fn c_upd_aggregate_pythnet(_input: *mut u8, clock_slot: u64, clock_timestamp: i64) -> bool;
-
     #[allow(unused)]
-    pub fn c_upd_twap(_input: *mut u8, nslots: i64);
+    pub fn c_upd_twap(_input: *mut u8, nslots: i64, decay_rate: i64, max_diff: i64);
 }
 
 #[cfg(not(target_arch = "bpf"))]
 #[link(name = "cpyth-native")]
 extern "C" {
-    pub fn c_upd_aggregate_pythnet(_input: *mut u8, clock_slot: u64, clock_timestamp: i64) -> bool;
-
     #[allow(unused)]
-    pub fn c_upd_twap(_input: *mut u8, nslots: i64);
-}
-
-#[inline]
-pub unsafe fn c_upd_aggregate(input: *mut u8, clock_slot: u64, clock_timestamp: i64) -> bool {
-    c_upd_aggregate_pythnet(input, clock_slot, clock_timestamp)
+    pub fn c_upd_twap(_input: *mut u8, nslots: i64, decay_rate: i64, max_diff: i64);
 }
 
-/// Publish component price, never returning an error even if the update failed
+/// Publish component price, never returning an error even if the update failed. A rejected update
+/// logs the reason, so that publishers sending many updates in one transaction can tell which ones
+/// were not applied.
 // account[0] funding account       [signer writable]
 // account[1] price account         [writable]
 // account[2] sysvar_clock account  []
@@ -71,14 +119,24 @@ pub fn upd_price_no_fail_on_error(
     instruction_data: &[u8],
 ) -> ProgramResult {
     match upd_price(program_id, accounts, instruction_data) {
-        Err(_) => Ok(()),
+        Err(err) => {
+            msg!("upd_price_no_fail_on_error: rejected: {:?}", err);
+            Ok(())
+        }
         Ok(value) => Ok(value),
     }
 }
 
 /// Update a publisher's price for the provided product. If this update is
 /// the first update in a slot, this operation will also trigger price aggregation
-/// and result in a new aggregate price in the account.
+/// and result in a new aggregate price in the account. Price accounts with an aggregation interval
+/// (see `set_agg_interval`) are only aggregated once that many slots have passed since their last
+/// aggregation, and price accounts with aggregation rotation (see `agg_rotation_`) only by the
+/// update of the leader of the slot. A new aggregate is also sent as return data, see
+/// `return_aggregate`. An update without enough compute units left for the aggregation (see
+/// `UpdPriceWithBudgetArgs::remaining_compute_units`) only updates the component, logs an
+/// `AggregationDeferred` event and records the deferral in `StatusChange::agg_deferred_`, a later
+/// update of the slot aggregating.
 ///
 /// account[0] the publisher's account (funds the tx) [signer writable]
 ///            fails if the publisher's public key is not permissioned for the price account.
@@ -96,12 +154,125 @@ pub fn upd_price_no_fail_on_error(
 ///            which allows the called-into program to authenticate that it is being invoked by the oracle
 ///            program. []
 /// account[6] message buffer data [writable]
+///
+/// The `SlotHashes` sysvar account can be appended to any of these account lists. If provided,
+/// the slots skipped by the cluster don't count toward the max latency of the quotes in the
+/// aggregation, so that quotes aren't considered stale just because no blocks were produced.
+///
+/// The `Instructions` sysvar account can be appended as well, before the `SlotHashes` sysvar
+/// account if both are provided. It is required by price accounts with a CPI guard (see
+/// `cpi_guard_`), which only accept price updates that are top-level instructions or directly
+/// invoked by one of their allowed programs.
+///
+/// A writable message buffer account (see `MessageBufferAccount`) can be appended before these
+/// sysvar accounts. If the update triggers a successful aggregation, the new aggregate is
+/// appended to the message buffer as a `PriceMessage`.
+///
+/// The writable component log account of the price account (see `ComponentLogAccount`) can be
+/// appended before the message buffer account. The component update, if any, is then appended to
+/// the log.
+///
+/// The treasury account followed by the system program account can be appended before all of
+/// them. They are required by price accounts that charge a fee for price updates (see
+/// `fee_lamports_`), which the publisher pays into the treasury.
+///
+/// With the `wormhole` feature, the accounts of the Wormhole core bridge (see `WormholeAccounts`)
+/// can be appended after the fee accounts. If the update triggers a successful aggregation, an
+/// attestation of the new aggregate is posted to the bridge (see `post_price_attestation`).
 pub fn upd_price(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     instruction_data: &[u8],
+) -> ProgramResult {
+    process_upd_price(program_id, accounts, instruction_data, None, None, 0)
+}
+
+/// Update a publisher's price like `upd_price`, also recording the time at which the publisher
+/// observed the price (`publish_time`) so that consumers can measure the end-to-end latency. The
+/// update is rejected if the publish time is more than `MAX_PUBLISH_TIME_DRIFT` seconds ahead of
+/// the clock.
+///
+/// Takes the same accounts as `upd_price`.
+pub fn upd_price_with_time(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let cmd_args = load::<UpdPriceWithTimeArgs>(instruction_data)?;
+    process_upd_price(
+        program_id,
+        accounts,
+        instruction_data,
+        Some(cmd_args.publish_time),
+        None,
+        0,
+    )
+}
+
+/// Update a publisher's price like `upd_price`, also recording the last trade observed by the
+/// publisher (price, size and venue) as the freshest trade of the publisher (see
+/// `PublisherStats::last_trade_`). An update with a trade size of 0 doesn't carry a trade and
+/// leaves the last trade of the publisher unchanged, like the other price updates.
+///
+/// Takes the same accounts as `upd_price`.
+pub fn upd_price_with_trade(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let cmd_args = load::<UpdPriceWithTradeArgs>(instruction_data)?;
+    let trade = (cmd_args.trade_size != 0).then_some(LastTrade {
+        price_:           cmd_args.trade_price,
+        size_:            cmd_args.trade_size,
+        pub_slot_:        cmd_args.publishing_slot,
+        venue_id_:        cmd_args.venue_id,
+        heartbeat_slots_: 0,
+    });
+    process_upd_price(program_id, accounts, instruction_data, None, trade, 0)
+}
+
+/// Update a publisher's price like `upd_price`, leaving the aggregation to a later price update
+/// if fewer compute units than it may need are left for the instruction (see
+/// `UpdPriceWithBudgetArgs::remaining_compute_units`).
+///
+/// Takes the same accounts as `upd_price`.
+pub fn upd_price_with_budget(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let cmd_args = load::<UpdPriceWithBudgetArgs>(instruction_data)?;
+    process_upd_price(
+        program_id,
+        accounts,
+        instruction_data,
+        None,
+        None,
+        cmd_args.remaining_compute_units,
+    )
+}
+
+/// Process an `upd_price` instruction, whose arguments start with `UpdPriceArgs`. `publish_time`
+/// is the publish time of the update and `trade` its trade print if the instruction has them, and
+/// `remaining_compute_units` the compute units left for it, 0 if unknown.
+fn process_upd_price(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+    publish_time: Option<i64>,
+    trade: Option<LastTrade>,
+    remaining_compute_units: u32,
 ) -> ProgramResult {
     let cmd_args = load::<UpdPriceArgs>(instruction_data)?;
+    let (accounts, slot_hashes_account) = split_slot_hashes_account(accounts);
+    let (accounts, instructions_account) = split_instructions_account(accounts);
+    let (accounts, message_buffer_account) =
+        split_message_buffer_account(program_id, accounts, cmd_args.header.version);
+    let (accounts, component_log_account) =
+        split_component_log_account(program_id, accounts, cmd_args.header.version);
+    #[cfg(feature = "wormhole")]
+    let (accounts, wormhole_accounts) = split_wormhole_accounts(accounts);
+    let (accounts, fee_accounts) = split_fee_accounts(accounts);
 
     #[allow(unused_variables)]
     let (funding_account, price_account, clock_account, maybe_accumulator_accounts) = match accounts
@@ -123,97 +294,135 @@ pub fn upd_price(
         )),
         _ => Err(OracleError::InvalidNumberOfAccounts),
     }?;
+    check_distinct_accounts(&[funding_account, price_account, clock_account])?;
 
     check_valid_funding_account(funding_account)?;
     check_valid_writable_account(program_id, price_account)?;
+    if let Some(message_buffer_account) = message_buffer_account {
+        check_valid_writable_account(program_id, message_buffer_account)?;
+    }
+    if let Some(component_log_account) = component_log_account {
+        check_valid_writable_account(program_id, component_log_account)?;
+        pyth_assert(
+            load_checked::<ComponentLogAccount>(component_log_account, cmd_args.header.version)?
+                .price_account_
+                == *price_account.key,
+            OracleError::InvalidComponentLog.into(),
+        )?;
+    }
     // Check clock
     let clock = Clock::from_account_info(clock_account)?;
 
-    let publisher_index: usize;
-    let latest_aggregate_price: PriceInfo;
-
-    // The price_data borrow happens in a scope because it must be
-    // dropped before we borrow again as raw data pointer for the C
-    // aggregation logic.
-    {
-        // Verify that symbol account is initialized
-        let price_data = load_checked::<PriceAccount>(price_account, cmd_args.header.version)?;
-
-        publisher_index = match find_publisher_index(
-            &price_data.comp_[..try_convert::<u32, usize>(price_data.num_)?],
-            funding_account.key,
-        ) {
-            Some(index) => index,
-            None => {
-                return Err(OracleError::PermissionViolation.into());
-            }
-        };
-
-        latest_aggregate_price = price_data.agg_;
-        let latest_publisher_price = price_data.comp_[publisher_index].latest_;
-
-        // Check that publisher is publishing a more recent price
+    if let Some(publish_time) = publish_time {
         pyth_assert(
-            !is_component_update(cmd_args)?
-                || (cmd_args.publishing_slot > latest_publisher_price.pub_slot_
-                    && cmd_args.publishing_slot <= clock.slot),
-            ProgramError::InvalidArgument,
+            publish_time <= clock.unix_timestamp.saturating_add(MAX_PUBLISH_TIME_DRIFT),
+            OracleError::PublishTimeInFuture.into(),
         )?;
     }
 
-    // Try to update the aggregate
-    #[allow(unused_variables)]
-    if clock.slot > latest_aggregate_price.pub_slot_ {
-        let updated = unsafe {
-            // NOTE: c_upd_aggregate must use a raw pointer to price
-            // data. Solana's `<account>.borrow_*` methods require exclusive
-            // access, i.e. no other borrow can exist for the account.
-            c_upd_aggregate(
-                price_account.try_borrow_mut_data()?.as_mut_ptr(),
-                clock.slot,
-                clock.unix_timestamp,
-            )
-        };
-
-        // If the aggregate was successfully updated, calculate the difference and update TWAP.
-        if updated {
-            let agg_diff = (clock.slot as i64)
-                - load_checked::<PriceAccount>(price_account, cmd_args.header.version)?.prev_slot_
-                    as i64;
-            // Encapsulate TWAP update logic in a function to minimize unsafe block scope.
-            unsafe {
-                c_upd_twap(price_account.try_borrow_mut_data()?.as_mut_ptr(), agg_diff);
+    let is_component_update = is_component_update(cmd_args)?;
+    let (mut price_data, tail) =
+        load_checked_with_tail::<PriceAccount>(price_account, cmd_args.header.version)?;
+    let publisher_expiry = PriceAccount::publisher_expiry(&tail, funding_account.key);
+    let agg_interval_slots = PriceAccount::agg_interval(&tail);
+    drop(tail);
+    let publisher_index = check_publisher_price_update(
+        &price_data,
+        funding_account.key,
+        publisher_expiry,
+        instructions_account,
+        if is_component_update {
+            Some(cmd_args.publishing_slot)
+        } else {
+            None
+        },
+        clock.slot,
+    )?;
+    // Everything that can reject the update is checked before the update writes to the accounts,
+    // so that a rejected update (see `upd_price_no_fail_on_error`) only migrated the price account
+    let fee_lamports = if is_component_update {
+        price_data.fee_lamports_
+    } else {
+        0
+    };
+    check_fee_accounts(program_id, fee_accounts, fee_lamports)?;
+    let component_status =
+        get_status_for_conf_price_ratio(cmd_args.price, cmd_args.confidence, cmd_args.status)?;
+    let log_publisher_index = try_convert(publisher_index)?;
+
+    // Only the first update of a slot aggregates, or the first update of the aggregation leader
+    // of the slot with aggregation rotation. The other ones, which are most of the price
+    // updates, keep the single borrow of the price account and only write the component.
+    let mut aggregate = None;
+    let is_aggregating =
+        is_aggregating_update(&price_data, agg_interval_slots, publisher_index, clock.slot);
+    // Check that the oracle PDA is correctly configured for the program we are calling, if the
+    // message is sent, i.e. if it wasn't sent yet or the update aggregates
+    let oracle_auth_bump = match &maybe_accumulator_accounts {
+        Some(accumulator_accounts) if price_data.message_sent_ == 0 || is_aggregating => {
+            let oracle_auth_seeds: &[&[u8]] = &[
+                UPD_PRICE_WRITE_SEED.as_bytes(),
+                &accumulator_accounts.program_id.key.to_bytes(),
+            ];
+            let (expected_oracle_auth_pda, bump) =
+                Pubkey::find_program_address(oracle_auth_seeds, program_id);
+            pyth_assert(
+                expected_oracle_auth_pda == *accumulator_accounts.oracle_auth_pda.key,
+                OracleError::InvalidPda.into(),
+            )?;
+            Some(bump)
+        }
+        _ => None,
+    };
+    // A component update without enough compute units left for the aggregation leaves it to the
+    // next price update of the slot rather than failing the transaction
+    let required_compute_units = aggregation_compute_units(&price_data);
+    if is_aggregating
+        && is_component_update
+        && remaining_compute_units != 0
+        && remaining_compute_units < required_compute_units
+    {
+        AggregationDeferred {
+            price_account: *price_account.key,
+            slot: clock.slot,
+            remaining_compute_units,
+            required_compute_units,
+        }
+        .emit();
+        price_data.status_change_.agg_deferred_ = 1;
+    } else if is_aggregating {
+        drop(price_data);
+        aggregate = aggregate_price(
+            price_account,
+            &clock,
+            slot_hashes_account,
+            message_buffer_account,
+            cmd_args.header.version,
+        )?;
+        // Reload price data as a struct after the aggregation borrows are dropped
+        price_data = load_checked::<PriceAccount>(price_account, cmd_args.header.version)?;
+
+        // Only successful aggregations are attested
+        #[cfg(feature = "wormhole")]
+        if let Some(wormhole_accounts) = &wormhole_accounts {
+            if price_data.last_slot_ == clock.slot {
+                post_price_attestation(
+                    program_id,
+                    funding_account,
+                    price_account.key,
+                    &price_data,
+                    wormhole_accounts,
+                )?;
             }
-            let mut price_data =
-                load_checked::<PriceAccount>(price_account, cmd_args.header.version)?;
-            // We want to send a message every time the aggregate price updates. However, during the migration,
-            // not every publisher will necessarily provide the accumulator accounts. The message_sent_ flag
-            // ensures that after every aggregate update, the next publisher who provides the accumulator accounts
-            // will send the message.
-            price_data.message_sent_ = 0;
-            price_data.update_price_cumulative()?;
         }
     }
 
-    // Reload price data as a struct after c_upd_aggregate() borrow is dropped
-    let mut price_data = load_checked::<PriceAccount>(price_account, cmd_args.header.version)?;
-
     // Feature-gated accumulator-specific code, used only on pythnet/pythtest
     {
-        if let Some(accumulator_accounts) = maybe_accumulator_accounts {
+        if let (Some(accumulator_accounts), Some(bump)) =
+            (maybe_accumulator_accounts, oracle_auth_bump)
+        {
             if price_data.message_sent_ == 0 {
-                // Check that the oracle PDA is correctly configured for the program we are calling.
-                let oracle_auth_seeds: &[&[u8]] = &[
-                    UPD_PRICE_WRITE_SEED.as_bytes(),
-                    &accumulator_accounts.program_id.key.to_bytes(),
-                ];
-                let (expected_oracle_auth_pda, bump) =
-                    Pubkey::find_program_address(oracle_auth_seeds, program_id);
-                pyth_assert(
-                    expected_oracle_auth_pda == *accumulator_accounts.oracle_auth_pda.key,
-                    OracleError::InvalidPda.into(),
-                )?;
-
                 let account_metas = vec![
                     AccountMeta {
                         pubkey:      *accumulator_accounts.whitelist.key,
@@ -262,25 +471,436 @@ pub fn upd_price(
     }
 
     // Try to update the publisher's price
-    if is_component_update(cmd_args)? {
-        // IMPORTANT: If the publisher does not meet the price/conf
-        // ratio condition, its price will not count for the next
-        // aggregate.
-        let status: u32 =
-            get_status_for_conf_price_ratio(cmd_args.price, cmd_args.confidence, cmd_args.status)?;
+    if is_component_update {
+        update_publisher_price(
+            &mut price_data.comp_[publisher_index].latest_,
+            component_status,
+            cmd_args.price,
+            cmd_args.confidence,
+            cmd_args.publishing_slot,
+        );
+        let stats = &mut price_data.publisher_stats_[publisher_index];
+        stats.record_update(clock.slot);
+        if let Some(publish_time) = publish_time {
+            stats.publish_time_ = publish_time;
+        }
+        if let Some(trade) = trade {
+            stats.last_trade_ = trade;
+        }
 
-        {
-            let publisher_price = &mut price_data.comp_[publisher_index].latest_;
-            publisher_price.price_ = cmd_args.price;
-            publisher_price.conf_ = cmd_args.confidence;
-            publisher_price.status_ = status;
-            publisher_price.pub_slot_ = cmd_args.publishing_slot;
+        if let Some(component_log_account) = component_log_account {
+            let component = &price_data.comp_[publisher_index].latest_;
+            load_checked::<ComponentLogAccount>(component_log_account, cmd_args.header.version)?
+                .push(ComponentLogEntry {
+                    publisher_index_: log_publisher_index,
+                    status_:          component.status_,
+                    slot_:            clock.slot,
+                    price_:           component.price_,
+                    conf_:            component.conf_,
+                });
         }
     }
 
+    // Paid last: the fee accounts are checked, only the transfer can still fail the transaction
+    pay_fee(program_id, funding_account, fee_accounts, fee_lamports)?;
+    return_aggregate(aggregate);
+    Ok(())
+}
+
+/// Publish component prices to several price accounts in a single instruction. The publisher's
+/// signature is checked once, then each price account is updated as if by `upd_price`, including
+/// triggering the aggregation on the first update in a new slot. Unlike `upd_price`, this
+/// instruction never sends messages to the message buffer program nor appends to component logs.
+///
+/// The instruction data is a `CommandHeader` followed by one `UpdPriceBatchEntry` per price
+/// account, in the same order as the accounts. The fees of the updated price accounts are paid in
+/// a single transfer. The return data is the last new aggregate, see `return_aggregate`.
+// account[0] funding account       [signer writable]
+// account[1] sysvar_clock account  []
+// account[2..] price accounts      [writable]
+// account[n-4] treasury account    [writable] (optional, see `upd_price`)
+// account[n-3] system program      [] (optional, see `upd_price`)
+// account[n-2] message buffer      [writable] (optional, see `upd_price`)
+// account[n-1] sysvar_instructions [] (optional, see `upd_price`)
+// account[n]   sysvar_slot_hashes  [] (optional, see `upd_price`)
+pub fn upd_price_batch(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    upd_price_entries(program_id, accounts, instruction_data, true).map(|_| ())
+}
+
+/// Same as `upd_price_batch`, except that a price account that can't be updated, e.g. because the
+/// publisher doesn't publish to it, is skipped instead of failing the instruction. The result of
+/// each update is sent as return data: one little-endian `u64` per price account, in the same
+/// order as the accounts, which is 0 if the price account was updated and the `ProgramError` (as
+/// converted to a `u64`) otherwise, instead of the new aggregates. A skipped price account is left
+/// untouched, unless the update failed after its aggregation was triggered. Only the fees of the
+/// updated price accounts are paid.
+// account[0] funding account       [signer writable]
+// account[1] sysvar_clock account  []
+// account[2..] price accounts      [writable]
+// account[n-4] treasury account    [writable] (optional, see `upd_price`)
+// account[n-3] system program      [] (optional, see `upd_price`)
+// account[n-2] message buffer      [writable] (optional, see `upd_price`)
+// account[n-1] sysvar_instructions [] (optional, see `upd_price`)
+// account[n]   sysvar_slot_hashes  [] (optional, see `upd_price`)
+pub fn upd_price_scatter(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let results = upd_price_entries(program_id, accounts, instruction_data, false)?;
+    let return_data: Vec<u8> = results
+        .iter()
+        .flat_map(|result| result.to_le_bytes())
+        .collect();
+    set_return_data(&return_data);
     Ok(())
 }
 
+/// Apply the `UpdPriceBatchEntry`s of an `upd_price_batch` or `upd_price_scatter` instruction and
+/// return the result of each of them, see `upd_price_scatter`. The first failure fails the
+/// instruction if `fail_on_error`.
+fn upd_price_entries(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+    fail_on_error: bool,
+) -> Result<Vec<u64>, ProgramError> {
+    let cmd_hdr = load::<CommandHeader>(instruction_data)?;
+    let (accounts, slot_hashes_account) = split_slot_hashes_account(accounts);
+    let (accounts, instructions_account) = split_instructions_account(accounts);
+    let (accounts, message_buffer_account) =
+        split_message_buffer_account(program_id, accounts, cmd_hdr.version);
+    let (accounts, fee_accounts) = split_fee_accounts(accounts);
+
+    let (funding_account, clock_account, price_accounts) = match accounts {
+        [x, y, rest @ ..] if !rest.is_empty() => Ok((x, y, rest)),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
+    }?;
+    // A price account can only be updated once per instruction
+    let mut roles = vec![funding_account, clock_account];
+    roles.extend(price_accounts);
+    check_distinct_accounts(&roles)?;
+
+    pyth_assert(
+        instruction_data.len()
+            == size_of::<CommandHeader>() + price_accounts.len() * size_of::<UpdPriceBatchEntry>(),
+        ProgramError::InvalidArgument,
+    )?;
+
+    check_valid_funding_account(funding_account)?;
+    if let Some(message_buffer_account) = message_buffer_account {
+        check_valid_writable_account(program_id, message_buffer_account)?;
+    }
+    let clock = Clock::from_account_info(clock_account)?;
+
+    // Update `price_account` with `entry` and return the fee of the update, along with the new
+    // aggregate if the update aggregated
+    let upd_price_entry = |price_account: &AccountInfo,
+                           entry: &UpdPriceBatchEntry|
+     -> Result<(u64, Option<AggregatePriceUpdate>), ProgramError> {
+        check_valid_writable_account(program_id, price_account)?;
+
+        let (mut price_data, tail) =
+            load_checked_with_tail::<PriceAccount>(price_account, cmd_hdr.version)?;
+        let publisher_expiry = PriceAccount::publisher_expiry(&tail, funding_account.key);
+        let agg_interval_slots = PriceAccount::agg_interval(&tail);
+        drop(tail);
+        let publisher_index = check_publisher_price_update(
+            &price_data,
+            funding_account.key,
+            publisher_expiry,
+            instructions_account,
+            Some(entry.publishing_slot),
+            clock.slot,
+        )?;
+        let fee_lamports = price_data.fee_lamports_;
+        let component_status =
+            get_status_for_conf_price_ratio(entry.price, entry.confidence, entry.status)?;
+
+        let mut aggregate = None;
+        if is_aggregating_update(&price_data, agg_interval_slots, publisher_index, clock.slot) {
+            drop(price_data);
+            aggregate = aggregate_price(
+                price_account,
+                &clock,
+                slot_hashes_account,
+                message_buffer_account,
+                cmd_hdr.version,
+            )?;
+            price_data = load_checked::<PriceAccount>(price_account, cmd_hdr.version)?;
+        }
+
+        update_publisher_price(
+            &mut price_data.comp_[publisher_index].latest_,
+            component_status,
+            entry.price,
+            entry.confidence,
+            entry.publishing_slot,
+        );
+        let stats = &mut price_data.publisher_stats_[publisher_index];
+        stats.record_update(clock.slot);
+        Ok((fee_lamports, aggregate))
+    };
+
+    let mut fee_lamports: u64 = 0;
+    let mut results = Vec::with_capacity(price_accounts.len());
+    let mut last_aggregate = None;
+    for (i, price_account) in price_accounts.iter().enumerate() {
+        let entry = load::<UpdPriceBatchEntry>(
+            &instruction_data[size_of::<CommandHeader>() + i * size_of::<UpdPriceBatchEntry>()..],
+        )?;
+
+        match upd_price_entry(price_account, entry) {
+            Ok((entry_fee_lamports, aggregate)) => {
+                fee_lamports = fee_lamports
+                    .checked_add(entry_fee_lamports)
+                    .ok_or(OracleError::IntegerCastingError)?;
+                results.push(0);
+                last_aggregate = aggregate.or(last_aggregate);
+            }
+            Err(err) if !fail_on_error => {
+                msg!("upd_price_scatter: rejected price account {}: {:?}", i, err);
+                results.push(u64::from(err));
+            }
+            Err(err) => return Err(err),
+        }
+    }
+
+    pay_fee(program_id, funding_account, fee_accounts, fee_lamports)?;
+    return_aggregate(last_aggregate);
+    Ok(results)
+}
+
+/// Check that `fee_accounts`, the treasury and system program accounts, are given if there is a
+/// fee of `fee_lamports` to pay
+fn check_fee_accounts(
+    program_id: &Pubkey,
+    fee_accounts: Option<(&AccountInfo, &AccountInfo)>,
+    fee_lamports: u64,
+) -> ProgramResult {
+    if fee_lamports == 0 {
+        return Ok(());
+    }
+    let (treasury_account, _) = fee_accounts.ok_or(OracleError::MissingFeeAccounts)?;
+    check_valid_treasury_account(program_id, treasury_account)
+}
+
+/// Transfer the `fee_lamports` owed for price updates from the publisher to the treasury, after
+/// checking the fee accounts (see `check_fee_accounts`)
+fn pay_fee<'a>(
+    program_id: &Pubkey,
+    publisher_account: &AccountInfo<'a>,
+    fee_accounts: Option<(&AccountInfo<'a>, &AccountInfo<'a>)>,
+    fee_lamports: u64,
+) -> ProgramResult {
+    check_fee_accounts(program_id, fee_accounts, fee_lamports)?;
+    let (treasury_account, system_program) = match fee_accounts {
+        Some(fee_accounts) if fee_lamports != 0 => fee_accounts,
+        _ => return Ok(()),
+    };
+    send_lamports(
+        publisher_account,
+        treasury_account,
+        system_program,
+        fee_lamports,
+    )
+}
+
+/// Find the component of `publisher` in `price_data` and return its index, after checking that
+/// the publisher hasn't expired (`publisher_expiry`, see `PriceAccount::read_publisher_expiry`).
+/// If `publishing_slot` is provided, this is a component update and we also check that the
+/// publisher is publishing a more recent price that is not in the future, that
+/// it hasn't reached the maximum number of updates in the current slot, and that the update passes
+/// the CPI guard of the price account, if enabled.
+pub(super) fn check_publisher_price_update(
+    price_data: &PriceAccount,
+    publisher: &Pubkey,
+    publisher_expiry: Option<u64>,
+    instructions_account: Option<&AccountInfo>,
+    publishing_slot: Option<u64>,
+    clock_slot: u64,
+) -> Result<usize, ProgramError> {
+    // Halted price feeds don't accept updates
+    pyth_assert(
+        price_data.agg_.status_ != PC_STATUS_HALTED,
+        OracleError::PriceHalted.into(),
+    )?;
+
+    let publisher_index = match find_publisher_index(
+        &price_data.comp_[..try_convert::<u32, usize>(price_data.num_)?],
+        publisher,
+    ) {
+        Some(index) => index,
+        None => {
+            return Err(OracleError::PermissionViolation.into());
+        }
+    };
+    pyth_assert(
+        publisher_expiry.map_or(true, |expiry_slot| clock_slot <= expiry_slot),
+        OracleError::PublisherExpired.into(),
+    )?;
+
+    // Check that publisher is publishing a more recent price
+    if let Some(publishing_slot) = publishing_slot {
+        pyth_assert(
+            publishing_slot > price_data.comp_[publisher_index].latest_.pub_slot_
+                && publishing_slot <= clock_slot,
+            ProgramError::InvalidArgument,
+        )?;
+
+        // Check that publisher hasn't reached the maximum number of updates in this slot
+        pyth_assert(
+            price_data.max_slot_updates_ == 0
+                || price_data.publisher_stats_[publisher_index].num_updates_in_slot(clock_slot)
+                    < price_data.max_slot_updates_,
+            OracleError::TooManyPriceUpdates.into(),
+        )?;
+
+        // Check that the update is a top-level instruction or directly invoked by an allowed
+        // program. The Instructions sysvar is required to tell, see `get_direct_caller`
+        if price_data.cpi_guard_ != 0 {
+            let instructions_account = instructions_account.ok_or(OracleError::UnauthorizedCpi)?;
+            pyth_assert(
+                get_direct_caller(instructions_account)?.map_or(true, |program| {
+                    program != Pubkey::default() && price_data.cpi_callers_.contains(&program)
+                }),
+                OracleError::UnauthorizedCpi.into(),
+            )?;
+        }
+    }
+
+    Ok(publisher_index)
+}
+
+/// Run the aggregation for the current slot and record it in the publisher statistics and their
+/// pruning, along with the change of the aggregate status, if any, clearing a deferral of the
+/// aggregation. If the aggregate was
+/// successfully updated, also update the EMA, the cumulative sums used for TWAP and the time
+/// machine, if any, append the new aggregate to the message buffer account, if given, and return
+/// it.
+///
+/// The price account data must not be borrowed when calling this function.
+pub(super) fn aggregate_price(
+    price_account: &AccountInfo,
+    clock: &Clock,
+    slot_hashes_account: Option<&AccountInfo>,
+    message_buffer_account: Option<&AccountInfo>,
+    version: u32,
+) -> Result<Option<AggregatePriceUpdate>, ProgramError> {
+    // The weights of the publishers follow their components, see `PublisherWeights`
+    let mut weights = [0; PC_NUM_COMP as usize];
+    if PriceAccount::has_extension::<PublisherWeights>(price_account)? {
+        let (price_data, mut publisher_weights) =
+            PriceAccount::load_with_extension_mut::<PublisherWeights>(price_account)?;
+        let num_components = price_data.num_.min(PC_NUM_COMP) as usize;
+        weights = publisher_weights.component_weights(&price_data.comp_[..num_components]);
+    }
+
+    let updated = {
+        let mut price_data = load_checked::<PriceAccount>(price_account, version)?;
+        // Longer outages extend the max latency by `u8::MAX` slots only
+        price_data.skipped_slots_ = match slot_hashes_account {
+            Some(slot_hashes_account) => get_num_skipped_slots(
+                slot_hashes_account,
+                clock.slot,
+                try_convert(max_latency(&price_data))?,
+            )?
+            .try_into()
+            .unwrap_or(u8::MAX),
+            None => 0,
+        };
+        let prev_status = price_data.agg_.status_;
+        let updated =
+            upd_aggregate_with_weights(&mut price_data, &weights, clock.slot, clock.unix_timestamp);
+        upd_publisher_stats(&mut price_data, clock.slot);
+        price_data.record_status_change(prev_status, clock.slot, clock.unix_timestamp);
+        price_data.status_change_.agg_deferred_ = 0;
+        updated
+    };
+
+    if PriceAccount::has_extension::<PublisherPruning>(price_account)? {
+        let (mut price_data, mut pruning) =
+            PriceAccount::load_with_extension_mut::<PublisherPruning>(price_account)?;
+        upd_publisher_pruning(&mut price_data, &mut pruning, clock.slot);
+    }
+
+    // If the aggregate was successfully updated, calculate the difference and update TWAP.
+    if updated {
+        let agg_diff = (clock.slot as i64)
+            - load_checked::<PriceAccount>(price_account, version)?.prev_slot_ as i64;
+        let decay = EmaWindow::DEFAULT.decay();
+        // Encapsulate TWAP update logic in a function to minimize unsafe block scope.
+        unsafe {
+            c_upd_twap(
+                price_account.try_borrow_mut_data()?.as_mut_ptr(),
+                agg_diff,
+                decay.rate,
+                decay.max_diff,
+            );
+        }
+        let mut price_data = load_checked::<PriceAccount>(price_account, version)?;
+        // We want to send a message every time the aggregate price updates. However, during the migration,
+        // not every publisher will necessarily provide the accumulator accounts. The message_sent_ flag
+        // ensures that after every aggregate update, the next publisher who provides the accumulator accounts
+        // will send the message.
+        price_data.message_sent_ = 0;
+        price_data.update_price_cumulative()?;
+        price_data.record_price_history();
+
+        let aggregate = AggregatePriceUpdate {
+            price_account:    *price_account.key,
+            slot:             price_data.agg_.pub_slot_,
+            price:            price_data.agg_.price_,
+            conf:             price_data.agg_.conf_,
+            status:           price_data.agg_.status_,
+            num_contributors: price_data.num_qt_,
+        };
+        aggregate.emit();
+
+        if let Some(message_buffer_account) = message_buffer_account {
+            load_checked::<MessageBufferAccount>(message_buffer_account, version)?
+                .push(PriceMessage::from_aggregate(price_account.key, &price_data));
+        }
+        drop(price_data);
+        PriceAccount::record_time_machine(price_account)?;
+        return Ok(Some(aggregate));
+    }
+
+    Ok(None)
+}
+
+/// Send `aggregate`, the last aggregate computed by the instruction, as return data, so that the
+/// publisher and the programs invoking the oracle can read it right away (see
+/// `client::parse_aggregate_return_data`). Nothing is returned if the instruction didn't
+/// aggregate. Must be called after the CPIs of the instruction, which clear the return data.
+pub(super) fn return_aggregate(aggregate: Option<AggregatePriceUpdate>) {
+    if let Some(aggregate) = aggregate {
+        set_return_data(bytes_of(&aggregate));
+    }
+}
+
+/// Store a new price for a publisher. `status` is the status of the update as given by
+/// `get_status_for_conf_price_ratio`.
+// IMPORTANT: If the publisher does not meet the price/conf
+// ratio condition, its price will not count for the next
+// aggregate.
+fn update_publisher_price(
+    publisher_price: &mut PriceInfo,
+    status: u32,
+    price: i64,
+    confidence: u64,
+    publishing_slot: u64,
+) {
+    publisher_price.price_ = price;
+    publisher_price.conf_ = confidence;
+    publisher_price.status_ = status;
+    publisher_price.pub_slot_ = publishing_slot;
+}
+
 /// Find the index of the publisher in the list of components.
 ///
 /// This method first tries to binary search for the publisher's key in the list of components
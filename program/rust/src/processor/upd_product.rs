@@ -10,6 +10,7 @@ use {
         },
         instruction::CommandHeader,
         utils::{
+            check_distinct_accounts,
             check_permissioned_funding_account,
             check_valid_funding_account,
         },
@@ -35,6 +36,7 @@ pub fn upd_product(
         [x, y, p] => Ok((x, y, p)),
         _ => Err(OracleError::InvalidNumberOfAccounts),
     }?;
+    check_distinct_accounts(&[funding_account, product_account, permissions_account])?;
 
     let hdr = load::<CommandHeader>(instruction_data)?;
 
@@ -0,0 +1,95 @@
+use {
+    crate::{
+        accounts::TREASURY_SEED,
+        deserialize::load,
+        instruction::WithdrawFeeArgs,
+        utils::{
+            check_authorized_funding_account,
+            check_distinct_accounts,
+            check_valid_funding_account,
+            check_valid_treasury_account,
+            get_rent,
+            pyth_assert,
+        },
+        OracleError,
+    },
+    solana_program::{
+        account_info::AccountInfo,
+        entrypoint::ProgramResult,
+        program::invoke_signed,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        system_instruction::transfer,
+        system_program::check_id,
+    },
+    std::mem::size_of,
+};
+
+/// Transfer `lamports` of the fees collected in the treasury to the destination account. The
+/// treasury keeps its rent exemption.
+// account[0] funding account       [signer writable]
+// account[1] treasury account      [writable]
+// account[2] destination account   [writable]
+// account[3] permissions account   []
+// account[4] system program        []
+pub fn withdraw_fee(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let cmd = load::<WithdrawFeeArgs>(instruction_data)?;
+
+    pyth_assert(
+        instruction_data.len() == size_of::<WithdrawFeeArgs>(),
+        ProgramError::InvalidArgument,
+    )?;
+
+    let (
+        funding_account,
+        treasury_account,
+        destination_account,
+        permissions_account,
+        system_program,
+    ) = match accounts {
+        [x, t, d, p, s] => Ok((x, t, d, p, s)),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
+    }?;
+    check_distinct_accounts(&[
+        funding_account,
+        treasury_account,
+        permissions_account,
+        system_program,
+    ])?;
+    check_distinct_accounts(&[treasury_account, destination_account])?;
+
+    check_valid_funding_account(funding_account)?;
+    check_authorized_funding_account(
+        program_id,
+        funding_account,
+        permissions_account,
+        &cmd.header,
+    )?;
+    let bump_seed = check_valid_treasury_account(program_id, treasury_account)?;
+    pyth_assert(
+        check_id(system_program.key),
+        OracleError::InvalidSystemAccount.into(),
+    )?;
+
+    let available = treasury_account
+        .lamports()
+        .saturating_sub(get_rent()?.minimum_balance(0));
+    pyth_assert(
+        cmd.lamports <= available,
+        OracleError::InsufficientTreasuryFunds.into(),
+    )?;
+
+    invoke_signed(
+        &transfer(treasury_account.key, destination_account.key, cmd.lamports),
+        &[
+            treasury_account.clone(),
+            destination_account.clone(),
+            system_program.clone(),
+        ],
+        &[&[TREASURY_SEED.as_bytes(), &[bump_seed]]],
+    )
+}
@@ -0,0 +1,554 @@
+//! Safe, typed accessors for off-chain consumers and other programs reading Pyth accounts.
+//!
+//! Every loader in this module validates the account size, magic number, version and account
+//! type before handing out a reference, so that integrators don't need to cast raw account data
+//! themselves.
+
+pub use crate::{
+    accounts::{
+        BucketAverage,
+        TimeMachine,
+        TimeMachineBucket,
+    },
+    error::OracleError,
+};
+use {
+    crate::{
+        accounts::{
+            read_product_attributes,
+            CompressedPriceAccount,
+            DerivedPriceAccount,
+            LastTrade,
+            MappingAccount,
+            PriceAccount,
+            PriceInfo,
+            ProductAccount,
+            ProductAttributeKey,
+            PublisherStats,
+            PythAccount,
+            SymbolIndexAccount,
+            MAPPING_SEED,
+        },
+        aggregation::{
+            is_aggregated_status,
+            price_ratio,
+        },
+        c_oracle_header::{
+            PC_STATUS_DEGRADED,
+            PC_STATUS_TRADING,
+            PC_VERSION,
+            PRICE_DRIFT_DECIMALS,
+        },
+        deserialize::{
+            load_checked_read_only,
+            ReadOnlyAccount,
+        },
+        utils::{
+            pyth_assert,
+            try_convert,
+        },
+    },
+    num_traits::FromPrimitive,
+    solana_program::{
+        account_info::AccountInfo,
+        clock::Clock,
+        decode_error::DecodeError,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+    std::{
+        cell::Ref,
+        mem::size_of,
+    },
+    thiserror::Error,
+};
+
+/// Read-only loading of initialized Pyth accounts.
+pub trait LoadPythAccount: PythAccount {
+    /// Borrow the data of `account` as `Self` after checking that it is a valid Pyth account of
+    /// the right type for the current `PC_VERSION`, and not corrupted (see
+    /// `PythAccount::is_consistent`). An account with an older layout is read as a copy migrated
+    /// to the current one (see `load_checked_read_only`).
+    fn load<'a>(account: &'a AccountInfo) -> Result<ReadOnlyAccount<'a, Self>, ProgramError> {
+        load_checked_read_only::<Self>(account, PC_VERSION)
+    }
+}
+
+impl<T: PythAccount> LoadPythAccount for T {
+}
+
+/// A price with a degree of uncertainty, represented as `price ± conf` scaled by `10^expo`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Price {
+    pub price:        i64,
+    pub conf:         u64,
+    pub expo:         i32,
+    /// Unix timestamp of the aggregation that produced this price
+    pub publish_time: i64,
+}
+
+impl Price {
+    /// The price of `self` quoted in `quote` at exponent `expo`, e.g. the price of ETH in BTC from
+    /// the prices of ETH and BTC in USD, computed like the aggregates of derived price accounts
+    /// (see `DerivedPriceAccount`). The publish time is the earliest of the two. `None` if the
+    /// quote price isn't positive or if the ratio doesn't fit.
+    pub fn ratio(&self, quote: &Price, expo: i32) -> Option<Price> {
+        let (price, conf) = price_ratio(
+            (self.price, self.conf, self.expo),
+            (quote.price, quote.conf, quote.expo),
+            expo,
+        )?;
+        Some(Price {
+            price,
+            conf,
+            expo,
+            publish_time: self.publish_time.min(quote.publish_time),
+        })
+    }
+}
+
+/// The reason `PriceAccount::get_price_checked` rejected a price.
+#[derive(Clone, Copy, Debug, Eq, Error, PartialEq)]
+pub enum PriceCheckError {
+    /// The status of the last aggregation isn't `PC_STATUS_TRADING` (the price may be halted,
+    /// degraded or the aggregation may have failed)
+    #[error("NotTrading")]
+    NotTrading,
+    /// The price was aggregated `age_slots` slots ago, more than allowed
+    #[error("TooStale")]
+    TooStale { age_slots: u64 },
+    /// The confidence is `conf_bps` basis points of the price, more than allowed
+    #[error("ConfidenceTooWide")]
+    ConfidenceTooWide { conf_bps: u64 },
+}
+
+impl PriceAccount {
+    /// Get the latest successfully aggregated price. If the last aggregation attempt didn't
+    /// succeed, this is the price of the previous successful aggregation. The price may be
+    /// degraded, see `is_degraded`.
+    pub fn get_price_unchecked(&self) -> Price {
+        latest_price(
+            &self.agg_,
+            self.timestamp_,
+            (self.prev_price_, self.prev_conf_, self.prev_timestamp_),
+            self.exponent,
+        )
+    }
+
+    /// Get the latest successfully aggregated price if it was published at most `max_age`
+    /// seconds before `clock.unix_timestamp`. Returns `None` otherwise.
+    pub fn get_price_no_older_than(&self, clock: &Clock, max_age: u64) -> Option<Price> {
+        let price = self.get_price_unchecked();
+        let age = clock.unix_timestamp.saturating_sub(price.publish_time);
+        if age <= i64::try_from(max_age).unwrap_or(i64::MAX) {
+            Some(price)
+        } else {
+            None
+        }
+    }
+
+    /// Get the price of the last aggregation after the checks most consumers need: the aggregate
+    /// status must be `PC_STATUS_TRADING`, the price must have been aggregated at most
+    /// `max_age_slots` slots before `clock.slot` and its confidence must be at most `max_conf_bps`
+    /// basis points of the price. Unlike `get_price_unchecked`, this never falls back to a
+    /// previous price.
+    pub fn get_price_checked(
+        &self,
+        max_conf_bps: u64,
+        max_age_slots: u64,
+        clock: &Clock,
+    ) -> Result<Price, PriceCheckError> {
+        if self.agg_.status_ != PC_STATUS_TRADING {
+            return Err(PriceCheckError::NotTrading);
+        }
+        let age_slots = clock.slot.saturating_sub(self.agg_.pub_slot_);
+        if age_slots > max_age_slots {
+            return Err(PriceCheckError::TooStale { age_slots });
+        }
+        let price = self.get_price_unchecked();
+        // Compare conf / |price| to max_conf_bps / 10^4 without dividing
+        let max_conf = u128::from(price.price.unsigned_abs()) * u128::from(max_conf_bps);
+        if u128::from(price.conf) * 10_000 > max_conf {
+            let conf_bps = (u128::from(price.conf) * 10_000)
+                .checked_div(u128::from(price.price.unsigned_abs()))
+                .map_or(u64::MAX, |conf_bps| {
+                    u64::try_from(conf_bps).unwrap_or(u64::MAX)
+                });
+            return Err(PriceCheckError::ConfidenceTooWide { conf_bps });
+        }
+        Ok(price)
+    }
+
+    /// Get the latest successfully aggregated price extrapolated to `slot` with the drift of the
+    /// price (see `PriceAccount::price_drift`), if it was aggregated at most `max_slots` slots
+    /// before `slot`. The confidence is widened by the extrapolated move, so a slightly stale
+    /// price can be used instead of failing. Returns `None` if the price is older, if the drift is
+    /// unknown (always on Solana) or if the extrapolated price doesn't fit.
+    pub fn get_price_extrapolated(&self, slot: u64, max_slots: u64) -> Option<Price> {
+        let price = self.get_price_unchecked();
+        let elapsed = slot.saturating_sub(self.last_slot_);
+        if elapsed > max_slots {
+            return None;
+        }
+        let scale = 10i128.pow(PRICE_DRIFT_DECIMALS);
+        let scaled_move = i128::from(self.price_drift()?) * i128::from(elapsed);
+        Some(Price {
+            price: i64::try_from(i128::from(price.price) + scaled_move / scale).ok()?,
+            conf: u64::try_from(i128::from(price.conf) + (scaled_move.abs() + scale - 1) / scale)
+                .ok()?,
+            ..price
+        })
+    }
+
+    /// Whether the last aggregation succeeded with a majority of quotes flagged as unreliable by
+    /// their publishers (`PC_STATUS_UNRELIABLE`). Consumers that need high confidence prices
+    /// should not use a degraded price.
+    pub fn is_degraded(&self) -> bool {
+        self.agg_.status_ == PC_STATUS_DEGRADED
+    }
+
+    /// Get the fast aggregate of the last aggregation (see `FastAggregate`), or `None` if it
+    /// didn't succeed. Unlike `get_price_unchecked`, this never falls back to a previous price.
+    pub fn get_fast_price_unchecked(&self) -> Option<Price> {
+        if self.fast_agg_.status_ == PC_STATUS_TRADING {
+            Some(Price {
+                price:        self.fast_agg_.price_,
+                conf:         self.fast_agg_.conf_,
+                expo:         self.exponent,
+                publish_time: self.timestamp_,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Get the number of successful aggregates of the price account (see
+    /// `PriceCumulative::aggregate_counter`). The price account has a new aggregate if the counter
+    /// differs from the one read before, whether or not the price changed.
+    pub fn get_aggregate_counter(&self) -> u64 {
+        self.price_cumulative.aggregate_counter
+    }
+
+    /// Get the number of seconds since the aggregate status became `PC_STATUS_TRADING` (see
+    /// `PriceAccount::status_change_`), at `clock.unix_timestamp`, or `None` if the status isn't
+    /// trading. Consumers that require a feed to have been trading for some time compare it to
+    /// their threshold, along with the age of the price (see `get_price_no_older_than`).
+    pub fn get_trading_duration(&self, clock: &Clock) -> Option<u64> {
+        if self.agg_.status_ != PC_STATUS_TRADING
+            || u32::from(self.status_change_.status_) != PC_STATUS_TRADING
+        {
+            return None;
+        }
+        let duration = clock
+            .unix_timestamp
+            .saturating_sub(self.status_change_.timestamp_);
+        Some(duration.max(0) as u64)
+    }
+
+    /// Get the statistics of `publisher`, or `None` if it doesn't publish to this price account.
+    pub fn publisher_stats(&self, publisher: &Pubkey) -> Option<&PublisherStats> {
+        let num_components = self.comp_.len().min(self.num_ as usize);
+        self.comp_[..num_components]
+            .iter()
+            .position(|component| component.pub_ == *publisher)
+            .map(|index| &self.publisher_stats_[index])
+    }
+
+    /// Get the freshest trade attached to a price update by one of the publishers (see
+    /// `PublisherStats::last_trade_`), or `None` if none of them attached one. Among trades of the
+    /// same slot, the trade of the first publisher is returned.
+    pub fn get_last_trade(&self) -> Option<&LastTrade> {
+        let num_components = self.comp_.len().min(self.num_ as usize);
+        self.publisher_stats_[..num_components]
+            .iter()
+            .map(|stats| &stats.last_trade_)
+            .filter(|trade| trade.size_ != 0)
+            .fold(None, |freshest, trade| match freshest {
+                Some(freshest) if freshest.pub_slot_ >= trade.pub_slot_ => Some(freshest),
+                _ => Some(trade),
+            })
+    }
+
+    /// Borrow the time machine of the price account `account` (see `TimeMachine`) after checking
+    /// the account like `load`, `None` if the price account has no time machine. The averages of
+    /// the last buckets are given by `TimeMachine::recent_averages`, the average over any period
+    /// by `TimeMachine::twap`.
+    pub fn load_time_machine<'a>(
+        account: &'a AccountInfo,
+    ) -> Result<Option<Ref<'a, TimeMachine>>, ProgramError> {
+        Self::load(account)?;
+        Self::load_extension::<TimeMachine>(account)
+    }
+}
+
+impl CompressedPriceAccount {
+    /// Get the last successfully aggregated price of the price account before it was halted and
+    /// compressed, like `PriceAccount::get_price_unchecked`.
+    pub fn get_price_unchecked(&self) -> Price {
+        latest_price(
+            &self.agg_,
+            self.timestamp_,
+            (self.prev_price_, self.prev_conf_, self.prev_timestamp_),
+            self.exponent,
+        )
+    }
+}
+
+impl DerivedPriceAccount {
+    /// Get the last ratio of the base and quote price accounts computed by `upd_derived`, even if
+    /// the last update couldn't compute it (see `is_trading`). Its publish time is the earliest
+    /// publish time of the parent aggregates it was computed from.
+    pub fn get_price_unchecked(&self) -> Price {
+        Price {
+            price:        self.agg_.price_,
+            conf:         self.agg_.conf_,
+            expo:         self.exponent,
+            publish_time: self.timestamp_,
+        }
+    }
+
+    /// Get the last ratio if the last update computed it from parent aggregates published at most
+    /// `max_age` seconds before `clock.unix_timestamp`. Returns `None` otherwise.
+    pub fn get_price_no_older_than(&self, clock: &Clock, max_age: u64) -> Option<Price> {
+        if !is_aggregated_status(self.agg_.status_) {
+            return None;
+        }
+        let price = self.get_price_unchecked();
+        let age = clock.unix_timestamp.saturating_sub(price.publish_time);
+        if age <= i64::try_from(max_age).unwrap_or(i64::MAX) {
+            Some(price)
+        } else {
+            None
+        }
+    }
+
+    /// Whether the last update computed the ratio from parents that were both trading
+    pub fn is_trading(&self) -> bool {
+        self.agg_.status_ == PC_STATUS_TRADING
+    }
+}
+
+/// The aggregate `agg` published at `timestamp` if it succeeded, the previous aggregate `prev`
+/// (price, confidence and publish time) otherwise
+fn latest_price(agg: &PriceInfo, timestamp: i64, prev: (i64, u64, i64), expo: i32) -> Price {
+    let (price, conf, publish_time) = if is_aggregated_status(agg.status_) {
+        (agg.price_, agg.conf_, timestamp)
+    } else {
+        prev
+    };
+
+    Price {
+        price,
+        conf,
+        expo,
+        publish_time,
+    }
+}
+
+impl PublisherStats {
+    /// Number of recorded aggregation rounds, at most `PUBLISHER_STATS_NUM_ROUNDS`.
+    pub fn num_rounds(&self) -> u32 {
+        u32::from(self.num_rounds_)
+    }
+
+    /// Number of recorded rounds in which the publisher's quote was included in the aggregate.
+    pub fn num_included_rounds(&self) -> u32 {
+        self.included_rounds_.count_ones()
+    }
+
+    /// Average latency in slots of the publisher's quotes over the rounds in which they were
+    /// included in the aggregate, or `None` if none was included.
+    pub fn average_latency(&self) -> Option<f64> {
+        let num_included_rounds = self.num_included_rounds();
+        if num_included_rounds == 0 {
+            return None;
+        }
+        let total_latency: u32 = self.latencies_.iter().copied().map(u32::from).sum();
+        Some(f64::from(total_latency) / f64::from(num_included_rounds))
+    }
+}
+
+/// Average price and confidence of the aggregates of a `TimeMachine` over a period, see
+/// `TimeMachine::twap`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Twap {
+    pub price:           i64,
+    pub conf:            u64,
+    /// Number of seconds of the period covered by buckets. The rest of the period had no
+    /// aggregate, or is older than the kept buckets.
+    pub covered_seconds: i64,
+}
+
+impl TimeMachine {
+    /// The average of the aggregates of the time machine from the unix timestamp `start_ts` to
+    /// `end_ts` (excluded), weighted by the number of slots they were held for, like
+    /// `TimeMachineBucket::average`. The aggregates of a bucket are taken as spread evenly over
+    /// its period, so that a bucket partly in the period counts for the same part of its slots.
+    /// The dark parts of the period, without an aggregate, are skipped. `None` if no aggregate held
+    /// for at least a slot is in the period, or if the sums overflow.
+    pub fn twap(&self, start_ts: i64, end_ts: i64) -> Option<Twap> {
+        let mut price_sum = 0i128;
+        let mut conf_sum = 0u128;
+        let mut num_slots = 0u128;
+        let mut covered_seconds = 0i64;
+        for bucket in self.buckets() {
+            let overlap = end_ts
+                .min(bucket.end_time())
+                .saturating_sub(start_ts.max(bucket.start_time_));
+            if overlap <= 0 {
+                continue;
+            }
+            // Weighting each bucket by its seconds in the period rather than their fraction of
+            // the bucket period keeps the sums exact, the common factor cancels out
+            price_sum = price_sum.checked_add(bucket.price_sum_.checked_mul(overlap.into())?)?;
+            conf_sum = conf_sum.checked_add(bucket.conf_sum_.checked_mul(overlap as u128)?)?;
+            num_slots = num_slots
+                .checked_add(u128::from(bucket.num_slots_).checked_mul(overlap as u128)?)?;
+            covered_seconds += overlap;
+        }
+        if num_slots == 0 {
+            return None;
+        }
+        Some(Twap {
+            price: i64::try_from(price_sum / i128::try_from(num_slots).ok()?).ok()?,
+            conf: u64::try_from(conf_sum / num_slots).ok()?,
+            covered_seconds,
+        })
+    }
+}
+
+impl MappingAccount {
+    /// The address of the mapping account of `program_id` for the current `PC_VERSION` if it was
+    /// created at its PDA (see `MAPPING_SEED`), so that products can be discovered without knowing
+    /// the mapping account beforehand.
+    pub fn find_pda(program_id: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(
+            &[MAPPING_SEED.as_bytes(), &PC_VERSION.to_le_bytes()],
+            program_id,
+        )
+        .0
+    }
+
+    /// Iterate over the product accounts listed in this mapping account.
+    pub fn iter_products(&self) -> impl Iterator<Item = &Pubkey> {
+        let number_of_products = self
+            .products_list
+            .len()
+            .min(self.number_of_products as usize);
+        self.products_list[..number_of_products].iter()
+    }
+}
+
+impl SymbolIndexAccount {
+    /// The price account indexed under `symbol`, e.g. `Crypto.BTC/USD`, if any. Programs should
+    /// still check that the price account is the one they expect, e.g. through its product.
+    pub fn find_price_account(&self, symbol: &str) -> Option<&Pubkey> {
+        self.get(&Self::symbol_hash(symbol.as_bytes()))
+    }
+}
+
+impl ProductAccount {
+    /// Read the metadata of the product account `account` after checking it like `load`. Fails if
+    /// an attribute is not UTF-8.
+    pub fn load_attributes(account: &AccountInfo) -> Result<ProductAttributes, ProgramError> {
+        let size = try_convert::<_, usize>(Self::load(account)?.header.size)?;
+        let data = account.try_borrow_data()?;
+        let kv_data = data
+            .get(size_of::<ProductAccount>()..size)
+            .ok_or(OracleError::AccountTooSmall)?;
+        let attributes = read_product_attributes(kv_data)?
+            .into_iter()
+            .map(|(key, value)| {
+                Ok((
+                    String::from_utf8(key.to_vec())
+                        .map_err(|_| OracleError::DeserializationError)?,
+                    String::from_utf8(value.to_vec())
+                        .map_err(|_| OracleError::DeserializationError)?,
+                ))
+            })
+            .collect::<Result<_, ProgramError>>()?;
+        Ok(ProductAttributes(attributes))
+    }
+
+    /// Read the reference price account of the product account `account` (see
+    /// `set_reference_price`) after checking it like `load`. Returns `None` if the product has no
+    /// reference price.
+    pub fn load_reference_price(account: &AccountInfo) -> Result<Option<Pubkey>, ProgramError> {
+        Self::load(account)?;
+        Self::read_reference_price(account)
+    }
+
+    /// Load the reference price account of the product account `product_account`, which must be
+    /// `price_account`, after checking both accounts like `load`. Fails with
+    /// `InvalidReferencePrice` if the product has no reference price, if it is another account or
+    /// if it isn't a price account of the product.
+    pub fn load_reference_price_account<'a>(
+        product_account: &AccountInfo,
+        price_account: &'a AccountInfo,
+    ) -> Result<ReadOnlyAccount<'a, PriceAccount>, ProgramError> {
+        pyth_assert(
+            Self::load_reference_price(product_account)? == Some(*price_account.key),
+            OracleError::InvalidReferencePrice.into(),
+        )?;
+        let price_data = PriceAccount::load(price_account)?;
+        pyth_assert(
+            price_data.product_account == *product_account.key,
+            OracleError::InvalidReferencePrice.into(),
+        )?;
+        Ok(price_data)
+    }
+}
+
+/// The metadata of a product account, as key-value pairs in the order they are stored.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProductAttributes(pub Vec<(String, String)>);
+
+impl ProductAttributes {
+    /// The value of the attribute `key`, if the product has it.
+    pub fn get(&self, key: ProductAttributeKey) -> Option<&str> {
+        self.get_unchecked(key.as_str())
+    }
+
+    /// The value of the attribute stored as `key`, including keys that are not a
+    /// `ProductAttributeKey`.
+    pub fn get_unchecked(&self, key: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(attribute_key, _)| attribute_key == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn symbol(&self) -> Option<&str> {
+        self.get(ProductAttributeKey::Symbol)
+    }
+
+    pub fn asset_type(&self) -> Option<&str> {
+        self.get(ProductAttributeKey::AssetType)
+    }
+
+    pub fn quote_currency(&self) -> Option<&str> {
+        self.get(ProductAttributeKey::QuoteCurrency)
+    }
+
+    pub fn base(&self) -> Option<&str> {
+        self.get(ProductAttributeKey::Base)
+    }
+
+    pub fn tenor(&self) -> Option<&str> {
+        self.get(ProductAttributeKey::Tenor)
+    }
+}
+
+impl OracleError {
+    /// Decode the custom error code of a failed instruction of the oracle (as in
+    /// `InstructionError::Custom`), or `None` if it isn't the code of an `OracleError`.
+    pub fn from_code(code: u32) -> Option<Self> {
+        Self::from_u32(code)
+    }
+}
+
+impl<T> DecodeError<T> for OracleError {
+    fn type_of() -> &'static str {
+        "OracleError"
+    }
+}
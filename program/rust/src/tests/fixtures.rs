@@ -0,0 +1,59 @@
+//! Dumps of real accounts that tests can load into the simulator (see
+//! `PythSimulator::load_oracle_account`) to replay on-chain state through new code paths. The
+//! dumps are in the format of `solana account <pubkey> --output json` and are stored in
+//! `test_data/fixtures`.
+use {
+    serde::Deserialize,
+    solana_program::pubkey::Pubkey,
+    solana_sdk::account::Account,
+    std::{
+        fs::File,
+        str::FromStr,
+    },
+};
+
+/// An account and its address, as dumped from a cluster
+pub struct AccountFixture {
+    pub pubkey:  Pubkey,
+    pub account: Account,
+}
+
+#[derive(Deserialize)]
+struct KeyedAccountDump {
+    pubkey:  String,
+    account: AccountDump,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AccountDump {
+    lamports:   u64,
+    /// The encoded data and the name of its encoding
+    data:       (String, String),
+    owner:      String,
+    executable: bool,
+    rent_epoch: u64,
+}
+
+impl AccountFixture {
+    /// Load the fixture `test_data/fixtures/<name>.json`
+    pub fn load(name: &str) -> AccountFixture {
+        let file = File::open(format!("./test_data/fixtures/{name}.json"))
+            .expect("Fixture file not found");
+        let dump: KeyedAccountDump =
+            serde_json::from_reader(&file).expect("Unable to parse the fixture");
+        let (encoded_data, encoding) = dump.account.data;
+        // `solana account --output json` always encodes the data in base64
+        assert_eq!(encoding, "base64", "Unsupported account data encoding");
+        AccountFixture {
+            pubkey:  Pubkey::from_str(&dump.pubkey).unwrap(),
+            account: Account {
+                lamports:   dump.account.lamports,
+                data:       base64::decode(encoded_data).unwrap(),
+                owner:      Pubkey::from_str(&dump.account.owner).unwrap(),
+                executable: dump.account.executable,
+                rent_epoch: dump.account.rent_epoch,
+            },
+        }
+    }
+}
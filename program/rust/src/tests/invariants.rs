@@ -0,0 +1,251 @@
+//! Structural invariants of the accounts of the oracle program, which every instruction must
+//! preserve. `PythSimulator` checks them at the end of each test (see
+//! `PythSimulator::check_invariants`), so that an instruction corrupting the links between the
+//! accounts is caught by any test exercising it rather than by the next instruction walking them.
+use {
+    crate::{
+        accounts::{
+            AccountHeader,
+            CompressedPriceAccount,
+            MappingAccount,
+            PriceAccount,
+            ProductAccount,
+            PythAccount,
+        },
+        c_oracle_header::{
+            PC_ACCTYPE_COMPONENT_LOG,
+            PC_ACCTYPE_COMPRESSED_PRICE,
+            PC_ACCTYPE_DERIVED_PRICE,
+            PC_ACCTYPE_MAPPING,
+            PC_ACCTYPE_MESSAGE_BUFFER,
+            PC_ACCTYPE_PERMISSIONS,
+            PC_ACCTYPE_PRICE,
+            PC_ACCTYPE_PRODUCT,
+            PC_ACCTYPE_SYMBOL_INDEX,
+            PC_MAGIC,
+            PC_MAP_TABLE_SIZE,
+            PC_NUM_COMP,
+            PC_VERSION,
+        },
+        deserialize::load,
+        layout::MAPPING_ACCOUNT_PRODUCTS_LIST_OFFSET,
+    },
+    bytemuck::bytes_of,
+    solana_program::pubkey::Pubkey,
+    std::{
+        collections::{
+            HashMap,
+            HashSet,
+        },
+        mem::size_of,
+    },
+};
+
+/// Describe how `accounts`, the data of the accounts owned by the oracle program, break the
+/// invariants of the oracle, one sorted line per violation:
+/// - every initialized account has the magic number, the version and a type of the program, and
+///   the accounts that other accounts link to are large enough for their type
+/// - the product and component lists of the mapping and price accounts have as many entries as
+///   their `num_` field says, without duplicates
+/// - every product belongs to exactly one mapping, and every price account to exactly one
+///   product, the one it points to
+///
+/// If `complete` is false, `accounts` may miss some accounts of the program, e.g. the accounts
+/// corrupted on purpose by a test. The links to the missing accounts are then not checked.
+pub fn find_violations(accounts: &HashMap<Pubkey, Vec<u8>>, complete: bool) -> Vec<String> {
+    let mut violations = vec![];
+    let mut account_types = HashMap::new();
+    for (key, data) in accounts {
+        match check_header(data) {
+            Ok(Some(account_type)) => {
+                account_types.insert(*key, account_type);
+            }
+            Ok(None) => {}
+            Err(violation) => violations.push(format!("account {key}: {violation}")),
+        }
+    }
+    let is_missing = |key: &Pubkey| !complete && !accounts.contains_key(key);
+
+    let mut product_mappings: HashMap<Pubkey, Vec<Pubkey>> = HashMap::new();
+    for (key, data) in accounts_of_type(accounts, &account_types, PC_ACCTYPE_MAPPING) {
+        let mapping_data = load::<MappingAccount>(data).unwrap();
+        let num_products = mapping_data.number_of_products as usize;
+        if num_products > PC_MAP_TABLE_SIZE as usize {
+            violations.push(format!("mapping {key}: {num_products} products"));
+            continue;
+        }
+        let expected_size =
+            MAPPING_ACCOUNT_PRODUCTS_LIST_OFFSET + num_products * size_of::<Pubkey>();
+        if mapping_data.header.size as usize != expected_size {
+            violations.push(format!(
+                "mapping {key}: size {} for {num_products} products",
+                mapping_data.header.size
+            ));
+        }
+        let (products, unused) = mapping_data.products_list.split_at(num_products);
+        if let Some(violation) = check_list(products, unused) {
+            violations.push(format!("mapping {key}: {violation} in the product list"));
+        }
+        for product in products
+            .iter()
+            .filter(|product| **product != Pubkey::default())
+        {
+            if account_types.get(product) != Some(&PC_ACCTYPE_PRODUCT) && !is_missing(product) {
+                violations.push(format!("mapping {key}: {product} is not a product account"));
+            }
+            product_mappings.entry(*product).or_default().push(key);
+        }
+    }
+
+    let mut price_products: HashMap<Pubkey, Vec<Pubkey>> = HashMap::new();
+    for (key, data) in accounts_of_type(accounts, &account_types, PC_ACCTYPE_PRODUCT) {
+        let num_mappings = product_mappings.get(&key).map_or(0, Vec::len);
+        if num_mappings > 1 || (num_mappings == 0 && complete) {
+            violations.push(format!("product {key}: in {num_mappings} mappings"));
+        }
+        let mut walked = HashSet::new();
+        let mut price = load::<ProductAccount>(data).unwrap().first_price_account;
+        while price != Pubkey::default() && !is_missing(&price) {
+            if !walked.insert(price) {
+                violations.push(format!("product {key}: cycle through {price}"));
+                break;
+            }
+            match price_links(accounts, account_types.get(&price), &price) {
+                Some((_, next_price)) => {
+                    price_products.entry(price).or_default().push(key);
+                    price = next_price;
+                }
+                None => {
+                    violations.push(format!("product {key}: {price} is not a price account"));
+                    break;
+                }
+            }
+        }
+    }
+
+    for account_type in [PC_ACCTYPE_PRICE, PC_ACCTYPE_COMPRESSED_PRICE] {
+        for (key, data) in accounts_of_type(accounts, &account_types, account_type) {
+            if account_type == PC_ACCTYPE_PRICE {
+                let price_data = load::<PriceAccount>(data).unwrap();
+                let num_components = price_data.num_ as usize;
+                if num_components > PC_NUM_COMP as usize {
+                    violations.push(format!("price {key}: {num_components} components"));
+                } else {
+                    let publishers = price_data
+                        .comp_
+                        .iter()
+                        .map(|component| component.pub_)
+                        .collect::<Vec<_>>();
+                    let (components, unused) = publishers.split_at(num_components);
+                    if let Some(violation) = check_list(components, unused) {
+                        violations.push(format!("price {key}: {violation} in the components"));
+                    }
+                }
+            }
+
+            let (product, _) = price_links(accounts, Some(&account_type), &key).unwrap();
+            let products = price_products.get(&key).map_or(&[][..], Vec::as_slice);
+            if products != [product] && !is_missing(&product) {
+                violations.push(format!(
+                    "price {key}: in the lists of {products:?} instead of {product}"
+                ));
+            }
+        }
+    }
+
+    violations.sort();
+    violations
+}
+
+/// The accounts of type `account_type`, given the `account_types` of `accounts`
+fn accounts_of_type<'a>(
+    accounts: &'a HashMap<Pubkey, Vec<u8>>,
+    account_types: &'a HashMap<Pubkey, u32>,
+    account_type: u32,
+) -> impl Iterator<Item = (Pubkey, &'a [u8])> + 'a {
+    account_types
+        .iter()
+        .filter(move |(_, t)| **t == account_type)
+        .map(move |(key, _)| (*key, accounts[key].as_slice()))
+}
+
+/// The type of the account `data`, or `None` if it isn't initialized
+fn check_header(data: &[u8]) -> Result<Option<u32>, String> {
+    let is_zero = |bytes: &[u8]| bytes.iter().all(|byte| *byte == 0);
+    let header = match load::<AccountHeader>(data) {
+        Ok(header) if is_zero(bytes_of(header)) => return Ok(None),
+        Ok(header) => *header,
+        Err(_) if is_zero(data) => return Ok(None),
+        Err(_) => return Err(format!("{} bytes are too few for a header", data.len())),
+    };
+    if header.magic_number != PC_MAGIC {
+        return Err(format!("magic number {:#x}", header.magic_number));
+    }
+    if header.version != PC_VERSION {
+        return Err(format!("version {}", header.version));
+    }
+    // The accounts that other accounts link to must be large enough to be walked, the others may
+    // have been created by an older version of the program with fewer fields
+    let minimum_size = match header.account_type {
+        PC_ACCTYPE_MAPPING => MappingAccount::MINIMUM_SIZE,
+        PC_ACCTYPE_PRODUCT => ProductAccount::MINIMUM_SIZE,
+        PC_ACCTYPE_PRICE => PriceAccount::MINIMUM_SIZE,
+        PC_ACCTYPE_COMPRESSED_PRICE => CompressedPriceAccount::MINIMUM_SIZE,
+        PC_ACCTYPE_PERMISSIONS
+        | PC_ACCTYPE_MESSAGE_BUFFER
+        | PC_ACCTYPE_SYMBOL_INDEX
+        | PC_ACCTYPE_COMPONENT_LOG
+        | PC_ACCTYPE_DERIVED_PRICE => 0,
+        account_type => return Err(format!("account type {account_type}")),
+    };
+    if data.len() < minimum_size || data.len() < header.size as usize {
+        return Err(format!(
+            "{} bytes are too few for type {} and size {}",
+            data.len(),
+            header.account_type,
+            header.size
+        ));
+    }
+    Ok(Some(header.account_type))
+}
+
+/// The product and the next price account of the price account `key` of type `account_type`, or
+/// `None` if it isn't a price account. The compressed price accounts stay in the list of their
+/// product.
+fn price_links(
+    accounts: &HashMap<Pubkey, Vec<u8>>,
+    account_type: Option<&u32>,
+    key: &Pubkey,
+) -> Option<(Pubkey, Pubkey)> {
+    match account_type {
+        Some(&PC_ACCTYPE_PRICE) => {
+            let price_data = load::<PriceAccount>(&accounts[key]).unwrap();
+            Some((price_data.product_account, price_data.next_price_account))
+        }
+        Some(&PC_ACCTYPE_COMPRESSED_PRICE) => {
+            let price_data = load::<CompressedPriceAccount>(&accounts[key]).unwrap();
+            Some((price_data.product_account, price_data.next_price_account))
+        }
+        _ => None,
+    }
+}
+
+/// Why `entries`, the first `num_` entries of a list, and `unused`, the rest of it, don't match
+/// `num_`, if they don't
+fn check_list(entries: &[Pubkey], unused: &[Pubkey]) -> Option<String> {
+    if let Some(index) = entries.iter().position(|entry| *entry == Pubkey::default()) {
+        return Some(format!("empty entry {index} of {}", entries.len()));
+    }
+    let mut distinct = HashSet::new();
+    if let Some(entry) = entries.iter().find(|entry| !distinct.insert(**entry)) {
+        return Some(format!("duplicate entry {entry}"));
+    }
+    if let Some(index) = unused.iter().position(|entry| *entry != Pubkey::default()) {
+        return Some(format!(
+            "entry {} past the first {}",
+            entries.len() + index,
+            entries.len()
+        ));
+    }
+    None
+}
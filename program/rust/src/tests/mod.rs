@@ -1,30 +1,105 @@
+mod fixtures;
+mod invariants;
+mod program_logs;
 mod pyth_simulator;
+mod test_add_mapping;
 mod test_add_price;
 mod test_add_product;
 mod test_add_publisher;
+mod test_agg_interval;
+mod test_agg_rotation;
+mod test_aggregate;
+mod test_aggregate_counter;
+mod test_aggregate_return_data;
 mod test_aggregation;
+mod test_aggregation_algorithms;
+mod test_aggregation_deferral;
+mod test_aggregation_properties;
+mod test_aliased_accounts;
 mod test_c_code;
 mod test_check_valid_signable_account_or_permissioned_funding_account;
+mod test_co_signing;
+mod test_compact_components;
+mod test_component_log;
+mod test_compress_price_account;
+mod test_compute_units;
+mod test_corrupted_accounts;
+mod test_cpi_guard;
 mod test_del_price;
 mod test_del_product;
 mod test_del_publisher;
+mod test_derived_price;
+mod test_deserialize;
+mod test_devnet;
 mod test_ema;
+mod test_error;
+mod test_events;
+mod test_fast_aggregate;
+mod test_fee;
+mod test_fixtures;
 mod test_full_publisher_set;
+mod test_fuzz;
+mod test_halt_price;
+mod test_heartbeat;
 mod test_init_mapping;
 mod test_init_price;
+mod test_invariants;
+mod test_layout;
+mod test_list_accounts;
+mod test_load;
+mod test_mapping_pda;
+mod test_market_hours;
+mod test_max_conf_ratio;
 mod test_message;
+mod test_message_buffer;
+mod test_migrations;
+mod test_oracle_core;
+mod test_oracle_instruction;
+mod test_outlier_band;
+mod test_permission_grant;
 mod test_permission_migration;
+mod test_price_bounds;
+mod test_price_drift;
+mod test_price_feed_index;
+mod test_product_attributes;
+mod test_program_logs;
 mod test_publish;
 mod test_publish_batch;
+mod test_publisher_expiry;
+mod test_publisher_pruning;
+mod test_publisher_stats;
+mod test_rate_limit;
+mod test_resign_publisher;
+mod test_rotate_publisher;
+mod test_sdk;
+mod test_set_exponent;
 mod test_set_max_latency;
 mod test_set_min_pub;
+mod test_set_publisher_excluded;
+mod test_set_publisher_weight;
+mod test_set_reference_price;
+mod test_simulator_snapshot;
 mod test_sizes;
+mod test_slot_hashes;
+mod test_status_changes;
+mod test_symbol_index;
+mod test_time_machine;
+mod test_unreliable_status;
 mod test_upd_aggregate;
+mod test_upd_aggregate_c;
 mod test_upd_permissions;
 mod test_upd_price;
+mod test_upd_price_batch;
 mod test_upd_price_no_fail_on_error;
+mod test_upd_price_scatter;
+mod test_upd_price_with_time;
+mod test_upd_price_with_trade;
 mod test_upd_product;
+mod test_upgrade_program;
 mod test_utils;
+mod test_weighted_aggregation;
+#[cfg(feature = "wormhole")]
+mod test_wormhole;
 
 
 mod test_twap;
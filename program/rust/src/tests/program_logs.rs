@@ -0,0 +1,109 @@
+//! Parsing of what the oracle program logs in simulated transactions (see
+//! `PythSimulator::simulate_program_logs`), so that tests can assert on messages, events and
+//! compute units rather than on raw log lines.
+use {
+    crate::events::Event,
+    bytemuck::pod_read_unaligned,
+    solana_program::pubkey::Pubkey,
+    std::{
+        mem::size_of,
+        str::FromStr,
+    },
+};
+
+/// What the oracle program logged while processing a transaction, in order. Lines logged by other
+/// programs, including the programs invoked by the oracle, are ignored.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProgramLogs {
+    /// Messages logged with `msg!`, except panics
+    pub messages:      Vec<String>,
+    /// Fields of each call to `sol_log_data`
+    pub data:          Vec<Vec<Vec<u8>>>,
+    /// Compute units consumed by each invocation of the oracle
+    pub compute_units: Vec<u64>,
+    /// Panic messages
+    pub panics:        Vec<String>,
+    /// Error of the invocation of the oracle that failed, if any
+    pub failure:       Option<String>,
+    /// Data last returned by the oracle with `set_return_data`, if any
+    pub return_data:   Option<Vec<u8>>,
+}
+
+impl ProgramLogs {
+    /// Parse the lines logged by the runtime for the oracle program `program_id`.
+    pub fn parse(program_id: &Pubkey, logs: &[String]) -> Self {
+        let mut program_logs = ProgramLogs::default();
+        // Programs being invoked, innermost last
+        let mut invocations: Vec<Pubkey> = vec![];
+
+        for log in logs {
+            let is_oracle = invocations.last() == Some(program_id);
+            if let Some(message) = log.strip_prefix("Program log: ") {
+                if !is_oracle {
+                    continue;
+                }
+                match message.strip_prefix("panicked at ") {
+                    Some(panic) => program_logs.panics.push(panic.to_string()),
+                    None => program_logs.messages.push(message.to_string()),
+                }
+            } else if let Some(data) = log.strip_prefix("Program data: ") {
+                if is_oracle {
+                    program_logs.data.push(
+                        data.split(' ')
+                            .map(|field| base64::decode(field).unwrap())
+                            .collect(),
+                    );
+                }
+            } else if let Some(return_data) = log.strip_prefix("Program return: ") {
+                if let Some((program, data)) = return_data.split_once(' ') {
+                    if Pubkey::from_str(program).as_ref() == Ok(program_id) {
+                        program_logs.return_data = Some(base64::decode(data).unwrap());
+                    }
+                }
+            } else if let Some(invocation) = log.strip_prefix("Program ") {
+                let (program, event) = match invocation.split_once(' ') {
+                    Some((program, event)) => (program, event),
+                    None => continue,
+                };
+                let program = match Pubkey::from_str(program) {
+                    Ok(program) => program,
+                    Err(_) => continue,
+                };
+
+                if event.starts_with("invoke [") {
+                    invocations.push(program);
+                } else if event == "success" {
+                    invocations.pop();
+                } else if let Some(error) = event.strip_prefix("failed: ") {
+                    invocations.pop();
+                    if program == *program_id {
+                        program_logs.failure = Some(error.to_string());
+                    }
+                } else if let Some(consumed) = event.strip_prefix("consumed ") {
+                    if program == *program_id {
+                        let compute_units = consumed.split(' ').next().unwrap().parse().unwrap();
+                        program_logs.compute_units.push(compute_units);
+                    }
+                }
+            }
+        }
+
+        program_logs
+    }
+
+    /// The events of type `E` logged by the oracle, in order.
+    pub fn events<E: Event>(&self) -> Vec<E> {
+        self.data
+            .iter()
+            .filter_map(|fields| match fields.as_slice() {
+                [field]
+                    if field.len() == E::DISCRIMINATOR.len() + size_of::<E>()
+                        && field.starts_with(&E::DISCRIMINATOR) =>
+                {
+                    Some(pod_read_unaligned(&field[E::DISCRIMINATOR.len()..]))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+}
@@ -1,21 +1,53 @@
+#[cfg(feature = "wormhole")]
+use {
+    crate::wormhole::WORMHOLE_PROGRAM_ID,
+    borsh::{
+        BorshDeserialize,
+        BorshSerialize,
+    },
+};
 use {
     crate::{
         accounts::{
+            read_product_attributes,
+            AccountHeader,
+            ComponentLogAccount,
+            DerivedPriceAccount,
             MappingAccount,
-            PERMISSIONS_SEED,
+            MessageBufferAccount,
+            PriceAccount,
+            ProductAccount,
+            PythAccount,
+            SymbolIndexAccount,
         },
         c_oracle_header::{
+            MAX_CPI_CALLERS,
+            PC_MAGIC,
+            PC_MAP_TABLE_SIZE,
             PC_PROD_ACC_SIZE,
             PC_PTYPE_PRICE,
+            PC_VERSION,
         },
+        client,
         deserialize::load,
+        error::OracleError,
+        events::AggregatePriceUpdate,
         instruction::{
-            AddPriceArgs,
-            AddPublisherArgs,
             CommandHeader,
             OracleCommand,
+            OracleInstruction,
+            PriceUpdate,
             UpdPermissionsArgs,
             UpdPriceArgs,
+            UpdPriceBatchEntry,
+            UpdPriceWithTimeArgs,
+            UpdPriceWithTradeArgs,
+        },
+        processor::process_instruction,
+        tests::{
+            fixtures::AccountFixture,
+            invariants,
+            program_logs::ProgramLogs,
         },
     },
     bytemuck::{
@@ -27,25 +59,37 @@ use {
         Serialize,
     },
     solana_program::{
+        account_info::AccountInfo,
         bpf_loader_upgradeable::{
             self,
             UpgradeableLoaderState,
         },
         clock::Clock,
+        entrypoint::{
+            ProgramResult,
+            MAX_PERMITTED_DATA_INCREASE,
+        },
         hash::Hash,
         instruction::{
             AccountMeta,
             Instruction,
+            InstructionError,
         },
         native_token::LAMPORTS_PER_SOL,
+        program::invoke,
+        program_error::ProgramError,
         pubkey::Pubkey,
         rent::Rent,
         stake_history::Epoch,
         system_instruction,
-        system_program,
-        sysvar::SysvarId,
+        sysvar::{
+            instructions::Instructions,
+            slot_hashes::SlotHashes,
+            SysvarId,
+        },
     },
     solana_program_test::{
+        processor,
         read_file,
         BanksClientError,
         ProgramTest,
@@ -54,19 +98,31 @@ use {
         ProgramTestError,
     },
     solana_sdk::{
-        account::Account,
+        account::{
+            Account,
+            AccountSharedData,
+        },
         signature::{
             Keypair,
             Signer,
         },
-        transaction::Transaction,
+        transaction::{
+            Transaction,
+            TransactionError,
+        },
     },
     std::{
-        collections::HashMap,
+        collections::{
+            HashMap,
+            HashSet,
+        },
         fs::File,
         iter::once,
         mem::size_of,
-        path::PathBuf,
+        path::{
+            Path,
+            PathBuf,
+        },
     },
 };
 
@@ -79,17 +135,119 @@ pub struct PythSimulator {
     /// transaction; otherwise, replayed transactions in different states can return stale
     /// results.
     last_blockhash:        Hash,
-    programdata_id:        Pubkey,
+    /// Accounts created for the oracle program by the simulator, see `snapshot`
+    oracle_accounts:       HashSet<Pubkey>,
+    /// Data of the accounts owned by the oracle program as of the last transaction, updated with
+    /// the writable accounts of each transaction, see `check_invariants`
+    oracle_state:          HashMap<Pubkey, Vec<u8>>,
+    /// Accounts written without going through the oracle program (see `tamper_account` and
+    /// `load_oracle_account`), which are left out of `oracle_state`
+    unchecked_accounts:    HashSet<Pubkey>,
     pub upgrade_authority: Keypair,
     pub genesis_keypair:   Keypair,
+    /// Program id of the stub CPI caller, see `process_cpi_caller`
+    pub cpi_caller_id:     Pubkey,
+    /// Layout of the oracle instructions sent by the simulator
+    instruction_layout:    InstructionLayout,
+    /// How the oracle program runs, see `ProgramMode`
+    program_mode:          ProgramMode,
+}
+
+/// How the simulator runs the oracle program
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ProgramMode {
+    /// `process_instruction` compiled with the tests and registered as a builtin program, which
+    /// runs under plain `cargo test`. The native program doesn't consume the compute units of the
+    /// binary and can't be upgraded.
+    Native,
+    /// The BPF binary at this path deployed as upgradable, which has to be built first (e.g. by
+    /// `cargo test-bpf`). The tests of the compute units, the logs, the return data and the
+    /// upgrades of the program run the binary, whose runtime they depend on.
+    Bpf(PathBuf),
+}
+
+/// Layout in which the simulator sends the instructions of the oracle program
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InstructionLayout {
+    /// The Borsh layout built by `client` (see `OracleInstruction::pack`)
+    Borsh,
+    /// The layout of the arguments of each command (see `OracleInstruction::pack_legacy`), the
+    /// only one understood by the programs built before the Borsh layout
+    Legacy,
+}
+
+/// Copy of the accounts owned by the oracle program at some point, see `PythSimulator::snapshot`
+pub struct OracleSnapshot {
+    accounts: HashMap<Pubkey, Account>,
 }
 
+#[derive(Clone, Copy)]
 pub struct Quote {
     pub price:      i64,
     pub confidence: u64,
     pub status:     u32,
 }
 
+/// Reason why an instruction of a failed transaction was rejected
+#[derive(Debug, PartialEq, Eq)]
+pub enum InstructionFailure {
+    /// The instruction returned an `OracleError`
+    Oracle(OracleError),
+    /// Any other error of the instruction, including custom errors that aren't oracle errors
+    Other(InstructionError),
+}
+
+impl From<OracleError> for InstructionFailure {
+    fn from(error: OracleError) -> Self {
+        InstructionFailure::Oracle(error)
+    }
+}
+
+impl From<ProgramError> for InstructionFailure {
+    fn from(error: ProgramError) -> Self {
+        // The runtime reports the errors of the program as the instruction errors of their codes
+        InstructionError::from(u64::from(error)).into()
+    }
+}
+
+impl From<InstructionError> for InstructionFailure {
+    fn from(error: InstructionError) -> Self {
+        match error {
+            InstructionError::Custom(code) => OracleError::from_code(code)
+                .map_or(InstructionFailure::Other(error), InstructionFailure::Oracle),
+            _ => InstructionFailure::Other(error),
+        }
+    }
+}
+
+/// Introspection of the transactions submitted through the simulator that are expected to fail
+pub trait TransactionFailure {
+    /// Index of the instruction that failed the transaction and the reason why it failed. Panics
+    /// if the transaction succeeded or failed outside of an instruction.
+    fn unwrap_instruction_failure(self) -> (u8, InstructionFailure);
+
+    /// Assert that the first instruction of the transaction failed with `expected`
+    fn assert_fails_with(self, expected: impl Into<InstructionFailure>);
+}
+
+impl<T> TransactionFailure for Result<T, BanksClientError> {
+    #[track_caller]
+    fn unwrap_instruction_failure(self) -> (u8, InstructionFailure) {
+        match self {
+            Ok(_) => panic!("the transaction succeeded"),
+            Err(err) => match err.unwrap() {
+                TransactionError::InstructionError(index, error) => (index, error.into()),
+                error => panic!("the transaction failed outside of an instruction: {error:?}"),
+            },
+        }
+    }
+
+    #[track_caller]
+    fn assert_fails_with(self, expected: impl Into<InstructionFailure>) {
+        assert_eq!(self.unwrap_instruction_failure(), (0, expected.into()));
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 struct ProductMetadata {
     symbol:         String,
@@ -100,13 +258,42 @@ struct ProductMetadata {
 }
 
 impl PythSimulator {
-    /// Deploys the oracle program as upgradable
+    /// Runs the oracle program natively (see `ProgramMode::Native`)
     pub async fn new() -> PythSimulator {
+        Self::new_with_mode(ProgramMode::Native, InstructionLayout::Borsh).await
+    }
+
+    /// Deploys the current build of the oracle program as upgradable, for the tests that depend on
+    /// the binary (see `ProgramMode::Bpf`)
+    pub async fn new_bpf() -> PythSimulator {
+        Self::new_with_program(&Self::current_program_binary(), InstructionLayout::Borsh).await
+    }
+
+    /// Path of the oracle program built from the current tree
+    pub fn current_program_binary() -> PathBuf {
         let target_dir = concat!(env!("CARGO_MANIFEST_DIR"), "/../../target");
-        let oracle_program_binary_path = PathBuf::from(target_dir).join("deploy/pyth_oracle.so");
-        let mut bpf_data = read_file(&oracle_program_binary_path);
+        PathBuf::from(target_dir).join("deploy/pyth_oracle.so")
+    }
+
+    /// Start a simulator running the oracle program `binary`, e.g. a build of a previous release,
+    /// and sending it instructions in `instruction_layout`. The program data account has room for
+    /// twice the largest of `binary` and the current build, so that the program can be upgraded
+    /// with `upgrade_program`.
+    pub async fn new_with_program(
+        binary: &Path,
+        instruction_layout: InstructionLayout,
+    ) -> PythSimulator {
+        Self::new_with_mode(ProgramMode::Bpf(binary.to_path_buf()), instruction_layout).await
+    }
 
+    /// Start a simulator running the oracle program in `program_mode` and sending it instructions
+    /// in `instruction_layout`
+    pub async fn new_with_mode(
+        program_mode: ProgramMode,
+        instruction_layout: InstructionLayout,
+    ) -> PythSimulator {
         let mut program_test = ProgramTest::default();
+        program_test.prefer_bpf(false);
         let program_key = Pubkey::new_unique();
         // This PDA is the actual address in the real world
         // https://docs.rs/solana-program/1.6.4/solana_program/bpf_loader_upgradeable/index.html
@@ -115,27 +302,48 @@ impl PythSimulator {
 
         let upgrade_authority_keypair = Keypair::new();
 
-        let program_deserialized = UpgradeableLoaderState::Program {
-            programdata_address: programdata_key,
-        };
         let programdata_deserialized = UpgradeableLoaderState::ProgramData {
             slot:                      1,
             upgrade_authority_address: Some(upgrade_authority_keypair.pubkey()),
         };
-
-        // Program contains a pointer to progradata
-        let program_vec = bincode::serialize(&program_deserialized).unwrap();
         // Programdata contains a header and the binary of the program
         let mut programdata_vec = bincode::serialize(&programdata_deserialized).unwrap();
-        programdata_vec.append(&mut bpf_data);
 
-        let program_account = Account {
-            lamports:   Rent::default().minimum_balance(program_vec.len()),
-            data:       program_vec,
-            owner:      bpf_loader_upgradeable::ID,
-            executable: true,
-            rent_epoch: Epoch::default(),
-        };
+        match &program_mode {
+            // The native program has no binary, but it still checks its upgrade authority against
+            // the header of the program data account
+            ProgramMode::Native => program_test.add_program(
+                "pyth_oracle",
+                program_key,
+                processor!(process_instruction),
+            ),
+            ProgramMode::Bpf(binary) => {
+                let mut bpf_data = read_file(binary);
+                let max_data_len = 2 * bpf_data
+                    .len()
+                    .max(read_file(Self::current_program_binary()).len());
+
+                let program_deserialized = UpgradeableLoaderState::Program {
+                    programdata_address: programdata_key,
+                };
+                // Program contains a pointer to progradata
+                let program_vec = bincode::serialize(&program_deserialized).unwrap();
+                let programdata_len = programdata_vec.len() + max_data_len;
+                programdata_vec.append(&mut bpf_data);
+                programdata_vec.resize(programdata_len, 0);
+
+                let program_account = Account {
+                    lamports:   Rent::default().minimum_balance(program_vec.len()),
+                    data:       program_vec,
+                    owner:      bpf_loader_upgradeable::ID,
+                    executable: true,
+                    rent_epoch: Epoch::default(),
+                };
+                // Add accounts to program test, now the program is deploy as upgradable
+                program_test.add_account(program_key, program_account);
+            }
+        }
+
         let programdata_account = Account {
             lamports:   Rent::default().minimum_balance(programdata_vec.len()),
             data:       programdata_vec,
@@ -143,11 +351,17 @@ impl PythSimulator {
             executable: false,
             rent_epoch: Epoch::default(),
         };
-
-        // Add accounts to program test, now the program is deploy as upgradable
-        program_test.add_account(program_key, program_account);
         program_test.add_account(programdata_key, programdata_account);
 
+        let cpi_caller_id = Pubkey::new_unique();
+        program_test.add_program("cpi_caller", cpi_caller_id, processor!(process_cpi_caller));
+        #[cfg(feature = "wormhole")]
+        program_test.add_program(
+            "mock_wormhole",
+            WORMHOLE_PROGRAM_ID,
+            processor!(process_mock_wormhole),
+        );
+
         // Start validator
         let context = program_test.start_with_context().await;
         let genesis_keypair = copy_keypair(&context.payer);
@@ -156,9 +370,14 @@ impl PythSimulator {
             program_id: program_key,
             context,
             last_blockhash,
-            programdata_id: programdata_key,
+            oracle_accounts: HashSet::from([client::get_permissions_pubkey(&program_key)]),
+            oracle_state: HashMap::new(),
+            unchecked_accounts: HashSet::new(),
             upgrade_authority: upgrade_authority_keypair,
             genesis_keypair: copy_keypair(&genesis_keypair),
+            cpi_caller_id,
+            instruction_layout,
+            program_mode,
         };
 
         // Transfer money to upgrade_authority so it can call the instructions
@@ -183,16 +402,59 @@ impl PythSimulator {
         result
     }
 
+    /// Upgrade the oracle program to `binary` like a deployment would, with the upgrade authority
+    /// and a buffer account holding the new binary. The accounts of the program are kept. The new
+    /// program runs from the next slot, to which the simulator advances.
+    pub async fn upgrade_program(&mut self, binary: &Path) -> Result<(), BanksClientError> {
+        assert!(
+            self.program_mode != ProgramMode::Native,
+            "the native program can't be upgraded"
+        );
+        let buffer_key = Pubkey::new_unique();
+        let mut buffer_vec = bincode::serialize(&UpgradeableLoaderState::Buffer {
+            authority_address: Some(self.upgrade_authority.pubkey()),
+        })
+        .unwrap();
+        buffer_vec.append(&mut read_file(binary));
+        let buffer_account = Account {
+            lamports:   Rent::default().minimum_balance(buffer_vec.len()),
+            data:       buffer_vec,
+            owner:      bpf_loader_upgradeable::ID,
+            executable: false,
+            rent_epoch: Epoch::default(),
+        };
+        self.context
+            .set_account(&buffer_key, &AccountSharedData::from(buffer_account));
+
+        let instruction = bpf_loader_upgradeable::upgrade(
+            &self.program_id,
+            &buffer_key,
+            &self.upgrade_authority.pubkey(),
+            &self.genesis_keypair.pubkey(),
+        );
+        self.process_ixs(
+            &[instruction],
+            &vec![],
+            &copy_keypair(&self.upgrade_authority),
+        )
+        .await?;
+        self.advance_slots(1).await
+    }
+
+    /// Send the next oracle instructions in `instruction_layout`, e.g. the Borsh layout once the
+    /// program has been upgraded from a build that only understands the legacy layout.
+    pub fn set_instruction_layout(&mut self, instruction_layout: InstructionLayout) {
+        self.instruction_layout = instruction_layout;
+    }
+
     /// Process a transaction containing `instructions` signed by `signers`.
     /// `payer` is used to pay for and sign the transaction.
-    async fn process_ixs(
+    pub async fn process_ixs(
         &mut self,
         instructions: &[Instruction],
         signers: &Vec<&Keypair>,
         payer: &Keypair,
     ) -> Result<(), BanksClientError> {
-        let mut transaction = Transaction::new_with_payer(instructions, Some(&payer.pubkey()));
-
         let blockhash = self
             .context
             .banks_client
@@ -201,13 +463,66 @@ impl PythSimulator {
             .unwrap();
         self.last_blockhash = blockhash;
 
-        transaction.partial_sign(&[payer], self.last_blockhash);
-        transaction.partial_sign(signers, self.last_blockhash);
-
+        let transaction = self.sign_transaction(instructions, signers, payer);
+        let writable_keys: Vec<Pubkey> = (0..transaction.message.account_keys.len())
+            .filter(|index| transaction.message.is_writable(*index))
+            .map(|index| transaction.message.account_keys[index])
+            .collect();
         self.context
             .banks_client
             .process_transaction(transaction)
-            .await
+            .await?;
+        self.update_oracle_state(&writable_keys).await;
+        Ok(())
+    }
+
+    /// Copy the accounts `keys`, which a transaction may have written, into `oracle_state`
+    async fn update_oracle_state(&mut self, keys: &[Pubkey]) {
+        for key in keys {
+            if self.unchecked_accounts.contains(key) {
+                continue;
+            }
+            match self.get_account(*key).await {
+                Some(account) if self.is_owned_by_oracle(&account) => {
+                    self.oracle_state.insert(*key, account.data);
+                }
+                _ => {
+                    self.oracle_state.remove(key);
+                }
+            }
+        }
+    }
+
+    /// Panic if the accounts of the oracle program break one of its invariants (see
+    /// `invariants::find_violations`). The simulator checks them when it is dropped, i.e. at the
+    /// end of each test, and a test can check them earlier to find which step corrupts them.
+    pub fn check_invariants(&self) {
+        let violations =
+            invariants::find_violations(&self.oracle_state, self.unchecked_accounts.is_empty());
+        assert!(
+            violations.is_empty(),
+            "the oracle accounts break its invariants:\n{}",
+            violations.join("\n")
+        );
+    }
+
+    /// Process a transaction like `process_ixs` and return the aggregate that the oracle sent as
+    /// return data, if any (see `client::parse_aggregate_return_data`).
+    pub async fn process_ixs_returning_aggregate(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &Vec<&Keypair>,
+        payer: &Keypair,
+    ) -> Result<Option<AggregatePriceUpdate>, BanksClientError> {
+        // Simulating against the current state returns exactly what processing the transaction
+        // right after does
+        let program_logs = self
+            .simulate_program_logs(instructions, signers, payer)
+            .await?;
+        self.process_ixs(instructions, signers, payer).await?;
+        Ok(program_logs
+            .return_data
+            .map(|return_data| client::parse_aggregate_return_data(&return_data).unwrap()))
     }
 
     /// Create an account owned by the pyth program containing `size` bytes.
@@ -231,6 +546,7 @@ impl PythSimulator {
         .await
         .unwrap();
 
+        self.oracle_accounts.insert(keypair.pubkey());
         keypair
     }
 
@@ -239,15 +555,10 @@ impl PythSimulator {
     pub async fn init_mapping(&mut self) -> Result<Keypair, BanksClientError> {
         let mapping_keypair = self.create_pyth_account(size_of::<MappingAccount>()).await;
 
-        let cmd: CommandHeader = OracleCommand::InitMapping.into();
-        let instruction = Instruction::new_with_bytes(
-            self.program_id,
-            bytes_of(&cmd),
-            vec![
-                AccountMeta::new(self.genesis_keypair.pubkey(), true),
-                AccountMeta::new(mapping_keypair.pubkey(), true),
-                AccountMeta::new(self.get_permissions_pubkey(), false),
-            ],
+        let instruction = client::init_mapping(
+            &self.program_id,
+            &self.genesis_keypair.pubkey(),
+            &mapping_keypair.pubkey(),
         );
 
         self.process_ixs(
@@ -259,51 +570,147 @@ impl PythSimulator {
         .map(|_| mapping_keypair)
     }
 
+    /// Create the mapping account at the mapping PDA (using init_mapping_pda instructions until it
+    /// is initialized, in a single transaction), returning its address. If `mapping_account` is
+    /// given, the PDA takes over its products and it is closed.
+    pub async fn init_mapping_pda(
+        &mut self,
+        mapping_account: Option<Pubkey>,
+    ) -> Result<Pubkey, BanksClientError> {
+        let num_instructions = (MappingAccount::MINIMUM_SIZE + MAX_PERMITTED_DATA_INCREASE - 1)
+            / MAX_PERMITTED_DATA_INCREASE;
+        self.send_init_mapping_pda(mapping_account, num_instructions)
+            .await
+            .map(|_| self.get_mapping_pda())
+    }
+
+    /// Send `num_instructions` init_mapping_pda instructions in a single transaction.
+    pub async fn send_init_mapping_pda(
+        &mut self,
+        mapping_account: Option<Pubkey>,
+        num_instructions: usize,
+    ) -> Result<(), BanksClientError> {
+        let instruction = client::init_mapping_pda(
+            &self.program_id,
+            &self.genesis_keypair.pubkey(),
+            mapping_account.as_ref(),
+        );
+
+        self.process_ixs(
+            &vec![instruction; num_instructions],
+            &vec![],
+            &copy_keypair(&self.genesis_keypair),
+        )
+        .await
+    }
+
+    /// Initialize a mapping account and link it after the full mapping account `tail_keypair`
+    /// (using the add_mapping instruction), returning the keypair associated with the
+    /// newly-created account.
+    pub async fn add_mapping(
+        &mut self,
+        tail_keypair: &Keypair,
+    ) -> Result<Keypair, BanksClientError> {
+        let mapping_keypair = self.create_pyth_account(size_of::<MappingAccount>()).await;
+
+        let instruction = client::add_mapping(
+            &self.program_id,
+            &self.genesis_keypair.pubkey(),
+            &tail_keypair.pubkey(),
+            &mapping_keypair.pubkey(),
+        );
+
+        self.process_ixs(
+            &[instruction],
+            &vec![tail_keypair, &mapping_keypair],
+            &copy_keypair(&self.genesis_keypair),
+        )
+        .await
+        .map(|_| mapping_keypair)
+    }
+
     /// Initialize a product account and add it to an existing mapping account (using the
     /// add_product instruction). Returns the keypair associated with the newly-created account.
     pub async fn add_product(
         &mut self,
         mapping_keypair: &Keypair,
+    ) -> Result<Keypair, BanksClientError> {
+        self.add_product_to(mapping_keypair.pubkey()).await
+    }
+
+    /// Same as `add_product`, for mapping accounts without a keypair such as the mapping PDA.
+    pub async fn add_product_to(
+        &mut self,
+        mapping_account: Pubkey,
     ) -> Result<Keypair, BanksClientError> {
         let product_keypair = self.create_pyth_account(PC_PROD_ACC_SIZE as usize).await;
 
-        let cmd: CommandHeader = OracleCommand::AddProduct.into();
-        let instruction = Instruction::new_with_bytes(
-            self.program_id,
-            bytes_of(&cmd),
-            vec![
-                AccountMeta::new(self.genesis_keypair.pubkey(), true),
-                AccountMeta::new(mapping_keypair.pubkey(), true),
-                AccountMeta::new(product_keypair.pubkey(), true),
-                AccountMeta::new(self.get_permissions_pubkey(), false),
-            ],
+        let instruction = client::add_product(
+            &self.program_id,
+            &self.genesis_keypair.pubkey(),
+            &mapping_account,
+            &product_keypair.pubkey(),
         );
 
         self.process_ixs(
             &[instruction],
-            &vec![mapping_keypair, &product_keypair],
+            &vec![&product_keypair],
             &copy_keypair(&self.genesis_keypair),
         )
         .await
         .map(|_| product_keypair)
     }
 
+    /// Replace the metadata of a product account with `attributes` (using the upd_product
+    /// instruction).
+    pub async fn upd_product(
+        &mut self,
+        product_keypair: &Keypair,
+        attributes: &[(&str, &str)],
+    ) -> Result<(), BanksClientError> {
+        let instruction = client::upd_product(
+            &self.program_id,
+            &self.genesis_keypair.pubkey(),
+            &product_keypair.pubkey(),
+            attributes,
+        );
+
+        self.process_ixs(
+            &[instruction],
+            &vec![product_keypair],
+            &copy_keypair(&self.genesis_keypair),
+        )
+        .await
+    }
+
+    /// Read the metadata of a product account as a list of key-value pairs.
+    pub async fn get_product_attributes(&mut self, product: Pubkey) -> Vec<(String, String)> {
+        let account = self.get_account(product).await.unwrap();
+        let product_data = load::<ProductAccount>(&account.data).unwrap();
+        let kv_data = &account.data[size_of::<ProductAccount>()..product_data.header.size as usize];
+        read_product_attributes(kv_data)
+            .unwrap()
+            .into_iter()
+            .map(|(key, value)| {
+                (
+                    String::from_utf8(key.to_vec()).unwrap(),
+                    String::from_utf8(value.to_vec()).unwrap(),
+                )
+            })
+            .collect()
+    }
+
     /// Delete a product account (using the del_product instruction).
     pub async fn del_product(
         &mut self,
         mapping_keypair: &Keypair,
         product_keypair: &Keypair,
     ) -> Result<(), BanksClientError> {
-        let cmd: CommandHeader = OracleCommand::DelProduct.into();
-        let instruction = Instruction::new_with_bytes(
-            self.program_id,
-            bytes_of(&cmd),
-            vec![
-                AccountMeta::new(self.genesis_keypair.pubkey(), true),
-                AccountMeta::new(mapping_keypair.pubkey(), true),
-                AccountMeta::new(product_keypair.pubkey(), true),
-                AccountMeta::new(self.get_permissions_pubkey(), false),
-            ],
+        let instruction = client::del_product(
+            &self.program_id,
+            &self.genesis_keypair.pubkey(),
+            &mapping_keypair.pubkey(),
+            &product_keypair.pubkey(),
         );
 
         self.process_ixs(
@@ -321,24 +728,15 @@ impl PythSimulator {
         product_keypair: &Keypair,
         expo: i32,
     ) -> Result<Keypair, BanksClientError> {
-        let price_keypair = self
-            .create_pyth_account(size_of::<crate::accounts::PriceAccount>())
-            .await;
+        let price_keypair = self.create_pyth_account(size_of::<PriceAccount>()).await;
 
-        let cmd = AddPriceArgs {
-            header:     OracleCommand::AddPrice.into(),
-            exponent:   expo,
-            price_type: PC_PTYPE_PRICE,
-        };
-        let instruction = Instruction::new_with_bytes(
-            self.program_id,
-            bytes_of(&cmd),
-            vec![
-                AccountMeta::new(self.genesis_keypair.pubkey(), true),
-                AccountMeta::new(product_keypair.pubkey(), true),
-                AccountMeta::new(price_keypair.pubkey(), true),
-                AccountMeta::new(self.get_permissions_pubkey(), false),
-            ],
+        let instruction = client::add_price(
+            &self.program_id,
+            &self.genesis_keypair.pubkey(),
+            &product_keypair.pubkey(),
+            &price_keypair.pubkey(),
+            expo,
+            PC_PTYPE_PRICE,
         );
 
         self.process_ixs(
@@ -353,108 +751,1545 @@ impl PythSimulator {
     /// Add a publisher to a price account (using the add_publisher instruction).
     pub async fn add_publisher(
         &mut self,
-        price_keypair: &Keypair,
+        price_account: Pubkey,
         publisher: Pubkey,
     ) -> Result<(), BanksClientError> {
-        let cmd = AddPublisherArgs {
-            header: OracleCommand::AddPublisher.into(),
+        let instruction = client::add_publisher(
+            &self.program_id,
+            &self.genesis_keypair.pubkey(),
+            &price_account,
             publisher,
-        };
-        let instruction = Instruction::new_with_bytes(
-            self.program_id,
-            bytes_of(&cmd),
-            vec![
-                AccountMeta::new(self.genesis_keypair.pubkey(), true),
-                AccountMeta::new(price_keypair.pubkey(), true),
-                AccountMeta::new(self.get_permissions_pubkey(), false),
-            ],
         );
 
         self.process_ixs(
             &[instruction],
-            &vec![price_keypair],
+            &vec![],
             &copy_keypair(&self.genesis_keypair),
         )
         .await
     }
 
-    /// Update price of a component price account (using the upd_price instruction).
-    pub async fn upd_price(
+    /// Add a publisher to a price account whose price updates are rejected after `expiry_slot`
+    /// (using the add_publisher_with_expiry instruction).
+    pub async fn add_publisher_with_expiry(
         &mut self,
-        publisher: &Keypair,
         price_account: Pubkey,
-        quote: Quote,
+        publisher: Pubkey,
+        expiry_slot: u64,
     ) -> Result<(), BanksClientError> {
-        self.upd_price_batch(
+        let instruction = client::add_publisher_with_expiry(
+            &self.program_id,
+            &self.genesis_keypair.pubkey(),
+            &price_account,
             publisher,
-            &HashMap::from_iter(once(("".to_string(), price_account))),
-            &HashMap::from_iter(once(("".to_string(), quote))),
+            expiry_slot,
+        );
+
+        self.process_ixs(
+            &[instruction],
+            &vec![],
+            &copy_keypair(&self.genesis_keypair),
         )
         .await
     }
 
-    /// Update price in multiple price account atomically (using the upd_price instruction)
-    pub async fn upd_price_batch(
+    /// Replace the key of a publisher of a price account (using the rotate_publisher
+    /// instruction).
+    pub async fn rotate_publisher(
         &mut self,
-        publisher: &Keypair,
-        price_accounts: &HashMap<String, Pubkey>,
-        quotes: &HashMap<String, Quote>,
+        price_account: Pubkey,
+        old_publisher: Pubkey,
+        new_publisher: Pubkey,
     ) -> Result<(), BanksClientError> {
-        let slot = self.context.banks_client.get_sysvar::<Clock>().await?.slot;
-        let mut instructions: Vec<Instruction> = vec![];
+        let instruction = client::rotate_publisher(
+            &self.program_id,
+            &self.genesis_keypair.pubkey(),
+            &price_account,
+            old_publisher,
+            new_publisher,
+        );
 
-        for (key, price_account) in price_accounts {
-            let cmd = UpdPriceArgs {
-                header:          OracleCommand::UpdPrice.into(),
-                status:          quotes[key].status,
-                unused_:         0,
-                price:           quotes[key].price,
-                confidence:      quotes[key].confidence,
-                publishing_slot: slot,
-            };
-            instructions.push(Instruction::new_with_bytes(
-                self.program_id,
-                bytes_of(&cmd),
-                vec![
-                    AccountMeta::new(publisher.pubkey(), true),
-                    AccountMeta::new(*price_account, false),
-                    AccountMeta::new(Clock::id(), false),
-                ],
-            ));
-        }
+        self.process_ixs(
+            &[instruction],
+            &vec![],
+            &copy_keypair(&self.genesis_keypair),
+        )
+        .await
+    }
 
-        self.process_ixs(&instructions, &vec![publisher], publisher)
+    /// Remove `publisher` from a price account, signed by the publisher itself (using the
+    /// resign_publisher instruction).
+    pub async fn resign_publisher(
+        &mut self,
+        publisher: &Keypair,
+        price_account: Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let instruction =
+            client::resign_publisher(&self.program_id, &publisher.pubkey(), &price_account);
+
+        self.process_ixs(&[instruction], &vec![publisher], publisher)
             .await
     }
 
-    // /// Delete a price account from an existing product account (using the del_price instruction).
-    pub async fn del_price(
+    /// Set the minimum number of publishers of a price account (using the set_min_pub instruction).
+    pub async fn set_min_pub(
         &mut self,
-        product_keypair: &Keypair,
-        price_keypair: &Keypair,
+        price_account: Pubkey,
+        minimum_publishers: u8,
     ) -> Result<(), BanksClientError> {
-        let cmd: CommandHeader = OracleCommand::DelPrice.into();
-        let instruction = Instruction::new_with_bytes(
-            self.program_id,
-            bytes_of(&cmd),
-            vec![
-                AccountMeta::new(self.genesis_keypair.pubkey(), true),
-                AccountMeta::new(product_keypair.pubkey(), true),
-                AccountMeta::new(price_keypair.pubkey(), true),
-                AccountMeta::new(self.get_permissions_pubkey(), false),
-            ],
+        let instruction = client::set_min_pub(
+            &self.program_id,
+            &self.genesis_keypair.pubkey(),
+            &price_account,
+            minimum_publishers,
         );
 
         self.process_ixs(
             &[instruction],
-            &vec![product_keypair, price_keypair],
+            &vec![],
             &copy_keypair(&self.genesis_keypair),
         )
         .await
     }
 
-    /// Update permissions (using the upd_permissions intruction) and return the pubkey of the
-    /// permissions account
+    /// Set the minimum number of publishers of a price account, signed by `authority` and
+    /// authorized by `permissions_account`, the permissions account or a permission grant (using
+    /// the set_min_pub instruction).
+    pub async fn set_min_pub_with_permissions(
+        &mut self,
+        authority: &Keypair,
+        permissions_account: Pubkey,
+        price_account: Pubkey,
+        minimum_publishers: u8,
+    ) -> Result<(), BanksClientError> {
+        let mut instruction = client::set_min_pub(
+            &self.program_id,
+            &authority.pubkey(),
+            &price_account,
+            minimum_publishers,
+        );
+        instruction.accounts[2].pubkey = permissions_account;
+
+        self.process_ixs(&[instruction], &vec![], authority).await
+    }
+
+    /// Exclude a publisher from the aggregation of a price account, or include it again (using
+    /// the set_publisher_excluded instruction).
+    pub async fn set_publisher_excluded(
+        &mut self,
+        price_account: Pubkey,
+        publisher: Pubkey,
+        excluded: bool,
+    ) -> Result<(), BanksClientError> {
+        let instruction = client::set_publisher_excluded(
+            &self.program_id,
+            &self.genesis_keypair.pubkey(),
+            &price_account,
+            publisher,
+            excluded,
+        );
+
+        self.process_ixs(
+            &[instruction],
+            &vec![],
+            &copy_keypair(&self.genesis_keypair),
+        )
+        .await
+    }
+
+    /// Set the weight of a publisher in the weighted aggregation mode of a price account (using
+    /// the set_publisher_weight instruction).
+    pub async fn set_publisher_weight(
+        &mut self,
+        price_account: Pubkey,
+        publisher: Pubkey,
+        weight: u32,
+    ) -> Result<(), BanksClientError> {
+        let instruction = client::set_publisher_weight(
+            &self.program_id,
+            &self.genesis_keypair.pubkey(),
+            &price_account,
+            publisher,
+            weight,
+        );
+
+        self.process_ixs(
+            &[instruction],
+            &vec![],
+            &copy_keypair(&self.genesis_keypair),
+        )
+        .await
+    }
+
+    /// Configure the pruning of the publishers of a price account (using the
+    /// set_publisher_pruning instruction).
+    pub async fn set_publisher_pruning(
+        &mut self,
+        price_account: Pubkey,
+        prune_rounds: u8,
+        readmit_rounds: u8,
+    ) -> Result<(), BanksClientError> {
+        let instruction = client::set_publisher_pruning(
+            &self.program_id,
+            &self.genesis_keypair.pubkey(),
+            &price_account,
+            prune_rounds,
+            readmit_rounds,
+        );
+
+        self.process_ixs(
+            &[instruction],
+            &vec![],
+            &copy_keypair(&self.genesis_keypair),
+        )
+        .await
+    }
+
+    /// Set the minimum number of slots between two aggregations of a price account (using the
+    /// set_agg_interval instruction).
+    pub async fn set_agg_interval(
+        &mut self,
+        price_account: Pubkey,
+        agg_interval_slots: u64,
+    ) -> Result<(), BanksClientError> {
+        let instruction = client::set_agg_interval(
+            &self.program_id,
+            &self.genesis_keypair.pubkey(),
+            &price_account,
+            agg_interval_slots,
+        );
+
+        self.process_ixs(
+            &[instruction],
+            &vec![],
+            &copy_keypair(&self.genesis_keypair),
+        )
+        .await
+    }
+
+    /// Add a time machine to a price account (using the init_time_machine instruction).
+    pub async fn init_time_machine(
+        &mut self,
+        price_account: Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let instruction = client::init_time_machine(
+            &self.program_id,
+            &self.genesis_keypair.pubkey(),
+            &price_account,
+        );
+
+        self.process_ixs(
+            &[instruction],
+            &vec![],
+            &copy_keypair(&self.genesis_keypair),
+        )
+        .await
+    }
+
+    /// Enable or disable aggregation rotation on a price account (using the set_agg_rotation
+    /// instruction).
+    pub async fn set_agg_rotation(
+        &mut self,
+        price_account: Pubkey,
+        enabled: bool,
+    ) -> Result<(), BanksClientError> {
+        let instruction = client::set_agg_rotation(
+            &self.program_id,
+            &self.genesis_keypair.pubkey(),
+            &price_account,
+            enabled,
+        );
+
+        self.process_ixs(
+            &[instruction],
+            &vec![],
+            &copy_keypair(&self.genesis_keypair),
+        )
+        .await
+    }
+
+    /// Set the aggregation mode of a price account (using the set_agg_mode instruction).
+    pub async fn set_agg_mode(
+        &mut self,
+        price_account: Pubkey,
+        agg_mode: u8,
+    ) -> Result<(), BanksClientError> {
+        let instruction = client::set_agg_mode(
+            &self.program_id,
+            &self.genesis_keypair.pubkey(),
+            &price_account,
+            agg_mode,
+        );
+
+        self.process_ixs(
+            &[instruction],
+            &vec![],
+            &copy_keypair(&self.genesis_keypair),
+        )
+        .await
+    }
+
+    /// Set the outlier band of a price account (using the set_outlier_band instruction).
+    pub async fn set_outlier_band(
+        &mut self,
+        price_account: Pubkey,
+        outlier_band: u16,
+    ) -> Result<(), BanksClientError> {
+        let instruction = client::set_outlier_band(
+            &self.program_id,
+            &self.genesis_keypair.pubkey(),
+            &price_account,
+            outlier_band,
+        );
+
+        self.process_ixs(
+            &[instruction],
+            &vec![],
+            &copy_keypair(&self.genesis_keypair),
+        )
+        .await
+    }
+
+    /// Set the maximum number of price updates per publisher and slot of a price account (using the
+    /// set_max_updates_per_slot instruction).
+    pub async fn set_max_updates_per_slot(
+        &mut self,
+        price_account: Pubkey,
+        max_updates_per_slot: u8,
+    ) -> Result<(), BanksClientError> {
+        let instruction = client::set_max_updates_per_slot(
+            &self.program_id,
+            &self.genesis_keypair.pubkey(),
+            &price_account,
+            max_updates_per_slot,
+        );
+
+        self.process_ixs(
+            &[instruction],
+            &vec![],
+            &copy_keypair(&self.genesis_keypair),
+        )
+        .await
+    }
+
+    /// Set the max confidence ratio of a price account, in basis points (using the
+    /// set_max_conf_ratio instruction).
+    pub async fn set_max_conf_ratio(
+        &mut self,
+        price_account: Pubkey,
+        max_conf_ratio: u32,
+    ) -> Result<(), BanksClientError> {
+        let instruction = client::set_max_conf_ratio(
+            &self.program_id,
+            &self.genesis_keypair.pubkey(),
+            &price_account,
+            max_conf_ratio,
+        );
+
+        self.process_ixs(
+            &[instruction],
+            &vec![],
+            &copy_keypair(&self.genesis_keypair),
+        )
+        .await
+    }
+
+    /// Set the price bounds of a price account (using the set_price_bounds instruction).
+    pub async fn set_price_bounds(
+        &mut self,
+        price_account: Pubkey,
+        min_price: i64,
+        max_price: i64,
+    ) -> Result<(), BanksClientError> {
+        let instruction = client::set_price_bounds(
+            &self.program_id,
+            &self.genesis_keypair.pubkey(),
+            &price_account,
+            min_price,
+            max_price,
+        );
+
+        self.process_ixs(
+            &[instruction],
+            &vec![],
+            &copy_keypair(&self.genesis_keypair),
+        )
+        .await
+    }
+
+    /// Set the CPI guard of a price account (using the set_cpi_guard instruction).
+    pub async fn set_cpi_guard(
+        &mut self,
+        price_account: Pubkey,
+        enabled: bool,
+        allowed_callers: [Pubkey; MAX_CPI_CALLERS],
+    ) -> Result<(), BanksClientError> {
+        let instruction = client::set_cpi_guard(
+            &self.program_id,
+            &self.genesis_keypair.pubkey(),
+            &price_account,
+            enabled,
+            allowed_callers,
+        );
+
+        self.process_ixs(
+            &[instruction],
+            &vec![],
+            &copy_keypair(&self.genesis_keypair),
+        )
+        .await
+    }
+
+    /// Set the market hours of a price account (using the set_market_hours instruction).
+    pub async fn set_market_hours(
+        &mut self,
+        price_account: Pubkey,
+        sessions: [[u16; 2]; 7],
+    ) -> Result<(), BanksClientError> {
+        let instruction = client::set_market_hours(
+            &self.program_id,
+            &self.genesis_keypair.pubkey(),
+            &price_account,
+            sessions,
+        );
+
+        self.process_ixs(
+            &[instruction],
+            &vec![],
+            &copy_keypair(&self.genesis_keypair),
+        )
+        .await
+    }
+
+    /// Set the fee of the price updates of a price account (using the set_fee instruction).
+    pub async fn set_fee(
+        &mut self,
+        price_account: Pubkey,
+        fee_lamports: u64,
+    ) -> Result<(), BanksClientError> {
+        let instruction = client::set_fee(
+            &self.program_id,
+            &self.genesis_keypair.pubkey(),
+            &price_account,
+            fee_lamports,
+        );
+
+        self.process_ixs(
+            &[instruction],
+            &vec![],
+            &copy_keypair(&self.genesis_keypair),
+        )
+        .await
+    }
+
+    /// Withdraw collected fees from the treasury, signed by `authority` (using the withdraw_fee
+    /// instruction).
+    pub async fn withdraw_fee(
+        &mut self,
+        authority: &Keypair,
+        destination: Pubkey,
+        lamports: u64,
+    ) -> Result<(), BanksClientError> {
+        let instruction = client::withdraw_fee(
+            &self.program_id,
+            &authority.pubkey(),
+            &destination,
+            lamports,
+        );
+
+        self.process_ixs(&[instruction], &vec![], authority).await
+    }
+
+    /// Grant `authority` the permission to run `command` (using the grant_permission instruction).
+    pub async fn grant_permission(
+        &mut self,
+        authority: Pubkey,
+        command: OracleCommand,
+    ) -> Result<(), BanksClientError> {
+        let instruction = client::grant_permission(
+            &self.program_id,
+            &self.genesis_keypair.pubkey(),
+            &authority,
+            command,
+        );
+
+        self.process_ixs(
+            &[instruction],
+            &vec![],
+            &copy_keypair(&self.genesis_keypair),
+        )
+        .await
+    }
+
+    /// Revoke the permission of `authority` to run `command` (using the revoke_permission
+    /// instruction).
+    pub async fn revoke_permission(
+        &mut self,
+        authority: Pubkey,
+        command: OracleCommand,
+    ) -> Result<(), BanksClientError> {
+        let instruction = client::revoke_permission(
+            &self.program_id,
+            &self.genesis_keypair.pubkey(),
+            &authority,
+            command,
+        );
+
+        self.process_ixs(
+            &[instruction],
+            &vec![],
+            &copy_keypair(&self.genesis_keypair),
+        )
+        .await
+    }
+
+    /// Assign a price feed index to a price account (using the init_price_feed_index
+    /// instruction).
+    pub async fn init_price_feed_index(
+        &mut self,
+        price_account: Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let instruction = client::init_price_feed_index(
+            &self.program_id,
+            &self.genesis_keypair.pubkey(),
+            &price_account,
+        );
+
+        self.process_ixs(
+            &[instruction],
+            &vec![],
+            &copy_keypair(&self.genesis_keypair),
+        )
+        .await
+    }
+
+    /// Change the exponent of a price account (using the set_exponent instruction).
+    pub async fn set_exponent(
+        &mut self,
+        price_account: Pubkey,
+        exponent: i32,
+    ) -> Result<(), BanksClientError> {
+        let instruction = client::set_exponent(
+            &self.program_id,
+            &self.genesis_keypair.pubkey(),
+            &price_account,
+            exponent,
+        );
+
+        self.process_ixs(
+            &[instruction],
+            &vec![],
+            &copy_keypair(&self.genesis_keypair),
+        )
+        .await
+    }
+
+    /// Halt a price account (using the halt_price instruction).
+    pub async fn halt_price(&mut self, price_account: Pubkey) -> Result<(), BanksClientError> {
+        let instruction = client::halt_price(
+            &self.program_id,
+            &self.genesis_keypair.pubkey(),
+            &price_account,
+        );
+
+        self.process_ixs(
+            &[instruction],
+            &vec![],
+            &copy_keypair(&self.genesis_keypair),
+        )
+        .await
+    }
+
+    /// Compress a halted price account (using the compress_price_account instruction) and send
+    /// the lamports it no longer needs to `recipient`, or to the genesis account if `None`.
+    pub async fn compress_price_account(
+        &mut self,
+        price_account: Pubkey,
+        recipient: Option<Pubkey>,
+    ) -> Result<(), BanksClientError> {
+        let instruction = client::compress_price_account(
+            &self.program_id,
+            &self.genesis_keypair.pubkey(),
+            &price_account,
+            recipient.as_ref(),
+        );
+
+        self.process_ixs(
+            &[instruction],
+            &vec![],
+            &copy_keypair(&self.genesis_keypair),
+        )
+        .await
+    }
+
+    /// Set the reference price account of a product account (using the set_reference_price
+    /// instruction).
+    pub async fn set_reference_price(
+        &mut self,
+        product_account: Pubkey,
+        price_account: Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let instruction = client::set_reference_price(
+            &self.program_id,
+            &self.genesis_keypair.pubkey(),
+            &product_account,
+            &price_account,
+        );
+
+        self.process_ixs(
+            &[instruction],
+            &vec![],
+            &copy_keypair(&self.genesis_keypair),
+        )
+        .await
+    }
+
+    /// Resume a halted price account (using the resume_price instruction).
+    pub async fn resume_price(&mut self, price_account: Pubkey) -> Result<(), BanksClientError> {
+        let instruction = client::resume_price(
+            &self.program_id,
+            &self.genesis_keypair.pubkey(),
+            &price_account,
+        );
+
+        self.process_ixs(
+            &[instruction],
+            &vec![],
+            &copy_keypair(&self.genesis_keypair),
+        )
+        .await
+    }
+
+    /// Update price of a component price account (using the upd_price instruction).
+    pub async fn upd_price(
+        &mut self,
+        publisher: &Keypair,
+        price_account: Pubkey,
+        quote: Quote,
+    ) -> Result<(), BanksClientError> {
+        self.upd_price_batch(
+            publisher,
+            &HashMap::from_iter(once(("".to_string(), price_account))),
+            &HashMap::from_iter(once(("".to_string(), quote))),
+        )
+        .await
+    }
+
+    /// Update price of a component price account, passing the SlotHashes sysvar so that the slots
+    /// skipped by the cluster don't make quotes stale.
+    pub async fn upd_price_with_slot_hashes(
+        &mut self,
+        publisher: &Keypair,
+        price_account: Pubkey,
+        quote: Quote,
+    ) -> Result<(), BanksClientError> {
+        let slot = self.context.banks_client.get_sysvar::<Clock>().await?.slot;
+        let mut instruction = self.upd_price_instruction(
+            OracleCommand::UpdPrice,
+            publisher,
+            price_account,
+            &quote,
+            slot,
+        );
+        instruction
+            .accounts
+            .push(AccountMeta::new_readonly(SlotHashes::id(), false));
+
+        self.process_ixs(&[instruction], &vec![publisher], publisher)
+            .await
+    }
+
+    /// Update price of a component price account, passing the Instructions sysvar as required by
+    /// price accounts with a CPI guard.
+    pub async fn upd_price_with_instructions_sysvar(
+        &mut self,
+        publisher: &Keypair,
+        price_account: Pubkey,
+        quote: Quote,
+    ) -> Result<(), BanksClientError> {
+        let slot = self.context.banks_client.get_sysvar::<Clock>().await?.slot;
+        let mut instruction = self.upd_price_instruction(
+            OracleCommand::UpdPrice,
+            publisher,
+            price_account,
+            &quote,
+            slot,
+        );
+        instruction
+            .accounts
+            .push(AccountMeta::new_readonly(Instructions::id(), false));
+
+        self.process_ixs(&[instruction], &vec![publisher], publisher)
+            .await
+    }
+
+    /// Update price of a component price account, passing the accounts of the mock Wormhole core
+    /// bridge so that a successful aggregation is attested (see `init_mock_wormhole`).
+    #[cfg(feature = "wormhole")]
+    pub async fn upd_price_with_wormhole(
+        &mut self,
+        publisher: &Keypair,
+        price_account: Pubkey,
+        quote: Quote,
+    ) -> Result<(), BanksClientError> {
+        let slot = self.context.banks_client.get_sysvar::<Clock>().await?.slot;
+        let mut instruction = self.upd_price_instruction(
+            OracleCommand::UpdPrice,
+            publisher,
+            price_account,
+            &quote,
+            slot,
+        );
+        instruction
+            .accounts
+            .extend(client::wormhole_accounts(&self.program_id, &price_account));
+
+        self.process_ixs(&[instruction], &vec![publisher], publisher)
+            .await
+    }
+
+    /// Create the config, sequence and fee collector accounts of the mock Wormhole core bridge
+    /// (see `process_mock_wormhole`) at the addresses used by `client::wormhole_accounts`, with a
+    /// fee of `fee` lamports per message.
+    #[cfg(feature = "wormhole")]
+    pub async fn init_mock_wormhole(&mut self, fee: u64) {
+        let accounts = client::wormhole_accounts(&self.program_id, &Pubkey::default());
+        let fee_collector_lamports = Rent::default().minimum_balance(0);
+        let mut bridge_data = vec![0; 24];
+        bridge_data[4..12].copy_from_slice(&fee_collector_lamports.to_le_bytes());
+        bridge_data[16..24].copy_from_slice(&fee.to_le_bytes());
+        for (key, data, owner) in [
+            (accounts[0].pubkey, bridge_data, WORMHOLE_PROGRAM_ID),
+            (accounts[3].pubkey, vec![0; 8], WORMHOLE_PROGRAM_ID),
+            (
+                accounts[4].pubkey,
+                vec![],
+                solana_program::system_program::id(),
+            ),
+        ] {
+            let account = Account {
+                lamports: Rent::default().minimum_balance(data.len()),
+                data,
+                owner,
+                executable: false,
+                rent_epoch: Epoch::default(),
+            };
+            self.context
+                .set_account(&key, &AccountSharedData::from(account));
+        }
+    }
+
+    /// The last message posted to the mock Wormhole core bridge for `price_account`, if any
+    #[cfg(feature = "wormhole")]
+    pub async fn get_wormhole_message(
+        &mut self,
+        price_account: Pubkey,
+    ) -> Option<MockPostedMessage> {
+        let message = client::get_wormhole_message_pubkey(&self.program_id, &price_account);
+        self.get_account(message)
+            .await
+            .map(|account| MockPostedMessage::try_from_slice(&account.data).unwrap())
+    }
+
+    /// Update price of a component price account from the stub CPI caller program (see
+    /// `process_cpi_caller`), optionally passing the Instructions sysvar.
+    pub async fn upd_price_via_cpi(
+        &mut self,
+        publisher: &Keypair,
+        price_account: Pubkey,
+        quote: Quote,
+        with_instructions_sysvar: bool,
+    ) -> Result<(), BanksClientError> {
+        let instruction = self
+            .upd_price_via_cpi_instruction(
+                publisher,
+                price_account,
+                quote,
+                with_instructions_sysvar,
+            )
+            .await?;
+
+        self.process_ixs(&[instruction], &vec![publisher], publisher)
+            .await
+    }
+
+    /// Build an instruction of the stub CPI caller program invoking upd_price, see
+    /// `upd_price_via_cpi`.
+    pub async fn upd_price_via_cpi_instruction(
+        &mut self,
+        publisher: &Keypair,
+        price_account: Pubkey,
+        quote: Quote,
+        with_instructions_sysvar: bool,
+    ) -> Result<Instruction, BanksClientError> {
+        let slot = self.context.banks_client.get_sysvar::<Clock>().await?.slot;
+        let mut instruction = self.upd_price_instruction(
+            OracleCommand::UpdPrice,
+            publisher,
+            price_account,
+            &quote,
+            slot,
+        );
+        if with_instructions_sysvar {
+            instruction
+                .accounts
+                .push(AccountMeta::new_readonly(Instructions::id(), false));
+        }
+        let mut accounts = vec![AccountMeta::new_readonly(self.program_id, false)];
+        accounts.extend(instruction.accounts);
+        Ok(Instruction::new_with_bytes(
+            self.cpi_caller_id,
+            &instruction.data,
+            accounts,
+        ))
+    }
+
+    /// Initialize a message buffer account (using the init_message_buffer instruction), returning
+    /// its keypair.
+    pub async fn init_message_buffer(&mut self) -> Result<Keypair, BanksClientError> {
+        let message_buffer_keypair = self
+            .create_pyth_account(size_of::<MessageBufferAccount>())
+            .await;
+
+        let instruction = client::init_message_buffer(
+            &self.program_id,
+            &self.genesis_keypair.pubkey(),
+            &message_buffer_keypair.pubkey(),
+        );
+
+        self.process_ixs(
+            &[instruction],
+            &vec![&message_buffer_keypair],
+            &copy_keypair(&self.genesis_keypair),
+        )
+        .await
+        .map(|_| message_buffer_keypair)
+    }
+
+    /// Initialize a symbol index account (using the init_symbol_index instruction), returning its
+    /// keypair.
+    pub async fn init_symbol_index(&mut self) -> Result<Keypair, BanksClientError> {
+        let symbol_index_keypair = self
+            .create_pyth_account(size_of::<SymbolIndexAccount>())
+            .await;
+
+        let instruction = client::init_symbol_index(
+            &self.program_id,
+            &self.genesis_keypair.pubkey(),
+            &symbol_index_keypair.pubkey(),
+        );
+
+        self.process_ixs(
+            &[instruction],
+            &vec![&symbol_index_keypair],
+            &copy_keypair(&self.genesis_keypair),
+        )
+        .await
+        .map(|_| symbol_index_keypair)
+    }
+
+    /// Add a price account to a product account and to the symbol index account `symbol_index`
+    /// (using the add_price instruction), returning the keypair of the price account.
+    pub async fn add_price_to_symbol_index(
+        &mut self,
+        product_keypair: &Keypair,
+        expo: i32,
+        symbol_index: Pubkey,
+    ) -> Result<Keypair, BanksClientError> {
+        let price_keypair = self.create_pyth_account(size_of::<PriceAccount>()).await;
+
+        let instruction = client::with_symbol_index(
+            client::add_price(
+                &self.program_id,
+                &self.genesis_keypair.pubkey(),
+                &product_keypair.pubkey(),
+                &price_keypair.pubkey(),
+                expo,
+                PC_PTYPE_PRICE,
+            ),
+            &symbol_index,
+        );
+
+        self.process_ixs(
+            &[instruction],
+            &vec![product_keypair, &price_keypair],
+            &copy_keypair(&self.genesis_keypair),
+        )
+        .await
+        .map(|_| price_keypair)
+    }
+
+    /// Delete a price account from a product account and from the symbol index account
+    /// `symbol_index` (using the del_price instruction).
+    pub async fn del_price_from_symbol_index(
+        &mut self,
+        product_keypair: &Keypair,
+        price_keypair: &Keypair,
+        symbol_index: Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let instruction = client::with_symbol_index(
+            client::del_price(
+                &self.program_id,
+                &self.genesis_keypair.pubkey(),
+                &product_keypair.pubkey(),
+                &price_keypair.pubkey(),
+                None,
+            ),
+            &symbol_index,
+        );
+
+        self.process_ixs(
+            &[instruction],
+            &vec![product_keypair, price_keypair],
+            &copy_keypair(&self.genesis_keypair),
+        )
+        .await
+    }
+
+    /// The price account indexed under `symbol` by the symbol index account `symbol_index`, if
+    /// any. The index is read in place, it is too large to be copied like other accounts.
+    pub async fn get_indexed_price(
+        &mut self,
+        symbol_index: Pubkey,
+        symbol: &str,
+    ) -> Option<Pubkey> {
+        let account = self.get_account(symbol_index).await.unwrap();
+        load::<SymbolIndexAccount>(&account.data)
+            .unwrap()
+            .find_price_account(symbol)
+            .copied()
+    }
+
+    /// Initialize a component log account for `price_account` (using the init_component_log
+    /// instruction), returning its keypair.
+    pub async fn init_component_log(
+        &mut self,
+        price_account: Pubkey,
+    ) -> Result<Keypair, BanksClientError> {
+        let component_log_keypair = self
+            .create_pyth_account(size_of::<ComponentLogAccount>())
+            .await;
+
+        let instruction = client::init_component_log(
+            &self.program_id,
+            &self.genesis_keypair.pubkey(),
+            &component_log_keypair.pubkey(),
+            &price_account,
+        );
+
+        self.process_ixs(
+            &[instruction],
+            &vec![&component_log_keypair],
+            &copy_keypair(&self.genesis_keypair),
+        )
+        .await
+        .map(|_| component_log_keypair)
+    }
+
+    /// Initialize a derived price account quoting `base_price_account` in `quote_price_account`
+    /// at `exponent` (using the init_derived_price instruction), returning its keypair.
+    pub async fn init_derived_price(
+        &mut self,
+        base_price_account: Pubkey,
+        quote_price_account: Pubkey,
+        exponent: i32,
+    ) -> Result<Keypair, BanksClientError> {
+        let derived_price_keypair = self
+            .create_pyth_account(size_of::<DerivedPriceAccount>())
+            .await;
+
+        let instruction = client::init_derived_price(
+            &self.program_id,
+            &self.genesis_keypair.pubkey(),
+            &derived_price_keypair.pubkey(),
+            &base_price_account,
+            &quote_price_account,
+            exponent,
+        );
+
+        self.process_ixs(
+            &[instruction],
+            &vec![&derived_price_keypair],
+            &copy_keypair(&self.genesis_keypair),
+        )
+        .await
+        .map(|_| derived_price_keypair)
+    }
+
+    /// Update the ratio of a derived price account (using the upd_derived instruction), in a
+    /// transaction paid by `payer`
+    pub async fn upd_derived(
+        &mut self,
+        payer: &Keypair,
+        derived_price_account: Pubkey,
+        base_price_account: Pubkey,
+        quote_price_account: Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let instruction = client::upd_derived(
+            &self.program_id,
+            &derived_price_account,
+            &base_price_account,
+            &quote_price_account,
+        );
+
+        self.process_ixs(&[instruction], &vec![], payer).await
+    }
+
+    /// Update price of a component price account, passing a component log account to which the
+    /// update is appended.
+    pub async fn upd_price_with_component_log(
+        &mut self,
+        publisher: &Keypair,
+        price_account: Pubkey,
+        quote: Quote,
+        component_log_account: Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let slot = self.context.banks_client.get_sysvar::<Clock>().await?.slot;
+        let instruction = client::with_component_log(
+            self.upd_price_instruction(
+                OracleCommand::UpdPrice,
+                publisher,
+                price_account,
+                &quote,
+                slot,
+            ),
+            &component_log_account,
+        );
+
+        self.process_ixs(&[instruction], &vec![publisher], publisher)
+            .await
+    }
+
+    /// Update price of a component price account, passing a message buffer account to which the
+    /// aggregation appends its result.
+    pub async fn upd_price_with_message_buffer(
+        &mut self,
+        publisher: &Keypair,
+        price_account: Pubkey,
+        quote: Quote,
+        message_buffer_account: Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let slot = self.context.banks_client.get_sysvar::<Clock>().await?.slot;
+        let mut instruction = self.upd_price_instruction(
+            OracleCommand::UpdPrice,
+            publisher,
+            price_account,
+            &quote,
+            slot,
+        );
+        instruction
+            .accounts
+            .push(AccountMeta::new(message_buffer_account, false));
+
+        self.process_ixs(&[instruction], &vec![publisher], publisher)
+            .await
+    }
+
+    /// Update price of a component price account, paying its fee into the treasury.
+    pub async fn upd_price_with_fee(
+        &mut self,
+        publisher: &Keypair,
+        price_account: Pubkey,
+        quote: Quote,
+    ) -> Result<(), BanksClientError> {
+        let slot = self.context.banks_client.get_sysvar::<Clock>().await?.slot;
+        let mut instruction = self.upd_price_instruction(
+            OracleCommand::UpdPrice,
+            publisher,
+            price_account,
+            &quote,
+            slot,
+        );
+        instruction.accounts.extend(self.fee_accounts());
+
+        self.process_ixs(&[instruction], &vec![publisher], publisher)
+            .await
+    }
+
+    /// Update price of a component price account with a publish time (using the
+    /// upd_price_with_time instruction), publishing at the current slot.
+    pub async fn upd_price_with_time(
+        &mut self,
+        publisher: &Keypair,
+        price_account: Pubkey,
+        quote: Quote,
+        publish_time: i64,
+    ) -> Result<(), BanksClientError> {
+        let slot = self.context.banks_client.get_sysvar::<Clock>().await?.slot;
+        let cmd = UpdPriceWithTimeArgs {
+            header: OracleCommand::UpdPriceWithTime.into(),
+            status: quote.status,
+            unused_: 0,
+            price: quote.price,
+            confidence: quote.confidence,
+            publishing_slot: slot,
+            publish_time,
+        };
+        let instruction = Instruction::new_with_bytes(
+            self.program_id,
+            bytes_of(&cmd),
+            vec![
+                AccountMeta::new(publisher.pubkey(), true),
+                AccountMeta::new(price_account, false),
+                AccountMeta::new_readonly(Clock::id(), false),
+            ],
+        );
+
+        self.process_ixs(&[instruction], &vec![publisher], publisher)
+            .await
+    }
+
+    /// Update price of a component price account with the last trade of the publisher (using the
+    /// upd_price_with_trade instruction), publishing at the current slot. A `trade_size` of 0
+    /// publishes the quote without a trade.
+    pub async fn upd_price_with_trade(
+        &mut self,
+        publisher: &Keypair,
+        price_account: Pubkey,
+        quote: Quote,
+        trade_price: i64,
+        trade_size: u64,
+        venue_id: u32,
+    ) -> Result<(), BanksClientError> {
+        let slot = self.context.banks_client.get_sysvar::<Clock>().await?.slot;
+        let cmd = UpdPriceWithTradeArgs {
+            header: OracleCommand::UpdPriceWithTrade.into(),
+            status: quote.status,
+            unused_: 0,
+            price: quote.price,
+            confidence: quote.confidence,
+            publishing_slot: slot,
+            trade_price,
+            trade_size,
+            venue_id,
+            unused_2_: 0,
+        };
+        let instruction = Instruction::new_with_bytes(
+            self.program_id,
+            bytes_of(&cmd),
+            vec![
+                AccountMeta::new(publisher.pubkey(), true),
+                AccountMeta::new(price_account, false),
+                AccountMeta::new_readonly(Clock::id(), false),
+            ],
+        );
+
+        self.process_ixs(&[instruction], &vec![publisher], publisher)
+            .await
+    }
+
+    /// Send a heartbeat of a publisher of a price account (using the heartbeat instruction).
+    pub async fn heartbeat(
+        &mut self,
+        publisher: &Keypair,
+        price_account: Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let instruction = client::heartbeat(&self.program_id, &publisher.pubkey(), &price_account);
+
+        self.process_ixs(&[instruction], &vec![publisher], publisher)
+            .await
+    }
+
+    /// Update price in multiple price account atomically (using the upd_price instruction)
+    pub async fn upd_price_batch(
+        &mut self,
+        publisher: &Keypair,
+        price_accounts: &HashMap<String, Pubkey>,
+        quotes: &HashMap<String, Quote>,
+    ) -> Result<(), BanksClientError> {
+        let instructions = self
+            .upd_price_instructions(publisher, price_accounts, quotes)
+            .await?;
+
+        self.process_ixs(&instructions, &vec![publisher], publisher)
+            .await
+    }
+
+    /// Update price in multiple price accounts using a single upd_price_batch instruction.
+    pub async fn upd_price_batch_ix(
+        &mut self,
+        publisher: &Keypair,
+        price_accounts: &HashMap<String, Pubkey>,
+        quotes: &HashMap<String, Quote>,
+    ) -> Result<(), BanksClientError> {
+        let instruction = self
+            .upd_price_batch_instruction(publisher, price_accounts, quotes)
+            .await?;
+
+        self.process_ixs(&[instruction], &vec![publisher], publisher)
+            .await
+    }
+
+    /// Publish `updates` using a single upd_price_scatter instruction and return the result of
+    /// each update, as returned by the program.
+    pub async fn upd_price_scatter(
+        &mut self,
+        publisher: &Keypair,
+        updates: &[(Pubkey, Quote)],
+    ) -> Result<Vec<Result<(), ProgramError>>, BanksClientError> {
+        let instruction = self
+            .upd_price_scatter_instruction(publisher, updates)
+            .await?;
+        // Simulating against the current state returns exactly what processing the transaction
+        // right after does
+        let program_logs = self
+            .simulate_program_logs(&[instruction.clone()], &vec![publisher], publisher)
+            .await?;
+        self.process_ixs(&[instruction], &vec![publisher], publisher)
+            .await?;
+        Ok(
+            client::parse_upd_price_scatter_results(&program_logs.return_data.unwrap_or_default())
+                .unwrap(),
+        )
+    }
+
+    /// Build an upd_price_scatter instruction publishing `updates` at the current slot.
+    pub async fn upd_price_scatter_instruction(
+        &mut self,
+        publisher: &Keypair,
+        updates: &[(Pubkey, Quote)],
+    ) -> Result<Instruction, BanksClientError> {
+        let slot = self.context.banks_client.get_sysvar::<Clock>().await?.slot;
+        let updates: Vec<(Pubkey, PriceUpdate)> = updates
+            .iter()
+            .map(|(price_account, quote)| {
+                (
+                    *price_account,
+                    PriceUpdate {
+                        status:          quote.status,
+                        price:           quote.price,
+                        confidence:      quote.confidence,
+                        publishing_slot: slot,
+                    },
+                )
+            })
+            .collect();
+        Ok(client::upd_price_scatter(
+            &self.program_id,
+            &publisher.pubkey(),
+            &updates,
+        ))
+    }
+
+    /// Build an upd_price instruction publishing `quote` at the current slot with
+    /// `remaining_compute_units` compute units left for it (see
+    /// `client::upd_price_with_budget`).
+    pub async fn upd_price_with_budget_instruction(
+        &mut self,
+        publisher: &Keypair,
+        price_account: Pubkey,
+        quote: Quote,
+        remaining_compute_units: u32,
+    ) -> Result<Instruction, BanksClientError> {
+        let slot = self.context.banks_client.get_sysvar::<Clock>().await?.slot;
+        Ok(client::upd_price_with_budget(
+            &self.program_id,
+            &publisher.pubkey(),
+            &price_account,
+            &PriceUpdate {
+                status:          quote.status,
+                price:           quote.price,
+                confidence:      quote.confidence,
+                publishing_slot: slot,
+            },
+            remaining_compute_units,
+        ))
+    }
+
+    /// Build one upd_price instruction per price account, publishing at the current slot.
+    pub async fn upd_price_instructions(
+        &mut self,
+        publisher: &Keypair,
+        price_accounts: &HashMap<String, Pubkey>,
+        quotes: &HashMap<String, Quote>,
+    ) -> Result<Vec<Instruction>, BanksClientError> {
+        let slot = self.context.banks_client.get_sysvar::<Clock>().await?.slot;
+        let mut instructions: Vec<Instruction> = vec![];
+
+        for (key, price_account) in price_accounts {
+            instructions.push(self.upd_price_instruction(
+                OracleCommand::UpdPrice,
+                publisher,
+                *price_account,
+                &quotes[key],
+                slot,
+            ));
+        }
+
+        Ok(instructions)
+    }
+
+    /// Update price of a component price account using the upd_price_no_fail_on_error
+    /// instruction. The transaction succeeds even if the update is rejected.
+    pub async fn upd_price_no_fail_on_error(
+        &mut self,
+        publisher: &Keypair,
+        price_account: Pubkey,
+        quote: Quote,
+    ) -> Result<(), BanksClientError> {
+        let instruction = self
+            .upd_price_no_fail_on_error_instruction(publisher, price_account, &quote)
+            .await?;
+
+        self.process_ixs(&[instruction], &vec![publisher], publisher)
+            .await
+    }
+
+    /// Build an upd_price_no_fail_on_error instruction, publishing at the current slot.
+    pub async fn upd_price_no_fail_on_error_instruction(
+        &mut self,
+        publisher: &Keypair,
+        price_account: Pubkey,
+        quote: &Quote,
+    ) -> Result<Instruction, BanksClientError> {
+        let slot = self.context.banks_client.get_sysvar::<Clock>().await?.slot;
+
+        Ok(self.upd_price_instruction(
+            OracleCommand::UpdPriceNoFailOnError,
+            publisher,
+            price_account,
+            quote,
+            slot,
+        ))
+    }
+
+    /// Build an instruction publishing `quote` to `price_account`. `command` must be one of the
+    /// commands taking `UpdPriceArgs`. Unlike the other instructions of the simulator, price
+    /// updates use the legacy layout, as publishers do to save compute units.
+    fn upd_price_instruction(
+        &self,
+        command: OracleCommand,
+        publisher: &Keypair,
+        price_account: Pubkey,
+        quote: &Quote,
+        publishing_slot: u64,
+    ) -> Instruction {
+        let cmd = UpdPriceArgs {
+            header: command.into(),
+            status: quote.status,
+            unused_: 0,
+            price: quote.price,
+            confidence: quote.confidence,
+            publishing_slot,
+        };
+        Instruction::new_with_bytes(
+            self.program_id,
+            bytes_of(&cmd),
+            vec![
+                AccountMeta::new(publisher.pubkey(), true),
+                AccountMeta::new(price_account, false),
+                AccountMeta::new(Clock::id(), false),
+            ],
+        )
+    }
+
+    /// Build a single upd_price_batch instruction updating every price account, publishing at the
+    /// current slot.
+    pub async fn upd_price_batch_instruction(
+        &mut self,
+        publisher: &Keypair,
+        price_accounts: &HashMap<String, Pubkey>,
+        quotes: &HashMap<String, Quote>,
+    ) -> Result<Instruction, BanksClientError> {
+        let slot = self.context.banks_client.get_sysvar::<Clock>().await?.slot;
+        let cmd: CommandHeader = OracleCommand::UpdPriceBatch.into();
+        let mut instruction_data = bytes_of(&cmd).to_vec();
+        let mut accounts = vec![
+            AccountMeta::new(publisher.pubkey(), true),
+            AccountMeta::new_readonly(Clock::id(), false),
+        ];
+
+        for (key, price_account) in price_accounts {
+            let entry = UpdPriceBatchEntry {
+                status:          quotes[key].status,
+                unused_:         0,
+                price:           quotes[key].price,
+                confidence:      quotes[key].confidence,
+                publishing_slot: slot,
+            };
+            instruction_data.extend_from_slice(bytes_of(&entry));
+            accounts.push(AccountMeta::new(*price_account, false));
+        }
+
+        Ok(Instruction::new_with_bytes(
+            self.program_id,
+            &instruction_data,
+            accounts,
+        ))
+    }
+
+    /// Simulate a transaction containing `instructions` signed by `signers` and return the number
+    /// of compute units it consumed. `payer` is used to pay for and sign the transaction. The
+    /// transaction is not committed.
+    pub async fn simulate_compute_units(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &Vec<&Keypair>,
+        payer: &Keypair,
+    ) -> Result<u64, BanksClientError> {
+        assert!(
+            self.program_mode != ProgramMode::Native,
+            "the native program doesn't consume the compute units of the binary"
+        );
+        let simulation = self
+            .context
+            .banks_client
+            .simulate_transaction(self.sign_transaction(instructions, signers, payer))
+            .await?;
+
+        if let Some(Err(err)) = simulation.result {
+            return Err(BanksClientError::TransactionError(err));
+        }
+
+        simulation
+            .simulation_details
+            .map(|details| details.units_consumed)
+            .ok_or(BanksClientError::ClientError(
+                "Simulation did not return any details",
+            ))
+    }
+
+    /// Process a transaction containing `instruction` signed by `signers` and return the number of
+    /// compute units it consumed. `payer` is used to pay for and sign the transaction.
+    pub async fn measure_compute_units(
+        &mut self,
+        instruction: Instruction,
+        signers: &Vec<&Keypair>,
+        payer: &Keypair,
+    ) -> Result<u64, BanksClientError> {
+        // Simulating against the current state consumes exactly as many compute units as
+        // processing the transaction right after
+        let compute_units = self
+            .simulate_compute_units(&[instruction.clone()], signers, payer)
+            .await?;
+        self.process_ixs(&[instruction], signers, payer).await?;
+        Ok(compute_units)
+    }
+
+    /// Simulate a transaction containing `instructions` signed by `signers` and return the log
+    /// messages it emitted. `payer` is used to pay for and sign the transaction. The transaction
+    /// is not committed.
+    pub async fn simulate_logs(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &Vec<&Keypair>,
+        payer: &Keypair,
+    ) -> Result<Vec<String>, BanksClientError> {
+        let simulation = self
+            .context
+            .banks_client
+            .simulate_transaction(self.sign_transaction(instructions, signers, payer))
+            .await?;
+
+        if let Some(Err(err)) = simulation.result {
+            return Err(BanksClientError::TransactionError(err));
+        }
+
+        simulation
+            .simulation_details
+            .map(|details| details.logs)
+            .ok_or(BanksClientError::ClientError(
+                "Simulation did not return any details",
+            ))
+    }
+
+    /// Simulate a transaction containing `instructions` signed by `signers` and parse what the
+    /// oracle program logged, whether or not the transaction succeeds. `payer` is used to pay for
+    /// and sign the transaction. The transaction is not committed.
+    pub async fn simulate_program_logs(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &Vec<&Keypair>,
+        payer: &Keypair,
+    ) -> Result<ProgramLogs, BanksClientError> {
+        let simulation = self
+            .context
+            .banks_client
+            .simulate_transaction(self.sign_transaction(instructions, signers, payer))
+            .await?;
+
+        simulation
+            .simulation_details
+            .map(|details| ProgramLogs::parse(&self.program_id, &details.logs))
+            .ok_or(BanksClientError::ClientError(
+                "Simulation did not return any details",
+            ))
+    }
+
+    /// Build a transaction containing `instructions`, signed by `payer` and `signers` with the
+    /// last known blockhash. The oracle instructions are sent in the instruction layout of the
+    /// simulator.
+    fn sign_transaction(
+        &self,
+        instructions: &[Instruction],
+        signers: &Vec<&Keypair>,
+        payer: &Keypair,
+    ) -> Transaction {
+        let instructions: Vec<Instruction> = instructions
+            .iter()
+            .map(|instruction| self.encode_instruction(instruction))
+            .collect();
+        let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+
+        transaction.partial_sign(&[payer], self.last_blockhash);
+        transaction.partial_sign(signers, self.last_blockhash);
+        transaction
+    }
+
+    /// `instruction` in the instruction layout of the simulator if it is a valid instruction of the
+    /// oracle program, unchanged otherwise. Instructions invoked via CPI are never changed.
+    fn encode_instruction(&self, instruction: &Instruction) -> Instruction {
+        if self.instruction_layout == InstructionLayout::Legacy
+            && instruction.program_id == self.program_id
+        {
+            if let Ok(data) = OracleInstruction::unpack(&instruction.data)
+                .and_then(|oracle_instruction| oracle_instruction.pack_legacy())
+            {
+                return Instruction {
+                    data,
+                    ..instruction.clone()
+                };
+            }
+        }
+        instruction.clone()
+    }
+
+    /// Delete a price account from an existing product account (using the del_price instruction).
+    pub async fn del_price(
+        &mut self,
+        product_keypair: &Keypair,
+        price_keypair: &Keypair,
+    ) -> Result<(), BanksClientError> {
+        self.del_price_with_recipient(product_keypair, price_keypair, None)
+            .await
+    }
+
+    /// Delete a price account from an existing product account (using the del_price instruction)
+    /// and send its lamports to `recipient`, or to the funding account if `recipient` is `None`.
+    pub async fn del_price_with_recipient(
+        &mut self,
+        product_keypair: &Keypair,
+        price_keypair: &Keypair,
+        recipient: Option<Pubkey>,
+    ) -> Result<(), BanksClientError> {
+        let instruction = client::del_price(
+            &self.program_id,
+            &self.genesis_keypair.pubkey(),
+            &product_keypair.pubkey(),
+            &price_keypair.pubkey(),
+            recipient.as_ref(),
+        );
+
+        self.process_ixs(
+            &[instruction],
+            &vec![product_keypair, price_keypair],
+            &copy_keypair(&self.genesis_keypair),
+        )
+        .await
+    }
+
+    /// Update permissions (using the upd_permissions intruction) and return the pubkey of the
+    /// permissions account
     pub async fn upd_permissions(
         &mut self,
         cmd_args: UpdPermissionsArgs,
@@ -462,15 +2297,12 @@ impl PythSimulator {
     ) -> Result<Pubkey, BanksClientError> {
         let permissions_pubkey = self.get_permissions_pubkey();
 
-        let instruction = Instruction::new_with_bytes(
-            self.program_id,
-            bytes_of(&cmd_args),
-            vec![
-                AccountMeta::new(payer.pubkey(), true),
-                AccountMeta::new_readonly(self.programdata_id, false),
-                AccountMeta::new(permissions_pubkey, false),
-                AccountMeta::new_readonly(system_program::id(), false),
-            ],
+        let instruction = client::upd_permissions(
+            &self.program_id,
+            &payer.pubkey(),
+            cmd_args.master_authority,
+            cmd_args.data_curation_authority,
+            cmd_args.security_authority,
         );
 
         self.process_ixs(&[instruction], &vec![], payer)
@@ -478,6 +2310,40 @@ impl PythSimulator {
             .map(|_| permissions_pubkey)
     }
 
+    /// Turn the co-signing mode of the permissions account on or off (using the set_co_signing
+    /// instruction)
+    pub async fn set_co_signing(&mut self, enabled: bool) -> Result<(), BanksClientError> {
+        let instruction =
+            client::set_co_signing(&self.program_id, &self.upgrade_authority.pubkey(), enabled);
+
+        self.process_ixs(
+            &[instruction],
+            &vec![],
+            &copy_keypair(&self.upgrade_authority),
+        )
+        .await
+    }
+
+    /// Process `instruction`, a co-signed command sent by the genesis account, in a transaction
+    /// that also contains an instruction signed by `co_signer`: a transfer of 0 lamports from
+    /// `co_signer` to the genesis account. The instructions sysvar account is appended to
+    /// `instruction` (see `client::co_signed`).
+    pub async fn process_co_signed_ix(
+        &mut self,
+        instruction: Instruction,
+        co_signer: &Keypair,
+    ) -> Result<(), BanksClientError> {
+        let co_signing_instruction =
+            system_instruction::transfer(&co_signer.pubkey(), &self.genesis_keypair.pubkey(), 0);
+
+        self.process_ixs(
+            &[client::co_signed(instruction), co_signing_instruction],
+            &vec![co_signer],
+            &copy_keypair(&self.genesis_keypair),
+        )
+        .await
+    }
+
     /// Get the account at `key`. Returns `None` if no such account exists.
     pub async fn get_account(&mut self, key: Pubkey) -> Option<Account> {
         self.context.banks_client.get_account(key).await.unwrap()
@@ -497,6 +2363,83 @@ impl PythSimulator {
         account.owner == self.program_id
     }
 
+    /// Get the content of the Pyth account `key` as a `T`. Panics if the account is not an
+    /// initialized oracle account of type `T`.
+    pub async fn get_pyth_account<T: PythAccount>(&mut self, key: Pubkey) -> T {
+        let account = self
+            .get_account(key)
+            .await
+            .unwrap_or_else(|| panic!("account {key} does not exist"));
+        assert!(
+            self.is_owned_by_oracle(&account),
+            "account {key} is not owned by the oracle"
+        );
+        let header = load::<AccountHeader>(&account.data).unwrap();
+        assert!(
+            header.magic_number == PC_MAGIC
+                && header.version == PC_VERSION
+                && header.account_type == T::ACCOUNT_TYPE,
+            "account {key} is not an account of type {}",
+            T::ACCOUNT_TYPE
+        );
+        *load::<T>(&account.data).unwrap()
+    }
+
+    /// List the product accounts of the list of mapping accounts starting at `mapping`, in order.
+    /// Panics if the list contains an invalid account or the same account twice.
+    pub async fn list_products(&mut self, mapping: Pubkey) -> Vec<(Pubkey, ProductAccount)> {
+        let mut visited = HashSet::new();
+        let mut products = vec![];
+        let mut next_mapping = mapping;
+        while next_mapping != Pubkey::default() {
+            assert!(
+                visited.insert(next_mapping),
+                "mapping account {next_mapping} is listed twice"
+            );
+            let mapping_data = self.get_pyth_account::<MappingAccount>(next_mapping).await;
+            let number_of_products = mapping_data.number_of_products as usize;
+            assert!(
+                number_of_products <= mapping_data.products_list.len(),
+                "mapping account {next_mapping} has too many products"
+            );
+            for product in mapping_data.products_list[..number_of_products].iter() {
+                assert!(
+                    visited.insert(*product),
+                    "product account {product} is listed twice"
+                );
+                let product_data = self.get_pyth_account::<ProductAccount>(*product).await;
+                products.push((*product, product_data));
+            }
+            next_mapping = mapping_data.next_mapping_account;
+        }
+        products
+    }
+
+    /// List the price accounts of `product`, in order. Panics if the list contains an invalid
+    /// account, a price account of another product or the same account twice.
+    pub async fn list_prices(&mut self, product: Pubkey) -> Vec<(Pubkey, PriceAccount)> {
+        let mut visited = HashSet::new();
+        let mut prices = vec![];
+        let mut next_price = self
+            .get_pyth_account::<ProductAccount>(product)
+            .await
+            .first_price_account;
+        while next_price != Pubkey::default() {
+            assert!(
+                visited.insert(next_price),
+                "price account {next_price} is listed twice"
+            );
+            let price_data = self.get_pyth_account::<PriceAccount>(next_price).await;
+            assert_eq!(
+                price_data.product_account, product,
+                "price account {next_price} belongs to another product"
+            );
+            prices.push((next_price, price_data));
+            next_price = price_data.next_price_account;
+        }
+        prices
+    }
+
     pub async fn airdrop(&mut self, to: &Pubkey, lamports: u64) -> Result<(), BanksClientError> {
         let instruction =
             system_instruction::transfer(&self.genesis_keypair.pubkey(), to, lamports);
@@ -510,9 +2453,34 @@ impl PythSimulator {
     }
 
     pub fn get_permissions_pubkey(&self) -> Pubkey {
-        let (permissions_pubkey, __bump) =
-            Pubkey::find_program_address(&[PERMISSIONS_SEED.as_bytes()], &self.program_id);
-        permissions_pubkey
+        client::get_permissions_pubkey(&self.program_id)
+    }
+
+    pub fn get_treasury_pubkey(&self) -> Pubkey {
+        client::get_treasury_pubkey(&self.program_id)
+    }
+
+    pub fn get_mapping_pda(&self) -> Pubkey {
+        MappingAccount::find_pda(&self.program_id)
+    }
+
+    pub fn get_permission_grant_pubkey(
+        &self,
+        authority: &Pubkey,
+        command: OracleCommand,
+    ) -> Pubkey {
+        client::get_permission_grant_pubkey(&self.program_id, authority, command)
+    }
+
+    /// Program id of the oracle, to build instructions with `client`
+    pub fn program_id(&self) -> Pubkey {
+        self.program_id
+    }
+
+    /// The treasury and system program accounts paying the fees of price updates (see
+    /// `client::fee_accounts`)
+    pub fn fee_accounts(&self) -> [AccountMeta; 2] {
+        client::fee_accounts(&self.program_id)
     }
 
     /// Setup 3 product accounts with 1 price account each and add a publisher to all of them.
@@ -545,7 +2513,7 @@ impl PythSimulator {
             let product_keypair = self.add_product(&mapping_keypair).await.unwrap();
             let price_keypair = self.add_price(&product_keypair, -5).await.unwrap();
             for publisher in publishers.iter() {
-                self.add_publisher(&price_keypair, *publisher)
+                self.add_publisher(price_keypair.pubkey(), *publisher)
                     .await
                     .unwrap();
             }
@@ -554,10 +2522,346 @@ impl PythSimulator {
         price_accounts
     }
 
+    /// Setup a price account under a new product with `num_publishers` funded publishers. Returns
+    /// the price account keypair and the publisher keypairs, in the order they were added.
+    pub async fn setup_feed_with_publishers(
+        &mut self,
+        num_publishers: usize,
+    ) -> (Keypair, Vec<Keypair>) {
+        let mapping_keypair = self.init_mapping().await.unwrap();
+        let product_keypair = self.add_product(&mapping_keypair).await.unwrap();
+        let price_keypair = self.add_price(&product_keypair, -5).await.unwrap();
+
+        let publishers: Vec<Keypair> = (0..num_publishers).map(|_| Keypair::new()).collect();
+        for publisher in publishers.iter() {
+            self.airdrop(&publisher.pubkey(), 100 * LAMPORTS_PER_SOL)
+                .await
+                .unwrap();
+            self.add_publisher(price_keypair.pubkey(), publisher.pubkey())
+                .await
+                .unwrap();
+        }
+        (price_keypair, publishers)
+    }
+
+    /// Setup `num_feeds` price accounts, each under a new product, and add `publishers` to all of
+    /// them. The products are added to a new list of mapping accounts, which is extended (using
+    /// the add_mapping instruction) whenever its tail is full. The product and price accounts of a
+    /// feed are created and initialized in a single transaction, and its publishers are added in
+    /// batches, so that setting up many feeds stays fast. Returns the first mapping account and
+    /// the product and price accounts of the feeds, in order.
+    pub async fn create_feeds(
+        &mut self,
+        num_feeds: usize,
+        publishers: &[Pubkey],
+    ) -> (Keypair, Vec<(Pubkey, Pubkey)>) {
+        // Keeps the add_publisher transactions under the maximum transaction size
+        const PUBLISHERS_PER_TRANSACTION: usize = 16;
+
+        let mapping_keypair = self.init_mapping().await.unwrap();
+        let mut tail_keypair = copy_keypair(&mapping_keypair);
+        let mut num_tail_products = 0;
+        let mut feeds = Vec::with_capacity(num_feeds);
+        for _ in 0..num_feeds {
+            if num_tail_products == PC_MAP_TABLE_SIZE as usize {
+                tail_keypair = self.add_mapping(&tail_keypair).await.unwrap();
+                num_tail_products = 0;
+            }
+
+            let product_keypair = Keypair::new();
+            let price_keypair = Keypair::new();
+            let (product, price) = (product_keypair.pubkey(), price_keypair.pubkey());
+            let funding_account = self.genesis_keypair.pubkey();
+            let create_account = |account: &Pubkey, size: usize| {
+                system_instruction::create_account(
+                    &funding_account,
+                    account,
+                    Rent::minimum_balance(&Rent::default(), size),
+                    size as u64,
+                    &self.program_id,
+                )
+            };
+            let instructions = [
+                create_account(&product, PC_PROD_ACC_SIZE as usize),
+                client::add_product(
+                    &self.program_id,
+                    &funding_account,
+                    &tail_keypair.pubkey(),
+                    &product,
+                ),
+                create_account(&price, size_of::<PriceAccount>()),
+                client::add_price(
+                    &self.program_id,
+                    &funding_account,
+                    &product,
+                    &price,
+                    -5,
+                    PC_PTYPE_PRICE,
+                ),
+            ];
+            self.process_ixs(
+                &instructions,
+                &vec![&product_keypair, &price_keypair],
+                &copy_keypair(&self.genesis_keypair),
+            )
+            .await
+            .unwrap();
+            self.oracle_accounts.insert(product);
+            self.oracle_accounts.insert(price);
+
+            for batch in publishers.chunks(PUBLISHERS_PER_TRANSACTION) {
+                let instructions: Vec<Instruction> = batch
+                    .iter()
+                    .map(|publisher| {
+                        client::add_publisher(
+                            &self.program_id,
+                            &funding_account,
+                            &price,
+                            *publisher,
+                        )
+                    })
+                    .collect();
+                self.process_ixs(&instructions, &vec![], &copy_keypair(&self.genesis_keypair))
+                    .await
+                    .unwrap();
+            }
+
+            num_tail_products += 1;
+            feeds.push((product, price));
+        }
+        (mapping_keypair, feeds)
+    }
+
+    /// Publish `quotes[i]` from `publishers[i]` to a price account, in order and with one
+    /// upd_price transaction per publisher.
+    pub async fn publish_all(
+        &mut self,
+        price_account: Pubkey,
+        publishers: &[Keypair],
+        quotes: &[Quote],
+    ) -> Result<(), BanksClientError> {
+        assert_eq!(publishers.len(), quotes.len());
+        for (publisher, quote) in publishers.iter().zip(quotes.iter()) {
+            self.upd_price(publisher, price_account, *quote).await?;
+        }
+        Ok(())
+    }
+
     /// Advance clock to slot `slot`.
     pub async fn warp_to_slot(&mut self, slot: u64) -> Result<(), ProgramTestError> {
         self.context.warp_to_slot(slot)
     }
+
+    /// Advance clock by `n` slots.
+    pub async fn advance_slots(&mut self, n: u64) -> Result<(), BanksClientError> {
+        let slot = self.get_clock().await?.slot;
+        self.context
+            .warp_to_slot(slot + n)
+            .map_err(|_| BanksClientError::ClientError("Invalid warp slot"))
+    }
+
+    /// Set the unix timestamp of the sysvar clock, keeping the current slot.
+    pub async fn set_clock_timestamp(
+        &mut self,
+        unix_timestamp: i64,
+    ) -> Result<(), BanksClientError> {
+        let mut clock = self.get_clock().await?;
+        clock.unix_timestamp = unix_timestamp;
+        self.context.set_sysvar(&clock);
+        Ok(())
+    }
+
+    /// Get the current sysvar clock.
+    pub async fn get_clock(&mut self) -> Result<Clock, BanksClientError> {
+        self.context.banks_client.get_sysvar::<Clock>().await
+    }
+
+    /// Copy the state of the oracle, i.e. all the accounts owned by the oracle program that were
+    /// created through this simulator, including the permissions account. Together with
+    /// `restore`, this lets a test run several scenarios from the same state without setting it up
+    /// again. The clock and the accounts of other programs are not part of the snapshot.
+    pub async fn snapshot(&mut self) -> OracleSnapshot {
+        let mut accounts = HashMap::new();
+        for key in self.oracle_accounts.clone() {
+            if let Some(account) = self.get_account(key).await {
+                if self.is_owned_by_oracle(&account) {
+                    accounts.insert(key, account);
+                }
+            }
+        }
+        OracleSnapshot { accounts }
+    }
+
+    /// Restore the state of the oracle to `snapshot`. Accounts deleted since the snapshot are
+    /// recreated and accounts created since the snapshot are deleted.
+    pub fn restore(&mut self, snapshot: &OracleSnapshot) {
+        for key in self.oracle_accounts.iter() {
+            if !snapshot.accounts.contains_key(key) {
+                self.context.set_account(key, &AccountSharedData::default());
+                self.oracle_state.remove(key);
+            }
+        }
+        for (key, account) in snapshot.accounts.iter() {
+            self.context
+                .set_account(key, &AccountSharedData::from(account.clone()));
+            if !self.unchecked_accounts.contains(key) {
+                self.oracle_state.insert(*key, account.data.clone());
+            }
+        }
+        self.oracle_accounts.extend(snapshot.accounts.keys());
+    }
+
+    /// Set the account of `fixture` as an account of the oracle program. The owner of the dumped
+    /// account (e.g. the oracle program of mainnet) is replaced by the program id of the
+    /// simulator, so that the account can go through the instructions of the program under test.
+    pub fn load_oracle_account(&mut self, fixture: &AccountFixture) {
+        let account = Account {
+            owner: self.program_id,
+            ..fixture.account.clone()
+        };
+        self.context
+            .set_account(&fixture.pubkey, &AccountSharedData::from(account));
+        self.oracle_accounts.insert(fixture.pubkey);
+        self.unchecked_accounts.insert(fixture.pubkey);
+        self.oracle_state.remove(&fixture.pubkey);
+    }
+
+    /// Overwrite the data of the account `key` with `tamper` applied to it, e.g. to flip its
+    /// magic number, truncate it or scramble its pointers, as no instruction of the program would.
+    /// Panics if the account does not exist.
+    pub async fn tamper_account(&mut self, key: Pubkey, tamper: impl FnOnce(&mut Vec<u8>)) {
+        let mut account = self
+            .get_account(key)
+            .await
+            .unwrap_or_else(|| panic!("account {key} does not exist"));
+        tamper(&mut account.data);
+        self.context
+            .set_account(&key, &AccountSharedData::from(account));
+        self.unchecked_accounts.insert(key);
+        self.oracle_state.remove(&key);
+    }
+}
+
+impl Drop for PythSimulator {
+    fn drop(&mut self) {
+        // A failed test already panics, and panicking again would abort the tests
+        if !std::thread::panicking() {
+            self.check_invariants();
+        }
+    }
+}
+
+/// Entrypoint of the stub CPI caller program of the simulator. It invokes the program of its first
+/// account with its instruction data and the rest of its accounts, which keep their signer and
+/// writable flags.
+fn process_cpi_caller(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let (program, callee_accounts) = accounts
+        .split_first()
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let instruction = Instruction::new_with_bytes(
+        *program.key,
+        instruction_data,
+        callee_accounts
+            .iter()
+            .map(|account| AccountMeta {
+                pubkey:      *account.key,
+                is_signer:   account.is_signer,
+                is_writable: account.is_writable,
+            })
+            .collect(),
+    );
+    invoke(&instruction, accounts)
+}
+
+/// Message account of the mock Wormhole core bridge of the simulator, see `process_mock_wormhole`
+#[cfg(feature = "wormhole")]
+#[derive(Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct MockPostedMessage {
+    pub emitter:           Pubkey,
+    /// Number of messages posted by the emitter before this one
+    pub sequence:          u64,
+    pub nonce:             u32,
+    pub consistency_level: u8,
+    pub payload:           Vec<u8>,
+}
+
+/// Entrypoint of the mock Wormhole core bridge of the simulator, deployed at
+/// `WORMHOLE_PROGRAM_ID`. It only implements `PostMessageUnreliable`: like the bridge, it checks
+/// the signers and that the fee of the message was paid to the fee collector, then it increments
+/// the sequence of the emitter and stores the message as a `MockPostedMessage` in the message
+/// account, creating it the first time.
+#[cfg(feature = "wormhole")]
+fn process_mock_wormhole(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    // The accounts of `post_price_attestation`, the clock and the rent are unused
+    if accounts.len() < 9 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let (bridge, message, emitter, sequence) =
+        (&accounts[0], &accounts[1], &accounts[2], &accounts[3]);
+    let (payer, fee_collector, system_program) = (&accounts[4], &accounts[5], &accounts[8]);
+    let (instruction, nonce, payload, consistency_level) =
+        <(u8, u32, Vec<u8>, u8)>::try_from_slice(instruction_data)
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+    if instruction != 8 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    if !(message.is_signer && emitter.is_signer && payer.is_signer) {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // The fee collector must have received the fee since the last message
+    {
+        let mut bridge_data = bridge.try_borrow_mut_data()?;
+        let last_lamports = u64::from_le_bytes(bridge_data[4..12].try_into().unwrap());
+        let fee = u64::from_le_bytes(bridge_data[16..24].try_into().unwrap());
+        if fee_collector.lamports() < last_lamports + fee {
+            return Err(ProgramError::InsufficientFunds);
+        }
+        bridge_data[4..12].copy_from_slice(&fee_collector.lamports().to_le_bytes());
+    }
+
+    let sequence_number = {
+        let mut sequence_data = sequence.try_borrow_mut_data()?;
+        let sequence_number = u64::from_le_bytes(sequence_data[..8].try_into().unwrap());
+        sequence_data[..8].copy_from_slice(&(sequence_number + 1).to_le_bytes());
+        sequence_number
+    };
+
+    let posted_message = MockPostedMessage {
+        emitter: *emitter.key,
+        sequence: sequence_number,
+        nonce,
+        consistency_level,
+        payload,
+    }
+    .try_to_vec()
+    .unwrap();
+    if message.data_is_empty() {
+        invoke(
+            &system_instruction::create_account(
+                payer.key,
+                message.key,
+                Rent::default().minimum_balance(posted_message.len()),
+                posted_message.len() as u64,
+                program_id,
+            ),
+            &[payer.clone(), message.clone(), system_program.clone()],
+        )?;
+    } else if message.owner != program_id || message.data_len() != posted_message.len() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    message
+        .try_borrow_mut_data()?
+        .copy_from_slice(&posted_message);
+    Ok(())
 }
 
 pub fn copy_keypair(keypair: &Keypair) -> Keypair {
@@ -0,0 +1,75 @@
+use {
+    crate::{
+        accounts::MappingAccount,
+        c_oracle_header::PC_MAP_TABLE_SIZE,
+        tests::pyth_simulator::{
+            PythSimulator,
+            TransactionFailure,
+        },
+    },
+    solana_program::{
+        instruction::InstructionError,
+        pubkey::Pubkey,
+    },
+    solana_sdk::signer::Signer,
+};
+
+#[tokio::test]
+async fn test_add_mapping() {
+    let mut sim = PythSimulator::new().await;
+    let first_mapping_keypair = sim.init_mapping().await.unwrap();
+
+    // A mapping account can only be extended once it is full
+    sim.add_mapping(&first_mapping_keypair)
+        .await
+        .assert_fails_with(InstructionError::InvalidArgument);
+
+    let mut products = vec![];
+    for _ in 0..PC_MAP_TABLE_SIZE {
+        products.push(
+            sim.add_product(&first_mapping_keypair)
+                .await
+                .unwrap()
+                .pubkey(),
+        );
+    }
+    sim.add_product(&first_mapping_keypair)
+        .await
+        .assert_fails_with(InstructionError::InvalidArgument);
+
+    let second_mapping_keypair = sim.add_mapping(&first_mapping_keypair).await.unwrap();
+    for _ in 0..2 {
+        products.push(
+            sim.add_product(&second_mapping_keypair)
+                .await
+                .unwrap()
+                .pubkey(),
+        );
+    }
+
+    // The first mapping account already has a successor
+    sim.add_mapping(&first_mapping_keypair)
+        .await
+        .assert_fails_with(InstructionError::InvalidArgument);
+
+    assert_eq!(
+        sim.get_pyth_account::<MappingAccount>(first_mapping_keypair.pubkey())
+            .await
+            .next_mapping_account,
+        second_mapping_keypair.pubkey()
+    );
+    assert_eq!(
+        sim.get_pyth_account::<MappingAccount>(second_mapping_keypair.pubkey())
+            .await
+            .next_mapping_account,
+        Pubkey::default()
+    );
+
+    let listed_products: Vec<Pubkey> = sim
+        .list_products(first_mapping_keypair.pubkey())
+        .await
+        .into_iter()
+        .map(|(product, _)| product)
+        .collect();
+    assert_eq!(listed_products, products);
+}
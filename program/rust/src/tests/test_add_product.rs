@@ -99,7 +99,7 @@ fn test_add_product() {
     }
     assert!(account_has_key_values(&product_account, &[]).unwrap());
 
-    size = populate_instruction(&mut instruction_data, &["foo", "bar"]);
+    size = populate_instruction(&mut instruction_data, &["base", "bar"]);
     // Add product with metadata
     assert!(process_instruction(
         &program_id,
@@ -122,7 +122,7 @@ fn test_add_product() {
         );
         assert!(mapping_data.products_list[1] == *product_account_2.key);
     }
-    assert!(account_has_key_values(&product_account_2, &["foo", "bar"]).unwrap());
+    assert!(account_has_key_values(&product_account_2, &["base", "bar"]).unwrap());
 
     // invalid account size
     let product_key_3 = Pubkey::new_unique();
@@ -4,6 +4,7 @@ use {
             clear_account,
             PermissionAccount,
             PriceAccount,
+            PublisherStats,
             PythAccount,
         },
         c_oracle_header::{
@@ -13,18 +14,24 @@ use {
         deserialize::load_checked,
         instruction::{
             AddPublisherArgs,
+            DelPublisherArgs,
             OracleCommand,
         },
         processor::process_instruction,
         tests::test_utils::AccountSetup,
         OracleError,
     },
-    bytemuck::bytes_of,
+    bytemuck::{
+        bytes_of,
+        Zeroable,
+    },
+    quickcheck_macros::quickcheck,
     solana_program::{
         program_error::ProgramError,
         pubkey::Pubkey,
         rent::Rent,
     },
+    std::collections::BTreeSet,
 };
 
 #[test]
@@ -191,3 +198,96 @@ fn test_add_publisher() {
         }
     }
 }
+
+/// After any sequence of additions and deletions, the price components are the publishers added
+/// and not deleted since, sorted by key and without duplicates, and each publisher keeps its
+/// statistics. Adding a publisher twice or deleting a missing one fails without effect.
+#[quickcheck]
+fn test_add_del_publishers_sorted(operations: Vec<(bool, u8)>) {
+    let program_id = Pubkey::new_unique();
+
+    let mut funding_setup = AccountSetup::new_funding();
+    let funding_account = funding_setup.as_account_info();
+
+    let mut price_setup = AccountSetup::new::<PriceAccount>(&program_id);
+    let price_account = price_setup.as_account_info();
+    PriceAccount::initialize(&price_account, PC_VERSION).unwrap();
+    **price_account.try_borrow_mut_lamports().unwrap() =
+        Rent::minimum_balance(&Rent::default(), PriceAccount::MINIMUM_SIZE);
+
+    let mut permissions_setup = AccountSetup::new_permission(&program_id);
+    let permissions_account = permissions_setup.as_account_info();
+    PermissionAccount::initialize(&permissions_account, PC_VERSION)
+        .unwrap()
+        .master_authority = *funding_account.key;
+
+    let mut publishers = BTreeSet::new();
+    for (add, key) in operations {
+        // Draw the keys from a small set so that the sequence has duplicates, and tag the
+        // statistics of each publisher with its key
+        let key = key % 16 + 1;
+        let publisher = Pubkey::new_from_array([key; 32]);
+        let instruction_data = if add {
+            bytes_of(&AddPublisherArgs {
+                header: OracleCommand::AddPublisher.into(),
+                publisher,
+            })
+            .to_vec()
+        } else {
+            bytes_of(&DelPublisherArgs {
+                header: OracleCommand::DelPublisher.into(),
+                publisher,
+            })
+            .to_vec()
+        };
+        let result = process_instruction(
+            &program_id,
+            &[
+                funding_account.clone(),
+                price_account.clone(),
+                permissions_account.clone(),
+            ],
+            &instruction_data,
+        );
+
+        let changed = if add {
+            publishers.insert(publisher)
+        } else {
+            publishers.remove(&publisher)
+        };
+        if changed {
+            assert_eq!(result, Ok(()));
+        } else {
+            assert_eq!(result, Err(ProgramError::InvalidArgument));
+        }
+
+        let mut price_data = load_checked::<PriceAccount>(&price_account, PC_VERSION).unwrap();
+        if add && changed {
+            let index = price_data
+                .comp_
+                .iter()
+                .position(|component| component.pub_ == publisher)
+                .unwrap();
+            assert_eq!(price_data.publisher_stats_[index], PublisherStats::zeroed());
+            price_data.publisher_stats_[index].included_rounds_ = u32::from(key);
+        }
+
+        let num_comps = price_data.num_ as usize;
+        assert_eq!(
+            price_data.comp_[..num_comps]
+                .iter()
+                .map(|component| component.pub_)
+                .collect::<Vec<_>>(),
+            publishers.iter().copied().collect::<Vec<_>>()
+        );
+        for (component, stats) in price_data.comp_[..num_comps]
+            .iter()
+            .zip(price_data.publisher_stats_.iter())
+        {
+            assert_eq!(
+                stats.included_rounds_,
+                u32::from(component.pub_.to_bytes()[0])
+            );
+        }
+    }
+}
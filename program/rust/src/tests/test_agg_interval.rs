@@ -0,0 +1,105 @@
+use {
+    crate::{
+        accounts::{
+            AggInterval,
+            PriceAccount,
+        },
+        c_oracle_header::PC_STATUS_TRADING,
+        tests::pyth_simulator::{
+            PythSimulator,
+            Quote,
+            TransactionFailure,
+        },
+    },
+    bytemuck::pod_read_unaligned,
+    solana_program::{
+        instruction::InstructionError,
+        rent::Rent,
+    },
+    solana_sdk::signer::Signer,
+    std::mem::size_of,
+};
+
+fn quote(price: i64) -> Quote {
+    Quote {
+        price,
+        confidence: 1,
+        status: PC_STATUS_TRADING,
+    }
+}
+
+#[tokio::test]
+async fn test_agg_interval() {
+    let mut sim = PythSimulator::new().await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(2).await;
+    let price = price_keypair.pubkey();
+    sim.set_min_pub(price, 1).await.unwrap();
+
+    sim.set_agg_interval(price, 0)
+        .await
+        .assert_fails_with(InstructionError::InvalidArgument);
+
+    // The default interval doesn't need any room in the price account
+    let initial_size = sim.get_account(price).await.unwrap().data.len();
+    sim.set_agg_interval(price, 1).await.unwrap();
+    assert_eq!(
+        sim.get_account(price).await.unwrap().data.len(),
+        initial_size
+    );
+
+    // A longer interval resizes the price account
+    sim.set_agg_interval(price, 3).await.unwrap();
+    let price_account = sim.get_account(price).await.unwrap();
+    let size = initial_size + PriceAccount::extension_size::<AggInterval>();
+    assert_eq!(price_account.data.len(), size);
+    assert!(price_account.lamports >= Rent::default().minimum_balance(size));
+    let agg_interval = pod_read_unaligned::<AggInterval>(
+        PriceAccount::extension_data::<AggInterval>(
+            &price_account.data[size_of::<PriceAccount>()..],
+        )
+        .unwrap(),
+    );
+    assert_eq!(agg_interval.slots_, 3);
+
+    sim.warp_to_slot(3).await.unwrap();
+    sim.upd_price(&publishers[0], price, quote(100))
+        .await
+        .unwrap();
+    sim.upd_price(&publishers[1], price, quote(100))
+        .await
+        .unwrap();
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(price_data.agg_.pub_slot_, 3);
+
+    // The updates of the slots in between only update the components
+    for slot in 4..6 {
+        sim.warp_to_slot(slot).await.unwrap();
+        for publisher in &publishers {
+            sim.upd_price(publisher, price, quote(110)).await.unwrap();
+        }
+        let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+        assert_eq!(price_data.agg_.pub_slot_, 3);
+        for component in &price_data.comp_[..2] {
+            assert_eq!(component.latest_.pub_slot_, slot);
+        }
+    }
+
+    // The first update once the interval has passed aggregates the latest quotes
+    sim.warp_to_slot(6).await.unwrap();
+    sim.upd_price(&publishers[0], price, quote(120))
+        .await
+        .unwrap();
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(price_data.agg_.pub_slot_, 6);
+    assert_eq!(price_data.agg_.price_, 110);
+    assert_eq!(price_data.agg_.status_, PC_STATUS_TRADING);
+
+    // Going back to the default aggregates in every slot again
+    sim.set_agg_interval(price, 1).await.unwrap();
+    sim.warp_to_slot(7).await.unwrap();
+    sim.upd_price(&publishers[0], price, quote(110))
+        .await
+        .unwrap();
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(price_data.agg_.pub_slot_, 7);
+}
@@ -0,0 +1,121 @@
+use {
+    crate::{
+        accounts::PriceAccount,
+        aggregation::aggregation_leader,
+        c_oracle_header::PC_STATUS_TRADING,
+        tests::pyth_simulator::{
+            PythSimulator,
+            Quote,
+        },
+    },
+    solana_program::pubkey::Pubkey,
+    solana_sdk::{
+        signature::Keypair,
+        signer::Signer,
+    },
+};
+
+/// Warp to `slot` and publish the quotes of `publishers[i]` for each `i` of `order`, in order.
+/// Returns the index of the publisher whose update aggregated the price account, if any.
+async fn publish_round(
+    sim: &mut PythSimulator,
+    price: Pubkey,
+    publishers: &[Keypair],
+    order: &[usize],
+    slot: u64,
+) -> Option<usize> {
+    sim.warp_to_slot(slot).await.unwrap();
+    let mut aggregator = None;
+    for &i in order {
+        let quote = Quote {
+            price:      100 + 10 * i as i64,
+            confidence: 1,
+            status:     PC_STATUS_TRADING,
+        };
+        sim.upd_price(&publishers[i], price, quote).await.unwrap();
+        let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+        if aggregator.is_none() && price_data.agg_.pub_slot_ == slot {
+            aggregator = Some(i);
+        }
+    }
+    aggregator
+}
+
+#[tokio::test]
+async fn test_agg_rotation() {
+    let mut sim = PythSimulator::new().await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(3).await;
+    let price = price_keypair.pubkey();
+    sim.set_min_pub(price, 1).await.unwrap();
+    sim.set_agg_rotation(price, true).await.unwrap();
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(price_data.agg_rotation_, 1);
+
+    // Without recent contributors anyone can aggregate
+    assert_eq!(
+        publish_round(&mut sim, price, &publishers, &[0, 1, 2], 2).await,
+        Some(0)
+    );
+
+    // The leader rotates among the active publishers, whatever the order of the updates
+    let mut num_aggregations = [0; 3];
+    for slot in 3..9 {
+        let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+        let leader = aggregation_leader(&price_data, slot);
+        assert_eq!(leader, Some(slot as usize % 3));
+        assert_eq!(
+            publish_round(&mut sim, price, &publishers, &[0, 1, 2], slot).await,
+            leader
+        );
+        num_aggregations[leader.unwrap()] += 1;
+
+        // The leader aggregates the quotes published before it in the slot
+        let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+        assert_eq!(price_data.agg_.status_, PC_STATUS_TRADING);
+        assert_eq!(price_data.agg_.price_, 110);
+    }
+    assert_eq!(num_aggregations, [2, 2, 2]);
+
+    // Publisher 2 goes missing but is still active for a while. The slot it should aggregate is
+    // not aggregated, and anyone aggregates the next slot.
+    assert_eq!(
+        publish_round(&mut sim, price, &publishers, &[1, 0], 9).await,
+        Some(0)
+    );
+    assert_eq!(
+        publish_round(&mut sim, price, &publishers, &[1, 0], 10).await,
+        Some(1)
+    );
+    assert_eq!(
+        publish_round(&mut sim, price, &publishers, &[1, 0], 11).await,
+        None
+    );
+    assert_eq!(
+        publish_round(&mut sim, price, &publishers, &[1, 0], 12).await,
+        Some(1)
+    );
+
+    // Once it is inactive, the other publishers take turns
+    assert_eq!(
+        publish_round(&mut sim, price, &publishers, &[1, 0], 20).await,
+        Some(1)
+    );
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(aggregation_leader(&price_data, 21), Some(1));
+    assert_eq!(aggregation_leader(&price_data, 22), Some(0));
+    assert_eq!(
+        publish_round(&mut sim, price, &publishers, &[0, 1], 21).await,
+        Some(1)
+    );
+    assert_eq!(
+        publish_round(&mut sim, price, &publishers, &[1, 0], 22).await,
+        Some(0)
+    );
+
+    // Without rotation, the first update of the slot aggregates again
+    sim.set_agg_rotation(price, false).await.unwrap();
+    assert_eq!(
+        publish_round(&mut sim, price, &publishers, &[0, 1], 23).await,
+        Some(0)
+    );
+}
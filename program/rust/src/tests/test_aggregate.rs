@@ -0,0 +1,362 @@
+use {
+    crate::{
+        accounts::PriceAccount,
+        aggregation::{
+            agg_params,
+            aggregate,
+            upd_aggregate_with_weights,
+            AggParams,
+            Aggregate,
+            Quote,
+        },
+        c_oracle_header::{
+            PC_AGG_MODE_MID,
+            PC_AGG_MODE_WEIGHTED,
+            PC_NUM_COMP,
+            PC_STATUS_DEGRADED,
+            PC_STATUS_TRADING,
+            PC_STATUS_UNKNOWN,
+            PC_STATUS_UNRELIABLE,
+        },
+    },
+    bytemuck::Zeroable,
+    quickcheck::{
+        Arbitrary,
+        TestResult,
+    },
+    quickcheck_macros::quickcheck,
+};
+
+const CURRENT_SLOT: u64 = 1000;
+
+impl Arbitrary for Quote {
+    fn arbitrary(g: &mut quickcheck::Gen) -> Self {
+        Quote {
+            price:    i64::from(u16::arbitrary(g)) - 100,
+            conf:     u64::from(u8::arbitrary(g)),
+            status:   match u8::arbitrary(g) % 8 {
+                0 => PC_STATUS_UNKNOWN,
+                1 | 2 => PC_STATUS_UNRELIABLE,
+                _ => PC_STATUS_TRADING,
+            },
+            pub_slot: CURRENT_SLOT - u64::from(u8::arbitrary(g) % 32),
+            weight:   u32::from(u8::arbitrary(g) % 4),
+            pub_time: i64::from(u8::arbitrary(g) % 4) * 1000,
+            excluded: u8::arbitrary(g) % 8 == 0,
+        }
+    }
+}
+
+impl Arbitrary for AggParams {
+    fn arbitrary(g: &mut quickcheck::Gen) -> Self {
+        let (min_price, max_price) = if bool::arbitrary(g) {
+            let bound = i64::from(u16::arbitrary(g));
+            (bound / 2, bound)
+        } else {
+            (0, 0)
+        };
+        AggParams {
+            min_pub: u8::arbitrary(g) % 4,
+            max_latency: i64::from(u8::arbitrary(g) % 30 + 1),
+            agg_mode: u8::arbitrary(g) % (PC_AGG_MODE_MID as u8 + 1),
+            outlier_band: u16::from(u8::arbitrary(g) % 8),
+            prev_aggregate: if bool::arbitrary(g) {
+                Some((
+                    i64::from(u16::arbitrary(g)),
+                    u64::from(u8::arbitrary(g)) * 16,
+                ))
+            } else {
+                None
+            },
+            min_price,
+            max_price,
+        }
+    }
+}
+
+/// A price account with `quotes` as the latest quotes and the configuration `params`, as it is
+/// before the aggregation of `CURRENT_SLOT + 1`
+fn price_account_with(quotes: &[Quote], params: &AggParams) -> PriceAccount {
+    let mut price_account = PriceAccount::zeroed();
+    price_account.num_ = quotes.len() as u32;
+    price_account.min_pub_ = params.min_pub;
+    price_account.max_latency_ = params.max_latency as u8;
+    price_account.agg_mode_ = params.agg_mode;
+    price_account.outlier_band_ = params.outlier_band;
+    price_account.min_price_ = params.min_price;
+    price_account.max_price_ = params.max_price;
+    if let Some((prev_price, prev_conf)) = params.prev_aggregate {
+        price_account.prev_slot_ = CURRENT_SLOT - 1;
+        price_account.prev_price_ = prev_price;
+        price_account.prev_conf_ = prev_conf;
+    }
+    for ((component, stats), quote) in price_account
+        .comp_
+        .iter_mut()
+        .zip(price_account.publisher_stats_.iter_mut())
+        .zip(quotes.iter())
+    {
+        component.latest_.price_ = quote.price;
+        component.latest_.conf_ = quote.conf;
+        component.latest_.status_ = quote.status;
+        component.latest_.pub_slot_ = quote.pub_slot;
+        stats.publish_time_ = quote.pub_time;
+        stats.excluded_ = u8::from(quote.excluded);
+    }
+    price_account
+}
+
+/// The aggregation of a price account gives the result of `aggregate` on its quotes.
+#[quickcheck]
+fn test_aggregate_matches_upd_aggregate(quotes: Vec<Quote>, params: AggParams) -> TestResult {
+    if quotes.len() > PC_NUM_COMP as usize {
+        return TestResult::discard();
+    }
+
+    let mut price_account = price_account_with(&quotes, &params);
+    assert_eq!(agg_params(&price_account), params);
+
+    let expected = aggregate(&quotes, CURRENT_SLOT + 1, &params);
+    let mut weights = [0; PC_NUM_COMP as usize];
+    for (weight, quote) in weights.iter_mut().zip(quotes.iter()) {
+        *weight = quote.weight;
+    }
+    let updated = upd_aggregate_with_weights(&mut price_account, &weights, CURRENT_SLOT + 1, 0);
+
+    let (price_conf, pub_time) = if updated {
+        (
+            Some((price_account.agg_.price_, price_account.agg_.conf_)),
+            price_account.agg_pub_time_,
+        )
+    } else {
+        (None, 0)
+    };
+    let degraded = price_account.agg_.status_ == PC_STATUS_DEGRADED;
+    let fast_price_conf = if price_account.fast_agg_.status_ == PC_STATUS_TRADING {
+        Some((
+            price_account.fast_agg_.price_,
+            price_account.fast_agg_.conf_,
+        ))
+    } else {
+        None
+    };
+    TestResult::from_bool(
+        expected
+            == Aggregate {
+                price_conf,
+                num_qt: price_account.num_qt_,
+                fast_price_conf,
+                fast_num_qt: price_account.fast_agg_.num_qt_,
+                pub_time,
+                degraded,
+            },
+    )
+}
+
+/// The quotes are independent of their order.
+#[quickcheck]
+fn test_aggregate_order_independent(quotes: Vec<Quote>, params: AggParams) -> TestResult {
+    if quotes.len() > PC_NUM_COMP as usize {
+        return TestResult::discard();
+    }
+
+    let mut reversed = quotes.clone();
+    reversed.reverse();
+    TestResult::from_bool(
+        aggregate(&quotes, CURRENT_SLOT + 1, &params)
+            == aggregate(&reversed, CURRENT_SLOT + 1, &params),
+    )
+}
+
+fn trading_quote(price: i64, conf: u64, pub_slot: u64) -> Quote {
+    Quote {
+        price,
+        conf,
+        status: PC_STATUS_TRADING,
+        pub_slot,
+        weight: 0,
+        pub_time: 0,
+        excluded: false,
+    }
+}
+
+#[test]
+fn test_aggregate() {
+    let params = AggParams {
+        min_pub: 2,
+        max_latency: 25,
+        ..AggParams::default()
+    };
+    let quotes = [
+        trading_quote(100, 2, CURRENT_SLOT),
+        trading_quote(102, 2, CURRENT_SLOT),
+        trading_quote(110, 4, CURRENT_SLOT - 10),
+        trading_quote(500, 4, CURRENT_SLOT - 30),
+    ];
+
+    // The last quote is stale
+    assert_eq!(
+        aggregate(&quotes, CURRENT_SLOT + 1, &params),
+        Aggregate {
+            price_conf:      Some((102, 4)),
+            num_qt:          3,
+            fast_price_conf: Some((101, 1)),
+            fast_num_qt:     2,
+            pub_time:        0,
+            degraded:        false,
+        }
+    );
+
+    // Too few quotes for the aggregate, but not for the fast aggregate
+    assert_eq!(
+        aggregate(
+            &quotes,
+            CURRENT_SLOT + 1,
+            &AggParams {
+                min_pub: 4,
+                ..params
+            }
+        ),
+        Aggregate {
+            price_conf:      None,
+            num_qt:          3,
+            fast_price_conf: Some((101, 1)),
+            fast_num_qt:     2,
+            pub_time:        0,
+            degraded:        false,
+        }
+    );
+
+    // Weights only matter in the weighted mode
+    let mut weighted_quotes = quotes;
+    weighted_quotes[2].weight = 10;
+    assert_eq!(
+        aggregate(&weighted_quotes, CURRENT_SLOT + 1, &params),
+        aggregate(&quotes, CURRENT_SLOT + 1, &params)
+    );
+    let weighted = aggregate(
+        &weighted_quotes,
+        CURRENT_SLOT + 1,
+        &AggParams {
+            agg_mode: PC_AGG_MODE_WEIGHTED as u8,
+            ..params
+        },
+    );
+    assert_eq!(weighted.price_conf, Some((110, 4)));
+
+    // The publish time of the aggregate is the earliest of the included quotes that have one
+    let mut timed_quotes = quotes;
+    timed_quotes[0].pub_time = 1_700_000_002;
+    timed_quotes[2].pub_time = 1_700_000_001;
+    timed_quotes[3].pub_time = 1_600_000_000;
+    assert_eq!(
+        aggregate(&timed_quotes, CURRENT_SLOT + 1, &params).pub_time,
+        1_700_000_001
+    );
+
+    // Excluded quotes are ignored
+    let mut excluded_quotes = quotes;
+    excluded_quotes[2].excluded = true;
+    assert_eq!(
+        aggregate(&excluded_quotes, CURRENT_SLOT + 1, &params),
+        aggregate(
+            &[quotes[0], quotes[1], quotes[3]],
+            CURRENT_SLOT + 1,
+            &params
+        )
+    );
+
+    // A majority of unreliable quotes degrades the aggregate, stale ones don't count
+    let mut unreliable_quotes = quotes;
+    unreliable_quotes[0].status = PC_STATUS_UNRELIABLE;
+    unreliable_quotes[3].status = PC_STATUS_UNRELIABLE;
+    assert_eq!(
+        aggregate(&unreliable_quotes, CURRENT_SLOT + 1, &params),
+        aggregate(&quotes, CURRENT_SLOT + 1, &params)
+    );
+    unreliable_quotes[2].status = PC_STATUS_UNRELIABLE;
+    let degraded = aggregate(&unreliable_quotes, CURRENT_SLOT + 1, &params);
+    assert!(degraded.degraded);
+    assert_eq!(degraded.price_conf, Some((102, 4)));
+
+    // Without quotes
+    assert_eq!(
+        aggregate(&[], CURRENT_SLOT + 1, &params),
+        Aggregate {
+            price_conf:      None,
+            num_qt:          0,
+            fast_price_conf: None,
+            fast_num_qt:     0,
+            pub_time:        0,
+            degraded:        false,
+        }
+    );
+}
+
+/// Quotes at the limits of the i64 range aggregate without overflowing
+#[test]
+fn test_aggregate_extreme_quotes() {
+    let params = AggParams {
+        min_pub: 1,
+        max_latency: 25,
+        ..AggParams::default()
+    };
+    let low = trading_quote(-1, i64::MAX as u64, CURRENT_SLOT);
+    let high = trading_quote(i64::MAX - 1, 1, CURRENT_SLOT);
+
+    // The quotes contribute i64::MIN, -1 and i64::MAX - 1, and i64::MAX - 2, i64::MAX - 1 and
+    // i64::MAX to the price model. The left confidence spans almost the whole i64 range.
+    let quotes = [low, low, high, high];
+    assert_eq!(
+        aggregate(&quotes, CURRENT_SLOT + 1, &params),
+        Aggregate {
+            price_conf:      Some((i64::MAX - 2, i64::MAX as u64 - 1)),
+            num_qt:          4,
+            fast_price_conf: Some((i64::MAX - 2, i64::MAX as u64 - 1)),
+            fast_num_qt:     4,
+            pub_time:        0,
+            degraded:        false,
+        }
+    );
+
+    // Maximal weights don't overflow the total weight
+    let weighted_quotes = quotes.map(|quote| Quote {
+        weight: u32::MAX,
+        ..quote
+    });
+    let weighted = aggregate(
+        &weighted_quotes,
+        CURRENT_SLOT + 1,
+        &AggParams {
+            agg_mode: PC_AGG_MODE_WEIGHTED as u8,
+            ..params
+        },
+    );
+    assert_eq!(
+        weighted.price_conf,
+        Some((i64::MAX - 2, i64::MAX as u64 - 1))
+    );
+
+    // Quotes whose price +/- conf doesn't fit in an i64 are excluded
+    let invalid_quotes = [
+        trading_quote(i64::MAX, 1, CURRENT_SLOT),
+        trading_quote(i64::MIN, 1, CURRENT_SLOT),
+        trading_quote(0, i64::MAX as u64 + 1, CURRENT_SLOT),
+        trading_quote(i64::MIN + 1, 1, CURRENT_SLOT),
+    ];
+    let result = aggregate(&invalid_quotes, CURRENT_SLOT + 1, &params);
+    assert_eq!(result.num_qt, 1);
+    assert_eq!(result.price_conf, Some((i64::MIN + 1, 1)));
+
+    // The outlier band of an extreme previous aggregate includes every quote
+    let banded = aggregate(
+        &quotes,
+        CURRENT_SLOT + 1,
+        &AggParams {
+            outlier_band: u16::MAX,
+            prev_aggregate: Some((i64::MIN, u64::MAX)),
+            ..params
+        },
+    );
+    assert_eq!(banded.num_qt, 4);
+}
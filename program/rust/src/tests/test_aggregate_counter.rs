@@ -0,0 +1,77 @@
+use {
+    crate::{
+        accounts::PriceAccount,
+        c_oracle_header::PC_STATUS_TRADING,
+        tests::pyth_simulator::{
+            PythSimulator,
+            Quote,
+        },
+    },
+    solana_program::pubkey::Pubkey,
+    solana_sdk::signer::Signer,
+};
+
+fn quote(price: i64) -> Quote {
+    Quote {
+        price,
+        confidence: 1,
+        status: PC_STATUS_TRADING,
+    }
+}
+
+async fn aggregate_counter(sim: &mut PythSimulator, price: Pubkey) -> u64 {
+    sim.get_pyth_account::<PriceAccount>(price)
+        .await
+        .get_aggregate_counter()
+}
+
+/// The aggregate counter is incremented on each successful aggregate, and only then
+#[tokio::test]
+async fn test_aggregate_counter() {
+    let mut sim = PythSimulator::new().await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(2).await;
+    let price = price_keypair.pubkey();
+    sim.set_min_pub(price, 1).await.unwrap();
+    assert_eq!(aggregate_counter(&mut sim, price).await, 0);
+
+    // The first aggregation has no quote to aggregate
+    sim.warp_to_slot(2).await.unwrap();
+    sim.upd_price(&publishers[0], price, quote(100))
+        .await
+        .unwrap();
+    assert_eq!(aggregate_counter(&mut sim, price).await, 0);
+
+    sim.warp_to_slot(3).await.unwrap();
+    sim.upd_price(&publishers[0], price, quote(100))
+        .await
+        .unwrap();
+    assert_eq!(aggregate_counter(&mut sim, price).await, 1);
+
+    // Only the first price update of a slot aggregates
+    sim.upd_price(&publishers[1], price, quote(100))
+        .await
+        .unwrap();
+    assert_eq!(aggregate_counter(&mut sim, price).await, 1);
+
+    // A new aggregate counts even if the price didn't change
+    sim.warp_to_slot(4).await.unwrap();
+    sim.upd_price(&publishers[0], price, quote(100))
+        .await
+        .unwrap();
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(price_data.agg_.price_, 100);
+    assert_eq!(price_data.get_aggregate_counter(), 2);
+
+    // A failed aggregation of stale quotes doesn't count
+    sim.warp_to_slot(100).await.unwrap();
+    sim.upd_price(&publishers[0], price, quote(100))
+        .await
+        .unwrap();
+    assert_eq!(aggregate_counter(&mut sim, price).await, 2);
+
+    sim.warp_to_slot(101).await.unwrap();
+    sim.upd_price(&publishers[0], price, quote(100))
+        .await
+        .unwrap();
+    assert_eq!(aggregate_counter(&mut sim, price).await, 3);
+}
@@ -0,0 +1,139 @@
+use {
+    crate::{
+        accounts::PriceAccount,
+        c_oracle_header::PC_STATUS_TRADING,
+        client::parse_aggregate_return_data,
+        events::AggregatePriceUpdate,
+        tests::pyth_simulator::{
+            PythSimulator,
+            Quote,
+        },
+    },
+    solana_program::{
+        instruction::Instruction,
+        pubkey::Pubkey,
+    },
+    solana_sdk::{
+        signature::Keypair,
+        signer::Signer,
+    },
+    std::collections::HashMap,
+};
+
+fn quote(price: i64) -> Quote {
+    Quote {
+        price,
+        confidence: 1,
+        status: PC_STATUS_TRADING,
+    }
+}
+
+/// The aggregate of the price account, as returned by the instruction that computed it
+async fn aggregate(sim: &mut PythSimulator, price: Pubkey) -> AggregatePriceUpdate {
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    AggregatePriceUpdate {
+        price_account:    price,
+        slot:             price_data.agg_.pub_slot_,
+        price:            price_data.agg_.price_,
+        conf:             price_data.agg_.conf_,
+        status:           price_data.agg_.status_,
+        num_contributors: price_data.num_qt_,
+    }
+}
+
+async fn upd_price(
+    sim: &mut PythSimulator,
+    publisher: &Keypair,
+    price: Pubkey,
+    price_quote: Quote,
+) -> Option<AggregatePriceUpdate> {
+    let instructions = sim
+        .upd_price_instructions(
+            publisher,
+            &HashMap::from([("".to_string(), price)]),
+            &HashMap::from([("".to_string(), price_quote)]),
+        )
+        .await
+        .unwrap();
+    process(sim, publisher, &instructions).await
+}
+
+async fn process(
+    sim: &mut PythSimulator,
+    publisher: &Keypair,
+    instructions: &[Instruction],
+) -> Option<AggregatePriceUpdate> {
+    sim.process_ixs_returning_aggregate(instructions, &vec![publisher], publisher)
+        .await
+        .unwrap()
+}
+
+/// The instructions that aggregate send the new aggregate as return data
+#[tokio::test]
+async fn test_aggregate_return_data() {
+    let mut sim = PythSimulator::new_bpf().await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(2).await;
+    let price = price_keypair.pubkey();
+    sim.set_min_pub(price, 1).await.unwrap();
+
+    // The first aggregation has no quote to aggregate
+    sim.warp_to_slot(2).await.unwrap();
+    assert_eq!(
+        upd_price(&mut sim, &publishers[0], price, quote(100)).await,
+        None
+    );
+
+    sim.warp_to_slot(3).await.unwrap();
+    let returned = upd_price(&mut sim, &publishers[0], price, quote(110))
+        .await
+        .unwrap();
+    assert_eq!(returned.price, 100);
+    assert_eq!(returned.num_contributors, 1);
+    assert_eq!(returned, aggregate(&mut sim, price).await);
+
+    // Only the first price update of a slot aggregates
+    assert_eq!(
+        upd_price(&mut sim, &publishers[1], price, quote(120)).await,
+        None
+    );
+
+    // upd_price_batch returns the aggregate too
+    sim.warp_to_slot(4).await.unwrap();
+    let instruction = sim
+        .upd_price_batch_instruction(
+            &publishers[0],
+            &HashMap::from([("".to_string(), price)]),
+            &HashMap::from([("".to_string(), quote(130))]),
+        )
+        .await
+        .unwrap();
+    let returned = process(&mut sim, &publishers[0], &[instruction])
+        .await
+        .unwrap();
+    assert_eq!(returned.num_contributors, 2);
+    assert_eq!(returned, aggregate(&mut sim, price).await);
+
+    // So does an update invoked by another program, which can read it after the CPI
+    sim.warp_to_slot(5).await.unwrap();
+    let instruction = sim
+        .upd_price_via_cpi_instruction(&publishers[1], price, quote(140), false)
+        .await
+        .unwrap();
+    let returned = process(&mut sim, &publishers[1], &[instruction])
+        .await
+        .unwrap();
+    assert_eq!(returned, aggregate(&mut sim, price).await);
+
+    // A failed aggregation of stale quotes returns nothing
+    sim.warp_to_slot(100).await.unwrap();
+    assert_eq!(
+        upd_price(&mut sim, &publishers[0], price, quote(100)).await,
+        None
+    );
+
+    // Other return data isn't an aggregate
+    assert_eq!(
+        parse_aggregate_return_data(&returned.price.to_le_bytes()),
+        None
+    );
+}
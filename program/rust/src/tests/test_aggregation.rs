@@ -1,7 +1,7 @@
 use {
     crate::{
         accounts::PriceAccount,
-        processor::c_upd_aggregate,
+        aggregation::upd_aggregate,
     },
     bytemuck::Zeroable,
     serde::{
@@ -46,13 +46,7 @@ fn test_quote_set(input_path_raw: &str) {
         current_component.latest_.pub_slot_ = ((current_slot as i64) + slot_diff) as u64;
     }
 
-    unsafe {
-        c_upd_aggregate(
-            (&mut price_account as *mut PriceAccount) as *mut u8,
-            current_slot + 1,
-            current_timestamp,
-        );
-    }
+    upd_aggregate(&mut price_account, current_slot + 1, current_timestamp);
 
     // For some idiotic reason the status in the input is a number and the output is a string.
     let result_status: String = match price_account.agg_.status_ {
@@ -0,0 +1,203 @@
+use {
+    crate::{
+        accounts::PriceAccount,
+        aggregation::{
+            aggregation_algorithm,
+            Quote,
+        },
+        c_oracle_header::{
+            PC_AGG_MODE_DEFAULT,
+            PC_AGG_MODE_MID,
+            PC_AGG_MODE_TRIMMED_MEAN,
+            PC_AGG_MODE_WEIGHTED,
+            PC_NUM_COMP,
+            PC_STATUS_TRADING,
+        },
+        tests::pyth_simulator::{
+            self,
+            PythSimulator,
+        },
+    },
+    quickcheck::TestResult,
+    quickcheck_macros::quickcheck,
+    solana_program::program_error::ProgramError,
+    solana_sdk::signer::Signer,
+};
+
+const MODES: [u32; 4] = [
+    PC_AGG_MODE_DEFAULT,
+    PC_AGG_MODE_WEIGHTED,
+    PC_AGG_MODE_TRIMMED_MEAN,
+    PC_AGG_MODE_MID,
+];
+
+/// Quotes of 5 publishers, the last one of which is an outlier
+const PRICES_CONFS: [(i64, u64); 5] = [(100, 2), (102, 2), (104, 4), (110, 10), (200, 2)];
+
+fn quotes() -> Vec<Quote> {
+    PRICES_CONFS
+        .iter()
+        .map(|(price, conf)| Quote {
+            price: *price,
+            conf: *conf,
+            status: PC_STATUS_TRADING,
+            ..Quote::default()
+        })
+        .collect()
+}
+
+/// Aggregate `quotes` with the algorithm of `agg_mode` and the weights `weights`
+fn aggregate_with(agg_mode: u32, quotes: &[Quote], weights: &[u32]) -> Option<(i64, u64)> {
+    aggregation_algorithm(agg_mode as u8)
+        .unwrap()
+        .aggregate(quotes, weights)
+}
+
+#[test]
+fn test_aggregation_algorithms() {
+    let quotes = quotes();
+    let uniform_weights = [1; 5];
+
+    // Each algorithm on the same quotes
+    assert_eq!(
+        MODES.map(|mode| aggregate_with(mode, &quotes, &uniform_weights)),
+        [
+            Some((104, 16)),
+            Some((104, 16)),
+            // The outlier is trimmed, the mean price of 105.33 is rounded down and the mean
+            // confidence of 5.33 up
+            Some((105, 6)),
+            // The outlier moves the midpoint of the quotes
+            Some((150, 52)),
+        ]
+    );
+
+    // Only the weighted median depends on the weights
+    let weights = [1, 1, 1, 1, 3];
+    assert_eq!(
+        MODES.map(|mode| aggregate_with(mode, &quotes, &weights)),
+        [
+            Some((104, 16)),
+            Some((110, 90)),
+            Some((105, 6)),
+            Some((150, 52)),
+        ]
+    );
+
+    // Without the outlier
+    let weights = [1, 1, 1, 1, 0];
+    assert_eq!(
+        MODES.map(|mode| aggregate_with(mode, &quotes, &weights)),
+        [
+            Some((102, 2)),
+            Some((102, 2)),
+            Some((103, 3)),
+            Some((105, 15)),
+        ]
+    );
+
+    // Without quotes
+    for mode in MODES {
+        assert_eq!(aggregate_with(mode, &quotes, &[0; 5]), None);
+    }
+
+    // Unknown modes don't have an algorithm
+    assert!(aggregation_algorithm(PC_AGG_MODE_MID as u8 + 1).is_none());
+}
+
+/// Every algorithm agrees on identical quotes, and gives an aggregate within the confidence
+/// intervals of the included quotes
+#[quickcheck]
+fn test_aggregation_algorithms_bounds(prices_confs_weights: Vec<(i16, u8, u8)>) -> TestResult {
+    let num_quotes = prices_confs_weights.len().min(PC_NUM_COMP as usize);
+    if num_quotes == 0 {
+        return TestResult::discard();
+    }
+    let mut quotes = vec![];
+    let mut weights = vec![];
+    for (price, conf, weight) in &prices_confs_weights[..num_quotes] {
+        quotes.push(Quote {
+            price: i64::from(*price),
+            conf: u64::from(*conf) + 1,
+            status: PC_STATUS_TRADING,
+            ..Quote::default()
+        });
+        weights.push(u32::from(*weight % 4));
+    }
+    let included = || {
+        quotes
+            .iter()
+            .zip(weights.iter())
+            .filter(|(_, weight)| **weight != 0)
+            .map(|(quote, _)| quote)
+    };
+    let lowest = included()
+        .map(|quote| quote.price - quote.conf as i64)
+        .min();
+    let highest = included()
+        .map(|quote| quote.price + quote.conf as i64)
+        .max();
+
+    for mode in MODES {
+        let result = aggregate_with(mode, &quotes, &weights);
+        match (lowest, highest) {
+            (Some(lowest), Some(highest)) => {
+                let (price, conf) = result.unwrap();
+                if !(lowest <= price && price <= highest && conf > 0) {
+                    return TestResult::failed();
+                }
+            }
+            _ => {
+                if result.is_some() {
+                    return TestResult::failed();
+                }
+            }
+        }
+
+        let identical_quotes = vec![quotes[0]; num_quotes];
+        if aggregate_with(mode, &identical_quotes, &vec![1; num_quotes])
+            != Some((quotes[0].price, quotes[0].conf))
+        {
+            return TestResult::failed();
+        }
+    }
+    TestResult::passed()
+}
+
+/// The aggregation mode set by the authority selects the algorithm of the aggregation
+#[tokio::test]
+async fn test_set_agg_mode() {
+    let mut sim = PythSimulator::new().await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(5).await;
+    let price = price_keypair.pubkey();
+    sim.set_min_pub(price, 1).await.unwrap();
+    let sim_quotes = PRICES_CONFS.map(|(price, conf)| pyth_simulator::Quote {
+        price,
+        confidence: conf,
+        status: PC_STATUS_TRADING,
+    });
+    sim.publish_all(price, &publishers, &sim_quotes)
+        .await
+        .unwrap();
+
+    let quotes = quotes();
+    for (i, mode) in MODES.into_iter().enumerate() {
+        sim.set_agg_mode(price, mode as u8).await.unwrap();
+        sim.warp_to_slot(2 + i as u64).await.unwrap();
+        sim.publish_all(price, &publishers, &sim_quotes)
+            .await
+            .unwrap();
+
+        let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+        assert_eq!(u32::from(price_data.agg_mode_), mode);
+        assert_eq!(price_data.agg_.status_, PC_STATUS_TRADING);
+        assert_eq!(
+            Some((price_data.agg_.price_, price_data.agg_.conf_)),
+            aggregate_with(mode, &quotes, &[1; 5])
+        );
+    }
+
+    sim.set_agg_mode(price, PC_AGG_MODE_MID as u8 + 1)
+        .await
+        .assert_fails_with(ProgramError::InvalidArgument);
+}
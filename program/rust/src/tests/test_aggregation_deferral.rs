@@ -0,0 +1,201 @@
+use {
+    crate::{
+        accounts::PriceAccount,
+        aggregation::{
+            aggregation_compute_units,
+            AGGREGATION_MAX_COMPUTE_UNITS,
+            AGGREGATION_MIN_COMPUTE_UNITS,
+        },
+        c_oracle_header::{
+            PC_NUM_COMP,
+            PC_STATUS_TRADING,
+        },
+        events::AggregationDeferred,
+        tests::pyth_simulator::{
+            PythSimulator,
+            Quote,
+        },
+    },
+    bytemuck::Zeroable,
+    solana_program::{
+        instruction::Instruction,
+        pubkey::Pubkey,
+    },
+    solana_sdk::{
+        compute_budget::ComputeBudgetInstruction,
+        signature::Keypair,
+        signer::Signer,
+    },
+};
+
+fn quote(price: i64) -> Quote {
+    Quote {
+        price,
+        confidence: 1,
+        status: PC_STATUS_TRADING,
+    }
+}
+
+async fn instruction(
+    sim: &mut PythSimulator,
+    publisher: &Keypair,
+    price: Pubkey,
+    remaining_compute_units: u32,
+) -> Instruction {
+    sim.upd_price_with_budget_instruction(publisher, price, quote(120), remaining_compute_units)
+        .await
+        .unwrap()
+}
+
+/// The deferrals logged while processing `instructions` sent by `publisher`
+async fn process(
+    sim: &mut PythSimulator,
+    publisher: &Keypair,
+    instructions: &[Instruction],
+) -> Vec<AggregationDeferred> {
+    let logs = sim
+        .simulate_program_logs(instructions, &vec![publisher], publisher)
+        .await
+        .unwrap();
+    assert_eq!(logs.failure, None);
+    sim.process_ixs(instructions, &vec![publisher], publisher)
+        .await
+        .unwrap();
+    logs.events::<AggregationDeferred>()
+}
+
+#[tokio::test]
+async fn test_aggregation_deferral() {
+    let mut sim = PythSimulator::new_bpf().await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(2).await;
+    let price = price_keypair.pubkey();
+    sim.set_min_pub(price, 1).await.unwrap();
+    sim.warp_to_slot(2).await.unwrap();
+    sim.publish_all(price, &publishers, &[quote(100), quote(110)])
+        .await
+        .unwrap();
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    let required_compute_units = aggregation_compute_units(&price_data);
+    assert_eq!(price_data.agg_.pub_slot_, 2);
+
+    // Without enough compute units, the first update of the slot only updates its component
+    sim.warp_to_slot(3).await.unwrap();
+    let upd_price = instruction(&mut sim, &publishers[0], price, 1_000).await;
+    assert_eq!(
+        process(&mut sim, &publishers[0], &[upd_price]).await,
+        vec![AggregationDeferred {
+            price_account: price,
+            slot: 3,
+            remaining_compute_units: 1_000,
+            required_compute_units,
+        }]
+    );
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(price_data.agg_.pub_slot_, 2);
+    assert_eq!(price_data.status_change_.agg_deferred_, 1);
+    let component = price_data
+        .comp_
+        .iter()
+        .find(|component| component.pub_ == publishers[0].pubkey())
+        .unwrap();
+    assert_eq!(component.latest_.price_, 120);
+    assert_eq!(component.latest_.pub_slot_, 3);
+
+    // The next update of the slot aggregates
+    sim.upd_price(&publishers[1], price, quote(130))
+        .await
+        .unwrap();
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(price_data.agg_.pub_slot_, 3);
+    assert_eq!(price_data.agg_.status_, PC_STATUS_TRADING);
+    assert_eq!(price_data.status_change_.agg_deferred_, 0);
+
+    // Updates with enough compute units left aggregate
+    sim.warp_to_slot(4).await.unwrap();
+    let upd_price = instruction(&mut sim, &publishers[0], price, required_compute_units).await;
+    assert_eq!(
+        process(&mut sim, &publishers[0], &[upd_price]).await,
+        vec![]
+    );
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(price_data.agg_.pub_slot_, 4);
+
+    // Component updates that don't aggregate ignore the compute units
+    let upd_price = instruction(&mut sim, &publishers[1], price, 1).await;
+    assert_eq!(
+        process(&mut sim, &publishers[1], &[upd_price]).await,
+        vec![]
+    );
+}
+
+/// A transaction with too few compute units for the aggregation fails, unless its price update
+/// knows how many are left
+#[tokio::test]
+async fn test_aggregation_deferral_tight_budget() {
+    let mut sim = PythSimulator::new_bpf().await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(2).await;
+    let price = price_keypair.pubkey();
+    sim.set_min_pub(price, 1).await.unwrap();
+    sim.warp_to_slot(2).await.unwrap();
+    sim.publish_all(price, &publishers, &[quote(100), quote(110)])
+        .await
+        .unwrap();
+
+    // Enough compute units for a deferring update, but not for the aggregation
+    sim.warp_to_slot(3).await.unwrap();
+    let upd_price = instruction(&mut sim, &publishers[0], price, 1).await;
+    let deferring_compute_units = sim
+        .simulate_compute_units(&[upd_price], &vec![&publishers[0]], &publishers[0])
+        .await
+        .unwrap();
+    let compute_unit_limit = deferring_compute_units as u32 + 1_000;
+    let set_compute_unit_limit =
+        ComputeBudgetInstruction::set_compute_unit_limit(compute_unit_limit);
+
+    let upd_price = instruction(&mut sim, &publishers[0], price, 0).await;
+    assert!(sim
+        .process_ixs(
+            &[set_compute_unit_limit.clone(), upd_price],
+            &vec![&publishers[0]],
+            &publishers[0],
+        )
+        .await
+        .is_err());
+
+    let upd_price = instruction(&mut sim, &publishers[0], price, compute_unit_limit).await;
+    let deferrals = process(
+        &mut sim,
+        &publishers[0],
+        &[set_compute_unit_limit, upd_price],
+    )
+    .await;
+    assert_eq!(deferrals.len(), 1);
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(price_data.agg_.pub_slot_, 2);
+    assert_eq!(price_data.status_change_.agg_deferred_, 1);
+}
+
+/// The estimates grow with the number of components up to the ceiling with the full publisher
+/// set, which fits in the default compute unit limit of an instruction
+#[test]
+fn test_aggregation_compute_units() {
+    let mut price_account = PriceAccount::zeroed();
+    let mut prev_compute_units = 0;
+    for num_components in 0..=PC_NUM_COMP {
+        price_account.num_ = num_components;
+        let compute_units = aggregation_compute_units(&price_account);
+        assert!(compute_units >= prev_compute_units);
+        assert!(compute_units <= 200_000);
+        prev_compute_units = compute_units;
+    }
+    price_account.num_ = 2;
+    assert_eq!(
+        aggregation_compute_units(&price_account),
+        AGGREGATION_MIN_COMPUTE_UNITS
+    );
+    price_account.num_ = PC_NUM_COMP;
+    assert_eq!(
+        aggregation_compute_units(&price_account),
+        AGGREGATION_MAX_COMPUTE_UNITS
+    );
+}
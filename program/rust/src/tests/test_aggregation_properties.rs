@@ -0,0 +1,195 @@
+//! Properties of the price model checked on random sets of quotes. The quotes are generated from
+//! a fixed seed per case, so that the runs are reproducible and a failure reports the seed of the
+//! case to replay with `check_case`.
+
+use {
+    crate::{
+        aggregation::{
+            aggregate,
+            AggParams,
+            Quote,
+        },
+        c_oracle_header::{
+            PC_AGG_MODE_DEFAULT,
+            PC_AGG_MODE_MID,
+            PC_AGG_MODE_TRIMMED_MEAN,
+            PC_AGG_MODE_WEIGHTED,
+            PC_NUM_COMP,
+            PC_STATUS_HALTED,
+            PC_STATUS_IGNORED,
+            PC_STATUS_TRADING,
+            PC_STATUS_UNKNOWN,
+            PC_STATUS_UNRELIABLE,
+        },
+    },
+    pyth_oracle_core::aggregation::{
+        max_latency,
+        quote_weights,
+    },
+    rand::{
+        rngs::StdRng,
+        seq::SliceRandom,
+        Rng,
+        SeedableRng,
+    },
+};
+
+const NUM_CASES: u64 = 2_000;
+const SLOT: u64 = 1_000;
+
+/// A known aggregation mode or an unknown one, which aggregates like the default mode
+const AGG_MODES: [u32; 5] = [
+    PC_AGG_MODE_DEFAULT,
+    PC_AGG_MODE_WEIGHTED,
+    PC_AGG_MODE_TRIMMED_MEAN,
+    PC_AGG_MODE_MID,
+    42,
+];
+
+/// Mostly quotes that can be included, with some that are never included
+const STATUSES: [u32; 8] = [
+    PC_STATUS_TRADING,
+    PC_STATUS_TRADING,
+    PC_STATUS_TRADING,
+    PC_STATUS_TRADING,
+    PC_STATUS_UNRELIABLE,
+    PC_STATUS_UNKNOWN,
+    PC_STATUS_HALTED,
+    PC_STATUS_IGNORED,
+];
+
+/// A random quote for `SLOT`. Most prices are close to each other, some are far away or at the
+/// bounds of an i64 to exercise the overflow checks.
+fn random_quote(rng: &mut StdRng) -> Quote {
+    let price = match rng.gen_range(0..20) {
+        0 => rng.gen(),
+        1 => *[i64::MIN, i64::MAX].choose(rng).unwrap(),
+        _ => rng.gen_range(-1_000_000..1_000_000),
+    };
+    let conf = match rng.gen_range(0..20) {
+        0 => 0,
+        1 => rng.gen(),
+        _ => rng.gen_range(1..10_000),
+    };
+    Quote {
+        price,
+        conf,
+        status: *STATUSES.choose(rng).unwrap(),
+        // Some quotes are stale
+        pub_slot: SLOT - rng.gen_range(0..40),
+        weight: rng.gen_range(0..4),
+        pub_time: rng.gen_range(0..3) * rng.gen_range(1..1_000_000),
+        excluded: rng.gen_ratio(1, 20),
+    }
+}
+
+/// A random publisher set, i.e. the quotes of up to `PC_NUM_COMP` publishers, and a random
+/// configuration of its price account
+fn random_publisher_set(rng: &mut StdRng) -> (Vec<Quote>, AggParams) {
+    let num_quotes = rng.gen_range(0..=PC_NUM_COMP as usize);
+    let quotes = (0..num_quotes).map(|_| random_quote(rng)).collect();
+    let (min_price, max_price) = if rng.gen_ratio(1, 5) {
+        let min_price = rng.gen_range(-1_000_000..1_000_000);
+        (min_price, min_price + rng.gen_range(0..1_000_000))
+    } else {
+        (0, 0)
+    };
+    let params = AggParams {
+        min_pub: rng.gen_range(0..4),
+        max_latency: max_latency(rng.gen_range(0..40)),
+        agg_mode: *AGG_MODES.choose(rng).unwrap() as u8,
+        outlier_band: *[0, 0, 1, 3].choose(rng).unwrap(),
+        prev_aggregate: rng.gen_ratio(1, 2).then(|| {
+            (
+                rng.gen_range(-1_000_000..1_000_000),
+                rng.gen_range(1..10_000),
+            )
+        }),
+        min_price,
+        max_price,
+    };
+    (quotes, params)
+}
+
+/// The quotes included in the aggregate of `quotes`
+fn included_quotes(quotes: &[Quote], params: &AggParams) -> Vec<Quote> {
+    quotes
+        .iter()
+        .zip(quote_weights(quotes, SLOT, params))
+        .filter(|(_, weight)| *weight != 0)
+        .map(|(quote, _)| *quote)
+        .collect()
+}
+
+/// The aggregate price is between the lowest and the highest included prices, its confidence is
+/// positive, and it doesn't depend on the order of the publishers
+fn check_case(seed: u64) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let (quotes, params) = random_publisher_set(&mut rng);
+    let result = aggregate(&quotes, SLOT, &params);
+
+    let included = included_quotes(&quotes, &params);
+    assert_eq!(result.num_qt as usize, included.len(), "seed {seed}");
+    if let Some((price, conf)) = result.price_conf {
+        let low = included.iter().map(|quote| quote.price).min().unwrap();
+        let high = included.iter().map(|quote| quote.price).max().unwrap();
+        assert!(low <= price && price <= high, "seed {seed}");
+        assert!(conf > 0, "seed {seed}");
+    } else {
+        assert!(
+            included.is_empty() || included.len() < usize::from(params.min_pub),
+            "seed {seed}"
+        );
+    }
+    if let Some((_, fast_conf)) = result.fast_price_conf {
+        assert!(fast_conf > 0, "seed {seed}");
+    }
+
+    let mut shuffled = quotes.clone();
+    shuffled.shuffle(&mut rng);
+    assert_eq!(aggregate(&shuffled, SLOT, &params), result, "seed {seed}");
+}
+
+#[test]
+fn test_aggregation_properties() {
+    for seed in 0..NUM_CASES {
+        check_case(seed);
+    }
+}
+
+/// Adding a quote identical to an included quote doesn't move the aggregate price away from it,
+/// except in the trimmed mean mode: when the number of trimmed quotes grows, the lowest and the
+/// highest kept quotes are replaced by the added one, which can be on either side of their mean.
+/// The outlier band is off, as the added quote can otherwise bring enough quotes into the band to
+/// exclude the others.
+#[test]
+fn test_aggregation_monotone_under_identical_quotes() {
+    for seed in 0..NUM_CASES {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let (mut quotes, params) = random_publisher_set(&mut rng);
+        if u32::from(params.agg_mode) == PC_AGG_MODE_TRIMMED_MEAN {
+            continue;
+        }
+        quotes.truncate(PC_NUM_COMP as usize - 1);
+        let params = AggParams {
+            prev_aggregate: None,
+            ..params
+        };
+        let price = match aggregate(&quotes, SLOT, &params).price_conf {
+            Some((price, _)) => price,
+            None => continue,
+        };
+        let included = included_quotes(&quotes, &params);
+        let added = *included.choose(&mut rng).unwrap();
+
+        quotes.insert(rng.gen_range(0..=quotes.len()), added);
+        let result = aggregate(&quotes, SLOT, &params);
+        assert_eq!(result.num_qt as usize, included.len() + 1, "seed {seed}");
+        let (new_price, _) = result.price_conf.unwrap();
+        if added.price >= price {
+            assert!(new_price >= price, "seed {seed}");
+        } else {
+            assert!(new_price <= price, "seed {seed}");
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use {
+    crate::{
+        c_oracle_header::PC_STATUS_TRADING,
+        client,
+        error::OracleError,
+        instruction::PriceUpdate,
+        tests::pyth_simulator::{
+            copy_keypair,
+            PythSimulator,
+            TransactionFailure,
+        },
+    },
+    solana_program::{
+        instruction::Instruction,
+        pubkey::Pubkey,
+    },
+    solana_sdk::signature::{
+        Keypair,
+        Signer,
+    },
+};
+
+/// Check that `instruction` is rejected whenever one of its accounts of index in `roles` is
+/// replaced by one of the previous ones, i.e. when the same account is passed in two roles.
+/// `signer` funds and signs the transaction, it must be the only signer of `instruction`.
+async fn assert_rejects_aliases(
+    sim: &mut PythSimulator,
+    instruction: &Instruction,
+    roles: &[usize],
+    signer: &Keypair,
+) {
+    for (j, &aliased) in roles.iter().enumerate() {
+        for &original in &roles[..j] {
+            let mut aliased_instruction = instruction.clone();
+            aliased_instruction.accounts[aliased].pubkey = instruction.accounts[original].pubkey;
+            sim.process_ixs(&[aliased_instruction], &vec![], signer)
+                .await
+                .assert_fails_with(OracleError::AliasedAccounts);
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_aliased_accounts_admin_instructions() {
+    let mut sim = PythSimulator::new().await;
+    let mapping_keypair = sim.init_mapping().await.unwrap();
+    let product_keypair = sim.add_product(&mapping_keypair).await.unwrap();
+    let price_keypair = sim.add_price(&product_keypair, -5).await.unwrap();
+    let program_id = sim.program_id();
+    let authority = copy_keypair(&sim.genesis_keypair);
+    let funding = authority.pubkey();
+    let product = product_keypair.pubkey();
+    let price = price_keypair.pubkey();
+
+    let instructions = [
+        (client::set_min_pub(&program_id, &funding, &price, 1), 3),
+        (
+            client::add_publisher(&program_id, &funding, &price, Pubkey::new_unique()),
+            3,
+        ),
+        (client::halt_price(&program_id, &funding, &price), 3),
+        (
+            client::upd_product(&program_id, &funding, &product, &[("symbol", "BTC")]),
+            3,
+        ),
+        (client::set_fee(&program_id, &funding, &price, 1), 5),
+        (
+            client::del_price(&program_id, &funding, &product, &price, None),
+            4,
+        ),
+    ];
+    for (instruction, num_roles) in instructions {
+        let roles: Vec<usize> = (0..num_roles).collect();
+        assert_rejects_aliases(&mut sim, &instruction, &roles, &authority).await;
+    }
+
+    // The recipient of the lamports of a deleted price account may be the funding account
+    sim.process_ixs(
+        &[client::del_price(
+            &program_id,
+            &funding,
+            &product,
+            &price,
+            Some(&funding),
+        )],
+        &vec![],
+        &authority,
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_aliased_accounts_withdraw_fee() {
+    let mut sim = PythSimulator::new().await;
+    let program_id = sim.program_id();
+    let authority = copy_keypair(&sim.genesis_keypair);
+
+    // The treasury can't be its own destination
+    let treasury = client::get_treasury_pubkey(&program_id);
+    sim.withdraw_fee(&authority, treasury, 1)
+        .await
+        .assert_fails_with(OracleError::AliasedAccounts);
+}
+
+#[tokio::test]
+async fn test_aliased_accounts_publisher_instructions() {
+    let mut sim = PythSimulator::new().await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(1).await;
+    let program_id = sim.program_id();
+    let publisher = &publishers[0];
+    let price = price_keypair.pubkey();
+    let update = PriceUpdate {
+        status:          PC_STATUS_TRADING,
+        price:           100,
+        confidence:      1,
+        publishing_slot: sim.get_clock().await.unwrap().slot,
+    };
+
+    let upd_price = client::upd_price(&program_id, &publisher.pubkey(), &price, &update);
+    assert_rejects_aliases(&mut sim, &upd_price, &[0, 1, 2], publisher).await;
+    let resign_publisher = client::resign_publisher(&program_id, &publisher.pubkey(), &price);
+    assert_rejects_aliases(&mut sim, &resign_publisher, &[0, 1], publisher).await;
+
+    // A batch updates each price account at most once
+    let upd_price_batch = client::upd_price_batch(
+        &program_id,
+        &publisher.pubkey(),
+        &[(price, update), (price, update)],
+    );
+    sim.process_ixs(&[upd_price_batch], &vec![], publisher)
+        .await
+        .assert_fails_with(OracleError::AliasedAccounts);
+
+    sim.process_ixs(&[upd_price], &vec![], publisher)
+        .await
+        .unwrap();
+}
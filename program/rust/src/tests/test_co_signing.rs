@@ -0,0 +1,153 @@
+use {
+    crate::{
+        accounts::{
+            PermissionAccount,
+            PriceAccount,
+        },
+        c_oracle_header::PC_STATUS_HALTED,
+        client,
+        error::OracleError,
+        instruction::{
+            OracleCommand,
+            UpdPermissionsArgs,
+        },
+        tests::pyth_simulator::{
+            copy_keypair,
+            PythSimulator,
+            TransactionFailure,
+        },
+    },
+    solana_program::{
+        instruction::Instruction,
+        native_token::LAMPORTS_PER_SOL,
+        program_error::ProgramError,
+    },
+    solana_sdk::{
+        signature::Keypair,
+        signer::Signer,
+    },
+};
+
+/// In co-signing mode, the co-signed commands are rejected unless another authority key signs the
+/// same transaction
+#[tokio::test]
+async fn test_co_signing() {
+    let mut sim = PythSimulator::new().await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(2).await;
+    let price = price_keypair.pubkey();
+    let program_id = sim.program_id();
+    let authority = copy_keypair(&sim.genesis_keypair);
+    let security_authority = Keypair::new();
+    let stranger = Keypair::new();
+    for key in [security_authority.pubkey(), stranger.pubkey()] {
+        sim.airdrop(&key, 100 * LAMPORTS_PER_SOL).await.unwrap();
+    }
+    sim.upd_permissions(
+        UpdPermissionsArgs {
+            header:                  OracleCommand::UpdPermissions.into(),
+            master_authority:        authority.pubkey(),
+            data_curation_authority: authority.pubkey(),
+            security_authority:      security_authority.pubkey(),
+        },
+        &copy_keypair(&sim.upgrade_authority),
+    )
+    .await
+    .unwrap();
+
+    sim.set_co_signing(true).await.unwrap();
+    let permissions = sim.get_permissions_pubkey();
+    let permissions_account = sim.get_account(permissions).await.unwrap();
+    assert_eq!(
+        permissions_account.data.len(),
+        PermissionAccount::CO_SIGNING_SIZE
+    );
+
+    let rotated_publisher = Keypair::new();
+    let co_signed_instructions: [Instruction; 4] = [
+        client::set_exponent(&program_id, &authority.pubkey(), &price, -4),
+        client::del_publisher(
+            &program_id,
+            &authority.pubkey(),
+            &price,
+            publishers[1].pubkey(),
+        ),
+        client::halt_price(&program_id, &authority.pubkey(), &price),
+        client::rotate_publisher(
+            &program_id,
+            &authority.pubkey(),
+            &price,
+            publishers[0].pubkey(),
+            rotated_publisher.pubkey(),
+        ),
+    ];
+
+    // A single signer is rejected: without the instructions sysvar, with the sysvar but no other
+    // signer, signed twice by the same authority or co-signed by a key that is not an authority
+    for instruction in co_signed_instructions.iter() {
+        sim.process_ixs(&[instruction.clone()], &vec![], &authority)
+            .await
+            .assert_fails_with(OracleError::MissingCoSigner);
+        sim.process_ixs(
+            &[client::co_signed(instruction.clone())],
+            &vec![],
+            &authority,
+        )
+        .await
+        .assert_fails_with(OracleError::MissingCoSigner);
+        for co_signer in [&authority, &stranger] {
+            sim.process_co_signed_ix(instruction.clone(), co_signer)
+                .await
+                .assert_fails_with(OracleError::MissingCoSigner);
+        }
+    }
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(price_data.exponent, -5);
+    assert_eq!(price_data.num_, 2);
+    assert_ne!(price_data.agg_.status_, PC_STATUS_HALTED);
+
+    // The co-signer doesn't authorize the command on its own
+    sim.process_co_signed_ix(
+        client::halt_price(&program_id, &security_authority.pubkey(), &price),
+        &authority,
+    )
+    .await
+    .assert_fails_with(OracleError::PermissionViolation);
+
+    // Two distinct authority keys
+    for instruction in co_signed_instructions {
+        sim.process_co_signed_ix(instruction, &security_authority)
+            .await
+            .unwrap();
+    }
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(price_data.exponent, -4);
+    assert_eq!(price_data.num_, 1);
+    assert_eq!(price_data.comp_[0].pub_, rotated_publisher.pubkey());
+    assert_eq!(price_data.agg_.status_, PC_STATUS_HALTED);
+
+    // Other administrative commands don't need a co-signer
+    sim.set_min_pub(price, 1).await.unwrap();
+    sim.resume_price(price).await.unwrap();
+
+    // Without the mode, a single signer is enough again
+    sim.set_co_signing(false).await.unwrap();
+    sim.halt_price(price).await.unwrap();
+    sim.set_exponent(price, -5).await.unwrap();
+}
+
+/// The co-signed commands can't be authorized by permission grants
+#[tokio::test]
+async fn test_co_signing_permission_grant() {
+    let mut sim = PythSimulator::new().await;
+    let authority = Keypair::new();
+    for command in [
+        OracleCommand::DelPublisher,
+        OracleCommand::RotatePublisher,
+        OracleCommand::SetExponent,
+        OracleCommand::HaltPrice,
+    ] {
+        sim.grant_permission(authority.pubkey(), command)
+            .await
+            .assert_fails_with(ProgramError::InvalidArgument);
+    }
+}
@@ -0,0 +1,160 @@
+use {
+    crate::{
+        accounts::{
+            PermissionAccount,
+            PriceAccount,
+            PriceComponent,
+            PriceInfo,
+            PublisherStats,
+            PythAccount,
+        },
+        aggregation::upd_aggregate,
+        c_oracle_header::{
+            PC_NUM_COMP,
+            PC_STATUS_TRADING,
+            PC_VERSION,
+        },
+        deserialize::load_checked,
+        error::OracleError,
+        instruction::{
+            CommandHeader,
+            OracleCommand,
+        },
+        processor::process_instruction,
+        tests::test_utils::AccountSetup,
+    },
+    bytemuck::{
+        bytes_of,
+        Zeroable,
+    },
+    solana_program::pubkey::Pubkey,
+};
+
+const SLOT: u64 = 42;
+
+fn quote(price: i64) -> PriceInfo {
+    PriceInfo {
+        price_:           price,
+        conf_:            10,
+        status_:          PC_STATUS_TRADING,
+        pub_slot_:        SLOT,
+        corp_act_status_: 0,
+    }
+}
+
+/// The aggregate price, confidence and status and the number of quotes of an aggregation of
+/// `price_data` at the next slot
+fn aggregate(price_data: &PriceAccount) -> (i64, u64, u32, u32) {
+    let mut price_data = *price_data;
+    upd_aggregate(&mut price_data, SLOT + 1, 0);
+    (
+        price_data.agg_.price_,
+        price_data.agg_.conf_,
+        price_data.agg_.status_,
+        price_data.num_qt_,
+    )
+}
+
+#[test]
+fn test_compact_components() {
+    let program_id = Pubkey::new_unique();
+    let publishers = [
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    ];
+    let compact: CommandHeader = OracleCommand::CompactComponents.into();
+
+    let mut funding_setup = AccountSetup::new_funding();
+    let funding_account = funding_setup.as_account_info();
+
+    let mut attacker_setup = AccountSetup::new_funding();
+    let attacker_account = attacker_setup.as_account_info();
+
+    let mut price_setup = AccountSetup::new::<PriceAccount>(&program_id);
+    let price_account = price_setup.as_account_info();
+    PriceAccount::initialize(&price_account, PC_VERSION).unwrap();
+
+    let mut permissions_setup = AccountSetup::new_permission(&program_id);
+    let permissions_account = permissions_setup.as_account_info();
+    {
+        let mut permissions_account_data =
+            PermissionAccount::initialize(&permissions_account, PC_VERSION).unwrap();
+        permissions_account_data.master_authority = *funding_account.key;
+    }
+
+    // The publishers have the components 0, 2 and 4, the components 1 and 3 are zeroed and the
+    // slot after the last component holds a stale component
+    let aggregate_before = {
+        let mut price_data = load_checked::<PriceAccount>(&price_account, PC_VERSION).unwrap();
+        price_data.num_ = 5;
+        price_data.min_pub_ = 1;
+        for (i, publisher) in publishers.iter().enumerate() {
+            price_data.comp_[2 * i].pub_ = *publisher;
+            price_data.comp_[2 * i].latest_ = quote(100 + 10 * i as i64);
+            price_data.publisher_stats_[2 * i].num_rounds_ = i as u64 + 1;
+        }
+        price_data.comp_[5].pub_ = Pubkey::new_unique();
+        price_data.comp_[5].latest_ = quote(1_000);
+        price_data.publisher_stats_[5].num_rounds_ = 9;
+        aggregate(&price_data)
+    };
+    assert_eq!(aggregate_before.2, PC_STATUS_TRADING);
+    assert_eq!(aggregate_before.3, 3);
+
+    assert_eq!(
+        process_instruction(
+            &program_id,
+            &[
+                attacker_account.clone(),
+                price_account.clone(),
+                permissions_account.clone(),
+            ],
+            bytes_of(&compact),
+        ),
+        Err(OracleError::PermissionViolation.into())
+    );
+
+    process_instruction(
+        &program_id,
+        &[
+            funding_account.clone(),
+            price_account.clone(),
+            permissions_account.clone(),
+        ],
+        bytes_of(&compact),
+    )
+    .unwrap();
+
+    let compacted = {
+        let price_data = load_checked::<PriceAccount>(&price_account, PC_VERSION).unwrap();
+        assert_eq!(price_data.num_, 3);
+        for (i, publisher) in publishers.iter().enumerate() {
+            assert_eq!(price_data.comp_[i].pub_, *publisher);
+            assert_eq!(price_data.comp_[i].latest_, quote(100 + 10 * i as i64));
+            assert_eq!(price_data.publisher_stats_[i].num_rounds_, i as u64 + 1);
+        }
+        for i in 3..PC_NUM_COMP as usize {
+            assert_eq!(price_data.comp_[i], PriceComponent::zeroed());
+            assert_eq!(price_data.publisher_stats_[i], PublisherStats::zeroed());
+        }
+
+        // The aggregation sees the same quotes
+        assert_eq!(aggregate(&price_data), aggregate_before);
+        *price_data
+    };
+
+    // Compacting again changes nothing
+    process_instruction(
+        &program_id,
+        &[
+            funding_account.clone(),
+            price_account.clone(),
+            permissions_account.clone(),
+        ],
+        bytes_of(&compact),
+    )
+    .unwrap();
+    let price_data = load_checked::<PriceAccount>(&price_account, PC_VERSION).unwrap();
+    assert_eq!(bytes_of(&*price_data), bytes_of(&compacted));
+}
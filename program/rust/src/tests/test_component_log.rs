@@ -0,0 +1,150 @@
+use {
+    crate::{
+        accounts::{
+            ComponentLogAccount,
+            ComponentLogEntry,
+            PriceAccount,
+        },
+        c_oracle_header::{
+            COMPONENT_LOG_CAPACITY,
+            PC_STATUS_TRADING,
+        },
+        error::OracleError,
+        tests::pyth_simulator::{
+            PythSimulator,
+            Quote,
+            TransactionFailure,
+        },
+    },
+    bytemuck::Zeroable,
+    solana_sdk::{
+        signature::Keypair,
+        signer::Signer,
+    },
+};
+
+fn entry(slot: u64) -> ComponentLogEntry {
+    ComponentLogEntry {
+        slot_: slot,
+        ..ComponentLogEntry::zeroed()
+    }
+}
+
+fn quote(price: i64) -> Quote {
+    Quote {
+        price,
+        confidence: 1,
+        status: PC_STATUS_TRADING,
+    }
+}
+
+/// The index of the component of `publisher` in `price_data`
+fn publisher_index(price_data: &PriceAccount, publisher: &Keypair) -> u32 {
+    price_data
+        .comp_
+        .iter()
+        .position(|component| component.pub_ == publisher.pubkey())
+        .unwrap() as u32
+}
+
+#[test]
+fn test_component_log_wraparound() {
+    let mut component_log = ComponentLogAccount::zeroed();
+    assert_eq!(component_log.entries().count(), 0);
+
+    for slot in 0..3 {
+        component_log.push(entry(slot));
+    }
+    assert_eq!(
+        component_log
+            .entries()
+            .map(|entry| entry.slot_)
+            .collect::<Vec<_>>(),
+        vec![0, 1, 2]
+    );
+
+    // Once full, the log keeps the last entries, oldest first
+    let num_entries = COMPONENT_LOG_CAPACITY as u64 + 5;
+    for slot in 3..num_entries {
+        component_log.push(entry(slot));
+    }
+    assert_eq!(component_log.next_seq_, num_entries);
+    assert_eq!(component_log.entries().count(), COMPONENT_LOG_CAPACITY);
+    for (entry, slot) in component_log.entries().zip(5..) {
+        assert_eq!(entry.slot_, slot);
+    }
+    assert_eq!(
+        component_log.entries_[0].slot_,
+        COMPONENT_LOG_CAPACITY as u64
+    );
+}
+
+#[tokio::test]
+async fn test_upd_price_appends_to_component_log() {
+    let mut sim = PythSimulator::new().await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(2).await;
+    let price = price_keypair.pubkey();
+    let component_log = sim.init_component_log(price).await.unwrap().pubkey();
+    let component_log_data = sim
+        .get_account_data_as::<ComponentLogAccount>(component_log)
+        .await
+        .unwrap();
+    assert_eq!(component_log_data.price_account_, price);
+    assert_eq!(component_log_data.next_seq_, 0);
+
+    sim.warp_to_slot(2).await.unwrap();
+    sim.upd_price_with_component_log(&publishers[0], price, quote(100), component_log)
+        .await
+        .unwrap();
+    sim.upd_price_with_component_log(&publishers[1], price, quote(110), component_log)
+        .await
+        .unwrap();
+
+    // Updates without the component log still succeed and aren't logged
+    sim.warp_to_slot(3).await.unwrap();
+    sim.upd_price(&publishers[0], price, quote(120))
+        .await
+        .unwrap();
+    sim.warp_to_slot(4).await.unwrap();
+    sim.upd_price_with_component_log(&publishers[0], price, quote(130), component_log)
+        .await
+        .unwrap();
+
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    let component_log_data = sim
+        .get_account_data_as::<ComponentLogAccount>(component_log)
+        .await
+        .unwrap();
+    let logged = |publisher: &Keypair, slot: u64, price: i64| ComponentLogEntry {
+        publisher_index_: publisher_index(&price_data, publisher),
+        status_:          PC_STATUS_TRADING,
+        slot_:            slot,
+        price_:           price,
+        conf_:            1,
+    };
+    assert_eq!(
+        component_log_data.entries().copied().collect::<Vec<_>>(),
+        vec![
+            logged(&publishers[0], 2, 100),
+            logged(&publishers[1], 2, 110),
+            logged(&publishers[0], 4, 130),
+        ]
+    );
+
+    // The component log of a price account doesn't log the updates of other price accounts
+    let (other_price_keypair, other_publishers) = sim.setup_feed_with_publishers(1).await;
+    let other_price = other_price_keypair.pubkey();
+    sim.upd_price_with_component_log(&other_publishers[0], other_price, quote(100), component_log)
+        .await
+        .assert_fails_with(OracleError::InvalidComponentLog);
+    sim.upd_price(&other_publishers[0], other_price, quote(100))
+        .await
+        .unwrap();
+    assert_eq!(
+        sim.get_account_data_as::<ComponentLogAccount>(component_log)
+            .await
+            .unwrap()
+            .next_seq_,
+        3
+    );
+}
@@ -0,0 +1,126 @@
+use {
+    crate::{
+        accounts::{
+            CompressedPriceAccount,
+            PriceAccount,
+        },
+        c_oracle_header::{
+            PC_ACCTYPE_COMPRESSED_PRICE,
+            PC_STATUS_HALTED,
+            PC_STATUS_TRADING,
+        },
+        error::OracleError,
+        layout::COMPRESSED_PRICE_ACCOUNT_SIZE,
+        sdk::LoadPythAccount,
+        tests::pyth_simulator::{
+            PythSimulator,
+            Quote,
+            TransactionFailure,
+        },
+    },
+    solana_program::{
+        account_info::AccountInfo,
+        clock::Epoch,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        rent::Rent,
+    },
+    solana_sdk::signer::Signer,
+};
+
+#[tokio::test]
+async fn test_compress_price_account() {
+    let mut sim = PythSimulator::new().await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(2).await;
+    let price = price_keypair.pubkey();
+    sim.set_min_pub(price, 1).await.unwrap();
+    let quotes = [100, 110].map(|price| Quote {
+        price,
+        confidence: 5,
+        status: PC_STATUS_TRADING,
+    });
+    sim.publish_all(price, &publishers, &quotes).await.unwrap();
+    sim.warp_to_slot(2).await.unwrap();
+    sim.publish_all(price, &publishers, &quotes).await.unwrap();
+    sim.warp_to_slot(3).await.unwrap();
+    sim.publish_all(price, &publishers, &quotes).await.unwrap();
+
+    // Only halted feeds can be compressed
+    sim.compress_price_account(price, None)
+        .await
+        .assert_fails_with(ProgramError::InvalidArgument);
+
+    sim.halt_price(price).await.unwrap();
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    let lamports = sim.get_account(price).await.unwrap().lamports;
+    let recipient = Pubkey::new_unique();
+    sim.compress_price_account(price, Some(recipient))
+        .await
+        .unwrap();
+
+    // The rent of the space of the components goes to the recipient
+    let mut account = sim.get_account(price).await.unwrap();
+    let rent = Rent::default().minimum_balance(COMPRESSED_PRICE_ACCOUNT_SIZE);
+    assert!(sim.is_owned_by_oracle(&account));
+    assert_eq!(account.data.len(), COMPRESSED_PRICE_ACCOUNT_SIZE);
+    assert_eq!(account.lamports, rent);
+    assert_eq!(
+        sim.get_account(recipient).await.unwrap().lamports,
+        lamports - rent
+    );
+
+    // The final aggregate and the moving averages remain readable
+    let mut account_lamports = account.lamports;
+    let account_info = AccountInfo::new(
+        &price,
+        false,
+        false,
+        &mut account_lamports,
+        &mut account.data,
+        &account.owner,
+        false,
+        Epoch::default(),
+    );
+    assert_eq!(
+        PriceAccount::load(&account_info).err(),
+        Some(OracleError::AccountTooSmall.into())
+    );
+    let compressed_data = *CompressedPriceAccount::load(&account_info).unwrap();
+    assert_eq!(
+        compressed_data.header.account_type,
+        PC_ACCTYPE_COMPRESSED_PRICE
+    );
+    assert_eq!(
+        compressed_data.header.size as usize,
+        COMPRESSED_PRICE_ACCOUNT_SIZE
+    );
+    assert_eq!(compressed_data.agg_.status_, PC_STATUS_HALTED);
+    assert_eq!(
+        compressed_data.get_price_unchecked(),
+        price_data.get_price_unchecked()
+    );
+    assert_eq!(compressed_data.get_price_unchecked().price, 105);
+    assert_eq!(compressed_data.twap_.val_, price_data.twap_.val_);
+    assert_eq!(compressed_data.twac_.val_, price_data.twac_.val_);
+    assert_eq!(
+        compressed_data.price_cumulative.price,
+        price_data.price_cumulative.price
+    );
+    assert_eq!(compressed_data.product_account, price_data.product_account);
+    assert_eq!(
+        compressed_data.next_price_account,
+        price_data.next_price_account
+    );
+
+    // The feed can't be updated, resumed or compressed anymore
+    sim.warp_to_slot(4).await.unwrap();
+    sim.upd_price(&publishers[0], price, quotes[0])
+        .await
+        .assert_fails_with(OracleError::AccountTooSmall);
+    sim.resume_price(price)
+        .await
+        .assert_fails_with(OracleError::AccountTooSmall);
+    sim.compress_price_account(price, None)
+        .await
+        .assert_fails_with(OracleError::AccountTooSmall);
+}
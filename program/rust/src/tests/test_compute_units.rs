@@ -0,0 +1,313 @@
+use {
+    crate::{
+        accounts::PriceAccount,
+        aggregation::{
+            aggregation_compute_units,
+            AGGREGATION_MAX_COMPUTE_UNITS,
+            AGGREGATION_MIN_COMPUTE_UNITS,
+        },
+        c_oracle_header::{
+            PC_NUM_COMP,
+            PC_STATUS_TRADING,
+        },
+        deserialize::load_mut,
+        tests::{
+            fixtures::AccountFixture,
+            pyth_simulator::{
+                PythSimulator,
+                Quote,
+            },
+        },
+    },
+    solana_program::{
+        instruction::Instruction,
+        pubkey::Pubkey,
+    },
+    solana_sdk::{
+        signature::Keypair,
+        signer::Signer,
+    },
+    std::{
+        collections::HashMap,
+        iter::once,
+    },
+};
+
+// Compute unit ceilings. These are estimates: the tests of this file, including the comparisons
+// between instructions, haven't been run against the BPF program yet, so the ceilings are not
+// known to leave headroom above the current costs. Once they are measured, a test failing here
+// means that an instruction got more expensive and should be looked at before raising the ceiling.
+/// An estimate rather than a measured cost, to be lowered to the cost of a component update once
+/// `test_upd_price_compute_units` has run
+const UPD_PRICE_MAX_COMPUTE_UNITS: u64 = 20_000;
+/// The aggregation ceilings are the ones from which the compute units needed by the aggregation
+/// are estimated (see `aggregation_compute_units`)
+const UPD_AGGREGATE_MAX_COMPUTE_UNITS: u64 = AGGREGATION_MIN_COMPUTE_UNITS as u64;
+const UPD_AGGREGATE_FULL_PUBLISHER_SET_MAX_COMPUTE_UNITS: u64 =
+    AGGREGATION_MAX_COMPUTE_UNITS as u64;
+/// Cost of returning the results of a scatter update on top of the same batch update
+const UPD_PRICE_SCATTER_MAX_OVERHEAD_COMPUTE_UNITS: u64 = 1_000;
+
+#[tokio::test]
+async fn test_upd_price_compute_units() {
+    let mut sim = PythSimulator::new_bpf().await;
+    let publisher_1 = Keypair::new();
+    let publisher_2 = Keypair::new();
+    let security_authority = Keypair::new();
+    let price_accounts = sim
+        .setup_product_fixture(
+            &[publisher_1.pubkey(), publisher_2.pubkey()],
+            security_authority.pubkey(),
+        )
+        .await;
+    let price = price_accounts["LTC"];
+    sim.set_min_pub(price, 1).await.unwrap();
+
+    sim.upd_price(&publisher_1, price, quote(100))
+        .await
+        .unwrap();
+
+    // The aggregation already ran in this slot, so this is only a component update
+    let instruction = upd_price_instruction(&mut sim, &publisher_2, price, 110).await;
+    let upd_price_compute_units = sim
+        .measure_compute_units(instruction, &vec![&publisher_2], &publisher_2)
+        .await
+        .unwrap();
+    assert!(
+        upd_price_compute_units <= UPD_PRICE_MAX_COMPUTE_UNITS,
+        "upd_price consumed {upd_price_compute_units} compute units"
+    );
+
+    // The first update of the next slot aggregates the previous quotes
+    sim.warp_to_slot(2).await.unwrap();
+    let instruction = upd_price_instruction(&mut sim, &publisher_1, price, 120).await;
+    let upd_aggregate_compute_units = sim
+        .measure_compute_units(instruction, &vec![&publisher_1], &publisher_1)
+        .await
+        .unwrap();
+    assert!(
+        upd_aggregate_compute_units <= UPD_AGGREGATE_MAX_COMPUTE_UNITS,
+        "upd_price with aggregation consumed {upd_aggregate_compute_units} compute units"
+    );
+    assert!(upd_aggregate_compute_units > upd_price_compute_units);
+
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(price_data.agg_.price_, 105);
+    assert_eq!(price_data.agg_.status_, PC_STATUS_TRADING);
+    // Price updates with fewer compute units left than the estimate defer the aggregation
+    assert!(upd_aggregate_compute_units <= u64::from(aggregation_compute_units(&price_data)));
+}
+
+/// The price updates skipping the aggregation because of the aggregation interval cost about as
+/// much as the component updates
+#[tokio::test]
+async fn test_agg_interval_compute_units() {
+    let mut sim = PythSimulator::new_bpf().await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(2).await;
+    let price = price_keypair.pubkey();
+    sim.set_min_pub(price, 1).await.unwrap();
+    sim.set_agg_interval(price, 3).await.unwrap();
+
+    sim.warp_to_slot(3).await.unwrap();
+    for publisher in &publishers {
+        sim.upd_price(publisher, price, quote(100)).await.unwrap();
+    }
+
+    // The first update of the next slot doesn't aggregate, the interval hasn't passed
+    sim.warp_to_slot(4).await.unwrap();
+    let instruction = upd_price_instruction(&mut sim, &publishers[0], price, 110).await;
+    let skip_compute_units = sim
+        .measure_compute_units(instruction, &vec![&publishers[0]], &publishers[0])
+        .await
+        .unwrap();
+    assert!(
+        skip_compute_units <= UPD_PRICE_MAX_COMPUTE_UNITS,
+        "upd_price skipping the aggregation consumed {skip_compute_units} compute units"
+    );
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(price_data.agg_.pub_slot_, 3);
+
+    sim.warp_to_slot(6).await.unwrap();
+    let instruction = upd_price_instruction(&mut sim, &publishers[0], price, 120).await;
+    let upd_aggregate_compute_units = sim
+        .measure_compute_units(instruction, &vec![&publishers[0]], &publishers[0])
+        .await
+        .unwrap();
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(price_data.agg_.pub_slot_, 6);
+    assert!(
+        skip_compute_units < upd_aggregate_compute_units,
+        "upd_price skipping the aggregation consumed {skip_compute_units} compute units and \
+         {upd_aggregate_compute_units} when aggregating"
+    );
+}
+
+#[tokio::test]
+async fn test_upd_aggregate_full_publisher_set_compute_units() {
+    let mut sim = PythSimulator::new_bpf().await;
+    let publishers: Vec<Keypair> = (0..PC_NUM_COMP).map(|_| Keypair::new()).collect();
+    let security_authority = Keypair::new();
+    let price_accounts = sim
+        .setup_product_fixture(
+            &publishers
+                .iter()
+                .map(|publisher| publisher.pubkey())
+                .collect::<Vec<Pubkey>>(),
+            security_authority.pubkey(),
+        )
+        .await;
+    let price = price_accounts["LTC"];
+
+    for (i, publisher) in publishers.iter().enumerate() {
+        sim.upd_price(publisher, price, quote(100 + i as i64))
+            .await
+            .unwrap();
+    }
+
+    sim.warp_to_slot(2).await.unwrap();
+    let instruction = upd_price_instruction(&mut sim, &publishers[0], price, 100).await;
+    let compute_units = sim
+        .measure_compute_units(instruction, &vec![&publishers[0]], &publishers[0])
+        .await
+        .unwrap();
+    assert!(
+        compute_units <= UPD_AGGREGATE_FULL_PUBLISHER_SET_MAX_COMPUTE_UNITS,
+        "upd_price with aggregation of {PC_NUM_COMP} publishers consumed {compute_units} compute units"
+    );
+
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(price_data.num_qt_, PC_NUM_COMP);
+    assert_eq!(price_data.agg_.status_, PC_STATUS_TRADING);
+    assert!(compute_units <= u64::from(aggregation_compute_units(&price_data)));
+}
+
+/// Publishers are found by a binary search in sorted price accounts, and by a linear search in
+/// accounts that are not sorted
+#[tokio::test]
+async fn test_upd_price_publisher_lookup_compute_units() {
+    let mut sim = PythSimulator::new_bpf().await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(PC_NUM_COMP as usize).await;
+    let price = price_keypair.pubkey();
+    // The first component of the sorted account, and the last one once the components are reversed
+    let (publisher, other_publisher) = {
+        let mut publishers: Vec<&Keypair> = publishers.iter().collect();
+        publishers.sort_by_key(|publisher| publisher.pubkey());
+        (publishers[0], publishers[1])
+    };
+
+    // Another publisher runs the aggregation of the slot first, so that only the lookup and the
+    // component update are measured
+    sim.warp_to_slot(2).await.unwrap();
+    sim.upd_price(other_publisher, price, quote(100))
+        .await
+        .unwrap();
+    let instruction = upd_price_instruction(&mut sim, publisher, price, 100).await;
+    let sorted_compute_units = sim
+        .measure_compute_units(instruction, &vec![publisher], publisher)
+        .await
+        .unwrap();
+
+    // Reverse the components, as in accounts from before the publishers were sorted
+    let mut account = sim.get_account(price).await.unwrap();
+    {
+        let price_data = load_mut::<PriceAccount>(&mut account.data).unwrap();
+        let num_comps = price_data.num_ as usize;
+        price_data.comp_[..num_comps].reverse();
+        price_data.publisher_stats_[..num_comps].reverse();
+    }
+    sim.load_oracle_account(&AccountFixture {
+        pubkey: price,
+        account,
+    });
+
+    sim.warp_to_slot(3).await.unwrap();
+    sim.upd_price(other_publisher, price, quote(100))
+        .await
+        .unwrap();
+    let instruction = upd_price_instruction(&mut sim, publisher, price, 100).await;
+    let unsorted_compute_units = sim
+        .measure_compute_units(instruction, &vec![publisher], publisher)
+        .await
+        .unwrap();
+
+    assert!(
+        sorted_compute_units < unsorted_compute_units,
+        "finding the publisher consumed {sorted_compute_units} compute units in a sorted account \
+         and {unsorted_compute_units} in an unsorted one"
+    );
+    assert!(sorted_compute_units <= UPD_PRICE_MAX_COMPUTE_UNITS);
+}
+
+/// A scatter update costs about as much as a batch update of the same price accounts
+#[tokio::test]
+async fn test_upd_price_scatter_compute_units() {
+    let mut sim = PythSimulator::new_bpf().await;
+    let publisher = Keypair::new();
+    let security_authority = Keypair::new();
+    let price_accounts = sim
+        .setup_product_fixture(&[publisher.pubkey()], security_authority.pubkey())
+        .await;
+    let quotes: HashMap<String, Quote> = price_accounts
+        .keys()
+        .map(|symbol| (symbol.clone(), quote(100)))
+        .collect();
+    let updates: Vec<(Pubkey, Quote)> = price_accounts
+        .iter()
+        .map(|(symbol, price_account)| (*price_account, quotes[symbol]))
+        .collect();
+
+    // Both are simulated against the same state, in which the updates also aggregate
+    let batch_instruction = sim
+        .upd_price_batch_instruction(&publisher, &price_accounts, &quotes)
+        .await
+        .unwrap();
+    let batch_compute_units = sim
+        .simulate_compute_units(&[batch_instruction], &vec![&publisher], &publisher)
+        .await
+        .unwrap();
+    let scatter_instruction = sim
+        .upd_price_scatter_instruction(&publisher, &updates)
+        .await
+        .unwrap();
+    let scatter_compute_units = sim
+        .simulate_compute_units(&[scatter_instruction], &vec![&publisher], &publisher)
+        .await
+        .unwrap();
+
+    assert!(
+        scatter_compute_units <= batch_compute_units + UPD_PRICE_SCATTER_MAX_OVERHEAD_COMPUTE_UNITS,
+        "upd_price_scatter consumed {scatter_compute_units} compute units and upd_price_batch \
+         {batch_compute_units} for {} price accounts",
+        updates.len()
+    );
+}
+
+fn quote(price: i64) -> Quote {
+    Quote {
+        price,
+        confidence: 5,
+        status: PC_STATUS_TRADING,
+    }
+}
+
+async fn upd_price_instruction(
+    sim: &mut PythSimulator,
+    publisher: &Keypair,
+    price_account: Pubkey,
+    price: i64,
+) -> Instruction {
+    sim.upd_price_instructions(
+        publisher,
+        &HashMap::from_iter(once(("".to_string(), price_account))),
+        &HashMap::from_iter(once(("".to_string(), quote(price)))),
+    )
+    .await
+    .unwrap()
+    .remove(0)
+}
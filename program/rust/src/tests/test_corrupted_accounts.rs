@@ -0,0 +1,316 @@
+use {
+    crate::{
+        c_oracle_header::{
+            PC_ACCTYPE_PRICE,
+            PC_ACCTYPE_PRODUCT,
+            PC_MAP_TABLE_SIZE,
+            PC_NUM_COMP,
+            PC_STATUS_TRADING,
+            PC_VERSION,
+        },
+        error::OracleError,
+        layout::{
+            ACCOUNT_HEADER_ACCOUNT_TYPE_OFFSET,
+            ACCOUNT_HEADER_MAGIC_OFFSET,
+            ACCOUNT_HEADER_SIZE,
+            ACCOUNT_HEADER_VERSION_OFFSET,
+            MAPPING_ACCOUNT_NUMBER_OF_PRODUCTS_OFFSET,
+            PRICE_ACCOUNT_NUM_OFFSET,
+            PRICE_ACCOUNT_PRODUCT_ACCOUNT_OFFSET,
+            PRODUCT_ACCOUNT_FIRST_PRICE_ACCOUNT_OFFSET,
+        },
+        tests::pyth_simulator::{
+            InstructionFailure,
+            PythSimulator,
+            Quote,
+            TransactionFailure,
+        },
+    },
+    solana_program::{
+        instruction::InstructionError,
+        native_token::LAMPORTS_PER_SOL,
+        pubkey::Pubkey,
+    },
+    solana_program_test::BanksClientError,
+    solana_sdk::{
+        signature::Keypair,
+        signer::Signer,
+    },
+};
+
+/// A way to corrupt an account that no instruction of the program would write, and the error
+/// that the instructions loading the corrupted account must fail with
+struct Corruption {
+    name:     &'static str,
+    tamper:   Box<dyn Fn(&mut Vec<u8>)>,
+    expected: InstructionFailure,
+}
+
+impl Corruption {
+    fn new(
+        name: &'static str,
+        tamper: impl Fn(&mut Vec<u8>) + 'static,
+        expected: impl Into<InstructionFailure>,
+    ) -> Self {
+        Corruption {
+            name,
+            tamper: Box::new(tamper),
+            expected: expected.into(),
+        }
+    }
+}
+
+fn write_u32(data: &mut [u8], offset: usize, value: u32) {
+    data[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+/// The corruptions of the header of an account, which every account type rejects.
+/// `other_account_type` is the type of another kind of account.
+fn header_corruptions(other_account_type: u32) -> Vec<Corruption> {
+    vec![
+        Corruption::new(
+            "flipped magic number",
+            |data| data[ACCOUNT_HEADER_MAGIC_OFFSET] ^= 0xff,
+            OracleError::InvalidAccountHeader,
+        ),
+        Corruption::new(
+            "other version",
+            |data| write_u32(data, ACCOUNT_HEADER_VERSION_OFFSET, PC_VERSION + 1),
+            OracleError::InvalidAccountHeader,
+        ),
+        Corruption::new(
+            "other account type",
+            move |data| write_u32(data, ACCOUNT_HEADER_ACCOUNT_TYPE_OFFSET, other_account_type),
+            OracleError::InvalidAccountHeader,
+        ),
+        Corruption::new(
+            "truncated to its header",
+            |data| data.truncate(ACCOUNT_HEADER_SIZE),
+            OracleError::AccountTooSmall,
+        ),
+        Corruption::new("emptied", |data| data.clear(), OracleError::AccountTooSmall),
+    ]
+}
+
+/// The instructions that load the accounts of a `Feed`
+#[derive(Clone, Copy, Debug)]
+enum Instruction {
+    AddProduct,
+    DelProduct,
+    UpdProduct,
+    AddPrice,
+    DelPrice,
+    AddPublisher,
+    SetMinPub,
+    UpdPrice,
+}
+
+/// A mapping account with a product account that has a price account with a publisher
+struct Feed {
+    mapping:   Keypair,
+    product:   Keypair,
+    price:     Keypair,
+    publisher: Keypair,
+}
+
+impl Feed {
+    async fn new(sim: &mut PythSimulator) -> Self {
+        let mapping = sim.init_mapping().await.unwrap();
+        let product = sim.add_product(&mapping).await.unwrap();
+        let price = sim.add_price(&product, -5).await.unwrap();
+        let publisher = Keypair::new();
+        sim.airdrop(&publisher.pubkey(), 100 * LAMPORTS_PER_SOL)
+            .await
+            .unwrap();
+        sim.add_publisher(price.pubkey(), publisher.pubkey())
+            .await
+            .unwrap();
+        Feed {
+            mapping,
+            product,
+            price,
+            publisher,
+        }
+    }
+
+    async fn run(
+        &self,
+        sim: &mut PythSimulator,
+        instruction: Instruction,
+    ) -> Result<(), BanksClientError> {
+        match instruction {
+            Instruction::AddProduct => sim.add_product(&self.mapping).await.map(|_| ()),
+            Instruction::DelProduct => sim.del_product(&self.mapping, &self.product).await,
+            Instruction::UpdProduct => {
+                sim.upd_product(&self.product, &[("symbol", "Crypto.BTC/USD")])
+                    .await
+            }
+            Instruction::AddPrice => sim.add_price(&self.product, -5).await.map(|_| ()),
+            Instruction::DelPrice => sim.del_price(&self.product, &self.price).await,
+            Instruction::AddPublisher => {
+                sim.add_publisher(self.price.pubkey(), Pubkey::new_unique())
+                    .await
+            }
+            Instruction::SetMinPub => sim.set_min_pub(self.price.pubkey(), 2).await,
+            Instruction::UpdPrice => {
+                let quote = Quote {
+                    price:      100,
+                    confidence: 1,
+                    status:     PC_STATUS_TRADING,
+                };
+                sim.upd_price(&self.publisher, self.price.pubkey(), quote)
+                    .await
+            }
+        }
+    }
+}
+
+/// Run each of `instructions` on the feed after each of `corruptions` of `account`, from the same
+/// initial state. The instructions must fail with the expected error of the corruption and leave
+/// the corrupted account as it is.
+async fn check_corruptions(
+    sim: &mut PythSimulator,
+    feed: &Feed,
+    account: Pubkey,
+    corruptions: &[Corruption],
+    instructions: &[Instruction],
+) {
+    let snapshot = sim.snapshot().await;
+    for corruption in corruptions {
+        for instruction in instructions {
+            sim.tamper_account(account, &corruption.tamper).await;
+            let tampered = sim.get_account(account).await.unwrap().data;
+
+            let (index, failure) = feed
+                .run(sim, *instruction)
+                .await
+                .unwrap_instruction_failure();
+            assert_eq!(
+                (index, &failure),
+                (0, &corruption.expected),
+                "{instruction:?} on an account with a {}",
+                corruption.name
+            );
+            assert_eq!(
+                sim.get_account(account).await.unwrap().data,
+                tampered,
+                "{instruction:?} on an account with a {}",
+                corruption.name
+            );
+            sim.restore(&snapshot);
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_corrupted_mapping_account() {
+    let mut sim = PythSimulator::new().await;
+    let feed = Feed::new(&mut sim).await;
+
+    let mut corruptions = header_corruptions(PC_ACCTYPE_PRODUCT);
+    corruptions.push(Corruption::new(
+        "product count beyond the product list",
+        |data| {
+            write_u32(
+                data,
+                MAPPING_ACCOUNT_NUMBER_OF_PRODUCTS_OFFSET,
+                PC_MAP_TABLE_SIZE + 1,
+            )
+        },
+        OracleError::CorruptedAccount,
+    ));
+    check_corruptions(
+        &mut sim,
+        &feed,
+        feed.mapping.pubkey(),
+        &corruptions,
+        &[Instruction::AddProduct, Instruction::DelProduct],
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_corrupted_product_account() {
+    let mut sim = PythSimulator::new().await;
+    let feed = Feed::new(&mut sim).await;
+
+    check_corruptions(
+        &mut sim,
+        &feed,
+        feed.product.pubkey(),
+        &header_corruptions(PC_ACCTYPE_PRICE),
+        &[
+            Instruction::UpdProduct,
+            Instruction::AddPrice,
+            Instruction::DelPrice,
+            Instruction::DelProduct,
+        ],
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_corrupted_price_account() {
+    let mut sim = PythSimulator::new().await;
+    let feed = Feed::new(&mut sim).await;
+
+    let mut corruptions = header_corruptions(PC_ACCTYPE_PRODUCT);
+    corruptions.push(Corruption::new(
+        "publisher count beyond the components",
+        |data| write_u32(data, PRICE_ACCOUNT_NUM_OFFSET, PC_NUM_COMP + 1),
+        OracleError::CorruptedAccount,
+    ));
+    check_corruptions(
+        &mut sim,
+        &feed,
+        feed.price.pubkey(),
+        &corruptions,
+        &[
+            Instruction::UpdPrice,
+            Instruction::AddPublisher,
+            Instruction::SetMinPub,
+            Instruction::DelPrice,
+        ],
+    )
+    .await;
+}
+
+/// A price account is only unlinked from its product if each of them points to the other
+#[tokio::test]
+async fn test_scrambled_price_list() {
+    let mut sim = PythSimulator::new().await;
+    let feed = Feed::new(&mut sim).await;
+    let scrambled = |offset: usize| {
+        move |data: &mut Vec<u8>| {
+            data[offset..offset + 32].copy_from_slice(&Pubkey::new_unique().to_bytes())
+        }
+    };
+
+    check_corruptions(
+        &mut sim,
+        &feed,
+        feed.product.pubkey(),
+        &[Corruption::new(
+            "scrambled first price account",
+            scrambled(PRODUCT_ACCOUNT_FIRST_PRICE_ACCOUNT_OFFSET),
+            InstructionError::InvalidArgument,
+        )],
+        &[Instruction::DelPrice],
+    )
+    .await;
+    check_corruptions(
+        &mut sim,
+        &feed,
+        feed.price.pubkey(),
+        &[Corruption::new(
+            "scrambled product account",
+            scrambled(PRICE_ACCOUNT_PRODUCT_ACCOUNT_OFFSET),
+            InstructionError::InvalidArgument,
+        )],
+        &[Instruction::DelPrice],
+    )
+    .await;
+
+    // The feed is untouched by the failed deletions
+    sim.del_price(&feed.product, &feed.price).await.unwrap();
+}
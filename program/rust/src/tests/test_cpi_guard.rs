@@ -0,0 +1,135 @@
+use {
+    crate::{
+        accounts::PriceAccount,
+        c_oracle_header::{
+            MAX_CPI_CALLERS,
+            PC_STATUS_TRADING,
+        },
+        error::OracleError,
+        tests::pyth_simulator::{
+            PythSimulator,
+            Quote,
+            TransactionFailure,
+        },
+    },
+    solana_program::{
+        instruction::{
+            AccountMeta,
+            Instruction,
+        },
+        pubkey::Pubkey,
+    },
+    solana_sdk::signer::Signer,
+    std::collections::HashMap,
+};
+
+fn quote(price: i64) -> Quote {
+    Quote {
+        price,
+        confidence: 1,
+        status: PC_STATUS_TRADING,
+    }
+}
+
+#[tokio::test]
+async fn test_cpi_guard() {
+    let mut sim = PythSimulator::new().await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(1).await;
+    let price = price_keypair.pubkey();
+    let publisher = &publishers[0];
+
+    // Without a CPI guard, other programs can invoke price updates
+    sim.upd_price_via_cpi(publisher, price, quote(100), false)
+        .await
+        .unwrap();
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(price_data.comp_[0].latest_.price_, 100);
+
+    sim.set_cpi_guard(price, true, [Pubkey::default(); MAX_CPI_CALLERS])
+        .await
+        .unwrap();
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(price_data.cpi_guard_, 1);
+
+    // Top-level price updates are accepted, but only with the Instructions sysvar to tell
+    sim.warp_to_slot(2).await.unwrap();
+    sim.upd_price(publisher, price, quote(110))
+        .await
+        .assert_fails_with(OracleError::UnauthorizedCpi);
+    sim.upd_price_batch_ix(
+        publisher,
+        &HashMap::from([("".to_string(), price)]),
+        &HashMap::from([("".to_string(), quote(110))]),
+    )
+    .await
+    .assert_fails_with(OracleError::UnauthorizedCpi);
+    sim.upd_price_with_instructions_sysvar(publisher, price, quote(110))
+        .await
+        .unwrap();
+
+    // Price updates invoked by other programs are rejected
+    sim.warp_to_slot(3).await.unwrap();
+    for with_instructions_sysvar in [false, true] {
+        sim.upd_price_via_cpi(publisher, price, quote(120), with_instructions_sysvar)
+            .await
+            .assert_fails_with(OracleError::UnauthorizedCpi);
+    }
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(price_data.comp_[0].latest_.price_, 110);
+
+    // Unless they are allowed
+    let mut allowed_callers = [Pubkey::default(); MAX_CPI_CALLERS];
+    allowed_callers[1] = sim.cpi_caller_id;
+    sim.set_cpi_guard(price, true, allowed_callers)
+        .await
+        .unwrap();
+    sim.upd_price_via_cpi(publisher, price, quote(120), true)
+        .await
+        .unwrap();
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(price_data.comp_[0].latest_.price_, 120);
+    assert_eq!(price_data.cpi_callers_, allowed_callers);
+
+    // But only as direct callers: the allowed program can't let another program invoke the update
+    sim.warp_to_slot(4).await.unwrap();
+    let instruction = sim
+        .upd_price_via_cpi_instruction(publisher, price, quote(125), true)
+        .await
+        .unwrap();
+    let mut accounts = vec![AccountMeta::new_readonly(sim.cpi_caller_id, false)];
+    accounts.extend(instruction.accounts);
+    let nested_instruction =
+        Instruction::new_with_bytes(sim.cpi_caller_id, &instruction.data, accounts);
+    sim.process_ixs(&[nested_instruction], &vec![publisher], publisher)
+        .await
+        .assert_fails_with(OracleError::UnauthorizedCpi);
+
+    // Disabling the guard accepts price updates from any program again
+    sim.set_cpi_guard(price, false, [Pubkey::default(); MAX_CPI_CALLERS])
+        .await
+        .unwrap();
+    sim.warp_to_slot(5).await.unwrap();
+    sim.upd_price_via_cpi(publisher, price, quote(130), false)
+        .await
+        .unwrap();
+    sim.warp_to_slot(6).await.unwrap();
+    sim.upd_price(publisher, price, quote(140)).await.unwrap();
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(price_data.cpi_guard_, 0);
+    assert_eq!(price_data.comp_[0].latest_.price_, 140);
+}
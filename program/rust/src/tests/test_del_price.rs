@@ -1,9 +1,36 @@
 use {
     crate::{
-        accounts::ProductAccount,
-        tests::pyth_simulator::PythSimulator,
+        accounts::{
+            AccountHeader,
+            PermissionAccount,
+            PriceAccount,
+            ProductAccount,
+            PythAccount,
+        },
+        c_oracle_header::PC_VERSION,
+        deserialize::{
+            load_account_as,
+            load_checked,
+        },
+        error::OracleError,
+        instruction::{
+            CommandHeader,
+            OracleCommand,
+        },
+        processor::process_instruction,
+        tests::{
+            pyth_simulator::{
+                PythSimulator,
+                TransactionFailure,
+            },
+            test_utils::AccountSetup,
+        },
+    },
+    bytemuck::bytes_of,
+    solana_program::{
+        instruction::InstructionError,
+        pubkey::Pubkey,
     },
-    solana_program::pubkey::Pubkey,
     solana_sdk::signer::Signer,
 };
 
@@ -21,11 +48,21 @@ async fn test_del_price() {
     assert!(sim.get_account(price1.pubkey()).await.is_some());
     assert!(sim.get_account(price2_1.pubkey()).await.is_some());
 
-    assert!(sim.del_price(&product2, &price1).await.is_err());
-    assert!(sim.del_price(&product1, &price2_1).await.is_err());
-    assert!(sim.del_price(&product1, &price2_2).await.is_err());
-    assert!(sim.del_price(&product3, &price2_1).await.is_err());
-    assert!(sim.del_price(&product3, &price2_2).await.is_err());
+    sim.del_price(&product2, &price1)
+        .await
+        .assert_fails_with(InstructionError::InvalidArgument);
+    sim.del_price(&product1, &price2_1)
+        .await
+        .assert_fails_with(InstructionError::InvalidArgument);
+    sim.del_price(&product1, &price2_2)
+        .await
+        .assert_fails_with(InstructionError::InvalidArgument);
+    sim.del_price(&product3, &price2_1)
+        .await
+        .assert_fails_with(InstructionError::InvalidArgument);
+    sim.del_price(&product3, &price2_2)
+        .await
+        .assert_fails_with(InstructionError::InvalidArgument);
 
     sim.del_price(&product1, &price1).await.unwrap();
     assert!(sim.get_account(price1.pubkey()).await.is_none());
@@ -38,7 +75,9 @@ async fn test_del_price() {
 
 
     // price2_1 is the 2nd item in the linked list since price2_2 got added after t.
-    assert!(sim.del_price(&product2, &price2_1).await.is_err());
+    sim.del_price(&product2, &price2_1)
+        .await
+        .assert_fails_with(InstructionError::InvalidArgument);
     // Can delete the accounts in the opposite order though
     assert!(sim.del_price(&product2, &price2_2).await.is_ok());
     assert!(sim.del_price(&product2, &price2_1).await.is_ok());
@@ -53,3 +92,128 @@ async fn test_del_price() {
 
     assert!(product2_data.first_price_account == Pubkey::default());
 }
+
+#[tokio::test]
+async fn test_del_price_relink_and_recipient() {
+    let mut sim = PythSimulator::new().await;
+    let mapping_keypair = sim.init_mapping().await.unwrap();
+    let product = sim.add_product(&mapping_keypair).await.unwrap();
+    let price_1 = sim.add_price(&product, -8).await.unwrap();
+    let price_2 = sim.add_price(&product, -8).await.unwrap();
+    let price_3 = sim.add_price(&product, -8).await.unwrap();
+
+    // New price accounts are added at the head of the list: price_3 -> price_2 -> price_1
+    let product_data = sim
+        .get_account_data_as::<ProductAccount>(product.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(product_data.first_price_account, price_3.pubkey());
+
+    let recipient = Pubkey::new_unique();
+    let price_3_lamports = sim.get_account(price_3.pubkey()).await.unwrap().lamports;
+
+    sim.del_price_with_recipient(&product, &price_3, Some(recipient))
+        .await
+        .unwrap();
+    assert!(sim.get_account(price_3.pubkey()).await.is_none());
+    assert_eq!(
+        sim.get_account(recipient).await.unwrap().lamports,
+        price_3_lamports
+    );
+
+    let product_data = sim
+        .get_account_data_as::<ProductAccount>(product.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(product_data.first_price_account, price_2.pubkey());
+    let price_2_data = sim
+        .get_account_data_as::<PriceAccount>(price_2.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(price_2_data.next_price_account, price_1.pubkey());
+
+    // The price account can't receive its own lamports
+    assert!(sim
+        .del_price_with_recipient(&product, &price_2, Some(price_2.pubkey()))
+        .await
+        .is_err());
+
+    sim.del_price(&product, &price_2).await.unwrap();
+    let product_data = sim
+        .get_account_data_as::<ProductAccount>(product.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(product_data.first_price_account, price_1.pubkey());
+
+    sim.del_price(&product, &price_1).await.unwrap();
+    let product_data = sim
+        .get_account_data_as::<ProductAccount>(product.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(product_data.first_price_account, Pubkey::default());
+}
+
+#[test]
+fn test_del_price_zeroes_header() {
+    let program_id = Pubkey::new_unique();
+
+    let mut funding_setup = AccountSetup::new_funding();
+    let funding_account = funding_setup.as_account_info();
+
+    let mut product_setup = AccountSetup::new::<ProductAccount>(&program_id);
+    let product_account = product_setup.as_account_info();
+    ProductAccount::initialize(&product_account, PC_VERSION).unwrap();
+
+    let mut price_setup = AccountSetup::new::<PriceAccount>(&program_id);
+    let price_account = price_setup.as_account_info();
+    PriceAccount::initialize(&price_account, PC_VERSION).unwrap();
+
+    let mut permissions_setup = AccountSetup::new_permission(&program_id);
+    let permissions_account = permissions_setup.as_account_info();
+
+    {
+        let mut permissions_account_data =
+            PermissionAccount::initialize(&permissions_account, PC_VERSION).unwrap();
+        permissions_account_data.master_authority = *funding_account.key;
+    }
+
+    {
+        let mut product_data =
+            load_checked::<ProductAccount>(&product_account, PC_VERSION).unwrap();
+        product_data.first_price_account = *price_account.key;
+        let mut price_data = load_checked::<PriceAccount>(&price_account, PC_VERSION).unwrap();
+        price_data.product_account = *product_account.key;
+    }
+
+    let funding_lamports = funding_account.lamports();
+    let price_lamports = price_account.lamports();
+
+    process_instruction(
+        &program_id,
+        &[
+            funding_account.clone(),
+            product_account.clone(),
+            price_account.clone(),
+            permissions_account.clone(),
+        ],
+        bytes_of::<CommandHeader>(&OracleCommand::DelPrice.into()),
+    )
+    .unwrap();
+
+    {
+        let header = load_account_as::<AccountHeader>(&price_account).unwrap();
+        assert_eq!(header.magic_number, 0);
+        assert_eq!(header.version, 0);
+        assert_eq!(header.account_type, 0);
+        assert_eq!(header.size, 0);
+    }
+    assert_eq!(price_account.lamports(), 0);
+    assert_eq!(
+        funding_account.lamports(),
+        funding_lamports + price_lamports
+    );
+    assert_eq!(
+        load_checked::<PriceAccount>(&price_account, PC_VERSION).err(),
+        Some(OracleError::InvalidAccountHeader.into())
+    );
+}
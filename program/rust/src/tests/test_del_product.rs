@@ -1,9 +1,15 @@
 use {
     crate::{
         accounts::MappingAccount,
-        tests::pyth_simulator::PythSimulator,
+        tests::pyth_simulator::{
+            PythSimulator,
+            TransactionFailure,
+        },
+    },
+    solana_program::{
+        instruction::InstructionError,
+        pubkey::Pubkey,
     },
-    solana_program::pubkey::Pubkey,
     solana_sdk::signer::Signer,
     std::mem::{
         size_of,
@@ -30,12 +36,13 @@ async fn test_del_product() {
     assert!(sim.get_account(product4.pubkey()).await.is_some());
 
     // Can't delete a product with a price account
-    assert!(sim.del_product(&mapping_keypair, &product3).await.is_err());
+    sim.del_product(&mapping_keypair, &product3)
+        .await
+        .assert_fails_with(InstructionError::InvalidArgument);
     // Can't delete mismatched product/mapping accounts
-    assert!(sim
-        .del_product(&mapping_keypair, &product1_2)
+    sim.del_product(&mapping_keypair, &product1_2)
         .await
-        .is_err());
+        .assert_fails_with(InstructionError::InvalidArgument);
 
     assert!(sim.del_product(&mapping_keypair, &product2).await.is_ok());
 
@@ -69,6 +76,74 @@ async fn test_del_product() {
     ));
 }
 
+#[tokio::test]
+async fn test_del_product_head_middle_tail() {
+    let mut sim = PythSimulator::new().await;
+    let mapping_keypair = sim.init_mapping().await.unwrap();
+    let product1 = sim.add_product(&mapping_keypair).await.unwrap();
+    let product2 = sim.add_product(&mapping_keypair).await.unwrap();
+    let product3 = sim.add_product(&mapping_keypair).await.unwrap();
+    let product4 = sim.add_product(&mapping_keypair).await.unwrap();
+    let product5 = sim.add_product(&mapping_keypair).await.unwrap();
+
+    // Removing the tail just shrinks the list
+    assert!(sim.del_product(&mapping_keypair, &product5).await.is_ok());
+    let mapping_data = sim
+        .get_account_data_as::<MappingAccount>(mapping_keypair.pubkey())
+        .await
+        .unwrap();
+    assert!(mapping_product_list_equals(
+        &mapping_data,
+        vec![
+            product1.pubkey(),
+            product2.pubkey(),
+            product3.pubkey(),
+            product4.pubkey()
+        ]
+    ));
+
+    // Removing the head moves the tail into the empty slot
+    assert!(sim.del_product(&mapping_keypair, &product1).await.is_ok());
+    let mapping_data = sim
+        .get_account_data_as::<MappingAccount>(mapping_keypair.pubkey())
+        .await
+        .unwrap();
+    assert!(mapping_product_list_equals(
+        &mapping_data,
+        vec![product4.pubkey(), product2.pubkey(), product3.pubkey()]
+    ));
+
+    // Removing from the middle moves the tail into the empty slot
+    assert!(sim.del_product(&mapping_keypair, &product2).await.is_ok());
+    let mapping_data = sim
+        .get_account_data_as::<MappingAccount>(mapping_keypair.pubkey())
+        .await
+        .unwrap();
+    assert!(mapping_product_list_equals(
+        &mapping_data,
+        vec![product4.pubkey(), product3.pubkey()]
+    ));
+
+    // Deleted products can't be deleted again
+    assert!(sim.del_product(&mapping_keypair, &product2).await.is_err());
+
+    assert!(sim.del_product(&mapping_keypair, &product4).await.is_ok());
+    assert!(sim.del_product(&mapping_keypair, &product3).await.is_ok());
+    let mapping_data = sim
+        .get_account_data_as::<MappingAccount>(mapping_keypair.pubkey())
+        .await
+        .unwrap();
+    assert!(mapping_product_list_equals(&mapping_data, vec![]));
+    assert!(mapping_data
+        .products_list
+        .iter()
+        .all(|key| *key == Pubkey::default()));
+
+    for product in [&product1, &product2, &product3, &product4, &product5] {
+        assert!(sim.get_account(product.pubkey()).await.is_none());
+    }
+}
+
 /// Returns true if the list of products in `mapping_data` contains the keys in `expected` (in the
 /// same order). Also checks `mapping_data.num_` and `size_`.
 fn mapping_product_list_equals(mapping_data: &MappingAccount, expected: Vec<Pubkey>) -> bool {
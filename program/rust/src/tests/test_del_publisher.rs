@@ -4,6 +4,7 @@ use {
             PermissionAccount,
             PriceAccount,
             PriceInfo,
+            PublisherStats,
             PythAccount,
         },
         c_oracle_header::{
@@ -21,6 +22,7 @@ use {
         processor::process_instruction,
         tests::test_utils::AccountSetup,
     },
+    bytemuck::Zeroable,
     solana_program::pubkey::Pubkey,
     std::mem::size_of,
 };
@@ -103,6 +105,8 @@ fn test_del_publisher() {
         price_data.comp_[1].latest_ = p2;
         price_data.comp_[0].pub_ = publisher;
         price_data.comp_[1].pub_ = publisher2;
+        price_data.publisher_stats_[0].num_rounds_ = 3;
+        price_data.publisher_stats_[1].num_rounds_ = 7;
     }
 
     // Delete publisher at position 0
@@ -136,6 +140,9 @@ fn test_del_publisher() {
         assert_eq!(price_data.header.size, PriceAccount::INITIAL_SIZE);
         assert!(price_data.comp_[0].pub_ == publisher2);
         assert!(price_data.comp_[1].pub_ == Pubkey::default());
+        // The statistics move along with the price components
+        assert_eq!(price_data.publisher_stats_[0].num_rounds_, 7);
+        assert_eq!(price_data.publisher_stats_[1], PublisherStats::zeroed());
 
         price_data.num_ = 2;
         price_data.comp_[0].latest_ = p2;
@@ -0,0 +1,200 @@
+use {
+    crate::{
+        accounts::{
+            DerivedPriceAccount,
+            PriceAccount,
+        },
+        aggregation::price_ratio,
+        c_oracle_header::{
+            PC_STATUS_TRADING,
+            PC_STATUS_UNKNOWN,
+        },
+        error::OracleError,
+        tests::pyth_simulator::{
+            PythSimulator,
+            Quote,
+            TransactionFailure,
+        },
+    },
+    quickcheck_macros::quickcheck,
+    solana_program::native_token::LAMPORTS_PER_SOL,
+    solana_sdk::{
+        signature::Keypair,
+        signer::Signer,
+    },
+};
+
+/// The ratio `base / quote` of two prices given as `(price, conf, expo)`, at exponent `expo`,
+/// computed with floats from the closed-form expressions
+fn closed_form_ratio(base: (i64, u64, i32), quote: (i64, u64, i32), expo: i32) -> (f64, f64) {
+    let value = |(price, conf, price_expo): (i64, u64, i32)| {
+        let scale = 10f64.powi(price_expo);
+        (price as f64 * scale, conf as f64 * scale)
+    };
+    let (base_price, base_conf) = value(base);
+    let (quote_price, quote_conf) = value(quote);
+    let ratio = base_price / quote_price;
+    let conf =
+        (base_conf * quote_price + base_price.abs() * quote_conf) / (quote_price * quote_price);
+    let scale = 10f64.powi(-expo);
+    (ratio * scale, conf * scale)
+}
+
+/// Whether `value` is `expected`, up to the float errors of `expected` and `rounding` units
+fn is_close(value: f64, expected: f64, rounding: f64) -> bool {
+    (value - expected).abs() <= expected.abs() * 1e-12 + rounding
+}
+
+#[test]
+fn test_price_ratio() {
+    // ETH/BTC from ETH/USD = 3000 ± 1.5 and BTC/USD = 60000 ± 30 is 0.05 ± 0.00005
+    let eth = (300_000_000, 150_000, -5);
+    let btc = (6_000_000_000, 3_000_000, -5);
+    assert_eq!(price_ratio(eth, btc, -8), Some((5_000_000, 5_000)));
+    assert_eq!(price_ratio(eth, btc, -5), Some((5_000, 5)));
+    // The parents don't need to have the same exponent
+    assert_eq!(
+        price_ratio((300_000_000_000, 150_000_000, -8), btc, -8),
+        Some((5_000_000, 5_000))
+    );
+    assert_eq!(price_ratio(btc, eth, 0), Some((20, 1)));
+
+    // The price is rounded toward 0 and the confidence up
+    assert_eq!(price_ratio((1, 0, 0), (3, 0, 0), -2), Some((33, 0)));
+    assert_eq!(price_ratio((1, 1, 0), (3, 0, 0), -2), Some((33, 34)));
+    assert_eq!(price_ratio((-1, 1, 0), (3, 0, 0), -2), Some((-33, 34)));
+    assert_eq!(
+        price_ratio((-300_000_000, 150_000, -5), btc, -8),
+        Some((-5_000_000, 5_000))
+    );
+
+    // There is no ratio to a price that isn't positive, or that doesn't fit
+    assert_eq!(price_ratio(eth, (0, 1, -5), -8), None);
+    assert_eq!(price_ratio(eth, (-6_000_000_000, 3_000_000, -5), -8), None);
+    assert_eq!(price_ratio((i64::MAX, 0, 0), (1, 0, 0), -1), None);
+    assert_eq!(price_ratio((1, u64::MAX, 0), (1, 0, 0), -1), None);
+    assert_eq!(price_ratio(eth, btc, i32::MIN), None);
+}
+
+#[quickcheck]
+fn test_price_ratio_closed_form(
+    base_price: i32,
+    base_conf: u16,
+    quote_price: u32,
+    quote_conf: u16,
+) -> bool {
+    let base = (i64::from(base_price), u64::from(base_conf), -5);
+    let quote = (i64::from(quote_price) + 1, u64::from(quote_conf), -5);
+    let (price, conf) = price_ratio(base, quote, -8).unwrap();
+    let (expected_price, expected_conf) = closed_form_ratio(base, quote, -8);
+    is_close(price as f64, expected_price, 1.0)
+        && is_close(conf as f64, expected_conf, 1.0)
+        && conf as f64 >= expected_conf * (1.0 - 1e-12)
+}
+
+#[tokio::test]
+async fn test_upd_derived() {
+    let mut sim = PythSimulator::new().await;
+    let (eth_keypair, eth_publishers) = sim.setup_feed_with_publishers(1).await;
+    let (btc_keypair, btc_publishers) = sim.setup_feed_with_publishers(1).await;
+    let (eth, btc) = (eth_keypair.pubkey(), btc_keypair.pubkey());
+    let quote = |price, confidence| Quote {
+        price,
+        confidence,
+        status: PC_STATUS_TRADING,
+    };
+    let feeds = [
+        (eth, &eth_publishers[0], quote(300_012_345, 150_000)),
+        (btc, &btc_publishers[0], quote(6_000_050_000, 2_500_000)),
+    ];
+    for (price_account, _, _) in feeds {
+        sim.set_min_pub(price_account, 1).await.unwrap();
+    }
+    // The quotes of slot 2 are aggregated by the updates of slot 3
+    for slot in [2, 3] {
+        sim.warp_to_slot(slot).await.unwrap();
+        for (price_account, publisher, quote) in feeds {
+            sim.upd_price(publisher, price_account, quote)
+                .await
+                .unwrap();
+        }
+    }
+
+    let derived = sim.init_derived_price(eth, btc, -8).await.unwrap().pubkey();
+    let derived_data = sim.get_pyth_account::<DerivedPriceAccount>(derived).await;
+    assert_eq!(derived_data.exponent, -8);
+    assert_eq!(derived_data.base_price_account_, eth);
+    assert_eq!(derived_data.quote_price_account_, btc);
+    assert_eq!(derived_data.agg_.status_, PC_STATUS_UNKNOWN);
+
+    // Anyone can update the ratio
+    let cranker = Keypair::new();
+    sim.airdrop(&cranker.pubkey(), LAMPORTS_PER_SOL)
+        .await
+        .unwrap();
+    sim.warp_to_slot(4).await.unwrap();
+    sim.upd_derived(&cranker, derived, eth, btc).await.unwrap();
+
+    let eth_data = sim.get_pyth_account::<PriceAccount>(eth).await;
+    let btc_data = sim.get_pyth_account::<PriceAccount>(btc).await;
+    assert_eq!(eth_data.agg_.status_, PC_STATUS_TRADING);
+    assert_eq!(btc_data.agg_.status_, PC_STATUS_TRADING);
+    let base = (eth_data.agg_.price_, eth_data.agg_.conf_, eth_data.exponent);
+    let quote = (btc_data.agg_.price_, btc_data.agg_.conf_, btc_data.exponent);
+
+    let derived_data = sim.get_pyth_account::<DerivedPriceAccount>(derived).await;
+    assert_eq!(derived_data.agg_.status_, PC_STATUS_TRADING);
+    assert_eq!(
+        (derived_data.agg_.price_, derived_data.agg_.conf_),
+        price_ratio(base, quote, -8).unwrap()
+    );
+    let (expected_price, expected_conf) = closed_form_ratio(base, quote, -8);
+    assert!(is_close(
+        derived_data.agg_.price_ as f64,
+        expected_price,
+        1.0
+    ));
+    assert!(is_close(derived_data.agg_.conf_ as f64, expected_conf, 1.0));
+    assert_eq!(
+        derived_data.agg_.pub_slot_,
+        eth_data.agg_.pub_slot_.min(btc_data.agg_.pub_slot_)
+    );
+    assert_eq!(
+        derived_data.timestamp_,
+        eth_data.timestamp_.min(btc_data.timestamp_)
+    );
+    assert_eq!(derived_data.last_slot_, 4);
+
+    // The SDK computes the same ratio from the prices of the parents
+    let price = derived_data.get_price_unchecked();
+    assert_eq!(
+        eth_data
+            .get_price_unchecked()
+            .ratio(&btc_data.get_price_unchecked(), -8),
+        Some(price)
+    );
+    let clock = sim.get_clock().await.unwrap();
+    assert_eq!(
+        derived_data.get_price_no_older_than(&clock, u64::MAX),
+        Some(price)
+    );
+
+    // The parents must be given in order
+    sim.upd_derived(&cranker, derived, btc, eth)
+        .await
+        .assert_fails_with(OracleError::InvalidDerivedPrice);
+
+    // Without a quote aggregate, the ratio is not published but the last one is kept
+    sim.halt_price(btc).await.unwrap();
+    sim.warp_to_slot(5).await.unwrap();
+    sim.upd_derived(&cranker, derived, eth, btc).await.unwrap();
+    let derived_data = sim.get_pyth_account::<DerivedPriceAccount>(derived).await;
+    assert_eq!(derived_data.agg_.status_, PC_STATUS_UNKNOWN);
+    assert!(!derived_data.is_trading());
+    assert_eq!(derived_data.last_slot_, 4);
+    assert_eq!(
+        (derived_data.agg_.price_, derived_data.agg_.conf_),
+        (price.price, price.conf)
+    );
+    assert_eq!(derived_data.get_price_no_older_than(&clock, u64::MAX), None);
+}
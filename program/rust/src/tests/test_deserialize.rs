@@ -0,0 +1,189 @@
+use {
+    crate::{
+        accounts::{
+            AccountHeader,
+            MappingAccount,
+            PriceAccount,
+            ProductAccount,
+            PythAccount,
+        },
+        c_oracle_header::{
+            PC_STATUS_TRADING,
+            PC_VERSION,
+        },
+        deserialize::{
+            load_account_as,
+            load_account_as_mut,
+            load_checked,
+            load_mut,
+        },
+        error::OracleError,
+        instruction::{
+            OracleCommand,
+            UpdPriceArgs,
+        },
+        processor::process_instruction,
+        tests::test_utils::{
+            update_clock_slot,
+            AccountSetup,
+        },
+        utils::valid_fresh_account,
+    },
+    solana_program::pubkey::Pubkey,
+    std::mem::size_of,
+};
+
+#[test]
+fn test_load_account_too_small() {
+    // Funding accounts have no data
+    let mut funding_setup = AccountSetup::new_funding();
+    let funding_account = funding_setup.as_account_info();
+
+    assert_eq!(
+        load_account_as::<AccountHeader>(&funding_account).err(),
+        Some(OracleError::AccountTooSmall.into())
+    );
+    assert_eq!(
+        load_account_as_mut::<AccountHeader>(&funding_account).err(),
+        Some(OracleError::AccountTooSmall.into())
+    );
+    assert!(!valid_fresh_account(&funding_account));
+
+    // A product account is too small to hold a price account
+    let program_id = Pubkey::new_unique();
+    let mut product_setup = AccountSetup::new::<ProductAccount>(&program_id);
+    let product_account = product_setup.as_account_info();
+    ProductAccount::initialize(&product_account, PC_VERSION).unwrap();
+
+    assert_eq!(
+        load_account_as::<PriceAccount>(&product_account).err(),
+        Some(OracleError::AccountTooSmall.into())
+    );
+    assert_eq!(
+        load_checked::<PriceAccount>(&product_account, PC_VERSION).err(),
+        Some(OracleError::AccountTooSmall.into())
+    );
+}
+
+#[test]
+fn test_load_checked_invalid_header() {
+    let program_id = Pubkey::new_unique();
+
+    let mut price_setup = AccountSetup::new::<PriceAccount>(&program_id);
+    let price_account = price_setup.as_account_info();
+    PriceAccount::initialize(&price_account, PC_VERSION).unwrap();
+
+    assert!(load_checked::<PriceAccount>(&price_account, PC_VERSION).is_ok());
+
+    // Wrong version
+    assert_eq!(
+        load_checked::<PriceAccount>(&price_account, PC_VERSION + 1).err(),
+        Some(OracleError::InvalidAccountHeader.into())
+    );
+
+    // Wrong account type
+    assert_eq!(
+        load_checked::<ProductAccount>(&price_account, PC_VERSION).err(),
+        Some(OracleError::InvalidAccountHeader.into())
+    );
+
+    let mut mapping_setup = AccountSetup::new::<MappingAccount>(&program_id);
+    let mapping_account = mapping_setup.as_account_info();
+    MappingAccount::initialize(&mapping_account, PC_VERSION).unwrap();
+    assert_eq!(
+        load_checked::<PriceAccount>(&mapping_account, PC_VERSION).err(),
+        Some(OracleError::InvalidAccountHeader.into())
+    );
+
+    // Wrong magic number
+    load_account_as_mut::<AccountHeader>(&price_account)
+        .unwrap()
+        .magic_number = 0xdeadbeef;
+    assert_eq!(
+        load_checked::<PriceAccount>(&price_account, PC_VERSION).err(),
+        Some(OracleError::InvalidAccountHeader.into())
+    );
+}
+
+#[test]
+fn test_upd_price_malformed_price_account() {
+    let mut instruction_data = [0u8; size_of::<UpdPriceArgs>()];
+    {
+        let mut cmd = load_mut::<UpdPriceArgs>(&mut instruction_data).unwrap();
+        cmd.header = OracleCommand::UpdPrice.into();
+        cmd.status = PC_STATUS_TRADING;
+        cmd.price = 42;
+        cmd.confidence = 1;
+        cmd.publishing_slot = 1;
+    }
+
+    let program_id = Pubkey::new_unique();
+
+    let mut funding_setup = AccountSetup::new_funding();
+    let funding_account = funding_setup.as_account_info();
+
+    let mut clock_setup = AccountSetup::new_clock();
+    let mut clock_account = clock_setup.as_account_info();
+    clock_account.is_signer = false;
+    clock_account.is_writable = false;
+    update_clock_slot(&mut clock_account, 1);
+
+    // Uninitialized price account
+    let mut price_setup = AccountSetup::new::<PriceAccount>(&program_id);
+    let mut price_account = price_setup.as_account_info();
+    price_account.is_signer = false;
+
+    assert_eq!(
+        process_instruction(
+            &program_id,
+            &[
+                funding_account.clone(),
+                price_account.clone(),
+                clock_account.clone()
+            ],
+            &instruction_data
+        ),
+        Err(OracleError::InvalidAccountHeader.into())
+    );
+
+    // Price account with a corrupted magic number, even though the publisher is permissioned
+    PriceAccount::initialize(&price_account, PC_VERSION).unwrap();
+    {
+        let mut price_data = load_checked::<PriceAccount>(&price_account, PC_VERSION).unwrap();
+        price_data.num_ = 1;
+        price_data.comp_[0].pub_ = *funding_account.key;
+        price_data.header.magic_number = 0xdeadbeef;
+    }
+
+    assert_eq!(
+        process_instruction(
+            &program_id,
+            &[
+                funding_account.clone(),
+                price_account.clone(),
+                clock_account.clone()
+            ],
+            &instruction_data
+        ),
+        Err(OracleError::InvalidAccountHeader.into())
+    );
+
+    // Product account passed as the price account
+    let mut product_setup = AccountSetup::new::<ProductAccount>(&program_id);
+    let mut product_account = product_setup.as_account_info();
+    product_account.is_signer = false;
+    ProductAccount::initialize(&product_account, PC_VERSION).unwrap();
+
+    assert_eq!(
+        process_instruction(
+            &program_id,
+            &[
+                funding_account.clone(),
+                product_account.clone(),
+                clock_account.clone()
+            ],
+            &instruction_data
+        ),
+        Err(OracleError::AccountTooSmall.into())
+    );
+}
@@ -0,0 +1,198 @@
+//! The devnet instructions only exist in builds with the `devnet` feature. Run the tests with and
+//! without `--features devnet` to check both builds.
+
+#[cfg(not(feature = "devnet"))]
+use crate::instruction::OracleInstruction;
+#[cfg(feature = "devnet")]
+use {
+    crate::{
+        accounts::{
+            PermissionAccount,
+            PriceAccount,
+            PythAccount,
+        },
+        c_oracle_header::{
+            PC_STATUS_HALTED,
+            PC_STATUS_TRADING,
+            PC_STATUS_UNKNOWN,
+        },
+        deserialize::load_checked,
+        tests::test_utils::{
+            update_clock_slot,
+            AccountSetup,
+        },
+    },
+    solana_program::account_info::AccountInfo,
+};
+use {
+    crate::{
+        c_oracle_header::PC_VERSION,
+        error::OracleError,
+        instruction::{
+            CommandHeader,
+            OracleCommand,
+        },
+        processor::process_instruction,
+    },
+    bytemuck::bytes_of,
+    solana_program::pubkey::Pubkey,
+};
+
+#[cfg(not(feature = "devnet"))]
+#[test]
+fn test_devnet_instructions_rejected() {
+    let program_id = Pubkey::new_unique();
+    for command in [OracleCommand::ForceAggregate, OracleCommand::ResetPrice] {
+        let header: CommandHeader = command.into();
+        assert_eq!(
+            process_instruction(&program_id, &[], bytes_of(&header)),
+            Err(OracleError::UnrecognizedInstruction.into())
+        );
+    }
+
+    // Also in the Borsh layout
+    for instruction in [
+        OracleInstruction::ForceAggregate,
+        OracleInstruction::ResetPrice,
+    ] {
+        assert_eq!(
+            process_instruction(&program_id, &[], &instruction.pack()),
+            Err(OracleError::UnrecognizedInstruction.into())
+        );
+    }
+}
+
+/// Initialize `price_account` with a single publisher quoting 100 at slot 1
+#[cfg(feature = "devnet")]
+fn populate_price_account(price_account: &AccountInfo) {
+    let mut price_data = PriceAccount::initialize(price_account, PC_VERSION).unwrap();
+    price_data.num_ = 1;
+    price_data.min_pub_ = 1;
+    price_data.exponent = -5;
+    price_data.comp_[0].pub_ = Pubkey::new_unique();
+    price_data.comp_[0].latest_.price_ = 100;
+    price_data.comp_[0].latest_.conf_ = 5;
+    price_data.comp_[0].latest_.status_ = PC_STATUS_TRADING;
+    price_data.comp_[0].latest_.pub_slot_ = 1;
+}
+
+#[cfg(feature = "devnet")]
+#[test]
+fn test_force_aggregate() {
+    let program_id = Pubkey::new_unique();
+
+    let mut funding_setup = AccountSetup::new_funding();
+    let funding_account = funding_setup.as_account_info();
+
+    let mut price_setup = AccountSetup::new::<PriceAccount>(&program_id);
+    let price_account = price_setup.as_account_info();
+    populate_price_account(&price_account);
+
+    let mut clock_setup = AccountSetup::new_clock();
+    let mut clock_account = clock_setup.as_account_info();
+    update_clock_slot(&mut clock_account, 2);
+
+    let mut permissions_setup = AccountSetup::new_permission(&program_id);
+    let permissions_account = permissions_setup.as_account_info();
+    PermissionAccount::initialize(&permissions_account, PC_VERSION)
+        .unwrap()
+        .master_authority = *funding_account.key;
+
+    let header: CommandHeader = OracleCommand::ForceAggregate.into();
+    let accounts = [
+        funding_account.clone(),
+        price_account.clone(),
+        clock_account.clone(),
+        permissions_account.clone(),
+    ];
+
+    process_instruction(&program_id, &accounts, bytes_of(&header)).unwrap();
+    {
+        let price_data = load_checked::<PriceAccount>(&price_account, PC_VERSION).unwrap();
+        assert_eq!(price_data.agg_.status_, PC_STATUS_TRADING);
+        assert_eq!(price_data.agg_.price_, 100);
+        assert_eq!(price_data.agg_.pub_slot_, 2);
+        assert_eq!(price_data.num_qt_, 1);
+    }
+
+    // The aggregation can run again in the same slot
+    process_instruction(&program_id, &accounts, bytes_of(&header)).unwrap();
+
+    // Halted feeds aren't aggregated
+    load_checked::<PriceAccount>(&price_account, PC_VERSION)
+        .unwrap()
+        .agg_
+        .status_ = PC_STATUS_HALTED;
+    assert_eq!(
+        process_instruction(&program_id, &accounts, bytes_of(&header)),
+        Err(OracleError::PriceHalted.into())
+    );
+
+    // Only the master authority can force an aggregation
+    load_checked::<PermissionAccount>(&permissions_account, PC_VERSION)
+        .unwrap()
+        .master_authority = Pubkey::new_unique();
+    assert_eq!(
+        process_instruction(&program_id, &accounts, bytes_of(&header)),
+        Err(OracleError::PermissionViolation.into())
+    );
+}
+
+#[cfg(feature = "devnet")]
+#[test]
+fn test_reset_price() {
+    let program_id = Pubkey::new_unique();
+
+    let mut funding_setup = AccountSetup::new_funding();
+    let funding_account = funding_setup.as_account_info();
+
+    let mut price_setup = AccountSetup::new::<PriceAccount>(&program_id);
+    let price_account = price_setup.as_account_info();
+    populate_price_account(&price_account);
+    let (publisher, product) = {
+        let mut price_data = load_checked::<PriceAccount>(&price_account, PC_VERSION).unwrap();
+        price_data.product_account = Pubkey::new_unique();
+        price_data.agg_.price_ = 100;
+        price_data.agg_.status_ = PC_STATUS_HALTED;
+        price_data.prev_price_ = 90;
+        price_data.twap_.val_ = 95;
+        price_data.price_cumulative.price = 1000;
+        price_data.publisher_stats_[0].included_rounds_ = 1;
+        (price_data.comp_[0].pub_, price_data.product_account)
+    };
+
+    let mut permissions_setup = AccountSetup::new_permission(&program_id);
+    let permissions_account = permissions_setup.as_account_info();
+    PermissionAccount::initialize(&permissions_account, PC_VERSION)
+        .unwrap()
+        .master_authority = *funding_account.key;
+
+    let header: CommandHeader = OracleCommand::ResetPrice.into();
+    process_instruction(
+        &program_id,
+        &[
+            funding_account.clone(),
+            price_account.clone(),
+            permissions_account.clone(),
+        ],
+        bytes_of(&header),
+    )
+    .unwrap();
+
+    let price_data = load_checked::<PriceAccount>(&price_account, PC_VERSION).unwrap();
+    assert_eq!(price_data.agg_.status_, PC_STATUS_UNKNOWN);
+    assert_eq!(price_data.agg_.price_, 0);
+    assert_eq!(price_data.prev_price_, 0);
+    assert_eq!(price_data.twap_.val_, 0);
+    assert_eq!(price_data.price_cumulative.price, 0);
+    assert_eq!(price_data.comp_[0].latest_.price_, 0);
+    assert_eq!(price_data.comp_[0].latest_.pub_slot_, 0);
+    assert_eq!(price_data.publisher_stats_[0].included_rounds_, 0);
+
+    // The configuration and the publishers are kept
+    assert_eq!(price_data.num_, 1);
+    assert_eq!(price_data.min_pub_, 1);
+    assert_eq!(price_data.exponent, -5);
+    assert_eq!(price_data.comp_[0].pub_, publisher);
+    assert_eq!(price_data.product_account, product);
+}
@@ -2,14 +2,19 @@ extern crate test_generator;
 
 use {
     crate::{
-        accounts::PriceAccount,
-        processor::{
-            c_upd_aggregate,
-            c_upd_twap,
+        accounts::{
+            EmaDecay,
+            EmaWindow,
+            PriceAccount,
+            EMA_WINDOWS,
         },
+        aggregation::upd_aggregate,
+        processor::c_upd_twap,
     },
     bytemuck::Zeroable,
     csv::ReaderBuilder,
+    quickcheck::Arbitrary,
+    quickcheck_macros::quickcheck,
     serde::{
         Deserialize,
         Serialize,
@@ -111,23 +116,93 @@ fn run_ema_test(inputs: &[InputRecord], expected_outputs: &[OutputRecord]) {
 }
 
 
-// TODO: put these functions somewhere more accessible
-pub fn upd_aggregate(
-    price_account: &mut PriceAccount,
-    clock_slot: u64,
-    clock_timestamp: i64,
-) -> bool {
+// TODO: put this function somewhere more accessible
+pub fn upd_twap(price_account: &mut PriceAccount, nslots: i64) {
+    upd_twap_with_window(price_account, nslots, EmaWindow::DEFAULT)
+}
+
+pub fn upd_twap_with_window(price_account: &mut PriceAccount, nslots: i64, window: EmaWindow) {
+    let decay = window.decay();
     unsafe {
-        c_upd_aggregate(
+        c_upd_twap(
             (price_account as *mut PriceAccount) as *mut u8,
-            clock_slot,
-            clock_timestamp,
+            nslots,
+            decay.rate,
+            decay.max_diff,
         )
     }
 }
 
-pub fn upd_twap(price_account: &mut PriceAccount, nslots: i64) {
-    unsafe { c_upd_twap((price_account as *mut PriceAccount) as *mut u8, nslots) }
+/// The test data above was generated with these parameters
+#[test]
+fn test_ema_default_window() {
+    assert_eq!(
+        EmaWindow::DEFAULT.decay(),
+        EmaDecay {
+            rate:     -117_065,
+            max_diff: 4145,
+        }
+    );
+    assert!(EMA_WINDOWS.contains(&EmaWindow::DEFAULT));
+    for window in EMA_WINDOWS {
+        let decay = window.decay();
+        assert!(decay.rate < 0);
+        assert!(decay.max_diff > 0);
+    }
+}
+
+/// A constant aggregate published at random slot gaps, with one of the supported EMA windows
+#[derive(Clone, Debug)]
+struct ConstantAggregate {
+    window:    EmaWindow,
+    price:     i64,
+    conf:      u64,
+    expo:      i32,
+    slot_gaps: Vec<i64>,
+}
+
+impl Arbitrary for ConstantAggregate {
+    fn arbitrary(g: &mut quickcheck::Gen) -> Self {
+        let window = *g.choose(&EMA_WINDOWS).unwrap();
+        let max_diff = window.decay().max_diff;
+        // The decimal arithmetic of the C code supports neither zero nor `i64::MIN`
+        let price = match i64::arbitrary(g) {
+            0 | i64::MIN => 1,
+            price => price,
+        };
+        ConstantAggregate {
+            window,
+            price,
+            conf: u64::from(u32::arbitrary(g)).max(1),
+            expo: -i32::from(u8::arbitrary(g) % 13),
+            // Some gaps are long enough to restart the EMAs
+            slot_gaps: Vec::<u32>::arbitrary(g)
+                .into_iter()
+                .map(|gap| i64::from(gap) % (2 * max_diff) + 1)
+                .collect(),
+        }
+    }
+}
+
+/// Whether `ema` is `value` up to the precision of the decimal arithmetic of the C code, one unit
+/// and a relative error of 1e-4
+fn is_close(ema: i64, value: i64) -> bool {
+    ema.abs_diff(value) <= 1 + value.unsigned_abs() / 10_000
+}
+
+/// The EMAs of a constant aggregate are the aggregate, whatever the window
+#[quickcheck]
+fn test_ema_constant_price(input: ConstantAggregate) -> bool {
+    let mut price_account = PriceAccount::zeroed();
+    price_account.exponent = input.expo;
+    price_account.agg_.price_ = input.price;
+    price_account.agg_.conf_ = input.conf;
+
+    input.slot_gaps.iter().all(|slot_gap| {
+        upd_twap_with_window(&mut price_account, *slot_gap, input.window);
+        is_close(price_account.twap_.val_, input.price)
+            && is_close(price_account.twac_.val_, input.conf as i64)
+    })
 }
 
 
@@ -0,0 +1,76 @@
+use {
+    crate::sdk::OracleError,
+    solana_program::{
+        decode_error::DecodeError,
+        program_error::ProgramError,
+    },
+};
+
+/// The custom error code of each error. Clients decode the errors of failed transactions with
+/// these codes, so existing codes must never change and new errors get the next code.
+const ERROR_CODES: &[(OracleError, u32)] = &[
+    (OracleError::Generic, 600),
+    (OracleError::IntegerCastingError, 601),
+    (OracleError::UnknownCError, 602),
+    (OracleError::UnrecognizedInstruction, 603),
+    (OracleError::InvalidFundingAccount, 604),
+    (OracleError::InvalidSignableAccount, 605),
+    (OracleError::InvalidSystemAccount, 606),
+    (OracleError::InvalidWritableAccount, 607),
+    (OracleError::InvalidFreshAccount, 608),
+    (OracleError::InvalidInstructionVersion, 609),
+    (OracleError::InstructionDataTooShort, 610),
+    (OracleError::InstructionDataSliceMisaligned, 611),
+    (OracleError::AccountTooSmall, 612),
+    (OracleError::DeserializationError, 613),
+    (OracleError::InvalidUpgradeAuthority, 614),
+    (OracleError::InvalidPda, 615),
+    (OracleError::InvalidAccountHeader, 616),
+    (OracleError::InvalidNumberOfAccounts, 617),
+    (OracleError::InvalidReadableAccount, 618),
+    (OracleError::PermissionViolation, 619),
+    (OracleError::NeedsSuccesfulAggregation, 620),
+    (OracleError::PriceHalted, 621),
+    (OracleError::TooManyPriceUpdates, 622),
+    (OracleError::UnsupportedAccountLayout, 623),
+    (OracleError::PublishTimeInFuture, 624),
+    (OracleError::UnauthorizedCpi, 625),
+    (OracleError::MaxLastFeedIndexReached, 626),
+    (OracleError::MissingFeeAccounts, 627),
+    (OracleError::InsufficientTreasuryFunds, 628),
+    (OracleError::UnknownProductAttribute, 629),
+    (OracleError::InvalidProductAttribute, 630),
+    (OracleError::AliasedAccounts, 631),
+    (OracleError::InvalidReferencePrice, 632),
+    (OracleError::ArithmeticOverflow, 633),
+    (OracleError::MissingCoSigner, 634),
+    (OracleError::SymbolAlreadyIndexed, 635),
+    (OracleError::SymbolIndexFull, 636),
+    (OracleError::MissingSymbol, 637),
+    (OracleError::CorruptedAccount, 638),
+    (OracleError::PublisherExpired, 639),
+    (OracleError::InvalidComponentLog, 640),
+    (OracleError::InvalidDerivedPrice, 641),
+];
+
+#[test]
+fn test_error_codes() {
+    for (error, code) in ERROR_CODES {
+        assert_eq!(
+            ProgramError::from(error.clone()),
+            ProgramError::Custom(*code)
+        );
+        assert_eq!(OracleError::from_code(*code).as_ref(), Some(error));
+    }
+
+    // The table lists every error
+    for code in 0..1000 {
+        let listed = ERROR_CODES
+            .iter()
+            .find(|(_, listed_code)| *listed_code == code)
+            .map(|(error, _)| error.clone());
+        assert_eq!(OracleError::from_code(code), listed);
+    }
+
+    assert_eq!(<OracleError as DecodeError<()>>::type_of(), "OracleError");
+}
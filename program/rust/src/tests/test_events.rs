@@ -0,0 +1,110 @@
+use {
+    crate::{
+        accounts::PriceAccount,
+        c_oracle_header::PC_STATUS_TRADING,
+        events::{
+            AggregatePriceUpdate,
+            Event,
+        },
+        tests::pyth_simulator::{
+            PythSimulator,
+            Quote,
+        },
+    },
+    solana_program::hash::hash,
+    solana_sdk::{
+        signature::Keypair,
+        signer::Signer,
+    },
+    std::{
+        collections::HashMap,
+        iter::once,
+        mem::size_of,
+    },
+};
+
+#[test]
+fn test_event_layout() {
+    // Anchor discriminator
+    assert_eq!(
+        AggregatePriceUpdate::DISCRIMINATOR,
+        hash(b"event:AggregatePriceUpdate").to_bytes()[..8]
+    );
+    // No padding, so that the bytes of the struct are its borsh serialization
+    assert_eq!(size_of::<AggregatePriceUpdate>(), 32 + 8 + 8 + 8 + 4 + 4);
+}
+
+#[tokio::test]
+async fn test_aggregate_price_update_event() {
+    let mut sim = PythSimulator::new_bpf().await;
+    let publisher = Keypair::new();
+    let security_authority = Keypair::new();
+    let price_accounts = sim
+        .setup_product_fixture(&[publisher.pubkey()], security_authority.pubkey())
+        .await;
+    let price = price_accounts["BTC"];
+    sim.set_min_pub(price, 1).await.unwrap();
+
+    let price_account = HashMap::from_iter(once(("BTC".to_string(), price)));
+    let quote = |value: i64| {
+        HashMap::from_iter(once((
+            "BTC".to_string(),
+            Quote {
+                price:      value,
+                confidence: 5,
+                status:     PC_STATUS_TRADING,
+            },
+        )))
+    };
+
+    // There is no valid quote to aggregate yet, so no event is emitted
+    let instructions = sim
+        .upd_price_instructions(&publisher, &price_account, &quote(100))
+        .await
+        .unwrap();
+    let logs = sim
+        .simulate_logs(&instructions, &vec![&publisher], &publisher)
+        .await
+        .unwrap();
+    assert!(!logs.iter().any(|log| log.starts_with("Program data: ")));
+
+    sim.upd_price_batch(&publisher, &price_account, &quote(100))
+        .await
+        .unwrap();
+    sim.warp_to_slot(2).await.unwrap();
+
+    let instructions = sim
+        .upd_price_instructions(&publisher, &price_account, &quote(200))
+        .await
+        .unwrap();
+    let logs = sim
+        .simulate_logs(&instructions, &vec![&publisher], &publisher)
+        .await
+        .unwrap();
+
+    let event = AggregatePriceUpdate {
+        price_account:    price,
+        slot:             2,
+        price:            100,
+        conf:             5,
+        status:           PC_STATUS_TRADING,
+        num_contributors: 1,
+    };
+    let expected_log = format!("Program data: {}", base64::encode(event.data()));
+    assert_eq!(
+        logs.iter()
+            .filter(|log| log.starts_with("Program data: "))
+            .collect::<Vec<&String>>(),
+        vec![&expected_log]
+    );
+
+    sim.upd_price_batch(&publisher, &price_account, &quote(200))
+        .await
+        .unwrap();
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(price_data.agg_.price_, event.price);
+    assert_eq!(price_data.num_qt_, event.num_contributors);
+}
@@ -0,0 +1,129 @@
+use {
+    crate::{
+        accounts::PriceAccount,
+        aggregation::upd_aggregate,
+        c_oracle_header::{
+            PC_STATUS_TRADING,
+            PC_STATUS_UNKNOWN,
+        },
+        tests::pyth_simulator::{
+            PythSimulator,
+            Quote,
+        },
+    },
+    bytemuck::Zeroable,
+    solana_sdk::signer::Signer,
+};
+
+const CURRENT_SLOT: u64 = 1000;
+
+/// A price account with a trading quote of confidence 1 for each `(price, pub_slot)` of `quotes`
+fn price_account_with(quotes: &[(i64, u64)], min_pub: u8) -> PriceAccount {
+    let mut price_account = PriceAccount::zeroed();
+    price_account.num_ = quotes.len() as u32;
+    price_account.min_pub_ = min_pub;
+    for (component, (price, pub_slot)) in price_account.comp_.iter_mut().zip(quotes.iter()) {
+        component.latest_.price_ = *price;
+        component.latest_.conf_ = 1;
+        component.latest_.status_ = PC_STATUS_TRADING;
+        component.latest_.pub_slot_ = *pub_slot;
+    }
+    price_account
+}
+
+#[test]
+fn test_fast_aggregate() {
+    // The third publisher lags by a few slots
+    let quotes = [
+        (100, CURRENT_SLOT),
+        (101, CURRENT_SLOT),
+        (200, CURRENT_SLOT - 5),
+    ];
+
+    let mut price_account = price_account_with(&quotes, 1);
+    assert!(upd_aggregate(&mut price_account, CURRENT_SLOT + 1, 0));
+    assert_eq!(price_account.agg_.price_, 101);
+    assert_eq!(price_account.agg_.conf_, 98);
+    assert_eq!(price_account.num_qt_, 3);
+    // Only the quotes of the last slot are in the fast aggregate
+    assert_eq!(price_account.fast_agg_.status_, PC_STATUS_TRADING);
+    assert_eq!(price_account.fast_agg_.price_, 100);
+    assert_eq!(price_account.fast_agg_.conf_, 1);
+    assert_eq!(price_account.fast_agg_.num_qt_, 2);
+    assert_eq!(price_account.fast_agg_.pub_slot_, CURRENT_SLOT + 1);
+
+    // It is the aggregate of the fresh quotes alone
+    let mut fresh_price_account = price_account_with(&quotes[..2], 1);
+    assert!(upd_aggregate(&mut fresh_price_account, CURRENT_SLOT + 1, 0));
+    assert_eq!(
+        fresh_price_account.agg_.price_,
+        price_account.fast_agg_.price_
+    );
+    assert_eq!(
+        fresh_price_account.agg_.conf_,
+        price_account.fast_agg_.conf_
+    );
+
+    // The fast aggregate doesn't require min_pub quotes
+    let mut price_account = price_account_with(&quotes, 4);
+    assert!(!upd_aggregate(&mut price_account, CURRENT_SLOT + 1, 0));
+    assert_eq!(price_account.agg_.status_, PC_STATUS_UNKNOWN);
+    assert_eq!(price_account.fast_agg_.status_, PC_STATUS_TRADING);
+    assert_eq!(price_account.fast_agg_.price_, 100);
+    assert_eq!(price_account.fast_agg_.num_qt_, 2);
+
+    // Without any fresh quote, only the canonical aggregate succeeds
+    let mut price_account = price_account_with(&quotes, 1);
+    assert!(upd_aggregate(&mut price_account, CURRENT_SLOT + 2, 0));
+    assert_eq!(price_account.num_qt_, 3);
+    assert_eq!(price_account.fast_agg_.status_, PC_STATUS_UNKNOWN);
+    assert_eq!(price_account.fast_agg_.num_qt_, 0);
+    assert_eq!(price_account.fast_agg_.pub_slot_, CURRENT_SLOT + 2);
+    assert_eq!(price_account.get_fast_price_unchecked(), None);
+}
+
+#[tokio::test]
+async fn test_fast_aggregate_with_lagging_publisher() {
+    let mut sim = PythSimulator::new().await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(3).await;
+    let price = price_keypair.pubkey();
+    sim.set_min_pub(price, 3).await.unwrap();
+
+    let quote = |price: i64| Quote {
+        price,
+        confidence: 5,
+        status: PC_STATUS_TRADING,
+    };
+    sim.publish_all(price, &publishers, &[quote(100); 3])
+        .await
+        .unwrap();
+
+    // The price moves but the last publisher doesn't update its quote
+    sim.warp_to_slot(2).await.unwrap();
+    sim.publish_all(price, &publishers[..2], &[quote(110); 2])
+        .await
+        .unwrap();
+    sim.warp_to_slot(3).await.unwrap();
+    sim.upd_price(&publishers[0], price, quote(110))
+        .await
+        .unwrap();
+
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(price_data.agg_.status_, PC_STATUS_TRADING);
+    assert_eq!(price_data.agg_.price_, 105);
+    assert_eq!(price_data.num_qt_, 3);
+    assert_eq!(price_data.fast_agg_.status_, PC_STATUS_TRADING);
+    assert_eq!(price_data.fast_agg_.price_, 110);
+    assert_eq!(price_data.fast_agg_.conf_, 5);
+    assert_eq!(price_data.fast_agg_.num_qt_, 2);
+    assert_eq!(price_data.fast_agg_.pub_slot_, 3);
+
+    let fast_price = price_data.get_fast_price_unchecked().unwrap();
+    assert_eq!(fast_price.price, 110);
+    assert_eq!(fast_price.conf, 5);
+    assert_eq!(fast_price.expo, price_data.exponent);
+    assert_eq!(fast_price.publish_time, price_data.timestamp_);
+}
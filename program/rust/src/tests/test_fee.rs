@@ -0,0 +1,156 @@
+use {
+    crate::{
+        accounts::PriceAccount,
+        c_oracle_header::PC_STATUS_TRADING,
+        error::OracleError,
+        tests::pyth_simulator::{
+            copy_keypair,
+            PythSimulator,
+            Quote,
+            TransactionFailure,
+        },
+    },
+    solana_program::{
+        native_token::LAMPORTS_PER_SOL,
+        pubkey::Pubkey,
+        rent::Rent,
+    },
+    solana_sdk::signer::Signer,
+    std::collections::HashMap,
+};
+
+const FEE: u64 = LAMPORTS_PER_SOL / 100;
+
+fn quote(price: i64) -> Quote {
+    Quote {
+        price,
+        confidence: 1,
+        status: PC_STATUS_TRADING,
+    }
+}
+
+#[tokio::test]
+async fn test_fee() {
+    let mut sim = PythSimulator::new().await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(1).await;
+    let price = price_keypair.pubkey();
+    let treasury = sim.get_treasury_pubkey();
+    let rent_exemption = Rent::default().minimum_balance(0);
+
+    // Price updates are free until a fee is set
+    sim.upd_price(&publishers[0], price, quote(100))
+        .await
+        .unwrap();
+    assert!(sim.get_account(treasury).await.is_none());
+
+    // Setting a fee funds the treasury up to its rent exemption
+    sim.set_fee(price, FEE).await.unwrap();
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(price_data.fee_lamports_, FEE);
+    assert_eq!(
+        sim.get_account(treasury).await.unwrap().lamports,
+        rent_exemption
+    );
+
+    sim.warp_to_slot(2).await.unwrap();
+    sim.upd_price(&publishers[0], price, quote(110))
+        .await
+        .assert_fails_with(OracleError::MissingFeeAccounts);
+
+    sim.upd_price_with_fee(&publishers[0], price, quote(110))
+        .await
+        .unwrap();
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(price_data.comp_[0].latest_.price_, 110);
+    assert_eq!(
+        sim.get_account(treasury).await.unwrap().lamports,
+        rent_exemption + FEE
+    );
+
+    // A rejected update doesn't pay the fee, here for publishing in the same slot again
+    let mut instruction = sim
+        .upd_price_no_fail_on_error_instruction(&publishers[0], price, &quote(115))
+        .await
+        .unwrap();
+    instruction.accounts.extend(sim.fee_accounts());
+    sim.process_ixs(&[instruction], &vec![&publishers[0]], &publishers[0])
+        .await
+        .unwrap();
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(price_data.comp_[0].latest_.price_, 110);
+    assert_eq!(
+        sim.get_account(treasury).await.unwrap().lamports,
+        rent_exemption + FEE
+    );
+
+    // A batch pays the fees of all its price accounts
+    let (second_price_keypair, _) = sim.setup_feed_with_publishers(0).await;
+    let second_price = second_price_keypair.pubkey();
+    sim.add_publisher(second_price, publishers[0].pubkey())
+        .await
+        .unwrap();
+    sim.set_fee(second_price, 2 * FEE).await.unwrap();
+
+    sim.warp_to_slot(3).await.unwrap();
+    let price_accounts = HashMap::from([
+        ("FIRST".to_string(), price),
+        ("SECOND".to_string(), second_price),
+    ]);
+    let quotes = HashMap::from([
+        ("FIRST".to_string(), quote(120)),
+        ("SECOND".to_string(), quote(200)),
+    ]);
+    let mut instruction = sim
+        .upd_price_batch_instruction(&publishers[0], &price_accounts, &quotes)
+        .await
+        .unwrap();
+    instruction.accounts.extend(sim.fee_accounts());
+    sim.process_ixs(&[instruction], &vec![&publishers[0]], &publishers[0])
+        .await
+        .unwrap();
+    assert_eq!(
+        sim.get_account(treasury).await.unwrap().lamports,
+        rent_exemption + 4 * FEE
+    );
+
+    // Only the authority can withdraw the fees, and the treasury keeps its rent exemption
+    let destination = Pubkey::new_unique();
+    sim.withdraw_fee(&publishers[0], destination, FEE)
+        .await
+        .assert_fails_with(OracleError::PermissionViolation);
+    let authority = copy_keypair(&sim.genesis_keypair);
+    sim.withdraw_fee(&authority, destination, 4 * FEE + 1)
+        .await
+        .assert_fails_with(OracleError::InsufficientTreasuryFunds);
+    sim.withdraw_fee(&authority, destination, 4 * FEE)
+        .await
+        .unwrap();
+    assert_eq!(
+        sim.get_account(destination).await.unwrap().lamports,
+        4 * FEE
+    );
+    assert_eq!(
+        sim.get_account(treasury).await.unwrap().lamports,
+        rent_exemption
+    );
+
+    // Without a fee, the fee accounts are not needed anymore
+    sim.set_fee(price, 0).await.unwrap();
+    sim.warp_to_slot(4).await.unwrap();
+    sim.upd_price(&publishers[0], price, quote(130))
+        .await
+        .unwrap();
+    assert_eq!(
+        sim.get_account(treasury).await.unwrap().lamports,
+        rent_exemption
+    );
+}
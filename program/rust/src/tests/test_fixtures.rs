@@ -0,0 +1,132 @@
+//! Replay of accounts dumped from pythnet. The price account fixture has the pythnet layout, so the
+//! tests that use it only run in pythnet builds.
+
+#[cfg(feature = "pythnet")]
+use {
+    crate::{
+        accounts::PriceAccount,
+        aggregation::upd_aggregate,
+        c_oracle_header::PC_STATUS_TRADING,
+        migrations::PRICE_ACCOUNT_LAYOUT_VERSION,
+        tests::pyth_simulator::Quote,
+    },
+    solana_program::native_token::LAMPORTS_PER_SOL,
+    solana_sdk::{
+        signature::Keypair,
+        signer::Signer,
+    },
+};
+use {
+    crate::{
+        accounts::ProductAccount,
+        tests::{
+            fixtures::AccountFixture,
+            pyth_simulator::PythSimulator,
+        },
+    },
+    solana_program::pubkey::Pubkey,
+    std::str::FromStr,
+};
+
+/// Price and product accounts of BTC/USD on pythnet
+const PRICE_FIXTURE: &str = "pythnet_btc_usd_price";
+const PRODUCT_FIXTURE: &str = "pythnet_btc_usd_product";
+const PYTHNET_ORACLE_PROGRAM_ID: &str = "FsJ3A3u2vn5cTVofAjvy6y5kwABJAqYWpe4975bi2epH";
+
+#[tokio::test]
+async fn test_load_product_fixture() {
+    let fixture = AccountFixture::load(PRODUCT_FIXTURE);
+    assert_eq!(
+        fixture.account.owner,
+        Pubkey::from_str(PYTHNET_ORACLE_PROGRAM_ID).unwrap()
+    );
+
+    let mut sim = PythSimulator::new().await;
+    sim.load_oracle_account(&fixture);
+    let product_data = sim.get_pyth_account::<ProductAccount>(fixture.pubkey).await;
+    assert_eq!(
+        product_data.first_price_account,
+        AccountFixture::load(PRICE_FIXTURE).pubkey
+    );
+    assert_eq!(
+        sim.get_product_attributes(fixture.pubkey).await,
+        [
+            ("asset_type", "Crypto"),
+            ("base", "BTC"),
+            ("description", "BTC/USD"),
+            ("generic_symbol", "BTCUSD"),
+            ("quote_currency", "USD"),
+            ("symbol", "Crypto.BTC/USD"),
+        ]
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+    );
+}
+
+/// A pythnet price account keeps aggregating its dumped quotes with the quotes of new publishers
+#[cfg(feature = "pythnet")]
+#[tokio::test]
+async fn test_replay_price_fixture() {
+    let fixture = AccountFixture::load(PRICE_FIXTURE);
+    let price = fixture.pubkey;
+    let mut sim = PythSimulator::new().await;
+    sim.load_oracle_account(&fixture);
+
+    // Mainnet accounts predate layout versions
+    let dumped = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(dumped.layout_version_, 0);
+    assert_eq!(dumped.exponent, -8);
+    assert_eq!(dumped.num_, 3);
+    assert_eq!(dumped.agg_.status_, PC_STATUS_TRADING);
+    let dumped_slot = dumped.agg_.pub_slot_;
+    let dumped_timestamp = dumped.timestamp_;
+
+    // The first write migrates the account and keeps the dumped components
+    let publisher = Keypair::new();
+    sim.airdrop(&publisher.pubkey(), 100 * LAMPORTS_PER_SOL)
+        .await
+        .unwrap();
+    sim.add_publisher(price, publisher.pubkey()).await.unwrap();
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(price_data.layout_version_, PRICE_ACCOUNT_LAYOUT_VERSION);
+    assert_eq!(price_data.num_, 4);
+    // The migration dates the trading status from the last aggregate
+    assert_eq!(price_data.status_change_.slot_, dumped_slot);
+    assert_eq!(price_data.status_change_.timestamp_, dumped_timestamp);
+    assert_eq!(
+        u32::from(price_data.status_change_.status_),
+        PC_STATUS_TRADING
+    );
+    for component in dumped.comp_[..3].iter() {
+        assert!(price_data.comp_[..4].contains(component));
+    }
+
+    // The first update aggregates the dumped quotes
+    let quote = Quote {
+        price:      6_712_500_000_000,
+        confidence: 2_000_000_000,
+        status:     PC_STATUS_TRADING,
+    };
+    sim.warp_to_slot(dumped_slot + 1).await.unwrap();
+    sim.set_clock_timestamp(dumped_timestamp + 1).await.unwrap();
+    sim.upd_price(&publisher, price, quote).await.unwrap();
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(price_data.agg_.status_, PC_STATUS_TRADING);
+    assert_eq!(price_data.agg_.pub_slot_, dumped_slot + 1);
+    assert_eq!(price_data.num_qt_, 3);
+    assert_eq!(price_data.prev_price_, dumped.agg_.price_);
+
+    // The next one also includes the quote of the new publisher
+    let mut expected = price_data;
+    assert!(upd_aggregate(
+        &mut expected,
+        dumped_slot + 2,
+        dumped_timestamp + 2
+    ));
+    sim.warp_to_slot(dumped_slot + 2).await.unwrap();
+    sim.set_clock_timestamp(dumped_timestamp + 2).await.unwrap();
+    sim.upd_price(&publisher, price, quote).await.unwrap();
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(price_data.num_qt_, 4);
+    assert_eq!(price_data.agg_, expected.agg_);
+    assert_eq!(price_data.prev_price_, expected.prev_price_);
+}
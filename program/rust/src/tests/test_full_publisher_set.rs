@@ -10,10 +10,7 @@ use {
             Quote,
         },
     },
-    solana_sdk::{
-        signature::Keypair,
-        signer::Signer,
-    },
+    solana_sdk::signer::Signer,
 };
 
 // Verify that the whole publisher set participates in aggregate
@@ -27,51 +24,28 @@ use {
 #[tokio::test]
 async fn test_full_publisher_set() -> Result<(), Box<dyn std::error::Error>> {
     let mut sim = PythSimulator::new().await;
-    let pub_keypairs: Vec<_> = (0..PC_NUM_COMP).map(|_idx| Keypair::new()).collect();
-    let pub_pubkeys: Vec<_> = pub_keypairs.iter().map(|kp| kp.pubkey()).collect();
-
-    let security_authority = Keypair::new();
-    let price_accounts = sim
-        .setup_product_fixture(pub_pubkeys.as_slice(), security_authority.pubkey())
-        .await;
-    let price = price_accounts["LTC"];
+    let (price_keypair, pub_keypairs) = sim.setup_feed_with_publishers(PC_NUM_COMP as usize).await;
+    let price = price_keypair.pubkey();
 
+    let quote = |price: i64| Quote {
+        price,
+        confidence: 30,
+        status: PC_STATUS_TRADING,
+    };
 
+    // Divide publishers into two even parts (assuming the max PC_NUM_COMP size is even) and
+    // publish a distinct price to each half
     let n_pubs = pub_keypairs.len();
-
-    // Divide publishers into two even parts (assuming the max PC_NUM_COMP size is even)
-    let (first_half, second_half) = pub_keypairs.split_at(n_pubs / 2);
-
-    // Starting with the first publisher in each half, publish an update
-    for (first_kp, second_kp) in first_half.iter().zip(second_half.iter()) {
-        let first_quote = Quote {
-            price:      100,
-            confidence: 30,
-            status:     PC_STATUS_TRADING,
-        };
-
-        sim.upd_price(first_kp, price, first_quote).await?;
-
-        let second_quote = Quote {
-            price:      120,
-            confidence: 30,
-            status:     PC_STATUS_TRADING,
-        };
-
-        sim.upd_price(second_kp, price, second_quote).await?;
-    }
+    let quotes: Vec<Quote> = (0..n_pubs)
+        .map(|idx| quote(if idx < n_pubs / 2 { 100 } else { 120 }))
+        .collect();
+    sim.publish_all(price, &pub_keypairs, &quotes).await?;
 
     // Advance slot once from 1 to 2
     sim.warp_to_slot(2).await?;
 
     // Final price update to trigger aggregation
-    let first_kp = pub_keypairs.first().unwrap();
-    let first_quote = Quote {
-        price:      100,
-        confidence: 30,
-        status:     PC_STATUS_TRADING,
-    };
-    sim.upd_price(first_kp, price, first_quote).await?;
+    sim.upd_price(&pub_keypairs[0], price, quote(100)).await?;
 
     {
         let price_data = sim
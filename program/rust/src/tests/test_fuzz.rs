@@ -0,0 +1,188 @@
+use {
+    crate::{
+        accounts::MappingAccount,
+        c_oracle_header::{
+            PC_PROD_ACC_SIZE,
+            PC_VERSION,
+        },
+        fuzz::{
+            run,
+            ACCOUNT_PERMISSIONS,
+            ACCOUNT_RAW,
+            ACCOUNT_ZEROED,
+            FLAG_SIGNER,
+            FLAG_WRITABLE,
+            KEY_CLOCK,
+            KEY_PERMISSIONS,
+            OWNER_PROGRAM,
+            OWNER_SHIFT,
+            OWNER_SYSTEM_PROGRAM,
+            OWNER_SYSVAR,
+        },
+        instruction::{
+            CommandHeader,
+            OracleCommand,
+        },
+    },
+    quickcheck_macros::quickcheck,
+    std::mem::size_of,
+};
+
+/// Builds inputs of the fuzz harness
+#[derive(Default)]
+struct FuzzInput(Vec<u8>);
+
+impl FuzzInput {
+    fn new(num_accounts: u8) -> Self {
+        FuzzInput(vec![num_accounts])
+    }
+
+    fn account(mut self, flags: u8, owner: u8, kind: u8, data_len: usize) -> Self {
+        self.0.push(flags | (owner << OWNER_SHIFT));
+        self.0.push(kind);
+        self.0
+            .extend_from_slice(&u16::try_from(data_len).unwrap().to_le_bytes());
+        self
+    }
+
+    fn bytes(mut self, bytes: &[u8]) -> Self {
+        self.0.extend_from_slice(bytes);
+        self
+    }
+
+    fn instruction(mut self, accounts: &[u8], data: &[u8]) -> Self {
+        self.0.push(accounts.len() as u8);
+        self.0.extend_from_slice(accounts);
+        self.0
+            .extend_from_slice(&u16::try_from(data.len()).unwrap().to_le_bytes());
+        self.0.extend_from_slice(data);
+        self
+    }
+}
+
+fn command(command: OracleCommand, version: u32) -> Vec<u8> {
+    bytemuck::bytes_of(&CommandHeader {
+        version,
+        command: command as i32,
+    })
+    .to_vec()
+}
+
+/// The harness never panics on an arbitrary input
+#[quickcheck]
+fn test_fuzz_arbitrary_input(input: Vec<u8>) {
+    run(&input);
+}
+
+#[test]
+fn test_fuzz() {
+    let signer_writable = FLAG_SIGNER | FLAG_WRITABLE;
+    let accounts = FuzzInput::new(4)
+        // 0: funding account, which is also the master authority
+        .account(signer_writable, OWNER_SYSTEM_PROGRAM, ACCOUNT_ZEROED, 0)
+        // 1: fresh mapping account
+        .account(
+            signer_writable,
+            OWNER_PROGRAM,
+            ACCOUNT_ZEROED,
+            size_of::<MappingAccount>(),
+        )
+        // 2: fresh product account
+        .account(
+            signer_writable,
+            OWNER_PROGRAM,
+            ACCOUNT_ZEROED,
+            PC_PROD_ACC_SIZE as usize,
+        )
+        // 3: permissions account
+        .account(
+            0,
+            OWNER_PROGRAM,
+            (KEY_PERMISSIONS << 4) | ACCOUNT_PERMISSIONS,
+            0,
+        );
+
+    assert_eq!(run(&accounts.0), 0);
+
+    let input = accounts
+        .instruction(&[0, 1, 3], &command(OracleCommand::InitMapping, PC_VERSION))
+        // The mapping account isn't fresh anymore
+        .instruction(&[0, 1, 3], &command(OracleCommand::InitMapping, PC_VERSION))
+        .instruction(
+            &[0, 1, 2, 3],
+            &command(OracleCommand::AddProduct, PC_VERSION),
+        )
+        // Wrong version
+        .instruction(
+            &[0, 1, 3],
+            &command(OracleCommand::InitMapping, PC_VERSION + 1),
+        )
+        // Wrong number of accounts, the indices wrap around
+        .instruction(&[4, 5], &command(OracleCommand::InitMapping, PC_VERSION));
+    assert_eq!(run(&input.0), 2);
+
+    // A deleted account is cleared at the end of the instruction, so it can be added again
+    let input = FuzzInput::new(4)
+        .account(signer_writable, OWNER_SYSTEM_PROGRAM, ACCOUNT_ZEROED, 0)
+        .account(
+            signer_writable,
+            OWNER_PROGRAM,
+            ACCOUNT_ZEROED,
+            size_of::<MappingAccount>(),
+        )
+        .account(
+            signer_writable,
+            OWNER_PROGRAM,
+            ACCOUNT_ZEROED,
+            PC_PROD_ACC_SIZE as usize,
+        )
+        .account(
+            0,
+            OWNER_PROGRAM,
+            (KEY_PERMISSIONS << 4) | ACCOUNT_PERMISSIONS,
+            0,
+        )
+        .instruction(&[0, 1, 3], &command(OracleCommand::InitMapping, PC_VERSION));
+    let add_product = command(OracleCommand::AddProduct, PC_VERSION);
+    let input = input
+        .instruction(&[0, 1, 2, 3], &add_product)
+        .instruction(&[0, 1, 2, 3], &add_product)
+        .instruction(
+            &[0, 1, 2, 3],
+            &command(OracleCommand::DelProduct, PC_VERSION),
+        )
+        .instruction(&[0, 1, 2, 3], &add_product);
+    assert_eq!(run(&input.0), 4);
+
+    // Only the accounts outside of the program hold the raw data of the input
+    let input = FuzzInput::new(3)
+        .account(signer_writable, OWNER_SYSTEM_PROGRAM, ACCOUNT_RAW, 32)
+        .bytes(&[0xab; 32])
+        .account(
+            signer_writable,
+            OWNER_PROGRAM,
+            ACCOUNT_RAW,
+            size_of::<MappingAccount>(),
+        )
+        .bytes(&[0xab; size_of::<MappingAccount>()])
+        .account(
+            0,
+            OWNER_PROGRAM,
+            (KEY_PERMISSIONS << 4) | ACCOUNT_PERMISSIONS,
+            0,
+        )
+        .instruction(&[0, 1, 2], &command(OracleCommand::InitMapping, PC_VERSION));
+    assert_eq!(run(&input.0), 1);
+
+    // Sysvars can hold arbitrary data too
+    let input = FuzzInput::new(1)
+        .account(0, OWNER_SYSVAR, (KEY_CLOCK << 4) | ACCOUNT_RAW, 40)
+        .bytes(&[0xab; 40])
+        .instruction(&[0, 0, 0, 0], &command(OracleCommand::UpdPrice, PC_VERSION));
+    assert_eq!(run(&input.0), 0);
+
+    // Truncated inputs
+    assert_eq!(run(&[]), 0);
+    assert_eq!(run(&FuzzInput::default().0), 0);
+    assert_eq!(run(&[8, 0xff]), 0);
+}
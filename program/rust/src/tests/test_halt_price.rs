@@ -0,0 +1,189 @@
+use {
+    crate::{
+        accounts::{
+            PermissionAccount,
+            PriceAccount,
+            PythAccount,
+        },
+        c_oracle_header::{
+            PC_STATUS_HALTED,
+            PC_STATUS_TRADING,
+            PC_STATUS_UNKNOWN,
+            PC_VERSION,
+        },
+        deserialize::load_checked,
+        error::OracleError,
+        instruction::{
+            CommandHeader,
+            OracleCommand,
+        },
+        processor::process_instruction,
+        tests::{
+            pyth_simulator::{
+                PythSimulator,
+                Quote,
+                TransactionFailure,
+            },
+            test_utils::AccountSetup,
+        },
+    },
+    bytemuck::bytes_of,
+    solana_program::{
+        instruction::InstructionError,
+        pubkey::Pubkey,
+    },
+    solana_sdk::{
+        signature::Keypair,
+        signer::Signer,
+    },
+};
+
+#[tokio::test]
+async fn test_halt_price() {
+    let mut sim = PythSimulator::new().await;
+    let publisher = Keypair::new();
+    let security_authority = Keypair::new();
+    let price_accounts = sim
+        .setup_product_fixture(&[publisher.pubkey()], security_authority.pubkey())
+        .await;
+    let price = price_accounts["LTC"];
+    sim.set_min_pub(price, 1).await.unwrap();
+
+    let quote = |price: i64| Quote {
+        price,
+        confidence: 5,
+        status: PC_STATUS_TRADING,
+    };
+
+    sim.upd_price(&publisher, price, quote(100)).await.unwrap();
+    sim.warp_to_slot(2).await.unwrap();
+    sim.upd_price(&publisher, price, quote(110)).await.unwrap();
+
+    sim.halt_price(price).await.unwrap();
+    {
+        let price_data = sim
+            .get_account_data_as::<PriceAccount>(price)
+            .await
+            .unwrap();
+        assert_eq!(price_data.agg_.status_, PC_STATUS_HALTED);
+        assert_eq!(price_data.agg_.price_, 100);
+    }
+
+    // Publishers can't update a halted price
+    sim.warp_to_slot(3).await.unwrap();
+    sim.upd_price(&publisher, price, quote(120))
+        .await
+        .assert_fails_with(OracleError::PriceHalted);
+    {
+        let price_data = sim
+            .get_account_data_as::<PriceAccount>(price)
+            .await
+            .unwrap();
+        assert_eq!(price_data.agg_.status_, PC_STATUS_HALTED);
+        assert_eq!(price_data.comp_[0].latest_.price_, 110);
+    }
+
+    sim.resume_price(price).await.unwrap();
+    {
+        let price_data = sim
+            .get_account_data_as::<PriceAccount>(price)
+            .await
+            .unwrap();
+        assert_eq!(price_data.agg_.status_, PC_STATUS_UNKNOWN);
+    }
+
+    // Only halted prices can be resumed
+    sim.resume_price(price)
+        .await
+        .assert_fails_with(InstructionError::InvalidArgument);
+
+    // Updates are accepted again and the aggregation resumes
+    sim.upd_price(&publisher, price, quote(120)).await.unwrap();
+    {
+        let price_data = sim
+            .get_account_data_as::<PriceAccount>(price)
+            .await
+            .unwrap();
+        assert_eq!(price_data.agg_.status_, PC_STATUS_TRADING);
+        assert_eq!(price_data.agg_.price_, 110);
+        assert_eq!(price_data.agg_.pub_slot_, 3);
+        assert_eq!(price_data.comp_[0].latest_.price_, 120);
+    }
+}
+
+#[test]
+fn test_halt_price_permissions() {
+    let program_id = Pubkey::new_unique();
+
+    let mut funding_setup = AccountSetup::new_funding();
+    let funding_account = funding_setup.as_account_info();
+
+    let mut attacker_setup = AccountSetup::new_funding();
+    let attacker_account = attacker_setup.as_account_info();
+
+    let mut price_setup = AccountSetup::new::<PriceAccount>(&program_id);
+    let price_account = price_setup.as_account_info();
+    PriceAccount::initialize(&price_account, PC_VERSION).unwrap();
+
+    let mut permissions_setup = AccountSetup::new_permission(&program_id);
+    let permissions_account = permissions_setup.as_account_info();
+    {
+        let mut permissions_account_data =
+            PermissionAccount::initialize(&permissions_account, PC_VERSION).unwrap();
+        permissions_account_data.master_authority = *funding_account.key;
+    }
+
+    let halt: CommandHeader = OracleCommand::HaltPrice.into();
+    let resume: CommandHeader = OracleCommand::ResumePrice.into();
+
+    for cmd in [halt, resume] {
+        assert_eq!(
+            process_instruction(
+                &program_id,
+                &[
+                    attacker_account.clone(),
+                    price_account.clone(),
+                    permissions_account.clone(),
+                ],
+                bytes_of(&cmd),
+            ),
+            Err(OracleError::PermissionViolation.into())
+        );
+    }
+
+    process_instruction(
+        &program_id,
+        &[
+            funding_account.clone(),
+            price_account.clone(),
+            permissions_account.clone(),
+        ],
+        bytes_of(&halt),
+    )
+    .unwrap();
+    assert_eq!(
+        load_checked::<PriceAccount>(&price_account, PC_VERSION)
+            .unwrap()
+            .agg_
+            .status_,
+        PC_STATUS_HALTED
+    );
+
+    process_instruction(
+        &program_id,
+        &[
+            funding_account.clone(),
+            price_account.clone(),
+            permissions_account.clone(),
+        ],
+        bytes_of(&resume),
+    )
+    .unwrap();
+    assert_eq!(
+        load_checked::<PriceAccount>(&price_account, PC_VERSION)
+            .unwrap()
+            .agg_
+            .status_,
+        PC_STATUS_UNKNOWN
+    );
+}
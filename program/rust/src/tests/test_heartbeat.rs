@@ -0,0 +1,148 @@
+use {
+    crate::{
+        accounts::{
+            PriceAccount,
+            PublisherStats,
+        },
+        c_oracle_header::PC_STATUS_TRADING,
+        error::OracleError,
+        tests::pyth_simulator::{
+            PythSimulator,
+            Quote,
+            TransactionFailure,
+        },
+    },
+    bytemuck::Zeroable,
+    solana_program::native_token::LAMPORTS_PER_SOL,
+    solana_sdk::{
+        signature::Keypair,
+        signer::Signer,
+    },
+};
+
+/// The statistics of `publisher` in `price_data`
+fn stats(price_data: &PriceAccount, publisher: &Keypair) -> PublisherStats {
+    *price_data.publisher_stats(&publisher.pubkey()).unwrap()
+}
+
+fn quote(price: i64) -> Quote {
+    Quote {
+        price,
+        confidence: 1,
+        status: PC_STATUS_TRADING,
+    }
+}
+
+#[test]
+fn test_record_heartbeat() {
+    let mut stats = PublisherStats::zeroed();
+    assert_eq!(stats.last_seen_slot(), 0);
+
+    stats.record_update(10);
+    assert_eq!(stats.last_seen_slot(), 10);
+    stats.record_heartbeat(25);
+    assert_eq!(stats.last_seen_slot(), 25);
+    assert_eq!(stats.last_upd_slot_, 10);
+    assert_eq!(stats.num_updates_in_slot(25), 0);
+
+    // A price update resets the heartbeat
+    stats.record_update(30);
+    assert_eq!(stats.last_seen_slot(), 30);
+    assert_eq!(stats.last_trade_.heartbeat_slots_, 0);
+
+    // Heartbeats too far from the last price update saturate
+    stats.record_heartbeat(30 + u64::from(u32::MAX) + 1);
+    assert_eq!(stats.last_seen_slot(), 30 + u64::from(u32::MAX));
+}
+
+#[tokio::test]
+async fn test_heartbeat() {
+    let mut sim = PythSimulator::new().await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(2).await;
+    let price = price_keypair.pubkey();
+    sim.set_min_pub(price, 1).await.unwrap();
+
+    sim.warp_to_slot(2).await.unwrap();
+    sim.publish_all(price, &publishers, &[quote(100), quote(110)])
+        .await
+        .unwrap();
+    let before = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(stats(&before, &publishers[0]).last_seen_slot(), 2);
+
+    // A heartbeat only bumps the last seen slot of the publisher
+    sim.warp_to_slot(30).await.unwrap();
+    sim.heartbeat(&publishers[0], price).await.unwrap();
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    let publisher_stats = stats(&price_data, &publishers[0]);
+    assert_eq!(publisher_stats.last_seen_slot(), 30);
+    assert_eq!(publisher_stats.last_upd_slot_, 2);
+    assert_eq!(
+        publisher_stats.num_updates_,
+        stats(&before, &publishers[0]).num_updates_
+    );
+    assert_eq!(price_data.comp_, before.comp_);
+    assert_eq!(
+        stats(&price_data, &publishers[1]),
+        stats(&before, &publishers[1])
+    );
+    // It doesn't aggregate either
+    assert_eq!(price_data.last_slot_, before.last_slot_);
+    assert_eq!(price_data.agg_, before.agg_);
+
+    // The quote of the publisher doesn't stay fresh with heartbeats and is left out of the
+    // aggregate once stale
+    sim.upd_price(&publishers[1], price, quote(120))
+        .await
+        .unwrap();
+    sim.warp_to_slot(31).await.unwrap();
+    sim.heartbeat(&publishers[0], price).await.unwrap();
+    sim.upd_price(&publishers[1], price, quote(130))
+        .await
+        .unwrap();
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(price_data.agg_.status_, PC_STATUS_TRADING);
+    assert_eq!(price_data.agg_.price_, 120);
+    assert_eq!(price_data.num_qt_, 1);
+    assert_eq!(stats(&price_data, &publishers[0]).last_seen_slot(), 31);
+
+    // Heartbeats don't count toward the max number of updates per slot
+    sim.set_max_updates_per_slot(price, 1).await.unwrap();
+    sim.warp_to_slot(32).await.unwrap();
+    sim.heartbeat(&publishers[0], price).await.unwrap();
+    sim.upd_price(&publishers[0], price, quote(140))
+        .await
+        .unwrap();
+    sim.heartbeat(&publishers[0], price).await.unwrap();
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    let publisher_stats = stats(&price_data, &publishers[0]);
+    assert_eq!(publisher_stats.last_seen_slot(), 32);
+    assert_eq!(publisher_stats.num_updates_in_slot(32), 1);
+
+    // Only publishers of the price account can send heartbeats
+    let stranger = Keypair::new();
+    sim.airdrop(&stranger.pubkey(), LAMPORTS_PER_SOL)
+        .await
+        .unwrap();
+    sim.heartbeat(&stranger, price)
+        .await
+        .assert_fails_with(OracleError::PermissionViolation);
+
+    // Expired publishers and halted price accounts don't accept them
+    let trial = Keypair::new();
+    sim.airdrop(&trial.pubkey(), LAMPORTS_PER_SOL)
+        .await
+        .unwrap();
+    sim.add_publisher_with_expiry(price, trial.pubkey(), 40)
+        .await
+        .unwrap();
+    sim.heartbeat(&trial, price).await.unwrap();
+    sim.warp_to_slot(41).await.unwrap();
+    sim.heartbeat(&trial, price)
+        .await
+        .assert_fails_with(OracleError::PublisherExpired);
+
+    sim.halt_price(price).await.unwrap();
+    sim.heartbeat(&publishers[0], price)
+        .await
+        .assert_fails_with(OracleError::PriceHalted);
+}
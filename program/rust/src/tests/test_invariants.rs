@@ -0,0 +1,122 @@
+use {
+    crate::{
+        accounts::{
+            MappingAccount,
+            PriceAccount,
+            ProductAccount,
+        },
+        deserialize::load_mut,
+        tests::{
+            invariants::find_violations,
+            pyth_simulator::PythSimulator,
+        },
+    },
+    bytemuck::Pod,
+    solana_program::pubkey::Pubkey,
+    solana_sdk::signer::Signer,
+    std::collections::HashMap,
+};
+
+/// `accounts` with `corrupt` applied to the data of `key`, read as a `T`
+fn corrupted<T: Pod>(
+    accounts: &HashMap<Pubkey, Vec<u8>>,
+    key: Pubkey,
+    corrupt: impl FnOnce(&mut T),
+) -> HashMap<Pubkey, Vec<u8>> {
+    let mut accounts = accounts.clone();
+    corrupt(load_mut::<T>(accounts.get_mut(&key).unwrap()).unwrap());
+    accounts
+}
+
+fn sorted(mut violations: Vec<String>) -> Vec<String> {
+    violations.sort();
+    violations
+}
+
+#[tokio::test]
+async fn test_invariants() {
+    let mut sim = PythSimulator::new().await;
+    let publishers = [Pubkey::new_unique(), Pubkey::new_unique()];
+    let (mapping_keypair, feeds) = sim.create_feeds(2, &publishers).await;
+    let mapping = mapping_keypair.pubkey();
+    let [(product, price), (other_product, other_price)] = [feeds[0], feeds[1]];
+    sim.check_invariants();
+
+    let mut accounts = HashMap::new();
+    for key in [mapping, product, price, other_product, other_price] {
+        accounts.insert(key, sim.get_account(key).await.unwrap().data);
+    }
+    assert_eq!(find_violations(&accounts, true), Vec::<String>::new());
+
+    // The accounts linked to can only be missing if the accounts are known to be incomplete
+    let mut without_product = accounts.clone();
+    without_product.remove(&product);
+    assert_eq!(
+        find_violations(&without_product, true),
+        vec![
+            format!("mapping {mapping}: {product} is not a product account"),
+            format!("price {price}: in the lists of [] instead of {product}"),
+        ]
+    );
+    assert_eq!(
+        find_violations(&without_product, false),
+        Vec::<String>::new()
+    );
+
+    // A corrupted header hides the account from the accounts linking to it
+    let flipped_magic = corrupted(&accounts, price, |price_data: &mut PriceAccount| {
+        price_data.header.magic_number = 0xdeadbeef;
+    });
+    assert_eq!(
+        find_violations(&flipped_magic, true),
+        vec![
+            format!("account {price}: magic number 0xdeadbeef"),
+            format!("product {product}: {price} is not a price account"),
+        ]
+    );
+
+    let extra_component = corrupted(&accounts, price, |price_data: &mut PriceAccount| {
+        price_data.num_ += 1;
+    });
+    assert_eq!(
+        find_violations(&extra_component, true),
+        vec![format!(
+            "price {price}: empty entry 2 of 3 in the components"
+        )]
+    );
+
+    // Listing a product twice leaves it in two mappings, and the product it replaced in none
+    let duplicate_product = corrupted(&accounts, mapping, |mapping_data: &mut MappingAccount| {
+        mapping_data.products_list[1] = product;
+    });
+    assert_eq!(
+        find_violations(&duplicate_product, true),
+        sorted(vec![
+            format!("mapping {mapping}: duplicate entry {product} in the product list"),
+            format!("product {product}: in 2 mappings"),
+            format!("product {other_product}: in 0 mappings"),
+        ])
+    );
+
+    let cycle = corrupted(&accounts, price, |price_data: &mut PriceAccount| {
+        price_data.next_price_account = price;
+    });
+    assert_eq!(
+        find_violations(&cycle, true),
+        vec![format!("product {product}: cycle through {price}")]
+    );
+
+    // Linking a price account to another product unlinks the price account it replaced
+    let stray_price = corrupted(
+        &accounts,
+        other_product,
+        |product_data: &mut ProductAccount| {
+            product_data.first_price_account = price;
+        },
+    );
+    let violations = find_violations(&stray_price, true);
+    assert_eq!(violations.len(), 2);
+    assert!(violations.contains(&format!(
+        "price {other_price}: in the lists of [] instead of {other_product}"
+    )));
+}
@@ -0,0 +1,253 @@
+use {
+    crate::{
+        accounts::{
+            MappingAccount,
+            PriceAccount,
+            PythAccount,
+        },
+        c_oracle_header::PC_STATUS_TRADING,
+        layout::*,
+        tests::pyth_simulator::{
+            PythSimulator,
+            Quote,
+        },
+    },
+    bytemuck::pod_read_unaligned,
+    solana_program::pubkey::Pubkey,
+    solana_sdk::signer::Signer,
+};
+
+/// The offsets are part of the binary interface of the program, changing any of them breaks the
+/// clients that read the accounts
+#[test]
+fn test_layout_offsets() {
+    assert_eq!(ACCOUNT_HEADER_SIZE, 16);
+    assert_eq!(
+        [
+            ACCOUNT_HEADER_MAGIC_OFFSET,
+            ACCOUNT_HEADER_VERSION_OFFSET,
+            ACCOUNT_HEADER_ACCOUNT_TYPE_OFFSET,
+            ACCOUNT_HEADER_SIZE_OFFSET,
+        ],
+        [0, 4, 8, 12]
+    );
+
+    assert_eq!(MAPPING_ACCOUNT_SIZE, 20536);
+    assert_eq!(MAPPING_ACCOUNT_NUMBER_OF_PRODUCTS_OFFSET, 16);
+    assert_eq!(MAPPING_ACCOUNT_NEXT_MAPPING_ACCOUNT_OFFSET, 24);
+    assert_eq!(MAPPING_ACCOUNT_PRODUCTS_LIST_OFFSET, 56);
+    assert_eq!(
+        MAPPING_ACCOUNT_PRODUCTS_LIST_OFFSET as u32,
+        MappingAccount::INITIAL_SIZE
+    );
+
+    assert_eq!(PRODUCT_ACCOUNT_SIZE, 512);
+    assert_eq!(PRODUCT_ACCOUNT_FIRST_PRICE_ACCOUNT_OFFSET, 16);
+    assert_eq!(PRODUCT_ACCOUNT_ATTRIBUTES_OFFSET, 48);
+    assert_eq!(PRODUCT_ACCOUNT_REFERENCE_PRICE_OFFSET, 512);
+
+    assert_eq!(PRICE_ACCOUNT_ALIGN, 8);
+    assert_eq!(
+        [
+            PRICE_ACCOUNT_PRICE_TYPE_OFFSET,
+            PRICE_ACCOUNT_EXPONENT_OFFSET,
+            PRICE_ACCOUNT_NUM_OFFSET,
+            PRICE_ACCOUNT_NUM_QT_OFFSET,
+            PRICE_ACCOUNT_LAST_SLOT_OFFSET,
+            PRICE_ACCOUNT_VALID_SLOT_OFFSET,
+            PRICE_ACCOUNT_TWAP_OFFSET,
+            PRICE_ACCOUNT_TWAC_OFFSET,
+            PRICE_ACCOUNT_TIMESTAMP_OFFSET,
+            PRICE_ACCOUNT_MIN_PUB_OFFSET,
+            PRICE_ACCOUNT_PRODUCT_ACCOUNT_OFFSET,
+            PRICE_ACCOUNT_NEXT_PRICE_ACCOUNT_OFFSET,
+            PRICE_ACCOUNT_PREV_SLOT_OFFSET,
+            PRICE_ACCOUNT_PREV_PRICE_OFFSET,
+            PRICE_ACCOUNT_PREV_CONF_OFFSET,
+            PRICE_ACCOUNT_PREV_TIMESTAMP_OFFSET,
+            PRICE_ACCOUNT_AGG_OFFSET,
+            PRICE_ACCOUNT_COMP_OFFSET,
+        ],
+        [16, 20, 24, 28, 32, 40, 48, 72, 96, 104, 112, 144, 176, 184, 192, 200, 208, 240]
+    );
+    // The layout version is in the space that was unused before layout versions
+    assert_eq!(PRICE_ACCOUNT_LAYOUT_VERSION_OFFSET, 111);
+    #[cfg(feature = "pythnet")]
+    assert_eq!(
+        [
+            PRICE_ACCOUNT_SIZE,
+            PRICE_ACCOUNT_PUBLISHER_STATS_OFFSET,
+            PRICE_ACCOUNT_FAST_AGG_OFFSET,
+            PRICE_ACCOUNT_FEED_INDEX_OFFSET,
+            PRICE_ACCOUNT_STATUS_CHANGE_OFFSET,
+            PRICE_ACCOUNT_STATUS_HISTORY_OFFSET,
+            PRICE_ACCOUNT_PRICE_CUMULATIVE_OFFSET,
+            PRICE_ACCOUNT_AGGREGATE_COUNTER_OFFSET,
+        ],
+        [12576, 6384, 12016, 12052, 12248, 12400, 12528, 12568]
+    );
+    #[cfg(feature = "solana")]
+    assert_eq!(
+        [
+            PRICE_ACCOUNT_SIZE,
+            PRICE_ACCOUNT_PUBLISHER_STATS_OFFSET,
+            PRICE_ACCOUNT_FAST_AGG_OFFSET,
+            PRICE_ACCOUNT_FEED_INDEX_OFFSET,
+            PRICE_ACCOUNT_STATUS_CHANGE_OFFSET,
+            PRICE_ACCOUNT_PRICE_CUMULATIVE_OFFSET,
+            PRICE_ACCOUNT_AGGREGATE_COUNTER_OFFSET,
+        ],
+        [6432, 3312, 6128, 6164, 6360, 6384, 6424]
+    );
+
+    assert_eq!(PRICE_ACCOUNT_EXTENSIONS_OFFSET, PRICE_ACCOUNT_SIZE);
+    #[cfg(feature = "pythnet")]
+    assert_eq!(PRICE_ACCOUNT_MAX_SIZE, 23400);
+    #[cfg(feature = "solana")]
+    assert_eq!(PRICE_ACCOUNT_MAX_SIZE, 13416);
+    assert_eq!(
+        [
+            EXTENSION_HEADER_SIZE,
+            EXTENSION_HEADER_KIND_OFFSET,
+            EXTENSION_HEADER_SIZE_OFFSET,
+        ],
+        [8, 0, 4]
+    );
+    assert_eq!(
+        [
+            EXTENSION_KIND_PUBLISHER_EXPIRIES,
+            EXTENSION_KIND_PUBLISHER_PRUNING,
+            EXTENSION_KIND_AGG_INTERVAL,
+            EXTENSION_KIND_PUBLISHER_WEIGHTS,
+            EXTENSION_KIND_TIME_MACHINE,
+        ],
+        [1, 2, 3, 4, 5]
+    );
+    assert_eq!(
+        [
+            PUBLISHER_EXPIRY_SIZE,
+            PUBLISHER_EXPIRY_PUBLISHER_OFFSET,
+            PUBLISHER_EXPIRY_EXPIRY_SLOT_OFFSET,
+        ],
+        [40, 0, 32]
+    );
+    assert_eq!(
+        [
+            PUBLISHER_WEIGHT_SIZE,
+            PUBLISHER_WEIGHT_PUBLISHER_OFFSET,
+            PUBLISHER_WEIGHT_WEIGHT_OFFSET,
+        ],
+        [40, 0, 32]
+    );
+    assert_eq!(
+        [
+            PUBLISHER_PRUNING_PRUNE_ROUNDS_OFFSET,
+            PUBLISHER_PRUNING_READMIT_ROUNDS_OFFSET,
+            PUBLISHER_PRUNING_STATES_OFFSET,
+        ],
+        [0, 1, 8]
+    );
+    assert_eq!(
+        [
+            PRUNING_STATE_SIZE,
+            PRUNING_STATE_PUBLISHER_OFFSET,
+            PRUNING_STATE_PRUNED_OFFSET,
+            PRUNING_STATE_ROUNDS_OFFSET,
+        ],
+        [40, 0, 32, 36]
+    );
+    assert_eq!(
+        [
+            TIME_MACHINE_NUM_BUCKETS_OFFSET,
+            TIME_MACHINE_VALID_OFFSET,
+            TIME_MACHINE_BUCKETS_OFFSET,
+        ],
+        [0, 8, 16]
+    );
+    assert_eq!(
+        [
+            TIME_MACHINE_BUCKET_SIZE,
+            TIME_MACHINE_BUCKET_START_TIME_OFFSET,
+            TIME_MACHINE_BUCKET_NUM_SLOTS_OFFSET,
+            TIME_MACHINE_BUCKET_PRICE_SUM_OFFSET,
+            TIME_MACHINE_BUCKET_CONF_SUM_OFFSET,
+        ],
+        [48, 0, 8, 16, 32]
+    );
+
+    assert_eq!(COMPRESSED_PRICE_ACCOUNT_SIZE, 272);
+
+    assert_eq!(PRICE_COMPONENT_SIZE, 96);
+    assert_eq!(
+        [
+            PRICE_COMPONENT_PUB_OFFSET,
+            PRICE_COMPONENT_AGG_OFFSET,
+            PRICE_COMPONENT_LATEST_OFFSET,
+        ],
+        [0, 32, 64]
+    );
+    assert_eq!(PRICE_INFO_SIZE, 32);
+    assert_eq!(
+        [
+            PRICE_INFO_PRICE_OFFSET,
+            PRICE_INFO_CONF_OFFSET,
+            PRICE_INFO_STATUS_OFFSET,
+            PRICE_INFO_CORP_ACT_STATUS_OFFSET,
+            PRICE_INFO_PUB_SLOT_OFFSET,
+        ],
+        [0, 8, 16, 20, 24]
+    );
+}
+
+/// A client can read a price account with the offsets alone
+#[tokio::test]
+async fn test_layout_read_price_account() {
+    let mut sim = PythSimulator::new().await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(2).await;
+    let price = price_keypair.pubkey();
+    let quotes = [Quote {
+        price:      100,
+        confidence: 1,
+        status:     PC_STATUS_TRADING,
+    }; 2];
+    sim.publish_all(price, &publishers, &quotes).await.unwrap();
+    sim.warp_to_slot(2).await.unwrap();
+    sim.publish_all(price, &publishers, &quotes).await.unwrap();
+
+    let data = sim.get_account(price).await.unwrap().data;
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(data.len(), PRICE_ACCOUNT_SIZE);
+
+    let read_i64 = |offset: usize| pod_read_unaligned::<i64>(&data[offset..offset + 8]);
+    let read_u32 = |offset: usize| pod_read_unaligned::<u32>(&data[offset..offset + 4]);
+    let read_pubkey = |offset: usize| Pubkey::new(&data[offset..offset + 32]);
+
+    assert_eq!(
+        read_u32(PRICE_ACCOUNT_EXPONENT_OFFSET) as i32,
+        price_data.exponent
+    );
+    assert_eq!(read_u32(PRICE_ACCOUNT_NUM_OFFSET), 2);
+    assert_eq!(
+        read_pubkey(PRICE_ACCOUNT_PRODUCT_ACCOUNT_OFFSET),
+        price_data.product_account
+    );
+    assert_eq!(
+        read_i64(PRICE_ACCOUNT_AGG_OFFSET + PRICE_INFO_PRICE_OFFSET),
+        price_data.agg_.price_
+    );
+    for (i, publisher) in publishers.iter().enumerate() {
+        let component = PRICE_ACCOUNT_COMP_OFFSET + i * PRICE_COMPONENT_SIZE;
+        assert_eq!(
+            read_pubkey(component + PRICE_COMPONENT_PUB_OFFSET),
+            publisher.pubkey()
+        );
+        assert_eq!(
+            read_i64(component + PRICE_COMPONENT_LATEST_OFFSET + PRICE_INFO_PRICE_OFFSET),
+            100
+        );
+    }
+    assert_eq!(
+        read_u32(PRICE_ACCOUNT_FEED_INDEX_OFFSET),
+        price_data.feed_index_
+    );
+}
@@ -0,0 +1,53 @@
+use {
+    crate::tests::pyth_simulator::PythSimulator,
+    solana_program::pubkey::Pubkey,
+    solana_sdk::signer::Signer,
+};
+
+#[tokio::test]
+async fn test_list_accounts() {
+    let mut sim = PythSimulator::new().await;
+    let mapping_keypair = sim.init_mapping().await.unwrap();
+    assert!(sim.list_products(mapping_keypair.pubkey()).await.is_empty());
+
+    let product_1 = sim.add_product(&mapping_keypair).await.unwrap();
+    let product_2 = sim.add_product(&mapping_keypair).await.unwrap();
+    let price_1 = sim.add_price(&product_1, -5).await.unwrap();
+    let price_2 = sim.add_price(&product_1, -8).await.unwrap();
+
+    let products = sim.list_products(mapping_keypair.pubkey()).await;
+    assert_eq!(
+        products
+            .iter()
+            .map(|(product, _)| *product)
+            .collect::<Vec<_>>(),
+        vec![product_1.pubkey(), product_2.pubkey()]
+    );
+    assert_eq!(products[0].1.first_price_account, price_2.pubkey());
+    assert_eq!(products[1].1.first_price_account, Pubkey::default());
+
+    // New price accounts are added at the head of the list
+    let prices = sim.list_prices(product_1.pubkey()).await;
+    assert_eq!(
+        prices.iter().map(|(price, _)| *price).collect::<Vec<_>>(),
+        vec![price_2.pubkey(), price_1.pubkey()]
+    );
+    assert_eq!(prices[0].1.exponent, -8);
+    assert_eq!(prices[1].1.exponent, -5);
+    assert!(sim.list_prices(product_2.pubkey()).await.is_empty());
+
+    sim.del_price(&product_1, &price_2).await.unwrap();
+    let prices = sim.list_prices(product_1.pubkey()).await;
+    assert_eq!(
+        prices.iter().map(|(price, _)| *price).collect::<Vec<_>>(),
+        vec![price_1.pubkey()]
+    );
+}
+
+#[tokio::test]
+#[should_panic(expected = "is not an account of type")]
+async fn test_list_prices_of_invalid_product() {
+    let mut sim = PythSimulator::new().await;
+    let mapping_keypair = sim.init_mapping().await.unwrap();
+    sim.list_prices(mapping_keypair.pubkey()).await;
+}
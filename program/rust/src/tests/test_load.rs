@@ -0,0 +1,136 @@
+use {
+    crate::{
+        accounts::{
+            MappingAccount,
+            PriceAccount,
+            ProductAccount,
+        },
+        c_oracle_header::{
+            PC_MAP_TABLE_SIZE,
+            PC_STATUS_TRADING,
+        },
+        tests::pyth_simulator::{
+            PythSimulator,
+            Quote,
+        },
+    },
+    rand::{
+        seq::SliceRandom,
+        Rng,
+    },
+    solana_program::pubkey::Pubkey,
+    solana_sdk::{
+        native_token::LAMPORTS_PER_SOL,
+        signature::Keypair,
+        signer::Signer,
+    },
+    std::{
+        collections::HashMap,
+        time::Instant,
+    },
+};
+
+const NUM_FEEDS: usize = 500;
+const NUM_PUBLISHERS: usize = 2;
+const NUM_ROUNDS: u64 = 20;
+const UPDATES_PER_ROUND: usize = 100;
+
+/// Setup a large number of feeds, resize their products and drive random updates. It takes a while
+/// so it is ignored by default, run it with `cargo test test_load -- --ignored --nocapture` to see
+/// the wall time of each phase.
+#[tokio::test]
+#[ignore]
+async fn test_load() {
+    let mut sim = PythSimulator::new().await;
+    let publishers: Vec<Keypair> = (0..NUM_PUBLISHERS).map(|_| Keypair::new()).collect();
+    for publisher in publishers.iter() {
+        sim.airdrop(&publisher.pubkey(), 100 * LAMPORTS_PER_SOL)
+            .await
+            .unwrap();
+    }
+    let publisher_keys: Vec<_> = publishers
+        .iter()
+        .map(|publisher| publisher.pubkey())
+        .collect();
+
+    let start = Instant::now();
+    let (mapping_keypair, feeds) = sim.create_feeds(NUM_FEEDS, &publisher_keys).await;
+    for (_, price) in feeds.iter() {
+        sim.set_min_pub(*price, 1).await.unwrap();
+    }
+    println!("Setup of {NUM_FEEDS} feeds: {:?}", start.elapsed());
+
+    // Setting the reference price resizes the product accounts
+    let start = Instant::now();
+    for (product, price) in feeds.iter() {
+        sim.set_reference_price(*product, *price).await.unwrap();
+    }
+    println!("Resize of {NUM_FEEDS} products: {:?}", start.elapsed());
+
+    let start = Instant::now();
+    let mut rng = rand::thread_rng();
+    let mut last_quotes = HashMap::new();
+    for round in 0..NUM_ROUNDS {
+        sim.warp_to_slot(2 + round).await.unwrap();
+        for (_, price) in feeds.choose_multiple(&mut rng, UPDATES_PER_ROUND) {
+            let i = rng.gen_range(0..NUM_PUBLISHERS);
+            let quote = Quote {
+                price:      rng.gen_range(1..1_000_000),
+                confidence: rng.gen_range(1..1_000),
+                status:     PC_STATUS_TRADING,
+            };
+            sim.upd_price(&publishers[i], *price, quote).await.unwrap();
+            last_quotes.insert((*price, i), quote);
+        }
+    }
+    println!(
+        "{} updates: {:?}",
+        NUM_ROUNDS as usize * UPDATES_PER_ROUND,
+        start.elapsed()
+    );
+
+    // The mapping accounts list every product once, in order
+    let products = sim.list_products(mapping_keypair.pubkey()).await;
+    assert_eq!(
+        products
+            .iter()
+            .map(|(product, _)| *product)
+            .collect::<Vec<_>>(),
+        feeds
+            .iter()
+            .map(|(product, _)| *product)
+            .collect::<Vec<_>>()
+    );
+    let mapping_data = sim
+        .get_pyth_account::<MappingAccount>(mapping_keypair.pubkey())
+        .await;
+    assert_eq!(
+        mapping_data.number_of_products as usize,
+        NUM_FEEDS.min(PC_MAP_TABLE_SIZE as usize)
+    );
+
+    for (product, price) in feeds.iter() {
+        let product_account = sim.get_account(*product).await.unwrap();
+        assert_eq!(
+            product_account.data.len(),
+            ProductAccount::REFERENCE_PRICE_SIZE
+        );
+        assert_eq!(
+            Pubkey::new(&product_account.data[ProductAccount::REFERENCE_PRICE_OFFSET..]),
+            *price
+        );
+
+        let prices = sim.list_prices(*product).await;
+        assert_eq!(prices.len(), 1);
+        let price_data: PriceAccount = prices[0].1;
+        assert_eq!(prices[0].0, *price);
+        assert_eq!(price_data.num_ as usize, NUM_PUBLISHERS);
+        for (i, component) in price_data.comp_[..NUM_PUBLISHERS].iter().enumerate() {
+            assert_eq!(component.pub_, publishers[i].pubkey());
+            if let Some(quote) = last_quotes.get(&(*price, i)) {
+                assert_eq!(component.latest_.price_, quote.price);
+                assert_eq!(component.latest_.conf_, quote.confidence);
+            }
+        }
+    }
+}
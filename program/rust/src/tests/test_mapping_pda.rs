@@ -0,0 +1,96 @@
+use {
+    crate::{
+        accounts::{
+            MappingAccount,
+            PythAccount,
+        },
+        error::OracleError,
+        tests::pyth_simulator::{
+            PythSimulator,
+            TransactionFailure,
+        },
+    },
+    solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE,
+    solana_sdk::signer::Signer,
+    std::mem::size_of,
+};
+
+#[tokio::test]
+async fn test_init_mapping_pda() {
+    let mut sim = PythSimulator::new().await;
+    let mapping_pda = sim.get_mapping_pda();
+
+    // The PDA is allocated over several instructions and only initialized by the last one
+    sim.send_init_mapping_pda(None, 1).await.unwrap();
+    let account = sim.get_account(mapping_pda).await.unwrap();
+    assert!(sim.is_owned_by_oracle(&account));
+    assert_eq!(account.data.len(), MAX_PERMITTED_DATA_INCREASE);
+    assert!(account.data.iter().all(|byte| *byte == 0));
+    sim.add_product_to(mapping_pda)
+        .await
+        .assert_fails_with(OracleError::AccountTooSmall);
+
+    sim.send_init_mapping_pda(None, 2).await.unwrap();
+    let account = sim.get_account(mapping_pda).await.unwrap();
+    assert_eq!(account.data.len(), size_of::<MappingAccount>());
+    let mapping_data = sim.get_pyth_account::<MappingAccount>(mapping_pda).await;
+    assert_eq!(mapping_data.header.size, MappingAccount::INITIAL_SIZE);
+    assert_eq!(mapping_data.number_of_products, 0);
+
+    // Products are added to the PDA like to any mapping account
+    let product = sim.add_product_to(mapping_pda).await.unwrap();
+    let mapping_data = sim.get_pyth_account::<MappingAccount>(mapping_pda).await;
+    assert_eq!(
+        mapping_data.iter_products().collect::<Vec<_>>(),
+        [&product.pubkey()]
+    );
+
+    // The PDA can't be initialized twice
+    sim.send_init_mapping_pda(None, 1)
+        .await
+        .assert_fails_with(OracleError::InvalidFreshAccount);
+}
+
+#[tokio::test]
+async fn test_migrate_mapping_to_pda() {
+    let mut sim = PythSimulator::new().await;
+    let mapping_keypair = sim.init_mapping().await.unwrap();
+    let products = [
+        sim.add_product(&mapping_keypair).await.unwrap().pubkey(),
+        sim.add_product(&mapping_keypair).await.unwrap().pubkey(),
+    ];
+    let mapping_data = sim
+        .get_pyth_account::<MappingAccount>(mapping_keypair.pubkey())
+        .await;
+
+    // Only mapping accounts can be migrated
+    let fresh_account = sim.create_pyth_account(size_of::<MappingAccount>()).await;
+    assert_eq!(
+        sim.init_mapping_pda(Some(fresh_account.pubkey()))
+            .await
+            .unwrap_instruction_failure(),
+        (2, OracleError::InvalidAccountHeader.into())
+    );
+    assert!(sim.get_account(sim.get_mapping_pda()).await.is_none());
+
+    // The PDA takes over the products of the mapping account, which is closed
+    let mapping_pda = sim
+        .init_mapping_pda(Some(mapping_keypair.pubkey()))
+        .await
+        .unwrap();
+    let mapping_pda_data = sim.get_pyth_account::<MappingAccount>(mapping_pda).await;
+    assert_eq!(
+        bytemuck::bytes_of(&mapping_pda_data),
+        bytemuck::bytes_of(&mapping_data)
+    );
+    assert_eq!(
+        mapping_pda_data.iter_products().collect::<Vec<_>>(),
+        products.iter().collect::<Vec<_>>()
+    );
+    assert!(sim.get_account(mapping_keypair.pubkey()).await.is_none());
+
+    let product = sim.add_product_to(mapping_pda).await.unwrap();
+    let mapping_pda_data = sim.get_pyth_account::<MappingAccount>(mapping_pda).await;
+    assert_eq!(mapping_pda_data.number_of_products, 3);
+    assert_eq!(mapping_pda_data.products_list[2], product.pubkey());
+}
@@ -0,0 +1,171 @@
+use {
+    crate::{
+        accounts::{
+            MarketHours,
+            PriceAccount,
+        },
+        aggregation::upd_aggregate,
+        c_oracle_header::{
+            PC_STATUS_TRADING,
+            PC_STATUS_UNKNOWN,
+        },
+        tests::pyth_simulator::{
+            PythSimulator,
+            Quote,
+            TransactionFailure,
+        },
+    },
+    bytemuck::Zeroable,
+    solana_program::instruction::InstructionError,
+    solana_sdk::signer::Signer,
+};
+
+const MINUTE: i64 = 60;
+const DAY: i64 = 24 * 60 * MINUTE;
+/// 2024-01-01T00:00:00Z, a Monday
+const MONDAY: i64 = 1_704_067_200;
+
+/// Open from 14:30 to 21:00 UTC on weekdays
+const WEEKDAY_SESSIONS: [[u16; 2]; 7] = [
+    [870, 1260],
+    [870, 1260],
+    [870, 1260],
+    [870, 1260],
+    [870, 1260],
+    [0, 0],
+    [0, 0],
+];
+
+#[test]
+fn test_market_hours_boundaries() {
+    let market_hours = MarketHours::new(WEEKDAY_SESSIONS);
+    assert!(market_hours.is_valid());
+
+    // The opening minute is included and the closing minute excluded
+    assert!(!market_hours.is_open(MONDAY + 870 * MINUTE - 1));
+    assert!(market_hours.is_open(MONDAY + 870 * MINUTE));
+    assert!(market_hours.is_open(MONDAY + 1260 * MINUTE - 1));
+    assert!(!market_hours.is_open(MONDAY + 1260 * MINUTE));
+    assert!(market_hours.is_open(MONDAY + 4 * DAY + 900 * MINUTE));
+    assert!(!market_hours.is_open(MONDAY + 5 * DAY + 900 * MINUTE));
+    assert!(!market_hours.is_open(MONDAY + 6 * DAY + 900 * MINUTE));
+    assert!(market_hours.is_open(MONDAY + 7 * DAY + 900 * MINUTE));
+    assert!(market_hours.is_open(MONDAY - 7 * DAY + 900 * MINUTE));
+
+    // From Sunday 22:00 to Friday 22:00 UTC, across midnight
+    let market_hours = MarketHours::new([
+        [0, 1440],
+        [0, 1440],
+        [0, 1440],
+        [0, 1440],
+        [0, 1320],
+        [0, 0],
+        [1320, 1440],
+    ]);
+    assert!(market_hours.is_valid());
+    assert!(!market_hours.is_open(MONDAY - DAY + 1320 * MINUTE - 1));
+    assert!(market_hours.is_open(MONDAY - DAY + 1320 * MINUTE));
+    assert!(market_hours.is_open(MONDAY - 1));
+    assert!(market_hours.is_open(MONDAY));
+    assert!(market_hours.is_open(MONDAY + DAY - 1));
+    assert!(market_hours.is_open(MONDAY + DAY));
+    assert!(market_hours.is_open(MONDAY + 4 * DAY + 1320 * MINUTE - 1));
+    assert!(!market_hours.is_open(MONDAY + 4 * DAY + 1320 * MINUTE));
+
+    // Without a schedule, the market is always open
+    let market_hours = MarketHours::zeroed();
+    assert!(market_hours.is_valid());
+    assert!(market_hours.is_open(MONDAY + 5 * DAY));
+    assert!(market_hours.is_open(0));
+
+    let mut sessions = WEEKDAY_SESSIONS;
+    sessions[2] = [1260, 870];
+    assert!(!MarketHours::new(sessions).is_valid());
+    sessions[2] = [870, 1441];
+    assert!(!MarketHours::new(sessions).is_valid());
+}
+
+#[test]
+fn test_upd_aggregate_outside_market_hours() {
+    let mut price_account = PriceAccount::zeroed();
+    price_account.num_ = 1;
+    price_account.min_pub_ = 1;
+    price_account.market_hours_ = MarketHours::new(WEEKDAY_SESSIONS);
+    price_account.comp_[0].latest_.price_ = 100;
+    price_account.comp_[0].latest_.conf_ = 1;
+    price_account.comp_[0].latest_.status_ = PC_STATUS_TRADING;
+    price_account.comp_[0].latest_.pub_slot_ = 1000;
+
+    assert!(upd_aggregate(
+        &mut price_account,
+        1001,
+        MONDAY + 900 * MINUTE
+    ));
+    assert_eq!(price_account.agg_.status_, PC_STATUS_TRADING);
+    assert_eq!(price_account.fast_agg_.status_, PC_STATUS_TRADING);
+
+    assert!(!upd_aggregate(
+        &mut price_account,
+        1001,
+        MONDAY + 1260 * MINUTE
+    ));
+    assert_eq!(price_account.agg_.status_, PC_STATUS_UNKNOWN);
+    assert_eq!(price_account.fast_agg_.status_, PC_STATUS_UNKNOWN);
+    assert_eq!(price_account.last_slot_, 1001);
+}
+
+#[tokio::test]
+async fn test_set_market_hours() {
+    let mut sim = PythSimulator::new().await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(1).await;
+    let price = price_keypair.pubkey();
+    sim.set_min_pub(price, 1).await.unwrap();
+    sim.set_market_hours(price, WEEKDAY_SESSIONS).await.unwrap();
+
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(price_data.market_hours_, MarketHours::new(WEEKDAY_SESSIONS));
+
+    let quote = Quote {
+        price:      100,
+        confidence: 1,
+        status:     PC_STATUS_TRADING,
+    };
+
+    // Saturday
+    sim.set_clock_timestamp(MONDAY + 5 * DAY + 900 * MINUTE)
+        .await
+        .unwrap();
+    sim.upd_price(&publishers[0], price, quote).await.unwrap();
+    sim.warp_to_slot(2).await.unwrap();
+    sim.set_clock_timestamp(MONDAY + 5 * DAY + 900 * MINUTE)
+        .await
+        .unwrap();
+    sim.upd_price(&publishers[0], price, quote).await.unwrap();
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(price_data.agg_.status_, PC_STATUS_UNKNOWN);
+
+    // Monday
+    sim.warp_to_slot(3).await.unwrap();
+    sim.set_clock_timestamp(MONDAY + 7 * DAY + 900 * MINUTE)
+        .await
+        .unwrap();
+    sim.upd_price(&publishers[0], price, quote).await.unwrap();
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(price_data.agg_.status_, PC_STATUS_TRADING);
+    assert_eq!(price_data.agg_.price_, 100);
+
+    let mut sessions = WEEKDAY_SESSIONS;
+    sessions[0] = [870, 1441];
+    sim.set_market_hours(price, sessions)
+        .await
+        .assert_fails_with(InstructionError::InvalidArgument);
+}
@@ -0,0 +1,168 @@
+use {
+    crate::{
+        accounts::PriceAccount,
+        aggregation::{
+            exceeds_max_conf_ratio,
+            upd_aggregate,
+        },
+        c_oracle_header::{
+            PC_STATUS_TRADING,
+            PC_STATUS_UNKNOWN,
+        },
+        tests::pyth_simulator::{
+            PythSimulator,
+            Quote,
+        },
+    },
+    bytemuck::Zeroable,
+    solana_sdk::signer::Signer,
+};
+
+const CURRENT_SLOT: u64 = 1000;
+
+/// A price account with the max confidence ratio `max_conf_ratio` and a trading quote for each
+/// `(price, conf)` of `quotes`
+fn price_account_with(quotes: &[(i64, u64)], max_conf_ratio: u32) -> PriceAccount {
+    let mut price_account = PriceAccount::zeroed();
+    price_account.num_ = quotes.len() as u32;
+    price_account.min_pub_ = 1;
+    price_account.max_conf_ratio_ = max_conf_ratio;
+    for (component, (price, conf)) in price_account.comp_.iter_mut().zip(quotes.iter()) {
+        component.latest_.price_ = *price;
+        component.latest_.conf_ = *conf;
+        component.latest_.status_ = PC_STATUS_TRADING;
+        component.latest_.pub_slot_ = CURRENT_SLOT;
+    }
+    price_account
+}
+
+#[test]
+fn test_exceeds_max_conf_ratio() {
+    // A confidence of exactly the max ratio is accepted
+    assert!(!exceeds_max_conf_ratio(10_000, 100, 100));
+    assert!(exceeds_max_conf_ratio(10_000, 101, 100));
+    assert!(!exceeds_max_conf_ratio(-10_000, 100, 100));
+    assert!(exceeds_max_conf_ratio(-10_000, 101, 100));
+
+    // No limit
+    assert!(!exceeds_max_conf_ratio(10_000, u64::MAX, 0));
+    assert!(!exceeds_max_conf_ratio(0, 1, 0));
+
+    // A price of 0 only has an acceptable confidence of 0
+    assert!(exceeds_max_conf_ratio(0, 1, u32::MAX));
+    assert!(!exceeds_max_conf_ratio(0, 0, 1));
+
+    // The extremes don't overflow
+    assert!(!exceeds_max_conf_ratio(i64::MIN, u64::MAX, u32::MAX));
+    assert!(exceeds_max_conf_ratio(1, u64::MAX, u32::MAX));
+}
+
+#[test]
+fn test_max_conf_ratio_boundary() {
+    let quotes = [(10_000, 20), (10_000, 20), (10_000, 20)];
+    let mut price_account = price_account_with(&quotes, 0);
+    assert!(upd_aggregate(&mut price_account, CURRENT_SLOT + 1, 0));
+    let (price, conf) = (price_account.agg_.price_, price_account.agg_.conf_);
+    assert_eq!(price, 10_000);
+    // The ratio of the aggregate in basis points, exactly
+    assert_eq!(conf * 10_000 % price as u64, 0);
+    let ratio = (conf * 10_000 / price as u64) as u32;
+
+    // The aggregate is published up to its own ratio
+    for max_conf_ratio in [ratio, ratio + 1, u32::MAX] {
+        let mut price_account = price_account_with(&quotes, max_conf_ratio);
+        assert!(upd_aggregate(&mut price_account, CURRENT_SLOT + 1, 0));
+        assert_eq!(price_account.agg_.status_, PC_STATUS_TRADING);
+        assert_eq!(
+            (price_account.agg_.price_, price_account.agg_.conf_),
+            (price, conf)
+        );
+        assert_eq!(price_account.last_slot_, CURRENT_SLOT + 1);
+    }
+
+    // Below it, the aggregation fails and the wide aggregate isn't published
+    let mut price_account = price_account_with(&quotes, ratio - 1);
+    assert!(!upd_aggregate(&mut price_account, CURRENT_SLOT + 1, 0));
+    assert_eq!(price_account.agg_.status_, PC_STATUS_UNKNOWN);
+    assert_eq!(price_account.agg_.price_, 0);
+    assert_eq!(price_account.agg_.conf_, 0);
+    assert_eq!(price_account.last_slot_, 0);
+    assert_eq!(price_account.fast_agg_.status_, PC_STATUS_UNKNOWN);
+    // The quotes still count as valid
+    assert_eq!(price_account.num_qt_, 3);
+}
+
+#[test]
+fn test_max_conf_ratio_zero_price() {
+    // An aggregate of 0 with no uncertainty is published whatever the limit
+    let mut price_account = price_account_with(&[(0, 0), (0, 0)], 1);
+    assert!(upd_aggregate(&mut price_account, CURRENT_SLOT + 1, 0));
+    assert_eq!(price_account.agg_.status_, PC_STATUS_TRADING);
+    assert_eq!(price_account.agg_.price_, 0);
+
+    // With some uncertainty, it is only published without a limit
+    let mut price_account = price_account_with(&[(0, 1), (0, 1)], u32::MAX);
+    assert!(!upd_aggregate(&mut price_account, CURRENT_SLOT + 1, 0));
+    assert_eq!(price_account.agg_.status_, PC_STATUS_UNKNOWN);
+
+    let mut price_account = price_account_with(&[(0, 1), (0, 1)], 0);
+    assert!(upd_aggregate(&mut price_account, CURRENT_SLOT + 1, 0));
+    assert_eq!(price_account.agg_.status_, PC_STATUS_TRADING);
+}
+
+#[tokio::test]
+async fn test_set_max_conf_ratio() {
+    let mut sim = PythSimulator::new().await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(2).await;
+    let price = price_keypair.pubkey();
+    sim.set_min_pub(price, 1).await.unwrap();
+    // At most 1% of the price
+    sim.set_max_conf_ratio(price, 100).await.unwrap();
+    assert_eq!(
+        sim.get_pyth_account::<PriceAccount>(price)
+            .await
+            .max_conf_ratio_,
+        100
+    );
+
+    let quote = |price: i64, confidence: u64| Quote {
+        price,
+        confidence,
+        status: PC_STATUS_TRADING,
+    };
+
+    // Narrow quotes are published
+    sim.publish_all(price, &publishers, &[quote(1_000, 1), quote(1_000, 1)])
+        .await
+        .unwrap();
+    sim.warp_to_slot(2).await.unwrap();
+    sim.upd_price(&publishers[0], price, quote(1_000, 30))
+        .await
+        .unwrap();
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(price_data.agg_.status_, PC_STATUS_TRADING);
+    assert_eq!(price_data.agg_.price_, 1_000);
+
+    // Quotes of 3% make the aggregate too wide
+    sim.upd_price(&publishers[1], price, quote(1_000, 30))
+        .await
+        .unwrap();
+    sim.warp_to_slot(3).await.unwrap();
+    sim.upd_price(&publishers[0], price, quote(1_000, 30))
+        .await
+        .unwrap();
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(price_data.agg_.status_, PC_STATUS_UNKNOWN);
+    assert_eq!(price_data.last_slot_, 2);
+
+    // Without the limit, the same quotes are published
+    sim.set_max_conf_ratio(price, 0).await.unwrap();
+    sim.warp_to_slot(4).await.unwrap();
+    sim.upd_price(&publishers[0], price, quote(1_000, 30))
+        .await
+        .unwrap();
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(price_data.agg_.status_, PC_STATUS_TRADING);
+    assert_eq!(price_data.last_slot_, 4);
+    assert!(price_data.agg_.conf_ > 10);
+}
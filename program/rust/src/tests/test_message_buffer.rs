@@ -0,0 +1,137 @@
+use {
+    crate::{
+        accounts::{
+            MessageBufferAccount,
+            PriceAccount,
+        },
+        c_oracle_header::{
+            MESSAGE_BUFFER_CAPACITY,
+            PC_STATUS_TRADING,
+        },
+        messages::PriceMessage,
+        tests::pyth_simulator::{
+            PythSimulator,
+            Quote,
+        },
+    },
+    bytemuck::Zeroable,
+    solana_sdk::signer::Signer,
+};
+
+fn message(price: i64) -> PriceMessage {
+    PriceMessage {
+        price,
+        ..PriceMessage::zeroed()
+    }
+}
+
+#[test]
+fn test_message_buffer_wraparound() {
+    let mut message_buffer = MessageBufferAccount::zeroed();
+    assert_eq!(message_buffer.messages().count(), 0);
+
+    for price in 0..3 {
+        message_buffer.push(message(price));
+    }
+    assert_eq!(
+        message_buffer
+            .messages()
+            .map(|message| (message.sequence, message.price))
+            .collect::<Vec<_>>(),
+        vec![(0, 0), (1, 1), (2, 2)]
+    );
+
+    // Once full, the buffer keeps the last messages, oldest first
+    let num_messages = MESSAGE_BUFFER_CAPACITY as i64 + 5;
+    for price in 3..num_messages {
+        message_buffer.push(message(price));
+    }
+    assert_eq!(message_buffer.next_seq_, num_messages as u64);
+    assert_eq!(message_buffer.messages().count(), MESSAGE_BUFFER_CAPACITY);
+    for (message, price) in message_buffer.messages().zip(5..) {
+        assert_eq!(message.sequence, price as u64);
+        assert_eq!(message.price, price);
+    }
+    assert_eq!(
+        message_buffer.messages_[0].sequence,
+        MESSAGE_BUFFER_CAPACITY as u64
+    );
+}
+
+#[tokio::test]
+async fn test_upd_price_appends_to_message_buffer() {
+    let mut sim = PythSimulator::new().await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(1).await;
+    let price = price_keypair.pubkey();
+    sim.set_min_pub(price, 1).await.unwrap();
+    let message_buffer = sim.init_message_buffer().await.unwrap().pubkey();
+
+    let quote = |value| Quote {
+        price:      value,
+        confidence: 1,
+        status:     PC_STATUS_TRADING,
+    };
+
+    // The aggregation fails without quotes, so there is no message
+    sim.upd_price_with_message_buffer(&publishers[0], price, quote(100), message_buffer)
+        .await
+        .unwrap();
+    let message_buffer_data = sim
+        .get_account_data_as::<MessageBufferAccount>(message_buffer)
+        .await
+        .unwrap();
+    assert_eq!(message_buffer_data.next_seq_, 0);
+
+    sim.warp_to_slot(2).await.unwrap();
+    sim.upd_price_with_message_buffer(&publishers[0], price, quote(110), message_buffer)
+        .await
+        .unwrap();
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    let first_message = PriceMessage {
+        sequence:      0,
+        price_account: price,
+        price:         100,
+        conf:          price_data.agg_.conf_,
+        exponent:      price_data.exponent,
+        feed_index:    0,
+        publish_time:  price_data.timestamp_,
+        slot:          price_data.agg_.pub_slot_,
+    };
+
+    // Aggregations of price updates without the message buffer aren't appended
+    sim.warp_to_slot(3).await.unwrap();
+    sim.upd_price(&publishers[0], price, quote(120))
+        .await
+        .unwrap();
+    sim.warp_to_slot(4).await.unwrap();
+    sim.upd_price_with_message_buffer(&publishers[0], price, quote(130), message_buffer)
+        .await
+        .unwrap();
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(price_data.agg_.price_, 120);
+
+    let message_buffer_data = sim
+        .get_account_data_as::<MessageBufferAccount>(message_buffer)
+        .await
+        .unwrap();
+    assert_eq!(
+        message_buffer_data.messages().copied().collect::<Vec<_>>(),
+        vec![
+            first_message,
+            PriceMessage {
+                sequence: 1,
+                price: 120,
+                conf: price_data.agg_.conf_,
+                publish_time: price_data.timestamp_,
+                slot: price_data.agg_.pub_slot_,
+                ..first_message
+            }
+        ]
+    );
+}
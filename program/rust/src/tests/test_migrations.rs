@@ -0,0 +1,245 @@
+use {
+    crate::{
+        accounts::{
+            PriceAccount,
+            PythAccount,
+        },
+        c_oracle_header::{
+            PC_MAX_SEND_LATENCY,
+            PC_VERSION,
+        },
+        deserialize::{
+            load_account_as,
+            load_account_as_mut,
+            load_checked,
+            load_checked_read_only,
+            ReadOnlyAccount,
+        },
+        error::OracleError,
+        layout::{
+            PRICE_ACCOUNT_PRICE_CUMULATIVE_OFFSET,
+            PRICE_ACCOUNT_PUBLISHER_STATS_OFFSET,
+        },
+        migrations::{
+            migrate,
+            migrate_with,
+            Migration,
+            PRICE_ACCOUNT_LAYOUT_VERSION,
+        },
+        tests::{
+            pyth_simulator::PythSimulator,
+            test_utils::AccountSetup,
+        },
+    },
+    bytemuck::{
+        bytes_of,
+        bytes_of_mut,
+        Zeroable,
+    },
+    solana_program::{
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+    solana_sdk::signer::Signer,
+};
+
+fn unreachable_migration(_price_account: &mut PriceAccount) -> Result<(), OracleError> {
+    panic!("the account is already past this layout version");
+}
+
+/// Layout version 2 makes the default max latency explicit
+fn migrate_v1_to_v2(price_account: &mut PriceAccount) -> Result<(), OracleError> {
+    if price_account.max_latency_ == 0 {
+        price_account.max_latency_ = PC_MAX_SEND_LATENCY as u8;
+    }
+    Ok(())
+}
+
+/// Layout version 3 counts the outlier band in half confidence intervals
+fn migrate_v2_to_v3(price_account: &mut PriceAccount) -> Result<(), OracleError> {
+    price_account.outlier_band_ = price_account
+        .outlier_band_
+        .checked_mul(2)
+        .ok_or(OracleError::IntegerCastingError)?;
+    Ok(())
+}
+
+const TEST_MIGRATIONS: &[Migration] = &[unreachable_migration, migrate_v1_to_v2, migrate_v2_to_v3];
+
+#[test]
+fn test_migrate_with() {
+    let mut price_account = PriceAccount::zeroed();
+    price_account.layout_version_ = 1;
+    price_account.outlier_band_ = 5;
+    price_account.min_pub_ = 3;
+
+    migrate_with(&mut price_account, TEST_MIGRATIONS).unwrap();
+    assert_eq!(price_account.layout_version_, 3);
+    assert_eq!(price_account.max_latency_, PC_MAX_SEND_LATENCY as u8);
+    assert_eq!(price_account.outlier_band_, 10);
+    assert_eq!(price_account.min_pub_, 3);
+
+    // Migrating an account with the last layout version does nothing
+    migrate_with(&mut price_account, TEST_MIGRATIONS).unwrap();
+    assert_eq!(price_account.layout_version_, 3);
+    assert_eq!(price_account.outlier_band_, 10);
+
+    // The migrations stop at the first failure
+    let mut price_account = PriceAccount::zeroed();
+    price_account.layout_version_ = 1;
+    price_account.outlier_band_ = u16::MAX;
+    assert_eq!(
+        migrate_with(&mut price_account, TEST_MIGRATIONS),
+        Err(OracleError::IntegerCastingError)
+    );
+    assert_eq!(price_account.layout_version_, 2);
+
+    // Newer layouts can't be migrated
+    let mut price_account = PriceAccount::zeroed();
+    price_account.layout_version_ = 4;
+    assert_eq!(
+        migrate_with(&mut price_account, TEST_MIGRATIONS),
+        Err(OracleError::UnsupportedAccountLayout)
+    );
+}
+
+#[test]
+fn test_migrate_clears_reused_component_space() {
+    // Accounts created before layout versions have stale components after the ones in use
+    let mut price_account = PriceAccount::zeroed();
+    bytes_of_mut(&mut price_account).fill(0xab);
+    price_account.layout_version_ = 0;
+    let mut stale = price_account;
+
+    migrate(&mut price_account).unwrap();
+    assert_eq!(price_account.layout_version_, PRICE_ACCOUNT_LAYOUT_VERSION);
+    stale.layout_version_ = PRICE_ACCOUNT_LAYOUT_VERSION;
+    let bytes = bytes_of(&price_account);
+    let stale_bytes = bytes_of(&stale);
+    assert_eq!(
+        bytes[..PRICE_ACCOUNT_PUBLISHER_STATS_OFFSET],
+        stale_bytes[..PRICE_ACCOUNT_PUBLISHER_STATS_OFFSET]
+    );
+    assert_eq!(
+        bytes[PRICE_ACCOUNT_PRICE_CUMULATIVE_OFFSET..],
+        stale_bytes[PRICE_ACCOUNT_PRICE_CUMULATIVE_OFFSET..]
+    );
+    let mut expected = price_account;
+    bytes_of_mut(&mut expected)
+        [PRICE_ACCOUNT_PUBLISHER_STATS_OFFSET..PRICE_ACCOUNT_PRICE_CUMULATIVE_OFFSET]
+        .fill(0);
+    expected.layout_version_ = PRICE_ACCOUNT_LAYOUT_VERSION;
+    expected.status_change_ = price_account.status_change_;
+    assert!(bytes_of(&price_account) == bytes_of(&expected));
+    assert_eq!(price_account.status_change_.slot_, stale.agg_.pub_slot_);
+}
+
+#[test]
+fn test_migrate_clears_fields_added_since_layout_version_1() {
+    // Accounts migrated to layout version 1 by older versions of the program kept the stale
+    // components, so every field added since then is cleared by its own migration
+    let mut price_account = PriceAccount::zeroed();
+    price_account.layout_version_ = 1;
+    price_account.publisher_stats_[0].publish_time_ = 42;
+    price_account.agg_pub_time_ = 1;
+    price_account.cpi_guard_ = 1;
+    price_account.cpi_callers_[0] = Pubkey::new_unique();
+    price_account.market_hours_.sessions_[0] = [1, 2];
+    price_account.feed_index_ = 1;
+    price_account.fee_lamports_ = 1;
+    price_account.agg_rotation_ = 1;
+    price_account.max_conf_ratio_ = 1;
+
+    migrate(&mut price_account).unwrap();
+    assert_eq!(price_account.layout_version_, PRICE_ACCOUNT_LAYOUT_VERSION);
+    assert_eq!(price_account.publisher_stats_[0].publish_time_, 42);
+    assert_eq!(price_account.agg_pub_time_, 0);
+    assert_eq!(price_account.cpi_guard_, 0);
+    assert_eq!(price_account.cpi_callers_[0], Pubkey::default());
+    assert_eq!(price_account.market_hours_.sessions_, [[0; 2]; 7]);
+    assert_eq!(price_account.feed_index_, 0);
+    assert_eq!(price_account.fee_lamports_, 0);
+    assert_eq!(price_account.agg_rotation_, 0);
+    assert_eq!(price_account.max_conf_ratio_, 0);
+}
+
+#[test]
+fn test_load_checked_migrates_price_account() {
+    let program_id = Pubkey::new_unique();
+    let mut price_setup = AccountSetup::new::<PriceAccount>(&program_id);
+    let mut price_account = price_setup.as_account_info();
+    PriceAccount::initialize(&price_account, PC_VERSION).unwrap();
+
+    // Accounts created before layout versions have layout version 0. They can't be migrated by a
+    // read-only access, which reads a migrated copy instead.
+    load_account_as_mut::<PriceAccount>(&price_account)
+        .unwrap()
+        .unused_ = 0xab;
+    price_account.is_writable = false;
+    assert_eq!(
+        load_checked::<PriceAccount>(&price_account, PC_VERSION).err(),
+        Some(ProgramError::from(OracleError::UnsupportedAccountLayout))
+    );
+    match load_checked_read_only::<PriceAccount>(&price_account, PC_VERSION).unwrap() {
+        ReadOnlyAccount::Migrated(price_data) => {
+            assert_eq!(price_data.layout_version_, PRICE_ACCOUNT_LAYOUT_VERSION);
+            assert_eq!(price_data.unused_, 0);
+        }
+        ReadOnlyAccount::Borrowed(_) => panic!("the account needs a migration"),
+    }
+    assert_eq!(
+        load_account_as::<PriceAccount>(&price_account)
+            .unwrap()
+            .layout_version_,
+        0
+    );
+
+    price_account.is_writable = true;
+    assert_eq!(
+        load_checked::<PriceAccount>(&price_account, PC_VERSION)
+            .unwrap()
+            .layout_version_,
+        PRICE_ACCOUNT_LAYOUT_VERSION
+    );
+
+    // Up to date accounts can be loaded read-only
+    price_account.is_writable = false;
+    load_checked::<PriceAccount>(&price_account, PC_VERSION).unwrap();
+    assert!(matches!(
+        load_checked_read_only::<PriceAccount>(&price_account, PC_VERSION).unwrap(),
+        ReadOnlyAccount::Borrowed(_)
+    ));
+
+    price_account.is_writable = true;
+    load_checked::<PriceAccount>(&price_account, PC_VERSION)
+        .unwrap()
+        .layout_version_ = PRICE_ACCOUNT_LAYOUT_VERSION + 1;
+    assert_eq!(
+        load_checked::<PriceAccount>(&price_account, PC_VERSION).err(),
+        Some(ProgramError::from(OracleError::UnsupportedAccountLayout))
+    );
+    assert_eq!(
+        load_checked_read_only::<PriceAccount>(&price_account, PC_VERSION).err(),
+        Some(ProgramError::from(OracleError::UnsupportedAccountLayout))
+    );
+    assert_eq!(
+        load_account_as::<PriceAccount>(&price_account)
+            .unwrap()
+            .layout_version_,
+        PRICE_ACCOUNT_LAYOUT_VERSION + 1
+    );
+}
+
+#[tokio::test]
+async fn test_new_price_account_layout_version() {
+    let mut sim = PythSimulator::new().await;
+    let mapping_keypair = sim.init_mapping().await.unwrap();
+    let product_keypair = sim.add_product(&mapping_keypair).await.unwrap();
+    let price_keypair = sim.add_price(&product_keypair, -5).await.unwrap();
+
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price_keypair.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(price_data.layout_version_, PRICE_ACCOUNT_LAYOUT_VERSION);
+}
@@ -0,0 +1,70 @@
+use {
+    crate::{
+        accounts::PriceAccount,
+        c_oracle_header::PC_STATUS_TRADING,
+        tests::pyth_simulator::{
+            PythSimulator,
+            Quote,
+        },
+    },
+    pyth_oracle_core::price::PriceAccountView,
+    solana_sdk::signer::Signer,
+};
+
+fn quote(price: i64, confidence: u64) -> Quote {
+    Quote {
+        price,
+        confidence,
+        status: PC_STATUS_TRADING,
+    }
+}
+
+/// `PriceAccountView` reads the price accounts written by the program and recomputes their
+/// aggregate
+#[tokio::test]
+async fn test_oracle_core() {
+    let mut sim = PythSimulator::new().await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(3).await;
+    let price = price_keypair.pubkey();
+    sim.set_min_pub(price, 1).await.unwrap();
+
+    for (i, publisher) in publishers.iter().enumerate() {
+        sim.upd_price(publisher, price, quote(100 * (i as i64 + 1), 10))
+            .await
+            .unwrap();
+    }
+    // The first update of the next slot aggregates the quotes of the previous slot
+    sim.warp_to_slot(2).await.unwrap();
+    sim.upd_price(&publishers[0], price, quote(1_000, 10))
+        .await
+        .unwrap();
+
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    let account = sim.get_account(price).await.unwrap();
+    let view = PriceAccountView::new(&account.data).unwrap();
+    assert_eq!(view.exponent(), price_data.exponent);
+    assert_eq!(view.num_components(), 3);
+    assert_eq!(view.num_qt(), price_data.num_qt_);
+    assert_eq!(view.last_slot(), price_data.last_slot_);
+    assert_eq!(view.timestamp(), price_data.timestamp_);
+    assert_eq!(view.aggregate().price, price_data.agg_.price_);
+    assert_eq!(view.aggregate().conf, price_data.agg_.conf_);
+    assert_eq!(view.aggregate().status, price_data.agg_.status_);
+    assert_eq!(view.aggregate().pub_slot, price_data.agg_.pub_slot_);
+    for (i, publisher) in publishers.iter().enumerate() {
+        let component = view.component(i).unwrap();
+        assert_eq!(component.publisher, publisher.pubkey().to_bytes());
+        assert_eq!(component.agg.price, price_data.comp_[i].agg_.price_);
+        assert_eq!(component.latest.price, price_data.comp_[i].latest_.price_);
+    }
+    assert_eq!(view.component(3), None);
+
+    assert_eq!(price_data.agg_.status_, PC_STATUS_TRADING);
+    let result = view.recompute_aggregate();
+    assert_eq!(
+        result.price_conf,
+        Some((price_data.agg_.price_, price_data.agg_.conf_))
+    );
+    assert_eq!(result.num_qt, price_data.num_qt_);
+    assert_eq!(result.num_qt, 3);
+}
@@ -0,0 +1,371 @@
+use {
+    crate::{
+        accounts::{
+            PermissionAccount,
+            PriceAccount,
+            PythAccount,
+        },
+        c_oracle_header::{
+            PC_PTYPE_PRICE,
+            PC_STATUS_TRADING,
+            PC_VERSION,
+        },
+        deserialize::load_checked,
+        error::OracleError,
+        instruction::{
+            CommandHeader,
+            OracleCommand,
+            OracleInstruction,
+            PriceUpdate,
+            SetMinPubArgs,
+            UpdPriceArgs,
+        },
+        processor::process_instruction,
+        tests::test_utils::AccountSetup,
+    },
+    bytemuck::bytes_of,
+    num_traits::ToPrimitive,
+    solana_program::{
+        account_info::AccountInfo,
+        pubkey::Pubkey,
+    },
+};
+
+fn sample_instructions() -> Vec<OracleInstruction> {
+    let update = PriceUpdate {
+        status:          PC_STATUS_TRADING,
+        price:           -42,
+        confidence:      7,
+        publishing_slot: 1000,
+    };
+    vec![
+        OracleInstruction::InitMapping,
+        OracleInstruction::AddMapping,
+        OracleInstruction::AddProduct,
+        OracleInstruction::UpdProduct {
+            attributes: vec![
+                ("symbol".to_string(), "BTC/USD".to_string()),
+                ("tenor".to_string(), "".to_string()),
+            ],
+        },
+        OracleInstruction::AddPrice {
+            exponent:   -5,
+            price_type: PC_PTYPE_PRICE,
+        },
+        OracleInstruction::AddPublisher {
+            publisher: Pubkey::new_unique(),
+        },
+        OracleInstruction::DelPublisher {
+            publisher: Pubkey::new_unique(),
+        },
+        OracleInstruction::UpdPrice {
+            status:          update.status,
+            price:           update.price,
+            confidence:      update.confidence,
+            publishing_slot: update.publishing_slot,
+        },
+        OracleInstruction::AggPrice {
+            status:          update.status,
+            price:           update.price,
+            confidence:      update.confidence,
+            publishing_slot: update.publishing_slot,
+        },
+        OracleInstruction::InitPrice {
+            exponent:   -8,
+            price_type: PC_PTYPE_PRICE,
+        },
+        OracleInstruction::SetMinPub {
+            minimum_publishers: 3,
+        },
+        OracleInstruction::UpdPriceNoFailOnError {
+            status:          update.status,
+            price:           update.price,
+            confidence:      update.confidence,
+            publishing_slot: update.publishing_slot,
+        },
+        OracleInstruction::DelPrice,
+        OracleInstruction::DelProduct,
+        OracleInstruction::UpdPermissions {
+            master_authority:        Pubkey::new_unique(),
+            data_curation_authority: Pubkey::new_unique(),
+            security_authority:      Pubkey::new_unique(),
+        },
+        OracleInstruction::SetMaxLatency { max_latency: 12 },
+        OracleInstruction::UpdPriceBatch {
+            updates: vec![
+                update,
+                PriceUpdate {
+                    price: 43,
+                    ..update
+                },
+            ],
+        },
+        OracleInstruction::SetPublisherWeight {
+            publisher: Pubkey::new_unique(),
+            weight:    10,
+        },
+        OracleInstruction::SetAggMode { agg_mode: 1 },
+        OracleInstruction::HaltPrice,
+        OracleInstruction::ResumePrice,
+        OracleInstruction::SetOutlierBand { outlier_band: 5 },
+        OracleInstruction::SetExponent { exponent: -3 },
+        OracleInstruction::ResignPublisher,
+        OracleInstruction::ForceAggregate,
+        OracleInstruction::ResetPrice,
+        OracleInstruction::SetMaxUpdatesPerSlot {
+            max_updates_per_slot: 1,
+        },
+        OracleInstruction::SetPriceBounds {
+            min_price: -100,
+            max_price: 100,
+        },
+        OracleInstruction::UpdPriceWithTime {
+            status:          update.status,
+            price:           update.price,
+            confidence:      update.confidence,
+            publishing_slot: update.publishing_slot,
+            publish_time:    1_700_000_000,
+        },
+        OracleInstruction::SetCpiGuard {
+            enabled:         true,
+            allowed_callers: [
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::default(),
+                Pubkey::default(),
+            ],
+        },
+        OracleInstruction::SetMarketHours {
+            sessions: [
+                [570, 960],
+                [570, 960],
+                [570, 960],
+                [570, 960],
+                [570, 960],
+                [0, 0],
+                [0, 0],
+            ],
+        },
+        OracleInstruction::InitPriceFeedIndex,
+        OracleInstruction::InitMessageBuffer,
+        OracleInstruction::RotatePublisher {
+            old_publisher: Pubkey::new_unique(),
+            new_publisher: Pubkey::new_unique(),
+        },
+        OracleInstruction::SetFee { fee_lamports: 5000 },
+        OracleInstruction::WithdrawFee { lamports: 10_000 },
+        OracleInstruction::GrantPermission {
+            authority: Pubkey::new_unique(),
+            command:   OracleCommand::SetMinPub as i32,
+        },
+        OracleInstruction::RevokePermission {
+            authority: Pubkey::new_unique(),
+            command:   OracleCommand::SetMinPub as i32,
+        },
+        OracleInstruction::SetPublisherExcluded {
+            publisher: Pubkey::new_unique(),
+            excluded:  true,
+        },
+        OracleInstruction::InitMappingPda,
+        OracleInstruction::UpdPriceScatter {
+            updates: vec![update],
+        },
+        OracleInstruction::SetAggRotation { enabled: true },
+        OracleInstruction::CompressPriceAccount,
+        OracleInstruction::SetReferencePrice,
+        OracleInstruction::UpdPriceWithTrade {
+            status:          update.status,
+            price:           update.price,
+            confidence:      update.confidence,
+            publishing_slot: update.publishing_slot,
+            trade_price:     update.price - 1,
+            trade_size:      25,
+            venue_id:        3,
+        },
+        OracleInstruction::SetCoSigning { enabled: true },
+        OracleInstruction::CompactComponents,
+        OracleInstruction::InitSymbolIndex,
+        OracleInstruction::AddPublisherWithExpiry {
+            publisher:   Pubkey::new_unique(),
+            expiry_slot: 1_000,
+        },
+        OracleInstruction::SetMaxConfRatio {
+            max_conf_ratio: 250,
+        },
+        OracleInstruction::Heartbeat,
+        OracleInstruction::InitComponentLog,
+        OracleInstruction::InitDerivedPrice { exponent: -8 },
+        OracleInstruction::UpdDerived,
+        OracleInstruction::SetPublisherPruning {
+            prune_rounds:   8,
+            readmit_rounds: 2,
+        },
+        OracleInstruction::SetAggInterval {
+            agg_interval_slots: 5,
+        },
+        OracleInstruction::InitTimeMachine,
+        OracleInstruction::UpdPriceWithBudget {
+            status:                  update.status,
+            price:                   update.price,
+            confidence:              update.confidence,
+            publishing_slot:         update.publishing_slot,
+            remaining_compute_units: 50_000,
+        },
+    ]
+}
+
+#[test]
+fn test_oracle_instruction_layouts() {
+    for instruction in sample_instructions() {
+        let borsh_data = instruction.pack();
+        assert_eq!(
+            bytemuck::pod_read_unaligned::<CommandHeader>(&borsh_data[..8]).command,
+            OracleCommand::Borsh.to_i32().unwrap()
+        );
+        assert_eq!(
+            OracleInstruction::unpack(&borsh_data),
+            Ok(instruction.clone())
+        );
+
+        let legacy_data = instruction.pack_legacy().unwrap();
+        let header = bytemuck::pod_read_unaligned::<CommandHeader>(&legacy_data[..8]);
+        assert_eq!(header.version, PC_VERSION);
+        assert_eq!(header.command, instruction.command().to_i32().unwrap());
+        assert_eq!(OracleInstruction::unpack(&legacy_data), Ok(instruction));
+    }
+
+    // The legacy layout is the bytes of the arguments struct
+    let legacy_data = OracleInstruction::UpdPrice {
+        status:          PC_STATUS_TRADING,
+        price:           -42,
+        confidence:      7,
+        publishing_slot: 1000,
+    }
+    .pack_legacy()
+    .unwrap();
+    let args = UpdPriceArgs {
+        header:          OracleCommand::UpdPrice.into(),
+        status:          PC_STATUS_TRADING,
+        unused_:         0,
+        price:           -42,
+        confidence:      7,
+        publishing_slot: 1000,
+    };
+    assert_eq!(legacy_data, bytes_of(&args));
+
+    // Product attributes are limited to 255 bytes in the legacy layout
+    let long_attributes = OracleInstruction::UpdProduct {
+        attributes: vec![("symbol".to_string(), "a".repeat(256))],
+    };
+    assert_eq!(
+        long_attributes.pack_legacy(),
+        Err(OracleError::IntegerCastingError)
+    );
+}
+
+#[test]
+fn test_oracle_instruction_invalid_data() {
+    let mut data = OracleInstruction::SetMinPub {
+        minimum_publishers: 1,
+    }
+    .pack();
+
+    // Trailing bytes
+    data.push(0);
+    assert_eq!(
+        OracleInstruction::unpack(&data),
+        Err(OracleError::DeserializationError)
+    );
+
+    // Unknown variant
+    let mut data = OracleInstruction::InitMapping.pack();
+    data[8] = u8::MAX;
+    assert_eq!(
+        OracleInstruction::unpack(&data),
+        Err(OracleError::DeserializationError)
+    );
+    assert_eq!(
+        process_instruction(&Pubkey::new_unique(), &[], &data),
+        Err(OracleError::DeserializationError.into())
+    );
+
+    // Wrong version
+    let mut data = OracleInstruction::InitMapping.pack();
+    data[0] = 0;
+    assert_eq!(
+        OracleInstruction::unpack(&data),
+        Err(OracleError::InvalidInstructionVersion)
+    );
+
+    // Legacy batch with a truncated entry
+    let mut data = OracleInstruction::UpdPriceBatch { updates: vec![] }
+        .pack_legacy()
+        .unwrap();
+    data.extend_from_slice(&[0; 8]);
+    assert_eq!(
+        OracleInstruction::unpack(&data),
+        Err(OracleError::DeserializationError)
+    );
+
+    // Removed commands have no instruction
+    let header: CommandHeader = OracleCommand::ResizePriceAccount.into();
+    assert_eq!(
+        OracleInstruction::unpack(bytes_of(&header)),
+        Err(OracleError::UnrecognizedInstruction)
+    );
+}
+
+fn min_pub(price_account: &AccountInfo) -> u8 {
+    load_checked::<PriceAccount>(price_account, PC_VERSION)
+        .unwrap()
+        .min_pub_
+}
+
+#[test]
+fn test_borsh_instruction_matches_legacy_instruction() {
+    let program_id = Pubkey::new_unique();
+
+    let mut funding_setup = AccountSetup::new_funding();
+    let funding_account = funding_setup.as_account_info();
+
+    let mut price_setup = AccountSetup::new::<PriceAccount>(&program_id);
+    let price_account = price_setup.as_account_info();
+    PriceAccount::initialize(&price_account, PC_VERSION).unwrap();
+
+    let mut permissions_setup = AccountSetup::new_permission(&program_id);
+    let permissions_account = permissions_setup.as_account_info();
+    PermissionAccount::initialize(&permissions_account, PC_VERSION)
+        .unwrap()
+        .master_authority = *funding_account.key;
+
+    let accounts = [
+        funding_account.clone(),
+        price_account.clone(),
+        permissions_account.clone(),
+    ];
+    let borsh_data = OracleInstruction::SetMinPub {
+        minimum_publishers: 5,
+    }
+    .pack();
+    process_instruction(&program_id, &accounts, &borsh_data).unwrap();
+    assert_eq!(min_pub(&price_account), 5);
+
+    let legacy_args = SetMinPubArgs {
+        header:             OracleCommand::SetMinPub.into(),
+        minimum_publishers: 7,
+        unused_:            [0; 3],
+    };
+    process_instruction(&program_id, &accounts, bytes_of(&legacy_args)).unwrap();
+    assert_eq!(min_pub(&price_account), 7);
+
+    // The permissions are checked against the command of the Borsh instruction
+    let mut permissions_data =
+        load_checked::<PermissionAccount>(&permissions_account, PC_VERSION).unwrap();
+    permissions_data.master_authority = Pubkey::new_unique();
+    drop(permissions_data);
+    assert_eq!(
+        process_instruction(&program_id, &accounts, &borsh_data),
+        Err(OracleError::PermissionViolation.into())
+    );
+    assert_eq!(min_pub(&price_account), 7);
+}
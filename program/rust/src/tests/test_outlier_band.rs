@@ -0,0 +1,255 @@
+use {
+    crate::{
+        accounts::PriceAccount,
+        aggregation::upd_aggregate,
+        c_oracle_header::{
+            PC_NUM_COMP,
+            PC_STATUS_TRADING,
+        },
+        tests::pyth_simulator::{
+            PythSimulator,
+            Quote,
+        },
+    },
+    bytemuck::Zeroable,
+    quickcheck::{
+        Arbitrary,
+        TestResult,
+    },
+    quickcheck_macros::quickcheck,
+    solana_sdk::signer::Signer,
+};
+
+const CURRENT_SLOT: u64 = 1000;
+
+#[derive(Clone, Debug, Copy)]
+pub struct BandQuote {
+    price: i64,
+    conf:  u64,
+}
+
+impl Arbitrary for BandQuote {
+    fn arbitrary(g: &mut quickcheck::Gen) -> Self {
+        BandQuote {
+            price: i64::from(u16::arbitrary(g)),
+            conf:  u64::from(u8::arbitrary(g)),
+        }
+    }
+}
+
+/// The previous aggregate and the configuration of the price account
+#[derive(Clone, Debug, Copy)]
+pub struct BandSetup {
+    prev_price:   i64,
+    prev_conf:    u64,
+    outlier_band: u16,
+    min_pub:      u8,
+}
+
+impl Arbitrary for BandSetup {
+    fn arbitrary(g: &mut quickcheck::Gen) -> Self {
+        BandSetup {
+            prev_price:   i64::from(u16::arbitrary(g)),
+            prev_conf:    u64::from(u8::arbitrary(g)) * 16,
+            outlier_band: u16::from(u8::arbitrary(g) % 8),
+            min_pub:      u8::arbitrary(g) % 4,
+        }
+    }
+}
+
+/// Run the aggregation on `quotes` and return the aggregate price, confidence, status and number
+/// of included quotes
+fn aggregate(quotes: &[BandQuote], setup: BandSetup) -> (i64, u64, u32, u32) {
+    let mut price_account = PriceAccount::zeroed();
+    price_account.num_ = quotes.len() as u32;
+    price_account.min_pub_ = setup.min_pub;
+    price_account.outlier_band_ = setup.outlier_band;
+    price_account.prev_slot_ = CURRENT_SLOT - 1;
+    price_account.prev_price_ = setup.prev_price;
+    price_account.prev_conf_ = setup.prev_conf;
+    for (component, quote) in price_account.comp_.iter_mut().zip(quotes.iter()) {
+        component.latest_.price_ = quote.price;
+        component.latest_.conf_ = quote.conf;
+        component.latest_.status_ = PC_STATUS_TRADING;
+        component.latest_.pub_slot_ = CURRENT_SLOT;
+    }
+
+    upd_aggregate(&mut price_account, CURRENT_SLOT + 1, 0);
+
+    (
+        price_account.agg_.price_,
+        price_account.agg_.conf_,
+        price_account.agg_.status_,
+        price_account.num_qt_,
+    )
+}
+
+/// The outlier band gives the same aggregate as aggregating only the quotes inside the band,
+/// unless too few valid quotes are inside it, in which case it is ignored.
+#[quickcheck]
+fn test_outlier_band_matches_filtered_quotes(
+    quotes: Vec<BandQuote>,
+    setup: BandSetup,
+) -> TestResult {
+    if quotes.len() > PC_NUM_COMP as usize {
+        return TestResult::discard();
+    }
+
+    let without_band = BandSetup {
+        outlier_band: 0,
+        ..setup
+    };
+    let in_band: Vec<BandQuote> = quotes
+        .iter()
+        .filter(|quote| {
+            setup.outlier_band == 0
+                || (quote.price - setup.prev_price).unsigned_abs()
+                    <= u64::from(setup.outlier_band) * setup.prev_conf
+        })
+        .copied()
+        .collect();
+    let num_valid_in_band = in_band.iter().filter(|quote| quote.conf != 0).count();
+
+    let expected = if num_valid_in_band != 0 && num_valid_in_band >= usize::from(setup.min_pub) {
+        aggregate(&in_band, without_band)
+    } else {
+        aggregate(&quotes, without_band)
+    };
+
+    TestResult::from_bool(aggregate(&quotes, setup) == expected)
+}
+
+#[test]
+fn test_outlier_band() {
+    let quotes = [
+        BandQuote {
+            price: 100,
+            conf:  1,
+        },
+        BandQuote {
+            price: 101,
+            conf:  1,
+        },
+        BandQuote {
+            price: 102,
+            conf:  1,
+        },
+        BandQuote {
+            price: 500,
+            conf:  1,
+        },
+    ];
+    let setup = BandSetup {
+        prev_price:   100,
+        prev_conf:    1,
+        outlier_band: 5,
+        min_pub:      1,
+    };
+
+    assert_eq!(
+        aggregate(
+            &quotes,
+            BandSetup {
+                outlier_band: 0,
+                ..setup
+            }
+        ),
+        (101, 2, PC_STATUS_TRADING, 4)
+    );
+    // The quote at 500 is excluded
+    assert_eq!(aggregate(&quotes, setup), (101, 1, PC_STATUS_TRADING, 3));
+
+    // If all quotes are outliers, the band is ignored
+    assert_eq!(
+        aggregate(
+            &quotes,
+            BandSetup {
+                prev_price: 1000,
+                ..setup
+            }
+        ),
+        (101, 2, PC_STATUS_TRADING, 4)
+    );
+
+    // Same if fewer than min_pub quotes are inside the band
+    assert_eq!(
+        aggregate(
+            &quotes,
+            BandSetup {
+                min_pub: 4,
+                ..setup
+            }
+        ),
+        (101, 2, PC_STATUS_TRADING, 4)
+    );
+
+    // Without a previous aggregate, there is no band
+    let mut price_account = PriceAccount::zeroed();
+    price_account.num_ = 1;
+    price_account.outlier_band_ = 1;
+    price_account.prev_price_ = 100;
+    price_account.prev_conf_ = 1;
+    price_account.comp_[0].latest_.price_ = 500;
+    price_account.comp_[0].latest_.conf_ = 1;
+    price_account.comp_[0].latest_.status_ = PC_STATUS_TRADING;
+    price_account.comp_[0].latest_.pub_slot_ = CURRENT_SLOT;
+    assert!(upd_aggregate(&mut price_account, CURRENT_SLOT + 1, 0));
+    assert_eq!(price_account.agg_.price_, 500);
+}
+
+#[tokio::test]
+async fn test_set_outlier_band() {
+    let mut sim = PythSimulator::new().await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(3).await;
+    let price = price_keypair.pubkey();
+    sim.set_min_pub(price, 1).await.unwrap();
+    sim.set_outlier_band(price, 10).await.unwrap();
+
+    let quote = |price: i64| Quote {
+        price,
+        confidence: 1,
+        status: PC_STATUS_TRADING,
+    };
+
+    sim.publish_all(price, &publishers, &[quote(100); 3])
+        .await
+        .unwrap();
+    sim.warp_to_slot(2).await.unwrap();
+    sim.publish_all(price, &publishers, &[quote(100), quote(100), quote(1000)])
+        .await
+        .unwrap();
+    sim.warp_to_slot(3).await.unwrap();
+    sim.upd_price(&publishers[0], price, quote(100))
+        .await
+        .unwrap();
+
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(price_data.outlier_band_, 10);
+    assert_eq!(price_data.prev_price_, 100);
+    assert_eq!(price_data.prev_conf_, 1);
+    assert_eq!(price_data.agg_.status_, PC_STATUS_TRADING);
+    assert_eq!(price_data.agg_.price_, 100);
+    assert_eq!(price_data.num_qt_, 2);
+
+    // The outlier doesn't count as included in the publisher statistics. The rounds of slot 1
+    // (without quotes) and slot 2 come before it.
+    let outlier_stats = price_data.publisher_stats(&publishers[2].pubkey()).unwrap();
+    assert_eq!(outlier_stats.num_rounds(), 3);
+    assert_eq!(outlier_stats.num_included_rounds(), 1);
+
+    // Disabling the band includes the outlier again
+    sim.set_outlier_band(price, 0).await.unwrap();
+    sim.warp_to_slot(4).await.unwrap();
+    sim.upd_price(&publishers[0], price, quote(100))
+        .await
+        .unwrap();
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(price_data.num_qt_, 3);
+    assert_eq!(price_data.agg_.status_, PC_STATUS_TRADING);
+}
@@ -0,0 +1,103 @@
+use {
+    crate::{
+        accounts::PriceAccount,
+        error::OracleError,
+        instruction::OracleCommand,
+        tests::pyth_simulator::{
+            PythSimulator,
+            TransactionFailure,
+        },
+    },
+    solana_program::{
+        instruction::InstructionError,
+        native_token::LAMPORTS_PER_SOL,
+    },
+    solana_sdk::{
+        signature::Keypair,
+        signer::Signer,
+    },
+};
+
+#[tokio::test]
+async fn test_permission_grant() {
+    let mut sim = PythSimulator::new().await;
+    let (price_keypair, _) = sim.setup_feed_with_publishers(1).await;
+    let price = price_keypair.pubkey();
+    let authority = Keypair::new();
+    sim.airdrop(&authority.pubkey(), 100 * LAMPORTS_PER_SOL)
+        .await
+        .unwrap();
+    let permissions = sim.get_permissions_pubkey();
+    let grant = sim.get_permission_grant_pubkey(&authority.pubkey(), OracleCommand::SetMinPub);
+
+    // Without a grant, the authority isn't allowed by the permissions account
+    sim.set_min_pub_with_permissions(&authority, permissions, price, 2)
+        .await
+        .assert_fails_with(OracleError::PermissionViolation);
+
+    sim.grant_permission(authority.pubkey(), OracleCommand::SetMinPub)
+        .await
+        .unwrap();
+    let grant_account = sim.get_account(grant).await.unwrap();
+    assert!(grant_account.data.is_empty());
+    assert!(grant_account.lamports > 0);
+
+    // Granting twice does nothing
+    sim.grant_permission(authority.pubkey(), OracleCommand::SetMinPub)
+        .await
+        .unwrap();
+    assert_eq!(sim.get_account(grant).await.unwrap(), grant_account);
+
+    sim.set_min_pub_with_permissions(&authority, grant, price, 2)
+        .await
+        .unwrap();
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(price_data.min_pub_, 2);
+
+    // The grant only authorizes its authority, for its command
+    let other_authority = Keypair::new();
+    sim.airdrop(&other_authority.pubkey(), 100 * LAMPORTS_PER_SOL)
+        .await
+        .unwrap();
+    sim.set_min_pub_with_permissions(&other_authority, grant, price, 3)
+        .await
+        .assert_fails_with(OracleError::InvalidPda);
+    let other_grant =
+        sim.get_permission_grant_pubkey(&authority.pubkey(), OracleCommand::SetMaxLatency);
+    sim.grant_permission(authority.pubkey(), OracleCommand::SetMaxLatency)
+        .await
+        .unwrap();
+    sim.set_min_pub_with_permissions(&authority, other_grant, price, 3)
+        .await
+        .assert_fails_with(OracleError::InvalidPda);
+
+    // Granting the permissions to manage permissions would escalate privileges
+    sim.grant_permission(authority.pubkey(), OracleCommand::GrantPermission)
+        .await
+        .assert_fails_with(InstructionError::InvalidArgument);
+    sim.grant_permission(authority.pubkey(), OracleCommand::RevokePermission)
+        .await
+        .assert_fails_with(InstructionError::InvalidArgument);
+
+    sim.revoke_permission(authority.pubkey(), OracleCommand::SetMinPub)
+        .await
+        .unwrap();
+    assert!(sim.get_account(grant).await.is_none());
+    sim.set_min_pub_with_permissions(&authority, grant, price, 3)
+        .await
+        .assert_fails_with(OracleError::InvalidReadableAccount);
+    sim.revoke_permission(authority.pubkey(), OracleCommand::SetMinPub)
+        .await
+        .assert_fails_with(InstructionError::InvalidArgument);
+
+    // The master authority still uses the permissions account
+    sim.set_min_pub(price, 3).await.unwrap();
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(price_data.min_pub_, 3);
+}
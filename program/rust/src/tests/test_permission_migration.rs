@@ -16,6 +16,7 @@ use {
             DelPublisherArgs,
             InitPriceArgs,
             OracleCommand::{
+                AddMapping,
                 AddPrice,
                 AddProduct,
                 AddPublisher,
@@ -143,6 +144,20 @@ fn test_permission_migration() {
         Err(OracleError::PermissionViolation.into())
     );
 
+    assert_eq!(
+        process_instruction(
+            &program_id,
+            &[
+                attacker_account.clone(),
+                mapping_account.clone(),
+                next_mapping_account.clone(),
+                permissions_account.clone()
+            ],
+            bytes_of::<CommandHeader>(&AddMapping.into())
+        ),
+        Err(OracleError::PermissionViolation.into())
+    );
+
     assert_eq!(
         process_instruction(
             &program_id,
@@ -0,0 +1,121 @@
+use {
+    crate::{
+        accounts::PriceAccount,
+        aggregation::upd_aggregate,
+        c_oracle_header::{
+            PC_STATUS_TRADING,
+            PC_STATUS_UNKNOWN,
+        },
+        tests::pyth_simulator::{
+            PythSimulator,
+            Quote,
+            TransactionFailure,
+        },
+    },
+    bytemuck::Zeroable,
+    solana_program::instruction::InstructionError,
+    solana_sdk::signer::Signer,
+};
+
+const CURRENT_SLOT: u64 = 1000;
+
+/// A price account with the price bounds `[min_price, max_price]` and a trading quote of
+/// confidence 1 for each price of `prices`
+fn price_account_with(prices: &[i64], min_price: i64, max_price: i64) -> PriceAccount {
+    let mut price_account = PriceAccount::zeroed();
+    price_account.num_ = prices.len() as u32;
+    price_account.min_pub_ = 1;
+    price_account.min_price_ = min_price;
+    price_account.max_price_ = max_price;
+    for (component, price) in price_account.comp_.iter_mut().zip(prices.iter()) {
+        component.latest_.price_ = *price;
+        component.latest_.conf_ = 1;
+        component.latest_.status_ = PC_STATUS_TRADING;
+        component.latest_.pub_slot_ = CURRENT_SLOT;
+    }
+    price_account
+}
+
+#[test]
+fn test_price_bounds() {
+    // Without bounds, every quote is included
+    let mut price_account = price_account_with(&[99, 100, 200, 201], 0, 0);
+    assert!(upd_aggregate(&mut price_account, CURRENT_SLOT + 1, 0));
+    assert_eq!(price_account.num_qt_, 4);
+
+    // The bounds are inclusive
+    let mut price_account = price_account_with(&[99, 100, 200, 201], 100, 200);
+    assert!(upd_aggregate(&mut price_account, CURRENT_SLOT + 1, 0));
+    assert_eq!(price_account.num_qt_, 2);
+    assert_eq!(price_account.agg_.price_, 150);
+    assert_eq!(price_account.fast_agg_.num_qt_, 2);
+
+    // A fat-fingered quote doesn't move the aggregate
+    let mut price_account = price_account_with(&[100, 101, 102, 100_000], 50, 150);
+    assert!(upd_aggregate(&mut price_account, CURRENT_SLOT + 1, 0));
+    assert_eq!(price_account.num_qt_, 3);
+    assert_eq!(price_account.agg_.price_, 101);
+
+    // Bounds can be negative and a single price
+    let mut price_account = price_account_with(&[-5, -4, 0], -5, -5);
+    assert!(upd_aggregate(&mut price_account, CURRENT_SLOT + 1, 0));
+    assert_eq!(price_account.num_qt_, 1);
+    assert_eq!(price_account.agg_.price_, -5);
+
+    // The aggregation fails if all quotes are out of bounds
+    let mut price_account = price_account_with(&[99, 201], 100, 200);
+    assert!(!upd_aggregate(&mut price_account, CURRENT_SLOT + 1, 0));
+    assert_eq!(price_account.num_qt_, 0);
+    assert_eq!(price_account.agg_.status_, PC_STATUS_UNKNOWN);
+    assert_eq!(price_account.fast_agg_.status_, PC_STATUS_UNKNOWN);
+}
+
+#[tokio::test]
+async fn test_set_price_bounds() {
+    let mut sim = PythSimulator::new().await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(3).await;
+    let price = price_keypair.pubkey();
+    sim.set_min_pub(price, 1).await.unwrap();
+    sim.set_price_bounds(price, 50, 150).await.unwrap();
+
+    let quote = |price: i64| Quote {
+        price,
+        confidence: 1,
+        status: PC_STATUS_TRADING,
+    };
+
+    sim.publish_all(price, &publishers, &[quote(100), quote(110), quote(1000)])
+        .await
+        .unwrap();
+    sim.warp_to_slot(2).await.unwrap();
+    sim.upd_price(&publishers[0], price, quote(100))
+        .await
+        .unwrap();
+
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(price_data.min_price_, 50);
+    assert_eq!(price_data.max_price_, 150);
+    assert_eq!(price_data.agg_.status_, PC_STATUS_TRADING);
+    assert_eq!(price_data.agg_.price_, 105);
+    assert_eq!(price_data.num_qt_, 2);
+
+    // The minimum can't be above the maximum
+    sim.set_price_bounds(price, 150, 50)
+        .await
+        .assert_fails_with(InstructionError::InvalidArgument);
+
+    // Removing the bounds includes the quote at 1000 again
+    sim.set_price_bounds(price, 0, 0).await.unwrap();
+    sim.warp_to_slot(3).await.unwrap();
+    sim.upd_price(&publishers[0], price, quote(100))
+        .await
+        .unwrap();
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(price_data.num_qt_, 3);
+}
@@ -0,0 +1,142 @@
+#[cfg(feature = "pythnet")]
+use {
+    crate::{
+        accounts::PriceAccount,
+        c_oracle_header::PC_STATUS_TRADING,
+        tests::pyth_simulator::{
+            PythSimulator,
+            Quote,
+        },
+    },
+    solana_sdk::signer::Signer,
+};
+use {
+    crate::{
+        accounts::PriceHistory,
+        c_oracle_header::PRICE_HISTORY_LEN,
+    },
+    bytemuck::Zeroable,
+};
+
+fn history(points: &[(u64, i64)]) -> PriceHistory {
+    let mut history = PriceHistory::zeroed();
+    for &(slot, price) in points {
+        history.record(slot, price);
+    }
+    history
+}
+
+#[test]
+fn test_price_drift_trends() {
+    // Up 3 per slot, one aggregate every other slot
+    let rising: Vec<(u64, i64)> = (0..7)
+        .map(|i| (100 + 2 * i, 1_000 + 6 * i as i64))
+        .collect();
+    assert_eq!(history(&rising).drift(), Some(3_000_000));
+
+    // Down 4 per slot with skipped slots
+    let falling: Vec<(u64, i64)> = [10, 11, 13, 17, 18, 25, 30]
+        .iter()
+        .map(|&slot| (slot, 500 - 4 * slot as i64))
+        .collect();
+    assert_eq!(history(&falling).drift(), Some(-4_000_000));
+
+    let flat: Vec<(u64, i64)> = (0..5).map(|slot| (slot, 42)).collect();
+    assert_eq!(history(&flat).drift(), Some(0));
+
+    // Least squares fit of a noisy series, with the fractional part of the slope
+    assert_eq!(
+        history(&[(0, 0), (1, 2), (2, 1), (3, 3)]).drift(),
+        Some(800_000)
+    );
+    assert_eq!(history(&[(0, 0), (1, 0), (2, -1)]).drift(), Some(-500_000));
+}
+
+#[test]
+fn test_price_drift_history() {
+    assert_eq!(PriceHistory::zeroed().drift(), None);
+    assert_eq!(history(&[(5, 100)]).drift(), None);
+    // All the aggregates are in the same slot
+    assert_eq!(history(&[(5, 100), (5, 110)]).drift(), None);
+    assert_eq!(history(&[(5, 100), (6, 110)]).drift(), Some(10_000_000));
+
+    // Only the last aggregates are kept
+    let mut points = vec![(0, 1_000_000), (1, -1_000_000), (2, 1_000_000)];
+    points.extend((10..10 + PRICE_HISTORY_LEN as u64).map(|slot| (slot, 2 * slot as i64)));
+    let price_history = history(&points);
+    assert_eq!(usize::from(price_history.num_entries_), PRICE_HISTORY_LEN);
+    assert!(price_history.entries().all(|(slot, _)| slot >= 10));
+    assert_eq!(price_history.drift(), Some(2_000_000));
+}
+
+#[test]
+fn test_price_drift_overflow() {
+    // The squared slot span doesn't fit in an i128
+    assert_eq!(
+        history(&[(0, i64::MIN), (u64::MAX, i64::MAX), (1, 0)]).drift(),
+        None
+    );
+    // The slope doesn't fit in an i64
+    assert_eq!(history(&[(0, i64::MIN), (1, i64::MAX)]).drift(), None);
+    assert_eq!(
+        history(&[(0, i64::MIN), (1_000_000_000_000, i64::MAX)]).drift(),
+        Some(18_446_744_073_709)
+    );
+}
+
+#[cfg(feature = "pythnet")]
+#[tokio::test]
+async fn test_price_drift_extrapolation() {
+    let mut sim = PythSimulator::new().await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(1).await;
+    let price = price_keypair.pubkey();
+    sim.set_min_pub(price, 1).await.unwrap();
+
+    // The price goes up 10 per slot, the aggregate of each slot is the quote of 2 slots before
+    for slot in (2..=20).step_by(2) {
+        sim.warp_to_slot(slot).await.unwrap();
+        let quote = Quote {
+            price:      1_000 + 10 * slot as i64,
+            confidence: 5,
+            status:     PC_STATUS_TRADING,
+        };
+        sim.publish_all(price, &publishers, &[quote]).await.unwrap();
+    }
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(price_data.last_slot_, 20);
+    assert_eq!(price_data.agg_.price_, 1_180);
+    assert_eq!(
+        usize::from(price_data.price_history_.num_entries_),
+        PRICE_HISTORY_LEN
+    );
+    assert_eq!(price_data.price_drift(), Some(10_000_000));
+
+    let latest = price_data.get_price_unchecked();
+    assert_eq!(price_data.get_price_extrapolated(20, 5), Some(latest));
+    let extrapolated = price_data.get_price_extrapolated(23, 5).unwrap();
+    assert_eq!(extrapolated.price, 1_210);
+    assert_eq!(extrapolated.conf, latest.conf + 30);
+    assert_eq!(extrapolated.publish_time, latest.publish_time);
+    assert_eq!(price_data.get_price_extrapolated(26, 5), None);
+
+    // The history is rescaled with the prices
+    sim.set_exponent(price, -4).await.unwrap();
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(price_data.price_drift(), Some(1_000_000));
+}
+
+/// Price accounts on Solana don't keep a price history
+#[cfg(feature = "solana")]
+#[test]
+fn test_price_drift_solana() {
+    use crate::accounts::PriceAccount;
+
+    let mut price_account = PriceAccount::zeroed();
+    price_account.agg_.price_ = 100;
+    price_account.agg_.pub_slot_ = 1;
+    price_account.record_price_history();
+    price_account.agg_.pub_slot_ = 2;
+    price_account.record_price_history();
+    assert_eq!(price_account.price_drift(), None);
+    assert_eq!(price_account.get_price_extrapolated(2, 5), None);
+}
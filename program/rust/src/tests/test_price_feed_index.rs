@@ -0,0 +1,63 @@
+use {
+    crate::{
+        accounts::{
+            PermissionAccount,
+            PriceAccount,
+        },
+        tests::pyth_simulator::PythSimulator,
+    },
+    solana_sdk::signer::Signer,
+    std::mem::size_of,
+};
+
+#[tokio::test]
+async fn test_init_price_feed_index() {
+    let mut sim = PythSimulator::new().await;
+    let mapping_keypair = sim.init_mapping().await.unwrap();
+    let product_keypair = sim.add_product(&mapping_keypair).await.unwrap();
+    let mut prices = vec![];
+    for _ in 0..3 {
+        prices.push(sim.add_price(&product_keypair, -5).await.unwrap().pubkey());
+    }
+
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(prices[0])
+        .await
+        .unwrap();
+    assert_eq!(price_data.feed_index_, 0);
+
+    // Indexes are assigned in order, starting at 1
+    for price in prices.iter() {
+        sim.init_price_feed_index(*price).await.unwrap();
+    }
+    for (expected_index, price) in (1..).zip(prices.iter()) {
+        let price_data = sim
+            .get_account_data_as::<PriceAccount>(*price)
+            .await
+            .unwrap();
+        assert_eq!(price_data.feed_index_, expected_index);
+    }
+
+    // A price account keeps its index, and the counter doesn't move
+    sim.init_price_feed_index(prices[1]).await.unwrap();
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(prices[1])
+        .await
+        .unwrap();
+    assert_eq!(price_data.feed_index_, 2);
+    let new_price = sim.add_price(&product_keypair, -5).await.unwrap().pubkey();
+    sim.init_price_feed_index(new_price).await.unwrap();
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(new_price)
+        .await
+        .unwrap();
+    assert_eq!(price_data.feed_index_, 4);
+
+    // The permissions account stores the last assigned index
+    let permissions_pubkey = sim.get_permissions_pubkey();
+    let permissions_account = sim.get_account(permissions_pubkey).await.unwrap();
+    assert_eq!(
+        permissions_account.data[size_of::<PermissionAccount>()..PermissionAccount::NEW_SIZE],
+        4u32.to_le_bytes()
+    );
+}
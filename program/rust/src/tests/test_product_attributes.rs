@@ -0,0 +1,144 @@
+use {
+    crate::{
+        accounts::{
+            validate_product_attributes,
+            ProductAccount,
+            ProductAttributeKey,
+        },
+        error::OracleError,
+        sdk::ProductAttributes,
+        tests::pyth_simulator::{
+            PythSimulator,
+            TransactionFailure,
+        },
+    },
+    solana_program::{
+        account_info::AccountInfo,
+        clock::Epoch,
+        entrypoint::ProgramResult,
+    },
+    solana_sdk::signer::Signer,
+};
+
+#[test]
+fn test_product_attribute_keys() {
+    for key in ProductAttributeKey::ALL {
+        assert_eq!(
+            ProductAttributeKey::from_bytes(key.as_str().as_bytes()),
+            Some(key)
+        );
+    }
+    assert_eq!(ProductAttributeKey::from_bytes(b"quote_currencly"), None);
+    assert_eq!(ProductAttributeKey::from_bytes(b"Symbol"), None);
+}
+
+/// Validate the metadata made of the string pairs `attributes`
+fn validate(attributes: &[(&str, &str)]) -> ProgramResult {
+    let attributes: Vec<(&[u8], &[u8])> = attributes
+        .iter()
+        .map(|(key, value)| (key.as_bytes(), value.as_bytes()))
+        .collect();
+    validate_product_attributes(&attributes)
+}
+
+#[test]
+fn test_validate_product_attributes() {
+    assert_eq!(validate(&[]), Ok(()));
+    assert_eq!(
+        validate(&[("symbol", "Crypto.BTC/USD"), ("tenor", "Spot")]),
+        Ok(())
+    );
+    assert_eq!(
+        validate(&[("symbol", "BTC"), ("symbol", "ETH")]),
+        Err(OracleError::InvalidProductAttribute.into())
+    );
+    assert_eq!(
+        validate(&[("symbol", "")]),
+        Err(OracleError::InvalidProductAttribute.into())
+    );
+    assert_eq!(
+        validate_product_attributes(&[(b"symbol".as_slice(), [0xff].as_slice())]),
+        Err(OracleError::InvalidProductAttribute.into())
+    );
+
+    let unknown = [("quote_currencly", "USD"), ("custom", "")];
+    #[cfg(not(feature = "unknown-product-attributes"))]
+    assert_eq!(
+        validate(&unknown),
+        Err(OracleError::UnknownProductAttribute.into())
+    );
+    #[cfg(feature = "unknown-product-attributes")]
+    assert_eq!(validate(&unknown), Ok(()));
+}
+
+#[tokio::test]
+async fn test_product_attributes() {
+    let mut sim = PythSimulator::new().await;
+    let mapping_keypair = sim.init_mapping().await.unwrap();
+    let product_keypair = sim.add_product(&mapping_keypair).await.unwrap();
+    let product = product_keypair.pubkey();
+
+    let attributes = [
+        ("symbol", "Crypto.BTC/USD"),
+        ("asset_type", "Crypto"),
+        ("quote_currency", "USD"),
+        ("base", "BTC"),
+        ("tenor", "Spot"),
+    ];
+    sim.upd_product(&product_keypair, &attributes)
+        .await
+        .unwrap();
+
+    let mut account = sim.get_account(product).await.unwrap();
+    let mut lamports = account.lamports;
+    let account_info = AccountInfo::new(
+        &product,
+        false,
+        false,
+        &mut lamports,
+        &mut account.data,
+        &account.owner,
+        false,
+        Epoch::default(),
+    );
+    let product_attributes = ProductAccount::load_attributes(&account_info).unwrap();
+    assert_eq!(
+        product_attributes,
+        ProductAttributes(
+            attributes
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect()
+        )
+    );
+    assert_eq!(product_attributes.symbol(), Some("Crypto.BTC/USD"));
+    assert_eq!(product_attributes.asset_type(), Some("Crypto"));
+    assert_eq!(product_attributes.quote_currency(), Some("USD"));
+    assert_eq!(product_attributes.base(), Some("BTC"));
+    assert_eq!(product_attributes.tenor(), Some("Spot"));
+    assert_eq!(product_attributes.get(ProductAttributeKey::Country), None);
+
+    // Invalid metadata leaves the account untouched
+    sim.upd_product(&product_keypair, &[("symbol", "BTC"), ("symbol", "ETH")])
+        .await
+        .assert_fails_with(OracleError::InvalidProductAttribute);
+    let typo = sim
+        .upd_product(&product_keypair, &[("quote_currencly", "USD")])
+        .await;
+    #[cfg(not(feature = "unknown-product-attributes"))]
+    {
+        typo.assert_fails_with(OracleError::UnknownProductAttribute);
+        assert_eq!(
+            sim.get_product_attributes(product).await,
+            product_attributes.0
+        );
+    }
+    #[cfg(feature = "unknown-product-attributes")]
+    {
+        typo.unwrap();
+        assert_eq!(
+            sim.get_product_attributes(product).await,
+            vec![("quote_currencly".to_string(), "USD".to_string())]
+        );
+    }
+}
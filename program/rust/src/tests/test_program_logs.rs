@@ -0,0 +1,166 @@
+use {
+    crate::{
+        c_oracle_header::PC_STATUS_TRADING,
+        error::OracleError,
+        events::{
+            AggregatePriceUpdate,
+            Event,
+        },
+        tests::{
+            program_logs::ProgramLogs,
+            pyth_simulator::{
+                PythSimulator,
+                Quote,
+            },
+        },
+    },
+    solana_program::{
+        native_token::LAMPORTS_PER_SOL,
+        pubkey::Pubkey,
+    },
+    solana_sdk::{
+        signature::Keypair,
+        signer::Signer,
+    },
+    std::{
+        collections::HashMap,
+        iter::once,
+    },
+};
+
+#[test]
+fn test_parse_program_logs() {
+    let oracle = Pubkey::new_unique();
+    let other_program = Pubkey::new_unique();
+    let event = AggregatePriceUpdate {
+        price_account:    Pubkey::new_unique(),
+        slot:             2,
+        price:            100,
+        conf:             5,
+        status:           PC_STATUS_TRADING,
+        num_contributors: 1,
+    };
+    let logs = vec![
+        format!("Program {other_program} invoke [1]"),
+        "Program log: not the oracle".to_string(),
+        format!("Program {other_program} consumed 100 of 200000 compute units"),
+        format!("Program {other_program} success"),
+        format!("Program {oracle} invoke [1]"),
+        "Program log: first message".to_string(),
+        format!("Program data: {}", base64::encode(event.data())),
+        format!("Program {other_program} invoke [2]"),
+        "Program log: still not the oracle".to_string(),
+        "Program data: AQID".to_string(),
+        format!("Program return: {other_program} AQID"),
+        format!("Program {other_program} success"),
+        format!("Program data: AQID {}", base64::encode([4, 5])),
+        format!("Program return: {oracle} {}", base64::encode([6, 7])),
+        format!("Program {oracle} consumed 1234 of 199900 compute units"),
+        format!("Program {oracle} success"),
+        format!("Program {oracle} invoke [1]"),
+        "Program log: panicked at 'oops', src/processor.rs:1:1".to_string(),
+        format!("Program {oracle} consumed 567 of 198666 compute units"),
+        format!("Program {oracle} failed: Program failed to complete"),
+    ];
+
+    let program_logs = ProgramLogs::parse(&oracle, &logs);
+    assert_eq!(
+        program_logs,
+        ProgramLogs {
+            messages:      vec!["first message".to_string()],
+            data:          vec![vec![event.data()], vec![vec![1, 2, 3], vec![4, 5]]],
+            compute_units: vec![1234, 567],
+            panics:        vec!["'oops', src/processor.rs:1:1".to_string()],
+            failure:       Some("Program failed to complete".to_string()),
+            return_data:   Some(vec![6, 7]),
+        }
+    );
+    assert_eq!(program_logs.events::<AggregatePriceUpdate>(), vec![event]);
+}
+
+#[tokio::test]
+async fn test_simulate_program_logs() {
+    let mut sim = PythSimulator::new_bpf().await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(1).await;
+    let price = price_keypair.pubkey();
+    let publisher = &publishers[0];
+    sim.set_min_pub(price, 1).await.unwrap();
+
+    let quote = Quote {
+        price:      100,
+        confidence: 5,
+        status:     PC_STATUS_TRADING,
+    };
+    sim.upd_price(publisher, price, quote).await.unwrap();
+
+    // A rejected update is logged
+    let instruction = sim
+        .upd_price_no_fail_on_error_instruction(publisher, price, &quote)
+        .await
+        .unwrap();
+    let program_logs = sim
+        .simulate_program_logs(&[instruction], &vec![publisher], publisher)
+        .await
+        .unwrap();
+    assert!(program_logs
+        .messages
+        .contains(&"upd_price_no_fail_on_error: rejected: InvalidArgument".to_string()));
+    assert!(program_logs.events::<AggregatePriceUpdate>().is_empty());
+    assert_eq!(program_logs.failure, None);
+
+    // The aggregation emits an event
+    sim.warp_to_slot(2).await.unwrap();
+    let instruction = sim
+        .upd_price_no_fail_on_error_instruction(publisher, price, &quote)
+        .await
+        .unwrap();
+    let program_logs = sim
+        .simulate_program_logs(&[instruction.clone()], &vec![publisher], publisher)
+        .await
+        .unwrap();
+    assert_eq!(
+        program_logs.events::<AggregatePriceUpdate>(),
+        vec![AggregatePriceUpdate {
+            price_account:    price,
+            slot:             2,
+            price:            100,
+            conf:             5,
+            status:           PC_STATUS_TRADING,
+            num_contributors: 1,
+        }]
+    );
+    assert_eq!(program_logs.panics, Vec::<String>::new());
+    assert_eq!(program_logs.failure, None);
+    assert_eq!(
+        program_logs.compute_units,
+        vec![sim
+            .simulate_compute_units(&[instruction], &vec![publisher], publisher)
+            .await
+            .unwrap()]
+    );
+
+    // The logs of failed transactions are parsed as well
+    let not_a_publisher = Keypair::new();
+    sim.airdrop(&not_a_publisher.pubkey(), 100 * LAMPORTS_PER_SOL)
+        .await
+        .unwrap();
+    let instructions = sim
+        .upd_price_instructions(
+            &not_a_publisher,
+            &HashMap::from_iter(once(("BTC".to_string(), price))),
+            &HashMap::from_iter(once(("BTC".to_string(), quote))),
+        )
+        .await
+        .unwrap();
+    let program_logs = sim
+        .simulate_program_logs(&instructions, &vec![&not_a_publisher], &not_a_publisher)
+        .await
+        .unwrap();
+    assert_eq!(
+        program_logs.failure,
+        Some(format!(
+            "custom program error: {:#x}",
+            OracleError::PermissionViolation as u32
+        ))
+    );
+}
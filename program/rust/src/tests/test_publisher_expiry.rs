@@ -0,0 +1,159 @@
+use {
+    crate::{
+        accounts::{
+            PriceAccount,
+            PublisherExpiries,
+        },
+        c_oracle_header::PC_STATUS_TRADING,
+        error::OracleError,
+        tests::pyth_simulator::{
+            PythSimulator,
+            Quote,
+            TransactionFailure,
+        },
+    },
+    solana_program::{
+        account_info::AccountInfo,
+        clock::Epoch,
+        instruction::InstructionError,
+        native_token::LAMPORTS_PER_SOL,
+        pubkey::Pubkey,
+        rent::Rent,
+    },
+    solana_sdk::{
+        signature::Keypair,
+        signer::Signer,
+    },
+    std::{
+        collections::HashMap,
+        mem::size_of,
+    },
+};
+
+fn quote(price: i64) -> Quote {
+    Quote {
+        price,
+        confidence: 1,
+        status: PC_STATUS_TRADING,
+    }
+}
+
+async fn funded_keypair(sim: &mut PythSimulator) -> Keypair {
+    let keypair = Keypair::new();
+    sim.airdrop(&keypair.pubkey(), 100 * LAMPORTS_PER_SOL)
+        .await
+        .unwrap();
+    keypair
+}
+
+async fn publisher_expiry(
+    sim: &mut PythSimulator,
+    price: Pubkey,
+    publisher: &Pubkey,
+) -> Option<u64> {
+    let mut account = sim.get_account(price).await.unwrap();
+    let account_info = AccountInfo::new(
+        &price,
+        false,
+        false,
+        &mut account.lamports,
+        &mut account.data,
+        &account.owner,
+        false,
+        Epoch::default(),
+    );
+    PriceAccount::read_publisher_expiry(&account_info, publisher).unwrap()
+}
+
+#[tokio::test]
+async fn test_publisher_expiry() {
+    let mut sim = PythSimulator::new().await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(1).await;
+    let price = price_keypair.pubkey();
+    let regular = &publishers[0];
+    assert_eq!(
+        sim.get_account(price).await.unwrap().data.len(),
+        size_of::<PriceAccount>()
+    );
+    assert_eq!(
+        publisher_expiry(&mut sim, price, &regular.pubkey()).await,
+        None
+    );
+
+    // The price account is resized to hold the expiries
+    let trial = funded_keypair(&mut sim).await;
+    sim.add_publisher_with_expiry(price, trial.pubkey(), 10)
+        .await
+        .unwrap();
+    let price_account = sim.get_account(price).await.unwrap();
+    let size = size_of::<PriceAccount>() + PriceAccount::extension_size::<PublisherExpiries>();
+    assert_eq!(price_account.data.len(), size);
+    assert!(price_account.lamports >= Rent::default().minimum_balance(size));
+    assert_eq!(
+        publisher_expiry(&mut sim, price, &trial.pubkey()).await,
+        Some(10)
+    );
+    assert_eq!(
+        publisher_expiry(&mut sim, price, &regular.pubkey()).await,
+        None
+    );
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(price_data.num_, 2);
+
+    // The updates are accepted up to and including the expiry slot
+    sim.warp_to_slot(5).await.unwrap();
+    sim.upd_price(&trial, price, quote(100)).await.unwrap();
+    sim.warp_to_slot(10).await.unwrap();
+    sim.upd_price(&trial, price, quote(101)).await.unwrap();
+
+    sim.warp_to_slot(11).await.unwrap();
+    sim.upd_price(&trial, price, quote(102))
+        .await
+        .assert_fails_with(OracleError::PublisherExpired);
+    let price_accounts = HashMap::from([("price".to_string(), price)]);
+    let quotes = HashMap::from([("price".to_string(), quote(102))]);
+    sim.upd_price_batch_ix(&trial, &price_accounts, &quotes)
+        .await
+        .assert_fails_with(OracleError::PublisherExpired);
+    sim.upd_price(regular, price, quote(100)).await.unwrap();
+
+    // A rotated publisher keeps its expiry
+    let rotated = funded_keypair(&mut sim).await;
+    sim.rotate_publisher(price, trial.pubkey(), rotated.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(
+        publisher_expiry(&mut sim, price, &rotated.pubkey()).await,
+        Some(10)
+    );
+    sim.upd_price(&rotated, price, quote(102))
+        .await
+        .assert_fails_with(OracleError::PublisherExpired);
+
+    // Another publisher with an expiry doesn't resize the account again
+    let other_trial = funded_keypair(&mut sim).await;
+    sim.add_publisher_with_expiry(price, other_trial.pubkey(), 100)
+        .await
+        .unwrap();
+    assert_eq!(sim.get_account(price).await.unwrap().data.len(), size);
+    sim.upd_price(&other_trial, price, quote(100))
+        .await
+        .unwrap();
+
+    // A publisher added without an expiry has none, even in a resized account
+    let later = funded_keypair(&mut sim).await;
+    sim.add_publisher(price, later.pubkey()).await.unwrap();
+    assert_eq!(
+        publisher_expiry(&mut sim, price, &later.pubkey()).await,
+        None
+    );
+    sim.upd_price(&later, price, quote(100)).await.unwrap();
+
+    // Existing publishers and the default key can't be added again with an expiry
+    sim.add_publisher_with_expiry(price, regular.pubkey(), 100)
+        .await
+        .assert_fails_with(InstructionError::InvalidArgument);
+    sim.add_publisher_with_expiry(price, Pubkey::default(), 100)
+        .await
+        .assert_fails_with(InstructionError::InvalidArgument);
+}
@@ -0,0 +1,219 @@
+use {
+    crate::{
+        accounts::{
+            PriceAccount,
+            PriceComponent,
+            PruningState,
+            PublisherPruning,
+            PublisherStats,
+        },
+        c_oracle_header::PC_STATUS_TRADING,
+        tests::pyth_simulator::{
+            PythSimulator,
+            Quote,
+            TransactionFailure,
+        },
+    },
+    bytemuck::{
+        pod_read_unaligned,
+        Zeroable,
+    },
+    solana_program::{
+        instruction::InstructionError,
+        pubkey::Pubkey,
+        rent::Rent,
+    },
+    solana_sdk::{
+        signature::Keypair,
+        signer::Signer,
+    },
+    std::mem::size_of,
+};
+
+/// Quotes outside of the price bounds of the test feed are excluded from the aggregate
+const GOOD: i64 = 100;
+const BAD: i64 = 10_000;
+
+fn quote(price: i64) -> Quote {
+    Quote {
+        price,
+        confidence: 1,
+        status: PC_STATUS_TRADING,
+    }
+}
+
+async fn publisher_pruning(sim: &mut PythSimulator, price: Pubkey) -> PublisherPruning {
+    let account = sim.get_account(price).await.unwrap();
+    pod_read_unaligned(
+        PriceAccount::extension_data::<PublisherPruning>(
+            &account.data[size_of::<PriceAccount>()..],
+        )
+        .unwrap(),
+    )
+}
+
+/// Publish the quote of `flapping` after the good quotes of the other publishers in a new slot.
+/// The first update of the slot aggregates the quotes of the previous slot.
+async fn publish_round(
+    sim: &mut PythSimulator,
+    price: Pubkey,
+    publishers: &[Keypair],
+    slot: u64,
+    flapping: i64,
+) -> PriceAccount {
+    sim.warp_to_slot(slot).await.unwrap();
+    let (flapping_publisher, others) = publishers.split_last().unwrap();
+    for publisher in others {
+        sim.upd_price(publisher, price, quote(GOOD)).await.unwrap();
+    }
+    sim.upd_price(flapping_publisher, price, quote(flapping))
+        .await
+        .unwrap();
+    sim.get_pyth_account::<PriceAccount>(price).await
+}
+
+fn excluded(price_data: &PriceAccount, publisher: &Keypair) -> u8 {
+    price_data
+        .publisher_stats(&publisher.pubkey())
+        .unwrap()
+        .excluded_
+}
+
+#[test]
+fn test_pruning_state_transitions() {
+    let mut state = PruningState::zeroed();
+
+    // Intermittent misses don't prune a publisher
+    for included in [false, false, true, false, true, false, false] {
+        assert!(!state.record_round(included, 3, 2));
+    }
+    assert_eq!(state.rounds_, 2);
+    assert!(state.record_round(false, 3, 2));
+    assert_eq!((state.pruned_, state.rounds_), (1, 0));
+
+    // A flapping pruned publisher isn't readmitted
+    for included in [true, false, true, false, false, true] {
+        assert!(state.record_round(included, 3, 2));
+    }
+    assert_eq!(state.rounds_, 1);
+    assert!(!state.record_round(true, 3, 2));
+    assert_eq!((state.pruned_, state.rounds_), (0, 0));
+}
+
+#[test]
+fn test_pruning_states_follow_components() {
+    let publishers = [(); 4].map(|_| Pubkey::new_unique());
+    let components = |publishers: &[Pubkey]| {
+        publishers
+            .iter()
+            .map(|publisher| PriceComponent {
+                pub_: *publisher,
+                ..PriceComponent::zeroed()
+            })
+            .collect::<Vec<_>>()
+    };
+    let mut pruning = PublisherPruning::zeroed();
+    let initial = components(&publishers[..3]);
+    for index in 0..initial.len() {
+        pruning.state_mut(index, &initial).rounds_ = index as u32 + 1;
+    }
+
+    // The second publisher was removed and a new one was added before the others
+    let moved = components(&[publishers[3], publishers[0], publishers[2]]);
+    let rounds = (0..moved.len())
+        .map(|index| pruning.state_mut(index, &moved).rounds_)
+        .collect::<Vec<_>>();
+    assert_eq!(rounds, vec![0, 1, 3]);
+    for (state, component) in pruning.states_.iter().zip(moved.iter()) {
+        assert_eq!(state.publisher_, component.pub_);
+    }
+}
+
+#[tokio::test]
+async fn test_publisher_pruning() {
+    let mut sim = PythSimulator::new().await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(3).await;
+    let price = price_keypair.pubkey();
+    let flapping = &publishers[2];
+    sim.set_min_pub(price, 1).await.unwrap();
+    sim.set_price_bounds(price, 50, 200).await.unwrap();
+
+    // Enabling the pruning resizes the price account
+    let initial_size = sim.get_account(price).await.unwrap().data.len();
+    sim.set_publisher_pruning(price, 3, 0)
+        .await
+        .assert_fails_with(InstructionError::InvalidArgument);
+    sim.set_publisher_pruning(price, 3, 2).await.unwrap();
+    let price_account = sim.get_account(price).await.unwrap();
+    let size = initial_size + PriceAccount::extension_size::<PublisherPruning>();
+    assert_eq!(price_account.data.len(), size);
+    assert!(price_account.lamports >= Rent::default().minimum_balance(size));
+    let pruning = publisher_pruning(&mut sim, price).await;
+    assert_eq!((pruning.prune_rounds_, pruning.readmit_rounds_), (3, 2));
+
+    // No one has published in the first round, then the flapping publisher misses two more
+    publish_round(&mut sim, price, &publishers, 2, BAD).await;
+    let price_data = publish_round(&mut sim, price, &publishers, 3, BAD).await;
+    assert_eq!(excluded(&price_data, flapping), 0);
+    let price_data = publish_round(&mut sim, price, &publishers, 4, GOOD).await;
+    assert_eq!(excluded(&price_data, flapping), PublisherStats::PRUNED);
+    for publisher in &publishers[..2] {
+        assert_eq!(excluded(&price_data, publisher), 0);
+    }
+
+    // Manual exclusions don't readmit pruned publishers
+    sim.set_publisher_excluded(price, flapping.pubkey(), true)
+        .await
+        .unwrap();
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(
+        excluded(&price_data, flapping),
+        PublisherStats::EXCLUDED | PublisherStats::PRUNED
+    );
+    sim.set_publisher_excluded(price, flapping.pubkey(), false)
+        .await
+        .unwrap();
+
+    // The pruned publisher alternates good and bad quotes, which doesn't readmit it. Its good
+    // quotes are not included.
+    let price_data = publish_round(&mut sim, price, &publishers, 5, BAD).await;
+    assert_eq!(price_data.num_qt_, 2);
+    publish_round(&mut sim, price, &publishers, 6, GOOD).await;
+    let price_data = publish_round(&mut sim, price, &publishers, 7, GOOD).await;
+    assert_eq!(price_data.num_qt_, 2);
+    assert_eq!(excluded(&price_data, flapping), PublisherStats::PRUNED);
+    let flapping_index = price_data
+        .comp_
+        .iter()
+        .position(|component| component.pub_ == flapping.pubkey())
+        .unwrap();
+    let state = publisher_pruning(&mut sim, price).await.states_[flapping_index];
+    assert_eq!(state.publisher_, flapping.pubkey());
+    assert_eq!((state.pruned_, state.rounds_), (1, 1));
+
+    // Two good quotes in a row readmit it
+    let price_data = publish_round(&mut sim, price, &publishers, 8, GOOD).await;
+    assert_eq!(excluded(&price_data, flapping), 0);
+    let price_data = publish_round(&mut sim, price, &publishers, 9, GOOD).await;
+    assert_eq!(price_data.num_qt_, 3);
+
+    // Once admitted, alternating good and bad quotes doesn't prune it
+    for (slot, flapping_price) in (10..16).zip([BAD, GOOD].iter().cycle()) {
+        let price_data = publish_round(&mut sim, price, &publishers, slot, *flapping_price).await;
+        assert_eq!(excluded(&price_data, flapping), 0);
+    }
+
+    // Disabling the pruning readmits everyone
+    for slot in 16..19 {
+        publish_round(&mut sim, price, &publishers, slot, BAD).await;
+    }
+    let price_data = publish_round(&mut sim, price, &publishers, 19, BAD).await;
+    assert_eq!(excluded(&price_data, flapping), PublisherStats::PRUNED);
+    sim.set_publisher_pruning(price, 0, 0).await.unwrap();
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(excluded(&price_data, flapping), 0);
+    let pruning = publisher_pruning(&mut sim, price).await;
+    assert_eq!(pruning, PublisherPruning::zeroed());
+    let price_data = publish_round(&mut sim, price, &publishers, 20, BAD).await;
+    assert_eq!(excluded(&price_data, flapping), 0);
+}
@@ -0,0 +1,183 @@
+use {
+    crate::{
+        accounts::{
+            PriceAccount,
+            PublisherStats,
+        },
+        aggregation::{
+            upd_aggregate,
+            upd_publisher_stats,
+        },
+        c_oracle_header::{
+            PC_MAX_SEND_LATENCY,
+            PC_STATUS_TRADING,
+            PC_STATUS_UNKNOWN,
+            PUBLISHER_STATS_NUM_ROUNDS,
+        },
+        tests::pyth_simulator::{
+            PythSimulator,
+            Quote,
+        },
+    },
+    bytemuck::Zeroable,
+    solana_sdk::{
+        signature::Keypair,
+        signer::Signer,
+    },
+};
+
+#[test]
+fn test_record_round() {
+    let mut stats = PublisherStats::zeroed();
+    assert_eq!(stats.num_rounds(), 0);
+    assert_eq!(stats.num_included_rounds(), 0);
+    assert_eq!(stats.average_latency(), None);
+
+    stats.record_round(Some(1));
+    stats.record_round(None);
+    stats.record_round(Some(4));
+    assert_eq!(stats.num_rounds(), 3);
+    assert_eq!(stats.num_included_rounds(), 2);
+    assert_eq!(stats.included_rounds_, 0b101);
+    assert_eq!(stats.average_latency(), Some(2.5));
+
+    // Latencies saturate
+    stats.record_round(Some(1000));
+    assert_eq!(stats.latencies_[3], u8::MAX);
+
+    // Only the last PUBLISHER_STATS_NUM_ROUNDS rounds are kept
+    for _ in 0..PUBLISHER_STATS_NUM_ROUNDS - 1 {
+        stats.record_round(None);
+    }
+    assert_eq!(stats.num_rounds(), PUBLISHER_STATS_NUM_ROUNDS as u32);
+    assert_eq!(stats.num_included_rounds(), 1);
+    assert_eq!(stats.average_latency(), Some(255.0));
+
+    stats.record_round(None);
+    assert_eq!(stats.num_rounds(), PUBLISHER_STATS_NUM_ROUNDS as u32);
+    assert_eq!(stats.num_included_rounds(), 0);
+    assert_eq!(stats.average_latency(), None);
+
+    for _ in 0..PUBLISHER_STATS_NUM_ROUNDS {
+        stats.record_round(Some(2));
+    }
+    assert_eq!(stats.included_rounds_, u32::MAX);
+    assert_eq!(stats.average_latency(), Some(2.0));
+}
+
+#[test]
+fn test_upd_publisher_stats() {
+    let mut price_account = PriceAccount::zeroed();
+    price_account.num_ = 3;
+    price_account.min_pub_ = 1;
+    for component in price_account.comp_[..3].iter_mut() {
+        component.latest_.price_ = 100;
+        component.latest_.conf_ = 1;
+        component.latest_.status_ = PC_STATUS_TRADING;
+        component.latest_.pub_slot_ = 1000;
+    }
+    // Stale quote
+    price_account.comp_[1].latest_.pub_slot_ = 1000 - u64::from(PC_MAX_SEND_LATENCY);
+    // Quote without a trading status
+    price_account.comp_[2].latest_.status_ = PC_STATUS_UNKNOWN;
+    // Stats of a slot without a publisher
+    price_account.publisher_stats_[3].num_rounds_ = 5;
+
+    assert!(upd_aggregate(&mut price_account, 1003, 0));
+    upd_publisher_stats(&mut price_account, 1003);
+
+    let stats = &price_account.publisher_stats_;
+    assert_eq!(stats[0].num_rounds(), 1);
+    assert_eq!(stats[0].num_included_rounds(), 1);
+    assert_eq!(stats[0].average_latency(), Some(3.0));
+    assert_eq!(stats[1].num_rounds(), 1);
+    assert_eq!(stats[1].num_included_rounds(), 0);
+    assert_eq!(stats[2].num_rounds(), 1);
+    assert_eq!(stats[2].num_included_rounds(), 0);
+    assert_eq!(stats[3].num_rounds(), 5);
+}
+
+#[tokio::test]
+async fn test_publisher_stats() {
+    let mut sim = PythSimulator::new().await;
+
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(3).await;
+    let price = price_keypair.pubkey();
+    let (fast_publisher, slow_publisher, silent_publisher) =
+        (&publishers[0], &publishers[1], &publishers[2]);
+    sim.set_min_pub(price, 1).await.unwrap();
+
+    let quote = |slot: u64| Quote {
+        price:      100 + slot as i64,
+        confidence: 1,
+        status:     PC_STATUS_TRADING,
+    };
+
+    // The fast publisher publishes every slot and the slow publisher every other slot. Every
+    // slot starts a new aggregation round.
+    for slot in 1..=6 {
+        if slot > 1 {
+            sim.warp_to_slot(slot).await.unwrap();
+        }
+        sim.upd_price(fast_publisher, price, quote(slot))
+            .await
+            .unwrap();
+        if slot % 2 == 1 {
+            sim.upd_price(slow_publisher, price, quote(slot))
+                .await
+                .unwrap();
+        }
+    }
+
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+
+    // No quote was published before the first round, so it includes none
+    let fast_stats = price_data
+        .publisher_stats(&fast_publisher.pubkey())
+        .unwrap();
+    assert_eq!(fast_stats.num_rounds(), 6);
+    assert_eq!(fast_stats.num_included_rounds(), 5);
+    assert_eq!(fast_stats.average_latency(), Some(1.0));
+
+    let slow_stats = price_data
+        .publisher_stats(&slow_publisher.pubkey())
+        .unwrap();
+    assert_eq!(slow_stats.num_rounds(), 6);
+    assert_eq!(slow_stats.num_included_rounds(), 5);
+    assert_eq!(slow_stats.average_latency(), Some(1.4));
+
+    let silent_stats = price_data
+        .publisher_stats(&silent_publisher.pubkey())
+        .unwrap();
+    assert_eq!(silent_stats.num_rounds(), 6);
+    assert_eq!(silent_stats.num_included_rounds(), 0);
+    assert_eq!(silent_stats.average_latency(), None);
+
+    assert!(price_data
+        .publisher_stats(&Keypair::new().pubkey())
+        .is_none());
+
+    // Adding a publisher reorders the components but keeps the statistics of each publisher
+    let new_publisher = Keypair::new();
+    sim.add_publisher(price, new_publisher.pubkey())
+        .await
+        .unwrap();
+
+    let new_price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    for publisher in [fast_publisher, slow_publisher, silent_publisher] {
+        assert_eq!(
+            new_price_data.publisher_stats(&publisher.pubkey()),
+            price_data.publisher_stats(&publisher.pubkey())
+        );
+    }
+    assert_eq!(
+        new_price_data.publisher_stats(&new_publisher.pubkey()),
+        Some(&PublisherStats::zeroed())
+    );
+}
@@ -0,0 +1,166 @@
+use {
+    crate::{
+        accounts::{
+            PriceAccount,
+            PriceInfo,
+            PythAccount,
+        },
+        c_oracle_header::{
+            PC_STATUS_TRADING,
+            PC_VERSION,
+        },
+        deserialize::{
+            load_checked,
+            load_mut,
+        },
+        error::OracleError,
+        instruction::{
+            OracleCommand,
+            UpdPriceArgs,
+        },
+        processor::process_instruction,
+        tests::{
+            pyth_simulator::PythSimulator,
+            test_utils::{
+                update_clock_slot,
+                AccountSetup,
+            },
+        },
+    },
+    solana_program::{
+        account_info::AccountInfo,
+        pubkey::Pubkey,
+    },
+    solana_sdk::signer::Signer,
+    std::mem::size_of,
+};
+
+fn populate_instruction(
+    instruction_data: &mut [u8],
+    command: OracleCommand,
+    price: i64,
+    pub_slot: u64,
+) {
+    let mut cmd = load_mut::<UpdPriceArgs>(instruction_data).unwrap();
+    cmd.header = command.into();
+    cmd.status = PC_STATUS_TRADING;
+    cmd.price = price;
+    cmd.confidence = 1;
+    cmd.publishing_slot = pub_slot;
+    cmd.unused_ = 0;
+}
+
+fn latest_price(price_account: &AccountInfo) -> PriceInfo {
+    load_checked::<PriceAccount>(price_account, PC_VERSION)
+        .unwrap()
+        .comp_[0]
+        .latest_
+}
+
+#[test]
+fn test_max_updates_per_slot() {
+    let mut instruction_data = [0u8; size_of::<UpdPriceArgs>()];
+
+    let program_id = Pubkey::new_unique();
+
+    let mut funding_setup = AccountSetup::new_funding();
+    let funding_account = funding_setup.as_account_info();
+
+    let mut price_setup = AccountSetup::new::<PriceAccount>(&program_id);
+    let mut price_account = price_setup.as_account_info();
+    price_account.is_signer = false;
+    PriceAccount::initialize(&price_account, PC_VERSION).unwrap();
+
+    {
+        let mut price_data = load_checked::<PriceAccount>(&price_account, PC_VERSION).unwrap();
+        price_data.num_ = 1;
+        price_data.comp_[0].pub_ = *funding_account.key;
+        price_data.max_slot_updates_ = 1;
+    }
+
+    let mut clock_setup = AccountSetup::new_clock();
+    let mut clock_account = clock_setup.as_account_info();
+    clock_account.is_signer = false;
+    clock_account.is_writable = false;
+
+    update_clock_slot(&mut clock_account, 10);
+
+    let accounts = [
+        funding_account.clone(),
+        price_account.clone(),
+        clock_account.clone(),
+    ];
+
+    populate_instruction(&mut instruction_data, OracleCommand::UpdPrice, 42, 8);
+    process_instruction(&program_id, &accounts, &instruction_data).unwrap();
+    let stored_price = latest_price(&price_account);
+    assert_eq!(stored_price.price_, 42);
+    assert_eq!(stored_price.pub_slot_, 8);
+
+    // A second update in the same slot is rejected, even with a more recent publishing slot
+    populate_instruction(&mut instruction_data, OracleCommand::UpdPrice, 43, 9);
+    assert_eq!(
+        process_instruction(&program_id, &accounts, &instruction_data),
+        Err(OracleError::TooManyPriceUpdates.into())
+    );
+    assert_eq!(latest_price(&price_account), stored_price);
+
+    // It is ignored by upd_price_no_fail_on_error
+    populate_instruction(
+        &mut instruction_data,
+        OracleCommand::UpdPriceNoFailOnError,
+        43,
+        9,
+    );
+    process_instruction(&program_id, &accounts, &instruction_data).unwrap();
+    assert_eq!(latest_price(&price_account), stored_price);
+
+    // The limit resets in the next slot
+    update_clock_slot(&mut clock_account, 11);
+    populate_instruction(&mut instruction_data, OracleCommand::UpdPrice, 44, 9);
+    process_instruction(&program_id, &accounts, &instruction_data).unwrap();
+    assert_eq!(latest_price(&price_account).price_, 44);
+    {
+        let price_data = load_checked::<PriceAccount>(&price_account, PC_VERSION).unwrap();
+        assert_eq!(price_data.publisher_stats_[0].num_updates_in_slot(11), 1);
+        assert_eq!(price_data.publisher_stats_[0].num_updates_in_slot(12), 0);
+    }
+
+    // Without a limit, several updates are accepted in the same slot
+    load_checked::<PriceAccount>(&price_account, PC_VERSION)
+        .unwrap()
+        .max_slot_updates_ = 0;
+    populate_instruction(&mut instruction_data, OracleCommand::UpdPrice, 45, 10);
+    process_instruction(&program_id, &accounts, &instruction_data).unwrap();
+    populate_instruction(&mut instruction_data, OracleCommand::UpdPrice, 46, 11);
+    process_instruction(&program_id, &accounts, &instruction_data).unwrap();
+    assert_eq!(latest_price(&price_account).price_, 46);
+    assert_eq!(
+        load_checked::<PriceAccount>(&price_account, PC_VERSION)
+            .unwrap()
+            .publisher_stats_[0]
+            .num_updates_in_slot(11),
+        3
+    );
+}
+
+#[tokio::test]
+async fn test_set_max_updates_per_slot() {
+    let mut sim = PythSimulator::new().await;
+    let (price_keypair, _) = sim.setup_feed_with_publishers(1).await;
+    let price = price_keypair.pubkey();
+
+    sim.set_max_updates_per_slot(price, 2).await.unwrap();
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(price_data.max_slot_updates_, 2);
+
+    sim.set_max_updates_per_slot(price, 0).await.unwrap();
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(price_data.max_slot_updates_, 0);
+}
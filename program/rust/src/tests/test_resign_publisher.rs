@@ -0,0 +1,88 @@
+use {
+    crate::{
+        accounts::{
+            PriceAccount,
+            PriceComponent,
+        },
+        c_oracle_header::PC_STATUS_TRADING,
+        error::OracleError,
+        tests::pyth_simulator::{
+            PythSimulator,
+            Quote,
+            TransactionFailure,
+        },
+    },
+    bytemuck::Zeroable,
+    solana_program::{
+        instruction::InstructionError,
+        native_token::LAMPORTS_PER_SOL,
+    },
+    solana_sdk::{
+        signature::Keypair,
+        signer::Signer,
+    },
+};
+
+#[tokio::test]
+async fn test_resign_publisher() {
+    let mut sim = PythSimulator::new().await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(3).await;
+    let price = price_keypair.pubkey();
+    sim.set_min_pub(price, 1).await.unwrap();
+
+    let quote = Quote {
+        price:      100,
+        confidence: 5,
+        status:     PC_STATUS_TRADING,
+    };
+    sim.publish_all(price, &publishers, &[quote; 3])
+        .await
+        .unwrap();
+
+    // A non-publisher can't remove anyone
+    let stranger = Keypair::new();
+    sim.airdrop(&stranger.pubkey(), 100 * LAMPORTS_PER_SOL)
+        .await
+        .unwrap();
+    sim.resign_publisher(&stranger, price)
+        .await
+        .assert_fails_with(InstructionError::InvalidArgument);
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(price_data.num_, 3);
+
+    sim.resign_publisher(&publishers[1], price).await.unwrap();
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(price_data.num_, 2);
+    assert!(price_data
+        .publisher_stats(&publishers[1].pubkey())
+        .is_none());
+    for publisher in [&publishers[0], &publishers[2]] {
+        assert!(price_data.publisher_stats(&publisher.pubkey()).is_some());
+    }
+    assert_eq!(price_data.comp_[2], PriceComponent::zeroed());
+
+    // The publisher can neither publish nor resign again
+    sim.upd_price(&publishers[1], price, quote)
+        .await
+        .assert_fails_with(OracleError::PermissionViolation);
+    sim.resign_publisher(&publishers[1], price)
+        .await
+        .assert_fails_with(InstructionError::InvalidArgument);
+
+    // The remaining publishers still aggregate
+    sim.warp_to_slot(2).await.unwrap();
+    sim.upd_price(&publishers[0], price, quote).await.unwrap();
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(price_data.agg_.status_, PC_STATUS_TRADING);
+    assert_eq!(price_data.agg_.price_, 100);
+    assert_eq!(price_data.num_qt_, 2);
+}
@@ -0,0 +1,157 @@
+use {
+    crate::{
+        accounts::{
+            PermissionAccount,
+            PriceAccount,
+        },
+        c_oracle_header::PC_STATUS_TRADING,
+        error::OracleError,
+        instruction::OracleCommand,
+        tests::pyth_simulator::{
+            PythSimulator,
+            Quote,
+            TransactionFailure,
+        },
+    },
+    bytemuck::Zeroable,
+    solana_program::{
+        instruction::InstructionError,
+        native_token::LAMPORTS_PER_SOL,
+        pubkey::Pubkey,
+    },
+    solana_sdk::{
+        signature::Keypair,
+        signer::Signer,
+    },
+};
+
+fn quote(price: i64) -> Quote {
+    Quote {
+        price,
+        confidence: 1,
+        status: PC_STATUS_TRADING,
+    }
+}
+
+fn publisher_index(price_data: &PriceAccount, publisher: &Pubkey) -> Option<usize> {
+    price_data.comp_[..price_data.num_ as usize]
+        .iter()
+        .position(|component| component.pub_ == *publisher)
+}
+
+#[test]
+fn test_rotate_publisher_authority() {
+    let mut permissions = PermissionAccount::zeroed();
+    permissions.master_authority = Pubkey::new_unique();
+    permissions.data_curation_authority = Pubkey::new_unique();
+    permissions.security_authority = Pubkey::new_unique();
+
+    assert!(permissions.is_authorized(
+        &permissions.master_authority,
+        OracleCommand::RotatePublisher
+    ));
+    assert!(permissions.is_authorized(
+        &permissions.data_curation_authority,
+        OracleCommand::RotatePublisher
+    ));
+    assert!(!permissions.is_authorized(
+        &permissions.security_authority,
+        OracleCommand::RotatePublisher
+    ));
+    assert!(!permissions.is_authorized(
+        &permissions.data_curation_authority,
+        OracleCommand::AddPublisher
+    ));
+}
+
+#[tokio::test]
+async fn test_rotate_publisher() {
+    let mut sim = PythSimulator::new().await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(2).await;
+    let price = price_keypair.pubkey();
+    sim.set_min_pub(price, 2).await.unwrap();
+
+    sim.upd_price(&publishers[0], price, quote(100))
+        .await
+        .unwrap();
+    sim.upd_price(&publishers[1], price, quote(110))
+        .await
+        .unwrap();
+    sim.warp_to_slot(2).await.unwrap();
+    sim.upd_price(&publishers[1], price, quote(110))
+        .await
+        .unwrap();
+
+    let before = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(before.agg_.status_, PC_STATUS_TRADING);
+    assert_eq!(before.agg_.price_, 105);
+    let old_index = publisher_index(&before, &publishers[0].pubkey()).unwrap();
+
+    let new_publisher = Keypair::new();
+    sim.airdrop(&new_publisher.pubkey(), 100 * LAMPORTS_PER_SOL)
+        .await
+        .unwrap();
+    sim.rotate_publisher(price, publishers[0].pubkey(), new_publisher.pubkey())
+        .await
+        .unwrap();
+
+    // The component and the statistics of the publisher are kept under its new key
+    let after = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(after.num_, 2);
+    assert_eq!(publisher_index(&after, &publishers[0].pubkey()), None);
+    let new_index = publisher_index(&after, &new_publisher.pubkey()).unwrap();
+    assert_eq!(
+        after.comp_[new_index].latest_,
+        before.comp_[old_index].latest_
+    );
+    assert_eq!(after.comp_[new_index].agg_, before.comp_[old_index].agg_);
+    assert_eq!(
+        after.publisher_stats_[new_index],
+        before.publisher_stats_[old_index]
+    );
+    assert_eq!(after.agg_, before.agg_);
+    assert!(after.comp_[0].pub_ < after.comp_[1].pub_);
+
+    // The latest quote of the rotated publisher still counts towards the next aggregate
+    sim.warp_to_slot(3).await.unwrap();
+    sim.upd_price(&publishers[1], price, quote(110))
+        .await
+        .unwrap();
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(price_data.agg_.status_, PC_STATUS_TRADING);
+    assert_eq!(price_data.agg_.price_, 105);
+    assert_eq!(price_data.num_qt_, 2);
+
+    // Only the new key can publish
+    sim.upd_price(&publishers[0], price, quote(120))
+        .await
+        .assert_fails_with(OracleError::PermissionViolation);
+    sim.upd_price(&new_publisher, price, quote(120))
+        .await
+        .unwrap();
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(price_data.comp_[new_index].latest_.price_, 120);
+
+    // The old key must be a publisher and the new key must not be one already
+    sim.rotate_publisher(price, publishers[0].pubkey(), Pubkey::new_unique())
+        .await
+        .assert_fails_with(InstructionError::InvalidArgument);
+    sim.rotate_publisher(price, new_publisher.pubkey(), publishers[1].pubkey())
+        .await
+        .assert_fails_with(InstructionError::InvalidArgument);
+    sim.rotate_publisher(price, new_publisher.pubkey(), Pubkey::default())
+        .await
+        .assert_fails_with(InstructionError::InvalidArgument);
+}
@@ -0,0 +1,299 @@
+use {
+    crate::{
+        accounts::{
+            MappingAccount,
+            PriceAccount,
+            PythAccount,
+            TimeMachine,
+        },
+        c_oracle_header::{
+            PC_STATUS_HALTED,
+            PC_STATUS_TRADING,
+            PC_STATUS_UNKNOWN,
+            PC_VERSION,
+        },
+        deserialize::load_checked,
+        error::OracleError,
+        sdk::{
+            LoadPythAccount,
+            Price,
+            PriceCheckError,
+            Twap,
+        },
+        tests::test_utils::AccountSetup,
+    },
+    bytemuck::Zeroable,
+    solana_program::{
+        clock::Clock,
+        pubkey::Pubkey,
+    },
+};
+
+#[test]
+fn test_sdk_load() {
+    let program_id = Pubkey::new_unique();
+
+    let mut price_setup = AccountSetup::new::<PriceAccount>(&program_id);
+    let price_account = price_setup.as_account_info();
+
+    let mut mapping_setup = AccountSetup::new::<MappingAccount>(&program_id);
+    let mapping_account = mapping_setup.as_account_info();
+
+    // Uninitialized accounts are rejected
+    assert_eq!(
+        PriceAccount::load(&price_account).err(),
+        Some(OracleError::InvalidAccountHeader.into())
+    );
+
+    PriceAccount::initialize(&price_account, PC_VERSION).unwrap();
+    MappingAccount::initialize(&mapping_account, PC_VERSION).unwrap();
+
+    assert!(PriceAccount::load(&price_account).is_ok());
+    assert!(MappingAccount::load(&mapping_account).is_ok());
+
+    // Accounts of the wrong type are rejected
+    assert_eq!(
+        PriceAccount::load(&mapping_account).err(),
+        Some(OracleError::InvalidAccountHeader.into())
+    );
+    assert_eq!(
+        MappingAccount::load(&price_account).err(),
+        Some(OracleError::AccountTooSmall.into())
+    );
+}
+
+#[test]
+fn test_sdk_get_price_no_older_than() {
+    let program_id = Pubkey::new_unique();
+
+    let mut price_setup = AccountSetup::new::<PriceAccount>(&program_id);
+    let price_account = price_setup.as_account_info();
+    PriceAccount::initialize(&price_account, PC_VERSION).unwrap();
+
+    {
+        let mut price_data = load_checked::<PriceAccount>(&price_account, PC_VERSION).unwrap();
+        price_data.exponent = -5;
+        price_data.agg_.price_ = 100;
+        price_data.agg_.conf_ = 10;
+        price_data.agg_.status_ = PC_STATUS_TRADING;
+        price_data.timestamp_ = 1000;
+        price_data.prev_price_ = 90;
+        price_data.prev_conf_ = 9;
+        price_data.prev_timestamp_ = 990;
+    }
+
+    let clock = Clock {
+        unix_timestamp: 1010,
+        ..Clock::default()
+    };
+
+    {
+        let price_data = PriceAccount::load(&price_account).unwrap();
+        assert_eq!(
+            price_data.get_price_no_older_than(&clock, 10),
+            Some(Price {
+                price:        100,
+                conf:         10,
+                expo:         -5,
+                publish_time: 1000,
+            })
+        );
+        assert_eq!(price_data.get_price_no_older_than(&clock, 9), None);
+    }
+
+    // If the last aggregation failed, fall back to the previous successful one
+    {
+        let mut price_data = load_checked::<PriceAccount>(&price_account, PC_VERSION).unwrap();
+        price_data.agg_.status_ = PC_STATUS_UNKNOWN;
+    }
+
+    {
+        let price_data = PriceAccount::load(&price_account).unwrap();
+        assert_eq!(
+            price_data.get_price_no_older_than(&clock, 20),
+            Some(Price {
+                price:        90,
+                conf:         9,
+                expo:         -5,
+                publish_time: 990,
+            })
+        );
+        assert_eq!(price_data.get_price_no_older_than(&clock, 19), None);
+        assert_eq!(
+            price_data.get_price_unchecked(),
+            Price {
+                price:        90,
+                conf:         9,
+                expo:         -5,
+                publish_time: 990,
+            }
+        );
+    }
+}
+
+#[test]
+fn test_sdk_get_price_checked() {
+    let program_id = Pubkey::new_unique();
+
+    let mut price_setup = AccountSetup::new::<PriceAccount>(&program_id);
+    let price_account = price_setup.as_account_info();
+    PriceAccount::initialize(&price_account, PC_VERSION).unwrap();
+
+    {
+        let mut price_data = load_checked::<PriceAccount>(&price_account, PC_VERSION).unwrap();
+        price_data.exponent = -5;
+        price_data.agg_.price_ = -2_000;
+        price_data.agg_.conf_ = 10;
+        price_data.agg_.status_ = PC_STATUS_TRADING;
+        price_data.agg_.pub_slot_ = 100;
+        price_data.timestamp_ = 1000;
+        price_data.prev_price_ = 1_900;
+        price_data.prev_conf_ = 1;
+    }
+
+    let clock = Clock {
+        slot: 105,
+        ..Clock::default()
+    };
+
+    {
+        let price_data = PriceAccount::load(&price_account).unwrap();
+        // The confidence is 50 basis points of the price
+        assert_eq!(
+            price_data.get_price_checked(50, 5, &clock),
+            Ok(Price {
+                price:        -2_000,
+                conf:         10,
+                expo:         -5,
+                publish_time: 1000,
+            })
+        );
+        assert_eq!(
+            price_data.get_price_checked(49, 5, &clock),
+            Err(PriceCheckError::ConfidenceTooWide { conf_bps: 50 })
+        );
+        assert_eq!(
+            price_data.get_price_checked(50, 4, &clock),
+            Err(PriceCheckError::TooStale { age_slots: 5 })
+        );
+    }
+
+    // A zero price has an infinitely wide confidence
+    {
+        let mut price_data = load_checked::<PriceAccount>(&price_account, PC_VERSION).unwrap();
+        price_data.agg_.price_ = 0;
+    }
+    assert_eq!(
+        PriceAccount::load(&price_account)
+            .unwrap()
+            .get_price_checked(10_000, 5, &clock),
+        Err(PriceCheckError::ConfidenceTooWide { conf_bps: u64::MAX })
+    );
+
+    // Prices that aren't trading are rejected, the previous price isn't used
+    for status in [PC_STATUS_UNKNOWN, PC_STATUS_HALTED] {
+        {
+            let mut price_data = load_checked::<PriceAccount>(&price_account, PC_VERSION).unwrap();
+            price_data.agg_.status_ = status;
+        }
+        assert_eq!(
+            PriceAccount::load(&price_account)
+                .unwrap()
+                .get_price_checked(u64::MAX, u64::MAX, &clock),
+            Err(PriceCheckError::NotTrading)
+        );
+    }
+}
+
+#[test]
+fn test_sdk_iter_products() {
+    let program_id = Pubkey::new_unique();
+
+    let mut mapping_setup = AccountSetup::new::<MappingAccount>(&program_id);
+    let mapping_account = mapping_setup.as_account_info();
+    MappingAccount::initialize(&mapping_account, PC_VERSION).unwrap();
+
+    let products = [Pubkey::new_unique(), Pubkey::new_unique()];
+
+    {
+        let mapping_data = MappingAccount::load(&mapping_account).unwrap();
+        assert_eq!(mapping_data.iter_products().count(), 0);
+    }
+
+    {
+        let mut mapping_data =
+            load_checked::<MappingAccount>(&mapping_account, PC_VERSION).unwrap();
+        mapping_data.products_list[..products.len()].copy_from_slice(&products);
+        mapping_data.number_of_products = products.len() as u32;
+    }
+
+    let mapping_data = MappingAccount::load(&mapping_account).unwrap();
+    assert_eq!(
+        mapping_data
+            .iter_products()
+            .copied()
+            .collect::<Vec<Pubkey>>(),
+        products.to_vec()
+    );
+}
+
+#[test]
+fn test_sdk_time_machine_twap() {
+    // A multiple of `TIME_MACHINE_BUCKET_SECONDS`
+    const T0: i64 = 1_700_000_040;
+
+    let mut time_machine = TimeMachine::zeroed();
+    time_machine.record(T0, 100, 10, 2).unwrap();
+    time_machine.record(T0 + 30, 130, 4, 1).unwrap();
+    // No aggregate from T0 + 60 to T0 + 120
+    time_machine.record(T0 + 120, 200, 8, 3).unwrap();
+    time_machine.record(T0 + 180, -7, 0, 2).unwrap();
+
+    // A whole bucket gives its average
+    assert_eq!(
+        time_machine.twap(T0, T0 + 60),
+        Some(Twap {
+            price:           110,
+            conf:            8,
+            covered_seconds: 60,
+        })
+    );
+    assert_eq!(
+        time_machine.twap(T0, T0 + 180),
+        Some(Twap {
+            price:           155,
+            conf:            8,
+            covered_seconds: 120,
+        })
+    );
+
+    // Partial buckets count for their seconds in the period,
+    // (330 * 15 + 600 * 20) / (3 * 15 + 3 * 20) = 161.43
+    assert_eq!(
+        time_machine.twap(T0 + 45, T0 + 140),
+        Some(Twap {
+            price:           161,
+            conf:            8,
+            covered_seconds: 35,
+        })
+    );
+    // Rounded toward zero, (600 * 10 - 14 * 10) / (3 * 10 + 2 * 10) = 117.2
+    assert_eq!(
+        time_machine.twap(T0 + 170, T0 + 190),
+        Some(Twap {
+            price:           117,
+            conf:            4,
+            covered_seconds: 20,
+        })
+    );
+
+    // No aggregate in the period
+    assert_eq!(time_machine.twap(T0 + 60, T0 + 120), None);
+    assert_eq!(time_machine.twap(T0 + 240, T0 + 300), None);
+    assert_eq!(time_machine.twap(T0 + 10, T0 + 10), None);
+    assert_eq!(time_machine.twap(T0 + 60, T0), None);
+
+    let mut time_machine = TimeMachine::zeroed();
+    time_machine.record(T0, i64::MAX, 0, u64::MAX).unwrap();
+    assert_eq!(time_machine.twap(T0, T0 + 60), None);
+}
@@ -0,0 +1,252 @@
+use {
+    crate::{
+        accounts::PriceAccount,
+        c_oracle_header::{
+            MAX_EXPONENT,
+            MAX_NUM_DECIMALS,
+            PC_STATUS_TRADING,
+        },
+        error::OracleError,
+        tests::pyth_simulator::{
+            PythSimulator,
+            Quote,
+            TransactionFailure,
+        },
+    },
+    bytemuck::{
+        bytes_of,
+        Zeroable,
+    },
+    quickcheck_macros::quickcheck,
+    solana_program::{
+        instruction::InstructionError,
+        native_token::LAMPORTS_PER_SOL,
+    },
+    solana_sdk::{
+        signature::Keypair,
+        signer::Signer,
+    },
+};
+
+/// A price account at `exponent` with the same `price` and `conf` in every price field
+fn price_account_with(exponent: i32, price: i64, conf: u64) -> PriceAccount {
+    let mut price_account = PriceAccount::zeroed();
+    price_account.exponent = exponent;
+    price_account.agg_.price_ = price;
+    price_account.agg_.conf_ = conf;
+    price_account.prev_price_ = price;
+    price_account.prev_conf_ = conf;
+    price_account.fast_agg_.price_ = price;
+    price_account.fast_agg_.conf_ = conf;
+    price_account.min_price_ = price;
+    price_account.max_price_ = price;
+    for component in price_account.comp_.iter_mut() {
+        component.agg_.price_ = price;
+        component.agg_.conf_ = conf;
+        component.latest_.price_ = price;
+        component.latest_.conf_ = conf;
+    }
+    price_account.twap_.val_ = price;
+    price_account.twap_.numer_ = 7;
+    price_account.twap_.denom_ = 11;
+    price_account.twac_.val_ = conf as i64;
+    price_account.price_cumulative.price = i128::from(price) * 10;
+    price_account.price_cumulative.conf = u128::from(conf) * 10;
+    price_account
+}
+
+/// Check that every price field of `price_account` is `price` and every confidence field `conf`
+fn assert_values(price_account: &PriceAccount, price: i64, conf: u64) {
+    assert_eq!(price_account.agg_.price_, price);
+    assert_eq!(price_account.agg_.conf_, conf);
+    assert_eq!(price_account.prev_price_, price);
+    assert_eq!(price_account.prev_conf_, conf);
+    assert_eq!(price_account.fast_agg_.price_, price);
+    assert_eq!(price_account.fast_agg_.conf_, conf);
+    assert_eq!(price_account.min_price_, price);
+    assert_eq!(price_account.max_price_, price);
+    for component in price_account.comp_.iter() {
+        assert_eq!(component.agg_.price_, price);
+        assert_eq!(component.agg_.conf_, conf);
+        assert_eq!(component.latest_.price_, price);
+        assert_eq!(component.latest_.conf_, conf);
+    }
+    assert_eq!(price_account.twap_.val_, price);
+    assert_eq!(price_account.twac_.val_, conf as i64);
+    // The EMA numerators and denominators don't depend on the exponent
+    assert_eq!(price_account.twap_.numer_, 7);
+    assert_eq!(price_account.twap_.denom_, 11);
+}
+
+#[test]
+fn test_set_exponent() {
+    // Fewer decimals: prices are rounded toward zero and confidences up
+    let mut price_account = price_account_with(-8, -12_345_678, 1_001);
+    price_account.set_exponent(-5).unwrap();
+    assert_eq!(price_account.exponent, -5);
+    assert_values(&price_account, -12_345, 2);
+    assert_eq!(price_account.price_cumulative.price, -123_456);
+    assert_eq!(price_account.price_cumulative.conf, 11);
+
+    // More decimals are exact
+    price_account.set_exponent(-8).unwrap();
+    assert_eq!(price_account.exponent, -8);
+    assert_values(&price_account, -12_345_000, 2_000);
+
+    // From the smallest to the largest exponent
+    let mut price_account = price_account_with(-MAX_NUM_DECIMALS, i64::MAX, i64::MAX as u64);
+    price_account.set_exponent(MAX_EXPONENT).unwrap();
+    assert_values(&price_account, 0, 1);
+    assert_eq!(price_account.price_cumulative.price, 0);
+    assert_eq!(price_account.price_cumulative.conf, 1);
+
+    // and back, which overflows for any non-zero value
+    let mut price_account = price_account_with(MAX_EXPONENT, 0, 0);
+    price_account.set_exponent(-MAX_NUM_DECIMALS).unwrap();
+    assert_values(&price_account, 0, 0);
+
+    let mut price_account = price_account_with(MAX_EXPONENT, 1, 0);
+    assert_eq!(
+        price_account.set_exponent(-MAX_NUM_DECIMALS),
+        Err(OracleError::IntegerCastingError)
+    );
+
+    // A value that doesn't fit leaves the account unchanged, even if the others were rescaled
+    let mut price_account = price_account_with(MAX_EXPONENT, 0, 0);
+    price_account.agg_.price_ = 1;
+    price_account.publisher_stats_[1].last_trade_.price_ = i64::MAX;
+    let before = price_account;
+    assert_eq!(
+        price_account.set_exponent(MAX_EXPONENT - 1),
+        Err(OracleError::IntegerCastingError)
+    );
+    assert_eq!(bytes_of(&price_account), bytes_of(&before));
+
+    // The largest shift that fits in an i64
+    let mut price_account = price_account_with(MAX_EXPONENT, -9, 9);
+    price_account.set_exponent(MAX_EXPONENT - 18).unwrap();
+    assert_values(
+        &price_account,
+        -9_000_000_000_000_000_000,
+        9_000_000_000_000_000_000,
+    );
+
+    let mut price_account = price_account_with(MAX_EXPONENT, -9, 9);
+    assert_eq!(
+        price_account.set_exponent(MAX_EXPONENT - 19),
+        Err(OracleError::IntegerCastingError)
+    );
+}
+
+/// Adding decimals and removing them again gives back the original values.
+#[quickcheck]
+fn test_set_exponent_roundtrip(price: i64, conf: u64, from: i32, shift: u8) -> bool {
+    let from = from.rem_euclid(MAX_NUM_DECIMALS + MAX_EXPONENT + 1) - MAX_NUM_DECIMALS;
+    let to = (from - i32::from(shift % 31)).max(-MAX_NUM_DECIMALS);
+    let conf = conf / 2;
+
+    let mut price_account = price_account_with(from, price, conf);
+    match price_account.set_exponent(to) {
+        Ok(()) => {
+            price_account.set_exponent(from).unwrap();
+            assert_values(&price_account, price, conf);
+            price_account.price_cumulative.price == i128::from(price) * 10
+                && price_account.price_cumulative.conf == u128::from(conf) * 10
+        }
+        // Only overflows when rescaling a non-zero value by a large enough factor
+        Err(error) => {
+            error == OracleError::IntegerCastingError
+                && (price != 0 || conf != 0)
+                && (u128::from(price.unsigned_abs()).max(u128::from(conf)))
+                    .checked_mul(10u128.pow((from - to) as u32))
+                    .map_or(true, |value| value > i64::MAX as u128)
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_set_exponent_instruction() {
+    let mut sim = PythSimulator::new().await;
+    let mapping_keypair = sim.init_mapping().await.unwrap();
+    let product_keypair = sim.add_product(&mapping_keypair).await.unwrap();
+
+    // Exponent range of add_price
+    for exponent in [-MAX_NUM_DECIMALS, MAX_EXPONENT] {
+        sim.add_price(&product_keypair, exponent).await.unwrap();
+    }
+    for exponent in [-MAX_NUM_DECIMALS - 1, MAX_EXPONENT + 1] {
+        sim.add_price(&product_keypair, exponent)
+            .await
+            .assert_fails_with(InstructionError::InvalidArgument);
+    }
+
+    let publisher = Keypair::new();
+    sim.airdrop(&publisher.pubkey(), 100 * LAMPORTS_PER_SOL)
+        .await
+        .unwrap();
+    let price_keypair = sim.add_price(&product_keypair, -5).await.unwrap();
+    let price = price_keypair.pubkey();
+    sim.add_publisher(price, publisher.pubkey()).await.unwrap();
+    sim.set_min_pub(price, 1).await.unwrap();
+
+    let quote = |price: i64| Quote {
+        price,
+        confidence: 5,
+        status: PC_STATUS_TRADING,
+    };
+    sim.upd_price(&publisher, price, quote(150)).await.unwrap();
+    sim.warp_to_slot(2).await.unwrap();
+    sim.upd_price(&publisher, price, quote(150)).await.unwrap();
+
+    let before = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(before.agg_.status_, PC_STATUS_TRADING);
+
+    sim.set_exponent(price, -8).await.unwrap();
+    let after = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(after.exponent, -8);
+    assert_eq!(after.agg_.price_, before.agg_.price_ * 1000);
+    assert_eq!(after.agg_.conf_, before.agg_.conf_ * 1000);
+    assert_eq!(after.twap_.val_, before.twap_.val_ * 1000);
+    assert_eq!(after.twac_.val_, before.twac_.val_ * 1000);
+    assert_eq!(
+        after.comp_[0].latest_.price_,
+        before.comp_[0].latest_.price_ * 1000
+    );
+    assert_eq!(
+        after.price_cumulative.price,
+        before.price_cumulative.price * 1000
+    );
+
+    // Publishers keep publishing at the new exponent
+    sim.warp_to_slot(3).await.unwrap();
+    sim.upd_price(&publisher, price, quote(150_000))
+        .await
+        .unwrap();
+    sim.warp_to_slot(4).await.unwrap();
+    sim.upd_price(&publisher, price, quote(150_000))
+        .await
+        .unwrap();
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(price_data.agg_.status_, PC_STATUS_TRADING);
+    assert_eq!(price_data.agg_.price_, 150_000);
+
+    sim.set_exponent(price, -MAX_NUM_DECIMALS - 1)
+        .await
+        .assert_fails_with(InstructionError::InvalidArgument);
+    sim.set_exponent(price, -MAX_NUM_DECIMALS).await.unwrap();
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(price_data.exponent, -MAX_NUM_DECIMALS);
+    assert_eq!(price_data.agg_.price_, 150_000 * 10_000_000_000);
+}
@@ -5,7 +5,11 @@ use {
             PriceAccount,
             PythAccount,
         },
-        c_oracle_header::PC_VERSION,
+        c_oracle_header::{
+            PC_STATUS_TRADING,
+            PC_STATUS_UNKNOWN,
+            PC_VERSION,
+        },
         deserialize::{
             load_checked,
             load_mut,
@@ -15,13 +19,23 @@ use {
             SetMinPubArgs,
         },
         processor::process_instruction,
-        tests::test_utils::AccountSetup,
+        tests::{
+            pyth_simulator::{
+                PythSimulator,
+                Quote,
+            },
+            test_utils::AccountSetup,
+        },
     },
     solana_program::{
         account_info::AccountInfo,
         program_error::ProgramError,
         pubkey::Pubkey,
     },
+    solana_sdk::{
+        signature::Keypair,
+        signer::Signer,
+    },
     std::mem::size_of,
 };
 
@@ -78,6 +92,90 @@ fn test_set_min_pub() {
     assert_eq!(get_min_pub(&price_account), Ok(2));
 }
 
+#[tokio::test]
+async fn test_min_pub_threshold() {
+    let mut sim = PythSimulator::new().await;
+    let publisher_1 = Keypair::new();
+    let publisher_2 = Keypair::new();
+    let publisher_3 = Keypair::new();
+    let security_authority = Keypair::new();
+    let price_accounts = sim
+        .setup_product_fixture(
+            &[
+                publisher_1.pubkey(),
+                publisher_2.pubkey(),
+                publisher_3.pubkey(),
+            ],
+            security_authority.pubkey(),
+        )
+        .await;
+    let price = price_accounts["LTC"];
+
+    sim.set_min_pub(price, 2).await.unwrap();
+
+    let quote = |value: i64| Quote {
+        price:      value,
+        confidence: 5,
+        status:     PC_STATUS_TRADING,
+    };
+
+    // Only one publisher contributed in the previous slot, which is below the threshold
+    sim.upd_price(&publisher_1, price, quote(100))
+        .await
+        .unwrap();
+    sim.warp_to_slot(2).await.unwrap();
+    sim.upd_price(&publisher_1, price, quote(101))
+        .await
+        .unwrap();
+
+    {
+        let price_data = sim
+            .get_account_data_as::<PriceAccount>(price)
+            .await
+            .unwrap();
+        assert_eq!(price_data.num_qt_, 1);
+        assert_eq!(price_data.agg_.status_, PC_STATUS_UNKNOWN);
+    }
+
+    // Two publishers contributed in the previous slot, which is exactly the threshold
+    sim.upd_price(&publisher_2, price, quote(103))
+        .await
+        .unwrap();
+    sim.warp_to_slot(3).await.unwrap();
+    sim.upd_price(&publisher_1, price, quote(102))
+        .await
+        .unwrap();
+
+    {
+        let price_data = sim
+            .get_account_data_as::<PriceAccount>(price)
+            .await
+            .unwrap();
+        assert_eq!(price_data.num_qt_, 2);
+        assert_eq!(price_data.agg_.status_, PC_STATUS_TRADING);
+        assert_eq!(price_data.agg_.price_, 102);
+    }
+
+    // Raising the threshold above the number of contributing publishers stops trading
+    sim.set_min_pub(price, 3).await.unwrap();
+    sim.upd_price(&publisher_2, price, quote(104))
+        .await
+        .unwrap();
+    sim.warp_to_slot(4).await.unwrap();
+    sim.upd_price(&publisher_1, price, quote(105))
+        .await
+        .unwrap();
+
+    {
+        let price_data = sim
+            .get_account_data_as::<PriceAccount>(price)
+            .await
+            .unwrap();
+        assert_eq!(price_data.num_qt_, 2);
+        assert_eq!(price_data.agg_.status_, PC_STATUS_UNKNOWN);
+    }
+}
+
 // Create an upd_product instruction that sets the product metadata to strings
 fn populate_instruction(instruction_data: &mut [u8], min_pub: u8) {
     let mut hdr = load_mut::<SetMinPubArgs>(instruction_data).unwrap();
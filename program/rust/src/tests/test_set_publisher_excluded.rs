@@ -0,0 +1,99 @@
+use {
+    crate::{
+        accounts::PriceAccount,
+        c_oracle_header::PC_STATUS_TRADING,
+        tests::pyth_simulator::{
+            PythSimulator,
+            Quote,
+            TransactionFailure,
+        },
+    },
+    solana_program::{
+        instruction::InstructionError,
+        pubkey::Pubkey,
+    },
+    solana_sdk::signer::Signer,
+};
+
+fn quote(price: i64) -> Quote {
+    Quote {
+        price,
+        confidence: 1,
+        status: PC_STATUS_TRADING,
+    }
+}
+
+#[tokio::test]
+async fn test_set_publisher_excluded() {
+    let mut sim = PythSimulator::new().await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(3).await;
+    let price = price_keypair.pubkey();
+    sim.set_min_pub(price, 1).await.unwrap();
+    let excluded_publisher = publishers[2].pubkey();
+    sim.set_publisher_excluded(price, excluded_publisher, true)
+        .await
+        .unwrap();
+
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(
+        price_data
+            .publisher_stats(&excluded_publisher)
+            .unwrap()
+            .excluded_,
+        1
+    );
+
+    // The quote of the excluded publisher is stored but not aggregated
+    sim.publish_all(price, &publishers, &[quote(100), quote(110), quote(1000)])
+        .await
+        .unwrap();
+    sim.warp_to_slot(2).await.unwrap();
+    sim.upd_price(&publishers[0], price, quote(100))
+        .await
+        .unwrap();
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    let excluded_index = price_data.comp_[..3]
+        .iter()
+        .position(|component| component.pub_ == excluded_publisher)
+        .unwrap();
+    assert_eq!(price_data.comp_[excluded_index].latest_.price_, 1000);
+    assert_eq!(price_data.agg_.status_, PC_STATUS_TRADING);
+    assert_eq!(price_data.agg_.price_, 105);
+    assert_eq!(price_data.num_qt_, 2);
+    assert_eq!(
+        price_data
+            .publisher_stats(&excluded_publisher)
+            .unwrap()
+            .num_included_rounds(),
+        0
+    );
+
+    // Including the publisher again counts its latest quote
+    sim.set_publisher_excluded(price, excluded_publisher, false)
+        .await
+        .unwrap();
+    sim.upd_price(&publishers[2], price, quote(1000))
+        .await
+        .unwrap();
+    sim.warp_to_slot(3).await.unwrap();
+    sim.upd_price(&publishers[0], price, quote(100))
+        .await
+        .unwrap();
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(price_data.num_qt_, 3);
+    assert_eq!(price_data.agg_.price_, 110);
+
+    // Only publishers of the price account can be excluded
+    sim.set_publisher_excluded(price, Pubkey::new_unique(), true)
+        .await
+        .assert_fails_with(InstructionError::InvalidArgument);
+}
@@ -0,0 +1,256 @@
+use {
+    crate::{
+        accounts::{
+            AggInterval,
+            PermissionAccount,
+            PriceAccount,
+            PublisherWeights,
+            PythAccount,
+        },
+        c_oracle_header::{
+            PC_AGG_MODE_DEFAULT,
+            PC_AGG_MODE_MID,
+            PC_AGG_MODE_WEIGHTED,
+            PC_STATUS_TRADING,
+            PC_VERSION,
+        },
+        deserialize::{
+            load_checked,
+            load_mut,
+        },
+        error::OracleError,
+        instruction::{
+            OracleCommand,
+            SetAggModeArgs,
+        },
+        processor::process_instruction,
+        tests::{
+            pyth_simulator::{
+                PythSimulator,
+                Quote,
+                TransactionFailure,
+            },
+            test_utils::AccountSetup,
+        },
+    },
+    pyth_oracle_core::price::PriceAccountView,
+    solana_program::{
+        instruction::InstructionError,
+        native_token::LAMPORTS_PER_SOL,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        rent::Rent,
+    },
+    solana_sdk::{
+        signature::Keypair,
+        signer::Signer,
+    },
+    std::mem::size_of,
+};
+
+fn quote(price: i64) -> Quote {
+    Quote {
+        price,
+        confidence: 1,
+        status: PC_STATUS_TRADING,
+    }
+}
+
+#[tokio::test]
+async fn test_set_publisher_weight() {
+    let mut sim = PythSimulator::new().await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(3).await;
+    let price = price_keypair.pubkey();
+    sim.set_min_pub(price, 1).await.unwrap();
+
+    // Weights of 0 don't need any room in the price account
+    let initial_size = sim.get_account(price).await.unwrap().data.len();
+    sim.set_publisher_weight(price, publishers[0].pubkey(), 0)
+        .await
+        .unwrap();
+    assert_eq!(
+        sim.get_account(price).await.unwrap().data.len(),
+        initial_size
+    );
+
+    // Unknown publishers are rejected
+    sim.set_publisher_weight(price, Pubkey::new_unique(), 3)
+        .await
+        .assert_fails_with(InstructionError::InvalidArgument);
+
+    // The first weight resizes the price account
+    sim.set_publisher_weight(price, publishers[2].pubkey(), 10)
+        .await
+        .unwrap();
+    let price_account = sim.get_account(price).await.unwrap();
+    let size = initial_size + PriceAccount::extension_size::<PublisherWeights>();
+    assert_eq!(price_account.data.len(), size);
+    assert!(price_account.lamports >= Rent::default().minimum_balance(size));
+    let view = PriceAccountView::new(&price_account.data).unwrap();
+    assert_eq!(
+        view.publisher_weight(&publishers[2].pubkey().to_bytes()),
+        10
+    );
+    assert_eq!(view.publisher_weight(&publishers[0].pubkey().to_bytes()), 0);
+    sim.set_agg_mode(price, PC_AGG_MODE_WEIGHTED as u8)
+        .await
+        .unwrap();
+
+    // The price updates don't overwrite the weights, and the heavy publisher dominates the
+    // weighted median
+    sim.warp_to_slot(2).await.unwrap();
+    sim.publish_all(price, &publishers, &[quote(100), quote(200), quote(300)])
+        .await
+        .unwrap();
+    sim.warp_to_slot(3).await.unwrap();
+    sim.upd_price(&publishers[0], price, quote(100))
+        .await
+        .unwrap();
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(price_data.agg_.pub_slot_, 3);
+    assert_eq!(price_data.agg_.price_, 300);
+
+    // A rotated publisher keeps its weight
+    let rotated_publisher = Keypair::new();
+    sim.airdrop(&rotated_publisher.pubkey(), LAMPORTS_PER_SOL)
+        .await
+        .unwrap();
+    sim.rotate_publisher(price, publishers[2].pubkey(), rotated_publisher.pubkey())
+        .await
+        .unwrap();
+    sim.upd_price(&rotated_publisher, price, quote(300))
+        .await
+        .unwrap();
+    sim.warp_to_slot(4).await.unwrap();
+    sim.upd_price(&publishers[0], price, quote(100))
+        .await
+        .unwrap();
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(price_data.agg_.pub_slot_, 4);
+    assert_eq!(price_data.agg_.price_, 300);
+
+    // Setting the weight back to 0 counts the publisher once again
+    sim.set_publisher_weight(price, rotated_publisher.pubkey(), 0)
+        .await
+        .unwrap();
+    sim.warp_to_slot(5).await.unwrap();
+    sim.upd_price(&publishers[0], price, quote(100))
+        .await
+        .unwrap();
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(price_data.agg_.pub_slot_, 5);
+    assert_eq!(price_data.agg_.price_, 200);
+}
+
+#[tokio::test]
+async fn test_set_publisher_weight_after_other_extensions() {
+    let mut sim = PythSimulator::new().await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(2).await;
+    let price = price_keypair.pubkey();
+    let initial_size = sim.get_account(price).await.unwrap().data.len();
+    sim.set_agg_interval(price, 3).await.unwrap();
+
+    // The weights are added after the aggregation interval, which doesn't move
+    sim.set_publisher_weight(price, publishers[1].pubkey(), 4)
+        .await
+        .unwrap();
+    let price_account = sim.get_account(price).await.unwrap();
+    assert_eq!(
+        price_account.data.len(),
+        initial_size
+            + PriceAccount::extension_size::<AggInterval>()
+            + PriceAccount::extension_size::<PublisherWeights>()
+    );
+    assert_eq!(
+        PriceAccount::agg_interval(&price_account.data[size_of::<PriceAccount>()..]),
+        3
+    );
+    let view = PriceAccountView::new(&price_account.data).unwrap();
+    assert_eq!(view.publisher_weight(&publishers[1].pubkey().to_bytes()), 4);
+}
+
+#[test]
+fn test_set_agg_mode() {
+    let mut mode_instruction_data = [0u8; size_of::<SetAggModeArgs>()];
+
+    let program_id = Pubkey::new_unique();
+
+    let mut funding_setup = AccountSetup::new_funding();
+    let funding_account = funding_setup.as_account_info();
+
+    let mut attacker_setup = AccountSetup::new_funding();
+    let attacker_account = attacker_setup.as_account_info();
+
+    let mut price_setup = AccountSetup::new::<PriceAccount>(&program_id);
+    let price_account = price_setup.as_account_info();
+    PriceAccount::initialize(&price_account, PC_VERSION).unwrap();
+
+    let mut permissions_setup = AccountSetup::new_permission(&program_id);
+    let permissions_account = permissions_setup.as_account_info();
+
+    {
+        let mut permissions_account_data =
+            PermissionAccount::initialize(&permissions_account, PC_VERSION).unwrap();
+        permissions_account_data.master_authority = *funding_account.key;
+        permissions_account_data.data_curation_authority = *funding_account.key;
+        permissions_account_data.security_authority = *funding_account.key;
+    }
+
+    {
+        let price_data = load_checked::<PriceAccount>(&price_account, PC_VERSION).unwrap();
+        assert_eq!(u32::from(price_data.agg_mode_), PC_AGG_MODE_DEFAULT);
+    }
+
+    populate_mode_instruction(&mut mode_instruction_data, PC_AGG_MODE_WEIGHTED as u8);
+    process_instruction(
+        &program_id,
+        &[
+            funding_account.clone(),
+            price_account.clone(),
+            permissions_account.clone(),
+        ],
+        &mode_instruction_data,
+    )
+    .unwrap();
+
+    {
+        let price_data = load_checked::<PriceAccount>(&price_account, PC_VERSION).unwrap();
+        assert_eq!(u32::from(price_data.agg_mode_), PC_AGG_MODE_WEIGHTED);
+    }
+
+    // Unknown modes are rejected
+    populate_mode_instruction(&mut mode_instruction_data, PC_AGG_MODE_MID as u8 + 1);
+    assert_eq!(
+        process_instruction(
+            &program_id,
+            &[
+                funding_account.clone(),
+                price_account.clone(),
+                permissions_account.clone(),
+            ],
+            &mode_instruction_data,
+        ),
+        Err(ProgramError::InvalidArgument)
+    );
+
+    populate_mode_instruction(&mut mode_instruction_data, PC_AGG_MODE_DEFAULT as u8);
+    assert_eq!(
+        process_instruction(
+            &program_id,
+            &[
+                attacker_account.clone(),
+                price_account.clone(),
+                permissions_account.clone(),
+            ],
+            &mode_instruction_data,
+        ),
+        Err(OracleError::PermissionViolation.into())
+    );
+}
+
+// Populate the instruction data with SetAggModeArgs
+fn populate_mode_instruction(instruction_data: &mut [u8], agg_mode: u8) {
+    let mut cmd = load_mut::<SetAggModeArgs>(instruction_data).unwrap();
+    cmd.header = OracleCommand::SetAggMode.into();
+    cmd.agg_mode = agg_mode;
+}
@@ -0,0 +1,199 @@
+use {
+    crate::{
+        accounts::{
+            PriceAccount,
+            ProductAccount,
+        },
+        c_oracle_header::PC_PROD_ACC_SIZE,
+        error::OracleError,
+        tests::pyth_simulator::{
+            PythSimulator,
+            TransactionFailure,
+        },
+    },
+    solana_program::{
+        account_info::AccountInfo,
+        clock::Epoch,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        rent::Rent,
+    },
+    solana_sdk::{
+        account::Account,
+        signer::Signer,
+    },
+    std::mem::size_of,
+};
+
+/// Call `f` with the account info of the simulated account `account`
+fn with_account_info<T>(
+    key: &Pubkey,
+    account: &mut Account,
+    f: impl FnOnce(&AccountInfo) -> T,
+) -> T {
+    let account_info = AccountInfo::new(
+        key,
+        false,
+        false,
+        &mut account.lamports,
+        &mut account.data,
+        &account.owner,
+        false,
+        Epoch::default(),
+    );
+    f(&account_info)
+}
+
+#[tokio::test]
+async fn test_set_reference_price() {
+    let mut sim = PythSimulator::new().await;
+    let mapping_keypair = sim.init_mapping().await.unwrap();
+    let product_keypair = sim.add_product(&mapping_keypair).await.unwrap();
+    let product = product_keypair.pubkey();
+    let spot_keypair = sim.add_price(&product_keypair, -5).await.unwrap();
+    let twap_keypair = sim.add_price(&product_keypair, -5).await.unwrap();
+    let (spot, twap) = (spot_keypair.pubkey(), twap_keypair.pubkey());
+    sim.upd_product(&product_keypair, &[("symbol", "Crypto.BTC/USD")])
+        .await
+        .unwrap();
+
+    let mut product_account = sim.get_account(product).await.unwrap();
+    assert_eq!(product_account.data.len(), PC_PROD_ACC_SIZE as usize);
+    with_account_info(&product, &mut product_account, |account_info| {
+        assert_eq!(
+            ProductAccount::load_reference_price(account_info).unwrap(),
+            None
+        );
+    });
+
+    // The product account is resized to hold the reference price
+    sim.set_reference_price(product, spot).await.unwrap();
+    let mut product_account = sim.get_account(product).await.unwrap();
+    assert_eq!(
+        product_account.data.len(),
+        ProductAccount::REFERENCE_PRICE_SIZE
+    );
+    assert!(
+        product_account.lamports
+            >= Rent::default().minimum_balance(ProductAccount::REFERENCE_PRICE_SIZE)
+    );
+    assert_eq!(
+        sim.get_product_attributes(product).await,
+        vec![("symbol".to_string(), "Crypto.BTC/USD".to_string())]
+    );
+    let mut spot_account = sim.get_account(spot).await.unwrap();
+    let mut twap_account = sim.get_account(twap).await.unwrap();
+    with_account_info(&product, &mut product_account, |product_info| {
+        assert_eq!(
+            ProductAccount::load_reference_price(product_info).unwrap(),
+            Some(spot)
+        );
+        with_account_info(&spot, &mut spot_account, |spot_info| {
+            let price_data =
+                ProductAccount::load_reference_price_account(product_info, spot_info).unwrap();
+            assert_eq!(price_data.product_account, product);
+        });
+        with_account_info(&twap, &mut twap_account, |twap_info| {
+            assert_eq!(
+                ProductAccount::load_reference_price_account(product_info, twap_info).err(),
+                Some(OracleError::InvalidReferencePrice.into())
+            );
+        });
+    });
+
+    // Changing the reference price doesn't resize the account again
+    sim.set_reference_price(product, twap).await.unwrap();
+    let product_account = sim.get_account(product).await.unwrap();
+    assert_eq!(
+        product_account.data.len(),
+        ProductAccount::REFERENCE_PRICE_SIZE
+    );
+    assert_eq!(
+        Pubkey::new(&product_account.data[ProductAccount::REFERENCE_PRICE_OFFSET..]),
+        twap
+    );
+
+    // The metadata can't overwrite the reference price
+    let capacity = PC_PROD_ACC_SIZE as usize - size_of::<ProductAccount>();
+    let long_value = "x".repeat(255);
+    let filler = "y".repeat(capacity - (1 + 11 + 1 + 255) - (1 + 5 + 1));
+    sim.upd_product(
+        &product_keypair,
+        &[
+            ("description", long_value.as_str()),
+            ("tenor", filler.as_str()),
+        ],
+    )
+    .await
+    .unwrap();
+    let overflow_filler = format!("{filler}y");
+    sim.upd_product(
+        &product_keypair,
+        &[
+            ("description", long_value.as_str()),
+            ("tenor", overflow_filler.as_str()),
+        ],
+    )
+    .await
+    .assert_fails_with(ProgramError::InvalidArgument);
+    let product_account = sim.get_account(product).await.unwrap();
+    assert_eq!(
+        Pubkey::new(&product_account.data[ProductAccount::REFERENCE_PRICE_OFFSET..]),
+        twap
+    );
+
+    // Deleting the reference price clears it
+    sim.del_price(&product_keypair, &twap_keypair)
+        .await
+        .unwrap();
+    let mut product_account = sim.get_account(product).await.unwrap();
+    with_account_info(&product, &mut product_account, |account_info| {
+        assert_eq!(
+            ProductAccount::load_reference_price(account_info).unwrap(),
+            None
+        );
+    });
+    sim.set_reference_price(product, spot).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_set_reference_price_of_other_product() {
+    let mut sim = PythSimulator::new().await;
+    let mapping_keypair = sim.init_mapping().await.unwrap();
+    let product_keypair = sim.add_product(&mapping_keypair).await.unwrap();
+    let other_product_keypair = sim.add_product(&mapping_keypair).await.unwrap();
+    let other_price_keypair = sim.add_price(&other_product_keypair, -5).await.unwrap();
+    let product = product_keypair.pubkey();
+
+    sim.set_reference_price(product, other_price_keypair.pubkey())
+        .await
+        .assert_fails_with(OracleError::InvalidReferencePrice);
+    // Only price accounts can be the reference price
+    sim.set_reference_price(product, other_product_keypair.pubkey())
+        .await
+        .assert_fails_with(OracleError::AccountTooSmall);
+    assert_eq!(
+        sim.get_account(product).await.unwrap().data.len(),
+        PC_PROD_ACC_SIZE as usize
+    );
+
+    // The SDK also checks that the reference price belongs to the product
+    let product = other_product_keypair.pubkey();
+    let price = other_price_keypair.pubkey();
+    sim.set_reference_price(product, price).await.unwrap();
+    let mut product_account = sim.get_account(product).await.unwrap();
+    let mut price_account = sim.get_account(price).await.unwrap();
+    let mut price_data = bytemuck::pod_read_unaligned::<PriceAccount>(&price_account.data);
+    price_data.product_account = Pubkey::new_unique();
+    price_account
+        .data
+        .copy_from_slice(bytemuck::bytes_of(&price_data));
+    with_account_info(&product, &mut product_account, |product_info| {
+        with_account_info(&price, &mut price_account, |price_info| {
+            assert_eq!(
+                ProductAccount::load_reference_price_account(product_info, price_info).err(),
+                Some(OracleError::InvalidReferencePrice.into())
+            );
+        });
+    });
+}
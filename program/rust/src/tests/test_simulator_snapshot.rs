@@ -0,0 +1,78 @@
+use {
+    crate::{
+        accounts::PriceAccount,
+        c_oracle_header::{
+            PC_STATUS_TRADING,
+            PC_STATUS_UNKNOWN,
+        },
+        tests::pyth_simulator::{
+            PythSimulator,
+            Quote,
+        },
+    },
+    solana_sdk::signer::Signer,
+};
+
+fn quote(price: i64) -> Quote {
+    Quote {
+        price,
+        confidence: 1,
+        status: PC_STATUS_TRADING,
+    }
+}
+
+#[tokio::test]
+async fn test_simulator_snapshot() {
+    let mut sim = PythSimulator::new().await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(3).await;
+    let price = price_keypair.pubkey();
+    sim.set_min_pub(price, 1).await.unwrap();
+
+    let snapshot = sim.snapshot().await;
+
+    // All the publishers publish
+    sim.publish_all(price, &publishers, &[quote(100), quote(110), quote(120)])
+        .await
+        .unwrap();
+    sim.warp_to_slot(2).await.unwrap();
+    sim.upd_price(&publishers[0], price, quote(100))
+        .await
+        .unwrap();
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(price_data.agg_.status_, PC_STATUS_TRADING);
+    assert_eq!(price_data.agg_.price_, 110);
+    assert_eq!(price_data.num_qt_, 3);
+
+    // From the same state, only two of them publish
+    sim.restore(&snapshot);
+    sim.publish_all(price, &publishers[..2], &[quote(100), quote(110)])
+        .await
+        .unwrap();
+    sim.warp_to_slot(3).await.unwrap();
+    sim.upd_price(&publishers[0], price, quote(100))
+        .await
+        .unwrap();
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(price_data.agg_.status_, PC_STATUS_TRADING);
+    assert_eq!(price_data.agg_.price_, 105);
+    assert_eq!(price_data.num_qt_, 2);
+    assert_eq!(price_data.comp_[2].latest_.pub_slot_, 0);
+
+    // Accounts created after the snapshot are deleted
+    let mapping_keypair = sim.init_mapping().await.unwrap();
+    sim.restore(&snapshot);
+    assert!(sim.get_account(mapping_keypair.pubkey()).await.is_none());
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(price_data.agg_.status_, PC_STATUS_UNKNOWN);
+    assert_eq!(price_data.num_qt_, 0);
+    assert_eq!(price_data.num_, 3);
+}
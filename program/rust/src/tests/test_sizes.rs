@@ -2,7 +2,11 @@ use {
     crate::{
         accounts::{
             AccountHeader,
+            ComponentLogAccount,
+            ComponentLogEntry,
+            DerivedPriceAccount,
             MappingAccount,
+            MessageBufferAccount,
             PermissionAccount,
             PriceAccount,
             PriceComponent,
@@ -10,11 +14,16 @@ use {
             PriceInfo,
             ProductAccount,
             PythAccount,
+            SymbolIndexAccount,
+            SymbolIndexEntry,
         },
         c_oracle_header::{
+            COMPONENT_LOG_CAPACITY,
+            MESSAGE_BUFFER_CAPACITY,
             PC_MAP_TABLE_SIZE,
             PC_NUM_COMP,
             PC_VERSION,
+            SYMBOL_INDEX_CAPACITY,
             ZSTD_UPPER_BOUND,
         },
         deserialize::{
@@ -24,13 +33,38 @@ use {
         instruction::{
             AddPriceArgs,
             AddPublisherArgs,
+            AddPublisherWithExpiryArgs,
             CommandHeader,
             DelPublisherArgs,
+            InitDerivedPriceArgs,
             InitPriceArgs,
+            PermissionGrantArgs,
+            RotatePublisherArgs,
+            SetAggIntervalArgs,
+            SetAggModeArgs,
+            SetAggRotationArgs,
+            SetCoSigningArgs,
+            SetCpiGuardArgs,
+            SetExponentArgs,
+            SetFeeArgs,
+            SetMarketHoursArgs,
+            SetMaxConfRatioArgs,
             SetMaxLatencyArgs,
+            SetMaxUpdatesPerSlotArgs,
             SetMinPubArgs,
+            SetOutlierBandArgs,
+            SetPriceBoundsArgs,
+            SetPublisherExcludedArgs,
+            SetPublisherPruningArgs,
+            SetPublisherWeightArgs,
             UpdPriceArgs,
+            UpdPriceBatchEntry,
+            UpdPriceWithBudgetArgs,
+            UpdPriceWithTimeArgs,
+            UpdPriceWithTradeArgs,
+            WithdrawFeeArgs,
         },
+        messages::PriceMessage,
         tests::test_utils::AccountSetup,
         utils::try_convert,
     },
@@ -55,25 +89,97 @@ fn test_sizes() {
 
     {
         use crate::{
-            accounts::PriceCumulative,
-            c_oracle_header::PC_NUM_COMP_PYTHNET,
+            accounts::{
+                AggInterval,
+                ExtensionHeader,
+                FastAggregate,
+                LastTrade,
+                PriceCumulative,
+                PruningState,
+                PublisherExpiries,
+                PublisherExpiry,
+                PublisherPruning,
+                PublisherStats,
+                PublisherWeight,
+                PublisherWeights,
+                TimeMachine,
+                TimeMachineBucket,
+            },
+            c_oracle_header::{
+                PC_NUM_COMP_SLOTS,
+                TIME_MACHINE_NUM_BUCKETS,
+            },
         };
 
-        // Sanity-check the Pythnet PC_NUM_COMP
-        assert_eq!(PC_NUM_COMP, 64);
+        // Sanity-check the PC_NUM_COMP of the chain
+        #[cfg(feature = "pythnet")]
+        assert_eq!((PC_NUM_COMP, PC_NUM_COMP_SLOTS), (64, 128));
+        #[cfg(feature = "solana")]
+        assert_eq!((PC_NUM_COMP, PC_NUM_COMP_SLOTS), (32, 64));
+        // The fields added after the publisher statistics and the unused space
+        let extension_size = if cfg!(feature = "solana") {
+            size_of::<[[u64; 4]; 7]>()
+        } else {
+            size_of::<[[u64; 4]; 15]>()
+        };
 
         assert_eq!(
             size_of::<PriceAccount>(),
             48 + u64::BITS as usize
                 + 3 * size_of::<Pubkey>()
                 + size_of::<PriceInfo>()
-                + (PC_NUM_COMP_PYTHNET as usize) * size_of::<PriceComponent>()
+                + (PC_NUM_COMP as usize) * size_of::<PriceComponent>()
+                + (PC_NUM_COMP as usize) * size_of::<PublisherStats>()
+                + size_of::<FastAggregate>()
+                + extension_size
                 + size_of::<PriceCumulative>()
         );
+        // The publisher statistics and the fast aggregate use the space of the unused price
+        // components
+        assert_eq!(
+            (PC_NUM_COMP as usize) * size_of::<PublisherStats>()
+                + size_of::<FastAggregate>()
+                + extension_size,
+            (PC_NUM_COMP_SLOTS - PC_NUM_COMP) as usize * size_of::<PriceComponent>()
+        );
+        #[cfg(feature = "pythnet")]
         assert_eq!(size_of::<PriceAccount>(), 12576);
-        assert!(size_of::<PriceAccount>() == try_convert::<_, usize>(ZSTD_UPPER_BOUND).unwrap());
+        #[cfg(feature = "solana")]
+        assert_eq!(size_of::<PriceAccount>(), 6432);
+        assert!(PriceAccount::MAX_SIZE == try_convert::<_, usize>(ZSTD_UPPER_BOUND).unwrap());
 
         assert_eq!(size_of::<PriceCumulative>(), 48);
+        assert_eq!(size_of::<FastAggregate>(), 32);
+        assert_eq!(size_of::<PublisherStats>(), 88);
+        assert_eq!(size_of::<LastTrade>(), 32);
+        assert_eq!(size_of::<PublisherExpiry>(), 40);
+        assert_eq!(size_of::<PruningState>(), 40);
+        assert_eq!(
+            size_of::<PublisherPruning>(),
+            8 + (PC_NUM_COMP as usize) * size_of::<PruningState>()
+        );
+        assert_eq!(size_of::<PublisherWeight>(), 40);
+        assert_eq!(
+            size_of::<PublisherWeights>(),
+            (PC_NUM_COMP as usize) * size_of::<PublisherWeight>()
+        );
+        assert_eq!(size_of::<TimeMachineBucket>(), 48);
+        assert_eq!(
+            size_of::<TimeMachine>(),
+            16 + TIME_MACHINE_NUM_BUCKETS * size_of::<TimeMachineBucket>()
+        );
+        assert_eq!(size_of::<ExtensionHeader>(), 8);
+        // The extensions have no padding
+        assert_eq!(
+            PriceAccount::MAX_SIZE,
+            size_of::<PriceAccount>()
+                + 5 * size_of::<ExtensionHeader>()
+                + size_of::<PublisherExpiries>()
+                + size_of::<PublisherPruning>()
+                + size_of::<AggInterval>()
+                + size_of::<PublisherWeights>()
+                + size_of::<TimeMachine>()
+        );
     }
 
     assert_eq!(size_of::<CommandHeader>(), 8);
@@ -83,7 +189,31 @@ fn test_sizes() {
     assert_eq!(size_of::<SetMaxLatencyArgs>(), 12);
     assert_eq!(size_of::<AddPublisherArgs>(), 40);
     assert_eq!(size_of::<DelPublisherArgs>(), 40);
+    assert_eq!(size_of::<AddPublisherWithExpiryArgs>(), 48);
     assert_eq!(size_of::<UpdPriceArgs>(), 40);
+    assert_eq!(size_of::<UpdPriceBatchEntry>(), 32);
+    assert_eq!(size_of::<SetPublisherWeightArgs>(), 44);
+    assert_eq!(size_of::<SetAggModeArgs>(), 12);
+    assert_eq!(size_of::<SetOutlierBandArgs>(), 12);
+    assert_eq!(size_of::<SetExponentArgs>(), 12);
+    assert_eq!(size_of::<SetMaxUpdatesPerSlotArgs>(), 12);
+    assert_eq!(size_of::<SetPriceBoundsArgs>(), 24);
+    assert_eq!(size_of::<SetMaxConfRatioArgs>(), 12);
+    assert_eq!(size_of::<SetPublisherPruningArgs>(), 12);
+    assert_eq!(size_of::<SetAggIntervalArgs>(), 16);
+    assert_eq!(size_of::<InitDerivedPriceArgs>(), 12);
+    assert_eq!(size_of::<UpdPriceWithTimeArgs>(), 48);
+    assert_eq!(size_of::<UpdPriceWithTradeArgs>(), 64);
+    assert_eq!(size_of::<UpdPriceWithBudgetArgs>(), 48);
+    assert_eq!(size_of::<SetCpiGuardArgs>(), 144);
+    assert_eq!(size_of::<SetMarketHoursArgs>(), 36);
+    assert_eq!(size_of::<RotatePublisherArgs>(), 72);
+    assert_eq!(size_of::<SetFeeArgs>(), 16);
+    assert_eq!(size_of::<WithdrawFeeArgs>(), 16);
+    assert_eq!(size_of::<PermissionGrantArgs>(), 48);
+    assert_eq!(size_of::<SetPublisherExcludedArgs>(), 44);
+    assert_eq!(size_of::<SetAggRotationArgs>(), 12);
+    assert_eq!(size_of::<SetCoSigningArgs>(), 12);
     assert_eq!(size_of::<Pubkey>(), 32);
     assert_eq!(size_of::<AccountHeader>(), 16);
     assert_eq!(size_of::<MappingAccount>(), 20536);
@@ -91,6 +221,24 @@ fn test_sizes() {
     assert_eq!(size_of::<PriceComponent>(), 96);
     assert_eq!(size_of::<PriceEma>(), 24);
     assert_eq!(size_of::<PermissionAccount>(), 112);
+    assert_eq!(PermissionAccount::NEW_SIZE, 116);
+    assert_eq!(PermissionAccount::CO_SIGNING_SIZE, 120);
+    assert_eq!(size_of::<PriceMessage>(), 80);
+    assert_eq!(
+        size_of::<MessageBufferAccount>(),
+        32 + MESSAGE_BUFFER_CAPACITY * size_of::<PriceMessage>()
+    );
+    assert_eq!(size_of::<SymbolIndexEntry>(), 64);
+    assert_eq!(
+        size_of::<SymbolIndexAccount>(),
+        24 + SYMBOL_INDEX_CAPACITY * size_of::<SymbolIndexEntry>()
+    );
+    assert_eq!(size_of::<DerivedPriceAccount>(), 136);
+    assert_eq!(size_of::<ComponentLogEntry>(), 32);
+    assert_eq!(
+        size_of::<ComponentLogAccount>(),
+        56 + COMPONENT_LOG_CAPACITY * size_of::<ComponentLogEntry>()
+    );
 }
 
 #[test]
@@ -0,0 +1,172 @@
+use {
+    crate::{
+        accounts::PriceAccount,
+        c_oracle_header::{
+            PC_MAX_SEND_LATENCY,
+            PC_STATUS_TRADING,
+            PC_STATUS_UNKNOWN,
+        },
+        tests::pyth_simulator::{
+            PythSimulator,
+            Quote,
+        },
+        utils::get_num_skipped_slots,
+    },
+    solana_program::{
+        account_info::AccountInfo,
+        hash::Hash,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        sysvar::{
+            self,
+            slot_hashes::SlotHashes,
+            SysvarId,
+        },
+    },
+    solana_sdk::{
+        signature::Keypair,
+        signer::Signer,
+    },
+};
+
+/// Number of skipped slots before `slot` when the slots with a block are `slots`
+fn num_skipped_slots(slots: &[u64], slot: u64, max_latency: u64) -> Result<u16, ProgramError> {
+    let slot_hashes = SlotHashes::new(
+        &slots
+            .iter()
+            .map(|slot| (*slot, Hash::default()))
+            .collect::<Vec<_>>(),
+    );
+    let mut data = bincode::serialize(&slot_hashes).unwrap();
+    let mut lamports = 0;
+    let key = SlotHashes::id();
+    let owner = sysvar::id();
+    let account = AccountInfo::new(
+        &key,
+        false,
+        false,
+        &mut lamports,
+        &mut data,
+        &owner,
+        false,
+        0,
+    );
+    get_num_skipped_slots(&account, slot, max_latency)
+}
+
+#[test]
+fn test_get_num_skipped_slots() {
+    let latency = u64::from(PC_MAX_SEND_LATENCY);
+
+    // No skipped slot
+    let slots: Vec<u64> = (0..100).collect();
+    assert_eq!(num_skipped_slots(&slots, 100, latency), Ok(0));
+    assert_eq!(num_skipped_slots(&[], 100, latency), Ok(0));
+
+    // Slots 51 to 89 were skipped
+    let slots: Vec<u64> = (0..=50).chain(90..100).collect();
+    assert_eq!(num_skipped_slots(&slots, 100, latency), Ok(39));
+    // Skipped slots outside of the latency window don't matter
+    assert_eq!(num_skipped_slots(&slots, 100, 5), Ok(0));
+
+    // Slots older than the listed ones count as having a block, so slots 11 to 98 were skipped
+    assert_eq!(num_skipped_slots(&[10, 99], 100, latency), Ok(88));
+    assert_eq!(num_skipped_slots(&[99], 100, latency), Ok(0));
+
+    // Only the SlotHashes sysvar is accepted
+    let mut data = bincode::serialize(&SlotHashes::new(&[])).unwrap();
+    let mut lamports = 0;
+    let key = Pubkey::new_unique();
+    let owner = sysvar::id();
+    let account = AccountInfo::new(
+        &key,
+        false,
+        false,
+        &mut lamports,
+        &mut data,
+        &owner,
+        false,
+        0,
+    );
+    assert_eq!(
+        get_num_skipped_slots(&account, 100, latency),
+        Err(ProgramError::InvalidArgument)
+    );
+}
+
+#[tokio::test]
+async fn test_upd_price_with_slot_hashes() {
+    let mut sim = PythSimulator::new().await;
+    let stopped_publisher = Keypair::new();
+    let live_publisher = Keypair::new();
+    let security_authority = Keypair::new();
+    let price_accounts = sim
+        .setup_product_fixture(
+            &[stopped_publisher.pubkey(), live_publisher.pubkey()],
+            security_authority.pubkey(),
+        )
+        .await;
+    let with_slot_hashes = price_accounts["LTC"];
+    let without_slot_hashes = price_accounts["BTC"];
+
+    let quote = || Quote {
+        price:      100,
+        confidence: 5,
+        status:     PC_STATUS_TRADING,
+    };
+    for price in [with_slot_hashes, without_slot_hashes] {
+        sim.set_min_pub(price, 1).await.unwrap();
+        sim.upd_price(&stopped_publisher, price, quote())
+            .await
+            .unwrap();
+    }
+
+    // The cluster skips slots 2 to 98: the quotes of slot 1 are only stale when not accounting
+    // for the skipped slots
+    sim.warp_to_slot(100).await.unwrap();
+    sim.upd_price_with_slot_hashes(&live_publisher, with_slot_hashes, quote())
+        .await
+        .unwrap();
+    sim.upd_price(&live_publisher, without_slot_hashes, quote())
+        .await
+        .unwrap();
+
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(with_slot_hashes)
+        .await
+        .unwrap();
+    assert!(price_data.skipped_slots_ > 0);
+    assert_eq!(price_data.agg_.status_, PC_STATUS_TRADING);
+    assert_eq!(price_data.num_qt_, 1);
+
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(without_slot_hashes)
+        .await
+        .unwrap();
+    assert_eq!(price_data.skipped_slots_, 0);
+    assert_eq!(price_data.agg_.status_, PC_STATUS_UNKNOWN);
+    assert_eq!(price_data.num_qt_, 0);
+
+    // The cluster produces every slot while a publisher has stopped: its quote becomes stale once
+    // PC_MAX_SEND_LATENCY blocks were produced after it
+    for slot in 101..=130 {
+        sim.warp_to_slot(slot).await.unwrap();
+        if slot % 10 == 0 {
+            sim.upd_price_with_slot_hashes(&live_publisher, with_slot_hashes, quote())
+                .await
+                .unwrap();
+        }
+    }
+
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(with_slot_hashes)
+        .await
+        .unwrap();
+    assert_eq!(price_data.skipped_slots_, 0);
+    assert_eq!(price_data.agg_.status_, PC_STATUS_TRADING);
+    assert_eq!(price_data.num_qt_, 1);
+    let stopped_stats = price_data
+        .publisher_stats(&stopped_publisher.pubkey())
+        .unwrap();
+    assert_eq!(stopped_stats.included_rounds_ & 0b1111, 0b1110);
+}
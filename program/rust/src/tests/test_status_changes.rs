@@ -0,0 +1,235 @@
+#[cfg(feature = "pythnet")]
+use crate::{
+    accounts::StatusHistoryEntry,
+    c_oracle_header::STATUS_HISTORY_LEN,
+};
+use {
+    crate::{
+        accounts::{
+            PriceAccount,
+            StatusChange,
+        },
+        c_oracle_header::{
+            PC_STATUS_HALTED,
+            PC_STATUS_TRADING,
+            PC_STATUS_UNKNOWN,
+        },
+        tests::pyth_simulator::{
+            PythSimulator,
+            Quote,
+        },
+    },
+    solana_program::{
+        clock::Clock,
+        pubkey::Pubkey,
+    },
+    solana_sdk::signer::Signer,
+};
+
+const START_TIMESTAMP: i64 = 1_700_000_000;
+
+fn quote(price: i64) -> Quote {
+    Quote {
+        price,
+        confidence: 1,
+        status: PC_STATUS_TRADING,
+    }
+}
+
+fn change(
+    slot: u64,
+    timestamp: i64,
+    prev_status: u32,
+    status: u32,
+    num_changes: u32,
+) -> StatusChange {
+    StatusChange {
+        slot_:         slot,
+        timestamp_:    timestamp,
+        prev_status_:  prev_status as u8,
+        status_:       status as u8,
+        agg_deferred_: 0,
+        unused_:       0,
+        num_changes_:  num_changes,
+    }
+}
+
+/// Warp to `slot`, at `START_TIMESTAMP + slot` seconds
+async fn warp(sim: &mut PythSimulator, slot: u64) {
+    sim.warp_to_slot(slot).await.unwrap();
+    sim.set_clock_timestamp(START_TIMESTAMP + slot as i64)
+        .await
+        .unwrap();
+}
+
+async fn status_change(sim: &mut PythSimulator, price: Pubkey) -> StatusChange {
+    sim.get_pyth_account::<PriceAccount>(price)
+        .await
+        .status_change_
+}
+
+/// The aggregations, halts and resumes record the changes of the aggregate status
+#[tokio::test]
+async fn test_status_changes() {
+    let mut sim = PythSimulator::new().await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(1).await;
+    let price = price_keypair.pubkey();
+    sim.set_min_pub(price, 1).await.unwrap();
+
+    // A new price account is unknown, which isn't a change
+    warp(&mut sim, 2).await;
+    sim.upd_price(&publishers[0], price, quote(100))
+        .await
+        .unwrap();
+    assert_eq!(status_change(&mut sim, price).await, change(0, 0, 0, 0, 0));
+
+    // The first successful aggregation starts trading
+    warp(&mut sim, 3).await;
+    sim.upd_price(&publishers[0], price, quote(100))
+        .await
+        .unwrap();
+    let trading = change(
+        3,
+        START_TIMESTAMP + 3,
+        PC_STATUS_UNKNOWN,
+        PC_STATUS_TRADING,
+        1,
+    );
+    assert_eq!(status_change(&mut sim, price).await, trading);
+
+    // Aggregations that keep trading aren't changes
+    warp(&mut sim, 4).await;
+    sim.upd_price(&publishers[0], price, quote(100))
+        .await
+        .unwrap();
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(price_data.agg_.pub_slot_, 4);
+    assert_eq!(price_data.status_change_, trading);
+    let clock = Clock {
+        unix_timestamp: START_TIMESTAMP + 33,
+        ..Clock::default()
+    };
+    assert_eq!(price_data.get_trading_duration(&clock), Some(30));
+
+    sim.halt_price(price).await.unwrap();
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(
+        price_data.status_change_,
+        change(
+            4,
+            START_TIMESTAMP + 4,
+            PC_STATUS_TRADING,
+            PC_STATUS_HALTED,
+            2
+        )
+    );
+    assert_eq!(price_data.get_trading_duration(&clock), None);
+
+    warp(&mut sim, 10).await;
+    sim.resume_price(price).await.unwrap();
+    assert_eq!(
+        status_change(&mut sim, price).await,
+        change(
+            10,
+            START_TIMESTAMP + 10,
+            PC_STATUS_HALTED,
+            PC_STATUS_UNKNOWN,
+            3
+        )
+    );
+
+    // The next aggregation trades again, the duration restarts
+    warp(&mut sim, 11).await;
+    sim.upd_price(&publishers[0], price, quote(100))
+        .await
+        .unwrap();
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(
+        price_data.status_change_,
+        change(
+            11,
+            START_TIMESTAMP + 11,
+            PC_STATUS_UNKNOWN,
+            PC_STATUS_TRADING,
+            4
+        )
+    );
+    assert_eq!(price_data.get_trading_duration(&clock), Some(22));
+
+    // An aggregation of stale quotes fails
+    warp(&mut sim, 100).await;
+    sim.upd_price(&publishers[0], price, quote(100))
+        .await
+        .unwrap();
+    assert_eq!(
+        status_change(&mut sim, price).await,
+        change(
+            100,
+            START_TIMESTAMP + 100,
+            PC_STATUS_TRADING,
+            PC_STATUS_UNKNOWN,
+            5
+        )
+    );
+
+    #[cfg(feature = "pythnet")]
+    {
+        let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+        let entry = |slot: u64, prev_status: u32, status: u32| StatusHistoryEntry {
+            slot_:        slot,
+            timestamp_:   (START_TIMESTAMP + slot as i64) as u32,
+            prev_status_: prev_status as u8,
+            status_:      status as u8,
+            unused_:      [0; 2],
+        };
+        assert_eq!(
+            price_data.status_changes().copied().collect::<Vec<_>>(),
+            vec![
+                entry(100, PC_STATUS_TRADING, PC_STATUS_UNKNOWN),
+                entry(11, PC_STATUS_UNKNOWN, PC_STATUS_TRADING),
+                entry(10, PC_STATUS_HALTED, PC_STATUS_UNKNOWN),
+                entry(4, PC_STATUS_TRADING, PC_STATUS_HALTED),
+                entry(3, PC_STATUS_UNKNOWN, PC_STATUS_TRADING),
+            ]
+        );
+    }
+}
+
+/// The status history keeps the last `STATUS_HISTORY_LEN` changes
+#[cfg(feature = "pythnet")]
+#[tokio::test]
+async fn test_status_history_wraps_around() {
+    let mut sim = PythSimulator::new().await;
+    let (price_keypair, _) = sim.setup_feed_with_publishers(1).await;
+    let price = price_keypair.pubkey();
+
+    let num_halts = STATUS_HISTORY_LEN as u64;
+    for i in 0..num_halts {
+        warp(&mut sim, 2 + 2 * i).await;
+        sim.halt_price(price).await.unwrap();
+        warp(&mut sim, 3 + 2 * i).await;
+        sim.resume_price(price).await.unwrap();
+    }
+
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(
+        price_data.status_change_.num_changes_,
+        2 * STATUS_HISTORY_LEN as u32
+    );
+    let changes: Vec<_> = price_data
+        .status_changes()
+        .map(|entry| (entry.slot_, u32::from(entry.status_)))
+        .collect();
+    // The oldest halts were overwritten
+    let expected: Vec<_> = (0..num_halts)
+        .rev()
+        .flat_map(|i| {
+            [
+                (3 + 2 * i, PC_STATUS_UNKNOWN),
+                (2 + 2 * i, PC_STATUS_HALTED),
+            ]
+        })
+        .take(STATUS_HISTORY_LEN)
+        .collect();
+    assert_eq!(changes, expected);
+}
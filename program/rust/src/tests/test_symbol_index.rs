@@ -0,0 +1,237 @@
+use {
+    crate::{
+        accounts::{
+            SymbolIndexAccount,
+            SymbolIndexEntry,
+        },
+        c_oracle_header::SYMBOL_INDEX_CAPACITY,
+        error::OracleError,
+        tests::pyth_simulator::{
+            PythSimulator,
+            TransactionFailure,
+        },
+    },
+    bytemuck::{
+        cast_slice_mut,
+        from_bytes_mut,
+    },
+    solana_program::pubkey::Pubkey,
+    solana_sdk::signer::Signer,
+    std::mem::size_of,
+};
+
+/// A symbol hash whose probing starts at `bucket`, distinguished from the other hashes of the
+/// bucket by `id`
+fn symbol_hash(bucket: usize, id: u8) -> [u8; 32] {
+    let mut symbol_hash = [0u8; 32];
+    symbol_hash[..8].copy_from_slice(&(bucket as u64).to_le_bytes());
+    symbol_hash[31] = id;
+    symbol_hash
+}
+
+/// Run `f` on an empty symbol index. The index is allocated on the heap as `u64`s to be aligned.
+fn with_symbol_index(f: impl FnOnce(&mut SymbolIndexAccount)) {
+    let mut data = vec![0u64; size_of::<SymbolIndexAccount>() / size_of::<u64>()];
+    f(from_bytes_mut(cast_slice_mut(&mut data)))
+}
+
+#[test]
+fn test_symbol_index_collisions() {
+    with_symbol_index(|index| {
+        let (a, b, c) = (symbol_hash(5, 1), symbol_hash(5, 2), symbol_hash(5, 3));
+        let d = symbol_hash(6, 4);
+        let prices: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        for (symbol_hash, price) in [a, b, c, d].iter().zip(prices.iter()) {
+            index.insert(symbol_hash, price).unwrap();
+        }
+        assert_eq!(index.num_entries_, 4);
+
+        // The colliding hashes and the hash displaced by them are probed linearly
+        let positions: Vec<Pubkey> = index.entries_[5..9]
+            .iter()
+            .map(|entry| entry.price_account_)
+            .collect();
+        assert_eq!(positions, prices);
+        for (symbol_hash, price) in [a, b, c, d].iter().zip(prices.iter()) {
+            assert_eq!(index.get(symbol_hash), Some(price));
+        }
+        assert_eq!(index.get(&symbol_hash(5, 5)), None);
+        assert_eq!(index.get(&symbol_hash(7, 3)), None);
+
+        // A symbol must be removed before being indexed again
+        assert_eq!(
+            index.insert(&a, &Pubkey::new_unique()),
+            Err(OracleError::SymbolAlreadyIndexed)
+        );
+        assert_eq!(
+            index.insert(&a, &prices[0]),
+            Err(OracleError::SymbolAlreadyIndexed)
+        );
+
+        // Only the entry of the symbol under the given price account is removed
+        assert!(!index.remove(&b, &prices[0]));
+        assert!(!index.remove(&symbol_hash(5, 5), &prices[1]));
+        assert!(index.remove(&b, &prices[1]));
+        assert!(!index.remove(&b, &prices[1]));
+        assert_eq!(index.num_entries_, 3);
+
+        // The following entries are moved back to stay reachable
+        assert_eq!(index.get(&b), None);
+        assert_eq!(index.get(&c), Some(&prices[2]));
+        assert_eq!(index.get(&d), Some(&prices[3]));
+        assert_eq!(index.entries_[6].price_account_, prices[2]);
+        assert_eq!(index.entries_[7].price_account_, prices[3]);
+        assert_eq!(
+            index.entries_[8],
+            SymbolIndexEntry {
+                symbol_hash_:   [0; 32],
+                price_account_: Pubkey::default(),
+            }
+        );
+
+        // An entry that is at its bucket is not moved
+        let (e, price_e) = (symbol_hash(8, 5), Pubkey::new_unique());
+        index.insert(&e, &price_e).unwrap();
+        assert!(index.remove(&a, &prices[0]));
+        assert_eq!(index.entries_[5].price_account_, prices[2]);
+        assert_eq!(index.entries_[6].price_account_, prices[3]);
+        assert_eq!(index.entries_[7].price_account_, Pubkey::default());
+        assert_eq!(index.entries_[8].price_account_, price_e);
+        assert_eq!(index.get(&e), Some(&price_e));
+        assert_eq!(index.entries().count(), 3);
+    });
+}
+
+#[test]
+fn test_symbol_index_wraps_around() {
+    with_symbol_index(|index| {
+        let last = SYMBOL_INDEX_CAPACITY - 1;
+        let (a, b, c) = (
+            symbol_hash(last, 1),
+            symbol_hash(last, 2),
+            symbol_hash(0, 3),
+        );
+        let prices: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        for (symbol_hash, price) in [a, b, c].iter().zip(prices.iter()) {
+            index.insert(symbol_hash, price).unwrap();
+        }
+        assert_eq!(index.entries_[last].price_account_, prices[0]);
+        assert_eq!(index.entries_[0].price_account_, prices[1]);
+        assert_eq!(index.entries_[1].price_account_, prices[2]);
+
+        assert!(index.remove(&a, &prices[0]));
+        assert_eq!(index.entries_[last].price_account_, prices[1]);
+        assert_eq!(index.entries_[0].price_account_, prices[2]);
+        assert_eq!(index.get(&b), Some(&prices[1]));
+        assert_eq!(index.get(&c), Some(&prices[2]));
+    });
+}
+
+#[test]
+fn test_symbol_index_full() {
+    with_symbol_index(|index| {
+        let price = Pubkey::new_unique();
+        for bucket in 0..SYMBOL_INDEX_CAPACITY {
+            index.insert(&symbol_hash(bucket, 1), &price).unwrap();
+        }
+        assert_eq!(index.num_entries_, SYMBOL_INDEX_CAPACITY as u32);
+
+        // Lookups of missing symbols still terminate
+        assert_eq!(index.get(&symbol_hash(0, 2)), None);
+        assert_eq!(
+            index.insert(&symbol_hash(0, 2), &price),
+            Err(OracleError::SymbolIndexFull)
+        );
+
+        assert!(index.remove(&symbol_hash(3, 1), &price));
+        index.insert(&symbol_hash(0, 2), &price).unwrap();
+        assert_eq!(index.entries_[3].symbol_hash_, symbol_hash(0, 2));
+        assert_eq!(index.get(&symbol_hash(0, 1)), Some(&price));
+    });
+}
+
+/// `add_price` and `del_price` maintain the symbol index they are given
+#[tokio::test]
+async fn test_symbol_index() {
+    let mut sim = PythSimulator::new().await;
+    let mapping_keypair = sim.init_mapping().await.unwrap();
+    let symbol_index = sim.init_symbol_index().await.unwrap().pubkey();
+    let btc_keypair = sim.add_product(&mapping_keypair).await.unwrap();
+    sim.upd_product(&btc_keypair, &[("symbol", "Crypto.BTC/USD")])
+        .await
+        .unwrap();
+    let eth_keypair = sim.add_product(&mapping_keypair).await.unwrap();
+    sim.upd_product(&eth_keypair, &[("symbol", "Crypto.ETH/USD")])
+        .await
+        .unwrap();
+
+    let btc_price_keypair = sim
+        .add_price_to_symbol_index(&btc_keypair, -8, symbol_index)
+        .await
+        .unwrap();
+    let eth_price_keypair = sim
+        .add_price_to_symbol_index(&eth_keypair, -8, symbol_index)
+        .await
+        .unwrap();
+    assert_eq!(
+        sim.get_indexed_price(symbol_index, "Crypto.BTC/USD").await,
+        Some(btc_price_keypair.pubkey())
+    );
+    assert_eq!(
+        sim.get_indexed_price(symbol_index, "Crypto.ETH/USD").await,
+        Some(eth_price_keypair.pubkey())
+    );
+    assert_eq!(
+        sim.get_indexed_price(symbol_index, "Crypto.SOL/USD").await,
+        None
+    );
+
+    // Price accounts added without the index aren't indexed
+    let unindexed_keypair = sim.add_price(&btc_keypair, -8).await.unwrap();
+    assert_eq!(
+        sim.get_indexed_price(symbol_index, "Crypto.BTC/USD").await,
+        Some(btc_price_keypair.pubkey())
+    );
+
+    // A symbol identifies a single price account
+    sim.add_price_to_symbol_index(&btc_keypair, -8, symbol_index)
+        .await
+        .assert_fails_with(OracleError::SymbolAlreadyIndexed);
+
+    // Products without a symbol can't be indexed
+    let unnamed_keypair = sim.add_product(&mapping_keypair).await.unwrap();
+    sim.add_price_to_symbol_index(&unnamed_keypair, -8, symbol_index)
+        .await
+        .assert_fails_with(OracleError::MissingSymbol);
+
+    // Deleting a price account that isn't indexed leaves the index untouched
+    sim.del_price_from_symbol_index(&btc_keypair, &unindexed_keypair, symbol_index)
+        .await
+        .unwrap();
+    assert_eq!(
+        sim.get_indexed_price(symbol_index, "Crypto.BTC/USD").await,
+        Some(btc_price_keypair.pubkey())
+    );
+
+    sim.del_price_from_symbol_index(&btc_keypair, &btc_price_keypair, symbol_index)
+        .await
+        .unwrap();
+    assert_eq!(
+        sim.get_indexed_price(symbol_index, "Crypto.BTC/USD").await,
+        None
+    );
+    assert_eq!(
+        sim.get_indexed_price(symbol_index, "Crypto.ETH/USD").await,
+        Some(eth_price_keypair.pubkey())
+    );
+
+    // The symbol can be indexed again once its price account is deleted
+    let new_btc_price_keypair = sim
+        .add_price_to_symbol_index(&btc_keypair, -8, symbol_index)
+        .await
+        .unwrap();
+    assert_eq!(
+        sim.get_indexed_price(symbol_index, "Crypto.BTC/USD").await,
+        Some(new_btc_price_keypair.pubkey())
+    );
+}
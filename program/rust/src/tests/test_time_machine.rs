@@ -0,0 +1,337 @@
+use {
+    crate::{
+        accounts::{
+            BucketAverage,
+            PriceAccount,
+            TimeMachine,
+            TimeMachineBucket,
+        },
+        c_oracle_header::{
+            PC_STATUS_TRADING,
+            TIME_MACHINE_BUCKET_SECONDS,
+            TIME_MACHINE_NUM_BUCKETS,
+        },
+        error::OracleError,
+        tests::pyth_simulator::{
+            PythSimulator,
+            Quote,
+        },
+    },
+    bytemuck::{
+        pod_read_unaligned,
+        Zeroable,
+    },
+    solana_program::{
+        pubkey::Pubkey,
+        rent::Rent,
+    },
+    solana_sdk::signer::Signer,
+    std::mem::size_of,
+};
+
+/// A multiple of `TIME_MACHINE_BUCKET_SECONDS`
+const T0: i64 = 1_700_000_040;
+
+fn quote(price: i64) -> Quote {
+    Quote {
+        price,
+        confidence: 1,
+        status: PC_STATUS_TRADING,
+    }
+}
+
+fn start_times(time_machine: &TimeMachine) -> Vec<i64> {
+    time_machine
+        .buckets()
+        .map(|bucket| bucket.start_time_)
+        .collect()
+}
+
+#[test]
+fn test_time_machine_record() {
+    let mut time_machine = TimeMachine::zeroed();
+    assert_eq!(time_machine.buckets().count(), 0);
+
+    time_machine.record(T0 + 10, 100, 4, 2).unwrap();
+    time_machine.record(T0 + 59, 200, 10, 3).unwrap();
+    assert_eq!(time_machine.num_buckets_, 1);
+    assert_eq!(time_machine.valid_, 0b1);
+    assert_eq!(
+        time_machine.buckets_[0],
+        TimeMachineBucket {
+            start_time_: T0,
+            num_slots_:  5,
+            price_sum_:  800,
+            conf_sum_:   38,
+        }
+    );
+
+    // The next aggregate starts the bucket of its period, the periods without any are invalid
+    time_machine.record(T0 + 60, -50, 1, 1).unwrap();
+    time_machine.record(T0 + 185, 300, 1, 4).unwrap();
+    assert_eq!(time_machine.num_buckets_, 4);
+    assert_eq!(time_machine.valid_, 0b1011);
+    assert_eq!(start_times(&time_machine), [T0 + 180, T0 + 60, T0]);
+    assert_eq!(time_machine.buckets_[1].price_sum_, -50);
+    assert_eq!(
+        time_machine.buckets_[2],
+        TimeMachineBucket {
+            start_time_: T0 + 120,
+            ..TimeMachineBucket::zeroed()
+        }
+    );
+
+    // An aggregate older than the last bucket is added to it
+    time_machine.record(T0 + 30, 100, 1, 4).unwrap();
+    assert_eq!(time_machine.num_buckets_, 4);
+    assert_eq!(time_machine.buckets_[3].num_slots_, 8);
+    assert_eq!(time_machine.buckets_[3].price_sum_, 1600);
+
+    // An overflow leaves the buckets unchanged
+    let before = time_machine;
+    assert_eq!(
+        time_machine.record(T0 + 190, i64::MAX, 1, u64::MAX),
+        Err(OracleError::ArithmeticOverflow)
+    );
+    assert_eq!(time_machine, before);
+}
+
+#[test]
+fn test_time_machine_wraparound() {
+    let mut time_machine = TimeMachine::zeroed();
+    let num_buckets = TIME_MACHINE_NUM_BUCKETS as i64;
+    for n in 0..num_buckets + 3 {
+        time_machine
+            .record(T0 + n * TIME_MACHINE_BUCKET_SECONDS, n, 0, 1)
+            .unwrap();
+    }
+
+    assert_eq!(
+        time_machine.num_buckets_,
+        TIME_MACHINE_NUM_BUCKETS as u64 + 3
+    );
+    // The oldest buckets were overwritten
+    assert_eq!(time_machine.buckets_[0].price_sum_, num_buckets);
+    assert_eq!(time_machine.buckets_[2].price_sum_, num_buckets + 2);
+    assert_eq!(time_machine.buckets_[3].price_sum_, 3);
+    let start_times = start_times(&time_machine);
+    assert_eq!(start_times.len(), TIME_MACHINE_NUM_BUCKETS);
+    assert_eq!(
+        start_times[0],
+        T0 + (num_buckets + 2) * TIME_MACHINE_BUCKET_SECONDS
+    );
+    assert_eq!(
+        start_times[TIME_MACHINE_NUM_BUCKETS - 1],
+        T0 + 3 * TIME_MACHINE_BUCKET_SECONDS
+    );
+    assert!(start_times.windows(2).all(|pair| pair[0] > pair[1]));
+    assert_eq!(time_machine.valid_, u64::MAX);
+}
+
+#[test]
+fn test_time_machine_dark_periods() {
+    let mut time_machine = TimeMachine::zeroed();
+    for n in 0..4 {
+        time_machine
+            .record(T0 + n * TIME_MACHINE_BUCKET_SECONDS, 100, 1, 1)
+            .unwrap();
+    }
+    assert_eq!(time_machine.valid_, 0b1111);
+
+    // The dark periods in between are marked invalid, up to the end of the entries
+    time_machine
+        .record(T0 + 63 * TIME_MACHINE_BUCKET_SECONDS, 200, 1, 1)
+        .unwrap();
+    assert_eq!(time_machine.num_buckets_, 64);
+    assert_eq!(time_machine.valid_, 0b1111 | 1 << 63);
+    for (entry, bucket) in time_machine.buckets_.iter().enumerate().skip(4).take(59) {
+        assert_eq!(
+            bucket.start_time_,
+            T0 + entry as i64 * TIME_MACHINE_BUCKET_SECONDS
+        );
+        assert_eq!(bucket.num_slots_, 0);
+    }
+
+    // And from the start of the entries, overwriting the oldest buckets
+    time_machine
+        .record(T0 + 66 * TIME_MACHINE_BUCKET_SECONDS, 300, 1, 1)
+        .unwrap();
+    assert_eq!(time_machine.num_buckets_, 67);
+    assert_eq!(time_machine.valid_, 0b1100 | 1 << 63);
+    assert_eq!(
+        time_machine.buckets_[1].start_time_,
+        T0 + 65 * TIME_MACHINE_BUCKET_SECONDS
+    );
+    assert_eq!(time_machine.buckets_[0].num_slots_, 0);
+    assert_eq!(
+        start_times(&time_machine),
+        [
+            T0 + 66 * TIME_MACHINE_BUCKET_SECONDS,
+            T0 + 63 * TIME_MACHINE_BUCKET_SECONDS,
+            T0 + 3 * TIME_MACHINE_BUCKET_SECONDS,
+        ]
+    );
+
+    // A longer dark period invalidates all the other buckets
+    time_machine
+        .record(T0 + 1_000 * TIME_MACHINE_BUCKET_SECONDS, 400, 1, 1)
+        .unwrap();
+    assert_eq!(time_machine.num_buckets_, 1_001);
+    let entry = 1_000 % TIME_MACHINE_NUM_BUCKETS;
+    assert_eq!(time_machine.valid_, 1 << entry);
+    assert_eq!(
+        time_machine.buckets_[(entry + 1) % TIME_MACHINE_NUM_BUCKETS].start_time_,
+        T0 + 937 * TIME_MACHINE_BUCKET_SECONDS
+    );
+    assert_eq!(time_machine.buckets().count(), 1);
+    assert_eq!(time_machine.recent_averages(T0 + 2_000 * 60, 64).count(), 1);
+}
+
+#[test]
+fn test_time_machine_recent_averages() {
+    let mut time_machine = TimeMachine::zeroed();
+    time_machine.record(T0, 100, 10, 1).unwrap();
+    time_machine.record(T0 + 30, 103, 20, 2).unwrap();
+    // Held for no slot
+    time_machine.record(T0 + 60, 1_000, 1, 0).unwrap();
+    time_machine.record(T0 + 120, -7, 3, 2).unwrap();
+
+    // The bucket of T0 + 120 isn't over yet
+    assert_eq!(
+        time_machine
+            .recent_averages(T0 + 150, 3)
+            .collect::<Vec<_>>(),
+        [BucketAverage {
+            start_time: T0,
+            price:      102,
+            conf:       16,
+            num_slots:  3,
+        }]
+    );
+
+    let averages = time_machine
+        .recent_averages(T0 + 180, 3)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        averages,
+        [
+            BucketAverage {
+                start_time: T0 + 120,
+                price:      -7,
+                conf:       3,
+                num_slots:  2,
+            },
+            BucketAverage {
+                start_time: T0,
+                price:      102,
+                conf:       16,
+                num_slots:  3,
+            },
+        ]
+    );
+    assert_eq!(time_machine.recent_averages(T0 + 180, 1).count(), 1);
+}
+
+async fn get_time_machine(sim: &mut PythSimulator, price: Pubkey) -> TimeMachine {
+    let price_account = sim.get_account(price).await.unwrap();
+    pod_read_unaligned::<TimeMachine>(
+        PriceAccount::extension_data::<TimeMachine>(
+            &price_account.data[size_of::<PriceAccount>()..],
+        )
+        .unwrap(),
+    )
+}
+
+/// Slot gap of the last aggregate of `price`, the number of slots its bucket is credited with
+async fn get_slot_gap(sim: &mut PythSimulator, price: Pubkey) -> u64 {
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(price_data.agg_.status_, PC_STATUS_TRADING);
+    price_data.agg_.pub_slot_ - price_data.prev_slot_
+}
+
+#[tokio::test]
+async fn test_init_time_machine() {
+    let mut sim = PythSimulator::new().await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(1).await;
+    let price = price_keypair.pubkey();
+    let publisher = &publishers[0];
+    sim.set_min_pub(price, 1).await.unwrap();
+
+    let initial_size = sim.get_account(price).await.unwrap().data.len();
+    sim.init_time_machine(price).await.unwrap();
+    let price_account = sim.get_account(price).await.unwrap();
+    let size = initial_size + PriceAccount::extension_size::<TimeMachine>();
+    assert_eq!(price_account.data.len(), size);
+    assert!(price_account.lamports >= Rent::default().minimum_balance(size));
+    assert_eq!(
+        get_time_machine(&mut sim, price).await,
+        TimeMachine::zeroed()
+    );
+
+    // A second time machine isn't added
+    sim.init_time_machine(price).await.unwrap();
+    assert_eq!(sim.get_account(price).await.unwrap().data.len(), size);
+
+    sim.set_clock_timestamp(T0).await.unwrap();
+    sim.upd_price(publisher, price, quote(100)).await.unwrap();
+
+    sim.warp_to_slot(2).await.unwrap();
+    sim.set_clock_timestamp(T0 + 10).await.unwrap();
+    sim.upd_price(publisher, price, quote(200)).await.unwrap();
+    let first_gap = get_slot_gap(&mut sim, price).await;
+
+    sim.warp_to_slot(3).await.unwrap();
+    sim.set_clock_timestamp(T0 + 20).await.unwrap();
+    sim.upd_price(publisher, price, quote(200)).await.unwrap();
+    let second_gap = get_slot_gap(&mut sim, price).await;
+
+    let time_machine = get_time_machine(&mut sim, price).await;
+    assert_eq!(start_times(&time_machine), [T0]);
+    assert_eq!(time_machine.buckets_[0].num_slots_, first_gap + second_gap);
+    assert_eq!(
+        time_machine.buckets_[0].price_sum_,
+        i128::from(100 * first_gap + 200 * second_gap)
+    );
+    assert_eq!(time_machine.recent_averages(T0 + 20, 1).count(), 0);
+    assert_eq!(
+        time_machine.twap(T0, T0 + 60).unwrap().price,
+        time_machine.buckets_[0].average().unwrap().price
+    );
+
+    // The next aggregates are in the buckets of their periods
+    sim.warp_to_slot(4).await.unwrap();
+    sim.set_clock_timestamp(T0 + 70).await.unwrap();
+    sim.upd_price(publisher, price, quote(300)).await.unwrap();
+    sim.warp_to_slot(5).await.unwrap();
+    sim.set_clock_timestamp(T0 + 200).await.unwrap();
+    sim.upd_price(publisher, price, quote(300)).await.unwrap();
+    let time_machine = get_time_machine(&mut sim, price).await;
+    assert_eq!(start_times(&time_machine), [T0 + 180, T0 + 60, T0]);
+    assert_eq!(time_machine.num_buckets_, 4);
+    assert_eq!(time_machine.valid_, 0b1011);
+    assert_eq!(
+        time_machine
+            .recent_averages(T0 + 200, 2)
+            .map(|average| average.price)
+            .collect::<Vec<_>>(),
+        [200, time_machine.buckets_[0].average().unwrap().price]
+    );
+
+    // After a longer dark period, only the bucket of the new aggregate is valid
+    sim.warp_to_slot(6).await.unwrap();
+    sim.set_clock_timestamp(T0 + 6_005).await.unwrap();
+    sim.upd_price(publisher, price, quote(300)).await.unwrap();
+    let time_machine = get_time_machine(&mut sim, price).await;
+    assert_eq!(time_machine.num_buckets_, 101);
+    assert_eq!(time_machine.valid_, 1 << 36);
+    assert_eq!(start_times(&time_machine), [T0 + 6_000]);
+    assert_eq!(time_machine.buckets_[37].start_time_, T0 + 2_220);
+    assert_eq!(time_machine.recent_averages(T0 + 6_060, 2).count(), 1);
+
+    // Changing the exponent clears the buckets
+    sim.set_exponent(price, -6).await.unwrap();
+    assert_eq!(
+        get_time_machine(&mut sim, price).await,
+        TimeMachine::zeroed()
+    );
+}
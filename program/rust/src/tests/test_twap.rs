@@ -1,5 +1,11 @@
 use {
-    super::test_utils::AccountSetup,
+    super::{
+        pyth_simulator::{
+            PythSimulator,
+            Quote,
+        },
+        test_utils::AccountSetup,
+    },
     crate::{
         accounts::{
             PriceAccount,
@@ -17,6 +23,10 @@ use {
     quickcheck::Arbitrary,
     quickcheck_macros::quickcheck,
     solana_program::pubkey::Pubkey,
+    solana_sdk::{
+        signature::Keypair,
+        signer::Signer,
+    },
 };
 
 #[derive(Clone, Debug, Copy)]
@@ -50,26 +60,28 @@ impl Arbitrary for DataEvent {
 #[quickcheck]
 fn test_twap(input: Vec<DataEvent>) -> bool {
     let mut price_cumulative = PriceCumulative {
-        price:          0,
-        conf:           0,
-        num_down_slots: 0,
-        unused:         0,
+        price:             0,
+        conf:              0,
+        num_down_slots:    0,
+        aggregate_counter: 0,
     };
 
     let mut data = Vec::<DataEvent>::new();
 
     for data_event in input {
-        price_cumulative.update(
-            data_event.price,
-            data_event.conf,
-            data_event.slot_gap,
-            data_event.max_latency,
-        );
+        price_cumulative
+            .update(
+                data_event.price,
+                data_event.conf,
+                data_event.slot_gap,
+                data_event.max_latency,
+            )
+            .unwrap();
         data.push(data_event);
         price_cumulative.check_price(data.as_slice());
         price_cumulative.check_conf(data.as_slice());
         price_cumulative.check_num_down_slots(data.as_slice());
-        price_cumulative.check_unused();
+        price_cumulative.check_aggregate_counter();
     }
 
     true
@@ -109,18 +121,18 @@ impl PriceCumulative {
             self.num_down_slots
         );
     }
-    pub fn check_unused(&self) {
-        assert_eq!(self.unused, 0);
+    pub fn check_aggregate_counter(&self) {
+        assert_eq!(self.aggregate_counter, 0);
     }
 }
 
 #[test]
 fn test_twap_unit() {
     let mut price_cumulative = PriceCumulative {
-        price:          1,
-        conf:           2,
-        num_down_slots: 3,
-        unused:         0,
+        price:             1,
+        conf:              2,
+        num_down_slots:    3,
+        aggregate_counter: 0,
     };
 
     let data = vec![
@@ -156,46 +168,54 @@ fn test_twap_unit() {
         },
     ];
 
-    price_cumulative.update(
-        data[0].price,
-        data[0].conf,
-        data[0].slot_gap,
-        data[0].max_latency,
-    );
+    price_cumulative
+        .update(
+            data[0].price,
+            data[0].conf,
+            data[0].slot_gap,
+            data[0].max_latency,
+        )
+        .unwrap();
     assert_eq!(price_cumulative.price, 5);
     assert_eq!(price_cumulative.conf, 10);
     assert_eq!(price_cumulative.num_down_slots, 3);
-    assert_eq!(price_cumulative.unused, 0);
+    assert_eq!(price_cumulative.aggregate_counter, 0);
 
-    price_cumulative.update(
-        data[1].price,
-        data[1].conf,
-        data[1].slot_gap,
-        data[1].max_latency,
-    );
+    price_cumulative
+        .update(
+            data[1].price,
+            data[1].conf,
+            data[1].slot_gap,
+            data[1].max_latency,
+        )
+        .unwrap();
     assert_eq!(price_cumulative.price, 9_223_372_036_854_775_812i128);
     assert_eq!(price_cumulative.conf, 18_446_744_073_709_551_625u128);
     assert_eq!(price_cumulative.num_down_slots, 3);
-    assert_eq!(price_cumulative.unused, 0);
+    assert_eq!(price_cumulative.aggregate_counter, 0);
 
-    price_cumulative.update(
-        data[2].price,
-        data[2].conf,
-        data[2].slot_gap,
-        data[2].max_latency,
-    );
+    price_cumulative
+        .update(
+            data[2].price,
+            data[2].conf,
+            data[2].slot_gap,
+            data[2].max_latency,
+        )
+        .unwrap();
     assert_eq!(price_cumulative.price, 9_223_372_036_854_775_512i128);
     assert_eq!(price_cumulative.conf, 18_446_744_073_709_551_745u128);
     assert_eq!(price_cumulative.num_down_slots, 8);
-    assert_eq!(price_cumulative.unused, 0);
+    assert_eq!(price_cumulative.aggregate_counter, 0);
 
     let mut price_cumulative_overflow = PriceCumulative {
-        price:          0,
-        conf:           0,
-        num_down_slots: 0,
-        unused:         0,
+        price:             0,
+        conf:              0,
+        num_down_slots:    0,
+        aggregate_counter: 0,
     };
-    price_cumulative_overflow.update(i64::MIN, u64::MAX, u64::MAX, u8::MAX);
+    price_cumulative_overflow
+        .update(i64::MIN, u64::MAX, u64::MAX, u8::MAX)
+        .unwrap();
     assert_eq!(
         price_cumulative_overflow.price,
         i128::MIN - i128::from(i64::MIN)
@@ -208,36 +228,85 @@ fn test_twap_unit() {
         price_cumulative_overflow.num_down_slots,
         u64::MAX - u64::from(u8::MAX)
     );
-    assert_eq!(price_cumulative_overflow.unused, 0);
+    assert_eq!(price_cumulative_overflow.aggregate_counter, 0);
 
     let mut price_cumulative_nonzero_max_latency = PriceCumulative {
-        price:          1,
-        conf:           2,
-        num_down_slots: 3,
-        unused:         0,
+        price:             1,
+        conf:              2,
+        num_down_slots:    3,
+        aggregate_counter: 0,
     };
 
-    price_cumulative_nonzero_max_latency.update(
-        data[3].price,
-        data[3].conf,
-        data[3].slot_gap,
-        data[3].max_latency,
-    );
+    price_cumulative_nonzero_max_latency
+        .update(
+            data[3].price,
+            data[3].conf,
+            data[3].slot_gap,
+            data[3].max_latency,
+        )
+        .unwrap();
     assert_eq!(price_cumulative_nonzero_max_latency.price, 5);
     assert_eq!(price_cumulative_nonzero_max_latency.conf, 10);
     assert_eq!(price_cumulative_nonzero_max_latency.num_down_slots, 3);
-    assert_eq!(price_cumulative_nonzero_max_latency.unused, 0);
+    assert_eq!(price_cumulative_nonzero_max_latency.aggregate_counter, 0);
 
-    price_cumulative_nonzero_max_latency.update(
-        data[4].price,
-        data[4].conf,
-        data[4].slot_gap,
-        data[4].max_latency,
-    );
+    price_cumulative_nonzero_max_latency
+        .update(
+            data[4].price,
+            data[4].conf,
+            data[4].slot_gap,
+            data[4].max_latency,
+        )
+        .unwrap();
     assert_eq!(price_cumulative_nonzero_max_latency.price, 53);
     assert_eq!(price_cumulative_nonzero_max_latency.conf, 66);
     assert_eq!(price_cumulative_nonzero_max_latency.num_down_slots, 6);
-    assert_eq!(price_cumulative_nonzero_max_latency.unused, 0);
+    assert_eq!(price_cumulative_nonzero_max_latency.aggregate_counter, 0);
+}
+
+/// The sums of a price account that was rescaled by `set_exponent` can overflow, in which case
+/// the update fails and leaves them unchanged
+#[test]
+fn test_twap_overflow() {
+    let initial = PriceCumulative {
+        price:             i128::MAX - i128::from(i64::MAX),
+        conf:              u128::MAX - u128::from(u64::MAX),
+        num_down_slots:    u64::MAX - 10,
+        aggregate_counter: 0,
+    };
+
+    // Up to the limits of the types
+    let mut price_cumulative = initial;
+    price_cumulative
+        .update(i64::MAX, u64::MAX, 1, u8::MAX)
+        .unwrap();
+    assert_eq!(price_cumulative.price, i128::MAX);
+    assert_eq!(price_cumulative.conf, u128::MAX);
+    assert_eq!(price_cumulative.num_down_slots, u64::MAX - 10);
+
+    // One past them
+    for (price, conf, slot_gap, max_latency) in [
+        (i64::MAX, 0, 2, u8::MAX),
+        (0, u64::MAX, 2, u8::MAX),
+        (0, 0, 12, 1),
+    ] {
+        let mut price_cumulative = initial;
+        assert_eq!(
+            price_cumulative.update(price, conf, slot_gap, max_latency),
+            Err(OracleError::ArithmeticOverflow)
+        );
+        assert_eq!(price_cumulative.price, initial.price);
+        assert_eq!(price_cumulative.conf, initial.conf);
+        assert_eq!(price_cumulative.num_down_slots, initial.num_down_slots);
+    }
+    let mut price_cumulative = PriceCumulative {
+        price: i128::MIN + i128::from(i64::MAX),
+        ..initial
+    };
+    assert_eq!(
+        price_cumulative.update(i64::MIN, 0, 1, u8::MAX),
+        Err(OracleError::ArithmeticOverflow)
+    );
 }
 
 #[test]
@@ -256,10 +325,10 @@ fn test_twap_with_price_account() {
         pub_slot_:        5,
     };
     price_data.price_cumulative = PriceCumulative {
-        price:          1,
-        conf:           2,
-        num_down_slots: 3,
-        unused:         0,
+        price:             1,
+        conf:              2,
+        num_down_slots:    3,
+        aggregate_counter: 0,
     };
     price_data.prev_slot_ = 3;
     price_data.update_price_cumulative().unwrap();
@@ -308,3 +377,69 @@ fn test_twap_with_price_account() {
     assert_eq!(price_data.price_cumulative.conf, 2 + 2 * 5 + 2);
     assert_eq!(price_data.price_cumulative.num_down_slots, 3);
 }
+
+/// Publish over several slots in the simulator and check that the TWAP between two observations of
+/// the cumulative sums matches the aggregate prices.
+#[tokio::test]
+async fn test_twap_over_time() {
+    let mut sim = PythSimulator::new().await;
+    let publisher = Keypair::new();
+    let security_authority = Keypair::new();
+    let price_accounts = sim
+        .setup_product_fixture(&[publisher.pubkey()], security_authority.pubkey())
+        .await;
+    let price = price_accounts["LTC"];
+    sim.set_min_pub(price, 1).await.unwrap();
+
+    let quote = |value: i64| Quote {
+        price:      value,
+        confidence: 5,
+        status:     PC_STATUS_TRADING,
+    };
+
+    sim.upd_price(&publisher, price, quote(100)).await.unwrap();
+    sim.advance_slots(1).await.unwrap();
+    sim.set_clock_timestamp(1000).await.unwrap();
+    sim.upd_price(&publisher, price, quote(200)).await.unwrap();
+
+    let first_observation = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(first_observation.agg_.status_, PC_STATUS_TRADING);
+    assert_eq!(first_observation.agg_.price_, 100);
+    assert_eq!(first_observation.timestamp_, 1000);
+
+    // The aggregate price stays at 200 for 3 slots
+    sim.advance_slots(3).await.unwrap();
+    sim.set_clock_timestamp(1003).await.unwrap();
+    sim.upd_price(&publisher, price, quote(300)).await.unwrap();
+
+    let second_observation = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(second_observation.agg_.status_, PC_STATUS_TRADING);
+    assert_eq!(second_observation.agg_.price_, 200);
+    assert_eq!(second_observation.timestamp_, 1003);
+    assert_eq!(
+        second_observation.agg_.pub_slot_ - first_observation.agg_.pub_slot_,
+        3
+    );
+
+    let slot_gap = i128::from(second_observation.agg_.pub_slot_ - first_observation.agg_.pub_slot_);
+    assert_eq!(
+        (second_observation.price_cumulative.price - first_observation.price_cumulative.price)
+            / slot_gap,
+        200
+    );
+    assert_eq!(
+        (second_observation.price_cumulative.conf - first_observation.price_cumulative.conf)
+            / slot_gap as u128,
+        5
+    );
+    assert_eq!(
+        second_observation.price_cumulative.num_down_slots,
+        first_observation.price_cumulative.num_down_slots
+    );
+}
@@ -0,0 +1,140 @@
+use {
+    crate::{
+        accounts::PriceAccount,
+        aggregation::upd_aggregate,
+        c_oracle_header::{
+            PC_STATUS_DEGRADED,
+            PC_STATUS_TRADING,
+            PC_STATUS_UNKNOWN,
+            PC_STATUS_UNRELIABLE,
+        },
+        tests::pyth_simulator::{
+            PythSimulator,
+            Quote,
+        },
+    },
+    bytemuck::Zeroable,
+    solana_sdk::signer::Signer,
+};
+
+const CURRENT_SLOT: u64 = 1000;
+
+/// A price account with a quote of confidence 1 published at `CURRENT_SLOT` for each
+/// `(price, status)` of `quotes`
+fn price_account_with(quotes: &[(i64, u32)]) -> PriceAccount {
+    let mut price_account = PriceAccount::zeroed();
+    price_account.num_ = quotes.len() as u32;
+    price_account.min_pub_ = 1;
+    for (component, (price, status)) in price_account.comp_.iter_mut().zip(quotes.iter()) {
+        component.latest_.price_ = *price;
+        component.latest_.conf_ = 1;
+        component.latest_.status_ = *status;
+        component.latest_.pub_slot_ = CURRENT_SLOT;
+    }
+    price_account
+}
+
+#[test]
+fn test_unreliable_status() {
+    // Unreliable quotes are included in the aggregate
+    let mut price_account = price_account_with(&[
+        (100, PC_STATUS_TRADING),
+        (110, PC_STATUS_UNRELIABLE),
+        (120, PC_STATUS_UNKNOWN),
+    ]);
+    assert!(upd_aggregate(&mut price_account, CURRENT_SLOT + 1, 0));
+    assert_eq!(price_account.num_qt_, 2);
+    assert_eq!(price_account.agg_.price_, 105);
+    assert_eq!(price_account.fast_agg_.num_qt_, 2);
+
+    // Half of the included quotes is not a majority
+    assert_eq!(price_account.agg_.status_, PC_STATUS_TRADING);
+    assert!(!price_account.is_degraded());
+
+    // A majority is
+    let mut price_account = price_account_with(&[
+        (100, PC_STATUS_TRADING),
+        (110, PC_STATUS_UNRELIABLE),
+        (120, PC_STATUS_UNRELIABLE),
+    ]);
+    assert!(upd_aggregate(&mut price_account, CURRENT_SLOT + 1, 0));
+    assert_eq!(price_account.agg_.status_, PC_STATUS_DEGRADED);
+    assert!(price_account.is_degraded());
+    assert_eq!(price_account.num_qt_, 3);
+    assert_eq!(price_account.agg_.price_, 110);
+    assert_eq!(price_account.get_price_unchecked().price, 110);
+    assert_eq!(price_account.fast_agg_.status_, PC_STATUS_TRADING);
+
+    // A degraded aggregate is still the previous price of the next aggregation
+    price_account.comp_[1].latest_.status_ = PC_STATUS_TRADING;
+    price_account.comp_[1].latest_.pub_slot_ = CURRENT_SLOT + 1;
+    assert!(upd_aggregate(&mut price_account, CURRENT_SLOT + 2, 0));
+    assert_eq!(price_account.agg_.status_, PC_STATUS_TRADING);
+    assert_eq!(price_account.prev_price_, 110);
+    assert_eq!(price_account.prev_slot_, CURRENT_SLOT + 1);
+
+    // Excluded unreliable quotes don't count
+    let mut price_account = price_account_with(&[
+        (100, PC_STATUS_TRADING),
+        (110, PC_STATUS_UNRELIABLE),
+        (120, PC_STATUS_UNRELIABLE),
+    ]);
+    price_account.min_price_ = 50;
+    price_account.max_price_ = 115;
+    assert!(upd_aggregate(&mut price_account, CURRENT_SLOT + 1, 0));
+    assert_eq!(price_account.num_qt_, 2);
+    assert_eq!(price_account.agg_.status_, PC_STATUS_TRADING);
+}
+
+#[tokio::test]
+async fn test_upd_price_unreliable_status() {
+    let mut sim = PythSimulator::new().await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(3).await;
+    let price = price_keypair.pubkey();
+    sim.set_min_pub(price, 1).await.unwrap();
+
+    let quote = |price: i64, status: u32| Quote {
+        price,
+        confidence: 1,
+        status,
+    };
+
+    sim.publish_all(
+        price,
+        &publishers,
+        &[
+            quote(100, PC_STATUS_UNRELIABLE),
+            quote(110, PC_STATUS_UNRELIABLE),
+            quote(120, PC_STATUS_TRADING),
+        ],
+    )
+    .await
+    .unwrap();
+    sim.warp_to_slot(2).await.unwrap();
+    sim.upd_price(&publishers[2], price, quote(120, PC_STATUS_TRADING))
+        .await
+        .unwrap();
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(price_data.agg_.status_, PC_STATUS_DEGRADED);
+    assert_eq!(price_data.agg_.price_, 110);
+    assert_eq!(price_data.num_qt_, 3);
+    assert_eq!(price_data.last_slot_, 2);
+
+    // The aggregate recovers once the publishers are confident again
+    sim.upd_price(&publishers[0], price, quote(100, PC_STATUS_TRADING))
+        .await
+        .unwrap();
+    sim.warp_to_slot(3).await.unwrap();
+    sim.upd_price(&publishers[2], price, quote(120, PC_STATUS_TRADING))
+        .await
+        .unwrap();
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(price_data.agg_.status_, PC_STATUS_TRADING);
+    assert_eq!(price_data.prev_price_, 110);
+}
@@ -5,6 +5,7 @@ use {
             PriceInfo,
             PythAccount,
         },
+        aggregation::upd_aggregate,
         c_oracle_header::{
             PC_STATUS_TRADING,
             PC_STATUS_UNKNOWN,
@@ -18,7 +19,6 @@ use {
             OracleCommand,
             UpdPriceArgs,
         },
-        processor::c_upd_aggregate,
         tests::test_utils::AccountSetup,
     },
     solana_program::pubkey::Pubkey,
@@ -87,13 +87,11 @@ fn test_upd_aggregate() {
         price_data.comp_[0].latest_ = p1;
     }
 
-    unsafe {
-        assert!(c_upd_aggregate(
-            price_account.try_borrow_mut_data().unwrap().as_mut_ptr(),
-            1001,
-            1,
-        ));
-    }
+    assert!(upd_aggregate(
+        &mut load_checked::<PriceAccount>(&price_account, PC_VERSION).unwrap(),
+        1001,
+        1,
+    ));
 
     {
         let price_data = load_checked::<PriceAccount>(&price_account, PC_VERSION).unwrap();
@@ -119,13 +117,11 @@ fn test_upd_aggregate() {
         price_data.comp_[1].latest_ = p2;
     }
 
-    unsafe {
-        assert!(c_upd_aggregate(
-            price_account.try_borrow_mut_data().unwrap().as_mut_ptr(),
-            1001,
-            2,
-        ));
-    }
+    assert!(upd_aggregate(
+        &mut load_checked::<PriceAccount>(&price_account, PC_VERSION).unwrap(),
+        1001,
+        2,
+    ));
 
     {
         let price_data = load_checked::<PriceAccount>(&price_account, PC_VERSION).unwrap();
@@ -152,13 +148,11 @@ fn test_upd_aggregate() {
         price_data.comp_[2].latest_ = p3;
     }
 
-    unsafe {
-        assert!(c_upd_aggregate(
-            price_account.try_borrow_mut_data().unwrap().as_mut_ptr(),
-            1001,
-            3,
-        ));
-    }
+    assert!(upd_aggregate(
+        &mut load_checked::<PriceAccount>(&price_account, PC_VERSION).unwrap(),
+        1001,
+        3,
+    ));
 
     {
         let price_data = load_checked::<PriceAccount>(&price_account, PC_VERSION).unwrap();
@@ -186,13 +180,11 @@ fn test_upd_aggregate() {
         price_data.comp_[3].latest_ = p4;
     }
 
-    unsafe {
-        assert!(c_upd_aggregate(
-            price_account.try_borrow_mut_data().unwrap().as_mut_ptr(),
-            1001,
-            4,
-        ));
-    }
+    assert!(upd_aggregate(
+        &mut load_checked::<PriceAccount>(&price_account, PC_VERSION).unwrap(),
+        1001,
+        4,
+    ));
 
     {
         let price_data = load_checked::<PriceAccount>(&price_account, PC_VERSION).unwrap();
@@ -208,13 +200,11 @@ fn test_upd_aggregate() {
         assert_eq!(price_data.prev_timestamp_, 3);
     }
 
-    unsafe {
-        assert!(c_upd_aggregate(
-            price_account.try_borrow_mut_data().unwrap().as_mut_ptr(),
-            1025,
-            5,
-        ));
-    }
+    assert!(upd_aggregate(
+        &mut load_checked::<PriceAccount>(&price_account, PC_VERSION).unwrap(),
+        1025,
+        5,
+    ));
 
     {
         let price_data = load_checked::<PriceAccount>(&price_account, PC_VERSION).unwrap();
@@ -230,13 +220,11 @@ fn test_upd_aggregate() {
     }
 
     // check what happens when nothing publishes for a while
-    unsafe {
-        assert!(!c_upd_aggregate(
-            price_account.try_borrow_mut_data().unwrap().as_mut_ptr(),
-            1026,
-            10,
-        ));
-    }
+    assert!(!upd_aggregate(
+        &mut load_checked::<PriceAccount>(&price_account, PC_VERSION).unwrap(),
+        1026,
+        10,
+    ));
 
     {
         let price_data = load_checked::<PriceAccount>(&price_account, PC_VERSION).unwrap();
@@ -251,13 +239,11 @@ fn test_upd_aggregate() {
         assert_eq!(price_data.prev_timestamp_, 5);
     }
 
-    unsafe {
-        assert!(!c_upd_aggregate(
-            price_account.try_borrow_mut_data().unwrap().as_mut_ptr(),
-            1028,
-            12,
-        ));
-    }
+    assert!(!upd_aggregate(
+        &mut load_checked::<PriceAccount>(&price_account, PC_VERSION).unwrap(),
+        1028,
+        12,
+    ));
 
     {
         let price_data = load_checked::<PriceAccount>(&price_account, PC_VERSION).unwrap();
@@ -282,13 +268,11 @@ fn test_upd_aggregate() {
         price_data.comp_[1].latest_ = p5;
     }
 
-    unsafe {
-        assert!(c_upd_aggregate(
-            price_account.try_borrow_mut_data().unwrap().as_mut_ptr(),
-            1025,
-            13,
-        ));
-    }
+    assert!(upd_aggregate(
+        &mut load_checked::<PriceAccount>(&price_account, PC_VERSION).unwrap(),
+        1025,
+        13,
+    ));
 
     {
         let price_data = load_checked::<PriceAccount>(&price_account, PC_VERSION).unwrap();
@@ -305,13 +289,11 @@ fn test_upd_aggregate() {
     }
 
     // verify behavior when publishing halts for 1 slot, causing the slot difference from p5 to exceed the PC_MAX_SEND_LATENCY threshold of 25.
-    unsafe {
-        assert!(c_upd_aggregate(
-            price_account.try_borrow_mut_data().unwrap().as_mut_ptr(),
-            1026,
-            14,
-        ));
-    }
+    assert!(upd_aggregate(
+        &mut load_checked::<PriceAccount>(&price_account, PC_VERSION).unwrap(),
+        1026,
+        14,
+    ));
 
     {
         let price_data = load_checked::<PriceAccount>(&price_account, PC_VERSION).unwrap();
@@ -339,13 +321,11 @@ fn test_upd_aggregate() {
         price_data.comp_[1].latest_ = p5;
     }
 
-    unsafe {
-        assert!(!c_upd_aggregate(
-            price_account.try_borrow_mut_data().unwrap().as_mut_ptr(),
-            1010,
-            15,
-        ));
-    }
+    assert!(!upd_aggregate(
+        &mut load_checked::<PriceAccount>(&price_account, PC_VERSION).unwrap(),
+        1010,
+        15,
+    ));
 
     {
         let price_data = load_checked::<PriceAccount>(&price_account, PC_VERSION).unwrap();
@@ -0,0 +1,181 @@
+// Differential tests comparing the Rust aggregation with the original C implementation, which is
+// still compiled into the native test library.
+use {
+    crate::{
+        accounts::{
+            PriceAccount,
+            PriceInfo,
+        },
+        aggregation::upd_aggregate,
+        c_oracle_header::{
+            PC_AGG_MODE_DEFAULT,
+            PC_AGG_MODE_MID,
+            PC_AGG_MODE_WEIGHTED,
+            PC_NUM_COMP,
+            PC_STATUS_TRADING,
+            PC_STATUS_UNKNOWN,
+        },
+    },
+    bytemuck::{
+        bytes_of,
+        Zeroable,
+    },
+    quickcheck::{
+        Arbitrary,
+        Gen,
+        TestResult,
+    },
+    quickcheck_macros::quickcheck,
+};
+
+mod c {
+    #[link(name = "cpyth-native")]
+    extern "C" {
+        pub fn c_upd_aggregate_pythnet(
+            _input: *mut u8,
+            clock_slot: u64,
+            clock_timestamp: i64,
+        ) -> bool;
+    }
+}
+
+const CURRENT_SLOT: u64 = 1000;
+
+#[derive(Clone, Debug)]
+struct AggregationInput {
+    quotes:      Vec<PriceInfo>,
+    min_pub:     u8,
+    max_latency: u8,
+    agg_mode:    u8,
+    prev_agg:    PriceInfo,
+}
+
+impl Arbitrary for AggregationInput {
+    fn arbitrary(g: &mut Gen) -> Self {
+        let num_quotes = usize::arbitrary(g) % (PC_NUM_COMP as usize + 1);
+        AggregationInput {
+            quotes:      (0..num_quotes).map(|_| arbitrary_quote(g)).collect(),
+            min_pub:     u8::arbitrary(g) % 8,
+            max_latency: u8::arbitrary(g) % 32,
+            // The C code only implements the default mode, and aggregates the other modes like it.
+            // Without publisher weights, so does the weighted mode.
+            agg_mode:    *g
+                .choose(&[
+                    PC_AGG_MODE_DEFAULT as u8,
+                    PC_AGG_MODE_WEIGHTED as u8,
+                    PC_AGG_MODE_MID as u8 + 1,
+                ])
+                .unwrap(),
+            prev_agg:    PriceInfo {
+                price_:           i64::arbitrary(g),
+                conf_:            u64::arbitrary(g),
+                status_:          *g.choose(&[PC_STATUS_TRADING, PC_STATUS_UNKNOWN]).unwrap(),
+                corp_act_status_: 0,
+                pub_slot_:        CURRENT_SLOT - u64::from(u8::arbitrary(g) % 4),
+            },
+        }
+    }
+}
+
+/// A quote that is valid most of the time, with occasional extreme values to exercise the
+/// overflow checks.
+fn arbitrary_quote(g: &mut Gen) -> PriceInfo {
+    let extreme = u8::arbitrary(g) % 8 == 0;
+    PriceInfo {
+        price_:           if extreme {
+            i64::arbitrary(g)
+        } else {
+            i64::from(u16::arbitrary(g)) - 1000
+        },
+        conf_:            if extreme {
+            u64::arbitrary(g)
+        } else {
+            u64::from(u8::arbitrary(g))
+        },
+        status_:          if u8::arbitrary(g) % 8 == 0 {
+            PC_STATUS_UNKNOWN
+        } else {
+            PC_STATUS_TRADING
+        },
+        corp_act_status_: if extreme {
+            u32::arbitrary(g)
+        } else {
+            u32::from(u8::arbitrary(g) % 4)
+        },
+        pub_slot_:        CURRENT_SLOT - u64::from(u8::arbitrary(g) % 40),
+    }
+}
+
+/// The settings that only exist in Rust, such as the outlier band, the price bounds or the market
+/// hours, are left disabled, since the C code doesn't implement them
+fn price_account_from(input: &AggregationInput) -> PriceAccount {
+    let mut price_account = PriceAccount::zeroed();
+    price_account.num_ = input.quotes.len() as u32;
+    price_account.min_pub_ = input.min_pub;
+    price_account.max_latency_ = input.max_latency;
+    price_account.agg_mode_ = input.agg_mode;
+    price_account.agg_ = input.prev_agg;
+    price_account.timestamp_ = 42;
+    for (component, quote) in price_account.comp_.iter_mut().zip(input.quotes.iter()) {
+        component.latest_ = *quote;
+    }
+    price_account
+}
+
+/// Whether the Rust aggregation that gave `result` succeeded with a confidence that the C code
+/// computes with an overflow (see `model_aggregate`)
+fn overflows_in_c(result: bool, price_account: &PriceAccount) -> bool {
+    result && price_account.agg_.conf_ > i64::MAX as u64
+}
+
+#[quickcheck]
+fn test_upd_aggregate_matches_c(input: AggregationInput) -> TestResult {
+    let mut rust_price_account = price_account_from(&input);
+    let mut c_price_account = price_account_from(&input);
+
+    let rust_result = upd_aggregate(&mut rust_price_account, CURRENT_SLOT + 1, 1234);
+    let c_result = unsafe {
+        c::c_upd_aggregate_pythnet(
+            (&mut c_price_account as *mut PriceAccount) as *mut u8,
+            CURRENT_SLOT + 1,
+            1234,
+        )
+    };
+    if overflows_in_c(rust_result, &rust_price_account) {
+        return TestResult::discard();
+    }
+    // The C code doesn't compute the fast aggregate
+    c_price_account.fast_agg_ = rust_price_account.fast_agg_;
+
+    TestResult::from_bool(
+        rust_result == c_result && bytes_of(&rust_price_account) == bytes_of(&c_price_account),
+    )
+}
+
+#[test]
+fn test_upd_aggregate_matches_c_repeated() {
+    // Aggregate the same account over several slots, as happens on chain
+    let mut g = Gen::new(100);
+    for _ in 0..100 {
+        let input = AggregationInput::arbitrary(&mut g);
+        let mut rust_price_account = price_account_from(&input);
+        let mut c_price_account = price_account_from(&input);
+
+        for slot in CURRENT_SLOT + 1..CURRENT_SLOT + 30 {
+            let rust_result = upd_aggregate(&mut rust_price_account, slot, slot as i64);
+            let c_result = unsafe {
+                c::c_upd_aggregate_pythnet(
+                    (&mut c_price_account as *mut PriceAccount) as *mut u8,
+                    slot,
+                    slot as i64,
+                )
+            };
+            if overflows_in_c(rust_result, &rust_price_account) {
+                break;
+            }
+            assert_eq!(rust_result, c_result);
+            c_price_account.fast_agg_ = rust_price_account.fast_agg_;
+            assert!(bytes_of(&rust_price_account) == bytes_of(&c_price_account));
+        }
+    }
+}
@@ -1,9 +1,6 @@
 use {
     crate::{
-        accounts::{
-            PermissionAccount,
-            PythAccount,
-        },
+        accounts::PermissionAccount,
         deserialize::load,
         error::OracleError,
         instruction::{
@@ -13,6 +10,7 @@ use {
         tests::pyth_simulator::{
             copy_keypair,
             PythSimulator,
+            TransactionFailure,
         },
     },
     solana_program::{
@@ -37,21 +35,17 @@ async fn test_upd_permissions() {
     let mut security_authority = Pubkey::new_unique();
 
     // Should fail because payer is not the authority
-    assert_eq!(
-        sim.upd_permissions(
-            UpdPermissionsArgs {
-                header: OracleCommand::UpdPermissions.into(),
-                master_authority,
-                data_curation_authority,
-                security_authority,
-            },
-            &copy_keypair(&sim.genesis_keypair)
-        )
-        .await
-        .unwrap_err()
-        .unwrap(),
-        OracleError::InvalidUpgradeAuthority.into()
-    );
+    sim.upd_permissions(
+        UpdPermissionsArgs {
+            header: OracleCommand::UpdPermissions.into(),
+            master_authority,
+            data_curation_authority,
+            security_authority,
+        },
+        &copy_keypair(&sim.genesis_keypair),
+    )
+    .await
+    .assert_fails_with(OracleError::InvalidUpgradeAuthority);
 
     let mut permissions_pubkey = sim
         .upd_permissions(
@@ -67,10 +61,7 @@ async fn test_upd_permissions() {
         .unwrap();
     let permission_account = sim.get_account(permissions_pubkey).await.unwrap();
 
-    assert_eq!(
-        permission_account.data.len(),
-        PermissionAccount::MINIMUM_SIZE
-    );
+    assert_eq!(permission_account.data.len(), PermissionAccount::NEW_SIZE);
     assert_eq!(
         Rent::default().minimum_balance(permission_account.data.len()),
         permission_account.lamports
@@ -93,21 +84,17 @@ async fn test_upd_permissions() {
 
 
     // Should fail because payer is not the authority
-    assert_eq!(
-        sim.upd_permissions(
-            UpdPermissionsArgs {
-                header: OracleCommand::UpdPermissions.into(),
-                master_authority,
-                data_curation_authority,
-                security_authority,
-            },
-            &copy_keypair(&sim.genesis_keypair)
-        )
-        .await
-        .unwrap_err()
-        .unwrap(),
-        OracleError::InvalidUpgradeAuthority.into()
-    );
+    sim.upd_permissions(
+        UpdPermissionsArgs {
+            header: OracleCommand::UpdPermissions.into(),
+            master_authority,
+            data_curation_authority,
+            security_authority,
+        },
+        &copy_keypair(&sim.genesis_keypair),
+    )
+    .await
+    .assert_fails_with(OracleError::InvalidUpgradeAuthority);
     permissions_pubkey = sim
         .upd_permissions(
             UpdPermissionsArgs {
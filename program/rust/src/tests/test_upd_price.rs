@@ -333,6 +333,73 @@ fn test_upd_price() {
     }
 }
 
+/// Prices and confidences at the limits of their types are stored and aggregated without
+/// overflowing
+#[test]
+fn test_upd_price_extreme_values() {
+    let mut instruction_data = [0u8; size_of::<UpdPriceArgs>()];
+    let program_id = Pubkey::new_unique();
+
+    let mut funding_setup = AccountSetup::new_funding();
+    let funding_account = funding_setup.as_account_info();
+
+    let mut price_setup = AccountSetup::new::<PriceAccount>(&program_id);
+    let mut price_account = price_setup.as_account_info();
+    price_account.is_signer = false;
+    PriceAccount::initialize(&price_account, PC_VERSION).unwrap();
+
+    {
+        let mut price_data = load_checked::<PriceAccount>(&price_account, PC_VERSION).unwrap();
+        price_data.num_ = 1;
+        price_data.comp_[0].pub_ = *funding_account.key;
+    }
+
+    let mut clock_setup = AccountSetup::new_clock();
+    let mut clock_account = clock_setup.as_account_info();
+    clock_account.is_signer = false;
+    clock_account.is_writable = false;
+
+    let mut publish = |price: i64, conf: u64, pub_slot: u64, slot: u64| {
+        populate_instruction(&mut instruction_data, price, conf, pub_slot);
+        update_clock_slot(&mut clock_account, slot);
+        process_instruction(
+            &program_id,
+            &[
+                funding_account.clone(),
+                price_account.clone(),
+                clock_account.clone(),
+            ],
+            &instruction_data,
+        )
+    };
+
+    // The confidence interval check holds for i64::MIN, which has no i64 absolute value
+    publish(i64::MIN, 1, 1, 1).unwrap();
+    publish(i64::MAX - 1, 1, 2, 3).unwrap();
+    {
+        let price_data = load_checked::<PriceAccount>(&price_account, PC_VERSION).unwrap();
+        assert_eq!(price_data.comp_[0].agg_.price_, i64::MIN);
+        assert_eq!(price_data.comp_[0].agg_.status_, PC_STATUS_TRADING);
+        // price - conf doesn't fit in an i64, so the quote isn't included
+        assert_eq!(price_data.agg_.status_, PC_STATUS_UNKNOWN);
+        assert_eq!(price_data.comp_[0].latest_.price_, i64::MAX - 1);
+    }
+
+    publish(i64::MAX - 1, u64::MAX, 4, 5).unwrap();
+    {
+        let price_data = load_checked::<PriceAccount>(&price_account, PC_VERSION).unwrap();
+        assert_eq!(price_data.agg_.price_, i64::MAX - 1);
+        assert_eq!(price_data.agg_.conf_, 1);
+        assert_eq!(price_data.agg_.status_, PC_STATUS_TRADING);
+        assert_eq!(
+            price_data.price_cumulative.price,
+            5 * i128::from(i64::MAX - 1)
+        );
+        assert_eq!(price_data.price_cumulative.conf, 5);
+        assert_eq!(price_data.comp_[0].latest_.status_, PC_STATUS_IGNORED);
+    }
+}
+
 // Create an upd_price instruction with the provided parameters
 fn populate_instruction(instruction_data: &mut [u8], price: i64, conf: u64, pub_slot: u64) {
     let mut cmd = load_mut::<UpdPriceArgs>(instruction_data).unwrap();
@@ -0,0 +1,300 @@
+use {
+    crate::{
+        accounts::{
+            PriceAccount,
+            PythAccount,
+        },
+        c_oracle_header::{
+            PC_STATUS_TRADING,
+            PC_STATUS_UNKNOWN,
+            PC_VERSION,
+        },
+        deserialize::load_checked,
+        error::OracleError,
+        instruction::{
+            CommandHeader,
+            OracleCommand,
+            UpdPriceBatchEntry,
+        },
+        processor::process_instruction,
+        tests::{
+            pyth_simulator::{
+                PythSimulator,
+                Quote,
+            },
+            test_utils::{
+                update_clock_slot,
+                AccountSetup,
+            },
+        },
+    },
+    bytemuck::bytes_of,
+    solana_program::{
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+    solana_sdk::{
+        signature::Keypair,
+        signer::Signer,
+    },
+    std::collections::HashMap,
+};
+
+#[test]
+fn test_upd_price_batch() {
+    let program_id = Pubkey::new_unique();
+
+    let mut funding_setup = AccountSetup::new_funding();
+    let funding_account = funding_setup.as_account_info();
+
+    let mut price_setup_1 = AccountSetup::new::<PriceAccount>(&program_id);
+    let mut price_account_1 = price_setup_1.as_account_info();
+    price_account_1.is_signer = false;
+    PriceAccount::initialize(&price_account_1, PC_VERSION).unwrap();
+
+    let mut price_setup_2 = AccountSetup::new::<PriceAccount>(&program_id);
+    let mut price_account_2 = price_setup_2.as_account_info();
+    price_account_2.is_signer = false;
+    PriceAccount::initialize(&price_account_2, PC_VERSION).unwrap();
+
+    for price_account in [&price_account_1, &price_account_2] {
+        let mut price_data = load_checked::<PriceAccount>(price_account, PC_VERSION).unwrap();
+        price_data.num_ = 1;
+        price_data.comp_[0].pub_ = *funding_account.key;
+    }
+
+    let mut clock_setup = AccountSetup::new_clock();
+    let mut clock_account = clock_setup.as_account_info();
+    clock_account.is_signer = false;
+    clock_account.is_writable = false;
+
+    update_clock_slot(&mut clock_account, 1);
+
+    process_instruction(
+        &program_id,
+        &[
+            funding_account.clone(),
+            clock_account.clone(),
+            price_account_1.clone(),
+            price_account_2.clone(),
+        ],
+        &populate_instruction(&[(42, 2, 1), (81, 3, 1)]),
+    )
+    .unwrap();
+
+    {
+        let price_data = load_checked::<PriceAccount>(&price_account_1, PC_VERSION).unwrap();
+        assert_eq!(price_data.comp_[0].latest_.price_, 42);
+        assert_eq!(price_data.comp_[0].latest_.conf_, 2);
+        assert_eq!(price_data.comp_[0].latest_.pub_slot_, 1);
+        assert_eq!(price_data.comp_[0].latest_.status_, PC_STATUS_TRADING);
+        assert_eq!(price_data.agg_.pub_slot_, 1);
+        assert_eq!(price_data.agg_.status_, PC_STATUS_UNKNOWN);
+    }
+
+    {
+        let price_data = load_checked::<PriceAccount>(&price_account_2, PC_VERSION).unwrap();
+        assert_eq!(price_data.comp_[0].latest_.price_, 81);
+        assert_eq!(price_data.comp_[0].latest_.conf_, 3);
+        assert_eq!(price_data.comp_[0].latest_.pub_slot_, 1);
+        assert_eq!(price_data.comp_[0].latest_.status_, PC_STATUS_TRADING);
+        assert_eq!(price_data.agg_.pub_slot_, 1);
+        assert_eq!(price_data.agg_.status_, PC_STATUS_UNKNOWN);
+    }
+
+    // The first update in a new slot triggers the aggregation of every price account
+    update_clock_slot(&mut clock_account, 2);
+
+    process_instruction(
+        &program_id,
+        &[
+            funding_account.clone(),
+            clock_account.clone(),
+            price_account_1.clone(),
+            price_account_2.clone(),
+        ],
+        &populate_instruction(&[(43, 2, 2), (82, 3, 2)]),
+    )
+    .unwrap();
+
+    {
+        let price_data = load_checked::<PriceAccount>(&price_account_1, PC_VERSION).unwrap();
+        assert_eq!(price_data.comp_[0].latest_.price_, 43);
+        assert_eq!(price_data.comp_[0].latest_.pub_slot_, 2);
+        assert_eq!(price_data.agg_.pub_slot_, 2);
+        assert_eq!(price_data.agg_.price_, 42);
+        assert_eq!(price_data.agg_.conf_, 2);
+        assert_eq!(price_data.agg_.status_, PC_STATUS_TRADING);
+    }
+
+    {
+        let price_data = load_checked::<PriceAccount>(&price_account_2, PC_VERSION).unwrap();
+        assert_eq!(price_data.comp_[0].latest_.price_, 82);
+        assert_eq!(price_data.comp_[0].latest_.pub_slot_, 2);
+        assert_eq!(price_data.agg_.pub_slot_, 2);
+        assert_eq!(price_data.agg_.price_, 81);
+        assert_eq!(price_data.agg_.conf_, 3);
+        assert_eq!(price_data.agg_.status_, PC_STATUS_TRADING);
+    }
+
+    // A stale update for any of the accounts fails the instruction
+    update_clock_slot(&mut clock_account, 3);
+
+    assert_eq!(
+        process_instruction(
+            &program_id,
+            &[
+                funding_account.clone(),
+                clock_account.clone(),
+                price_account_1.clone(),
+                price_account_2.clone(),
+            ],
+            &populate_instruction(&[(44, 2, 3), (83, 3, 2)]),
+        ),
+        Err(ProgramError::InvalidArgument)
+    );
+
+    // The number of entries must match the number of price accounts
+    assert_eq!(
+        process_instruction(
+            &program_id,
+            &[
+                funding_account.clone(),
+                clock_account.clone(),
+                price_account_1.clone(),
+                price_account_2.clone(),
+            ],
+            &populate_instruction(&[(45, 2, 4)]),
+        ),
+        Err(ProgramError::InvalidArgument)
+    );
+
+    // At least one price account is required
+    assert_eq!(
+        process_instruction(
+            &program_id,
+            &[funding_account.clone(), clock_account.clone()],
+            &populate_instruction(&[]),
+        ),
+        Err(OracleError::InvalidNumberOfAccounts.into())
+    );
+
+    // The publisher must be permissioned for every price account
+    {
+        let mut price_data = load_checked::<PriceAccount>(&price_account_2, PC_VERSION).unwrap();
+        price_data.comp_[0].pub_ = Pubkey::new_unique();
+    }
+
+    assert_eq!(
+        process_instruction(
+            &program_id,
+            &[
+                funding_account.clone(),
+                clock_account.clone(),
+                price_account_1.clone(),
+                price_account_2.clone(),
+            ],
+            &populate_instruction(&[(46, 2, 5), (84, 3, 5)]),
+        ),
+        Err(OracleError::PermissionViolation.into())
+    );
+}
+
+#[tokio::test]
+async fn test_upd_price_batch_compute_units() {
+    let mut sim = PythSimulator::new_bpf().await;
+    let publisher = Keypair::new();
+    let security_authority = Keypair::new();
+    let price_accounts = sim
+        .setup_product_fixture(&[publisher.pubkey()], security_authority.pubkey())
+        .await;
+
+    let quotes = make_quotes(&price_accounts, 100);
+
+    // A single upd_price_batch instruction is meant to be cheaper than one upd_price per price
+    // account, which this test checks once it is run against the BPF program
+    let instructions = sim
+        .upd_price_instructions(&publisher, &price_accounts, &quotes)
+        .await
+        .unwrap();
+    let separate_compute_units = sim
+        .simulate_compute_units(&instructions, &vec![&publisher], &publisher)
+        .await
+        .unwrap();
+
+    let batch_instruction = sim
+        .upd_price_batch_instruction(&publisher, &price_accounts, &quotes)
+        .await
+        .unwrap();
+    let batch_compute_units = sim
+        .simulate_compute_units(&[batch_instruction], &vec![&publisher], &publisher)
+        .await
+        .unwrap();
+
+    assert!(batch_compute_units < separate_compute_units);
+
+    sim.upd_price_batch_ix(&publisher, &price_accounts, &quotes)
+        .await
+        .unwrap();
+
+    for price in price_accounts.values() {
+        let price_data = sim
+            .get_account_data_as::<PriceAccount>(*price)
+            .await
+            .unwrap();
+        assert_eq!(price_data.comp_[0].latest_.price_, 100);
+        assert_eq!(price_data.comp_[0].latest_.conf_, 5);
+        assert_eq!(price_data.comp_[0].latest_.status_, PC_STATUS_TRADING);
+        assert_eq!(price_data.comp_[0].agg_.status_, PC_STATUS_UNKNOWN);
+    }
+
+    sim.warp_to_slot(2).await.unwrap();
+
+    let new_quotes = make_quotes(&price_accounts, 200);
+    sim.upd_price_batch_ix(&publisher, &price_accounts, &new_quotes)
+        .await
+        .unwrap();
+
+    for price in price_accounts.values() {
+        let price_data = sim
+            .get_account_data_as::<PriceAccount>(*price)
+            .await
+            .unwrap();
+        assert_eq!(price_data.comp_[0].latest_.price_, 200);
+        assert_eq!(price_data.comp_[0].agg_.price_, 100);
+        assert_eq!(price_data.comp_[0].agg_.conf_, 5);
+        assert_eq!(price_data.comp_[0].agg_.status_, PC_STATUS_TRADING);
+    }
+}
+
+// Create an upd_price_batch instruction from a list of (price, conf, pub_slot) updates
+fn populate_instruction(updates: &[(i64, u64, u64)]) -> Vec<u8> {
+    let cmd: CommandHeader = OracleCommand::UpdPriceBatch.into();
+    let mut instruction_data = bytes_of(&cmd).to_vec();
+    for (price, confidence, publishing_slot) in updates {
+        instruction_data.extend_from_slice(bytes_of(&UpdPriceBatchEntry {
+            status:          PC_STATUS_TRADING,
+            unused_:         0,
+            price:           *price,
+            confidence:      *confidence,
+            publishing_slot: *publishing_slot,
+        }));
+    }
+    instruction_data
+}
+
+fn make_quotes(price_accounts: &HashMap<String, Pubkey>, price: i64) -> HashMap<String, Quote> {
+    price_accounts
+        .keys()
+        .map(|key| {
+            (
+                key.to_string(),
+                Quote {
+                    price,
+                    confidence: 5,
+                    status: PC_STATUS_TRADING,
+                },
+            )
+        })
+        .collect()
+}
@@ -19,15 +19,25 @@ use {
             UpdPriceArgs,
         },
         processor::process_instruction,
-        tests::test_utils::{
-            update_clock_slot,
-            AccountSetup,
+        tests::{
+            pyth_simulator::{
+                PythSimulator,
+                Quote,
+            },
+            test_utils::{
+                update_clock_slot,
+                AccountSetup,
+            },
         },
     },
     solana_program::{
         program_error::ProgramError,
         pubkey::Pubkey,
     },
+    solana_sdk::{
+        signature::Keypair,
+        signer::Signer,
+    },
     std::mem::size_of,
 };
 
@@ -169,6 +179,105 @@ fn test_upd_price_no_fail_on_error_no_fail_on_error() {
     }
 }
 
+#[tokio::test]
+async fn test_upd_price_no_fail_on_error_logs() {
+    let mut sim = PythSimulator::new_bpf().await;
+    let publisher = Keypair::new();
+    let security_authority = Keypair::new();
+    let price_accounts = sim
+        .setup_product_fixture(&[publisher.pubkey()], security_authority.pubkey())
+        .await;
+    let btc_price = price_accounts["BTC"];
+    let eth_price = price_accounts["ETH"];
+
+    sim.upd_price(
+        &publisher,
+        btc_price,
+        Quote {
+            price:      100,
+            confidence: 5,
+            status:     PC_STATUS_TRADING,
+        },
+    )
+    .await
+    .unwrap();
+
+    // The BTC update is stale because the publisher already published in this slot, but it must
+    // not prevent the ETH update in the same transaction from being applied
+    let instructions = vec![
+        sim.upd_price_no_fail_on_error_instruction(
+            &publisher,
+            btc_price,
+            &Quote {
+                price:      200,
+                confidence: 6,
+                status:     PC_STATUS_TRADING,
+            },
+        )
+        .await
+        .unwrap(),
+        sim.upd_price_no_fail_on_error_instruction(
+            &publisher,
+            eth_price,
+            &Quote {
+                price:      300,
+                confidence: 7,
+                status:     PC_STATUS_TRADING,
+            },
+        )
+        .await
+        .unwrap(),
+    ];
+
+    let logs = sim
+        .simulate_logs(&instructions, &vec![&publisher], &publisher)
+        .await
+        .unwrap();
+    assert_eq!(
+        logs.iter()
+            .filter(|log| log.contains("upd_price_no_fail_on_error: rejected: InvalidArgument"))
+            .count(),
+        1
+    );
+
+    sim.process_ixs(&instructions, &vec![&publisher], &publisher)
+        .await
+        .unwrap();
+
+    let btc_price_data = sim
+        .get_account_data_as::<PriceAccount>(btc_price)
+        .await
+        .unwrap();
+    assert_eq!(btc_price_data.comp_[0].latest_.price_, 100);
+    assert_eq!(btc_price_data.comp_[0].latest_.conf_, 5);
+
+    let eth_price_data = sim
+        .get_account_data_as::<PriceAccount>(eth_price)
+        .await
+        .unwrap();
+    assert_eq!(eth_price_data.comp_[0].latest_.price_, 300);
+    assert_eq!(eth_price_data.comp_[0].latest_.conf_, 7);
+
+    // Successful updates don't log anything
+    sim.warp_to_slot(2).await.unwrap();
+    let instruction = sim
+        .upd_price_no_fail_on_error_instruction(
+            &publisher,
+            btc_price,
+            &Quote {
+                price:      400,
+                confidence: 8,
+                status:     PC_STATUS_TRADING,
+            },
+        )
+        .await
+        .unwrap();
+    let logs = sim
+        .simulate_logs(&[instruction], &vec![&publisher], &publisher)
+        .await
+        .unwrap();
+    assert!(!logs.iter().any(|log| log.contains("rejected")));
+}
 
 // Create an upd_price_no_fail_on_error or upd_price instruction with the provided parameters
 fn populate_instruction(
@@ -0,0 +1,105 @@
+use {
+    crate::{
+        accounts::PriceAccount,
+        c_oracle_header::PC_STATUS_TRADING,
+        error::OracleError,
+        tests::pyth_simulator::{
+            PythSimulator,
+            Quote,
+        },
+    },
+    solana_program::program_error::ProgramError,
+    solana_sdk::{
+        signature::Keypair,
+        signer::Signer,
+    },
+};
+
+fn quote(price: i64) -> Quote {
+    Quote {
+        price,
+        confidence: 1,
+        status: PC_STATUS_TRADING,
+    }
+}
+
+#[tokio::test]
+async fn test_upd_price_scatter() {
+    let mut sim = PythSimulator::new_bpf().await;
+    let publisher = Keypair::new();
+    let security_authority = Keypair::new();
+    let price_accounts = sim
+        .setup_product_fixture(&[publisher.pubkey()], security_authority.pubkey())
+        .await;
+    let (ltc, eth) = (price_accounts["LTC"], price_accounts["ETH"]);
+    sim.set_min_pub(ltc, 1).await.unwrap();
+    sim.set_min_pub(eth, 1).await.unwrap();
+    // A price account the publisher doesn't publish to
+    let (other_price_keypair, _) = sim.setup_feed_with_publishers(1).await;
+    let other_price = other_price_keypair.pubkey();
+
+    // The update of the other price account is skipped, and the others are applied
+    let results = sim
+        .upd_price_scatter(
+            &publisher,
+            &[
+                (ltc, quote(100)),
+                (other_price, quote(150)),
+                (eth, quote(200)),
+            ],
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        results,
+        vec![
+            Ok(()),
+            Err(ProgramError::from(OracleError::PermissionViolation)),
+            Ok(()),
+        ]
+    );
+    for (price_account, price) in [(ltc, 100), (eth, 200)] {
+        let price_data = sim
+            .get_account_data_as::<PriceAccount>(price_account)
+            .await
+            .unwrap();
+        assert_eq!(price_data.comp_[0].latest_.price_, price);
+        assert_eq!(price_data.comp_[0].latest_.status_, PC_STATUS_TRADING);
+    }
+    let other_price_data = sim
+        .get_account_data_as::<PriceAccount>(other_price)
+        .await
+        .unwrap();
+    assert_eq!(other_price_data.comp_[0].latest_.price_, 0);
+
+    // The instruction succeeds even if no price account is updated
+    let results = sim
+        .upd_price_scatter(&publisher, &[(ltc, quote(101)), (eth, quote(201))])
+        .await
+        .unwrap();
+    assert_eq!(
+        results,
+        vec![
+            Err(ProgramError::InvalidArgument),
+            Err(ProgramError::InvalidArgument)
+        ]
+    );
+    let price_data = sim.get_account_data_as::<PriceAccount>(eth).await.unwrap();
+    assert_eq!(price_data.comp_[0].latest_.price_, 200);
+
+    // The next slot aggregates the applied updates
+    sim.warp_to_slot(2).await.unwrap();
+    let results = sim
+        .upd_price_scatter(&publisher, &[(eth, quote(202)), (ltc, quote(102))])
+        .await
+        .unwrap();
+    assert_eq!(results, vec![Ok(()), Ok(())]);
+    for (price_account, price) in [(ltc, 100), (eth, 200)] {
+        let price_data = sim
+            .get_account_data_as::<PriceAccount>(price_account)
+            .await
+            .unwrap();
+        assert_eq!(price_data.agg_.status_, PC_STATUS_TRADING);
+        assert_eq!(price_data.agg_.price_, price);
+    }
+}
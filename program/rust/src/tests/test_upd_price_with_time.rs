@@ -0,0 +1,104 @@
+use {
+    crate::{
+        accounts::PriceAccount,
+        c_oracle_header::{
+            MAX_PUBLISH_TIME_DRIFT,
+            PC_STATUS_TRADING,
+        },
+        error::OracleError,
+        tests::pyth_simulator::{
+            PythSimulator,
+            Quote,
+            TransactionFailure,
+        },
+    },
+    solana_sdk::signer::Signer,
+};
+
+const NOW: i64 = 1_700_000_000;
+
+fn quote(price: i64) -> Quote {
+    Quote {
+        price,
+        confidence: 1,
+        status: PC_STATUS_TRADING,
+    }
+}
+
+#[tokio::test]
+async fn test_upd_price_with_time() {
+    let mut sim = PythSimulator::new().await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(2).await;
+    let price = price_keypair.pubkey();
+    sim.set_min_pub(price, 1).await.unwrap();
+    sim.set_clock_timestamp(NOW).await.unwrap();
+
+    sim.upd_price_with_time(&publishers[0], price, quote(100), NOW - 10)
+        .await
+        .unwrap();
+    sim.upd_price(&publishers[1], price, quote(110))
+        .await
+        .unwrap();
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(price_data.comp_[0].latest_.price_, 100);
+    assert_eq!(price_data.publisher_stats_[0].publish_time_, NOW - 10);
+    assert_eq!(price_data.publisher_stats_[1].publish_time_, 0);
+
+    // The publish time can be slightly ahead of the clock
+    sim.warp_to_slot(2).await.unwrap();
+    sim.set_clock_timestamp(NOW + 1).await.unwrap();
+    sim.upd_price_with_time(
+        &publishers[1],
+        price,
+        quote(120),
+        NOW + 1 + MAX_PUBLISH_TIME_DRIFT,
+    )
+    .await
+    .unwrap();
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(price_data.agg_.status_, PC_STATUS_TRADING);
+    assert_eq!(price_data.agg_.price_, 105);
+    assert_eq!(price_data.agg_pub_time_, NOW - 10);
+    assert_eq!(
+        price_data.publisher_stats_[1].publish_time_,
+        NOW + 1 + MAX_PUBLISH_TIME_DRIFT
+    );
+
+    // But not too far
+    sim.upd_price_with_time(
+        &publishers[0],
+        price,
+        quote(130),
+        NOW + 2 + MAX_PUBLISH_TIME_DRIFT,
+    )
+    .await
+    .assert_fails_with(OracleError::PublishTimeInFuture);
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(price_data.comp_[0].latest_.price_, 100);
+    assert_eq!(price_data.publisher_stats_[0].publish_time_, NOW - 10);
+
+    // A price update without a publish time keeps the publish time of the publisher
+    sim.upd_price(&publishers[0], price, quote(130))
+        .await
+        .unwrap();
+    sim.warp_to_slot(3).await.unwrap();
+    sim.upd_price(&publishers[0], price, quote(130))
+        .await
+        .unwrap();
+    let price_data = sim
+        .get_account_data_as::<PriceAccount>(price)
+        .await
+        .unwrap();
+    assert_eq!(price_data.publisher_stats_[0].publish_time_, NOW - 10);
+    assert_eq!(price_data.agg_.price_, 125);
+    assert_eq!(price_data.agg_pub_time_, NOW - 10);
+}
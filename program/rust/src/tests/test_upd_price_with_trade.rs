@@ -0,0 +1,156 @@
+use {
+    crate::{
+        accounts::{
+            LastTrade,
+            PriceAccount,
+        },
+        c_oracle_header::PC_STATUS_TRADING,
+        client,
+        instruction::PriceUpdate,
+        tests::pyth_simulator::{
+            PythSimulator,
+            Quote,
+        },
+    },
+    solana_sdk::signer::Signer,
+};
+
+fn quote(price: i64) -> Quote {
+    Quote {
+        price,
+        confidence: 1,
+        status: PC_STATUS_TRADING,
+    }
+}
+
+fn trade(price: i64, size: u64, pub_slot: u64, venue_id: u32) -> LastTrade {
+    LastTrade {
+        price_:           price,
+        size_:            size,
+        pub_slot_:        pub_slot,
+        venue_id_:        venue_id,
+        heartbeat_slots_: 0,
+    }
+}
+
+/// Publishers sending quotes with and without trades update the same price account
+#[tokio::test]
+async fn test_upd_price_with_trade() {
+    let mut sim = PythSimulator::new().await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(3).await;
+    let price = price_keypair.pubkey();
+    sim.set_min_pub(price, 1).await.unwrap();
+    let slot = sim.get_clock().await.unwrap().slot;
+
+    // A trade, a quote without a trade and a trade of size 0, which is no trade
+    sim.upd_price_with_trade(&publishers[0], price, quote(100), 101, 5, 1)
+        .await
+        .unwrap();
+    sim.upd_price(&publishers[1], price, quote(100))
+        .await
+        .unwrap();
+    sim.upd_price_with_trade(&publishers[2], price, quote(100), 1_000, 0, 2)
+        .await
+        .unwrap();
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    for component in price_data.comp_[..3].iter() {
+        assert_eq!(component.latest_.price_, 100);
+    }
+    assert_eq!(
+        price_data.publisher_stats_[0].last_trade_,
+        trade(101, 5, slot, 1)
+    );
+    assert_eq!(
+        price_data.publisher_stats_[1].last_trade_,
+        trade(0, 0, 0, 0)
+    );
+    assert_eq!(
+        price_data.publisher_stats_[2].last_trade_,
+        trade(0, 0, 0, 0)
+    );
+    assert_eq!(price_data.get_last_trade(), Some(&trade(101, 5, slot, 1)));
+
+    // A quote without a trade keeps the last trade of the publisher, the fresher trade of
+    // another publisher becomes the last trade of the feed. The trades don't change the aggregate.
+    sim.warp_to_slot(2).await.unwrap();
+    sim.upd_price(&publishers[0], price, quote(100))
+        .await
+        .unwrap();
+    sim.upd_price_with_trade(&publishers[1], price, quote(100), 1_000, 7, 2)
+        .await
+        .unwrap();
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(
+        (price_data.agg_.price_, price_data.agg_.status_),
+        (100, PC_STATUS_TRADING)
+    );
+    assert_eq!(
+        price_data.publisher_stats_[0].last_trade_,
+        trade(101, 5, slot, 1)
+    );
+    assert_eq!(
+        price_data.publisher_stats_[1].last_trade_,
+        trade(1_000, 7, 2, 2)
+    );
+    assert_eq!(price_data.get_last_trade(), Some(&trade(1_000, 7, 2, 2)));
+
+    // Trades of the same slot, the first publisher's is the last trade of the feed. The
+    // instruction built by the client uses the Borsh layout.
+    sim.warp_to_slot(3).await.unwrap();
+    let update = PriceUpdate {
+        status:          PC_STATUS_TRADING,
+        price:           100,
+        confidence:      1,
+        publishing_slot: 3,
+    };
+    let instruction = client::upd_price_with_trade(
+        &sim.program_id(),
+        &publishers[2].pubkey(),
+        &price,
+        &update,
+        99,
+        3,
+        4,
+    );
+    sim.process_ixs(&[instruction], &vec![&publishers[2]], &publishers[2])
+        .await
+        .unwrap();
+    sim.upd_price_with_trade(&publishers[0], price, quote(100), 102, 1, 1)
+        .await
+        .unwrap();
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(
+        price_data.publisher_stats_[2].last_trade_,
+        trade(99, 3, 3, 4)
+    );
+    assert_eq!(price_data.get_last_trade(), Some(&trade(102, 1, 3, 1)));
+
+    // The trade prices are rescaled with the other prices
+    sim.set_exponent(price, -4).await.unwrap();
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(price_data.publisher_stats_[0].last_trade_.price_, 10);
+    assert_eq!(price_data.publisher_stats_[1].last_trade_.price_, 100);
+    assert_eq!(price_data.publisher_stats_[2].last_trade_.price_, 9);
+}
+
+/// A price account has no last trade until a publisher attaches one
+#[tokio::test]
+async fn test_upd_price_with_trade_first_trade() {
+    let mut sim = PythSimulator::new().await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(1).await;
+    let price = price_keypair.pubkey();
+    sim.set_min_pub(price, 1).await.unwrap();
+
+    let slot = sim.get_clock().await.unwrap().slot;
+    sim.upd_price(&publishers[0], price, quote(100))
+        .await
+        .unwrap();
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(price_data.get_last_trade(), None);
+
+    sim.upd_price_with_trade(&publishers[0], price, quote(100), 101, 5, 1)
+        .await
+        .unwrap();
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(price_data.get_last_trade(), Some(&trade(101, 5, slot, 1)));
+}
@@ -12,6 +12,7 @@ use {
             PC_VERSION,
         },
         deserialize::{
+            load,
             load_checked,
             load_mut,
         },
@@ -20,12 +21,20 @@ use {
             OracleCommand,
         },
         processor::process_instruction,
-        tests::test_utils::AccountSetup,
+        tests::{
+            pyth_simulator::{
+                PythSimulator,
+                TransactionFailure,
+            },
+            test_utils::AccountSetup,
+        },
     },
     solana_program::{
+        instruction::InstructionError,
         program_error::ProgramError,
         pubkey::Pubkey,
     },
+    solana_sdk::signer::Signer,
     std::mem::size_of,
 };
 
@@ -54,7 +63,7 @@ fn test_upd_product() {
         permissions_account_data.security_authority = *funding_account.key;
     }
 
-    let kvs = ["foo", "barz"];
+    let kvs = ["base", "BTC"];
     let size = populate_instruction(&mut instruction_data, &kvs);
     assert!(process_instruction(
         &program_id,
@@ -124,6 +133,76 @@ fn test_upd_product() {
     assert!(account_has_key_values(&product_account, &kvs).unwrap_or(false));
 }
 
+#[tokio::test]
+async fn test_upd_product_resize_metadata() {
+    let mut sim = PythSimulator::new().await;
+    let mapping_keypair = sim.init_mapping().await.unwrap();
+    let product_keypair = sim.add_product(&mapping_keypair).await.unwrap();
+    let product = product_keypair.pubkey();
+
+    let attributes = |pairs: &[(&str, &str)]| -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    };
+
+    let initial = [("symbol", "Crypto.BTC/USD"), ("asset_type", "Crypto")];
+    sim.upd_product(&product_keypair, &initial).await.unwrap();
+    assert_eq!(
+        sim.get_product_attributes(product).await,
+        attributes(&initial)
+    );
+
+    // Shrinking clears the rest of the previous metadata
+    let shrunk = [("symbol", "BTC")];
+    sim.upd_product(&product_keypair, &shrunk).await.unwrap();
+    assert_eq!(
+        sim.get_product_attributes(product).await,
+        attributes(&shrunk)
+    );
+    {
+        let account = sim.get_account(product).await.unwrap();
+        let product_data = load::<ProductAccount>(&account.data).unwrap();
+        assert_eq!(
+            product_data.header.size,
+            ProductAccount::INITIAL_SIZE + 1 + 6 + 1 + 3
+        );
+        assert!(account.data[product_data.header.size as usize..]
+            .iter()
+            .all(|byte| *byte == 0));
+    }
+
+    // Growing up to the full capacity of the account
+    let capacity = PC_PROD_ACC_SIZE as usize - size_of::<ProductAccount>();
+    let long_value = "x".repeat(255);
+    let filler = "y".repeat(capacity - (1 + 11 + 1 + 255) - (1 + 5 + 1));
+    let full = [
+        ("description", long_value.as_str()),
+        ("tenor", filler.as_str()),
+    ];
+    sim.upd_product(&product_keypair, &full).await.unwrap();
+    assert_eq!(sim.get_product_attributes(product).await, attributes(&full));
+    {
+        let account = sim.get_account(product).await.unwrap();
+        let product_data = load::<ProductAccount>(&account.data).unwrap();
+        assert_eq!(product_data.header.size, PC_PROD_ACC_SIZE);
+    }
+
+    // Metadata that doesn't fit in the account is rejected and leaves the account untouched
+    let overflow_filler = format!("{filler}y");
+    sim.upd_product(
+        &product_keypair,
+        &[
+            ("description", long_value.as_str()),
+            ("tenor", overflow_filler.as_str()),
+        ],
+    )
+    .await
+    .assert_fails_with(InstructionError::InvalidArgument);
+    assert_eq!(sim.get_product_attributes(product).await, attributes(&full));
+}
+
 // Create an upd_product instruction that sets the product metadata to strings
 fn populate_instruction(instruction_data: &mut [u8], strings: &[&str]) -> usize {
     {
@@ -0,0 +1,123 @@
+use {
+    crate::{
+        accounts::PriceAccount,
+        c_oracle_header::PC_STATUS_TRADING,
+        migrations::PRICE_ACCOUNT_LAYOUT_VERSION,
+        tests::pyth_simulator::{
+            InstructionLayout,
+            PythSimulator,
+            Quote,
+        },
+    },
+    solana_program::pubkey::Pubkey,
+    solana_sdk::{
+        signature::Keypair,
+        signer::Signer,
+    },
+    std::{
+        env,
+        fs,
+        path::PathBuf,
+    },
+};
+
+/// Build of a previous release of the program to upgrade from in
+/// `test_upgrade_program_from_legacy_binary`, the test is skipped when it is not set
+const LEGACY_BINARY_VAR: &str = "PYTH_ORACLE_LEGACY_BINARY";
+
+fn quotes(price: i64) -> [Quote; 2] {
+    [price, price + 10].map(|price| Quote {
+        price,
+        confidence: 1,
+        status: PC_STATUS_TRADING,
+    })
+}
+
+/// Publish in the current slot and in the next one, and check that the second round aggregated the
+/// first one.
+async fn assert_publishes(sim: &mut PythSimulator, price: Pubkey, publishers: &[Keypair]) {
+    sim.publish_all(price, publishers, &quotes(200))
+        .await
+        .unwrap();
+    sim.advance_slots(1).await.unwrap();
+    sim.publish_all(price, publishers, &quotes(300))
+        .await
+        .unwrap();
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(price_data.agg_.status_, PC_STATUS_TRADING);
+    assert_eq!(price_data.agg_.price_, 205);
+}
+
+#[tokio::test]
+async fn test_upgrade_program() {
+    let mut sim = PythSimulator::new_bpf().await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(2).await;
+    let price = price_keypair.pubkey();
+    sim.set_min_pub(price, 1).await.unwrap();
+
+    // Part of the state is written with instructions in the legacy layout
+    sim.set_instruction_layout(InstructionLayout::Legacy);
+    sim.publish_all(price, &publishers, &quotes(100))
+        .await
+        .unwrap();
+    sim.advance_slots(1).await.unwrap();
+    sim.publish_all(price, &publishers, &quotes(100))
+        .await
+        .unwrap();
+    sim.set_instruction_layout(InstructionLayout::Borsh);
+
+    // The accounts are untouched by the upgrade
+    let data = sim.get_account(price).await.unwrap().data;
+    sim.upgrade_program(&PythSimulator::current_program_binary())
+        .await
+        .unwrap();
+    assert_eq!(sim.get_account(price).await.unwrap().data, data);
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(price_data.agg_.price_, 105);
+
+    assert_publishes(&mut sim, price, &publishers).await;
+}
+
+#[tokio::test]
+async fn test_upgrade_program_invalid_binary() {
+    let mut sim = PythSimulator::new_bpf().await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(2).await;
+    let price = price_keypair.pubkey();
+    sim.set_min_pub(price, 1).await.unwrap();
+
+    let binary = env::temp_dir().join(format!("pyth_oracle_invalid_{}.so", Pubkey::new_unique()));
+    fs::write(&binary, [0xAB; 1024]).unwrap();
+    let result = sim.upgrade_program(&binary).await;
+    fs::remove_file(&binary).unwrap();
+    assert!(result.is_err());
+
+    // The program is still the previous one
+    assert_publishes(&mut sim, price, &publishers).await;
+}
+
+/// Upgrade from a previous release of the program, e.g.
+/// `PYTH_ORACLE_LEGACY_BINARY=path/to/pyth_oracle.so cargo test test_upgrade_program`
+#[tokio::test]
+async fn test_upgrade_program_from_legacy_binary() {
+    let binary = match env::var_os(LEGACY_BINARY_VAR) {
+        Some(binary) => PathBuf::from(binary),
+        None => return,
+    };
+    let mut sim = PythSimulator::new_with_program(&binary, InstructionLayout::Legacy).await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(2).await;
+    let price = price_keypair.pubkey();
+    sim.set_min_pub(price, 1).await.unwrap();
+    sim.publish_all(price, &publishers, &quotes(100))
+        .await
+        .unwrap();
+
+    sim.upgrade_program(&PythSimulator::current_program_binary())
+        .await
+        .unwrap();
+    sim.set_instruction_layout(InstructionLayout::Borsh);
+
+    // The price account is migrated by its first update
+    assert_publishes(&mut sim, price, &publishers).await;
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    assert_eq!(price_data.layout_version_, PRICE_ACCOUNT_LAYOUT_VERSION);
+}
@@ -0,0 +1,201 @@
+use {
+    crate::{
+        accounts::PriceAccount,
+        aggregation::upd_aggregate_with_weights,
+        c_oracle_header::{
+            PC_AGG_MODE_DEFAULT,
+            PC_AGG_MODE_WEIGHTED,
+            PC_NUM_COMP,
+            PC_STATUS_TRADING,
+        },
+    },
+    bytemuck::Zeroable,
+    quickcheck::{
+        Arbitrary,
+        TestResult,
+    },
+    quickcheck_macros::quickcheck,
+};
+
+const CURRENT_SLOT: u64 = 1000;
+
+#[derive(Clone, Debug, Copy)]
+pub struct WeightedQuote {
+    price:  i64,
+    conf:   u64,
+    weight: u32,
+}
+
+impl Arbitrary for WeightedQuote {
+    fn arbitrary(g: &mut quickcheck::Gen) -> Self {
+        WeightedQuote {
+            price:  i64::from(u16::arbitrary(g)),
+            conf:   u64::from(u8::arbitrary(g)),
+            weight: u32::from(u8::arbitrary(g) % 4) + 1,
+        }
+    }
+}
+
+/// Run the aggregation on `quotes` and return the aggregate price, confidence and status
+fn aggregate(quotes: &[WeightedQuote], agg_mode: u32) -> (i64, u64, u32) {
+    let mut price_account = PriceAccount::zeroed();
+    price_account.agg_mode_ = agg_mode as u8;
+    price_account.num_ = quotes.len() as u32;
+    let mut weights = [0; PC_NUM_COMP as usize];
+    for ((component, weight), quote) in price_account
+        .comp_
+        .iter_mut()
+        .zip(weights.iter_mut())
+        .zip(quotes.iter())
+    {
+        component.latest_.price_ = quote.price;
+        component.latest_.conf_ = quote.conf;
+        component.latest_.status_ = PC_STATUS_TRADING;
+        *weight = quote.weight;
+        component.latest_.pub_slot_ = CURRENT_SLOT;
+    }
+
+    upd_aggregate_with_weights(&mut price_account, &weights, CURRENT_SLOT + 1, 0);
+
+    (
+        price_account.agg_.price_,
+        price_account.agg_.conf_,
+        price_account.agg_.status_,
+    )
+}
+
+/// With the same weight for every publisher, the weighted mode matches the default mode.
+#[quickcheck]
+fn test_weighted_aggregation_uniform_weights(
+    quotes: Vec<WeightedQuote>,
+    weight: u32,
+) -> TestResult {
+    if quotes.len() > PC_NUM_COMP as usize {
+        return TestResult::discard();
+    }
+
+    let uniform_quotes: Vec<WeightedQuote> = quotes
+        .iter()
+        .map(|quote| WeightedQuote { weight, ..*quote })
+        .collect();
+
+    TestResult::from_bool(
+        aggregate(&uniform_quotes, PC_AGG_MODE_WEIGHTED)
+            == aggregate(&uniform_quotes, PC_AGG_MODE_DEFAULT),
+    )
+}
+
+/// A publisher with weight `n` counts like `n` publishers with the same quote in the default mode.
+#[quickcheck]
+fn test_weighted_aggregation_replicated_quotes(quotes: Vec<WeightedQuote>) -> TestResult {
+    let replicated_quotes: Vec<WeightedQuote> = quotes
+        .iter()
+        .flat_map(|quote| std::iter::repeat(*quote).take(quote.weight as usize))
+        .collect();
+
+    if replicated_quotes.len() > PC_NUM_COMP as usize {
+        return TestResult::discard();
+    }
+
+    TestResult::from_bool(
+        aggregate(&quotes, PC_AGG_MODE_WEIGHTED)
+            == aggregate(&replicated_quotes, PC_AGG_MODE_DEFAULT),
+    )
+}
+
+/// The expected weighted aggregate of non-empty `quotes`, computed on the explicit multiset of
+/// the prices of every quote repeated by its weight, independently of the program's selection.
+fn expected_aggregate(quotes: &[WeightedQuote]) -> (i64, u64) {
+    let mut prices: Vec<i64> = quotes
+        .iter()
+        .flat_map(|quote| {
+            let conf = quote.conf as i64;
+            [quote.price - conf, quote.price, quote.price + conf]
+                .into_iter()
+                .cycle()
+                .take(3 * quote.weight.max(1) as usize)
+        })
+        .collect();
+    prices.sort();
+
+    let len = prices.len();
+    let p25 = prices[len / 4];
+    let p50 = if len % 2 == 1 {
+        prices[len / 2]
+    } else {
+        (i128::from(prices[len / 2 - 1]) + i128::from(prices[len / 2])).div_euclid(2) as i64
+    };
+    let p75 = prices[len - 1 - len / 4];
+    (p50, p50.abs_diff(p25).max(p75.abs_diff(p50)))
+}
+
+#[quickcheck]
+fn test_weighted_aggregation_matches_multiset(quotes: Vec<WeightedQuote>) -> TestResult {
+    if quotes.is_empty() || quotes.len() > PC_NUM_COMP as usize {
+        return TestResult::discard();
+    }
+    let (price, conf) = expected_aggregate(&quotes);
+    // The aggregation fails without a confidence
+    if conf == 0 {
+        return TestResult::discard();
+    }
+
+    TestResult::from_bool(
+        aggregate(&quotes, PC_AGG_MODE_WEIGHTED) == (price, conf, PC_STATUS_TRADING),
+    )
+}
+
+#[test]
+fn test_weighted_aggregation() {
+    let quotes = [
+        WeightedQuote {
+            price:  100,
+            conf:   10,
+            weight: 1,
+        },
+        WeightedQuote {
+            price:  200,
+            conf:   10,
+            weight: 1,
+        },
+        WeightedQuote {
+            price:  300,
+            conf:   10,
+            weight: 10,
+        },
+    ];
+
+    assert_eq!(
+        aggregate(&quotes, PC_AGG_MODE_DEFAULT),
+        (200, 90, PC_STATUS_TRADING)
+    );
+    // The heavy publisher dominates the weighted median
+    assert_eq!(
+        aggregate(&quotes, PC_AGG_MODE_WEIGHTED),
+        (300, 10, PC_STATUS_TRADING)
+    );
+
+    let quotes = [
+        WeightedQuote {
+            price:  100,
+            conf:   10,
+            weight: 2,
+        },
+        WeightedQuote {
+            price:  200,
+            conf:   20,
+            weight: 1,
+        },
+        WeightedQuote {
+            price:  300,
+            conf:   30,
+            weight: 1,
+        },
+    ];
+    // The p25, p50 and p75 of 90, 90, 100, 100, 110, 110, 180, 200, 220, 270, 300 and 330 are
+    // 100, 145 and 220
+    assert_eq!(
+        aggregate(&quotes, PC_AGG_MODE_WEIGHTED),
+        (145, 75, PC_STATUS_TRADING)
+    );
+}
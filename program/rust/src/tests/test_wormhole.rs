@@ -0,0 +1,169 @@
+use {
+    crate::{
+        accounts::PriceAccount,
+        c_oracle_header::PC_STATUS_TRADING,
+        client,
+        error::OracleError,
+        instruction::PriceUpdate,
+        tests::pyth_simulator::{
+            PythSimulator,
+            Quote,
+            TransactionFailure,
+        },
+        wormhole::{
+            PriceAttestation,
+            PRICE_ATTESTATION_MAGIC,
+            PRICE_ATTESTATION_VERSION,
+            WORMHOLE_CONSISTENCY_LEVEL,
+            WORMHOLE_PROGRAM_ID,
+        },
+    },
+    solana_program::pubkey::Pubkey,
+    solana_sdk::signer::Signer,
+};
+
+const FEE: u64 = 100;
+
+fn quote(price: i64) -> Quote {
+    Quote {
+        price,
+        confidence: 5,
+        status: PC_STATUS_TRADING,
+    }
+}
+
+#[tokio::test]
+async fn test_wormhole_attestation() {
+    let mut sim = PythSimulator::new().await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(1).await;
+    let price = price_keypair.pubkey();
+    sim.set_min_pub(price, 1).await.unwrap();
+    sim.init_mock_wormhole(FEE).await;
+    let fee_collector = client::wormhole_accounts(&sim.program_id(), &price)[4].pubkey;
+    let fee_collector_lamports = sim.get_account(fee_collector).await.unwrap().lamports;
+
+    // Nothing is attested without a successful aggregation
+    sim.upd_price_with_wormhole(&publishers[0], price, quote(100))
+        .await
+        .unwrap();
+    assert_eq!(sim.get_wormhole_message(price).await, None);
+
+    sim.warp_to_slot(2).await.unwrap();
+    sim.upd_price_with_wormhole(&publishers[0], price, quote(110))
+        .await
+        .unwrap();
+    let price_data = sim.get_pyth_account::<PriceAccount>(price).await;
+    let message = sim.get_wormhole_message(price).await.unwrap();
+    assert_eq!(
+        message.emitter,
+        client::get_wormhole_emitter_pubkey(&sim.program_id())
+    );
+    assert_eq!(message.sequence, 0);
+    assert_eq!(message.nonce, 0);
+    assert_eq!(message.consistency_level, WORMHOLE_CONSISTENCY_LEVEL);
+    assert_eq!(
+        PriceAttestation::from_bytes(&message.payload),
+        Some(PriceAttestation {
+            price_account: price,
+            feed_index:    price_data.feed_index_,
+            price:         100,
+            conf:          5,
+            exponent:      -5,
+            publish_time:  price_data.timestamp_,
+            slot:          2,
+            status:        PC_STATUS_TRADING,
+        })
+    );
+    assert_eq!(
+        sim.get_account(fee_collector).await.unwrap().lamports,
+        fee_collector_lamports + FEE
+    );
+
+    // The payload is made of big-endian integers
+    assert_eq!(message.payload.len(), PriceAttestation::LEN);
+    assert_eq!(message.payload[..4], PRICE_ATTESTATION_MAGIC);
+    assert_eq!(message.payload[4], PRICE_ATTESTATION_VERSION);
+    assert_eq!(message.payload[5..37], price.to_bytes());
+    assert_eq!(message.payload[41..49], 100i64.to_be_bytes());
+    assert_eq!(message.payload[57..61], (-5i32).to_be_bytes());
+    assert_eq!(message.payload[69..77], 2u64.to_be_bytes());
+
+    // The next aggregates reuse the message account of the price account
+    sim.warp_to_slot(3).await.unwrap();
+    sim.upd_price_with_wormhole(&publishers[0], price, quote(120))
+        .await
+        .unwrap();
+    let message = sim.get_wormhole_message(price).await.unwrap();
+    assert_eq!(message.sequence, 1);
+    let attestation = PriceAttestation::from_bytes(&message.payload).unwrap();
+    assert_eq!((attestation.price, attestation.slot), (110, 3));
+    let message_account = sim
+        .get_account(client::get_wormhole_message_pubkey(
+            &sim.program_id(),
+            &price,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(message_account.owner, WORMHOLE_PROGRAM_ID);
+
+    // Price updates without the accounts of the bridge are not attested
+    sim.warp_to_slot(4).await.unwrap();
+    sim.upd_price(&publishers[0], price, quote(130))
+        .await
+        .unwrap();
+    assert_eq!(sim.get_wormhole_message(price).await.unwrap().sequence, 1);
+    assert_eq!(
+        sim.get_account(fee_collector).await.unwrap().lamports,
+        fee_collector_lamports + 2 * FEE
+    );
+}
+
+#[tokio::test]
+async fn test_wormhole_attestation_checks_pdas() {
+    let mut sim = PythSimulator::new().await;
+    let (price_keypair, publishers) = sim.setup_feed_with_publishers(1).await;
+    let price = price_keypair.pubkey();
+    sim.set_min_pub(price, 1).await.unwrap();
+    sim.init_mock_wormhole(FEE).await;
+    sim.upd_price(&publishers[0], price, quote(100))
+        .await
+        .unwrap();
+    sim.warp_to_slot(2).await.unwrap();
+
+    let program_id = sim.program_id();
+    let publisher = publishers[0].pubkey();
+    let upd_price = |wormhole_accounts| {
+        let mut instruction = client::upd_price(
+            &program_id,
+            &publisher,
+            &price,
+            &PriceUpdate {
+                status:          PC_STATUS_TRADING,
+                price:           110,
+                confidence:      5,
+                publishing_slot: 2,
+            },
+        );
+        instruction.accounts.extend(wormhole_accounts);
+        instruction
+    };
+
+    // The message account of another price account
+    let instruction = upd_price(client::wormhole_accounts(
+        &program_id,
+        &Pubkey::new_unique(),
+    ));
+    sim.process_ixs(&[instruction], &vec![&publishers[0]], &publishers[0])
+        .await
+        .assert_fails_with(OracleError::InvalidPda);
+
+    // Another emitter
+    let mut wormhole_accounts = client::wormhole_accounts(&program_id, &price);
+    wormhole_accounts[2].pubkey = Pubkey::new_unique();
+    let instruction = upd_price(wormhole_accounts);
+    sim.process_ixs(&[instruction], &vec![&publishers[0]], &publishers[0])
+        .await
+        .assert_fails_with(OracleError::InvalidPda);
+
+    assert_eq!(sim.get_wormhole_message(price).await, None);
+}
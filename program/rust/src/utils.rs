@@ -2,15 +2,23 @@ use {
     crate::{
         accounts::{
             AccountHeader,
+            ComponentLogAccount,
+            MessageBufferAccount,
             PermissionAccount,
+            SymbolIndexAccount,
+            MAPPING_SEED,
             PERMISSIONS_SEED,
+            PERMISSION_GRANT_SEED,
+            TREASURY_SEED,
         },
         c_oracle_header::{
             MAX_CI_DIVISOR,
+            MAX_EXPONENT,
             MAX_NUM_DECIMALS,
             PC_STATUS_IGNORED,
         },
         deserialize::{
+            check_account_header,
             load_account_as,
             load_checked,
         },
@@ -29,13 +37,27 @@ use {
     solana_program::{
         account_info::AccountInfo,
         bpf_loader_upgradeable,
+        clock::Clock,
+        hash::Hash,
+        instruction::{
+            get_stack_height,
+            TRANSACTION_LEVEL_STACK_HEIGHT,
+        },
         program::invoke,
         program_error::ProgramError,
         pubkey::Pubkey,
         system_instruction::transfer,
-        sysvar::rent::Rent,
+        system_program,
+        sysvar::{
+            instructions,
+            rent::Rent,
+            slot_hashes,
+        },
+    },
+    std::{
+        cell::Ref,
+        mem::size_of,
     },
-    std::cell::Ref,
 };
 
 pub fn pyth_assert(condition: bool, error_code: ProgramError) -> Result<(), ProgramError> {
@@ -66,18 +88,102 @@ pub fn check_permissioned_funding_account(
     permissions_account: &AccountInfo,
     cmd_hdr: &CommandHeader,
 ) -> Result<(), ProgramError> {
+    check_authorized_funding_account(program_id, funding_account, permissions_account, cmd_hdr)?;
+    check_valid_writable_account(program_id, account)
+}
+
+/// Check that `funding_account` is a signer and is permissioned by the `permission_account` for
+/// the command of `cmd_hdr`. The permission grant of `funding_account` for the command (see
+/// `PERMISSION_GRANT_SEED`) can be given instead of the permissions account.
+pub fn check_authorized_funding_account(
+    program_id: &Pubkey,
+    funding_account: &AccountInfo,
+    permissions_account: &AccountInfo,
+    cmd_hdr: &CommandHeader,
+) -> Result<(), ProgramError> {
+    let command =
+        OracleCommand::from_i32(cmd_hdr.command).ok_or(OracleError::UnrecognizedInstruction)?;
+    if is_permission_grant(program_id, permissions_account) {
+        check_valid_funding_account(funding_account)?;
+        check_valid_permission_grant_account(
+            program_id,
+            permissions_account,
+            funding_account.key,
+            command,
+        )?;
+        return Ok(());
+    }
+
     check_valid_permissions_account(program_id, permissions_account)?;
     let permissions_account_data =
         load_checked::<PermissionAccount>(permissions_account, cmd_hdr.version)?;
     check_valid_funding_account(funding_account)?;
     pyth_assert(
-        permissions_account_data.is_authorized(
-            funding_account.key,
-            OracleCommand::from_i32(cmd_hdr.command).ok_or(OracleError::UnrecognizedInstruction)?,
-        ),
+        permissions_account_data.is_authorized(funding_account.key, command),
+        OracleError::PermissionViolation.into(),
+    )
+}
+
+/// Check that the command of `cmd_hdr`, run by `funding_account`, is co-signed if it is a
+/// co-signed command and the co-signing mode of `permissions_account` is on (see
+/// `PermissionAccount::is_co_signed`). Another authority key of the permissions account than
+/// `funding_account` must then sign an instruction of the same transaction, which is verified with
+/// the instructions sysvar account `instructions_account`.
+///
+/// A permission grant doesn't tell whether the mode is on, so co-signed commands can't be
+/// authorized by one.
+pub fn check_co_signed(
+    program_id: &Pubkey,
+    funding_account: &AccountInfo,
+    permissions_account: &AccountInfo,
+    instructions_account: Option<&AccountInfo>,
+    cmd_hdr: &CommandHeader,
+) -> Result<(), ProgramError> {
+    let command =
+        OracleCommand::from_i32(cmd_hdr.command).ok_or(OracleError::UnrecognizedInstruction)?;
+    if !PermissionAccount::is_co_signed(command) {
+        return Ok(());
+    }
+    pyth_assert(
+        !is_permission_grant(program_id, permissions_account),
         OracleError::PermissionViolation.into(),
     )?;
-    check_valid_writable_account(program_id, account)
+    check_valid_permissions_account(program_id, permissions_account)?;
+    if !PermissionAccount::is_co_signing(permissions_account)? {
+        return Ok(());
+    }
+
+    let instructions_account = instructions_account.ok_or(OracleError::MissingCoSigner)?;
+    let permissions_account_data =
+        load_checked::<PermissionAccount>(permissions_account, cmd_hdr.version)?;
+    // The instructions are read until the first index past the end of the transaction
+    let is_co_signed = (0..)
+        .map_while(|index| {
+            instructions::load_instruction_at_checked(index, instructions_account).ok()
+        })
+        .flat_map(|instruction| instruction.accounts)
+        .any(|account| {
+            account.is_signer
+                && account.pubkey != *funding_account.key
+                && permissions_account_data.is_authority(&account.pubkey)
+        });
+    pyth_assert(is_co_signed, OracleError::MissingCoSigner.into())
+}
+
+/// Check that the accounts of `accounts` are pairwise distinct. Every processor passes the accounts
+/// of the roles of its instruction that must not be the same account, e.g. the funding account and
+/// the price account, so that an account is never both debited as a payer and written as program
+/// data, or read and written under two different assumptions.
+pub fn check_distinct_accounts(accounts: &[&AccountInfo]) -> Result<(), ProgramError> {
+    for (i, account) in accounts.iter().enumerate() {
+        pyth_assert(
+            accounts[..i]
+                .iter()
+                .all(|previous_account| previous_account.key != account.key),
+            OracleError::AliasedAccounts.into(),
+        )?;
+    }
+    Ok(())
 }
 
 /// Returns `true` if the `account` is fresh, i.e., its data can be overwritten.
@@ -100,7 +206,7 @@ pub fn check_valid_fresh_account(account: &AccountInfo) -> Result<(), ProgramErr
 // Check that an exponent is within the range of permitted exponents for price accounts.
 pub fn check_exponent_range(expo: i32) -> Result<(), ProgramError> {
     pyth_assert(
-        (-MAX_NUM_DECIMALS..=MAX_NUM_DECIMALS).contains(&expo),
+        (-MAX_NUM_DECIMALS..=MAX_EXPONENT).contains(&expo),
         ProgramError::InvalidArgument,
     )
 }
@@ -158,13 +264,77 @@ pub fn check_valid_permissions_account(
     )
 }
 
+/// Whether `account` is an existing permission grant, i.e. an empty account of the program
+pub fn is_permission_grant(program_id: &Pubkey, account: &AccountInfo) -> bool {
+    account.owner == program_id && account.data_is_empty() && account.lamports() != 0
+}
+
+/// Check that `account` is the permission grant PDA of `authority` for `command` (see
+/// `PERMISSION_GRANT_SEED`) and return its bump seed
+pub fn check_valid_permission_grant_account(
+    program_id: &Pubkey,
+    account: &AccountInfo,
+    authority: &Pubkey,
+    command: OracleCommand,
+) -> Result<u8, ProgramError> {
+    let (grant_pda_address, bump_seed) = Pubkey::find_program_address(
+        &[
+            PERMISSION_GRANT_SEED.as_bytes(),
+            &authority.to_bytes(),
+            &(command as i32).to_le_bytes(),
+        ],
+        program_id,
+    );
+    pyth_assert(
+        grant_pda_address == *account.key,
+        OracleError::InvalidPda.into(),
+    )?;
+    Ok(bump_seed)
+}
+
+/// Check that `account` is the mapping PDA of `version` (see `MAPPING_SEED`) and return its bump
+/// seed
+pub fn check_valid_mapping_pda_account(
+    program_id: &Pubkey,
+    account: &AccountInfo,
+    version: u32,
+) -> Result<u8, ProgramError> {
+    let (mapping_pda_address, bump_seed) = Pubkey::find_program_address(
+        &[MAPPING_SEED.as_bytes(), &version.to_le_bytes()],
+        program_id,
+    );
+    pyth_assert(
+        mapping_pda_address == *account.key,
+        OracleError::InvalidPda.into(),
+    )?;
+    Ok(bump_seed)
+}
+
+/// Check that `account` is the treasury PDA (see `TREASURY_SEED`) and return its bump seed
+pub fn check_valid_treasury_account(
+    program_id: &Pubkey,
+    account: &AccountInfo,
+) -> Result<u8, ProgramError> {
+    let (treasury_pda_address, bump_seed) =
+        Pubkey::find_program_address(&[TREASURY_SEED.as_bytes()], program_id);
+    pyth_assert(
+        treasury_pda_address == *account.key,
+        OracleError::InvalidPda.into(),
+    )?;
+    Ok(bump_seed)
+}
+
 /// Checks whether this instruction is trying to update an individual publisher's price (`true`) or
 /// is only trying to refresh the aggregate (`false`)
 pub fn is_component_update(cmd_args: &UpdPriceArgs) -> Result<bool, OracleError> {
     match OracleCommand::from_i32(cmd_args.header.command)
         .ok_or(OracleError::UnrecognizedInstruction)?
     {
-        OracleCommand::UpdPrice | OracleCommand::UpdPriceNoFailOnError => Ok(true),
+        OracleCommand::UpdPrice
+        | OracleCommand::UpdPriceNoFailOnError
+        | OracleCommand::UpdPriceWithTime
+        | OracleCommand::UpdPriceWithTrade
+        | OracleCommand::UpdPriceWithBudget => Ok(true),
         _ => Ok(false),
     }
 }
@@ -175,15 +345,181 @@ pub fn get_status_for_conf_price_ratio(
     confidence: u64,
     status: u32,
 ) -> Result<u32, OracleError> {
-    let threshold_conf = price.abs() / MAX_CI_DIVISOR;
+    // i64::MIN has no absolute value in an i64
+    let threshold_conf = price.unsigned_abs() / MAX_CI_DIVISOR.unsigned_abs();
 
-    if confidence > try_convert::<_, u64>(threshold_conf)? {
+    if confidence > threshold_conf {
         Ok(PC_STATUS_IGNORED)
     } else {
         Ok(status)
     }
 }
 
+/// Size of an entry of the `SlotHashes` sysvar: a slot and the hash of its bank.
+const SLOT_HASH_ENTRY_SIZE: usize = size_of::<u64>() + size_of::<Hash>();
+
+/// Split the optional trailing `SlotHashes` sysvar account off `accounts`.
+pub fn split_slot_hashes_account<'a, 'b>(
+    accounts: &'a [AccountInfo<'b>],
+) -> (&'a [AccountInfo<'b>], Option<&'a AccountInfo<'b>>) {
+    match accounts.split_last() {
+        Some((last, rest)) if slot_hashes::check_id(last.key) => (rest, Some(last)),
+        _ => (accounts, None),
+    }
+}
+
+/// Split the optional trailing `Instructions` sysvar account off `accounts`.
+pub fn split_instructions_account<'a, 'b>(
+    accounts: &'a [AccountInfo<'b>],
+) -> (&'a [AccountInfo<'b>], Option<&'a AccountInfo<'b>>) {
+    match accounts.split_last() {
+        Some((last, rest)) if instructions::check_id(last.key) => (rest, Some(last)),
+        _ => (accounts, None),
+    }
+}
+
+/// Split the optional trailing treasury and system program accounts off `accounts`, which pay the
+/// fee of the price updates of price accounts with one (see `fee_lamports_`). They are recognized
+/// by the system program account.
+pub fn split_fee_accounts<'a, 'b>(
+    accounts: &'a [AccountInfo<'b>],
+) -> (
+    &'a [AccountInfo<'b>],
+    Option<(&'a AccountInfo<'b>, &'a AccountInfo<'b>)>,
+) {
+    match accounts {
+        [rest @ .., treasury, system_program] if system_program::check_id(system_program.key) => {
+            (rest, Some((treasury, system_program)))
+        }
+        _ => (accounts, None),
+    }
+}
+
+/// Split the optional trailing message buffer account off `accounts`. It is recognized as an
+/// account of the program with the header of a `MessageBufferAccount`.
+pub fn split_message_buffer_account<'a, 'b>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'b>],
+    version: u32,
+) -> (&'a [AccountInfo<'b>], Option<&'a AccountInfo<'b>>) {
+    match accounts.split_last() {
+        Some((last, rest))
+            if last.owner == program_id
+                && check_account_header::<MessageBufferAccount>(last, version).is_ok() =>
+        {
+            (rest, Some(last))
+        }
+        _ => (accounts, None),
+    }
+}
+
+/// Split the optional trailing component log account off `accounts`. It is recognized as an
+/// account of the program with the header of a `ComponentLogAccount`.
+pub fn split_component_log_account<'a, 'b>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'b>],
+    version: u32,
+) -> (&'a [AccountInfo<'b>], Option<&'a AccountInfo<'b>>) {
+    match accounts.split_last() {
+        Some((last, rest))
+            if last.owner == program_id
+                && check_account_header::<ComponentLogAccount>(last, version).is_ok() =>
+        {
+            (rest, Some(last))
+        }
+        _ => (accounts, None),
+    }
+}
+
+/// Split the optional trailing symbol index account off `accounts`. It is recognized as an
+/// account of the program with the header of a `SymbolIndexAccount`.
+pub fn split_symbol_index_account<'a, 'b>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'b>],
+    version: u32,
+) -> (&'a [AccountInfo<'b>], Option<&'a AccountInfo<'b>>) {
+    match accounts.split_last() {
+        Some((last, rest))
+            if last.owner == program_id
+                && check_account_header::<SymbolIndexAccount>(last, version).is_ok() =>
+        {
+            (rest, Some(last))
+        }
+        _ => (accounts, None),
+    }
+}
+
+/// The program that invoked the instruction being processed, `None` for a top-level instruction.
+/// Solana only exposes the programs of the top-level instructions (through the `Instructions`
+/// sysvar), which is the direct caller only for a single level of CPI, so deeper invocations fail
+/// with `UnauthorizedCpi` rather than being attributed to the program that started the chain.
+pub fn get_direct_caller(
+    instructions_account: &AccountInfo,
+) -> Result<Option<Pubkey>, ProgramError> {
+    let stack_height = get_stack_height();
+    if stack_height == TRANSACTION_LEVEL_STACK_HEIGHT {
+        return Ok(None);
+    }
+    pyth_assert(
+        stack_height == TRANSACTION_LEVEL_STACK_HEIGHT + 1,
+        OracleError::UnauthorizedCpi.into(),
+    )?;
+    let index = instructions::load_current_index_checked(instructions_account)?;
+    Ok(Some(
+        instructions::load_instruction_at_checked(usize::from(index), instructions_account)?
+            .program_id,
+    ))
+}
+
+/// Number of slots skipped by the cluster among the slots before `slot` that are needed to see
+/// `max_latency` slots with a block, i.e. the amount by which the max latency of a quote should be
+/// extended so that it is measured in produced slots.
+///
+/// `SlotHashes` lists the slots with a block, most recent first. Without skipped slots, its entry
+/// of index `max_latency` is `slot - 1 - max_latency`. Slots older than the listed ones are assumed
+/// to have a block.
+pub fn get_num_skipped_slots(
+    slot_hashes_account: &AccountInfo,
+    slot: u64,
+    max_latency: u64,
+) -> Result<u16, ProgramError> {
+    pyth_assert(
+        slot_hashes::check_id(slot_hashes_account.key),
+        ProgramError::InvalidArgument,
+    )?;
+
+    // The sysvar is the bincode serialization of a Vec<(Slot, Hash)>
+    let data = slot_hashes_account.try_borrow_data()?;
+    let read_u64 = |offset: usize| -> Result<u64, ProgramError> {
+        data.get(offset..)
+            .and_then(|bytes| bytes.get(..size_of::<u64>()))
+            .and_then(|bytes| bytes.try_into().ok())
+            .map(u64::from_le_bytes)
+            .ok_or(ProgramError::InvalidAccountData)
+    };
+    // The number of entries is read from the account, so the offset computation can overflow
+    let entry_slot = |index: u64| -> Result<i128, ProgramError> {
+        let offset = usize::try_from(index)
+            .ok()
+            .and_then(|index| index.checked_mul(SLOT_HASH_ENTRY_SIZE))
+            .and_then(|offset| offset.checked_add(size_of::<u64>()))
+            .ok_or(ProgramError::InvalidAccountData)?;
+        Ok(i128::from(read_u64(offset)?))
+    };
+
+    let num_entries = read_u64(0)?;
+    let oldest_slot = if num_entries > max_latency {
+        entry_slot(max_latency)?
+    } else if num_entries > 0 {
+        entry_slot(num_entries - 1)? - (i128::from(max_latency) + 1 - i128::from(num_entries))
+    } else {
+        return Ok(0);
+    };
+
+    let num_skipped = i128::from(slot) - 1 - i128::from(max_latency) - oldest_slot;
+    Ok(num_skipped.clamp(0, i128::from(u16::MAX)) as u16)
+}
+
 /// This struct represents UpgradeableLoaderState from bpf-upgradable-loader.
 /// Solana uses bincode for the struct. However the bincode crate is too big the space we have onchain,
 /// therefore we will use bytemuck for deserialization
@@ -234,18 +570,30 @@ pub fn check_is_upgrade_authority_for_program(
     Ok(())
 }
 
-#[cfg(not(test))]
+#[cfg(not(any(test, feature = "fuzz")))]
 pub fn get_rent() -> Result<Rent, ProgramError> {
     use solana_program::sysvar::Sysvar;
     Rent::get()
 }
 
-#[cfg(test)]
+// The rent sysvar is only available inside the runtime
+#[cfg(any(test, feature = "fuzz"))]
 pub fn get_rent() -> Result<Rent, ProgramError> {
     Ok(Rent::default())
 }
 
-#[allow(dead_code)]
+#[cfg(not(any(test, feature = "fuzz")))]
+pub fn get_clock() -> Result<Clock, ProgramError> {
+    use solana_program::sysvar::Sysvar;
+    Clock::get()
+}
+
+// The clock sysvar is only available inside the runtime, outside of it the clock is at slot 0
+#[cfg(any(test, feature = "fuzz"))]
+pub fn get_clock() -> Result<Clock, ProgramError> {
+    Ok(Clock::default())
+}
+
 pub fn send_lamports<'a>(
     from: &AccountInfo<'a>,
     to: &AccountInfo<'a>,
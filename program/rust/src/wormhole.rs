@@ -0,0 +1,266 @@
+//! Attestations of the aggregate prices posted to the Wormhole core bridge, enabled by the
+//! `wormhole` feature.
+//!
+//! When the accounts of the bridge are appended to an `upd_price` instruction (see
+//! `split_wormhole_accounts`) and the update triggers a successful aggregation, the oracle posts a
+//! `PriceAttestation` of the new aggregate as a Wormhole message. The message is emitted by the
+//! emitter PDA of the oracle (see `WORMHOLE_EMITTER_SEED`) and stored in a message account of the
+//! bridge that each price account reuses (see `WORMHOLE_MESSAGE_SEED`), so that attesting doesn't
+//! create an account per aggregate. The publisher pays the fee of the bridge and the rent of the
+//! message account.
+use {
+    crate::{
+        accounts::PriceAccount,
+        error::OracleError,
+        utils::{
+            pyth_assert,
+            send_lamports,
+        },
+    },
+    solana_program::{
+        account_info::AccountInfo,
+        entrypoint::ProgramResult,
+        instruction::{
+            AccountMeta,
+            Instruction,
+        },
+        program::invoke_signed,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        system_program,
+    },
+};
+
+/// Program id of the Wormhole core bridge
+pub const WORMHOLE_PROGRAM_ID: Pubkey =
+    solana_program::pubkey!("worm2ZoG2kUd4vFXhvjh93UUH596ayRfgQ2MgjNMTth");
+/// Seed of the PDA of the oracle that emits the attestations
+pub const WORMHOLE_EMITTER_SEED: &str = "emitter";
+/// Seed of the message account of the attestations of a price account, followed by the key of the
+/// price account
+pub const WORMHOLE_MESSAGE_SEED: &str = "wormhole_message";
+/// The attestations are posted once the block of the aggregation is confirmed
+pub const WORMHOLE_CONSISTENCY_LEVEL: u8 = 1;
+/// Index of the `PostMessageUnreliable` instruction of the core bridge, which reuses the message
+/// account of the emitter for messages of the same size
+const POST_MESSAGE_UNRELIABLE: u8 = 8;
+/// Offset of the fee per message in the config account of the bridge, after the guardian set
+/// index (`u32`), the last balance of the fee collector (`u64`) and the guardian set expiration
+/// time (`u32`)
+const BRIDGE_FEE_OFFSET: usize = 16;
+
+/// First bytes of the payload of an attestation
+pub const PRICE_ATTESTATION_MAGIC: [u8; 4] = *b"PTHA";
+/// Version of the layout of `PriceAttestation`
+pub const PRICE_ATTESTATION_VERSION: u8 = 1;
+
+/// Aggregate price of a price account after a successful aggregation, as posted to Wormhole. The
+/// payload is the magic number, the version, then the fields in order as big-endian integers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PriceAttestation {
+    pub price_account: Pubkey,
+    /// Stable index of the price feed, 0 if it has none (see `PriceAccount::feed_index_`)
+    pub feed_index:    u32,
+    pub price:         i64,
+    pub conf:          u64,
+    pub exponent:      i32,
+    /// Unix timestamp of the aggregation
+    pub publish_time:  i64,
+    /// Slot of the aggregation
+    pub slot:          u64,
+    /// Status of the aggregate, `PC_STATUS_TRADING` or `PC_STATUS_DEGRADED`
+    pub status:        u32,
+}
+
+impl PriceAttestation {
+    /// Size of the payload of an attestation
+    pub const LEN: usize = 4 + 1 + 32 + 4 + 8 + 8 + 4 + 8 + 8 + 4;
+
+    /// The attestation of the last aggregate of `price_data`, the price account `price_account`
+    pub fn from_aggregate(price_account: &Pubkey, price_data: &PriceAccount) -> Self {
+        PriceAttestation {
+            price_account: *price_account,
+            feed_index:    price_data.feed_index_,
+            price:         price_data.agg_.price_,
+            conf:          price_data.agg_.conf_,
+            exponent:      price_data.exponent,
+            publish_time:  price_data.timestamp_,
+            slot:          price_data.agg_.pub_slot_,
+            status:        price_data.agg_.status_,
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(Self::LEN);
+        bytes.extend_from_slice(&PRICE_ATTESTATION_MAGIC);
+        bytes.push(PRICE_ATTESTATION_VERSION);
+        bytes.extend_from_slice(&self.price_account.to_bytes());
+        bytes.extend_from_slice(&self.feed_index.to_be_bytes());
+        bytes.extend_from_slice(&self.price.to_be_bytes());
+        bytes.extend_from_slice(&self.conf.to_be_bytes());
+        bytes.extend_from_slice(&self.exponent.to_be_bytes());
+        bytes.extend_from_slice(&self.publish_time.to_be_bytes());
+        bytes.extend_from_slice(&self.slot.to_be_bytes());
+        bytes.extend_from_slice(&self.status.to_be_bytes());
+        bytes
+    }
+
+    /// Parse the payload of an attestation, `None` if it isn't one of this version
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != Self::LEN
+            || bytes[..4] != PRICE_ATTESTATION_MAGIC
+            || bytes[4] != PRICE_ATTESTATION_VERSION
+        {
+            return None;
+        }
+        let field = |offset: usize, len: usize| &bytes[offset..offset + len];
+        let word = |offset: usize| -> [u8; 4] { field(offset, 4).try_into().unwrap() };
+        let double_word = |offset: usize| -> [u8; 8] { field(offset, 8).try_into().unwrap() };
+        Some(PriceAttestation {
+            price_account: Pubkey::new(field(5, 32)),
+            feed_index:    u32::from_be_bytes(word(37)),
+            price:         i64::from_be_bytes(double_word(41)),
+            conf:          u64::from_be_bytes(double_word(49)),
+            exponent:      i32::from_be_bytes(word(57)),
+            publish_time:  i64::from_be_bytes(double_word(61)),
+            slot:          u64::from_be_bytes(double_word(69)),
+            status:        u32::from_be_bytes(word(77)),
+        })
+    }
+}
+
+/// Accounts of the core bridge needed to post an attestation
+pub struct WormholeAccounts<'a, 'b: 'a> {
+    /// Config of the bridge                    [writable]
+    pub bridge:           &'a AccountInfo<'b>,
+    /// Message PDA of the price account        [writable]
+    pub message:          &'a AccountInfo<'b>,
+    /// Emitter PDA of the oracle               []
+    pub emitter:          &'a AccountInfo<'b>,
+    /// Sequence of the emitter in the bridge   [writable]
+    pub sequence:         &'a AccountInfo<'b>,
+    /// Fee collector of the bridge             [writable]
+    pub fee_collector:    &'a AccountInfo<'b>,
+    /// sysvar_clock account                    []
+    pub clock:            &'a AccountInfo<'b>,
+    /// sysvar_rent account                     []
+    pub rent:             &'a AccountInfo<'b>,
+    pub system_program:   &'a AccountInfo<'b>,
+    pub wormhole_program: &'a AccountInfo<'b>,
+}
+
+/// Split the optional trailing accounts of the core bridge off `accounts`, in the order of the
+/// fields of `WormholeAccounts`. They are recognized by the core bridge program account.
+pub fn split_wormhole_accounts<'a, 'b>(
+    accounts: &'a [AccountInfo<'b>],
+) -> (&'a [AccountInfo<'b>], Option<WormholeAccounts<'a, 'b>>) {
+    let (rest, bridge_accounts) = accounts.split_at(accounts.len().saturating_sub(9));
+    match bridge_accounts {
+        [.., wormhole_program]
+            if bridge_accounts.len() == 9 && *wormhole_program.key == WORMHOLE_PROGRAM_ID =>
+        {
+            (
+                rest,
+                Some(WormholeAccounts {
+                    bridge: &bridge_accounts[0],
+                    message: &bridge_accounts[1],
+                    emitter: &bridge_accounts[2],
+                    sequence: &bridge_accounts[3],
+                    fee_collector: &bridge_accounts[4],
+                    clock: &bridge_accounts[5],
+                    rent: &bridge_accounts[6],
+                    system_program: &bridge_accounts[7],
+                    wormhole_program,
+                }),
+            )
+        }
+        _ => (accounts, None),
+    }
+}
+
+/// Post the attestation of the last aggregate of `price_data`, the price account `price_account`,
+/// to the core bridge. `payer` pays the fee of the bridge and the rent of the message account the
+/// first time it is used.
+pub fn post_price_attestation<'b>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'b>,
+    price_account: &Pubkey,
+    price_data: &PriceAccount,
+    accounts: &WormholeAccounts<'_, 'b>,
+) -> ProgramResult {
+    let (emitter, emitter_bump) =
+        Pubkey::find_program_address(&[WORMHOLE_EMITTER_SEED.as_bytes()], program_id);
+    pyth_assert(
+        emitter == *accounts.emitter.key,
+        OracleError::InvalidPda.into(),
+    )?;
+    let message_seeds: &[&[u8]] = &[WORMHOLE_MESSAGE_SEED.as_bytes(), price_account.as_ref()];
+    let (message, message_bump) = Pubkey::find_program_address(message_seeds, program_id);
+    pyth_assert(
+        message == *accounts.message.key,
+        OracleError::InvalidPda.into(),
+    )?;
+    pyth_assert(
+        system_program::check_id(accounts.system_program.key),
+        OracleError::InvalidSystemAccount.into(),
+    )?;
+
+    // The bridge checks that the fee of the message was paid to its fee collector beforehand
+    pyth_assert(
+        accounts.bridge.owner == accounts.wormhole_program.key,
+        ProgramError::InvalidArgument,
+    )?;
+    let fee = accounts
+        .bridge
+        .try_borrow_data()?
+        .get(BRIDGE_FEE_OFFSET..BRIDGE_FEE_OFFSET + 8)
+        .map(|fee| u64::from_le_bytes(fee.try_into().unwrap()))
+        .ok_or(OracleError::DeserializationError)?;
+    if fee > 0 {
+        send_lamports(payer, accounts.fee_collector, accounts.system_program, fee)?;
+    }
+
+    let instruction = Instruction::new_with_borsh(
+        *accounts.wormhole_program.key,
+        &(
+            POST_MESSAGE_UNRELIABLE,
+            0u32,
+            PriceAttestation::from_aggregate(price_account, price_data).to_bytes(),
+            WORMHOLE_CONSISTENCY_LEVEL,
+        ),
+        vec![
+            AccountMeta::new(*accounts.bridge.key, false),
+            AccountMeta::new(*accounts.message.key, true),
+            AccountMeta::new_readonly(*accounts.emitter.key, true),
+            AccountMeta::new(*accounts.sequence.key, false),
+            AccountMeta::new(*payer.key, true),
+            AccountMeta::new(*accounts.fee_collector.key, false),
+            AccountMeta::new_readonly(*accounts.clock.key, false),
+            AccountMeta::new_readonly(*accounts.rent.key, false),
+            AccountMeta::new_readonly(*accounts.system_program.key, false),
+        ],
+    );
+    invoke_signed(
+        &instruction,
+        &[
+            accounts.bridge.clone(),
+            accounts.message.clone(),
+            accounts.emitter.clone(),
+            accounts.sequence.clone(),
+            payer.clone(),
+            accounts.fee_collector.clone(),
+            accounts.clock.clone(),
+            accounts.rent.clone(),
+            accounts.system_program.clone(),
+            accounts.wormhole_program.clone(),
+        ],
+        &[
+            &[WORMHOLE_EMITTER_SEED.as_bytes(), &[emitter_bump]],
+            &[
+                WORMHOLE_MESSAGE_SEED.as_bytes(),
+                price_account.as_ref(),
+                &[message_bump],
+            ],
+        ],
+    )
+}
@@ -21,6 +21,20 @@ cd "${PYTH_DIR}"
 
 # Re-run tests affected by features
 cargo-test-bpf
+cargo-test-bpf --features devnet
+cargo-test-bpf --features wormhole
+cargo-test-bpf --no-default-features --features solana
+
+# The no_std core crate shared with the clients, on the host
+cargo test --locked -p pyth-oracle-core
+cargo test --locked -p pyth-oracle-core --no-default-features --features solana
+
+cargo-build-bpf --no-default-features --features solana -- --locked -Z build-std=std,panic_abort -Z build-std-features=panic_immediate_abort
+sha256sum ./target/**/*.so
+echo "Checking size of pyth_oracle.so for solana"
+./scripts/check-size.sh 81760
+mkdir -p target/pyth/solana/
+mv target/deploy/pyth_oracle.so target/pyth/solana/pyth_oracle_solana.so
 
 cargo-build-bpf -- --locked -Z build-std=std,panic_abort -Z build-std-features=panic_immediate_abort
 sha256sum ./target/**/*.so